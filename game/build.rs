@@ -0,0 +1,41 @@
+// 编译时写入版本信息：git 提交和构建日期，--version 和主菜单角落显示
+use std::path::Path;
+use std::process::Command;
+
+use chrono::{DateTime, Utc};
+
+fn main() {
+    let git_dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("../.git");
+    // 切换分支或提交后重新生成；不在 git 仓库里（例如源码包）时只运行一次
+    let head = git_dir.join("HEAD");
+    if head.exists() {
+        println!("cargo:rerun-if-changed={}", head.display());
+        if let Some(reference) = std::fs::read_to_string(&head)
+            .ok()
+            .and_then(|content| content.strip_prefix("ref: ").map(|r| r.trim().to_string()))
+        {
+            println!("cargo:rerun-if-changed={}", git_dir.join(reference).display());
+        }
+    }
+    println!("cargo:rerun-if-env-changed=SOURCE_DATE_EPOCH");
+
+    let git_hash = Command::new("git")
+        .args(["rev-parse", "--short", "HEAD"])
+        .output()
+        .ok()
+        .filter(|output| output.status.success())
+        .and_then(|output| String::from_utf8(output.stdout).ok())
+        .map(|hash| hash.trim().to_string())
+        .filter(|hash| !hash.is_empty())
+        .unwrap_or_else(|| "unknown".to_string());
+
+    // 设置 SOURCE_DATE_EPOCH 时使用它，便于重复构建得到相同的结果
+    let build_time = std::env::var("SOURCE_DATE_EPOCH")
+        .ok()
+        .and_then(|epoch| epoch.trim().parse::<i64>().ok())
+        .and_then(|seconds| DateTime::<Utc>::from_timestamp(seconds, 0))
+        .unwrap_or_else(Utc::now);
+
+    println!("cargo:rustc-env=BREAKOUT_GIT_HASH={}", git_hash);
+    println!("cargo:rustc-env=BREAKOUT_BUILD_DATE={}", build_time.format("%Y-%m-%d"));
+}
@@ -0,0 +1,589 @@
+use bevy::prelude::*;
+
+use crate::api::Score;
+use crate::fonts::{text_style, FontAssets, FontKind};
+use crate::i18n::Locale;
+use crate::network::{ApiCommand, ApiResponse, Network};
+use crate::{LeaderboardData, LeaderboardRow, LeaderboardScrollList, LEADERBOARD_ROW_HEIGHT};
+
+// 展台管理模式：客户端配置了管理密钥（BREAKOUT_ADMIN_API_KEY，和服务器使用同一个变量名）时，在排行榜按 Ctrl+Shift+A 开关。
+// 开启后 Tab / Shift+Tab 选中一行，Delete 或点击行尾的 X 删除成绩，H 把名字改为 REMOVED_NAME。
+// 修改立即反映在列表上，请求失败时恢复原样并提示。没有密钥时这些系统都不运行，界面上没有任何变化
+
+// 隐藏名字时使用的替代名
+const REMOVED_NAME: &str = "Removed";
+// 失败提示的显示时长和最后淡出的时长（秒）
+const TOAST_DURATION: f32 = 4.0;
+const TOAST_FADE: f32 = 1.0;
+
+const SELECTED_OUTLINE: Color = Color::rgb(1.0, 0.3, 0.3);
+const DELETE_BUTTON_COLOR: Color = Color::rgba(0.8, 0.2, 0.2, 0.85);
+const FAILED_COLOR: Color = Color::rgb(1.0, 0.45, 0.45);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ModerationKind {
+    Delete,
+    HideName,
+}
+
+// 已经在界面上生效、等待服务器确认的修改；失败时用 original 恢复
+#[derive(Debug, Clone)]
+struct PendingModeration {
+    kind: ModerationKind,
+    // 删除前所在的位置
+    index: usize,
+    original: Score,
+}
+
+#[derive(Resource, Default)]
+pub struct AdminMode {
+    active: bool,
+    // 选中的行在 LeaderboardData 中的下标
+    selected: usize,
+    pending: Vec<PendingModeration>,
+}
+
+// 排行榜行尾的删除按钮，只在管理模式下显示
+#[derive(Component)]
+pub struct AdminDeleteButton;
+
+// 管理模式的提示条
+#[derive(Component)]
+pub struct AdminBanner;
+
+#[derive(Component)]
+pub struct AdminToast {
+    age: f32,
+}
+
+// 运行条件：只有配置了管理密钥时管理系统才运行
+pub fn admin_available(network: Res<Network>) -> bool {
+    network.admin_available()
+}
+
+// 复用的排行榜行中的删除按钮，平时不显示也不占位置
+pub fn spawn_delete_button(parent: &mut ChildBuilder, fonts: &FontAssets) {
+    parent
+        .spawn((
+            ButtonBundle {
+                style: Style {
+                    display: Display::None,
+                    position_type: PositionType::Absolute,
+                    right: Val::Px(4.0),
+                    width: Val::Px(26.0),
+                    height: Val::Px(26.0),
+                    justify_content: JustifyContent::Center,
+                    align_items: AlignItems::Center,
+                    ..default()
+                },
+                background_color: BackgroundColor(DELETE_BUTTON_COLOR),
+                ..default()
+            },
+            AdminDeleteButton,
+        ))
+        .with_children(|parent| {
+            parent.spawn(TextBundle::from_section(
+                "X",
+                text_style(fonts, FontKind::Bold, 18.0, Color::WHITE),
+            ));
+        });
+}
+
+// Ctrl+Shift+A 开关管理模式
+pub fn toggle_admin_mode(
+    mut commands: Commands,
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    mut admin: ResMut<AdminMode>,
+    fonts: Res<FontAssets>,
+    locale: Res<Locale>,
+    banners: Query<Entity, With<AdminBanner>>,
+) {
+    let ctrl = keyboard_input.any_pressed([KeyCode::ControlLeft, KeyCode::ControlRight]);
+    let shift = keyboard_input.any_pressed([KeyCode::ShiftLeft, KeyCode::ShiftRight]);
+    if !(ctrl && shift && keyboard_input.just_pressed(KeyCode::KeyA)) {
+        return;
+    }
+    admin.active = !admin.active;
+    for entity in banners.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+    if admin.active {
+        spawn_banner(&mut commands, &fonts, &locale);
+    }
+}
+
+// 提示条不属于排行榜界面，排行榜重建时保留
+fn spawn_banner(commands: &mut Commands, fonts: &FontAssets, locale: &Locale) {
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    top: Val::Px(8.0),
+                    left: Val::Px(8.0),
+                    padding: UiRect::axes(Val::Px(10.0), Val::Px(4.0)),
+                    ..default()
+                },
+                background_color: BackgroundColor(Color::rgba(0.6, 0.1, 0.1, 0.9)),
+                z_index: ZIndex::Global(100),
+                ..default()
+            },
+            AdminBanner,
+        ))
+        .with_children(|parent| {
+            parent.spawn(TextBundle::from_section(
+                locale.tr("admin.banner", &[]),
+                text_style(fonts, FontKind::Bold, 16.0, Color::WHITE),
+            ));
+        });
+}
+
+// 管理模式下的选择和操作：键盘作用于选中的行，点击删除按钮作用于按钮所在的行
+pub fn admin_input(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    mut admin: ResMut<AdminMode>,
+    network: Res<Network>,
+    mut leaderboard_data: ResMut<LeaderboardData>,
+    buttons: Query<(&Interaction, &Parent), (Changed<Interaction>, With<AdminDeleteButton>)>,
+    rows: Query<&LeaderboardRow>,
+    mut list_query: Query<(&mut LeaderboardScrollList, &mut Style, &Parent)>,
+    node_query: Query<&Node>,
+) {
+    if !admin.active {
+        return;
+    }
+    let LeaderboardData::Loaded(data) = leaderboard_data.bypass_change_detection() else {
+        return;
+    };
+    let len = data.scores.len();
+    if len == 0 {
+        return;
+    }
+
+    // 移动选择时让选中的行保持在可见范围内
+    let shift = keyboard_input.any_pressed([KeyCode::ShiftLeft, KeyCode::ShiftRight]);
+    let previous = admin.selected;
+    if keyboard_input.just_pressed(KeyCode::Tab) {
+        admin.selected = if shift {
+            (admin.selected + len - 1) % len
+        } else {
+            (admin.selected + 1) % len
+        };
+    }
+    admin.selected = admin.selected.min(len - 1);
+    if admin.selected != previous {
+        for (mut list, mut style, parent) in list_query.iter_mut() {
+            let viewport = node_query.get(parent.get()).map(|node| node.size().y).unwrap_or(0.0);
+            let top = admin.selected as f32 * LEADERBOARD_ROW_HEIGHT;
+            let offset = (-list.position).clamp((top + LEADERBOARD_ROW_HEIGHT - viewport).max(0.0), top);
+            list.position = -offset;
+            style.top = Val::Px(list.position);
+        }
+    }
+
+    let clicked = buttons
+        .iter()
+        .filter(|(interaction, _)| **interaction == Interaction::Pressed)
+        .find_map(|(_, parent)| rows.get(parent.get()).ok().and_then(|row| row.index));
+    let action = if let Some(index) = clicked {
+        Some((ModerationKind::Delete, index))
+    } else if keyboard_input.just_pressed(KeyCode::Delete) {
+        Some((ModerationKind::Delete, admin.selected))
+    } else if keyboard_input.just_pressed(KeyCode::KeyH) {
+        Some((ModerationKind::HideName, admin.selected))
+    } else {
+        None
+    };
+    let Some((kind, index)) = action else {
+        return;
+    };
+    let Some(score) = data.scores.get(index) else {
+        return;
+    };
+    // 精简格式的成绩没有 id，无法操作
+    let Some(id) = score.id.clone() else {
+        return;
+    };
+    if kind == ModerationKind::HideName && score.player_name == REMOVED_NAME {
+        return;
+    }
+
+    // 先改界面再发请求
+    let original = score.clone();
+    match kind {
+        ModerationKind::Delete => {
+            data.scores.remove(index);
+            network.send(ApiCommand::DeleteScore { id });
+        }
+        ModerationKind::HideName => {
+            data.scores[index].player_name = REMOVED_NAME.to_string();
+            network.send(ApiCommand::RenameScore {
+                id,
+                player_name: REMOVED_NAME.to_string(),
+            });
+        }
+    }
+    admin.pending.push(PendingModeration { kind, index, original });
+    admin.selected = admin.selected.min(data.scores.len().saturating_sub(1));
+    leaderboard_data.set_changed();
+}
+
+// 服务器确认后丢掉记录；失败时把成绩放回原处（或恢复名字）并提示
+pub fn admin_responses(
+    mut commands: Commands,
+    mut responses: EventReader<ApiResponse>,
+    mut admin: ResMut<AdminMode>,
+    mut leaderboard_data: ResMut<LeaderboardData>,
+    fonts: Res<FontAssets>,
+    locale: Res<Locale>,
+) {
+    for response in responses.read() {
+        let (id, result) = match response {
+            ApiResponse::ScoreDeleted { id, result } | ApiResponse::ScoreRenamed { id, result } => (id, result),
+            _ => continue,
+        };
+        let Some(position) = admin
+            .pending
+            .iter()
+            .position(|pending| pending.original.id.as_deref() == Some(id.as_str()))
+        else {
+            continue;
+        };
+        let pending = admin.pending.remove(position);
+        let Err(e) = result else {
+            continue;
+        };
+        eprintln!("Moderation of score {} failed: {}", id, e);
+
+        // 期间排行榜可能已经刷新，按 id 恢复，避免出现重复的行
+        if let LeaderboardData::Loaded(data) = leaderboard_data.as_mut() {
+            let existing = data.scores.iter().position(|score| score.id.as_deref() == Some(id.as_str()));
+            match (pending.kind, existing) {
+                (ModerationKind::Delete, None) => {
+                    let index = pending.index.min(data.scores.len());
+                    data.scores.insert(index, pending.original.clone());
+                }
+                (ModerationKind::HideName, Some(index)) => {
+                    data.scores[index].player_name = pending.original.player_name.clone();
+                }
+                _ => {}
+            }
+        }
+        let message_key = match pending.kind {
+            ModerationKind::Delete => "admin.delete_failed",
+            ModerationKind::HideName => "admin.hide_failed",
+        };
+        let message = locale.tr(message_key, &[("name", &pending.original.player_name), ("error", e)]);
+        spawn_toast(&mut commands, &fonts, message);
+    }
+}
+
+fn spawn_toast(commands: &mut Commands, fonts: &FontAssets, message: String) {
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    bottom: Val::Px(20.0),
+                    width: Val::Percent(100.0),
+                    justify_content: JustifyContent::Center,
+                    ..default()
+                },
+                z_index: ZIndex::Global(100),
+                ..default()
+            },
+            AdminToast { age: 0.0 },
+        ))
+        .with_children(|parent| {
+            parent
+                .spawn(NodeBundle {
+                    style: Style {
+                        padding: UiRect::axes(Val::Px(16.0), Val::Px(8.0)),
+                        ..default()
+                    },
+                    background_color: BackgroundColor(Color::rgba(0.2, 0.2, 0.25, 0.9)),
+                    ..default()
+                })
+                .with_children(|parent| {
+                    parent.spawn(TextBundle::from_section(
+                        message,
+                        text_style(fonts, FontKind::Bold, 20.0, FAILED_COLOR),
+                    ));
+                });
+        });
+}
+
+pub fn fade_admin_toast(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut toasts: Query<(Entity, &mut AdminToast, &Children)>,
+    mut panels: Query<(&mut BackgroundColor, &Children), Without<AdminToast>>,
+    mut texts: Query<&mut Text>,
+) {
+    for (entity, mut toast, children) in toasts.iter_mut() {
+        toast.age += time.delta_seconds();
+        if toast.age >= TOAST_DURATION {
+            commands.entity(entity).despawn_recursive();
+            continue;
+        }
+        let alpha = ((TOAST_DURATION - toast.age) / TOAST_FADE).min(1.0);
+        for child in children.iter() {
+            let Ok((mut background, panel_children)) = panels.get_mut(*child) else {
+                continue;
+            };
+            background.0.set_a(0.9 * alpha);
+            for text_entity in panel_children.iter() {
+                if let Ok(mut text) = texts.get_mut(*text_entity) {
+                    text.sections[0].style.color.set_a(alpha);
+                }
+            }
+        }
+    }
+}
+
+// 删除按钮和选中行的描边跟随管理模式和当前选择；行被复用后按新的下标重新判断
+pub fn show_admin_affordances(
+    mut commands: Commands,
+    admin: Res<AdminMode>,
+    rows: Query<(Entity, &LeaderboardRow, Option<&Outline>)>,
+    mut buttons: Query<(&Parent, &mut Style), With<AdminDeleteButton>>,
+) {
+    let display = if admin.active { Display::Flex } else { Display::None };
+    for (parent, mut style) in buttons.iter_mut() {
+        let shown = rows.get(parent.get()).is_ok_and(|(_, row, _)| row.index.is_some());
+        let wanted = if shown { display } else { Display::None };
+        if style.display != wanted {
+            style.display = wanted;
+        }
+    }
+    for (entity, row, outline) in rows.iter() {
+        let color = if admin.active && row.index == Some(admin.selected) {
+            SELECTED_OUTLINE
+        } else {
+            Color::NONE
+        };
+        let unchanged = match outline {
+            Some(outline) => outline.color == color,
+            None => color == Color::NONE,
+        };
+        if !unchanged {
+            commands.entity(entity).insert(Outline::new(Val::Px(2.0), Val::ZERO, color));
+        }
+    }
+}
+
+// 离开排行榜时关闭管理模式；尚未返回的请求随界面一起取消，不再撤销
+pub fn cleanup_admin_mode(
+    mut commands: Commands,
+    mut admin: ResMut<AdminMode>,
+    query: Query<Entity, Or<(With<AdminBanner>, With<AdminToast>)>>,
+) {
+    *admin = AdminMode::default();
+    for entity in query.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::{ApiClient, LeaderboardResponse};
+    use crate::i18n::Language;
+    use crate::network::{drain_api_responses, NetworkStats, ServerStatus};
+    use breakout_shared::ADMIN_KEY_HEADER;
+    use std::io::{BufRead, BufReader, Read, Write};
+    use std::net::TcpListener;
+    use std::sync::{Arc, Mutex};
+    use std::time::Duration;
+
+    // 模拟的服务器：按顺序用给定的状态码和内容回答每个请求，记录请求行和是否带了管理密钥
+    struct MockBackend {
+        url: String,
+        requests: Arc<Mutex<Vec<String>>>,
+    }
+
+    fn mock_backend(responses: Vec<(u16, &'static str)>) -> MockBackend {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/api", listener.local_addr().unwrap());
+        let requests = Arc::new(Mutex::new(Vec::new()));
+        let recorded = requests.clone();
+        std::thread::spawn(move || {
+            for (status, body) in responses {
+                let Ok((stream, _)) = listener.accept() else {
+                    return;
+                };
+                let mut reader = BufReader::new(stream);
+                let mut request_line = String::new();
+                reader.read_line(&mut request_line).unwrap();
+                let mut content_length = 0;
+                let mut admin_key = None;
+                loop {
+                    let mut line = String::new();
+                    reader.read_line(&mut line).unwrap();
+                    let line = line.trim_end();
+                    if line.is_empty() {
+                        break;
+                    }
+                    let (name, value) = line.split_once(':').unwrap();
+                    if name.eq_ignore_ascii_case("content-length") {
+                        content_length = value.trim().parse().unwrap();
+                    } else if name.eq_ignore_ascii_case(ADMIN_KEY_HEADER) {
+                        admin_key = Some(value.trim().to_string());
+                    }
+                }
+                let mut request_body = vec![0; content_length];
+                reader.read_exact(&mut request_body).unwrap();
+                recorded.lock().unwrap().push(format!(
+                    "{} key={} {}",
+                    request_line.trim_end(),
+                    admin_key.unwrap_or_default(),
+                    String::from_utf8_lossy(&request_body)
+                ));
+
+                let response = format!(
+                    "HTTP/1.1 {} Mock\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                    status,
+                    body.len(),
+                    body
+                );
+                let mut stream = reader.into_inner();
+                stream.write_all(response.as_bytes()).unwrap();
+            }
+        });
+        MockBackend { url, requests }
+    }
+
+    fn score(id: &str, player_name: &str, points: u32) -> Score {
+        Score {
+            id: Some(id.to_string()),
+            player_name: player_name.to_string(),
+            score: points,
+            level: 1,
+            difficulty: "Medium".to_string(),
+            created_at: None,
+            rank: None,
+            season: None,
+            mode: None,
+            share_url: None,
+        }
+    }
+
+    // 管理模式已开启、选中第二行的排行榜，请求发给 backend
+    fn admin_app(backend: &MockBackend) -> App {
+        let client = ApiClient::with_base_url(&backend.url).with_admin_key(Some("secret".to_string()));
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, AssetPlugin::default()))
+            .init_asset::<Font>()
+            .insert_resource(Locale::new(Language::En))
+            .insert_resource(Network::spawn(client))
+            .insert_resource(LeaderboardData::Loaded(LeaderboardResponse {
+                scores: vec![score("a", "ALICE", 300), score("b", "JOKE", 200), score("c", "CAROL", 100)],
+                total: 3,
+                limit: 10,
+                offset: 0,
+                next_cursor: None,
+            }))
+            .insert_resource(AdminMode {
+                active: true,
+                selected: 1,
+                pending: Vec::new(),
+            })
+            .init_resource::<ButtonInput<KeyCode>>()
+            .init_resource::<ServerStatus>()
+            .init_resource::<NetworkStats>()
+            .add_event::<ApiResponse>()
+            .add_systems(Update, (drain_api_responses, admin_input, admin_responses).chain());
+        app.init_resource::<FontAssets>();
+        app
+    }
+
+    fn press(app: &mut App, key: KeyCode) {
+        app.world.resource_mut::<ButtonInput<KeyCode>>().press(key);
+        app.update();
+        let mut input = app.world.resource_mut::<ButtonInput<KeyCode>>();
+        input.release(key);
+        input.clear();
+    }
+
+    // 等待后台线程返回响应并处理完
+    fn wait_for_responses(app: &mut App) {
+        for _ in 0..500 {
+            app.update();
+            if app.world.resource::<AdminMode>().pending.is_empty() {
+                return;
+            }
+            std::thread::sleep(Duration::from_millis(10));
+        }
+        panic!("moderation request did not complete");
+    }
+
+    fn names(app: &App) -> Vec<String> {
+        match app.world.resource::<LeaderboardData>() {
+            LeaderboardData::Loaded(data) => data.scores.iter().map(|score| score.player_name.clone()).collect(),
+            _ => panic!("leaderboard not loaded"),
+        }
+    }
+
+    fn toasts(app: &mut App) -> Vec<String> {
+        let mut texts = app.world.query::<&Text>();
+        let mut toasts = app.world.query_filtered::<&Children, With<AdminToast>>();
+        let mut panels = app.world.query::<&Children>();
+        let mut messages = Vec::new();
+        for toast in toasts.iter(&app.world) {
+            for panel in toast.iter() {
+                for text in panels.get(&app.world, *panel).unwrap().iter() {
+                    messages.push(texts.get(&app.world, *text).unwrap().sections[0].value.clone());
+                }
+            }
+        }
+        messages
+    }
+
+    #[test]
+    fn failed_delete_puts_the_row_back_and_shows_a_toast() {
+        let backend = mock_backend(vec![(
+            404,
+            r#"{"error":"Not Found","message":"Score not found","timestamp":"2025-06-01T00:00:00Z"}"#,
+        )]);
+        let mut app = admin_app(&backend);
+
+        press(&mut app, KeyCode::Delete);
+        // 请求返回之前行已经移除
+        assert_eq!(names(&app), vec!["ALICE", "CAROL"]);
+
+        wait_for_responses(&mut app);
+        assert_eq!(names(&app), vec!["ALICE", "JOKE", "CAROL"]);
+        assert_eq!(toasts(&mut app), vec!["Could not delete JOKE: Score not found"]);
+        assert_eq!(*backend.requests.lock().unwrap(), vec!["DELETE /api/scores/b HTTP/1.1 key=secret "]);
+    }
+
+    #[test]
+    fn failed_rename_restores_the_name() {
+        let backend = mock_backend(vec![(500, "oops")]);
+        let mut app = admin_app(&backend);
+
+        press(&mut app, KeyCode::KeyH);
+        assert_eq!(names(&app), vec!["ALICE", REMOVED_NAME, "CAROL"]);
+
+        wait_for_responses(&mut app);
+        assert_eq!(names(&app), vec!["ALICE", "JOKE", "CAROL"]);
+        assert_eq!(toasts(&mut app), vec!["Could not hide JOKE: Failed to rename score: 500 Internal Server Error"]);
+        assert_eq!(
+            *backend.requests.lock().unwrap(),
+            vec![format!("PATCH /api/scores/b HTTP/1.1 key=secret {{\"player_name\":\"{}\"}}", REMOVED_NAME)]
+        );
+    }
+
+    #[test]
+    fn confirmed_delete_stays_removed() {
+        let backend = mock_backend(vec![(204, "")]);
+        let mut app = admin_app(&backend);
+
+        press(&mut app, KeyCode::Delete);
+        wait_for_responses(&mut app);
+        assert_eq!(names(&app), vec!["ALICE", "CAROL"]);
+        assert!(toasts(&mut app).is_empty());
+        // 删除最后一行以外的行后选择停在原来的位置
+        assert_eq!(app.world.resource::<AdminMode>().selected, 1);
+    }
+}
@@ -1,84 +1,79 @@
-use serde::{Deserialize, Serialize};
+// 接口的请求和响应格式和服务器共用
+pub use breakout_shared::api::{
+    CreateScoreRequest, DailyResponse, EventResponse, FlagsResponse, LeaderboardResponse, RaceProgress,
+    RaceProgressRequest, RaceResponse, RivalsResponse, Score, SeasonsResponse,
+};
+pub use breakout_shared::histogram::LevelHistogram;
+use breakout_shared::api::{ErrorResponse, RaceJoinRequest, RecentScoresResponse, RenameScoreRequest};
+use breakout_shared::{ADMIN_KEY_ENV, ADMIN_KEY_HEADER, API_URL_ENV, DEFAULT_API_URL};
 use std::error::Error;
+use std::time::Duration;
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct Score {
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub id: Option<String>,
-    pub player_name: String,
-    pub score: u32,
-    pub level: u32,
-    pub difficulty: String,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub created_at: Option<String>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub rank: Option<u32>,
-}
-
-#[derive(Debug, Serialize, Deserialize)]
-pub struct CreateScoreRequest {
-    pub player_name: String,
-    pub score: u32,
-    pub level: u32,
-    pub difficulty: String,
-}
-
-#[derive(Debug, Serialize, Deserialize)]
-pub struct LeaderboardResponse {
-    pub scores: Vec<Score>,
-    pub total: usize,
-    pub limit: usize,
-    pub offset: usize,
-}
+// 单个请求的超时时间，避免服务器无响应时一直等待
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(5);
 
 pub struct ApiClient {
     base_url: String,
     client: reqwest::blocking::Client,
+    // 管理密钥，只在展台操作员的机器上配置
+    admin_key: Option<String>,
 }
 
 impl ApiClient {
+    // 默认连接本机的服务器；设置 BREAKOUT_API_URL 时连接指定的地址（cargo xtask dev 会设置）
+    // 设置 BREAKOUT_ADMIN_API_KEY 时带上管理密钥，排行榜中可以删除成绩和隐藏名字
     pub fn new() -> Self {
+        let client = match std::env::var(API_URL_ENV) {
+            Ok(url) if !url.trim().is_empty() => Self::with_base_url(url.trim().trim_end_matches('/')),
+            _ => Self::with_base_url(DEFAULT_API_URL),
+        };
+        client.with_admin_key(std::env::var(ADMIN_KEY_ENV).ok())
+    }
+
+    pub fn with_base_url(base_url: &str) -> Self {
         Self {
-            base_url: "http://localhost:8080/api".to_string(),
-            client: reqwest::blocking::Client::new(),
+            base_url: base_url.to_string(),
+            client: reqwest::blocking::Client::builder()
+                .timeout(REQUEST_TIMEOUT)
+                .build()
+                .unwrap_or_else(|_| reqwest::blocking::Client::new()),
+            admin_key: None,
         }
     }
+
+    pub fn with_admin_key(mut self, admin_key: Option<String>) -> Self {
+        self.admin_key = admin_key.map(|key| key.trim().to_string()).filter(|key| !key.is_empty());
+        self
+    }
+
+    pub fn has_admin_key(&self) -> bool {
+        self.admin_key.is_some()
+    }
     
-    // 提交分数（非阻塞）
-    pub fn submit_score_async(&self, player_name: String, score: u32, level: u32, difficulty: String) {
-        let client = self.client.clone();
-        let url = format!("{}/scores", self.base_url);
-        
-        // 在新线程中发送请求，避免阻塞游戏
-        std::thread::spawn(move || {
-            let request = CreateScoreRequest {
-                player_name,
-                score,
-                level,
-                difficulty,
-            };
-            
-            match client.post(&url)
-                .json(&request)
-                .send() {
-                Ok(response) => {
-                    if response.status().is_success() {
-                        println!("Score submitted successfully!");
-                    } else {
-                        eprintln!("Failed to submit score: {}", response.status());
-                    }
-                }
-                Err(e) => {
-                    eprintln!("Error submitting score: {}", e);
-                }
-            }
-        });
+    // 提交分数（阻塞）
+    pub fn submit_score(&self, request: &CreateScoreRequest) -> Result<Score, Box<dyn Error>> {
+        let response = self.client.post(format!("{}/scores", self.base_url)).json(request).send()?;
+
+        if response.status().is_success() {
+            Ok(response.json()?)
+        } else {
+            Err(format!("Failed to submit score: {}", response.status()).into())
+        }
     }
     
-    // 获取排行榜（阻塞）
-    pub fn get_leaderboard(&self, limit: Option<usize>, difficulty: Option<&str>) -> Result<LeaderboardResponse, Box<dyn Error>> {
+    // 获取排行榜（阻塞），使用精简格式：排行榜不需要 id 和提交时间。
+    // 有管理密钥时使用完整格式，删除和改名需要 id
+    // season 为 None 时使用服务器的当前赛季，"all" 表示全部赛季；cursor 为上一页返回的 next_cursor
+    pub fn get_leaderboard(
+        &self,
+        limit: Option<usize>,
+        difficulty: Option<&str>,
+        season: Option<&str>,
+        cursor: Option<&str>,
+    ) -> Result<LeaderboardResponse, Box<dyn Error>> {
         let mut url = format!("{}/scores", self.base_url);
-        let mut params = Vec::new();
+        let fields = if self.admin_key.is_some() { "full" } else { "compact" };
+        let mut params = vec![format!("fields={}", fields)];
         
         if let Some(limit) = limit {
             params.push(format!("limit={}", limit));
@@ -87,11 +82,17 @@ impl ApiClient {
         if let Some(difficulty) = difficulty {
             params.push(format!("difficulty={}", difficulty));
         }
-        
-        if !params.is_empty() {
-            url.push_str("?");
-            url.push_str(&params.join("&"));
+
+        if let Some(season) = season {
+            params.push(format!("season={}", season));
         }
+
+        if let Some(cursor) = cursor {
+            params.push(format!("cursor={}", cursor));
+        }
+        
+        url.push('?');
+        url.push_str(&params.join("&"));
         
         let response = self.client.get(&url).send()?;
         
@@ -103,11 +104,195 @@ impl ApiClient {
         }
     }
     
-    // 测试连接
-    pub fn test_connection(&self) -> bool {
-        match self.client.get(&format!("{}/health", self.base_url)).send() {
-            Ok(response) => response.status().is_success(),
-            Err(_) => false,
+    // 获取最近提交的分数，按提交时间倒序（阻塞）
+    pub fn get_recent_scores(&self, limit: usize) -> Result<Vec<Score>, Box<dyn Error>> {
+        let response = self
+            .client
+            .get(format!("{}/scores/recent?limit={}", self.base_url, limit))
+            .send()?;
+
+        if response.status().is_success() {
+            let recent: RecentScoresResponse = response.json()?;
+            Ok(recent.scores)
+        } else {
+            Err(format!("Failed to get recent scores: {}", response.status()).into())
+        }
+    }
+
+    // 获取关卡分布（阻塞）
+    pub fn get_level_histogram(&self, difficulty: &str) -> Result<LevelHistogram, Box<dyn Error>> {
+        let response = self
+            .client
+            .get(format!("{}/stats/levels?difficulty={}", self.base_url, difficulty))
+            .send()?;
+
+        if response.status().is_success() {
+            Ok(response.json()?)
+        } else {
+            Err(format!("Failed to get level histogram: {}", response.status()).into())
+        }
+    }
+
+    // 获取赛季列表（阻塞）
+    pub fn get_seasons(&self) -> Result<SeasonsResponse, Box<dyn Error>> {
+        let response = self.client.get(format!("{}/seasons", self.base_url)).send()?;
+
+        if response.status().is_success() {
+            Ok(response.json()?)
+        } else {
+            Err(format!("Failed to get seasons: {}", response.status()).into())
+        }
+    }
+    
+    // 获取某一天的每日挑战榜单和该玩家的最好成绩（阻塞）
+    pub fn get_daily(&self, date: &str, player_name: &str, limit: usize) -> Result<DailyResponse, Box<dyn Error>> {
+        let limit = limit.to_string();
+        let response = self
+            .client
+            .get(format!("{}/daily", self.base_url))
+            .query(&[("date", date), ("player_name", player_name), ("limit", limit.as_str())])
+            .send()?;
+
+        if response.status().is_success() {
+            Ok(response.json()?)
+        } else {
+            Err(format!("Failed to get daily challenge: {}", response.status()).into())
+        }
+    }
+
+    // 获取本周活动（阻塞），服务器没有配置活动时返回 None
+    pub fn get_event(&self) -> Result<Option<EventResponse>, Box<dyn Error>> {
+        let response = self.client.get(format!("{}/event", self.base_url)).send()?;
+
+        if response.status() == reqwest::StatusCode::NOT_FOUND {
+            Ok(None)
+        } else if response.status().is_success() {
+            Ok(Some(response.json()?))
+        } else {
+            Err(format!("Failed to get weekly event: {}", response.status()).into())
         }
     }
-}
\ No newline at end of file
+
+    // 获取功能开关（阻塞）
+    pub fn get_flags(&self) -> Result<FlagsResponse, Box<dyn Error>> {
+        let response = self.client.get(format!("{}/flags", self.base_url)).send()?;
+
+        if response.status().is_success() {
+            Ok(response.json()?)
+        } else {
+            Err(format!("Failed to get feature flags: {}", response.status()).into())
+        }
+    }
+
+    // 创建比赛（阻塞），返回加入码和种子
+    pub fn create_race(&self, player_name: &str) -> Result<RaceResponse, Box<dyn Error>> {
+        let response = self
+            .client
+            .post(format!("{}/races", self.base_url))
+            .json(&RaceJoinRequest {
+                player_name: player_name.to_string(),
+            })
+            .send()?;
+        race_result(response, "create race")
+    }
+
+    // 用加入码加入比赛（阻塞）
+    pub fn join_race(&self, code: &str, player_name: &str) -> Result<RaceResponse, Box<dyn Error>> {
+        let response = self
+            .client
+            .post(format!("{}/races/{}/join", self.base_url, code))
+            .json(&RaceJoinRequest {
+                player_name: player_name.to_string(),
+            })
+            .send()?;
+        race_result(response, "join race")
+    }
+
+    // 上报自己的进度，返回双方的最新进度（阻塞）
+    pub fn post_race_progress(&self, code: &str, request: &RaceProgressRequest) -> Result<RaceResponse, Box<dyn Error>> {
+        let response = self
+            .client
+            .post(format!("{}/races/{}/progress", self.base_url, code))
+            .json(request)
+            .send()?;
+        race_result(response, "update race")
+    }
+
+    // 获取比赛状态（阻塞）
+    pub fn get_race(&self, code: &str) -> Result<RaceResponse, Box<dyn Error>> {
+        let response = self.client.get(format!("{}/races/{}", self.base_url, code)).send()?;
+        race_result(response, "get race")
+    }
+
+    // 获取对手推荐（阻塞）；玩家名放在路径中，需要转义
+    pub fn get_rivals(&self, player_name: &str, difficulty: &str) -> Result<RivalsResponse, Box<dyn Error>> {
+        let mut url = reqwest::Url::parse(&self.base_url)?;
+        url.path_segments_mut()
+            .map_err(|_| "Invalid base URL")?
+            .pop_if_empty()
+            .extend(["players", player_name, "rivals"]);
+        let response = self.client.get(url).query(&[("difficulty", difficulty)]).send()?;
+
+        if response.status().is_success() {
+            Ok(response.json()?)
+        } else {
+            Err(format!("Failed to get rivals: {}", response.status()).into())
+        }
+    }
+
+    // 删除成绩（阻塞，管理员功能）
+    pub fn delete_score(&self, id: &str) -> Result<(), Box<dyn Error>> {
+        let response = self.admin_request(reqwest::Method::DELETE, id)?.send()?;
+        admin_result(response, "delete score")
+    }
+
+    // 修改成绩的玩家名（阻塞，管理员功能）
+    pub fn rename_score(&self, id: &str, player_name: &str) -> Result<(), Box<dyn Error>> {
+        let response = self
+            .admin_request(reqwest::Method::PATCH, id)?
+            .json(&RenameScoreRequest {
+                player_name: player_name.to_string(),
+            })
+            .send()?;
+        admin_result(response, "rename score")
+    }
+
+    // /scores/{id} 上的管理请求，带管理密钥；没有密钥时不发送
+    fn admin_request(
+        &self,
+        method: reqwest::Method,
+        id: &str,
+    ) -> Result<reqwest::blocking::RequestBuilder, Box<dyn Error>> {
+        let admin_key = self.admin_key.as_deref().ok_or("No admin key configured")?;
+        let mut url = reqwest::Url::parse(&self.base_url)?;
+        url.path_segments_mut()
+            .map_err(|_| "Invalid base URL")?
+            .pop_if_empty()
+            .extend(["scores", id]);
+        Ok(self.client.request(method, url).header(ADMIN_KEY_HEADER, admin_key))
+    }
+}
+
+// 管理接口成功时没有内容；失败时优先使用服务器给出的说明（例如密钥错误或成绩不存在）
+fn admin_result(response: reqwest::blocking::Response, action: &str) -> Result<(), Box<dyn Error>> {
+    let status = response.status();
+    if status.is_success() {
+        return Ok(());
+    }
+    match response.json::<ErrorResponse>() {
+        Ok(body) => Err(body.message.into()),
+        Err(_) => Err(format!("Failed to {}: {}", action, status).into()),
+    }
+}
+
+// 比赛接口的响应；失败时优先使用服务器给出的说明（例如比赛不存在或已满员）
+fn race_result(response: reqwest::blocking::Response, action: &str) -> Result<RaceResponse, Box<dyn Error>> {
+    let status = response.status();
+    if status.is_success() {
+        return Ok(response.json()?);
+    }
+    match response.json::<ErrorResponse>() {
+        Ok(body) => Err(body.message.into()),
+        Err(_) => Err(format!("Failed to {}: {}", action, status).into()),
+    }
+}
@@ -0,0 +1,218 @@
+use bevy::prelude::*;
+
+use crate::fonts::{text_style, FontAssets, FontKind};
+use crate::kiosk::KioskConfig;
+use crate::{normalize_player_name, GameState, PlayerName};
+
+// 每个位置可选的字符，按上下键循环
+const INITIALS_ALPHABET: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZ0123456789";
+// 街机模式的名字长度
+const INITIALS_LENGTH: usize = 3;
+// 按住上下键多久后开始连续切换，以及连续切换的间隔（秒）
+const REPEAT_DELAY: f32 = 0.3;
+const REPEAT_INTERVAL: f32 = 0.08;
+
+const SLOT_COLOR: Color = Color::rgb(0.2, 0.2, 0.25);
+const ACTIVE_SLOT_COLOR: Color = Color::rgb(0.35, 0.3, 0.1);
+const ACTIVE_SLOT_BORDER: Color = Color::rgb(1.0, 0.85, 0.0);
+
+// 最近一次使用的输入设备，用于自动选择名字输入方式
+#[derive(Resource, Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum LastInputDevice {
+    #[default]
+    Keyboard,
+    Gamepad,
+}
+
+// 输入名称界面当前使用的输入方式
+#[derive(Resource, Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum NameEntryMode {
+    #[default]
+    Text,
+    Arcade,
+}
+
+// 街机式三字母输入的状态
+#[derive(Resource, Default)]
+pub struct ArcadeNameEntry {
+    // 每个位置在 INITIALS_ALPHABET 中的索引
+    slots: [usize; INITIALS_LENGTH],
+    active: usize,
+    // 正在按住的方向和已按住的时间
+    held: Option<(i32, f32)>,
+}
+
+impl ArcadeNameEntry {
+    // 用已保存名字的前三个字符预填，无法表示的字符用 A 代替
+    pub fn from_name(name: &str) -> Self {
+        let mut slots = [0; INITIALS_LENGTH];
+        for (slot, ch) in slots.iter_mut().zip(name.chars()) {
+            let ch = ch.to_ascii_uppercase();
+            *slot = INITIALS_ALPHABET.iter().position(|c| *c as char == ch).unwrap_or(0);
+        }
+        Self {
+            slots,
+            active: 0,
+            held: None,
+        }
+    }
+
+    fn cycle(&mut self, direction: i32) {
+        let count = INITIALS_ALPHABET.len() as i32;
+        let slot = &mut self.slots[self.active];
+        *slot = (*slot as i32 + direction).rem_euclid(count) as usize;
+    }
+
+    fn name(&self) -> String {
+        self.slots.iter().map(|index| INITIALS_ALPHABET[*index] as char).collect()
+    }
+}
+
+// 按住 held 秒后累计应连续切换的次数
+fn repeat_count(held: f32) -> i32 {
+    if held < REPEAT_DELAY {
+        0
+    } else {
+        ((held - REPEAT_DELAY) / REPEAT_INTERVAL) as i32 + 1
+    }
+}
+
+// 街机输入的一个字符位置，保存位置索引
+#[derive(Component)]
+pub struct InitialSlot(usize);
+
+// 记录最近使用的输入设备
+pub fn track_input_device(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    gamepad_input: Res<ButtonInput<GamepadButton>>,
+    mut last_input: ResMut<LastInputDevice>,
+) {
+    if gamepad_input.get_just_pressed().next().is_some() {
+        last_input.set_if_neq(LastInputDevice::Gamepad);
+    } else if keyboard_input.get_just_pressed().next().is_some() {
+        last_input.set_if_neq(LastInputDevice::Keyboard);
+    }
+}
+
+// 三个字符位置
+pub fn spawn_initial_slots(parent: &mut ChildBuilder, fonts: &FontAssets) {
+    parent
+        .spawn(NodeBundle {
+            style: Style {
+                margin: UiRect::top(Val::Px(50.0)),
+                column_gap: Val::Px(20.0),
+                ..default()
+            },
+            ..default()
+        })
+        .with_children(|parent| {
+            for index in 0..INITIALS_LENGTH {
+                parent
+                    .spawn((
+                        NodeBundle {
+                            style: Style {
+                                width: Val::Px(80.0),
+                                height: Val::Px(100.0),
+                                border: UiRect::all(Val::Px(3.0)),
+                                justify_content: JustifyContent::Center,
+                                align_items: AlignItems::Center,
+                                ..default()
+                            },
+                            background_color: BackgroundColor(SLOT_COLOR),
+                            border_color: BorderColor(Color::rgb(0.5, 0.5, 0.6)),
+                            ..default()
+                        },
+                        InitialSlot(index),
+                    ))
+                    .with_children(|parent| {
+                        parent.spawn(TextBundle::from_section(
+                            "",
+                            text_style(fonts, FontKind::Mono, 64.0, Color::WHITE),
+                        ));
+                    });
+            }
+        });
+}
+
+// 处理街机式输入：上下切换字符（按住连续切换），右/回车确认，左返回上一位
+pub fn arcade_name_system(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    gamepads: Res<Gamepads>,
+    gamepad_input: Res<ButtonInput<GamepadButton>>,
+    time: Res<Time>,
+    kiosk: Res<KioskConfig>,
+    mut entry: ResMut<ArcadeNameEntry>,
+    mut player_name: ResMut<PlayerName>,
+    mut next_state: ResMut<NextState<GameState>>,
+    mut slot_query: Query<(&InitialSlot, &Children, &mut BackgroundColor, &mut BorderColor)>,
+    mut text_query: Query<&mut Text>,
+) {
+    let gamepad_pressed = |button_type: GamepadButtonType| {
+        gamepads
+            .iter()
+            .any(|gamepad| gamepad_input.pressed(GamepadButton::new(gamepad, button_type)))
+    };
+    let gamepad_just_pressed = |button_type: GamepadButtonType| {
+        gamepads
+            .iter()
+            .any(|gamepad| gamepad_input.just_pressed(GamepadButton::new(gamepad, button_type)))
+    };
+
+    // 上下切换字符，按住超过 REPEAT_DELAY 后连续切换
+    let direction = if keyboard_input.pressed(KeyCode::ArrowUp) || gamepad_pressed(GamepadButtonType::DPadUp) {
+        Some(1)
+    } else if keyboard_input.pressed(KeyCode::ArrowDown) || gamepad_pressed(GamepadButtonType::DPadDown) {
+        Some(-1)
+    } else {
+        None
+    };
+    match (direction, entry.held) {
+        (Some(direction), Some((held_direction, held_time))) if direction == held_direction => {
+            let held = held_time + time.delta_seconds();
+            for _ in repeat_count(held_time)..repeat_count(held) {
+                entry.cycle(direction);
+            }
+            entry.held = Some((direction, held));
+        }
+        (Some(direction), _) => {
+            entry.cycle(direction);
+            entry.held = Some((direction, 0.0));
+        }
+        (None, _) => entry.held = None,
+    }
+
+    if keyboard_input.just_pressed(KeyCode::ArrowRight)
+        || keyboard_input.just_pressed(KeyCode::Enter)
+        || gamepad_just_pressed(GamepadButtonType::DPadRight)
+        || gamepad_just_pressed(GamepadButtonType::South)
+    {
+        if entry.active + 1 < INITIALS_LENGTH {
+            entry.active += 1;
+        } else if let Some(name) = normalize_player_name(&entry.name()) {
+            player_name.0 = name;
+            next_state.set(kiosk.after_name_entry());
+        }
+    } else if keyboard_input.just_pressed(KeyCode::ArrowLeft)
+        || keyboard_input.just_pressed(KeyCode::Backspace)
+        || gamepad_just_pressed(GamepadButtonType::DPadLeft)
+        || gamepad_just_pressed(GamepadButtonType::East)
+    {
+        entry.active = entry.active.saturating_sub(1);
+    }
+
+    if keyboard_input.just_pressed(KeyCode::Escape) || gamepad_just_pressed(GamepadButtonType::Select) {
+        next_state.set(kiosk.after_name_entry());
+    }
+
+    // 更新字符和当前位置的高亮
+    for (slot, children, mut background, mut border) in slot_query.iter_mut() {
+        let active = slot.0 == entry.active;
+        background.0 = if active { ACTIVE_SLOT_COLOR } else { SLOT_COLOR };
+        border.0 = if active { ACTIVE_SLOT_BORDER } else { Color::rgb(0.5, 0.5, 0.6) };
+        for child in children.iter() {
+            if let Ok(mut text) = text_query.get_mut(*child) {
+                text.sections[0].value = (INITIALS_ALPHABET[entry.slots[slot.0]] as char).to_string();
+            }
+        }
+    }
+}
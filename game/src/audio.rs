@@ -0,0 +1,215 @@
+use bevy::audio::Volume;
+use bevy::prelude::*;
+use std::path::Path;
+
+use crate::paddle_feedback::impact_intensity;
+use crate::{GameplayEvent, Settings, WINDOW_WIDTH};
+
+// 音效文件目录（相对于 assets）
+const SOUND_DIR: &str = "sounds";
+// 听者两耳间距；发声点放在 [-EAR_GAP/2, EAR_GAP/2] 之间实现声像
+const EAR_GAP: f32 = 2.0;
+
+// 游戏音效种类
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SoundKind {
+    BrickBreak,
+    BrickHit,
+    PaddleHit,
+    PowerUp,
+    Laser,
+}
+
+impl SoundKind {
+    const ALL: [SoundKind; 5] = [
+        SoundKind::BrickBreak,
+        SoundKind::BrickHit,
+        SoundKind::PaddleHit,
+        SoundKind::PowerUp,
+        SoundKind::Laser,
+    ];
+
+    fn file_name(&self) -> &'static str {
+        match self {
+            SoundKind::BrickBreak => "brick_break.ogg",
+            SoundKind::BrickHit => "brick_hit.ogg",
+            SoundKind::PaddleHit => "paddle_hit.ogg",
+            SoundKind::PowerUp => "powerup.ogg",
+            SoundKind::Laser => "laser.ogg",
+        }
+    }
+}
+
+// 游戏会加载的全部音效文件（相对于 assets）
+pub fn sound_files() -> impl Iterator<Item = (SoundKind, String)> {
+    SoundKind::ALL
+        .into_iter()
+        .map(|kind| (kind, format!("{}/{}", SOUND_DIR, kind.file_name())))
+}
+
+// 播放音效的事件；world_pos 为 None 时居中播放（UI、音乐）
+#[derive(Event, Debug, Clone, Copy)]
+pub struct SoundEvent {
+    pub kind: SoundKind,
+    pub world_pos: Option<Vec2>,
+    // 音量和播放速度的倍数，默认都是 1
+    pub volume: f32,
+    pub pitch: f32,
+}
+
+impl SoundEvent {
+    pub fn at(kind: SoundKind, position: Vec3) -> Self {
+        Self {
+            kind,
+            world_pos: Some(position.truncate()),
+            volume: 1.0,
+            pitch: 1.0,
+        }
+    }
+
+    // 按撞击强度（0 到 1）调整：越用力越响，音调也略高，听起来更有力
+    pub fn with_impact(self, intensity: f32) -> Self {
+        Self {
+            volume: 0.6 + 0.6 * intensity,
+            pitch: 0.95 + 0.15 * intensity,
+            ..self
+        }
+    }
+}
+
+// 已加载的音效（缺少的文件直接跳过）
+#[derive(Resource, Default)]
+pub struct SoundAssets {
+    sounds: Vec<(SoundKind, Handle<AudioSource>)>,
+}
+
+impl SoundAssets {
+    fn get(&self, kind: SoundKind) -> Option<Handle<AudioSource>> {
+        self.sounds
+            .iter()
+            .find(|(sound_kind, _)| *sound_kind == kind)
+            .map(|(_, handle)| handle.clone())
+    }
+}
+
+// 世界坐标 x 转换为声像：-1.0 最左，0.0 居中，1.0 最右
+pub fn stereo_pan(world_x: f32) -> f32 {
+    (world_x / (WINDOW_WIDTH / 2.0)).clamp(-1.0, 1.0)
+}
+
+// 把游戏事件转换为对应位置的音效
+pub fn gameplay_sounds(
+    mut gameplay_events: EventReader<GameplayEvent>,
+    mut sound_events: EventWriter<SoundEvent>,
+) {
+    for event in gameplay_events.read() {
+        let sound = match *event {
+            GameplayEvent::BrickDestroyed { position, .. }
+            | GameplayEvent::BombDetonated { position, .. }
+            | GameplayEvent::BossDefeated { position, .. } => {
+                SoundEvent::at(SoundKind::BrickBreak, position)
+            }
+            GameplayEvent::BrickDamaged { position }
+            | GameplayEvent::ArmorDeflected { position }
+            | GameplayEvent::ShieldDeflected { position, .. } => {
+                SoundEvent::at(SoundKind::BrickHit, position)
+            }
+            GameplayEvent::PaddleHit { position, speed, .. } => {
+                SoundEvent::at(SoundKind::PaddleHit, position).with_impact(impact_intensity(speed))
+            }
+            GameplayEvent::EdgeSave { position } => SoundEvent::at(SoundKind::PaddleHit, position),
+            GameplayEvent::PowerUpCollected { position, .. } => SoundEvent::at(SoundKind::PowerUp, position),
+            // 连锁奖励：砖块碎裂声之外再加一声奖励音
+            GameplayEvent::ChainReaction { position, .. } => SoundEvent::at(SoundKind::PowerUp, position),
+            // 护符碎开
+            GameplayEvent::DebuffWarded { position, .. } => SoundEvent::at(SoundKind::BrickHit, position),
+            GameplayEvent::LaserFired { position } => SoundEvent::at(SoundKind::Laser, position),
+            GameplayEvent::WardGained | GameplayEvent::LifeLost | GameplayEvent::InsuranceUsed => continue,
+        };
+        sound_events.send(sound);
+    }
+}
+
+// 加载音效并创建位于原点的听者
+pub fn setup_audio(mut commands: Commands, asset_server: Res<AssetServer>) {
+    let mut assets = SoundAssets::default();
+    for (kind, path) in sound_files() {
+        if Path::new("assets").join(&path).exists() {
+            assets.sounds.push((kind, asset_server.load(path)));
+        }
+    }
+    commands.insert_resource(assets);
+
+    commands.spawn((
+        SpatialBundle::default(),
+        SpatialListener::new(EAR_GAP),
+    ));
+}
+
+// 播放音效：有位置的音效按声像播放，单声道模式下全部居中
+pub fn play_sound_events(
+    mut commands: Commands,
+    mut events: EventReader<SoundEvent>,
+    assets: Res<SoundAssets>,
+    settings: Res<Settings>,
+) {
+    for event in events.read() {
+        let Some(source) = assets.get(event.kind) else {
+            continue;
+        };
+
+        let playback = PlaybackSettings::DESPAWN
+            .with_volume(Volume::new(event.volume))
+            .with_speed(event.pitch);
+        match event.world_pos {
+            Some(position) if !settings.mono_audio => {
+                let pan = stereo_pan(position.x);
+                commands.spawn((
+                    AudioBundle {
+                        source,
+                        settings: playback.with_spatial(true),
+                    },
+                    SpatialBundle::from_transform(Transform::from_xyz(
+                        pan * EAR_GAP / 2.0,
+                        0.0,
+                        1.0,
+                    )),
+                ));
+            }
+            _ => {
+                commands.spawn(AudioBundle {
+                    source,
+                    settings: playback,
+                });
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stereo_pan_is_centred_in_the_middle() {
+        assert_eq!(stereo_pan(0.0), 0.0);
+    }
+
+    #[test]
+    fn stereo_pan_reaches_full_pan_at_the_walls() {
+        assert_eq!(stereo_pan(-WINDOW_WIDTH / 2.0), -1.0);
+        assert_eq!(stereo_pan(WINDOW_WIDTH / 2.0), 1.0);
+    }
+
+    #[test]
+    fn stereo_pan_is_linear_between_centre_and_walls() {
+        assert_eq!(stereo_pan(WINDOW_WIDTH / 4.0), 0.5);
+        assert_eq!(stereo_pan(-WINDOW_WIDTH / 4.0), -0.5);
+    }
+
+    #[test]
+    fn stereo_pan_clamps_positions_outside_the_playfield() {
+        assert_eq!(stereo_pan(-WINDOW_WIDTH), -1.0);
+        assert_eq!(stereo_pan(WINDOW_WIDTH * 3.0), 1.0);
+    }
+}
@@ -0,0 +1,171 @@
+use std::fmt::Write;
+
+use crate::homing::HOMING_DROP_WEIGHT;
+
+use crate::{
+    BrickType, Difficulty, DifficultySettings, PowerUpType, BALL_SPEED, GOLDEN_BRICK_POINTS, PADDLE_SPEED,
+    POWERUP_DROP_CHANCE,
+};
+
+// 一个难度实际生效的数值
+pub struct DifficultyBalance {
+    pub difficulty: Difficulty,
+    pub lives: u32,
+    pub reset_lives_on_level: bool,
+    // 像素/秒，已乘上难度系数
+    pub ball_speed: f32,
+    pub paddle_speed: f32,
+    pub time_limit: Option<f32>,
+    pub edge_grace: f32,
+    pub paddle_tilt: bool,
+    pub slow_zone: bool,
+}
+
+impl DifficultyBalance {
+    fn new(settings: &DifficultySettings) -> Self {
+        Self {
+            difficulty: settings.difficulty,
+            lives: settings.lives,
+            reset_lives_on_level: settings.reset_lives_on_level,
+            ball_speed: BALL_SPEED * settings.ball_speed_modifier,
+            paddle_speed: PADDLE_SPEED * settings.paddle_speed_modifier,
+            time_limit: settings.time_limit,
+            edge_grace: settings.edge_grace,
+            paddle_tilt: settings.paddle_tilt,
+            slow_zone: settings.slow_zone,
+        }
+    }
+}
+
+// 平衡性报告：直接从游戏使用的构造函数和常量生成，--balance-report 和难度选择界面共用
+pub struct BalanceReport {
+    pub difficulties: Vec<DifficultyBalance>,
+    pub powerup_drop_chance: f64,
+}
+
+impl BalanceReport {
+    pub fn current() -> Self {
+        Self {
+            difficulties: Difficulty::ALL
+                .iter()
+                .map(|difficulty| DifficultyBalance::new(&DifficultySettings::new(*difficulty)))
+                .collect(),
+            powerup_drop_chance: POWERUP_DROP_CHANCE,
+        }
+    }
+
+    // 难度选择界面的数值条（文本 key，相对所有难度最大值的比例）
+    pub fn stat_bars(&self, balance: &DifficultyBalance) -> [(&'static str, f32); 4] {
+        let max_ball = self.difficulties.iter().map(|d| d.ball_speed).fold(0.0, f32::max);
+        let max_paddle = self.difficulties.iter().map(|d| d.paddle_speed).fold(0.0, f32::max);
+        let max_lives = self.difficulties.iter().map(|d| d.lives).max().unwrap_or(1) as f32;
+        [
+            ("difficulty.stat_ball", balance.ball_speed / max_ball),
+            ("difficulty.stat_paddle", balance.paddle_speed / max_paddle),
+            ("difficulty.stat_lives", balance.lives as f32 / max_lives),
+            ("difficulty.stat_timer", if balance.time_limit.is_some() { 1.0 } else { 0.0 }),
+        ]
+    }
+
+    // Markdown 格式的报告
+    pub fn to_markdown(&self) -> String {
+        let mut out = String::new();
+        let yes_no = |value: bool| if value { "yes" } else { "no" };
+
+        let _ = writeln!(out, "# Balance report\n");
+        let _ = writeln!(out, "## Difficulties\n");
+        let _ = writeln!(
+            out,
+            "| Difficulty | Lives | Reset lives each level | Ball speed (px/s) | Paddle speed (px/s) | Time limit (s) | Edge grace (px) | Paddle tilt | Slow zone |"
+        );
+        let _ = writeln!(out, "|---|---|---|---|---|---|---|---|---|");
+        for balance in &self.difficulties {
+            let _ = writeln!(
+                out,
+                "| {} | {} | {} | {:.0} | {:.0} | {} | {:.0} | {} | {} |",
+                balance.difficulty.name(),
+                balance.lives,
+                yes_no(balance.reset_lives_on_level),
+                balance.ball_speed,
+                balance.paddle_speed,
+                balance.time_limit.map(|t| format!("{:.0}", t)).unwrap_or_else(|| "-".to_string()),
+                balance.edge_grace,
+                yes_no(balance.paddle_tilt),
+                yes_no(balance.slow_zone),
+            );
+        }
+
+        let _ = writeln!(out, "\n## Power-ups\n");
+        let _ = writeln!(out, "Drop chance per brick destroyed by a ball: {:.0}%\n", self.powerup_drop_chance * 100.0);
+        let _ = writeln!(out, "Homing Ball share of random drops: {:.0}%\n", HOMING_DROP_WEIGHT * 100.0);
+        let _ = writeln!(out, "| Power-up | Duration (s) | Negative |");
+        let _ = writeln!(out, "|---|---|---|");
+        for power_type in PowerUpType::ALL {
+            let _ = writeln!(
+                out,
+                "| {} | {} | {} |",
+                power_type.name(),
+                power_type.duration().map(|d| format!("{:.0}", d)).unwrap_or_else(|| "-".to_string()),
+                yes_no(power_type.is_negative()),
+            );
+        }
+
+        let _ = writeln!(out, "\n## Bricks\n");
+        let _ = writeln!(out, "| Brick | First level | Points | Laser points |");
+        let _ = writeln!(out, "|---|---|---|---|");
+        for brick_type in BrickType::ALL {
+            let _ = writeln!(
+                out,
+                "| {} | {} | {} | {} |",
+                brick_type.name(),
+                brick_type.first_level(),
+                brick_type.points(),
+                brick_type.laser_points(),
+            );
+        }
+        // 金砖是普通砖的变体，每关一到两块，一定掉落有利道具
+        let _ = writeln!(
+            out,
+            "| Golden | {} | {} | {} |",
+            BrickType::Normal.first_level(),
+            GOLDEN_BRICK_POINTS,
+            GOLDEN_BRICK_POINTS * 3 / 2,
+        );
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // 平衡性数值的基准文件。数值改动是有意的，就重新生成：
+    // cargo run -p breakout-game -- --balance-report > game/src/balance_report.md
+    const GOLDEN_REPORT: &str = include_str!("balance_report.md");
+
+    #[test]
+    fn report_matches_the_golden_file() {
+        let report = BalanceReport::current().to_markdown();
+        for (line, (actual, expected)) in report.lines().zip(GOLDEN_REPORT.lines()).enumerate() {
+            assert_eq!(
+                actual,
+                expected,
+                "balance changed at line {} of balance_report.md; regenerate it if the change is intended",
+                line + 1
+            );
+        }
+        assert_eq!(report.lines().count(), GOLDEN_REPORT.lines().count(), "balance_report.md is out of date");
+    }
+
+    #[test]
+    fn stat_bars_are_relative_to_the_strongest_difficulty() {
+        let report = BalanceReport::current();
+        for balance in &report.difficulties {
+            for (_, value) in report.stat_bars(balance) {
+                assert!((0.0..=1.0).contains(&value));
+            }
+        }
+        let max_ball = report.difficulties.iter().map(|balance| report.stat_bars(balance)[0].1).fold(0.0, f32::max);
+        assert_eq!(max_ball, 1.0);
+    }
+}
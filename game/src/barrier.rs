@@ -0,0 +1,74 @@
+use bevy::prelude::*;
+
+use crate::bonus::Orientation;
+use crate::countdown::Countdown;
+use crate::safe_despawn::SafeDespawnExt;
+use crate::{GameEntity, BALL_SIZE, PADDLE_Y, WINDOW_WIDTH};
+
+// 安全网持续时间，最后几秒闪烁提示即将消失
+pub const BARRIER_DURATION: f32 = 12.0;
+const WARNING_TIME: f32 = 2.0;
+const FLASHES_PER_SECOND: f32 = 4.0;
+// 安全网在挡板下方的位置和厚度（正常方向下）
+const BARRIER_Y: f32 = PADDLE_Y - 30.0;
+const BARRIER_THICKNESS: f32 = 4.0;
+const BARRIER_COLOR: Color = Color::rgba(0.4, 1.0, 0.6, 0.8);
+
+// 挡板下方的安全网：存在期间球碰到它会弹回，不会出界丢命
+#[derive(Component)]
+pub struct Barrier {
+    timer: Countdown,
+}
+
+// 拾取道具时生成安全网；已经有安全网时只刷新时间
+pub fn raise_barrier(commands: &mut Commands, barriers: &mut Query<&mut Barrier>, orientation: Orientation) {
+    if let Ok(mut barrier) = barriers.get_single_mut() {
+        barrier.timer.restart(BARRIER_DURATION);
+        return;
+    }
+    commands.spawn((
+        SpriteBundle {
+            sprite: Sprite {
+                color: BARRIER_COLOR,
+                custom_size: Some(Vec2::new(WINDOW_WIDTH, BARRIER_THICKNESS)),
+                ..default()
+            },
+            transform: Transform::from_xyz(0.0, BARRIER_Y * orientation.sign(), 0.5),
+            ..default()
+        },
+        Barrier {
+            timer: Countdown::new(BARRIER_DURATION),
+        },
+        GameEntity,
+    ));
+}
+
+// 球向挡板一侧运动并碰到安全网时弹回；坐标按正常方向给出
+pub fn bounce(position: &mut Vec2, velocity: &mut Vec2) -> bool {
+    let surface = BARRIER_Y + (BARRIER_THICKNESS + BALL_SIZE.y) / 2.0;
+    if velocity.y >= 0.0 || position.y > surface {
+        return false;
+    }
+    position.y = surface;
+    velocity.y = velocity.y.abs();
+    true
+}
+
+// 计时结束后移除安全网，最后两秒闪烁；翻转关卡时跟着挡板换到另一侧
+pub fn update_barrier(
+    mut commands: Commands,
+    time: Res<Time>,
+    orientation: Res<Orientation>,
+    mut barriers: Query<(Entity, &mut Barrier, &mut Sprite, &mut Transform)>,
+) {
+    for (entity, mut barrier, mut sprite, mut transform) in barriers.iter_mut() {
+        if barrier.timer.tick(time.delta_seconds()) {
+            commands.safe_despawn(entity);
+            continue;
+        }
+        let remaining = barrier.timer.remaining();
+        let hidden = remaining <= WARNING_TIME && ((remaining * FLASHES_PER_SECOND * 2.0) as u32).is_multiple_of(2);
+        sprite.color.set_a(if hidden { 0.15 } else { BARRIER_COLOR.a() });
+        transform.translation.y = BARRIER_Y * orientation.sign();
+    }
+}
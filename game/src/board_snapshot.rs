@@ -0,0 +1,123 @@
+use bevy::prelude::*;
+use std::collections::HashMap;
+
+use crate::hazard_rules::PlayClock;
+use crate::{brick_cell, Brick, BrickType, GameplayEvent, Layout, BRICK_COLUMNS, BRICK_ROWS};
+
+// 小地图尺寸（像素）
+const DIAGRAM_WIDTH: f32 = 200.0;
+const DIAGRAM_HEIGHT: f32 = 130.0;
+const DIAGRAM_GAP: f32 = 2.0;
+// 最后多少秒内被打碎的砖块会用边框标出
+const RECENT_WINDOW: f32 = 10.0;
+
+// 本关每个格子最近一次被打碎的时间
+#[derive(Resource, Default)]
+pub struct BrickDestructionLog {
+    destroyed_at: HashMap<(usize, usize), f32>,
+}
+
+impl BrickDestructionLog {
+    pub fn clear(&mut self) {
+        self.destroyed_at.clear();
+    }
+}
+
+// 结束时的棋盘状态
+#[derive(Resource, Default)]
+pub struct BoardSnapshot {
+    // 剩余砖块所在格子及类型
+    remaining: Vec<((usize, usize), BrickType)>,
+    // 最后几秒内被打碎的格子
+    recently_destroyed: Vec<(usize, usize)>,
+}
+
+impl BoardSnapshot {
+    // 关卡开始前的完整布局，用于关卡预览
+    pub fn from_layout(layout: &Layout) -> Self {
+        Self {
+            remaining: layout.bricks.iter().map(|spec| (spec.cell, spec.brick_type)).collect(),
+            recently_destroyed: Vec::new(),
+        }
+    }
+}
+
+// 记录砖块被打碎的时间
+pub fn record_brick_destruction(
+    clock: Res<PlayClock>,
+    mut events: EventReader<GameplayEvent>,
+    mut log: ResMut<BrickDestructionLog>,
+) {
+    for event in events.read() {
+        if let GameplayEvent::BrickDestroyed { position, .. } = event {
+            if let Some(cell) = brick_cell(*position) {
+                log.destroyed_at.insert(cell, clock.now());
+            }
+        }
+    }
+}
+
+// 在 cleanup_game 清空场景之前保存剩余砖块
+pub fn capture_board_snapshot(
+    clock: Res<PlayClock>,
+    bricks: Query<(&Brick, &Transform)>,
+    log: Res<BrickDestructionLog>,
+    mut snapshot: ResMut<BoardSnapshot>,
+) {
+    let now = clock.now();
+    snapshot.remaining = bricks
+        .iter()
+        .filter_map(|(brick, transform)| brick_cell(transform.translation).map(|cell| (cell, brick.brick_type)))
+        .collect();
+    snapshot.recently_destroyed = log
+        .destroyed_at
+        .iter()
+        .filter(|(_, destroyed_at)| now - **destroyed_at <= RECENT_WINDOW)
+        .map(|(cell, _)| *cell)
+        .collect();
+}
+
+// 用彩色方块画出缩小的棋盘，游戏结束和胜利界面都可以使用
+pub fn spawn_board_diagram(parent: &mut ChildBuilder, snapshot: &BoardSnapshot) {
+    let cell_width = DIAGRAM_WIDTH / BRICK_COLUMNS as f32;
+    let cell_height = DIAGRAM_HEIGHT / BRICK_ROWS as f32;
+
+    parent
+        .spawn(NodeBundle {
+            style: Style {
+                width: Val::Px(DIAGRAM_WIDTH),
+                height: Val::Px(DIAGRAM_HEIGHT),
+                ..default()
+            },
+            background_color: BackgroundColor(Color::rgba(0.0, 0.0, 0.0, 0.4)),
+            ..default()
+        })
+        .with_children(|parent| {
+            let cell_style = |(row, col): (usize, usize), border: f32| Style {
+                position_type: PositionType::Absolute,
+                left: Val::Px(col as f32 * cell_width + DIAGRAM_GAP / 2.0),
+                top: Val::Px(row as f32 * cell_height + DIAGRAM_GAP / 2.0),
+                width: Val::Px(cell_width - DIAGRAM_GAP),
+                height: Val::Px(cell_height - DIAGRAM_GAP),
+                border: UiRect::all(Val::Px(border)),
+                ..default()
+            };
+
+            for (cell, brick_type) in &snapshot.remaining {
+                parent.spawn(NodeBundle {
+                    style: cell_style(*cell, 0.0),
+                    background_color: BackgroundColor(brick_type.color()),
+                    ..default()
+                });
+            }
+
+            // 最后阶段打碎的砖块只画边框
+            for cell in &snapshot.recently_destroyed {
+                parent.spawn(NodeBundle {
+                    style: cell_style(*cell, 1.0),
+                    border_color: BorderColor(Color::rgb(1.0, 0.9, 0.6)),
+                    ..default()
+                });
+            }
+        });
+}
@@ -0,0 +1,115 @@
+use bevy::prelude::*;
+
+use crate::countdown::Countdown;
+use crate::explosion::{Explosion, ExplosionSource};
+use crate::fonts::{text_style, FontAssets, FontKind};
+use crate::governor::EffectBudget;
+use crate::safe_despawn::SafeDespawnExt;
+use crate::weekly_event::Fogged;
+use crate::{spawn_particles, Brick, GameplayEvent, Score, BRICK_SIZE};
+
+// 定时炸弹的倒计时
+const BOMB_FUSE: f32 = 15.0;
+// 爆炸扣的分数
+const BOMB_PENALTY: u32 = 30;
+// 爆炸的波及半径：上下左右相邻的砖块都在范围内
+const BOMB_RADIUS: f32 = BRICK_SIZE.x * 1.5;
+// 被爆炸波及打碎的砖块按正常得分的这个百分比计分，0 表示不计分
+const BOMB_CHAIN_SCORE_PERCENT: u32 = 50;
+const LABEL_COLOR: Color = Color::WHITE;
+const LABEL_SIZE: f32 = 16.0;
+
+// 定时炸弹砖的倒计时，只在 Playing 状态前进，暂停时冻结；被迷雾遮住时也不计时
+#[derive(Component)]
+pub struct BombTimer(Countdown);
+
+impl Default for BombTimer {
+    fn default() -> Self {
+        Self(Countdown::new(BOMB_FUSE))
+    }
+}
+
+// 炸弹砖上显示剩余秒数的文字（砖块的子实体）
+#[derive(Component)]
+pub struct BombLabel;
+
+// 给新生成的炸弹砖加上倒计时文字；砖块按 BRICK_SIZE 缩放，文字反向缩放保持原大小
+pub fn attach_bomb_labels(
+    mut commands: Commands,
+    fonts: Res<FontAssets>,
+    bombs: Query<(Entity, &BombTimer), Added<BombTimer>>,
+) {
+    for (entity, timer) in bombs.iter() {
+        commands.entity(entity).with_children(|parent| {
+            parent.spawn((
+                Text2dBundle {
+                    text: Text::from_section(
+                        seconds_label(timer),
+                        text_style(&fonts, FontKind::Bold, LABEL_SIZE, LABEL_COLOR),
+                    ),
+                    transform: Transform {
+                        translation: Vec3::new(0.0, 0.0, 0.2),
+                        scale: Vec3::new(1.0 / BRICK_SIZE.x, 1.0 / BRICK_SIZE.y, 1.0),
+                        ..default()
+                    },
+                    ..default()
+                },
+                BombLabel,
+            ));
+        });
+    }
+}
+
+fn seconds_label(timer: &BombTimer) -> String {
+    (timer.0.remaining().ceil() as u32).to_string()
+}
+
+// 推进炸弹倒计时并刷新文字；倒计时结束时炸弹自己爆炸：扣分、中断连击，
+// 并通过爆炸队列波及周围的砖块（波及的得分按 BOMB_CHAIN_SCORE_PERCENT 打折）
+pub fn tick_bombs(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut bombs: Query<(Entity, &mut BombTimer, &Transform, &mut Brick, &Children), Without<Fogged>>,
+    mut labels: Query<&mut Text, With<BombLabel>>,
+    mut score: ResMut<Score>,
+    budget: Res<EffectBudget>,
+    mut gameplay_events: EventWriter<GameplayEvent>,
+    mut explosions: EventWriter<Explosion>,
+) {
+    for (entity, mut timer, transform, mut brick, children) in bombs.iter_mut() {
+        let detonated = timer.0.tick(time.delta_seconds());
+        let label = seconds_label(&timer);
+        for &child in children {
+            if let Ok(mut text) = labels.get_mut(child) {
+                if text.sections[0].value != label {
+                    text.sections[0].value = label.clone();
+                }
+            }
+        }
+        if !detonated {
+            continue;
+        }
+
+        // 本帧已经被球或激光打碎的炸弹不再爆炸
+        if brick.health <= 0 {
+            continue;
+        }
+        brick.health = 0;
+        let center = transform.translation;
+        spawn_particles(&mut commands, &budget, center, transform.scale);
+        commands.safe_despawn_recursive(entity);
+
+        score.0 = score.0.saturating_sub(BOMB_PENALTY);
+        gameplay_events.send(GameplayEvent::BombDetonated {
+            position: center,
+            penalty: BOMB_PENALTY,
+        });
+        explosions.send(Explosion {
+            center,
+            radius: BOMB_RADIUS,
+            score_percent: BOMB_CHAIN_SCORE_PERCENT,
+            combo: false,
+            source: ExplosionSource::Bomb,
+        });
+    }
+}
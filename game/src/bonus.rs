@@ -0,0 +1,202 @@
+use bevy::prelude::*;
+
+use crate::countdown::Countdown;
+use crate::fonts::{text_style, FontAssets, FontKind};
+use crate::i18n::Locale;
+use crate::{BrickSpec, BrickType, GameEntity, GameState, Layout, BRICK_COLUMNS, PADDLE_Y};
+
+// 每打完这么多关进入一次奖励关
+const BONUS_EVERY: u32 = 3;
+// 奖励关的时长（秒）
+const BONUS_DURATION: f32 = 30.0;
+// 奖励关砖块的分数倍数
+const BONUS_MULTIPLIER: u32 = 2;
+// 奖励关的砖块行数
+const BONUS_ROWS: usize = 4;
+
+const BONUS_COLOR: Color = Color::rgb(1.0, 0.85, 0.2);
+
+// 场地方向：翻转时挡板在上、砖块在下，球向上"落"出场外。
+// 与方向有关的位置和速度都在正常方向下计算，再用 apply 转换到当前方向
+#[derive(Resource, Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Orientation {
+    #[default]
+    Normal,
+    Flipped,
+}
+
+impl Orientation {
+    // 正常方向为 1，翻转为 -1
+    pub fn sign(self) -> f32 {
+        match self {
+            Orientation::Normal => 1.0,
+            Orientation::Flipped => -1.0,
+        }
+    }
+
+    // 在正常方向和当前方向之间转换；翻转是它自己的逆变换，两个方向都用这一个函数
+    pub fn apply(self, v: Vec2) -> Vec2 {
+        Vec2::new(v.x, v.y * self.sign())
+    }
+
+    // 挡板的高度
+    pub fn paddle_y(self) -> f32 {
+        PADDLE_Y * self.sign()
+    }
+}
+
+// 奖励关：上下翻转、限时、不会丢命、砖块双倍分数，时间到后自动进入下一关
+#[derive(Resource, Debug, Default)]
+pub struct BonusStage {
+    active: bool,
+    timer: Countdown,
+}
+
+impl BonusStage {
+    pub fn is_active(&self) -> bool {
+        self.active
+    }
+
+    // 打完第 level 关后是否进入奖励关
+    pub fn due_after(level: u32) -> bool {
+        level > 0 && level.is_multiple_of(BONUS_EVERY)
+    }
+
+    pub fn start(&mut self) {
+        self.active = true;
+        self.timer.restart(BONUS_DURATION);
+    }
+
+    pub fn finish(&mut self) {
+        self.active = false;
+    }
+
+    // 砖块分数的倍数
+    pub fn score_multiplier(&self) -> u32 {
+        if self.active {
+            BONUS_MULTIPLIER
+        } else {
+            1
+        }
+    }
+}
+
+pub fn bonus_active(bonus: Res<BonusStage>) -> bool {
+    bonus.active
+}
+
+pub fn bonus_inactive(bonus: Res<BonusStage>) -> bool {
+    !bonus.active
+}
+
+// 奖励关的布局：几行普通砖块，没有保底道具
+pub fn bonus_layout() -> Layout {
+    let bricks = (0..BONUS_ROWS)
+        .flat_map(|row| (0..BRICK_COLUMNS).map(move |col| (row, col)))
+        .map(|cell| BrickSpec {
+            cell,
+            brick_type: BrickType::Normal,
+            health: 1,
+            armor: None,
+            guaranteed_drop: None,
+            golden: false,
+            generator: None,
+        })
+        .collect();
+    Layout { bricks, ..default() }
+}
+
+// 奖励关的提示文字和剩余时间条
+#[derive(Component)]
+pub struct BonusBannerText;
+
+#[derive(Component)]
+pub struct BonusTimerBar;
+
+// 提示和时间条放在砖块一侧的屏幕边缘，与状态栏同侧；翻转时状态栏在底部
+pub fn spawn_bonus_hud(commands: &mut Commands, fonts: &FontAssets, orientation: Orientation) {
+    let edge = Val::Px(50.0);
+    let (top, bottom) = match orientation {
+        Orientation::Normal => (edge, Val::Auto),
+        Orientation::Flipped => (Val::Auto, edge),
+    };
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    width: Val::Percent(100.0),
+                    top,
+                    bottom,
+                    flex_direction: match orientation {
+                        Orientation::Normal => FlexDirection::Column,
+                        Orientation::Flipped => FlexDirection::ColumnReverse,
+                    },
+                    align_items: AlignItems::Center,
+                    row_gap: Val::Px(4.0),
+                    ..default()
+                },
+                ..default()
+            },
+            GameEntity,
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                TextBundle::from_section("", text_style(fonts, FontKind::Bold, 24.0, BONUS_COLOR)),
+                BonusBannerText,
+            ));
+            parent
+                .spawn(NodeBundle {
+                    style: Style {
+                        width: Val::Percent(60.0),
+                        height: Val::Px(6.0),
+                        ..default()
+                    },
+                    background_color: BackgroundColor(Color::rgba(1.0, 1.0, 1.0, 0.15)),
+                    ..default()
+                })
+                .with_children(|parent| {
+                    parent.spawn((
+                        NodeBundle {
+                            style: Style {
+                                width: Val::Percent(100.0),
+                                height: Val::Percent(100.0),
+                                ..default()
+                            },
+                            background_color: BackgroundColor(BONUS_COLOR),
+                            ..default()
+                        },
+                        BonusTimerBar,
+                    ));
+                });
+        });
+}
+
+// 奖励关计时；时间到后直接进入下一关，不显示胜利界面
+pub fn tick_bonus_stage(
+    time: Res<Time>,
+    mut bonus: ResMut<BonusStage>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    if bonus.timer.tick(time.delta_seconds()) && next_state.0.is_none() {
+        next_state.set(GameState::NextLevel);
+    }
+}
+
+pub fn update_bonus_hud(
+    bonus: Res<BonusStage>,
+    locale: Res<Locale>,
+    mut banners: Query<&mut Text, With<BonusBannerText>>,
+    mut bars: Query<&mut Style, With<BonusTimerBar>>,
+) {
+    let remaining = bonus.timer.remaining();
+    for mut text in banners.iter_mut() {
+        text.sections[0].value = locale.tr(
+            "bonus.banner",
+            &[("multiplier", &BONUS_MULTIPLIER), ("seconds", &(remaining.ceil() as u32))],
+        );
+    }
+    for mut style in bars.iter_mut() {
+        style.width = Val::Percent(remaining / BONUS_DURATION * 100.0);
+    }
+}
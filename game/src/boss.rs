@@ -0,0 +1,268 @@
+use bevy::prelude::*;
+
+use crate::bonus::Orientation;
+use crate::countdown::Countdown;
+use crate::curse::Curse;
+use crate::fonts::{text_style, FontAssets, FontKind};
+use crate::governor::EffectBudget;
+use crate::i18n::Locale;
+use crate::launch::HeldBall;
+use crate::safe_despawn::SafeDespawnExt;
+use crate::seasonal::SeasonalTheme;
+use crate::sticky::StuckBall;
+use crate::weekly_event::EventRun;
+use crate::{
+    brick_cell, brick_position, collide, spawn_bricks, spawn_particles, Ball, Brick, BrickSpec, BrickType, Collision,
+    GameEntity, GameplayEvent, Laser, Layout, Score, BALL_SIZE, BRICK_COLUMNS, LASER_SIZE, WINDOW_WIDTH,
+};
+
+// 每打完这么多关迎战一次首领
+const BOSS_EVERY: u32 = 5;
+// 普通局一共迎战几个首领，打败最后一个就通关
+const CAMPAIGN_BOSSES: u32 = 2;
+// 最后一个首领出现在这一关之后
+pub const FINAL_BOSS_LEVEL: u32 = BOSS_EVERY * CAMPAIGN_BOSSES;
+const BOSS_HEALTH: i32 = 50;
+const BOSS_SIZE: Vec2 = Vec2::new(220.0, 40.0);
+// 首领在砖块区上方左右移动
+const BOSS_Y: f32 = 255.0;
+const BOSS_SPEED: f32 = 80.0;
+// 首领离两侧墙壁的距离
+const BOSS_MARGIN: f32 = 20.0;
+// 每隔多久在正下方补一排普通砖块，补在第几行
+const BOSS_SPAWN_INTERVAL: f32 = 8.0;
+const BOSS_BRICK_ROW: usize = 0;
+// 激光对首领的伤害，和打砖块一样比球高
+const LASER_DAMAGE: i32 = 2;
+// 击败首领的奖励分数
+const BOSS_DEFEAT_BONUS: u32 = 2000;
+
+const BOSS_COLOR: Color = Color::rgb(0.6, 0.2, 0.8);
+const HEALTH_BAR_COLOR: Color = Color::rgb(0.9, 0.25, 0.3);
+
+// 首领关：每打完 BOSS_EVERY 关进入，关卡数不变；没有砖块墙，打败首领才算过关
+#[derive(Resource, Debug, Default)]
+pub struct BossStage {
+    active: bool,
+}
+
+impl BossStage {
+    pub fn is_active(&self) -> bool {
+        self.active
+    }
+
+    // 打完第 level 关后是否迎战首领
+    pub fn due_after(level: u32) -> bool {
+        level > 0 && level.is_multiple_of(BOSS_EVERY)
+    }
+
+    pub fn start(&mut self) {
+        self.active = true;
+    }
+
+    pub fn finish(&mut self) {
+        self.active = false;
+    }
+}
+
+pub fn boss_active(boss: Res<BossStage>) -> bool {
+    boss.active
+}
+
+pub fn boss_inactive(boss: Res<BossStage>) -> bool {
+    !boss.active
+}
+
+#[derive(Component)]
+pub struct Boss {
+    health: i32,
+    // 1.0 向右，-1.0 向左
+    direction: f32,
+    spawn_timer: Countdown,
+}
+
+// 界面顶部血条的填充部分
+#[derive(Component)]
+pub struct BossHealthBar;
+
+// 生成首领和顶部的血条
+pub fn spawn_boss(commands: &mut Commands, fonts: &FontAssets, locale: &Locale) {
+    commands.spawn((
+        SpriteBundle {
+            sprite: Sprite {
+                color: BOSS_COLOR,
+                ..default()
+            },
+            transform: Transform {
+                translation: Vec3::new(0.0, BOSS_Y, 0.0),
+                scale: BOSS_SIZE.extend(1.0),
+                ..default()
+            },
+            ..default()
+        },
+        Boss {
+            health: BOSS_HEALTH,
+            direction: 1.0,
+            spawn_timer: Countdown::new(BOSS_SPAWN_INTERVAL),
+        },
+        GameEntity,
+    ));
+
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    width: Val::Percent(100.0),
+                    top: Val::Px(50.0),
+                    flex_direction: FlexDirection::Column,
+                    align_items: AlignItems::Center,
+                    row_gap: Val::Px(4.0),
+                    ..default()
+                },
+                ..default()
+            },
+            GameEntity,
+        ))
+        .with_children(|parent| {
+            parent.spawn(TextBundle::from_section(
+                locale.tr("boss.title", &[]),
+                text_style(fonts, FontKind::Bold, 24.0, HEALTH_BAR_COLOR),
+            ));
+            parent
+                .spawn(NodeBundle {
+                    style: Style {
+                        width: Val::Percent(60.0),
+                        height: Val::Px(8.0),
+                        ..default()
+                    },
+                    background_color: BackgroundColor(Color::rgba(1.0, 1.0, 1.0, 0.15)),
+                    ..default()
+                })
+                .with_children(|parent| {
+                    parent.spawn((
+                        NodeBundle {
+                            style: Style {
+                                width: Val::Percent(100.0),
+                                height: Val::Percent(100.0),
+                                ..default()
+                            },
+                            background_color: BackgroundColor(HEALTH_BAR_COLOR),
+                            ..default()
+                        },
+                        BossHealthBar,
+                    ));
+                });
+        });
+}
+
+// 首领在两侧墙壁之间来回移动，每隔一段时间在自己正下方的空格里补一排普通砖块
+pub fn move_boss(
+    mut commands: Commands,
+    time: Res<Time>,
+    event_run: Res<EventRun>,
+    theme: Res<SeasonalTheme>,
+    mut bosses: Query<(&mut Boss, &mut Transform)>,
+    bricks: Query<&Transform, (With<Brick>, Without<Boss>)>,
+) {
+    let limit = WINDOW_WIDTH / 2.0 - BOSS_SIZE.x / 2.0 - BOSS_MARGIN;
+    for (mut boss, mut transform) in bosses.iter_mut() {
+        transform.translation.x += boss.direction * BOSS_SPEED * time.delta_seconds();
+        if transform.translation.x.abs() >= limit {
+            transform.translation.x = transform.translation.x.clamp(-limit, limit);
+            boss.direction = -transform.translation.x.signum();
+        }
+
+        if !boss.spawn_timer.tick(time.delta_seconds()) {
+            continue;
+        }
+        boss.spawn_timer.restart(BOSS_SPAWN_INTERVAL);
+        let occupied: Vec<(usize, usize)> = bricks.iter().filter_map(|brick| brick_cell(brick.translation)).collect();
+        let bricks = (0..BRICK_COLUMNS)
+            .map(|col| (BOSS_BRICK_ROW, col))
+            .filter(|cell| (brick_position(cell.0, cell.1).x - transform.translation.x).abs() <= BOSS_SIZE.x / 2.0)
+            .filter(|cell| !occupied.contains(cell))
+            .map(|cell| BrickSpec {
+                cell,
+                brick_type: BrickType::Normal,
+                health: 1,
+                armor: None,
+                guaranteed_drop: None,
+                golden: false,
+                generator: None,
+            })
+            .collect();
+        spawn_bricks(&mut commands, &Layout { bricks, ..default() }, Orientation::Normal, &event_run, &theme);
+    }
+}
+
+// 球和激光打中首领：球从被击中的一面弹开，激光消失。
+// 血量归零时首领消失并给出奖励分数，check_victory 据此过关
+pub fn boss_hits(
+    mut commands: Commands,
+    mut balls: Query<(&Transform, &mut Ball), (Without<HeldBall>, Without<StuckBall>, Without<Boss>)>,
+    lasers: Query<(Entity, &Transform), (With<Laser>, Without<Boss>)>,
+    mut bosses: Query<(Entity, &Transform, &mut Boss)>,
+    mut score: ResMut<Score>,
+    curse: Res<Curse>,
+    budget: Res<EffectBudget>,
+    mut gameplay_events: EventWriter<GameplayEvent>,
+) {
+    for (boss_entity, boss_transform, mut boss) in bosses.iter_mut() {
+        if boss.health <= 0 {
+            continue;
+        }
+        let position = boss_transform.translation;
+        let mut damage = 0;
+        for (ball_transform, mut ball) in balls.iter_mut() {
+            let Some(collision) = collide(ball_transform.translation, BALL_SIZE, position, BOSS_SIZE) else {
+                continue;
+            };
+            // 弹开后的几帧球可能还和首领重叠，这时已经在离开，不再算一次击中
+            let approaching = match collision {
+                Collision::Left => ball.velocity.x < 0.0,
+                Collision::Right => ball.velocity.x > 0.0,
+                Collision::Top => ball.velocity.y < 0.0,
+                Collision::Bottom => ball.velocity.y > 0.0,
+            };
+            if !approaching {
+                continue;
+            }
+            match collision {
+                Collision::Left | Collision::Right => ball.velocity.x = -ball.velocity.x,
+                Collision::Top | Collision::Bottom => ball.velocity.y = -ball.velocity.y,
+            }
+            damage += 1;
+        }
+        for (laser_entity, laser_transform) in lasers.iter() {
+            if collide(laser_transform.translation, LASER_SIZE, position, BOSS_SIZE).is_some() {
+                commands.safe_despawn(laser_entity);
+                damage += LASER_DAMAGE;
+            }
+        }
+        if damage == 0 {
+            continue;
+        }
+
+        boss.health -= damage;
+        if boss.health > 0 {
+            gameplay_events.send(GameplayEvent::BrickDamaged { position });
+            continue;
+        }
+        commands.safe_despawn_recursive(boss_entity);
+        let bonus = curse.apply(BOSS_DEFEAT_BONUS);
+        score.0 += bonus;
+        spawn_particles(&mut commands, &budget, position, boss_transform.scale);
+        gameplay_events.send(GameplayEvent::BossDefeated { position, bonus });
+    }
+}
+
+pub fn update_boss_health_bar(bosses: Query<&Boss>, mut bars: Query<&mut Style, With<BossHealthBar>>) {
+    let health = bosses.iter().map(|boss| boss.health.max(0)).sum::<i32>();
+    let width = Val::Percent(health as f32 / BOSS_HEALTH as f32 * 100.0);
+    for mut style in bars.iter_mut() {
+        if style.width != width {
+            style.width = width;
+        }
+    }
+}
@@ -0,0 +1,395 @@
+use bevy::input::mouse::MouseWheel;
+use bevy::prelude::*;
+use serde::Deserialize;
+
+use crate::fonts::{text_style, FontAssets, FontKind};
+use crate::i18n::Locale;
+use crate::profile::Profile;
+use crate::profiles::ProfilePaths;
+use crate::{read_scroll_input, GameState};
+
+// 编译时嵌入的更新日志
+const CHANGELOG_JSON: &str = include_str!("../assets/changelog.json");
+// 当前游戏版本
+pub const CURRENT_VERSION: &str = env!("CARGO_PKG_VERSION");
+
+// 语义化版本号 major.minor.patch
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Version(u32, u32, u32);
+
+impl Version {
+    // 只接受三段数字，预发布等后缀视为无效
+    pub fn parse(text: &str) -> Option<Self> {
+        let mut parts = text.trim().split('.').map(|part| part.parse::<u32>().ok());
+        let version = Version(parts.next()??, parts.next()??, parts.next()??);
+        if parts.next().is_some() {
+            return None;
+        }
+        Some(version)
+    }
+}
+
+// 更新条目的类别
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChangeKind {
+    Added,
+    Changed,
+    Fixed,
+}
+
+impl ChangeKind {
+    fn parse(text: &str) -> Option<Self> {
+        match text {
+            "added" => Some(ChangeKind::Added),
+            "changed" => Some(ChangeKind::Changed),
+            "fixed" => Some(ChangeKind::Fixed),
+            _ => None,
+        }
+    }
+
+    fn label_key(&self) -> &'static str {
+        match self {
+            ChangeKind::Added => "changelog.added",
+            ChangeKind::Changed => "changelog.changed",
+            ChangeKind::Fixed => "changelog.fixed",
+        }
+    }
+
+    fn color(&self) -> Color {
+        match self {
+            ChangeKind::Added => Color::rgb(0.4, 0.9, 0.4),
+            ChangeKind::Changed => Color::rgb(0.5, 0.7, 0.9),
+            ChangeKind::Fixed => Color::rgb(1.0, 0.85, 0.2),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ChangelogEntry {
+    pub kind: ChangeKind,
+    pub text: String,
+}
+
+#[derive(Debug, Clone)]
+pub struct ChangelogRelease {
+    pub version: Version,
+    pub label: String,
+    pub entries: Vec<ChangelogEntry>,
+}
+
+// 文件中的原始格式，字段先按字符串读取再逐条校验
+#[derive(Deserialize)]
+struct RawChangelog {
+    versions: Vec<RawRelease>,
+}
+
+#[derive(Deserialize)]
+struct RawRelease {
+    version: String,
+    #[serde(default)]
+    entries: Vec<RawEntry>,
+}
+
+#[derive(Deserialize)]
+struct RawEntry {
+    kind: String,
+    text: String,
+}
+
+// 解析更新日志，无效的版本和条目跳过并输出日志，结果按版本从新到旧排列
+pub fn parse_changelog(content: &str) -> Vec<ChangelogRelease> {
+    let raw: RawChangelog = match serde_json::from_str(content) {
+        Ok(raw) => raw,
+        Err(e) => {
+            eprintln!("Failed to parse changelog: {}", e);
+            return Vec::new();
+        }
+    };
+
+    let mut releases: Vec<ChangelogRelease> = raw
+        .versions
+        .into_iter()
+        .filter_map(|release| {
+            let Some(version) = Version::parse(&release.version) else {
+                eprintln!("Skipping changelog release with invalid version '{}'", release.version);
+                return None;
+            };
+            let entries = release
+                .entries
+                .into_iter()
+                .filter_map(|entry| match ChangeKind::parse(&entry.kind) {
+                    Some(kind) if !entry.text.trim().is_empty() => Some(ChangelogEntry { kind, text: entry.text }),
+                    _ => {
+                        eprintln!("Skipping changelog entry of kind '{}' in {}", entry.kind, release.version);
+                        None
+                    }
+                })
+                .collect();
+            Some(ChangelogRelease {
+                version,
+                label: release.version,
+                entries,
+            })
+        })
+        .collect();
+    releases.sort_by_key(|release| std::cmp::Reverse(release.version));
+    releases
+}
+
+// 当前版本比上次看过的版本新时显示提示（从未看过也算）
+pub fn has_unseen_changes(last_seen: Option<&str>) -> bool {
+    let Some(current) = Version::parse(CURRENT_VERSION) else {
+        return false;
+    };
+    match last_seen.and_then(Version::parse) {
+        Some(seen) => current > seen,
+        None => true,
+    }
+}
+
+// 启动时解析的更新日志
+#[derive(Resource)]
+pub struct Changelog {
+    pub releases: Vec<ChangelogRelease>,
+}
+
+impl Default for Changelog {
+    fn default() -> Self {
+        Self {
+            releases: parse_changelog(CHANGELOG_JSON),
+        }
+    }
+}
+
+#[derive(Component)]
+pub struct ChangelogUI;
+
+// 更新日志中可滚动的内容列表
+#[derive(Component, Default)]
+pub struct ChangelogScrollList {
+    position: f32,
+}
+
+// 更新日志界面；打开后记录为已查看
+pub fn setup_changelog_screen(
+    mut commands: Commands,
+    changelog: Res<Changelog>,
+    paths: Res<ProfilePaths>,
+    fonts: Res<FontAssets>,
+    locale: Res<Locale>,
+) {
+    let mut profile = Profile::load(&paths);
+    if profile.last_seen_version.as_deref() != Some(CURRENT_VERSION) {
+        profile.last_seen_version = Some(CURRENT_VERSION.to_string());
+        if let Err(e) = profile.save(&paths) {
+            eprintln!("Failed to save profile: {}", e);
+        }
+    }
+
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    width: Val::Percent(100.0),
+                    height: Val::Percent(100.0),
+                    flex_direction: FlexDirection::Column,
+                    justify_content: JustifyContent::Center,
+                    align_items: AlignItems::Center,
+                    ..default()
+                },
+                background_color: BackgroundColor(Color::rgb(0.1, 0.1, 0.15)),
+                ..default()
+            },
+            ChangelogUI,
+        ))
+        .with_children(|parent| {
+            parent.spawn(TextBundle::from_section(
+                locale.tr("changelog.title", &[]),
+                text_style(&fonts, FontKind::Bold, 60.0, Color::WHITE),
+            ));
+
+            // 裁剪区域，内部列表通过 top 偏移滚动
+            parent
+                .spawn(NodeBundle {
+                    style: Style {
+                        width: Val::Percent(70.0),
+                        height: Val::Percent(65.0),
+                        margin: UiRect::top(Val::Px(20.0)),
+                        overflow: Overflow::clip(),
+                        ..default()
+                    },
+                    background_color: BackgroundColor(Color::rgba(0.2, 0.2, 0.25, 0.8)),
+                    ..default()
+                })
+                .with_children(|parent| {
+                    parent
+                        .spawn((
+                            NodeBundle {
+                                style: Style {
+                                    width: Val::Percent(100.0),
+                                    flex_direction: FlexDirection::Column,
+                                    padding: UiRect::all(Val::Px(15.0)),
+                                    row_gap: Val::Px(6.0),
+                                    ..default()
+                                },
+                                ..default()
+                            },
+                            ChangelogScrollList::default(),
+                        ))
+                        .with_children(|parent| {
+                            if changelog.releases.is_empty() {
+                                parent.spawn(TextBundle::from_section(
+                                    locale.tr("changelog.empty", &[]),
+                                    text_style(&fonts, FontKind::Regular, 20.0, Color::rgb(0.5, 0.5, 0.5)),
+                                ));
+                            }
+
+                            for release in &changelog.releases {
+                                let title_key = if release.label == CURRENT_VERSION {
+                                    "changelog.version_current"
+                                } else {
+                                    "changelog.version"
+                                };
+                                let title = locale.tr(title_key, &[("version", &release.label)]);
+                                parent.spawn(TextBundle::from_section(
+                                    title,
+                                    text_style(&fonts, FontKind::Bold, 26.0, Color::rgb(1.0, 0.85, 0.2)),
+                                ).with_style(Style {
+                                    margin: UiRect::top(Val::Px(10.0)),
+                                    ..default()
+                                }));
+
+                                for entry in &release.entries {
+                                    parent
+                                        .spawn(NodeBundle {
+                                            style: Style {
+                                                column_gap: Val::Px(10.0),
+                                                ..default()
+                                            },
+                                            ..default()
+                                        })
+                                        .with_children(|parent| {
+                                            parent.spawn(TextBundle::from_section(
+                                                locale.tr(entry.kind.label_key(), &[]),
+                                                text_style(&fonts, FontKind::Mono, 16.0, entry.kind.color()),
+                                            ).with_style(Style {
+                                                width: Val::Px(80.0),
+                                                ..default()
+                                            }));
+                                            parent.spawn(TextBundle::from_section(
+                                                entry.text.clone(),
+                                                text_style(&fonts, FontKind::Regular, 18.0, Color::WHITE),
+                                            ));
+                                        });
+                                }
+                            }
+                        });
+                });
+
+            parent.spawn(TextBundle::from_section(
+                locale.tr("changelog.hint", &[]),
+                text_style(&fonts, FontKind::Regular, 20.0, Color::rgb(0.6, 0.6, 0.6)),
+            ).with_style(Style {
+                margin: UiRect::top(Val::Px(20.0)),
+                ..default()
+            }));
+        });
+}
+
+// 更新日志界面系统：滚动与返回
+pub fn changelog_screen_system(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    mut mouse_wheel: EventReader<MouseWheel>,
+    time: Res<Time>,
+    mut next_state: ResMut<NextState<GameState>>,
+    mut list_query: Query<(&mut ChangelogScrollList, &mut Style, &Parent, &Node)>,
+    node_query: Query<&Node>,
+) {
+    if keyboard_input.just_pressed(KeyCode::Escape) || keyboard_input.just_pressed(KeyCode::Space) {
+        next_state.set(GameState::MainMenu);
+        return;
+    }
+
+    let delta = read_scroll_input(&keyboard_input, &mut mouse_wheel, &time);
+    for (mut list, mut style, parent, list_node) in list_query.iter_mut() {
+        let visible_height = node_query.get(parent.get()).map(|node| node.size().y).unwrap_or(0.0);
+        let max_scroll = (list_node.size().y - visible_height).max(0.0);
+        list.position = (list.position + delta).clamp(-max_scroll, 0.0);
+        style.top = Val::Px(list.position);
+    }
+}
+
+// 清理更新日志界面
+pub fn cleanup_changelog_screen(mut commands: Commands, query: Query<Entity, With<ChangelogUI>>) {
+    for entity in query.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn version_parse_accepts_only_three_numbers() {
+        assert_eq!(Version::parse("0.4.0"), Some(Version(0, 4, 0)));
+        assert_eq!(Version::parse(" 1.10.2 "), Some(Version(1, 10, 2)));
+        assert_eq!(Version::parse("1.2"), None);
+        assert_eq!(Version::parse("1.2.3.4"), None);
+        assert_eq!(Version::parse("1.2.3-beta"), None);
+        assert_eq!(Version::parse(""), None);
+    }
+
+    #[test]
+    fn versions_compare_numerically_not_as_text() {
+        let parse = |text| Version::parse(text).unwrap();
+        assert!(parse("0.10.0") > parse("0.9.9"));
+        assert!(parse("1.0.0") > parse("0.99.99"));
+        assert!(parse("0.4.1") > parse("0.4.0"));
+    }
+
+    #[test]
+    fn invalid_releases_and_entries_are_skipped() {
+        let releases = parse_changelog(
+            r#"{ "versions": [
+                { "version": "0.3.0", "entries": [
+                    { "kind": "added", "text": "Boss fights" },
+                    { "kind": "removed", "text": "Unknown kind" },
+                    { "kind": "fixed", "text": "   " }
+                ] },
+                { "version": "next", "entries": [{ "kind": "added", "text": "Dropped" }] },
+                { "version": "0.10.0" }
+            ] }"#,
+        );
+        assert_eq!(releases.len(), 2);
+        // 按版本从新到旧
+        assert_eq!(releases[0].label, "0.10.0");
+        assert!(releases[0].entries.is_empty());
+        assert_eq!(releases[1].entries.len(), 1);
+        assert_eq!(releases[1].entries[0].kind, ChangeKind::Added);
+        assert_eq!(releases[1].entries[0].text, "Boss fights");
+    }
+
+    #[test]
+    fn malformed_file_gives_empty_changelog() {
+        assert!(parse_changelog("not json").is_empty());
+        assert!(parse_changelog(r#"{ "releases": [] }"#).is_empty());
+    }
+
+    #[test]
+    fn shipped_changelog_has_the_current_version() {
+        let releases = parse_changelog(CHANGELOG_JSON);
+        assert_eq!(releases.first().map(|release| release.label.as_str()), Some(CURRENT_VERSION));
+    }
+
+    #[test]
+    fn badge_shows_until_the_current_version_is_seen() {
+        assert!(has_unseen_changes(None));
+        assert!(has_unseen_changes(Some("0.0.1")));
+        // 存档里的版本无效时按从未看过处理
+        assert!(has_unseen_changes(Some("garbage")));
+        assert!(!has_unseen_changes(Some(CURRENT_VERSION)));
+        // 降级到旧版本后不再提示
+        assert!(!has_unseen_changes(Some("999.0.0")));
+    }
+}
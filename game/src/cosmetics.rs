@@ -0,0 +1,458 @@
+use bevy::ecs::system::EntityCommands;
+use bevy::prelude::*;
+
+use crate::fonts::{text_style, FontAssets, FontKind};
+use crate::i18n::{name_key, Locale};
+use crate::profile::Profile;
+use crate::profiles::ProfilePaths;
+use crate::{GameState, BALL_COLOR, PADDLE_COLOR};
+
+// 花纹由几条横向色带组成
+const BANDS: usize = 4;
+const LOCKED_ALPHA: f32 = 0.25;
+
+// 外观适用的对象
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CosmeticSlot {
+    Ball,
+    Paddle,
+}
+
+impl CosmeticSlot {
+    const ALL: [CosmeticSlot; 2] = [CosmeticSlot::Ball, CosmeticSlot::Paddle];
+
+    fn label_key(&self) -> &'static str {
+        match self {
+            CosmeticSlot::Ball => "cosmetics.ball",
+            CosmeticSlot::Paddle => "cosmetics.paddle",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub enum SkinPattern {
+    Solid,
+    // 与底色交替的条纹
+    Stripes(Color),
+    // 从底色（下）渐变到另一种颜色（上）
+    Gradient(Color),
+}
+
+// 解锁条件，按本地档案的终身统计判断
+#[derive(Debug, Clone, Copy)]
+pub enum Unlock {
+    Default,
+    Runs(u32),
+    Bricks(u32),
+    Combo(u32),
+    Level(u32),
+    PerfectHits(u32),
+    DebuffsWarded(u32),
+}
+
+impl Unlock {
+    pub fn is_met(&self, profile: &Profile) -> bool {
+        match *self {
+            Unlock::Default => true,
+            Unlock::Runs(count) => profile.total_runs >= count,
+            Unlock::Bricks(count) => profile.bricks_destroyed.values().sum::<u32>() >= count,
+            Unlock::Combo(count) => profile.best_combo >= count,
+            Unlock::Level(level) => profile.game_over_levels.keys().any(|reached| *reached >= level),
+            Unlock::PerfectHits(count) => profile.perfect_hits >= count,
+            Unlock::DebuffsWarded(count) => profile.debuffs_warded >= count,
+        }
+    }
+
+    fn describe(&self, locale: &Locale) -> String {
+        match self {
+            Unlock::Default => String::new(),
+            Unlock::Runs(count) => locale.tr("unlock.runs", &[("count", count)]),
+            Unlock::Bricks(count) => locale.tr("unlock.bricks", &[("count", count)]),
+            Unlock::Combo(count) => locale.tr("unlock.combo", &[("count", count)]),
+            Unlock::Level(level) => locale.tr("unlock.level", &[("level", level)]),
+            Unlock::PerfectHits(count) => locale.tr("unlock.perfect_hits", &[("count", count)]),
+            Unlock::DebuffsWarded(count) => locale.tr("unlock.debuffs_warded", &[("count", count)]),
+        }
+    }
+}
+
+pub struct Skin {
+    pub id: &'static str,
+    pub slot: CosmeticSlot,
+    pub base: Color,
+    pub pattern: SkinPattern,
+    pub unlock: Unlock,
+}
+
+// 所有外观；每个对象的第一项是默认外观
+pub const SKINS: &[Skin] = &[
+    Skin {
+        id: "ball_classic",
+        slot: CosmeticSlot::Ball,
+        base: BALL_COLOR,
+        pattern: SkinPattern::Solid,
+        unlock: Unlock::Default,
+    },
+    Skin {
+        id: "ember",
+        slot: CosmeticSlot::Ball,
+        base: Color::rgb(1.0, 0.45, 0.15),
+        pattern: SkinPattern::Solid,
+        unlock: Unlock::Runs(1),
+    },
+    Skin {
+        id: "sunset",
+        slot: CosmeticSlot::Ball,
+        base: Color::rgb(0.95, 0.3, 0.45),
+        pattern: SkinPattern::Gradient(Color::rgb(1.0, 0.85, 0.3)),
+        unlock: Unlock::Combo(10),
+    },
+    Skin {
+        id: "paddle_classic",
+        slot: CosmeticSlot::Paddle,
+        base: PADDLE_COLOR,
+        pattern: SkinPattern::Solid,
+        unlock: Unlock::Default,
+    },
+    Skin {
+        id: "mint",
+        slot: CosmeticSlot::Paddle,
+        base: Color::rgb(0.3, 0.85, 0.6),
+        pattern: SkinPattern::Stripes(Color::rgb(0.85, 1.0, 0.9)),
+        unlock: Unlock::Bricks(100),
+    },
+    Skin {
+        id: "royal",
+        slot: CosmeticSlot::Paddle,
+        base: Color::rgb(0.35, 0.2, 0.7),
+        pattern: SkinPattern::Gradient(Color::rgb(0.95, 0.75, 0.2)),
+        unlock: Unlock::Level(5),
+    },
+    Skin {
+        id: "gilded",
+        slot: CosmeticSlot::Paddle,
+        base: Color::rgb(0.85, 0.65, 0.15),
+        pattern: SkinPattern::Stripes(Color::rgb(1.0, 0.9, 0.5)),
+        unlock: Unlock::PerfectHits(25),
+    },
+    Skin {
+        id: "aegis",
+        slot: CosmeticSlot::Paddle,
+        base: Color::rgb(0.35, 0.55, 0.8),
+        pattern: SkinPattern::Gradient(Color::rgb(0.75, 0.9, 1.0)),
+        unlock: Unlock::DebuffsWarded(5),
+    },
+];
+
+fn mix(from: Color, to: Color, t: f32) -> Color {
+    let [r1, g1, b1, a1] = from.as_rgba_f32();
+    let [r2, g2, b2, a2] = to.as_rgba_f32();
+    Color::rgba(r1 + (r2 - r1) * t, g1 + (g2 - g1) * t, b1 + (b2 - b1) * t, a1 + (a2 - a1) * t)
+}
+
+impl Skin {
+    // 从下到上每条色带的颜色，纯色外观返回 None
+    fn bands(&self) -> Option<[Color; BANDS]> {
+        let mut bands = [self.base; BANDS];
+        match self.pattern {
+            SkinPattern::Solid => return None,
+            SkinPattern::Stripes(stripe) => {
+                for band in bands.iter_mut().skip(1).step_by(2) {
+                    *band = stripe;
+                }
+            }
+            SkinPattern::Gradient(top) => {
+                for (index, band) in bands.iter_mut().enumerate() {
+                    *band = mix(self.base, top, index as f32 / (BANDS - 1) as f32);
+                }
+            }
+        }
+        Some(bands)
+    }
+
+    // 给已生成的球或挡板加上花纹色带（子精灵随父实体缩放和旋转）
+    pub fn decorate(&self, entity: &mut EntityCommands) {
+        let Some(bands) = self.bands() else {
+            return;
+        };
+        entity.with_children(|parent| {
+            for (index, color) in bands.into_iter().enumerate() {
+                let height = 1.0 / BANDS as f32;
+                parent.spawn(SpriteBundle {
+                    sprite: Sprite { color, ..default() },
+                    transform: Transform {
+                        translation: Vec3::new(0.0, -0.5 + height * (index as f32 + 0.5), 0.1),
+                        scale: Vec3::new(1.0, height, 1.0),
+                        ..default()
+                    },
+                    ..default()
+                });
+            }
+        });
+    }
+}
+
+fn skin_by_id(id: Option<&str>, slot: CosmeticSlot, profile: &Profile) -> &'static Skin {
+    SKINS
+        .iter()
+        .find(|skin| skin.slot == slot && Some(skin.id) == id && skin.unlock.is_met(profile))
+        .unwrap_or_else(|| default_skin(slot))
+}
+
+fn default_skin(slot: CosmeticSlot) -> &'static Skin {
+    SKINS.iter().find(|skin| skin.slot == slot).expect("every slot has a default skin")
+}
+
+// 当前档案选择的外观；未解锁或不存在的选择回退到默认外观
+#[derive(Resource)]
+pub struct Cosmetics {
+    pub ball: &'static Skin,
+    pub paddle: &'static Skin,
+}
+
+impl Default for Cosmetics {
+    fn default() -> Self {
+        Self {
+            ball: default_skin(CosmeticSlot::Ball),
+            paddle: default_skin(CosmeticSlot::Paddle),
+        }
+    }
+}
+
+impl Cosmetics {
+    fn from_profile(profile: &Profile) -> Self {
+        Self {
+            ball: skin_by_id(profile.ball_skin.as_deref(), CosmeticSlot::Ball, profile),
+            paddle: skin_by_id(profile.paddle_skin.as_deref(), CosmeticSlot::Paddle, profile),
+        }
+    }
+
+    fn equipped(&self, slot: CosmeticSlot) -> &'static Skin {
+        match slot {
+            CosmeticSlot::Ball => self.ball,
+            CosmeticSlot::Paddle => self.paddle,
+        }
+    }
+}
+
+// 切换档案（包括启动时）后重新读取外观选择
+pub fn sync_cosmetics(paths: Res<ProfilePaths>, mut cosmetics: ResMut<Cosmetics>) {
+    if paths.is_changed() {
+        *cosmetics = Cosmetics::from_profile(&Profile::load(&paths));
+    }
+}
+
+// 外观界面
+#[derive(Component)]
+pub struct CosmeticsUI;
+
+// 外观列表中的一行，保存在 SKINS 中的索引
+#[derive(Component)]
+pub struct SkinRow(usize);
+
+#[derive(Component)]
+pub struct SkinStatusText(usize);
+
+#[derive(Resource, Default)]
+pub struct CosmeticsMenuState {
+    selected: usize,
+}
+
+// 行右侧的状态文字：已装备、未解锁条件或空
+fn skin_status(index: usize, profile: &Profile, cosmetics: &Cosmetics, locale: &Locale) -> (String, Color) {
+    let skin = &SKINS[index];
+    if !skin.unlock.is_met(profile) {
+        let condition = skin.unlock.describe(locale);
+        (locale.tr("cosmetics.locked", &[("condition", &condition)]), Color::rgb(0.5, 0.5, 0.5))
+    } else if std::ptr::eq(cosmetics.equipped(skin.slot), skin) {
+        (locale.tr("cosmetics.equipped", &[]), Color::rgb(0.2, 0.8, 0.2))
+    } else {
+        (String::new(), Color::WHITE)
+    }
+}
+
+// 外观的色块预览，未解锁时变暗
+fn spawn_swatch(parent: &mut ChildBuilder, skin: &Skin, unlocked: bool) {
+    let (width, height) = match skin.slot {
+        CosmeticSlot::Ball => (24.0, 24.0),
+        CosmeticSlot::Paddle => (72.0, 16.0),
+    };
+    let alpha = if unlocked { 1.0 } else { LOCKED_ALPHA };
+    parent
+        .spawn(NodeBundle {
+            style: Style {
+                width: Val::Px(width),
+                height: Val::Px(height),
+                flex_direction: FlexDirection::ColumnReverse,
+                ..default()
+            },
+            background_color: BackgroundColor(skin.base.with_a(alpha)),
+            ..default()
+        })
+        .with_children(|parent| {
+            for color in skin.bands().into_iter().flatten() {
+                parent.spawn(NodeBundle {
+                    style: Style {
+                        width: Val::Percent(100.0),
+                        height: Val::Percent(100.0 / BANDS as f32),
+                        ..default()
+                    },
+                    background_color: BackgroundColor(color.with_a(alpha)),
+                    ..default()
+                });
+            }
+        });
+}
+
+pub fn setup_cosmetics_screen(
+    mut commands: Commands,
+    fonts: Res<FontAssets>,
+    locale: Res<Locale>,
+    paths: Res<ProfilePaths>,
+    cosmetics: Res<Cosmetics>,
+    mut menu_state: ResMut<CosmeticsMenuState>,
+) {
+    let profile = Profile::load(&paths);
+    menu_state.selected = 0;
+
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    width: Val::Percent(100.0),
+                    height: Val::Percent(100.0),
+                    flex_direction: FlexDirection::Column,
+                    justify_content: JustifyContent::Center,
+                    align_items: AlignItems::Center,
+                    ..default()
+                },
+                background_color: BackgroundColor(Color::rgb(0.1, 0.1, 0.15)),
+                ..default()
+            },
+            CosmeticsUI,
+        ))
+        .with_children(|parent| {
+            parent.spawn(TextBundle::from_section(
+                locale.tr("cosmetics.title", &[]),
+                text_style(&fonts, FontKind::Bold, 60.0, Color::WHITE),
+            ));
+
+            for slot in CosmeticSlot::ALL {
+                parent.spawn(TextBundle::from_section(
+                    locale.tr(slot.label_key(), &[]),
+                    text_style(&fonts, FontKind::Bold, 26.0, Color::rgb(1.0, 0.85, 0.2)),
+                ).with_style(Style {
+                    margin: UiRect::top(Val::Px(20.0)),
+                    ..default()
+                }));
+
+                for (index, skin) in SKINS.iter().enumerate().filter(|(_, skin)| skin.slot == slot) {
+                    let unlocked = skin.unlock.is_met(&profile);
+                    let (status, status_color) = skin_status(index, &profile, &cosmetics, &locale);
+                    parent
+                        .spawn((
+                            NodeBundle {
+                                style: Style {
+                                    width: Val::Px(520.0),
+                                    margin: UiRect::top(Val::Px(6.0)),
+                                    padding: UiRect::axes(Val::Px(10.0), Val::Px(4.0)),
+                                    border: UiRect::all(Val::Px(2.0)),
+                                    column_gap: Val::Px(12.0),
+                                    align_items: AlignItems::Center,
+                                    ..default()
+                                },
+                                background_color: BackgroundColor(Color::rgba(0.2, 0.2, 0.25, 0.8)),
+                                border_color: BorderColor(Color::NONE),
+                                ..default()
+                            },
+                            SkinRow(index),
+                        ))
+                        .with_children(|parent| {
+                            spawn_swatch(parent, skin, unlocked);
+                            let name_color = if unlocked { Color::WHITE } else { Color::rgb(0.5, 0.5, 0.5) };
+                            parent.spawn(
+                                TextBundle::from_section(
+                                    locale.tr(&name_key("skin", skin.id), &[]),
+                                    text_style(&fonts, FontKind::Regular, 22.0, name_color),
+                                )
+                                .with_style(Style {
+                                    flex_grow: 1.0,
+                                    ..default()
+                                }),
+                            );
+                            parent.spawn((
+                                TextBundle::from_section(status, text_style(&fonts, FontKind::Regular, 18.0, status_color)),
+                                SkinStatusText(index),
+                            ));
+                        });
+                }
+            }
+
+            parent.spawn(TextBundle::from_section(
+                locale.tr("cosmetics.hint", &[]),
+                text_style(&fonts, FontKind::Regular, 20.0, Color::rgb(0.6, 0.6, 0.6)),
+            ).with_style(Style {
+                margin: UiRect::top(Val::Px(30.0)),
+                ..default()
+            }));
+        });
+}
+
+// 上下选择，回车装备已解锁的外观并保存到档案，ESC 返回档案界面
+pub fn cosmetics_screen_system(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    locale: Res<Locale>,
+    paths: Res<ProfilePaths>,
+    mut cosmetics: ResMut<Cosmetics>,
+    mut menu_state: ResMut<CosmeticsMenuState>,
+    mut next_state: ResMut<NextState<GameState>>,
+    mut row_query: Query<(&SkinRow, &mut BorderColor)>,
+    mut status_query: Query<(&SkinStatusText, &mut Text)>,
+) {
+    if keyboard_input.just_pressed(KeyCode::Escape) {
+        next_state.set(GameState::Profile);
+        return;
+    }
+
+    let count = SKINS.len();
+    if keyboard_input.just_pressed(KeyCode::ArrowUp) {
+        menu_state.selected = (menu_state.selected + count - 1) % count;
+    } else if keyboard_input.just_pressed(KeyCode::ArrowDown) {
+        menu_state.selected = (menu_state.selected + 1) % count;
+    }
+
+    if keyboard_input.just_pressed(KeyCode::Enter) {
+        let mut profile = Profile::load(&paths);
+        let skin = &SKINS[menu_state.selected];
+        if skin.unlock.is_met(&profile) {
+            let id = Some(skin.id.to_string());
+            match skin.slot {
+                CosmeticSlot::Ball => profile.ball_skin = id,
+                CosmeticSlot::Paddle => profile.paddle_skin = id,
+            }
+            if let Err(e) = profile.save(&paths) {
+                eprintln!("Failed to save profile: {}", e);
+            }
+            *cosmetics = Cosmetics::from_profile(&profile);
+            for (status, mut text) in status_query.iter_mut() {
+                let (value, color) = skin_status(status.0, &profile, &cosmetics, &locale);
+                text.sections[0].value = value;
+                text.sections[0].style.color = color;
+            }
+        }
+    }
+
+    for (row, mut border_color) in row_query.iter_mut() {
+        border_color.0 = if row.0 == menu_state.selected {
+            Color::rgb(1.0, 1.0, 0.0)
+        } else {
+            Color::NONE
+        };
+    }
+}
+
+pub fn cleanup_cosmetics_screen(mut commands: Commands, query: Query<Entity, With<CosmeticsUI>>) {
+    for entity in query.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+}
@@ -0,0 +1,75 @@
+// 倒计时：剩余时间夹在 0，只在越过 0 的那一帧报告一次结束。
+// 单帧时间很长（拖动窗口、断点）时也不会变成负数或重复结束
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Countdown {
+    remaining: f32,
+    just_finished: bool,
+}
+
+impl Countdown {
+    pub fn new(seconds: f32) -> Self {
+        Self {
+            remaining: seconds.max(0.0),
+            just_finished: false,
+        }
+    }
+
+    // 重新开始计时；同一帧内刷新的倒计时不会再报告结束
+    pub fn restart(&mut self, seconds: f32) {
+        *self = Self::new(seconds);
+    }
+
+    // 前进 delta 秒，返回这一帧是否刚好结束
+    pub fn tick(&mut self, delta: f32) -> bool {
+        self.just_finished = self.remaining > 0.0 && self.remaining <= delta;
+        self.remaining = (self.remaining - delta).max(0.0);
+        self.just_finished
+    }
+
+    pub fn remaining(&self) -> f32 {
+        self.remaining
+    }
+
+    pub fn just_finished(&self) -> bool {
+        self.just_finished
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn long_tick_finishes_once_and_clamps_at_zero() {
+        let mut countdown = Countdown::new(1.0);
+        assert!(countdown.tick(2.0));
+        assert_eq!(countdown.remaining(), 0.0);
+        assert!(!countdown.tick(2.0));
+        assert!(!countdown.just_finished());
+    }
+
+    #[test]
+    fn tick_landing_exactly_on_zero_finishes() {
+        let mut countdown = Countdown::new(0.5);
+        assert!(!countdown.tick(0.25));
+        assert!(countdown.tick(0.25));
+    }
+
+    #[test]
+    fn restart_clears_a_pending_finish() {
+        let mut countdown = Countdown::new(0.1);
+        assert!(countdown.tick(0.2));
+        // 同一帧拾取道具刷新了计时
+        countdown.restart(5.0);
+        assert!(!countdown.just_finished());
+        assert_eq!(countdown.remaining(), 5.0);
+    }
+
+    #[test]
+    fn zero_or_negative_countdown_never_finishes() {
+        let mut countdown = Countdown::new(-3.0);
+        assert_eq!(countdown.remaining(), 0.0);
+        assert!(!countdown.tick(1.0));
+        assert!(!Countdown::default().tick(1.0));
+    }
+}
@@ -0,0 +1,315 @@
+use bevy::prelude::*;
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::SeedableRng;
+
+use crate::fonts::{text_style, FontAssets, FontKind};
+use crate::gamepad::GamepadButtons;
+use crate::i18n::Locale;
+use crate::profile::RunStats;
+use crate::{GameEntity, GameState, PowerUpEffects, RunSeed};
+
+// 普通局打到第几关时提供诅咒
+pub const CURSE_LEVEL: u32 = 3;
+// 一次提供几个诅咒
+const OFFER_SIZE: usize = 2;
+// 由本局种子推导提供哪些诅咒，每日挑战的玩家看到同样的选择
+const OFFER_SEED_SALT: u64 = 0xD1B5_4A32_D192_ED03;
+// 限时诅咒每关的时间（秒），比困难模式的限时更紧
+const CURSE_TIME_LIMIT: f32 = 150.0;
+
+const CURSE_COLOR: Color = Color::rgb(0.75, 0.4, 1.0);
+
+// 可以选择的诅咒：让之后的每一关更难，换取更高的得分倍率
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CurseKind {
+    // 挡板缩小 15%
+    SmallPaddle,
+    // 球速加快 10%
+    FastBall,
+    // 随机掉落的道具减半
+    ScarceDrops,
+    // 每关都有时间限制
+    Timed,
+}
+
+impl CurseKind {
+    pub const ALL: [CurseKind; 4] = [CurseKind::SmallPaddle, CurseKind::FastBall, CurseKind::ScarceDrops, CurseKind::Timed];
+
+    // 写入本局统计和提交记录的名字
+    pub fn name(&self) -> &'static str {
+        match self {
+            CurseKind::SmallPaddle => "SmallPaddle",
+            CurseKind::FastBall => "FastBall",
+            CurseKind::ScarceDrops => "ScarceDrops",
+            CurseKind::Timed => "Timed",
+        }
+    }
+
+    pub fn label_key(&self) -> &'static str {
+        match self {
+            CurseKind::SmallPaddle => "curse.small_paddle",
+            CurseKind::FastBall => "curse.fast_ball",
+            CurseKind::ScarceDrops => "curse.scarce_drops",
+            CurseKind::Timed => "curse.timed",
+        }
+    }
+
+    pub fn description_key(&self) -> &'static str {
+        match self {
+            CurseKind::SmallPaddle => "curse.small_paddle_desc",
+            CurseKind::FastBall => "curse.fast_ball_desc",
+            CurseKind::ScarceDrops => "curse.scarce_drops_desc",
+            CurseKind::Timed => "curse.timed_desc",
+        }
+    }
+
+    // 诅咒生效期间所有得分的倍率（百分比）
+    pub fn multiplier_percent(&self) -> u32 {
+        match self {
+            CurseKind::SmallPaddle => 130,
+            CurseKind::FastBall => 120,
+            CurseKind::ScarceDrops => 125,
+            CurseKind::Timed => 150,
+        }
+    }
+}
+
+// 倍率的显示形式，例如 ×1.3
+fn multiplier_label(kind: CurseKind) -> String {
+    format!("×{}", kind.multiplier_percent() as f32 / 100.0)
+}
+
+// 本局选择的诅咒，一直持续到这一局结束；没有选择时为空
+#[derive(Resource, Debug, Default)]
+pub struct Curse(Option<CurseKind>);
+
+impl Curse {
+    pub fn kind(&self) -> Option<CurseKind> {
+        self.0
+    }
+
+    pub fn clear(&mut self) {
+        self.0 = None;
+    }
+
+    // 按诅咒的倍率计算得分
+    pub fn apply(&self, points: u32) -> u32 {
+        match self.0 {
+            Some(kind) => points * kind.multiplier_percent() / 100,
+            None => points,
+        }
+    }
+
+    // 每关开始时挡板宽度和球速的基准倍率
+    pub fn paddle_size_modifier(&self) -> f32 {
+        if self.0 == Some(CurseKind::SmallPaddle) { 0.85 } else { 1.0 }
+    }
+
+    pub fn ball_speed_modifier(&self) -> f32 {
+        if self.0 == Some(CurseKind::FastBall) { 1.1 } else { 1.0 }
+    }
+
+    // 随机掉落道具的概率；保底道具和金砖不受影响
+    pub fn drop_chance(&self, chance: f64) -> f64 {
+        if self.0 == Some(CurseKind::ScarceDrops) { chance / 2.0 } else { chance }
+    }
+
+    // 每关的时间限制：难度本来就有限时的话取较短的一个
+    pub fn time_limit(&self, difficulty_limit: Option<f32>) -> Option<f32> {
+        match (self.0, difficulty_limit) {
+            (Some(CurseKind::Timed), Some(limit)) => Some(limit.min(CURSE_TIME_LIMIT)),
+            (Some(CurseKind::Timed), None) => Some(CURSE_TIME_LIMIT),
+            (_, limit) => limit,
+        }
+    }
+}
+
+// 这一局提供的诅咒，同一个种子总是得到同样的选择
+pub fn offered_curses(seed: u64) -> Vec<CurseKind> {
+    let mut rng = StdRng::seed_from_u64(seed ^ OFFER_SEED_SALT);
+    CurseKind::ALL.choose_multiple(&mut rng, OFFER_SIZE).copied().collect()
+}
+
+// 诅咒选择界面：choices 之后的一项是放弃
+#[derive(Resource, Debug, Default)]
+pub struct CurseOffer {
+    choices: Vec<CurseKind>,
+    selected: usize,
+}
+
+#[derive(Component)]
+pub struct CurseChoiceUI;
+
+#[derive(Component)]
+pub struct CurseOption(usize);
+
+pub fn setup_curse_choice(
+    mut commands: Commands,
+    run_seed: Res<RunSeed>,
+    fonts: Res<FontAssets>,
+    locale: Res<Locale>,
+    mut offer: ResMut<CurseOffer>,
+) {
+    *offer = CurseOffer {
+        choices: offered_curses(run_seed.0),
+        selected: 0,
+    };
+
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    width: Val::Percent(100.0),
+                    height: Val::Percent(100.0),
+                    flex_direction: FlexDirection::Column,
+                    justify_content: JustifyContent::Center,
+                    align_items: AlignItems::Center,
+                    row_gap: Val::Px(12.0),
+                    ..default()
+                },
+                background_color: BackgroundColor(Color::rgb(0.1, 0.08, 0.15)),
+                ..default()
+            },
+            CurseChoiceUI,
+        ))
+        .with_children(|parent| {
+            parent.spawn(TextBundle::from_section(
+                locale.tr("curse.title", &[]),
+                text_style(&fonts, FontKind::Bold, 56.0, CURSE_COLOR),
+            ));
+            parent.spawn(TextBundle::from_section(
+                locale.tr("curse.subtitle", &[]),
+                text_style(&fonts, FontKind::Regular, 20.0, Color::rgb(0.7, 0.7, 0.7)),
+            ).with_style(Style {
+                margin: UiRect::bottom(Val::Px(20.0)),
+                ..default()
+            }));
+
+            let mut rows: Vec<(String, String)> = offer
+                .choices
+                .iter()
+                .map(|kind| {
+                    let label = locale.tr(kind.label_key(), &[]);
+                    (
+                        locale.tr("curse.option", &[("curse", &label), ("multiplier", &multiplier_label(*kind))]),
+                        locale.tr(kind.description_key(), &[]),
+                    )
+                })
+                .collect();
+            rows.push((locale.tr("curse.decline", &[]), locale.tr("curse.decline_desc", &[])));
+
+            for (index, (label, description)) in rows.into_iter().enumerate() {
+                parent
+                    .spawn((
+                        ButtonBundle {
+                            style: Style {
+                                width: Val::Px(460.0),
+                                flex_direction: FlexDirection::Column,
+                                padding: UiRect::all(Val::Px(12.0)),
+                                border: UiRect::all(Val::Px(3.0)),
+                                row_gap: Val::Px(4.0),
+                                ..default()
+                            },
+                            background_color: BackgroundColor(Color::rgba(0.2, 0.2, 0.25, 0.8)),
+                            border_color: BorderColor(Color::NONE),
+                            ..default()
+                        },
+                        CurseOption(index),
+                    ))
+                    .with_children(|parent| {
+                        parent.spawn(TextBundle::from_section(
+                            label,
+                            text_style(&fonts, FontKind::Bold, 26.0, Color::WHITE),
+                        ));
+                        parent.spawn(TextBundle::from_section(
+                            description,
+                            text_style(&fonts, FontKind::Regular, 18.0, Color::rgb(0.7, 0.7, 0.7)),
+                        ));
+                    });
+            }
+
+            parent.spawn(TextBundle::from_section(
+                locale.tr("curse.hint", &[]),
+                text_style(&fonts, FontKind::Regular, 18.0, Color::rgb(0.6, 0.6, 0.6)),
+            ).with_style(Style {
+                margin: UiRect::top(Val::Px(20.0)),
+                ..default()
+            }));
+        });
+}
+
+// 上下键或十字键选择，回车、空格或南键确认，也可以用鼠标点击；确认后进入下一关的预览
+pub fn curse_choice_system(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    gamepad: GamepadButtons,
+    mut next_state: ResMut<NextState<GameState>>,
+    mut offer: ResMut<CurseOffer>,
+    mut curse: ResMut<Curse>,
+    mut stats: ResMut<RunStats>,
+    mut power_effects: ResMut<PowerUpEffects>,
+    mut option_query: Query<(&Interaction, &CurseOption, &mut BorderColor)>,
+) {
+    let option_count = offer.choices.len() + 1;
+    let mut chosen = None;
+
+    if keyboard_input.just_pressed(KeyCode::ArrowUp) || gamepad.just_pressed(GamepadButtonType::DPadUp) {
+        offer.selected = (offer.selected + option_count - 1) % option_count;
+    } else if keyboard_input.just_pressed(KeyCode::ArrowDown) || gamepad.just_pressed(GamepadButtonType::DPadDown) {
+        offer.selected = (offer.selected + 1) % option_count;
+    } else if keyboard_input.just_pressed(KeyCode::Enter)
+        || keyboard_input.just_pressed(KeyCode::Space)
+        || gamepad.just_pressed(GamepadButtonType::South)
+    {
+        chosen = Some(offer.selected);
+    }
+
+    for (interaction, option, _) in option_query.iter() {
+        match interaction {
+            Interaction::Pressed => chosen = Some(option.0),
+            Interaction::Hovered => offer.selected = option.0,
+            Interaction::None => {}
+        }
+    }
+
+    for (_, option, mut border_color) in option_query.iter_mut() {
+        border_color.0 = if option.0 == offer.selected { CURSE_COLOR } else { Color::NONE };
+    }
+
+    let Some(index) = chosen else {
+        return;
+    };
+    // 放弃时 get 返回 None
+    curse.0 = offer.choices.get(index).copied();
+    stats.curse = curse.0.map(|kind| kind.name().to_string());
+    // 下一关的效果已经在进入这里之前重置，按选择的诅咒重新设置
+    *power_effects = PowerUpEffects::with_curse(&curse);
+    next_state.set(GameState::LevelInterlude);
+}
+
+pub fn cleanup_curse_choice(mut commands: Commands, query: Query<Entity, With<CurseChoiceUI>>) {
+    for entity in query.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+// 右下角的诅咒标记，在保险代币上方
+pub fn spawn_curse_badge(commands: &mut Commands, fonts: &FontAssets, locale: &Locale, curse: &Curse) {
+    let Some(kind) = curse.kind() else {
+        return;
+    };
+    let label = locale.tr(kind.label_key(), &[]);
+    commands.spawn((
+        TextBundle::from_section(
+            locale.tr("curse.badge", &[("curse", &label), ("multiplier", &multiplier_label(kind))]),
+            text_style(fonts, FontKind::Bold, 18.0, CURSE_COLOR),
+        )
+        .with_style(Style {
+            position_type: PositionType::Absolute,
+            right: Val::Px(10.0),
+            bottom: Val::Px(36.0),
+            ..default()
+        }),
+        GameEntity,
+    ));
+}
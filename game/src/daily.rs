@@ -0,0 +1,207 @@
+use bevy::prelude::*;
+use chrono::{NaiveDate, Utc};
+
+use crate::api::{DailyResponse, Score};
+use crate::fonts::{text_style, FontAssets, FontKind};
+use crate::i18n::Locale;
+use crate::network::{ApiCommand, ApiResponse, Network};
+use crate::profile::Profile;
+use crate::profiles::ProfilePaths;
+use crate::{start_new_run, Difficulty, DifficultySettings, GameState, PlayerName, RunResources};
+
+// 每日挑战固定使用的难度
+const DAILY_DIFFICULTY: Difficulty = Difficulty::Medium;
+// 挑战界面显示的榜单行数
+const DAILY_BOARD_SIZE: usize = 5;
+// 提交时的模式名，以及和服务器共用的种子算法
+pub use breakout_shared::{daily_seed, date_key, DAILY_MODE};
+
+// 正在进行的每日挑战：保存开始这一局时的 UTC 日期。
+// 提交时按这个日期校验，跨过午夜才结束的局仍然算在开始的那一天
+#[derive(Resource, Default)]
+pub struct DailyRun(pub Option<NaiveDate>);
+
+// 这一局结束时是否应该提交：每日挑战只提交比当天本机最好成绩更高的分数，并记录新的最好成绩
+pub fn should_submit(paths: &ProfilePaths, daily_run: &DailyRun, score: u32) -> bool {
+    let Some(date) = daily_run.0 else {
+        return true;
+    };
+    let mut profile = Profile::load(paths);
+    let best = profile.daily_best.entry(date_key(date)).or_insert(0);
+    if score <= *best {
+        return false;
+    }
+    *best = score;
+    if let Err(e) = profile.save(paths) {
+        eprintln!("Failed to save profile: {}", e);
+    }
+    true
+}
+
+#[derive(Component)]
+pub struct DailyUI;
+
+// 榜单行的容器，收到响应后填充
+#[derive(Component)]
+pub struct DailyBoardList;
+
+fn board_row(score: &Score) -> String {
+    format!("#{:<4} {:<20} {:>8}", score.rank.unwrap_or(0), score.player_name, score.score)
+}
+
+pub fn setup_daily_screen(
+    mut commands: Commands,
+    network: Res<Network>,
+    player_name: Res<PlayerName>,
+    fonts: Res<FontAssets>,
+    locale: Res<Locale>,
+) {
+    let today = date_key(Utc::now().date_naive());
+    network.send(ApiCommand::GetDaily {
+        date: today.clone(),
+        player_name: player_name.0.clone(),
+        limit: DAILY_BOARD_SIZE,
+    });
+
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    width: Val::Percent(100.0),
+                    height: Val::Percent(100.0),
+                    flex_direction: FlexDirection::Column,
+                    justify_content: JustifyContent::Center,
+                    align_items: AlignItems::Center,
+                    ..default()
+                },
+                background_color: BackgroundColor(Color::rgb(0.1, 0.1, 0.15)),
+                ..default()
+            },
+            DailyUI,
+        ))
+        .with_children(|parent| {
+            parent.spawn(TextBundle::from_section(
+                locale.tr("daily.title", &[]),
+                text_style(&fonts, FontKind::Bold, 60.0, Color::WHITE),
+            ));
+
+            parent.spawn(TextBundle::from_section(
+                locale.tr("daily.date", &[("date", &today)]),
+                text_style(&fonts, FontKind::Regular, 24.0, Color::rgb(0.5, 0.7, 0.9)),
+            ).with_style(Style {
+                margin: UiRect::top(Val::Px(10.0)),
+                ..default()
+            }));
+
+            parent
+                .spawn((
+                    NodeBundle {
+                        style: Style {
+                            width: Val::Px(460.0),
+                            min_height: Val::Px(200.0),
+                            margin: UiRect::top(Val::Px(30.0)),
+                            padding: UiRect::all(Val::Px(15.0)),
+                            flex_direction: FlexDirection::Column,
+                            row_gap: Val::Px(6.0),
+                            ..default()
+                        },
+                        background_color: BackgroundColor(Color::rgba(0.2, 0.2, 0.25, 0.8)),
+                        ..default()
+                    },
+                    DailyBoardList,
+                ))
+                .with_children(|parent| {
+                    parent.spawn(TextBundle::from_section(
+                        locale.tr("daily.loading", &[]),
+                        text_style(&fonts, FontKind::Regular, 20.0, Color::rgb(0.6, 0.6, 0.6)),
+                    ));
+                });
+
+            parent.spawn(TextBundle::from_section(
+                locale.tr("daily.hint", &[]),
+                text_style(&fonts, FontKind::Regular, 20.0, Color::rgb(0.6, 0.6, 0.6)),
+            ).with_style(Style {
+                margin: UiRect::top(Val::Px(30.0)),
+                ..default()
+            }));
+        });
+}
+
+// 用服务器返回的榜单替换加载提示：前几名加上自己当天的最好成绩
+pub fn daily_responses(
+    mut commands: Commands,
+    mut responses: EventReader<ApiResponse>,
+    fonts: Res<FontAssets>,
+    locale: Res<Locale>,
+    list_query: Query<Entity, With<DailyBoardList>>,
+) {
+    for response in responses.read() {
+        let ApiResponse::Daily(result) = response else {
+            continue;
+        };
+        let Ok(list) = list_query.get_single() else {
+            continue;
+        };
+        commands.entity(list).despawn_descendants();
+        commands.entity(list).with_children(|parent| match result {
+            Ok(DailyResponse { scores, player_best, .. }) => {
+                if scores.is_empty() {
+                    parent.spawn(TextBundle::from_section(
+                        locale.tr("daily.empty", &[]),
+                        text_style(&fonts, FontKind::Regular, 20.0, Color::rgb(0.6, 0.6, 0.6)),
+                    ));
+                }
+                for score in scores {
+                    parent.spawn(TextBundle::from_section(
+                        board_row(score),
+                        text_style(&fonts, FontKind::Mono, 20.0, Color::WHITE),
+                    ));
+                }
+                let (best_text, color) = match player_best {
+                    Some(best) => (
+                        locale.tr("daily.your_best", &[("row", &board_row(best))]),
+                        Color::rgb(1.0, 0.85, 0.2),
+                    ),
+                    None => (locale.tr("daily.not_played", &[]), Color::rgb(0.6, 0.6, 0.6)),
+                };
+                parent.spawn(TextBundle::from_section(best_text, text_style(&fonts, FontKind::Mono, 20.0, color)).with_style(
+                    Style {
+                        margin: UiRect::top(Val::Px(12.0)),
+                        ..default()
+                    },
+                ));
+            }
+            Err(e) => {
+                eprintln!("Failed to fetch daily challenge: {}", e);
+                parent.spawn(TextBundle::from_section(
+                    locale.tr("daily.offline", &[]),
+                    text_style(&fonts, FontKind::Regular, 20.0, Color::rgb(0.8, 0.2, 0.2)),
+                ));
+            }
+        });
+    }
+}
+
+// 空格开始今天的挑战（固定种子和难度），ESC 返回主菜单
+pub fn daily_screen_system(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    mut next_state: ResMut<NextState<GameState>>,
+    mut run: RunResources,
+) {
+    if keyboard_input.just_pressed(KeyCode::Escape) {
+        next_state.set(GameState::MainMenu);
+    } else if keyboard_input.just_pressed(KeyCode::Space) {
+        // 以开始这一局的时间确定挑战日期
+        let date = Utc::now().date_naive();
+        *run.difficulty_settings = DifficultySettings::new(DAILY_DIFFICULTY);
+        start_new_run(&mut run, Some(daily_seed(date)));
+        run.daily_run.0 = Some(date);
+        next_state.set(GameState::Playing);
+    }
+}
+
+pub fn cleanup_daily_screen(mut commands: Commands, query: Query<Entity, With<DailyUI>>) {
+    for entity in query.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+}
@@ -0,0 +1,114 @@
+use bevy::prelude::*;
+use bevy::window::WindowFocused;
+
+use crate::cosmetics::Cosmetics;
+use crate::governor::EffectBudget;
+use crate::level_select::GameMode;
+use crate::{spawn_particles, Ball, GameEntity, GameState, Settings, WINDOW_HEIGHT};
+
+// 慢镜头总时长与慢放倍率（按真实时间计算）
+const DEATH_CAM_DURATION: f32 = 1.2;
+const DEATH_CAM_TIME_SCALE: f32 = 0.3;
+// 球闪烁多久后碎裂
+const SHATTER_AT: f32 = 0.6;
+// 减少动态效果时的简单停顿
+const REDUCED_MOTION_PAUSE: f32 = 0.4;
+// 相机向球靠近的比例
+const CAMERA_NUDGE: f32 = 0.15;
+
+// 最后一条命丢失后的死亡镜头
+#[derive(Resource, Default)]
+pub struct DeathSequence {
+    elapsed: f32,
+    duration: f32,
+    reduced: bool,
+    shattered: bool,
+}
+
+// 进入死亡镜头：放慢游戏时间
+pub fn setup_death_sequence(
+    mut sequence: ResMut<DeathSequence>,
+    mut virtual_time: ResMut<Time<Virtual>>,
+    settings: Res<Settings>,
+) {
+    *sequence = DeathSequence {
+        elapsed: 0.0,
+        duration: if settings.reduce_motion { REDUCED_MOTION_PAUSE } else { DEATH_CAM_DURATION },
+        reduced: settings.reduce_motion,
+        shattered: false,
+    };
+    if !sequence.reduced {
+        virtual_time.set_relative_speed(DEATH_CAM_TIME_SCALE);
+    }
+}
+
+// 推进死亡镜头：球慢速下落并闪烁，随后在底边碎裂
+pub fn death_sequence_system(
+    mut commands: Commands,
+    mut sequence: ResMut<DeathSequence>,
+    real_time: Res<Time<Real>>,
+    time: Res<Time>,
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    gamepad_input: Res<ButtonInput<GamepadButton>>,
+    mut focus_events: EventReader<WindowFocused>,
+    mut next_state: ResMut<NextState<GameState>>,
+    game_mode: Res<GameMode>,
+    cosmetics: Res<Cosmetics>,
+    budget: Res<EffectBudget>,
+    mut ball_query: Query<(Entity, &mut Transform, &Ball, &mut Sprite), Without<Camera2d>>,
+    mut camera_query: Query<&mut Transform, (With<Camera2d>, With<GameEntity>)>,
+) {
+    // 任意键跳过；窗口失去焦点时直接结束，保证时间倍率被恢复
+    let lost_focus = focus_events.read().any(|event| !event.focused);
+    if lost_focus
+        || keyboard_input.get_just_pressed().next().is_some()
+        || gamepad_input.get_just_pressed().next().is_some()
+    {
+        next_state.set(game_mode.run_over_state());
+        return;
+    }
+
+    sequence.elapsed += real_time.delta_seconds();
+    if sequence.elapsed >= sequence.duration {
+        next_state.set(game_mode.run_over_state());
+        return;
+    }
+    if sequence.reduced {
+        return;
+    }
+
+    let bottom = -WINDOW_HEIGHT / 2.0;
+    for (entity, mut transform, ball, mut sprite) in ball_query.iter_mut() {
+        if sequence.shattered {
+            break;
+        }
+
+        if sequence.elapsed >= SHATTER_AT {
+            let position = Vec3::new(transform.translation.x, bottom, transform.translation.z);
+            spawn_particles(&mut commands, &budget, position, transform.scale * 2.0);
+            commands.entity(entity).despawn_recursive();
+            sequence.shattered = true;
+            break;
+        }
+
+        transform.translation += ball.velocity.extend(0.0) * time.delta_seconds();
+        transform.translation.y = transform.translation.y.max(bottom);
+
+        // 每 0.1 秒切换一次颜色
+        let flash_on = ((sequence.elapsed / 0.1) as u32).is_multiple_of(2);
+        sprite.color = if flash_on { Color::WHITE } else { cosmetics.ball.base };
+
+        let target = transform.translation.truncate() * CAMERA_NUDGE;
+        for mut camera_transform in camera_query.iter_mut() {
+            let current = camera_transform.translation.truncate();
+            let nudged = current.lerp(target, (real_time.delta_seconds() * 8.0).min(1.0));
+            camera_transform.translation.x = nudged.x;
+            camera_transform.translation.y = nudged.y;
+        }
+    }
+}
+
+// 离开死亡镜头时无条件恢复时间倍率
+pub fn cleanup_death_sequence(mut virtual_time: ResMut<Time<Virtual>>) {
+    virtual_time.set_relative_speed(1.0);
+}
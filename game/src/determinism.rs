@@ -0,0 +1,402 @@
+// 校验和与比较部分不依赖特性，确定性测试直接使用；只有记录和保存的插件需要开启 determinism-audit
+#![cfg_attr(not(feature = "determinism-audit"), allow(dead_code))]
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::game_rng::GameRng;
+use crate::{Ball, Brick};
+
+// 每隔多少个固定帧记录一次校验和
+pub const AUDIT_INTERVAL: usize = 30;
+// 坐标和速度按 1/16 像素量化，避免浮点噪声以外的差异被忽略
+const QUANTUM: f32 = 16.0;
+
+// 确定性检查插件：开启 determinism-audit 特性时，每隔几个固定帧记录一次游戏状态的校验和，
+// 和幽灵轨迹一起保存；之后用同一种子开局时逐帧比较，报告第一个不一致的字段。未开启时不注册任何内容
+pub struct DeterminismAuditPlugin;
+
+impl Plugin for DeterminismAuditPlugin {
+    #[cfg(feature = "determinism-audit")]
+    fn build(&self, app: &mut App) {
+        audit::build(app);
+    }
+
+    #[cfg(not(feature = "determinism-audit"))]
+    fn build(&self, _app: &mut App) {}
+}
+
+// 一次采样：各部分状态分别求哈希，出现差异时能指出是哪一部分
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StateChecksum {
+    // 本关的固定帧序号
+    pub tick: usize,
+    // 种子确认比较的是同一局
+    pub seed: u64,
+    // 局内随机数序列的位置：多取或少取一个随机数都会在这里体现
+    pub rng: u64,
+    pub paddle: u64,
+    pub balls: u64,
+    pub bricks: u64,
+    // 分数、生命和关卡
+    pub progress: u64,
+}
+
+impl StateChecksum {
+    // 采样当前状态；progress 依次是分数、生命和关卡
+    pub fn sample<'a>(
+        tick: usize,
+        seed: u64,
+        rng: &GameRng,
+        paddle: Option<&Transform>,
+        balls: impl Iterator<Item = (&'a Transform, &'a Ball)>,
+        bricks: impl Iterator<Item = (&'a Transform, &'a Brick)>,
+        progress: [u32; 3],
+    ) -> Self {
+        let mut rng_state = Fnv::new();
+        let (rng_seed, words) = rng.state();
+        rng_state.write_u64(rng_seed);
+        rng_state.write_u64(words);
+
+        let mut paddle_hash = Fnv::new();
+        if let Some(transform) = paddle {
+            paddle_hash.write_i32(quantize(transform.translation.x));
+        }
+        let balls = hash_sorted(
+            balls
+                .map(|(transform, ball)| {
+                    vec![
+                        quantize(transform.translation.x),
+                        quantize(transform.translation.y),
+                        quantize(ball.velocity.x),
+                        quantize(ball.velocity.y),
+                    ]
+                })
+                .collect(),
+        );
+        let bricks = hash_sorted(
+            bricks
+                .map(|(transform, brick)| {
+                    vec![
+                        quantize(transform.translation.x),
+                        quantize(transform.translation.y),
+                        brick.brick_type as i32,
+                        brick.health,
+                    ]
+                })
+                .collect(),
+        );
+        let mut progress_hash = Fnv::new();
+        for value in progress {
+            progress_hash.write(&value.to_le_bytes());
+        }
+
+        StateChecksum {
+            tick,
+            seed,
+            rng: rng_state.finish(),
+            paddle: paddle_hash.finish(),
+            balls,
+            bricks,
+            progress: progress_hash.finish(),
+        }
+    }
+
+    // 按比较顺序排列的字段
+    fn fields(&self) -> [(&'static str, u64); 6] {
+        [
+            ("seed", self.seed),
+            ("rng", self.rng),
+            ("paddle", self.paddle),
+            ("balls", self.balls),
+            ("bricks", self.bricks),
+            ("progress", self.progress),
+        ]
+    }
+
+    // 整体校验和
+    pub fn combined(&self) -> u64 {
+        let mut hasher = Fnv::new();
+        for (_, value) in self.fields() {
+            hasher.write_u64(value);
+        }
+        hasher.finish()
+    }
+}
+
+// 同一帧的采样和记录不一致：不一致的字段按比较顺序排列，第一个就是最早能看出差异的部分
+#[derive(Debug)]
+pub struct Divergence {
+    pub tick: usize,
+    pub expected: u64,
+    pub actual: u64,
+    pub differing: Vec<(&'static str, u64, u64)>,
+}
+
+impl Divergence {
+    pub fn report(&self, level: usize) {
+        eprintln!(
+            "Determinism audit: level {} tick {} diverged from the recording ({:#018x} != {:#018x})",
+            level, self.tick, self.expected, self.actual
+        );
+        let (name, recorded, current) = self.differing[0];
+        eprintln!("  first differing field: {} (recorded {:#018x}, actual {:#018x})", name, recorded, current);
+        let names: Vec<&str> = self.differing.iter().map(|(name, _, _)| *name).collect();
+        eprintln!("  differing fields: {}", names.join(", "));
+        // 挡板不同说明玩家的操作和记录不同，之后的差异是正常的；挡板相同而其他字段不同才是模拟本身不确定
+        if names.contains(&"paddle") {
+            eprintln!("  paddle input differs from the recording; later differences are expected");
+        } else {
+            eprintln!("  paddle input matches the recording; the simulation itself diverged");
+        }
+    }
+}
+
+// 比较记录和实际的采样，完全一致时返回 None
+pub fn find_divergence(expected: &StateChecksum, actual: &StateChecksum) -> Option<Divergence> {
+    if expected == actual {
+        return None;
+    }
+    let differing: Vec<(&'static str, u64, u64)> = expected
+        .fields()
+        .into_iter()
+        .zip(actual.fields())
+        .filter(|((_, recorded), (_, current))| recorded != current)
+        .map(|((name, recorded), (_, current))| (name, recorded, current))
+        .collect();
+    // 只有帧序号不同时其他字段都相同，按帧序号报告
+    let differing = if differing.is_empty() {
+        vec![("tick", expected.tick as u64, actual.tick as u64)]
+    } else {
+        differing
+    };
+    Some(Divergence {
+        tick: actual.tick,
+        expected: expected.combined(),
+        actual: actual.combined(),
+        differing,
+    })
+}
+
+// FNV-1a，跨平台、跨版本稳定
+struct Fnv(u64);
+
+impl Fnv {
+    fn new() -> Self {
+        Fnv(0xcbf2_9ce4_8422_2325)
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        for byte in bytes {
+            self.0 ^= u64::from(*byte);
+            self.0 = self.0.wrapping_mul(0x0100_0000_01b3);
+        }
+    }
+
+    fn write_i32(&mut self, value: i32) {
+        self.write(&value.to_le_bytes());
+    }
+
+    fn write_u64(&mut self, value: u64) {
+        self.write(&value.to_le_bytes());
+    }
+
+    fn finish(&self) -> u64 {
+        self.0
+    }
+}
+
+fn quantize(value: f32) -> i32 {
+    (value * QUANTUM).round() as i32
+}
+
+// 实体的遍历顺序不固定，先排序再求哈希
+fn hash_sorted(mut items: Vec<Vec<i32>>) -> u64 {
+    items.sort_unstable();
+    let mut hasher = Fnv::new();
+    for item in items {
+        for value in item {
+            hasher.write_i32(value);
+        }
+    }
+    hasher.finish()
+}
+
+#[cfg(feature = "determinism-audit")]
+mod audit {
+    use bevy::prelude::*;
+    use serde::{Deserialize, Serialize};
+    use std::path::PathBuf;
+
+    use super::{find_divergence, StateChecksum, AUDIT_INTERVAL};
+    use crate::game_rng::GameRng;
+    use crate::paddle::Paddle;
+    use crate::persistence::{load_versioned, save_versioned, Versioned};
+    use crate::profiles::ProfilePaths;
+    use crate::replay::{self, GhostTrace, ReplayRecorder};
+    use crate::{bonus, setup_game_over, Ball, Brick, DifficultySettings, GameState, Level, Lives, RunSeed, Score};
+
+    pub fn build(app: &mut App) {
+        app.init_resource::<AuditRecorder>()
+            .init_resource::<AuditPlayback>()
+            .add_systems(OnEnter(GameState::Playing), load_recorded_checksums)
+            .add_systems(
+                FixedUpdate,
+                record_checksum
+                    .after(replay::record_paddle_trace)
+                    .run_if(in_state(GameState::Playing).and_then(bonus::bonus_inactive)),
+            )
+            .add_systems(OnEnter(GameState::GameOver), save_audit_trace.after(setup_game_over));
+    }
+
+    // 与幽灵轨迹放在一起的校验和文件
+    #[derive(Debug, Clone, Serialize, Deserialize)]
+    struct AuditTrace {
+        seed: u64,
+        difficulty: String,
+        interval: usize,
+        // 按关卡分组，levels[0] 对应第一关
+        levels: Vec<Vec<StateChecksum>>,
+    }
+
+    impl AuditTrace {
+        fn path(paths: &ProfilePaths, difficulty: &str) -> PathBuf {
+            paths.replays().join(format!("ghost_{}.audit.json", difficulty.to_lowercase()))
+        }
+    }
+
+    impl Versioned for AuditTrace {
+        // 版本 2 加入随机数状态
+        const VERSION: u32 = 2;
+        const LABEL: &'static str = "determinism audit";
+        const PRETTY: bool = false;
+
+        // 旧记录里没有随机数状态，无法比较，丢弃后重新录制
+        fn migrate(version: u32, _data: serde_json::Value) -> Result<serde_json::Value, String> {
+            Err(format!("version {} has no RNG state; record the run again", version))
+        }
+    }
+
+    // 当前局记录的校验和
+    #[derive(Resource, Default)]
+    struct AuditRecorder {
+        levels: Vec<Vec<StateChecksum>>,
+    }
+
+    // 正在比较的记录：同一种子和难度的幽灵轨迹带有校验和时加载
+    #[derive(Resource, Default)]
+    struct AuditPlayback {
+        key: Option<(u64, String)>,
+        levels: Vec<Vec<StateChecksum>>,
+        interval: usize,
+        // 每关只报告第一次不一致
+        reported: Vec<usize>,
+    }
+
+    // 在记录挡板轨迹之后采样，帧序号和幽灵轨迹一致
+    fn record_checksum(
+        replay_recorder: Res<ReplayRecorder>,
+        mut recorder: ResMut<AuditRecorder>,
+        mut playback: ResMut<AuditPlayback>,
+        (run_seed, level, score, lives, game_rng): (Res<RunSeed>, Res<Level>, Res<Score>, Res<Lives>, Res<GameRng>),
+        paddle_query: Query<&Transform, With<Paddle>>,
+        ball_query: Query<(&Transform, &Ball)>,
+        brick_query: Query<(&Transform, &Brick)>,
+    ) {
+        let index = level.0.saturating_sub(1) as usize;
+        let Some(tick) = replay_recorder.levels.get(index).and_then(|trace| trace.len().checked_sub(1)) else {
+            return;
+        };
+        // 新的一局或重新开始这一关：丢弃之后的旧记录
+        if tick == 0 {
+            recorder.levels.truncate(index);
+        }
+        if tick % AUDIT_INTERVAL != 0 {
+            return;
+        }
+
+        let checksum = StateChecksum::sample(
+            tick,
+            run_seed.0,
+            &game_rng,
+            paddle_query.get_single().ok(),
+            ball_query.iter(),
+            brick_query.iter(),
+            [score.0, lives.0, level.0],
+        );
+        if recorder.levels.len() <= index {
+            recorder.levels.resize(index + 1, Vec::new());
+        }
+        recorder.levels[index].push(checksum);
+
+        compare_with_recording(&mut playback, index, &checksum);
+    }
+
+    // 和记录中同一帧的采样比较，不一致时输出报告
+    fn compare_with_recording(playback: &mut AuditPlayback, index: usize, actual: &StateChecksum) {
+        if playback.interval != AUDIT_INTERVAL || playback.reported.contains(&index) {
+            return;
+        }
+        let Some(expected) = playback
+            .levels
+            .get(index)
+            .and_then(|level| level.get(actual.tick / AUDIT_INTERVAL))
+        else {
+            return;
+        };
+        if let Some(divergence) = find_divergence(expected, actual) {
+            playback.reported.push(index);
+            divergence.report(index + 1);
+        }
+    }
+
+    // 开局时加载同一种子和难度的校验和记录；暂停后继续和进入下一关不会重复加载
+    fn load_recorded_checksums(
+        paths: Res<ProfilePaths>,
+        run_seed: Res<RunSeed>,
+        difficulty_settings: Res<DifficultySettings>,
+        mut playback: ResMut<AuditPlayback>,
+    ) {
+        let difficulty = difficulty_settings.difficulty.name();
+        let key = (run_seed.0, difficulty.to_string());
+        if playback.key.as_ref() == Some(&key) {
+            return;
+        }
+        let trace = load_versioned::<AuditTrace>(&AuditTrace::path(&paths, difficulty))
+            .filter(|trace| trace.seed == run_seed.0);
+        *playback = AuditPlayback {
+            key: Some(key),
+            interval: trace.as_ref().map_or(0, |trace| trace.interval),
+            levels: trace.map(|trace| trace.levels).unwrap_or_default(),
+            reported: Vec::new(),
+        };
+    }
+
+    // 结算时如果刚刚替换了幽灵轨迹，把这一局的校验和一起保存
+    fn save_audit_trace(
+        paths: Res<ProfilePaths>,
+        replay_recorder: Res<ReplayRecorder>,
+        recorder: Res<AuditRecorder>,
+        run_seed: Res<RunSeed>,
+        difficulty_settings: Res<DifficultySettings>,
+        mut playback: ResMut<AuditPlayback>,
+    ) {
+        let difficulty = difficulty_settings.difficulty.name();
+        let saved_ghost = GhostTrace::load(&paths, difficulty)
+            .is_some_and(|ghost| ghost.seed == run_seed.0 && ghost.levels == replay_recorder.levels);
+        if !saved_ghost {
+            return;
+        }
+        let trace = AuditTrace {
+            seed: run_seed.0,
+            difficulty: difficulty.to_string(),
+            interval: AUDIT_INTERVAL,
+            levels: recorder.levels.clone(),
+        };
+        if let Err(e) = save_versioned(&AuditTrace::path(&paths, difficulty), &trace) {
+            eprintln!("Failed to save determinism audit: {}", e);
+        }
+        // 下一局重新加载刚保存的记录
+        playback.key = None;
+    }
+}
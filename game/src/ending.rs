@@ -0,0 +1,670 @@
+use bevy::prelude::*;
+
+use crate::boss;
+use crate::fonts::{text_style, FontAssets, FontKind};
+use crate::gamepad::GamepadButtons;
+use crate::governor::{EffectBudget, EffectKind};
+use crate::i18n::{name_key, Locale};
+use crate::insurance;
+use crate::level_select::GameMode;
+use crate::profile::{self, RunStats};
+use crate::profiles::ProfilePaths;
+use crate::rank_result::{count_up, spawn_sparks, SparkBurst};
+use crate::replay;
+use crate::{start_new_run, DifficultySettings, GameState, Level, RunResources, Score, Settings};
+
+// 普通局打败这一关之后的首领就通关，由首领的出现间隔决定
+pub const FINAL_LEVEL: u32 = boss::FINAL_BOSS_LEVEL;
+
+const CREDITS: &str = include_str!("../assets/credits.txt");
+
+// 通关画面的统计行数
+const STAT_ROWS: usize = 5;
+// 时间线（秒）：第一行统计出现的时间、之后每行的间隔和每行数完的时长，统计数完之后多久开始滚动字幕
+const STATS_START: f32 = 1.0;
+const STAT_INTERVAL: f32 = 0.7;
+const STAT_COUNT_DURATION: f32 = 0.8;
+const CREDITS_DELAY: f32 = 0.8;
+// 字幕的可视高度、行高和滚动速度（像素），按住加速键时的倍数
+const CREDITS_HEIGHT: f32 = 180.0;
+const CREDIT_LINE_HEIGHT: f32 = 26.0;
+const CREDITS_SPEED: f32 = 40.0;
+const CREDITS_FAST: f32 = 4.0;
+// 彩纸：分几波撒下、每波的间隔和发射点数量，每个发射点的基础数量
+const CONFETTI_WAVES: usize = 6;
+const CONFETTI_INTERVAL: f32 = 0.6;
+const CONFETTI_EMITTERS: usize = 8;
+const CONFETTI_PER_EMITTER: usize = 8;
+
+const HEADING_COLOR: Color = Color::rgb(1.0, 0.85, 0.2);
+
+// 通关的彩纸：从屏幕顶部向下撒开，飘得慢、落得久
+const CONFETTI: SparkBurst = SparkBurst {
+    angle: 0.2 * std::f32::consts::PI..0.8 * std::f32::consts::PI,
+    speed: 40.0..160.0,
+    lifetime: 3.0,
+    gravity: 120.0,
+    size: Vec2::new(8.0, 5.0),
+};
+
+// 这一局是否就此通关：只有普通局有终点，单关挑战、比赛和淘汰赛照常结算
+pub fn campaign_complete(level: u32, game_mode: GameMode) -> bool {
+    game_mode == GameMode::Campaign && level >= FINAL_LEVEL
+}
+
+// 通关画面；elapsed 为时间线进行的秒数，scrolled 为字幕已经滚过的距离
+#[derive(Component, Default)]
+pub struct EndingUI {
+    elapsed: f32,
+    scrolled: f32,
+    // 已经撒下的彩纸波数
+    confetti_waves: usize,
+    selected: usize,
+}
+
+impl EndingUI {
+    // 直接跳到结尾：统计全部数完，字幕停在最后，不再撒彩纸
+    fn skip_to_end(&mut self) {
+        self.elapsed = self.elapsed.max(credits_start());
+        self.scrolled = credits_rest();
+        self.confetti_waves = CONFETTI_WAVES;
+    }
+
+    fn finished(&self) -> bool {
+        self.scrolled >= credits_rest()
+    }
+}
+
+// 一行统计，出现之前隐藏
+#[derive(Component)]
+pub struct EndingStatRow(usize);
+
+// 统计行的数值，出现后从 0 数到 value
+#[derive(Component)]
+pub struct EndingStatValue {
+    index: usize,
+    value: u32,
+    format: StatFormat,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum StatFormat {
+    Number,
+    // 秒数，显示为分:秒
+    Duration,
+}
+
+impl StatFormat {
+    fn display(&self, value: u32) -> String {
+        match self {
+            StatFormat::Number => value.to_string(),
+            StatFormat::Duration => format!("{}:{:02}", value / 60, value % 60),
+        }
+    }
+}
+
+// 字幕内容，在裁剪的可视区域里向上滚动
+#[derive(Component)]
+pub struct CreditsRoll;
+
+// 加速和跳过的提示，字幕停下后隐藏
+#[derive(Component)]
+pub struct CreditsHint;
+
+// 彩纸所在的全屏层
+#[derive(Component)]
+pub struct ConfettiLayer;
+
+// 最后的选项，字幕停下后显示
+#[derive(Component)]
+pub struct EndingOptions;
+
+#[derive(Component)]
+pub struct EndingOption(usize);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum EndingAction {
+    Submit,
+    MainMenu,
+}
+
+impl EndingAction {
+    const ALL: [EndingAction; 2] = [EndingAction::Submit, EndingAction::MainMenu];
+
+    fn label_key(&self) -> &'static str {
+        match self {
+            EndingAction::Submit => "ending.submit",
+            EndingAction::MainMenu => "ending.main_menu",
+        }
+    }
+}
+
+// 第 index 行统计出现的时间
+fn stat_reveal_at(index: usize) -> f32 {
+    STATS_START + index as f32 * STAT_INTERVAL
+}
+
+// 统计全部出现并数完之后开始滚动字幕
+fn credits_start() -> f32 {
+    stat_reveal_at(STAT_ROWS - 1) + STAT_COUNT_DURATION + CREDITS_DELAY
+}
+
+// 字幕停下时滚过的距离：从可视区域下方滚入，最后一行停在可视区域中间
+fn credits_rest() -> f32 {
+    CREDITS_HEIGHT / 2.0 + (CREDITS.lines().count() as f32 - 0.5) * CREDIT_LINE_HEIGHT
+}
+
+// 通关画面的统计：标签、数值和显示方式
+fn stat_rows(score: u32, stats: &RunStats) -> [(&'static str, u32, StatFormat); STAT_ROWS] {
+    [
+        ("ending.score", score, StatFormat::Number),
+        ("ending.time", stats.play_time as u32, StatFormat::Duration),
+        ("ending.bricks", stats.bricks_destroyed.values().sum(), StatFormat::Number),
+        ("ending.best_combo", stats.best_combo, StatFormat::Number),
+        ("ending.perfects", stats.perfect_hits, StatFormat::Number),
+    ]
+}
+
+// 进入通关画面：标题、依次出现的统计、字幕和最后的选项；减少动态效果时直接显示结尾，不撒彩纸
+pub fn setup_ending(
+    mut commands: Commands,
+    score: Res<Score>,
+    level: Res<Level>,
+    stats: Res<RunStats>,
+    difficulty_settings: Res<DifficultySettings>,
+    settings: Res<Settings>,
+    fonts: Res<FontAssets>,
+    locale: Res<Locale>,
+) {
+    let mut ending = EndingUI::default();
+    if settings.reduce_motion {
+        ending.skip_to_end();
+    }
+    let difficulty = locale.tr(&name_key("difficulty", difficulty_settings.difficulty.name()), &[]);
+
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    width: Val::Percent(100.0),
+                    height: Val::Percent(100.0),
+                    flex_direction: FlexDirection::Column,
+                    justify_content: JustifyContent::Center,
+                    align_items: AlignItems::Center,
+                    row_gap: Val::Px(6.0),
+                    ..default()
+                },
+                background_color: BackgroundColor(Color::rgb(0.05, 0.05, 0.1)),
+                ..default()
+            },
+            ending,
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                NodeBundle {
+                    style: Style {
+                        position_type: PositionType::Absolute,
+                        width: Val::Percent(100.0),
+                        height: Val::Percent(100.0),
+                        ..default()
+                    },
+                    z_index: ZIndex::Local(1),
+                    ..default()
+                },
+                ConfettiLayer,
+            ));
+
+            parent.spawn(TextBundle::from_section(
+                locale.tr("ending.title", &[]),
+                text_style(&fonts, FontKind::Bold, 54.0, HEADING_COLOR),
+            ));
+            parent.spawn(TextBundle::from_section(
+                locale.tr("ending.subtitle", &[("level", &level.0), ("difficulty", &difficulty)]),
+                text_style(&fonts, FontKind::Regular, 20.0, Color::rgb(0.7, 0.7, 0.7)),
+            ).with_style(Style {
+                margin: UiRect::bottom(Val::Px(10.0)),
+                ..default()
+            }));
+
+            for (index, (label, value, format)) in stat_rows(score.0, &stats).into_iter().enumerate() {
+                parent
+                    .spawn((
+                        NodeBundle {
+                            style: Style {
+                                width: Val::Px(320.0),
+                                justify_content: JustifyContent::SpaceBetween,
+                                ..default()
+                            },
+                            visibility: Visibility::Hidden,
+                            ..default()
+                        },
+                        EndingStatRow(index),
+                    ))
+                    .with_children(|parent| {
+                        parent.spawn(TextBundle::from_section(
+                            locale.tr(label, &[]),
+                            text_style(&fonts, FontKind::Regular, 20.0, Color::rgb(0.7, 0.7, 0.7)),
+                        ));
+                        parent.spawn((
+                            TextBundle::from_section(
+                                format.display(0),
+                                text_style(&fonts, FontKind::Mono, 20.0, Color::WHITE),
+                            ),
+                            EndingStatValue { index, value, format },
+                        ));
+                    });
+            }
+
+            // 字幕的可视区域，内容从下方滚入
+            parent
+                .spawn(NodeBundle {
+                    style: Style {
+                        width: Val::Px(500.0),
+                        height: Val::Px(CREDITS_HEIGHT),
+                        margin: UiRect::top(Val::Px(10.0)),
+                        overflow: Overflow::clip(),
+                        ..default()
+                    },
+                    ..default()
+                })
+                .with_children(|parent| {
+                    parent
+                        .spawn((
+                            NodeBundle {
+                                style: Style {
+                                    position_type: PositionType::Absolute,
+                                    top: Val::Px(CREDITS_HEIGHT),
+                                    width: Val::Percent(100.0),
+                                    flex_direction: FlexDirection::Column,
+                                    align_items: AlignItems::Center,
+                                    ..default()
+                                },
+                                ..default()
+                            },
+                            CreditsRoll,
+                        ))
+                        .with_children(|parent| {
+                            // "# " 开头的是标题行，空行只占位
+                            for line in CREDITS.lines() {
+                                let (text, style) = match line.strip_prefix("# ") {
+                                    Some(heading) => (heading, text_style(&fonts, FontKind::Bold, 20.0, HEADING_COLOR)),
+                                    None => (line, text_style(&fonts, FontKind::Regular, 18.0, Color::WHITE)),
+                                };
+                                parent.spawn(TextBundle::from_section(text, style).with_style(Style {
+                                    height: Val::Px(CREDIT_LINE_HEIGHT),
+                                    ..default()
+                                }));
+                            }
+                        });
+                });
+
+            parent.spawn((
+                TextBundle::from_section(
+                    locale.tr("ending.credits_hint", &[]),
+                    text_style(&fonts, FontKind::Regular, 16.0, Color::rgb(0.5, 0.5, 0.5)),
+                ),
+                CreditsHint,
+            ));
+
+            parent
+                .spawn((
+                    NodeBundle {
+                        style: Style {
+                            flex_direction: FlexDirection::Row,
+                            column_gap: Val::Px(20.0),
+                            margin: UiRect::top(Val::Px(10.0)),
+                            ..default()
+                        },
+                        visibility: Visibility::Hidden,
+                        ..default()
+                    },
+                    EndingOptions,
+                ))
+                .with_children(|parent| {
+                    for (index, action) in EndingAction::ALL.iter().enumerate() {
+                        parent
+                            .spawn((
+                                ButtonBundle {
+                                    style: Style {
+                                        width: Val::Px(260.0),
+                                        padding: UiRect::all(Val::Px(8.0)),
+                                        border: UiRect::all(Val::Px(2.0)),
+                                        justify_content: JustifyContent::Center,
+                                        ..default()
+                                    },
+                                    background_color: BackgroundColor(Color::rgba(0.2, 0.2, 0.25, 0.8)),
+                                    border_color: BorderColor(Color::NONE),
+                                    ..default()
+                                },
+                                EndingOption(index),
+                            ))
+                            .with_children(|parent| {
+                                parent.spawn(TextBundle::from_section(
+                                    locale.tr(action.label_key(), &[]),
+                                    text_style(&fonts, FontKind::Regular, 20.0, Color::WHITE),
+                                ));
+                            });
+                    }
+                });
+        });
+}
+
+// 推进时间线：ESC（手柄东键）跳到结尾，按住空格（手柄南键）加速字幕，开头几秒分几波撒下彩纸
+pub fn ending_timeline_system(
+    mut commands: Commands,
+    time: Res<Time>,
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    gamepad: GamepadButtons,
+    budget: Res<EffectBudget>,
+    mut ending_query: Query<&mut EndingUI>,
+    confetti_layer: Query<Entity, With<ConfettiLayer>>,
+) {
+    let Ok(mut ending) = ending_query.get_single_mut() else {
+        return;
+    };
+    let delta = time.delta_seconds();
+    ending.elapsed += delta;
+
+    if keyboard_input.just_pressed(KeyCode::Escape) || gamepad.just_pressed(GamepadButtonType::East) {
+        ending.skip_to_end();
+    }
+    if ending.elapsed >= credits_start() {
+        let fast = keyboard_input.pressed(KeyCode::Space) || gamepad.pressed(GamepadButtonType::South);
+        let speed = if fast { CREDITS_SPEED * CREDITS_FAST } else { CREDITS_SPEED };
+        ending.scrolled = (ending.scrolled + speed * delta).min(credits_rest());
+    }
+
+    let Ok(layer) = confetti_layer.get_single() else {
+        return;
+    };
+    while ending.confetti_waves < CONFETTI_WAVES && ending.elapsed >= ending.confetti_waves as f32 * CONFETTI_INTERVAL {
+        ending.confetti_waves += 1;
+        let count = budget.particle_count(EffectKind::Burst, CONFETTI_PER_EMITTER);
+        commands.entity(layer).with_children(|parent| {
+            // 发射点沿顶边均匀分布，相邻两波错开半个间距
+            let stagger = if ending.confetti_waves % 2 == 0 { 0.75 } else { 0.25 };
+            for emitter in 0..CONFETTI_EMITTERS {
+                let x = (emitter as f32 + stagger) / CONFETTI_EMITTERS as f32 * 100.0;
+                parent
+                    .spawn(NodeBundle {
+                        style: Style {
+                            position_type: PositionType::Absolute,
+                            left: Val::Percent(x),
+                            top: Val::Px(0.0),
+                            ..default()
+                        },
+                        ..default()
+                    })
+                    .with_children(|parent| spawn_sparks(parent, &CONFETTI, count));
+            }
+        });
+    }
+}
+
+// 按时间线显示统计、滚动字幕，字幕停下后显示选项
+pub fn show_ending(
+    ending_query: Query<&EndingUI>,
+    mut rows: Query<(&EndingStatRow, &mut Visibility), (Without<CreditsHint>, Without<EndingOptions>)>,
+    mut values: Query<(&EndingStatValue, &mut Text)>,
+    mut credits: Query<&mut Style, With<CreditsRoll>>,
+    mut hint: Query<&mut Visibility, (With<CreditsHint>, Without<EndingStatRow>, Without<EndingOptions>)>,
+    mut options: Query<&mut Visibility, (With<EndingOptions>, Without<EndingStatRow>, Without<CreditsHint>)>,
+) {
+    let Ok(ending) = ending_query.get_single() else {
+        return;
+    };
+    for (row, mut visibility) in rows.iter_mut() {
+        if ending.elapsed >= stat_reveal_at(row.0) {
+            *visibility = Visibility::Inherited;
+        }
+    }
+    for (stat, mut text) in values.iter_mut() {
+        let t = (ending.elapsed - stat_reveal_at(stat.index)) / STAT_COUNT_DURATION;
+        text.sections[0].value = stat.format.display(count_up(0, stat.value, t));
+    }
+    for mut style in credits.iter_mut() {
+        style.top = Val::Px(CREDITS_HEIGHT - ending.scrolled);
+    }
+    let finished = ending.finished();
+    for mut visibility in hint.iter_mut() {
+        *visibility = if finished { Visibility::Hidden } else { Visibility::Inherited };
+    }
+    for mut visibility in options.iter_mut() {
+        *visibility = if finished { Visibility::Inherited } else { Visibility::Hidden };
+    }
+}
+
+// 最后的选项：提交分数进入结算界面，或者只在本机结算后回到主菜单
+pub fn ending_menu_system(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    gamepad: GamepadButtons,
+    paths: Res<ProfilePaths>,
+    mut next_state: ResMut<NextState<GameState>>,
+    mut run: RunResources,
+    mut ending_query: Query<&mut EndingUI>,
+    mut option_query: Query<(Ref<Interaction>, &EndingOption, &mut BorderColor)>,
+) {
+    let Ok(mut ending) = ending_query.get_single_mut() else {
+        return;
+    };
+    if !ending.finished() {
+        return;
+    }
+    let option_count = EndingAction::ALL.len();
+    let mut chosen = None;
+
+    if keyboard_input.just_pressed(KeyCode::ArrowLeft) || gamepad.just_pressed(GamepadButtonType::DPadLeft) {
+        ending.selected = (ending.selected + option_count - 1) % option_count;
+    } else if keyboard_input.just_pressed(KeyCode::ArrowRight) || gamepad.just_pressed(GamepadButtonType::DPadRight) {
+        ending.selected = (ending.selected + 1) % option_count;
+    } else if keyboard_input.just_pressed(KeyCode::Enter)
+        || keyboard_input.just_pressed(KeyCode::Space)
+        || gamepad.just_pressed(GamepadButtonType::South)
+    {
+        chosen = Some(ending.selected);
+    }
+
+    for (interaction, option, _) in option_query.iter() {
+        match *interaction {
+            Interaction::Pressed => chosen = Some(option.0),
+            Interaction::Hovered if interaction.is_changed() => ending.selected = option.0,
+            _ => {}
+        }
+    }
+    for (_, option, mut border_color) in option_query.iter_mut() {
+        border_color.0 = if option.0 == ending.selected {
+            Color::rgb(1.0, 1.0, 0.0)
+        } else {
+            Color::NONE
+        };
+    }
+
+    let Some(index) = chosen else {
+        return;
+    };
+    match EndingAction::ALL[index] {
+        // 结算界面保存档案并提交分数
+        EndingAction::Submit => next_state.set(GameState::GameOver),
+        // 不提交，但幽灵轨迹、档案统计和保险代币照常在本机结算
+        EndingAction::MainMenu => {
+            if !run.run_submitted.0 {
+                replay::save_ghost_if_best(&paths, &run.recorder, &run.run_seed, &run.score, &run.difficulty_settings);
+                profile::record_run(&paths, &run.stats, run.score.0, run.level.0, run.difficulty_settings.difficulty);
+                insurance::settle_run(&paths, run.score.0, *run.insurance);
+            }
+            start_new_run(&mut run, None);
+            next_state.set(GameState::MainMenu);
+        }
+    }
+}
+
+// 离开通关画面时清除所有界面，包括还在飘落的彩纸
+pub fn cleanup_ending(mut commands: Commands, query: Query<Entity, With<EndingUI>>) {
+    for entity in query.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::boss::BossStage;
+    use crate::i18n::Language;
+    use crate::rank_result::CelebrationSpark;
+    use crate::Difficulty;
+    use std::time::Duration;
+
+    // 每帧 0.1 秒
+    const FRAME: f32 = 0.1;
+
+    // 只有时间线和显示两个系统；最后的选项会重置整局，由游戏里的状态流程负责
+    fn ending_app(reduce_motion: bool) -> App {
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, AssetPlugin::default()))
+            .init_asset::<Font>()
+            .insert_resource(Locale::new(Language::En))
+            .insert_resource(bevy::time::TimeUpdateStrategy::ManualDuration(Duration::from_secs_f32(FRAME)))
+            .init_state::<GameState>()
+            .init_resource::<ButtonInput<KeyCode>>()
+            .init_resource::<Gamepads>()
+            .init_resource::<ButtonInput<GamepadButton>>()
+            .init_resource::<EffectBudget>()
+            .insert_resource(Score(12_345))
+            .insert_resource(Level(FINAL_LEVEL))
+            .insert_resource(RunStats {
+                play_time: 754.0,
+                best_combo: 17,
+                perfect_hits: 9,
+                ..default()
+            })
+            .insert_resource(DifficultySettings::new(Difficulty::Medium))
+            .insert_resource(Settings {
+                reduce_motion,
+                ..default()
+            })
+            .add_systems(OnEnter(GameState::GameComplete), setup_ending)
+            .add_systems(
+                Update,
+                (ending_timeline_system, show_ending).chain().run_if(in_state(GameState::GameComplete)),
+            )
+            .add_systems(OnExit(GameState::GameComplete), cleanup_ending);
+        app.init_resource::<FontAssets>();
+        app.world.resource_mut::<NextState<GameState>>().set(GameState::GameComplete);
+        app
+    }
+
+    // 跑到时间线的 seconds 秒
+    fn run_until(app: &mut App, seconds: f32) {
+        while app.world.query::<&EndingUI>().get_single(&app.world).map_or(true, |ending| ending.elapsed < seconds) {
+            app.update();
+        }
+    }
+
+    fn shown_values(app: &mut App) -> Vec<String> {
+        let mut values: Vec<(usize, String)> = app
+            .world
+            .query::<(&EndingStatValue, &Text)>()
+            .iter(&app.world)
+            .map(|(stat, text)| (stat.index, text.sections[0].value.clone()))
+            .collect();
+        values.sort();
+        values.into_iter().map(|(_, value)| value).collect()
+    }
+
+    fn visible_rows(app: &mut App) -> usize {
+        app.world
+            .query::<(&EndingStatRow, &Visibility)>()
+            .iter(&app.world)
+            .filter(|(_, visibility)| **visibility != Visibility::Hidden)
+            .count()
+    }
+
+    fn options_shown(app: &mut App) -> bool {
+        *app.world.query_filtered::<&Visibility, With<EndingOptions>>().single(&app.world) != Visibility::Hidden
+    }
+
+    fn confetti(app: &mut App) -> usize {
+        app.world.query::<&CelebrationSpark>().iter(&app.world).count()
+    }
+
+    const FINAL_VALUES: [&str; 5] = ["12345", "12:34", "0", "17", "9"];
+
+    #[test]
+    fn the_final_boss_ends_only_the_campaign() {
+        assert!(BossStage::due_after(FINAL_LEVEL));
+        assert!(campaign_complete(FINAL_LEVEL, GameMode::Campaign));
+        assert!(!campaign_complete(FINAL_LEVEL - 5, GameMode::Campaign));
+        assert!(!campaign_complete(FINAL_LEVEL - 1, GameMode::Campaign));
+        assert!(!campaign_complete(FINAL_LEVEL, GameMode::SingleLevel));
+        assert!(!campaign_complete(FINAL_LEVEL, GameMode::Race));
+        assert!(!campaign_complete(FINAL_LEVEL, GameMode::Tournament));
+    }
+
+    #[test]
+    fn stats_count_up_one_by_one_then_credits_roll_into_the_options() {
+        let mut app = ending_app(false);
+        app.update();
+        assert_eq!(visible_rows(&mut app), 0);
+        assert!(confetti(&mut app) > 0, "confetti should start with the title");
+
+        // 第一行出现后正在数，第二行还没出现
+        run_until(&mut app, stat_reveal_at(0) + STAT_COUNT_DURATION / 2.0);
+        assert_eq!(visible_rows(&mut app), 1);
+        let score: u32 = shown_values(&mut app)[0].parse().unwrap();
+        assert!(score > 0 && score < 12_345, "score should be counting up, showed {}", score);
+
+        run_until(&mut app, credits_start());
+        assert_eq!(visible_rows(&mut app), STAT_ROWS);
+        assert_eq!(shown_values(&mut app), FINAL_VALUES);
+        assert!(!options_shown(&mut app));
+
+        // 字幕按正常速度滚完之后才显示选项
+        let roll = credits_rest() / CREDITS_SPEED;
+        run_until(&mut app, credits_start() + roll - 2.0 * FRAME);
+        assert!(!options_shown(&mut app));
+        run_until(&mut app, credits_start() + roll + FRAME);
+        assert!(options_shown(&mut app));
+    }
+
+    #[test]
+    fn holding_space_speeds_up_the_credits() {
+        let mut app = ending_app(false);
+        run_until(&mut app, credits_start());
+        app.world.resource_mut::<ButtonInput<KeyCode>>().press(KeyCode::Space);
+        let roll = credits_rest() / (CREDITS_SPEED * CREDITS_FAST);
+        run_until(&mut app, credits_start() + roll + FRAME);
+        assert!(options_shown(&mut app));
+    }
+
+    #[test]
+    fn escape_skips_to_the_end() {
+        let mut app = ending_app(false);
+        run_until(&mut app, FRAME);
+        app.world.resource_mut::<ButtonInput<KeyCode>>().press(KeyCode::Escape);
+        app.update();
+        assert_eq!(visible_rows(&mut app), STAT_ROWS);
+        assert_eq!(shown_values(&mut app), FINAL_VALUES);
+        assert!(options_shown(&mut app));
+    }
+
+    #[test]
+    fn reduce_motion_shows_everything_at_once_without_confetti() {
+        let mut app = ending_app(true);
+        app.update();
+        assert_eq!(visible_rows(&mut app), STAT_ROWS);
+        assert_eq!(shown_values(&mut app), FINAL_VALUES);
+        assert!(options_shown(&mut app));
+        assert_eq!(confetti(&mut app), 0);
+    }
+
+    #[test]
+    fn leaving_the_ending_removes_everything() {
+        let mut app = ending_app(false);
+        run_until(&mut app, CONFETTI_INTERVAL * 2.0);
+        assert!(confetti(&mut app) > 0);
+        app.world.resource_mut::<NextState<GameState>>().set(GameState::MainMenu);
+        app.update();
+        assert_eq!(confetti(&mut app), 0);
+        assert_eq!(app.world.query::<&Node>().iter(&app.world).count(), 0);
+    }
+}
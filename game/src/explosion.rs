@@ -0,0 +1,368 @@
+use bevy::prelude::*;
+use std::collections::VecDeque;
+
+use crate::bonus::BonusStage;
+use crate::cosmetics::Cosmetics;
+use crate::curse::Curse;
+use crate::fonts::{text_style, FontAssets, FontKind};
+use crate::game_rng::GameRng;
+use crate::governor::EffectBudget;
+use crate::hazard_rules::LastPaddleContact;
+use crate::i18n::Locale;
+use crate::launch::HeldBall;
+use crate::safe_despawn::SafeDespawnExt;
+use crate::shield::{GeneratorChain, Shield};
+use crate::team_colors::{HotTeam, TeamTint};
+use crate::{
+    drop_certain_powerup, spawn_particles, split_ball, Ball, Brick, BrickType, EventRun, GameEntity, GameplayEvent, Score,
+    BRICK_SIZE,
+};
+
+// 爆炸砖的爆炸半径（砖块中心之间的距离），默认炸到上下左右相邻的砖块
+pub const EXPLOSION_RADIUS: f32 = BRICK_SIZE.x * 1.2;
+// 火球打碎砖块时波及的半径
+const FIREBALL_RADIUS: f32 = BRICK_SIZE.x;
+// 一轮爆炸打碎这么多块砖时算作连锁，引发爆炸的球分裂成两颗
+pub const CHAIN_SPLIT_LENGTH: u32 = 6;
+// 分裂时两颗球各自偏转的角度（弧度）
+const CHAIN_SPLIT_ANGLE: f32 = 0.25;
+// "CHAIN xN" 提示的持续时间和上升速度；连锁越长字越大
+const CHAIN_POPUP_LIFETIME: f32 = 1.2;
+const CHAIN_POPUP_RISE_SPEED: f32 = 40.0;
+const CHAIN_POPUP_BASE_SIZE: f32 = 26.0;
+const CHAIN_POPUP_MAX_SIZE: f32 = 48.0;
+
+// 引发爆炸的来源：球和激光引发的连锁有奖励，定时炸弹是惩罚，不奖励
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExplosionSource {
+    Ball(Entity),
+    Laser,
+    Bomb,
+}
+
+// 一次爆炸：半径内的砖块受到 1 点伤害，打碎的砖块按 score_percent 计分
+#[derive(Event, Debug, Clone, Copy)]
+pub struct Explosion {
+    pub center: Vec3,
+    pub radius: f32,
+    pub score_percent: u32,
+    // 打碎的砖块是否计入连击
+    pub combo: bool,
+    pub source: ExplosionSource,
+}
+
+impl Explosion {
+    // 爆炸砖被打碎
+    pub fn brick(center: Vec3, combo: bool, source: ExplosionSource) -> Self {
+        Self {
+            center,
+            radius: EXPLOSION_RADIUS,
+            score_percent: 100,
+            combo,
+            source,
+        }
+    }
+
+    // 火球打碎砖块后的波及
+    pub fn fireball(center: Vec3, combo: bool, ball: Entity) -> Self {
+        Self {
+            center,
+            radius: FIREBALL_RADIUS,
+            score_percent: 100,
+            combo,
+            source: ExplosionSource::Ball(ball),
+        }
+    }
+}
+
+// 一轮爆炸：本帧的一次初始爆炸和它连锁引发的所有爆炸
+struct Wave {
+    source: ExplosionSource,
+    center: Vec3,
+    destroyed: u32,
+}
+
+// 连锁提示文字
+#[derive(Component)]
+pub struct ChainPopup {
+    age: f32,
+}
+
+// 结算本帧的所有爆炸。炸碎的爆炸砖加入队列继续爆炸，按队列逐个处理而不是递归；
+// 已经打碎的砖块不会再受伤，所以每块爆炸砖只会爆炸一次，连锁一定会结束。
+// 不可破坏和受护盾保护的砖块不受影响；炸碎的砖块计分、生成粒子，只掉落保底道具和金砖的道具。
+// 每轮爆炸分别统计打碎的砖块数，达到 CHAIN_SPLIT_LENGTH 时奖励一次分裂（每轮最多一次）
+pub fn resolve_explosions(
+    mut commands: Commands,
+    mut explosions: EventReader<Explosion>,
+    mut bricks: Query<(Entity, &Transform, &mut Brick, Option<&Shield>, Option<&TeamTint>)>,
+    mut balls: Query<(Entity, &Transform, &mut Ball, Option<&LastPaddleContact>), (Without<HeldBall>, Without<Brick>)>,
+    all_balls: Query<(), With<Ball>>,
+    mut score: ResMut<Score>,
+    (bonus, chain, hot_team, budget): (Res<BonusStage>, Res<GeneratorChain>, Res<HotTeam>, Res<EffectBudget>),
+    (event_run, curse, cosmetics): (Res<EventRun>, Res<Curse>, Res<Cosmetics>),
+    mut gameplay_events: EventWriter<GameplayEvent>,
+    mut game_rng: ResMut<GameRng>,
+) {
+    let mut waves = Vec::new();
+    let mut queue: VecDeque<(usize, Explosion)> = VecDeque::new();
+    for explosion in explosions.read() {
+        queue.push_back((waves.len(), *explosion));
+        waves.push(Wave {
+            source: explosion.source,
+            center: explosion.center,
+            destroyed: 0,
+        });
+    }
+    let multiplier = bonus.score_multiplier() * chain.score_multiplier();
+    while let Some((wave, explosion)) = queue.pop_front() {
+        for (entity, transform, mut brick, shield, tint) in bricks.iter_mut() {
+            if brick.health <= 0
+                || matches!(brick.brick_type, BrickType::Unbreakable)
+                || chain.deflects(shield).is_some()
+                || transform.translation.truncate().distance(explosion.center.truncate()) > explosion.radius
+            {
+                continue;
+            }
+
+            brick.health -= 1;
+            if brick.health > 0 {
+                gameplay_events.send(GameplayEvent::BrickDamaged {
+                    position: transform.translation,
+                });
+                brick.shade *= 0.8;
+                continue;
+            }
+
+            commands.safe_despawn_recursive(entity);
+            waves[wave].destroyed += 1;
+            let points = curse.apply(hot_team.apply(brick.points() * multiplier, tint) * explosion.score_percent / 100);
+            score.0 += points;
+            spawn_particles(&mut commands, &budget, transform.translation, transform.scale);
+            gameplay_events.send(GameplayEvent::BrickDestroyed {
+                brick_type: brick.brick_type,
+                points,
+                position: transform.translation,
+                combo: explosion.combo,
+            });
+            drop_certain_powerup(&mut commands, &mut game_rng, &brick, transform.translation, event_run.lasers_enabled());
+            if brick.brick_type == BrickType::Explosive {
+                queue.push_back((
+                    wave,
+                    Explosion {
+                        center: transform.translation,
+                        radius: EXPLOSION_RADIUS,
+                        ..explosion
+                    },
+                ));
+            }
+        }
+    }
+
+    // 分裂出的球在所有爆炸结算完之后才生成，本轮不会再触发新的连锁
+    let mut ball_count = all_balls.iter().count();
+    for wave in waves.iter().filter(|wave| wave.destroyed >= CHAIN_SPLIT_LENGTH) {
+        gameplay_events.send(GameplayEvent::ChainReaction {
+            position: wave.center,
+            length: wave.destroyed,
+        });
+        let splits = budget.ball_splits(1, ball_count);
+        if wave.source == ExplosionSource::Bomb || splits == 0 {
+            continue;
+        }
+        // 引发爆炸的球已经不在场上，或者连锁由激光引发时，分裂场上的任意一颗球
+        let source = match wave.source {
+            ExplosionSource::Ball(entity) if balls.contains(entity) => Some(entity),
+            _ => balls.iter().next().map(|(entity, ..)| entity),
+        };
+        let Some(Ok((_, transform, mut ball, contact))) = source.map(|entity| balls.get_mut(entity)) else {
+            continue;
+        };
+        // 分成两颗：原来的球向一侧偏转，新球向另一侧偏转
+        let velocity = ball.velocity;
+        ball.velocity = Vec2::from_angle(-CHAIN_SPLIT_ANGLE).rotate(velocity);
+        let twin = Ball {
+            velocity: Vec2::from_angle(CHAIN_SPLIT_ANGLE).rotate(velocity),
+        };
+        split_ball(&mut commands, transform, &twin, contact, splits, cosmetics.ball);
+        ball_count += splits;
+    }
+}
+
+// 连锁时在爆炸中心显示 "CHAIN xN"
+pub fn show_chain_popups(
+    mut commands: Commands,
+    mut gameplay_events: EventReader<GameplayEvent>,
+    fonts: Res<FontAssets>,
+    locale: Res<Locale>,
+) {
+    for event in gameplay_events.read() {
+        let GameplayEvent::ChainReaction { position, length } = *event else {
+            continue;
+        };
+        let size = (CHAIN_POPUP_BASE_SIZE + 2.0 * (length - CHAIN_SPLIT_LENGTH) as f32).min(CHAIN_POPUP_MAX_SIZE);
+        commands.spawn((
+            Text2dBundle {
+                text: Text::from_section(
+                    locale.tr("popup.chain", &[("length", &length)]),
+                    text_style(&fonts, FontKind::Bold, size, Color::rgb(1.0, 0.55, 0.15)),
+                ),
+                transform: Transform::from_translation(position.truncate().extend(5.0)),
+                ..default()
+            },
+            ChainPopup { age: 0.0 },
+            GameEntity,
+        ));
+    }
+}
+
+// 提示文字上升并淡出
+pub fn animate_chain_popups(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut popups: Query<(Entity, &mut ChainPopup, &mut Transform, &mut Text)>,
+) {
+    for (entity, mut popup, mut transform, mut text) in popups.iter_mut() {
+        popup.age += time.delta_seconds();
+        if popup.age >= CHAIN_POPUP_LIFETIME {
+            commands.safe_despawn(entity);
+            continue;
+        }
+        transform.translation.y += CHAIN_POPUP_RISE_SPEED * time.delta_seconds();
+        for section in text.sections.iter_mut() {
+            section.style.color.set_a(1.0 - popup.age / CHAIN_POPUP_LIFETIME);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bevy::ecs::system::RunSystemOnce;
+
+    fn explosion_app() -> App {
+        let mut app = App::new();
+        app.add_event::<Explosion>()
+            .add_event::<GameplayEvent>()
+            .insert_resource(Score(0))
+            .init_resource::<BonusStage>()
+            .init_resource::<GeneratorChain>()
+            .init_resource::<HotTeam>()
+            .init_resource::<EffectBudget>()
+            .init_resource::<EventRun>()
+            .init_resource::<Curse>()
+            .init_resource::<GameRng>()
+            .init_resource::<Cosmetics>();
+        app
+    }
+
+    // 从 x 开始向右排 count 块相邻的爆炸砖
+    fn spawn_explosive_row(app: &mut App, x: f32, y: f32, count: usize) -> Vec3 {
+        for i in 0..count {
+            app.world.spawn((
+                Transform::from_xyz(x + i as f32 * BRICK_SIZE.x, y, 0.0),
+                Brick {
+                    brick_type: BrickType::Explosive,
+                    health: 1,
+                    shade: 1.0,
+                    guaranteed_drop: None,
+                    golden: false,
+                },
+            ));
+        }
+        Vec3::new(x - BRICK_SIZE.x, y, 0.0)
+    }
+
+    fn spawn_ball(app: &mut App, velocity: Vec2) -> Entity {
+        app.world.spawn((Transform::default(), Ball { velocity })).id()
+    }
+
+    fn resolve(app: &mut App, explosions: Vec<Explosion>) -> Vec<u32> {
+        for explosion in explosions {
+            app.world.send_event(explosion);
+        }
+        app.world.run_system_once(resolve_explosions);
+        app.world.resource_mut::<Events<Explosion>>().update();
+        let events = app.world.resource::<Events<GameplayEvent>>();
+        let chains = events
+            .get_reader()
+            .read(events)
+            .filter_map(|event| match event {
+                GameplayEvent::ChainReaction { length, .. } => Some(*length),
+                _ => None,
+            })
+            .collect();
+        app.world.resource_mut::<Events<GameplayEvent>>().clear();
+        chains
+    }
+
+    fn ball_velocities(app: &mut App) -> Vec<Vec2> {
+        let mut velocities: Vec<Vec2> = app.world.query::<&Ball>().iter(&app.world).map(|ball| ball.velocity).collect();
+        velocities.sort_by(|a, b| a.x.total_cmp(&b.x));
+        velocities
+    }
+
+    fn assert_close(actual: Vec2, expected: Vec2) {
+        assert!((actual - expected).length() < 1e-3, "{:?} != {:?}", actual, expected);
+    }
+
+    #[test]
+    fn long_chain_splits_the_triggering_ball_once() {
+        let mut app = explosion_app();
+        let start = spawn_explosive_row(&mut app, 0.0, 0.0, CHAIN_SPLIT_LENGTH as usize);
+        let ball = spawn_ball(&mut app, Vec2::new(0.0, 300.0));
+        assert_eq!(resolve(&mut app, vec![Explosion::brick(start, true, ExplosionSource::Ball(ball))]), vec![6]);
+
+        let velocities = ball_velocities(&mut app);
+        assert_eq!(velocities.len(), 2);
+        let velocity = Vec2::new(0.0, 300.0);
+        assert_close(velocities[0], Vec2::from_angle(CHAIN_SPLIT_ANGLE).rotate(velocity));
+        assert_close(velocities[1], Vec2::from_angle(-CHAIN_SPLIT_ANGLE).rotate(velocity));
+        assert!(app.world.resource::<Score>().0 > 0);
+    }
+
+    #[test]
+    fn short_chain_is_not_rewarded() {
+        let mut app = explosion_app();
+        let start = spawn_explosive_row(&mut app, 0.0, 0.0, CHAIN_SPLIT_LENGTH as usize - 1);
+        let ball = spawn_ball(&mut app, Vec2::new(0.0, 300.0));
+        assert!(resolve(&mut app, vec![Explosion::brick(start, true, ExplosionSource::Ball(ball))]).is_empty());
+        assert_eq!(ball_velocities(&mut app).len(), 1);
+    }
+
+    #[test]
+    fn each_wave_splits_at_most_once() {
+        let mut app = explosion_app();
+        // 两排相距很远，是两轮互不相连的爆炸；第一轮 12 块也只分裂一次
+        let first = spawn_explosive_row(&mut app, 0.0, 0.0, 12);
+        let second = spawn_explosive_row(&mut app, 0.0, 200.0, 6);
+        let ball = spawn_ball(&mut app, Vec2::new(0.0, 300.0));
+        let chains = resolve(
+            &mut app,
+            vec![
+                Explosion::brick(first, true, ExplosionSource::Ball(ball)),
+                Explosion::brick(second, true, ExplosionSource::Laser),
+            ],
+        );
+        assert_eq!(chains, vec![12, 6]);
+        assert_eq!(ball_velocities(&mut app).len(), 3);
+    }
+
+    #[test]
+    fn laser_chain_splits_a_ball_in_play() {
+        let mut app = explosion_app();
+        let start = spawn_explosive_row(&mut app, 0.0, 0.0, 6);
+        spawn_ball(&mut app, Vec2::new(100.0, 200.0));
+        assert_eq!(resolve(&mut app, vec![Explosion::brick(start, true, ExplosionSource::Laser)]), vec![6]);
+        assert_eq!(ball_velocities(&mut app).len(), 2);
+    }
+
+    #[test]
+    fn bomb_chain_counts_but_does_not_split() {
+        let mut app = explosion_app();
+        let start = spawn_explosive_row(&mut app, 0.0, 0.0, 6);
+        spawn_ball(&mut app, Vec2::new(0.0, 300.0));
+        assert_eq!(resolve(&mut app, vec![Explosion::brick(start, false, ExplosionSource::Bomb)]), vec![6]);
+        assert_eq!(ball_velocities(&mut app).len(), 1);
+    }
+}
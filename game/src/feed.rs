@@ -0,0 +1,152 @@
+use bevy::prelude::*;
+use std::collections::VecDeque;
+
+use crate::fonts::{text_style, FontAssets, FontKind};
+use crate::i18n::{name_key, Locale};
+use crate::{GameEntity, GameplayEvent, Settings};
+
+// 同时显示的最多条数
+const FEED_MAX_LINES: usize = 4;
+// 每条显示时长、滑入与淡出时间（秒）
+const FEED_LIFETIME: f32 = 2.5;
+const FEED_SLIDE_TIME: f32 = 0.2;
+const FEED_FADE_TIME: f32 = 0.5;
+// 滑入起始偏移
+const FEED_SLIDE_DISTANCE: f32 = 30.0;
+
+struct FeedEntry {
+    text: String,
+    color: Color,
+    age: f32,
+}
+
+// 最近发生的事件，超过上限时丢弃最旧的
+#[derive(Resource, Default)]
+pub struct EventFeed {
+    entries: VecDeque<FeedEntry>,
+}
+
+impl EventFeed {
+    fn push(&mut self, text: String, color: Color) {
+        if self.entries.len() == FEED_MAX_LINES {
+            self.entries.pop_front();
+        }
+        self.entries.push_back(FeedEntry { text, color, age: 0.0 });
+    }
+
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+}
+
+// 事件栏中的一行
+#[derive(Component)]
+pub struct FeedLine(usize);
+
+// 在分数下方生成事件栏
+pub fn spawn_event_feed(commands: &mut Commands, fonts: &FontAssets) {
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    top: Val::Px(50.0),
+                    left: Val::Px(10.0),
+                    flex_direction: FlexDirection::Column,
+                    row_gap: Val::Px(2.0),
+                    ..default()
+                },
+                ..default()
+            },
+            GameEntity,
+        ))
+        .with_children(|parent| {
+            for index in 0..FEED_MAX_LINES {
+                parent.spawn((
+                    TextBundle::from_section("", text_style(fonts, FontKind::Regular, 18.0, Color::WHITE)),
+                    FeedLine(index),
+                ));
+            }
+        });
+}
+
+// 把值得一提的游戏事件写入事件栏
+pub fn collect_feed_events(
+    mut events: EventReader<GameplayEvent>,
+    mut feed: ResMut<EventFeed>,
+    settings: Res<Settings>,
+    locale: Res<Locale>,
+) {
+    for event in events.read() {
+        if !settings.event_feed {
+            continue;
+        }
+        match event {
+            GameplayEvent::BrickDestroyed { brick_type, points, .. } => feed.push(
+                locale.tr(
+                    "feed.brick_destroyed",
+                    &[("brick", &locale.tr(&name_key("brick", brick_type.name()), &[])), ("points", points)],
+                ),
+                Color::rgb(1.0, 0.9, 0.6),
+            ),
+            GameplayEvent::PowerUpCollected { power_type, .. } => feed.push(
+                locale.tr(
+                    "feed.powerup_collected",
+                    &[("powerup", &locale.tr(&name_key("powerup", power_type.name()), &[]).to_uppercase())],
+                ),
+                Color::rgb(0.5, 0.9, 1.0),
+            ),
+            GameplayEvent::WardGained => feed.push(locale.tr("feed.ward_gained", &[]), Color::rgb(0.6, 0.85, 1.0)),
+            GameplayEvent::DebuffWarded { power_type, .. } => feed.push(
+                locale.tr(
+                    "feed.debuff_warded",
+                    &[("powerup", &locale.tr(&name_key("powerup", power_type.name()), &[]).to_uppercase())],
+                ),
+                Color::rgb(0.6, 0.85, 1.0),
+            ),
+            GameplayEvent::LifeLost => feed.push(locale.tr("feed.life_lost", &[]), Color::rgb(1.0, 0.4, 0.4)),
+            GameplayEvent::InsuranceUsed => feed.push(locale.tr("feed.insurance_used", &[]), Color::rgb(1.0, 0.8, 0.3)),
+            GameplayEvent::BombDetonated { penalty, .. } => feed.push(
+                locale.tr("feed.bomb_detonated", &[("points", penalty)]),
+                Color::rgb(1.0, 0.35, 0.2),
+            ),
+            GameplayEvent::BossDefeated { bonus, .. } => feed.push(
+                locale.tr("feed.boss_defeated", &[("points", bonus)]),
+                Color::rgb(1.0, 0.85, 0.2),
+            ),
+            _ => {}
+        }
+    }
+}
+
+// 推进事件栏并刷新显示（只在游戏进行中运行，暂停时条目不会过期）
+pub fn update_event_feed(
+    time: Res<Time>,
+    mut feed: ResMut<EventFeed>,
+    settings: Res<Settings>,
+    mut line_query: Query<(&FeedLine, &mut Text, &mut Style)>,
+) {
+    crate::perf::perf_scope!("update_event_feed");
+    for entry in feed.entries.iter_mut() {
+        entry.age += time.delta_seconds();
+    }
+    feed.entries.retain(|entry| entry.age < FEED_LIFETIME);
+    if !settings.event_feed {
+        feed.clear();
+    }
+
+    for (line, mut text, mut style) in line_query.iter_mut() {
+        let Some(entry) = feed.entries.get(line.0) else {
+            text.sections[0].value.clear();
+            continue;
+        };
+
+        let slide = (entry.age / FEED_SLIDE_TIME).min(1.0);
+        let alpha = ((FEED_LIFETIME - entry.age) / FEED_FADE_TIME).min(1.0);
+        style.left = Val::Px(-FEED_SLIDE_DISTANCE * (1.0 - slide));
+        if text.sections[0].value != entry.text {
+            text.sections[0].value = entry.text.clone();
+        }
+        text.sections[0].style.color = entry.color.with_a(alpha);
+    }
+}
@@ -0,0 +1,94 @@
+use bevy::prelude::*;
+use bevy::transform::TransformSystem;
+
+use crate::{Ball, GameState, Laser, PowerUp};
+
+// 球、激光和道具的移动与碰撞按这个频率的固定帧运行，反弹和穿透不再随显示器刷新率变化
+pub const PHYSICS_HZ: f64 = 120.0;
+
+// 固定帧之间的平滑显示。固定帧里 Transform 是物理位置；渲染前换成上一固定帧和这一固定帧之间的插值，
+// 下一个固定帧开始时再换回物理位置。固定帧以外的系统直接移动了物体（停在挡板上的球、暂停后的纠正）时不插值，直接跳过去
+pub struct FixedStepPlugin;
+
+impl Plugin for FixedStepPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(Time::<Fixed>::from_hz(PHYSICS_HZ))
+            .add_systems(FixedFirst, restore_physics_positions)
+            .add_systems(FixedLast, record_physics_positions)
+            .add_systems(
+                PostUpdate,
+                (attach_interpolation, interpolate_transforms).before(TransformSystem::TransformPropagate),
+            );
+    }
+}
+
+// 一帧里可能运行好几个固定帧，而状态切换要到下一帧才生效。已经决定切换（丢掉最后一条命、过关、时间用完）后
+// 这一帧剩下的固定帧不再运行，事件不会重复发送
+pub fn no_transition_pending(next_state: Res<NextState<GameState>>) -> bool {
+    next_state.0.is_none()
+}
+
+#[derive(Component)]
+pub struct Interpolated {
+    previous: Vec3,
+    current: Vec3,
+    // 上一次留在 Transform 里的位置；和它不同说明物体被固定帧以外的系统移动过
+    rendered: Vec3,
+}
+
+impl Interpolated {
+    fn at(translation: Vec3) -> Self {
+        Self {
+            previous: translation,
+            current: translation,
+            rendered: translation,
+        }
+    }
+}
+
+// 新生成的球、激光和道具从生成的位置开始插值
+fn attach_interpolation(
+    mut commands: Commands,
+    query: Query<(Entity, &Transform), (Or<(With<Ball>, With<Laser>, With<PowerUp>)>, Without<Interpolated>)>,
+) {
+    for (entity, transform) in query.iter() {
+        commands.entity(entity).insert(Interpolated::at(transform.translation));
+    }
+}
+
+// 固定帧开始：换回物理位置，它也是这一帧插值的起点
+fn restore_physics_positions(mut query: Query<(&mut Transform, &mut Interpolated)>) {
+    for (mut transform, mut interpolated) in query.iter_mut() {
+        if transform.translation != interpolated.rendered {
+            interpolated.current = transform.translation;
+        }
+        if transform.translation != interpolated.current {
+            transform.translation = interpolated.current;
+        }
+        interpolated.previous = interpolated.current;
+    }
+}
+
+// 固定帧结束：记下这一帧的物理位置。之后到渲染前运行的系统看到的仍是物理位置
+fn record_physics_positions(mut query: Query<(&Transform, &mut Interpolated)>) {
+    for (transform, mut interpolated) in query.iter_mut() {
+        interpolated.current = transform.translation;
+        interpolated.rendered = transform.translation;
+    }
+}
+
+// 渲染前按距离下一个固定帧的进度插值
+fn interpolate_transforms(fixed_time: Res<Time<Fixed>>, mut query: Query<(&mut Transform, &mut Interpolated)>) {
+    let alpha = fixed_time.overstep_fraction();
+    for (mut transform, mut interpolated) in query.iter_mut() {
+        if transform.translation != interpolated.rendered {
+            interpolated.previous = transform.translation;
+            interpolated.current = transform.translation;
+        }
+        let rendered = interpolated.previous.lerp(interpolated.current, alpha);
+        if transform.translation != rendered {
+            transform.translation = rendered;
+        }
+        interpolated.rendered = rendered;
+    }
+}
@@ -0,0 +1,90 @@
+use bevy::prelude::*;
+use std::path::Path;
+
+use crate::i18n::Locale;
+
+// 字体文件（相对于 assets）
+const REGULAR_FONT_PATH: &str = "fonts/regular.ttf";
+const BOLD_FONT_PATH: &str = "fonts/bold.ttf";
+const MONO_FONT_PATH: &str = "fonts/mono.ttf";
+// 中文界面使用的字体，默认字体不包含中文字形
+const CJK_FONT_PATH: &str = "fonts/cjk.ttf";
+// 游戏会加载的全部字体文件，打包时检查它们是否存在
+pub const FONT_FILES: [&str; 4] = [REGULAR_FONT_PATH, BOLD_FONT_PATH, MONO_FONT_PATH, CJK_FONT_PATH];
+
+// 字体种类：标题用粗体，分数、计时等数字用等宽字体避免跳动
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FontKind {
+    Regular,
+    Bold,
+    Mono,
+}
+
+// 启动时加载的字体，缺失的文件使用 Bevy 默认字体
+#[derive(Resource)]
+pub struct FontAssets {
+    regular: Handle<Font>,
+    bold: Handle<Font>,
+    mono: Handle<Font>,
+    cjk: Option<Handle<Font>>,
+    // 当前语言需要 CJK 字体时所有文本都使用它
+    use_cjk: bool,
+}
+
+impl FromWorld for FontAssets {
+    fn from_world(world: &mut World) -> Self {
+        let use_cjk = world.get_resource::<Locale>().is_some_and(|locale| locale.language().uses_cjk());
+        let asset_server = world.resource::<AssetServer>();
+        let cjk = Path::new("assets")
+            .join(CJK_FONT_PATH)
+            .exists()
+            .then(|| asset_server.load(CJK_FONT_PATH.to_string()));
+        let mut fonts = Self {
+            regular: load_font(asset_server, REGULAR_FONT_PATH),
+            bold: load_font(asset_server, BOLD_FONT_PATH),
+            mono: load_font(asset_server, MONO_FONT_PATH),
+            cjk,
+            use_cjk: false,
+        };
+        fonts.set_cjk(use_cjk);
+        fonts
+    }
+}
+
+impl FontAssets {
+    pub fn set_cjk(&mut self, enabled: bool) {
+        if enabled && self.cjk.is_none() {
+            eprintln!("Font {} not found, Chinese text may not render", CJK_FONT_PATH);
+        }
+        self.use_cjk = enabled;
+    }
+
+    fn get(&self, kind: FontKind) -> Handle<Font> {
+        if let (true, Some(cjk)) = (self.use_cjk, &self.cjk) {
+            return cjk.clone();
+        }
+        match kind {
+            FontKind::Regular => self.regular.clone(),
+            FontKind::Bold => self.bold.clone(),
+            FontKind::Mono => self.mono.clone(),
+        }
+    }
+}
+
+fn load_font(asset_server: &AssetServer, path: &str) -> Handle<Font> {
+    if Path::new("assets").join(path).exists() {
+        asset_server.load(path.to_string())
+    } else {
+        eprintln!("Font {} not found, using default font", path);
+        Handle::default()
+    }
+}
+
+// 所有界面统一用这个函数构建文本样式
+pub fn text_style(fonts: &FontAssets, kind: FontKind, font_size: f32, color: Color) -> TextStyle {
+    TextStyle {
+        font: fonts.get(kind),
+        font_size,
+        color,
+    }
+}
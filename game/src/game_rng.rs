@@ -0,0 +1,62 @@
+use bevy::prelude::*;
+use rand::rngs::StdRng;
+use rand::{RngCore, SeedableRng};
+
+// 局内随机数：掉落判定、道具种类和下落方式、球停在挡板上的位置都从这里取。
+// 每关开始时按种子和关卡重新播种，同一种子和同样的操作得到同样的一局，回放和确定性检查依赖这一点。
+// 粒子之类只影响画面的随机数继续用 thread_rng，不要从这里取，否则特效预算会改变局内结果
+#[derive(Resource)]
+pub struct GameRng {
+    rng: StdRng,
+    seed: u64,
+    // 已经取出的 32 位字数；和 seed 一起就是随机数序列当前的位置
+    words: u64,
+}
+
+impl Default for GameRng {
+    fn default() -> Self {
+        Self::seeded(0)
+    }
+}
+
+impl GameRng {
+    fn seeded(seed: u64) -> Self {
+        Self {
+            rng: StdRng::seed_from_u64(seed),
+            seed,
+            words: 0,
+        }
+    }
+
+    // 新关卡开始时调用，和布局生成使用不同的盐
+    pub fn reseed(&mut self, run_seed: u64, level: u32) {
+        *self = Self::seeded(run_seed.rotate_left(29) ^ u64::from(level).wrapping_mul(0xD6E8_FEB8_6659_FD93));
+    }
+
+    // 随机数状态：种子和已取出的字数，确定性检查用它判断两次运行是否取了同样多的随机数
+    pub fn state(&self) -> (u64, u64) {
+        (self.seed, self.words)
+    }
+}
+
+impl RngCore for GameRng {
+    fn next_u32(&mut self) -> u32 {
+        self.words += 1;
+        self.rng.next_u32()
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.words += 2;
+        self.rng.next_u64()
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        self.words += dest.len().div_ceil(4) as u64;
+        self.rng.fill_bytes(dest);
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand::Error> {
+        self.fill_bytes(dest);
+        Ok(())
+    }
+}
@@ -0,0 +1,24 @@
+use bevy::ecs::system::SystemParam;
+use bevy::prelude::*;
+
+// 所有已连接手柄的按键状态。手柄中途插拔时 Bevy 会更新 Gamepads，
+// 每次查询都重新遍历当前连接的手柄，新插入的手柄立即可用
+#[derive(SystemParam)]
+pub struct GamepadButtons<'w> {
+    gamepads: Res<'w, Gamepads>,
+    input: Res<'w, ButtonInput<GamepadButton>>,
+}
+
+impl GamepadButtons<'_> {
+    pub fn pressed(&self, button_type: GamepadButtonType) -> bool {
+        self.gamepads
+            .iter()
+            .any(|gamepad| self.input.pressed(GamepadButton::new(gamepad, button_type)))
+    }
+
+    pub fn just_pressed(&self, button_type: GamepadButtonType) -> bool {
+        self.gamepads
+            .iter()
+            .any(|gamepad| self.input.just_pressed(GamepadButton::new(gamepad, button_type)))
+    }
+}
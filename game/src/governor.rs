@@ -0,0 +1,359 @@
+use bevy::diagnostic::{DiagnosticsStore, FrameTimeDiagnosticsPlugin};
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::Settings;
+
+// 平均帧时间（毫秒）超过这个值持续 DEGRADE_AFTER 秒时降一档
+const SLOW_FRAME_MS: f64 = 22.0;
+const DEGRADE_AFTER: f32 = 2.0;
+// 平均帧时间低于这个值持续 RESTORE_AFTER 秒时升一档；比降档更保守，避免来回切换
+const FAST_FRAME_MS: f64 = 14.0;
+const RESTORE_AFTER: f32 = 5.0;
+
+// 特效质量档位
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum QualityTier {
+    Low,
+    Medium,
+    High,
+}
+
+impl QualityTier {
+    pub const ALL: [QualityTier; 3] = [QualityTier::High, QualityTier::Medium, QualityTier::Low];
+
+    pub fn label_key(&self) -> &'static str {
+        match self {
+            QualityTier::Low => "quality.low",
+            QualityTier::Medium => "quality.medium",
+            QualityTier::High => "quality.high",
+        }
+    }
+
+    fn lower(self) -> Option<Self> {
+        match self {
+            QualityTier::High => Some(QualityTier::Medium),
+            QualityTier::Medium => Some(QualityTier::Low),
+            QualityTier::Low => None,
+        }
+    }
+
+    fn higher(self) -> Option<Self> {
+        match self {
+            QualityTier::Low => Some(QualityTier::Medium),
+            QualityTier::Medium => Some(QualityTier::High),
+            QualityTier::High => None,
+        }
+    }
+
+    fn budget(self) -> EffectBudget {
+        match self {
+            QualityTier::High => EffectBudget {
+                particle_scale: 1.0,
+                minor_effects: true,
+                max_balls: None,
+            },
+            QualityTier::Medium => EffectBudget {
+                particle_scale: 0.5,
+                minor_effects: true,
+                max_balls: Some(3),
+            },
+            QualityTier::Low => EffectBudget {
+                particle_scale: 0.25,
+                minor_effects: false,
+                max_balls: Some(2),
+            },
+        }
+    }
+}
+
+// 粒子的种类：碎裂是打碎砖块等主要反馈，次要特效（霜雾等）在最低档关闭
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EffectKind {
+    Burst,
+    Minor,
+}
+
+// 当前档位下的特效预算，生成粒子和分裂球时查询
+#[derive(Resource, Debug, Clone, Copy)]
+pub struct EffectBudget {
+    particle_scale: f32,
+    minor_effects: bool,
+    // 场上最多的球数，None 表示不限制
+    max_balls: Option<usize>,
+}
+
+impl Default for EffectBudget {
+    fn default() -> Self {
+        QualityTier::High.budget()
+    }
+}
+
+impl EffectBudget {
+    // 一次生成的粒子数；主要反馈至少保留一个粒子
+    pub fn particle_count(&self, kind: EffectKind, base: usize) -> usize {
+        match kind {
+            EffectKind::Minor if !self.minor_effects => 0,
+            EffectKind::Minor => (base as f32 * self.particle_scale).round() as usize,
+            EffectKind::Burst => ((base as f32 * self.particle_scale).round() as usize).max(1),
+        }
+    }
+
+    // 次要特效（霜雾、残影、墙上印记等）是否开启
+    pub fn minor_effects(&self) -> bool {
+        self.minor_effects
+    }
+
+    // 场上已有 current 个球时，最多还能分裂出几个
+    pub fn ball_splits(&self, requested: usize, current: usize) -> usize {
+        match self.max_balls {
+            Some(max_balls) => requested.min(max_balls.saturating_sub(current)),
+            None => requested,
+        }
+    }
+}
+
+// 根据帧时间自动调整特效档位；设置中固定了档位时不自动调整
+#[derive(Resource, Debug)]
+pub struct PerformanceGovernor {
+    pub tier: QualityTier,
+    // 是否由设置固定
+    pub pinned: bool,
+    slow_for: f32,
+    fast_for: f32,
+}
+
+impl Default for PerformanceGovernor {
+    fn default() -> Self {
+        Self {
+            tier: QualityTier::High,
+            pinned: false,
+            slow_for: 0.0,
+            fast_for: 0.0,
+        }
+    }
+}
+
+pub struct GovernorPlugin;
+
+impl Plugin for GovernorPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugins(FrameTimeDiagnosticsPlugin)
+            .init_resource::<PerformanceGovernor>()
+            .init_resource::<EffectBudget>()
+            .add_systems(Update, govern_quality);
+    }
+}
+
+// 每帧根据平滑后的帧时间和设置决定档位，档位变化时更新预算并输出日志
+fn govern_quality(
+    time: Res<Time>,
+    diagnostics: Res<DiagnosticsStore>,
+    settings: Res<Settings>,
+    mut governor: ResMut<PerformanceGovernor>,
+    mut budget: ResMut<EffectBudget>,
+) {
+    let previous = governor.tier;
+
+    if let Some(tier) = settings.quality {
+        governor.tier = tier;
+        governor.pinned = true;
+        governor.slow_for = 0.0;
+        governor.fast_for = 0.0;
+    } else {
+        if governor.pinned {
+            // 从固定档位切回自动时从最高档重新开始
+            governor.pinned = false;
+            governor.tier = QualityTier::High;
+        }
+        let frame_ms = diagnostics
+            .get(&FrameTimeDiagnosticsPlugin::FRAME_TIME)
+            .and_then(|frame_time| frame_time.smoothed());
+        if let Some(frame_ms) = frame_ms {
+            let delta = time.delta_seconds();
+            governor.slow_for = if frame_ms > SLOW_FRAME_MS { governor.slow_for + delta } else { 0.0 };
+            governor.fast_for = if frame_ms < FAST_FRAME_MS { governor.fast_for + delta } else { 0.0 };
+
+            if governor.slow_for >= DEGRADE_AFTER {
+                governor.slow_for = 0.0;
+                if let Some(lower) = governor.tier.lower() {
+                    info!(frame_ms, "frame time over budget, lowering effect quality to {:?}", lower);
+                    governor.tier = lower;
+                }
+            } else if governor.fast_for >= RESTORE_AFTER {
+                governor.fast_for = 0.0;
+                if let Some(higher) = governor.tier.higher() {
+                    info!(frame_ms, "frame time has headroom, raising effect quality to {:?}", higher);
+                    governor.tier = higher;
+                }
+            }
+        }
+    }
+
+    if governor.tier != previous {
+        *budget = governor.tier.budget();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bevy::diagnostic::{Diagnostic, DiagnosticMeasurement};
+    use bevy::ecs::system::RunSystemOnce;
+    use bevy::time::TimeUpdateStrategy;
+    use std::time::{Duration, Instant};
+
+    const FRAME: f32 = 0.25;
+
+    // 测试设定的帧时间（毫秒），每帧写入诊断数据。测量时间按游戏时间计，
+    // 每帧间隔比平滑窗口长，平滑后的值就是设定的值
+    #[derive(Resource)]
+    struct FrameTime(f64);
+
+    fn feed_frame_time(
+        time: Res<Time>,
+        frame_time: Res<FrameTime>,
+        mut start: Local<Option<Instant>>,
+        mut diagnostics: ResMut<DiagnosticsStore>,
+    ) {
+        let start = *start.get_or_insert_with(Instant::now);
+        if let Some(diagnostic) = diagnostics.get_mut(&FrameTimeDiagnosticsPlugin::FRAME_TIME) {
+            diagnostic.add_measurement(DiagnosticMeasurement {
+                time: start + time.elapsed(),
+                value: frame_time.0,
+            });
+        }
+    }
+
+    // 没有真实帧时间的 App：帧时间由 FrameTime 决定，每帧固定前进 FRAME 秒
+    fn governor_app() -> App {
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins)
+            .insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_secs_f32(FRAME)))
+            .init_resource::<DiagnosticsStore>()
+            .insert_resource(Settings::default())
+            .insert_resource(FrameTime(10.0))
+            .init_resource::<PerformanceGovernor>()
+            .init_resource::<EffectBudget>()
+            .add_systems(Update, (feed_frame_time, govern_quality).chain());
+        app.world
+            .resource_mut::<DiagnosticsStore>()
+            .add(Diagnostic::new(FrameTimeDiagnosticsPlugin::FRAME_TIME));
+        // 第一帧的时间间隔为 0
+        app.update();
+        app
+    }
+
+    fn run(app: &mut App, frame_ms: f64, seconds: f32) {
+        app.insert_resource(FrameTime(frame_ms));
+        for _ in 0..(seconds / FRAME).round() as u32 {
+            app.update();
+        }
+    }
+
+    fn tier(app: &App) -> QualityTier {
+        app.world.resource::<PerformanceGovernor>().tier
+    }
+
+    #[test]
+    fn budgets_shrink_with_the_tier() {
+        let high = QualityTier::High.budget();
+        let medium = QualityTier::Medium.budget();
+        let low = QualityTier::Low.budget();
+        assert_eq!(high.particle_count(EffectKind::Burst, 10), 10);
+        assert_eq!(medium.particle_count(EffectKind::Burst, 10), 5);
+        assert_eq!(low.particle_count(EffectKind::Burst, 10), 3);
+        // 主要反馈至少保留一个粒子，次要特效在最低档关闭
+        assert_eq!(low.particle_count(EffectKind::Burst, 1), 1);
+        assert_eq!(medium.particle_count(EffectKind::Minor, 6), 3);
+        assert_eq!(low.particle_count(EffectKind::Minor, 6), 0);
+        assert!(!low.minor_effects());
+
+        assert_eq!(high.ball_splits(2, 1), 2);
+        assert_eq!(high.ball_splits(2, 20), 2);
+        assert_eq!(medium.ball_splits(2, 1), 2);
+        assert_eq!(medium.ball_splits(2, 2), 1);
+        assert_eq!(low.ball_splits(2, 1), 1);
+        assert_eq!(low.ball_splits(2, 3), 0);
+    }
+
+    #[test]
+    fn slow_frames_lower_one_tier_every_two_seconds() {
+        let mut app = governor_app();
+        run(&mut app, 30.0, DEGRADE_AFTER - FRAME);
+        assert_eq!(tier(&app), QualityTier::High);
+        run(&mut app, 30.0, FRAME);
+        assert_eq!(tier(&app), QualityTier::Medium);
+        assert_eq!(app.world.resource::<EffectBudget>().ball_splits(2, 2), 1);
+        run(&mut app, 30.0, DEGRADE_AFTER);
+        assert_eq!(tier(&app), QualityTier::Low);
+        run(&mut app, 30.0, DEGRADE_AFTER * 3.0);
+        assert_eq!(tier(&app), QualityTier::Low);
+    }
+
+    #[test]
+    fn headroom_restores_one_tier_after_five_seconds() {
+        let mut app = governor_app();
+        run(&mut app, 30.0, DEGRADE_AFTER * 2.0);
+        assert_eq!(tier(&app), QualityTier::Low);
+
+        // 中途出现一次不够快的帧就重新计时
+        run(&mut app, 10.0, RESTORE_AFTER - 1.0);
+        run(&mut app, 18.0, FRAME);
+        run(&mut app, 10.0, RESTORE_AFTER - 1.0);
+        assert_eq!(tier(&app), QualityTier::Low);
+        run(&mut app, 10.0, 1.0);
+        assert_eq!(tier(&app), QualityTier::Medium);
+        run(&mut app, 10.0, RESTORE_AFTER);
+        assert_eq!(tier(&app), QualityTier::High);
+        assert_eq!(app.world.resource::<EffectBudget>().particle_count(EffectKind::Minor, 6), 6);
+    }
+
+    #[test]
+    fn pinned_quality_ignores_frame_time() {
+        let mut app = governor_app();
+        app.world.resource_mut::<Settings>().quality = Some(QualityTier::Medium);
+        run(&mut app, 10.0, RESTORE_AFTER * 2.0);
+        assert_eq!(tier(&app), QualityTier::Medium);
+        assert!(app.world.resource::<PerformanceGovernor>().pinned);
+
+        // 切回自动后从最高档开始
+        app.world.resource_mut::<Settings>().quality = None;
+        run(&mut app, 30.0, FRAME);
+        assert_eq!(tier(&app), QualityTier::High);
+        assert!(!app.world.resource::<PerformanceGovernor>().pinned);
+    }
+
+    // 最坏情况：一帧里打碎整屏砖块并撞上整屏冰砖。返回生成的粒子数
+    fn worst_case_particles(app: &mut App) -> usize {
+        let budget = *app.world.resource::<EffectBudget>();
+        app.world.run_system_once(move |mut commands: Commands| {
+            for index in 0..crate::BRICK_ROWS * crate::BRICK_COLUMNS {
+                let position = Vec3::new(index as f32, 0.0, 0.0);
+                crate::spawn_particles(&mut commands, &budget, position, Vec3::ONE);
+                crate::spawn_frost_puff(&mut commands, &budget, position);
+            }
+        });
+        let particles: Vec<Entity> =
+            app.world.query_filtered::<Entity, With<crate::Particle>>().iter(&app.world).collect();
+        for &entity in &particles {
+            app.world.despawn(entity);
+        }
+        particles.len()
+    }
+
+    #[test]
+    fn degraded_tiers_spawn_fewer_particles_in_the_worst_case() {
+        let mut app = governor_app();
+        let high = worst_case_particles(&mut app);
+        run(&mut app, 30.0, DEGRADE_AFTER);
+        let medium = worst_case_particles(&mut app);
+        run(&mut app, 30.0, DEGRADE_AFTER);
+        let low = worst_case_particles(&mut app);
+
+        let bricks = crate::BRICK_ROWS * crate::BRICK_COLUMNS;
+        assert_eq!(high, bricks * (10 + 6));
+        assert_eq!(medium, bricks * (5 + 3));
+        assert_eq!(low, bricks * 3);
+    }
+}
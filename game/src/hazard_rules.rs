@@ -0,0 +1,250 @@
+use bevy::prelude::*;
+
+use crate::{GameState, LevelTimer, PowerUpEffects};
+
+// 只在游戏进行中前进的时钟，暂停和死亡镜头的时间不计入。
+// 约定：Playing 状态下的计时（时间戳、倒计时、按经过时间算的动画相位）都用 PlayClock，
+// 暂停后回来不会跳变；菜单、音乐和网络请求等全局内容继续用 Time。推进局内计时的系统用 assert_uses_game_time! 检查
+#[derive(Resource, Default, Debug)]
+pub struct PlayClock {
+    elapsed: f32,
+    level_started_at: f32,
+}
+
+impl PlayClock {
+    pub fn now(&self) -> f32 {
+        self.elapsed
+    }
+
+    // 新关卡开始时记录起点
+    pub fn start_level(&mut self) {
+        self.level_started_at = self.elapsed;
+    }
+
+    // 本关已经玩了多久（暂停和过场不计）
+    pub fn level_time(&self) -> f32 {
+        self.elapsed - self.level_started_at
+    }
+}
+
+// 球最后一次被挡板接住（或边缘救回）的时间，按 PlayClock 计
+#[derive(Component, Debug, Clone, Copy)]
+pub struct LastPaddleContact {
+    pub time: f32,
+}
+
+// 与玩家最后一次接触相关的公平性规则都集中在这里
+pub struct HazardRules;
+
+impl HazardRules {
+    // 本关开始后还没碰过挡板的球打碎砖块不计入连击
+    pub fn counts_for_combo(contact: Option<&LastPaddleContact>, clock: &PlayClock) -> bool {
+        contact.is_some_and(|contact| contact.time >= clock.level_started_at)
+    }
+}
+
+// 局内计时的快照：游戏时钟、关卡倒计时和两个道具倒计时。暂停前后各取一次，比较哪些计时走了
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GameTimers {
+    play_clock: f32,
+    level_timer: f32,
+    laser: f32,
+    slow_motion: f32,
+}
+
+impl GameTimers {
+    pub fn capture(clock: &PlayClock, level_timer: &LevelTimer, effects: &PowerUpEffects) -> Self {
+        Self {
+            play_clock: clock.now(),
+            level_timer: level_timer.0.remaining(),
+            laser: effects.laser_timer.remaining(),
+            slow_motion: effects.slow_motion_timer.remaining(),
+        }
+    }
+
+    // 和 before 相比，moved 为 true 时返回数值变了的计时，为 false 时返回没变的计时
+    pub fn compare(&self, before: &GameTimers, moved: bool) -> Vec<&'static str> {
+        self.named()
+            .into_iter()
+            .zip(before.named())
+            .filter(|((_, now), (_, then))| (now != then) == moved)
+            .map(|((name, _), _)| name)
+            .collect()
+    }
+
+    fn named(&self) -> [(&'static str, f32); 4] {
+        [
+            ("play_clock", self.play_clock),
+            ("level_timer", self.level_timer),
+            ("laser", self.laser),
+            ("slow_motion", self.slow_motion),
+        ]
+    }
+}
+
+// 测试中检查局内计时：frozen 要求 before 之后一个计时都没动（暂停中），advanced 要求每个计时都走了（回到游戏后）
+#[cfg(test)]
+macro_rules! assert_game_time {
+    (frozen, $before:expr, $after:expr) => {{
+        let (before, after): (&crate::hazard_rules::GameTimers, &crate::hazard_rules::GameTimers) = (&$before, &$after);
+        let moved = after.compare(before, true);
+        assert!(moved.is_empty(), "gameplay timers moved: {:?} ({:?} -> {:?})", moved, before, after);
+    }};
+    (advanced, $before:expr, $after:expr) => {{
+        let (before, after): (&crate::hazard_rules::GameTimers, &crate::hazard_rules::GameTimers) = (&$before, &$after);
+        let stuck = after.compare(before, false);
+        assert!(stuck.is_empty(), "gameplay timers did not advance: {:?} ({:?} -> {:?})", stuck, before, after);
+    }};
+}
+#[cfg(test)]
+pub(crate) use assert_game_time;
+
+// 在推进局内计时的系统开头调用：调试版本中检查当前状态是 Playing。
+// 局内计时只能在 Playing 下前进，注册时漏了 run_if(in_state(GameState::Playing)) 会在这里报出来
+macro_rules! assert_uses_game_time {
+    ($state:expr, $name:literal) => {
+        debug_assert!(
+            *$state.get() == crate::GameState::Playing,
+            "{} advanced gameplay time in {:?}",
+            $name,
+            $state.get()
+        );
+    };
+}
+pub(crate) use assert_uses_game_time;
+
+// 推进游戏时钟（只在 Playing 状态运行）
+pub fn advance_play_clock(time: Res<Time>, state: Res<State<GameState>>, mut clock: ResMut<PlayClock>) {
+    assert_uses_game_time!(state, "advance_play_clock");
+    clock.elapsed += time.delta_seconds();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bevy::time::TimeUpdateStrategy;
+    use std::time::Duration;
+
+    const STEP: f32 = 0.1;
+
+    // 只有时钟系统的无窗口 App，每帧固定前进 STEP 秒
+    fn clock_app() -> App {
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins)
+            .insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_secs_f32(STEP)))
+            .init_state::<GameState>()
+            .init_resource::<PlayClock>()
+            .add_systems(Update, advance_play_clock.run_if(in_state(GameState::Playing)));
+        app.update();
+        app
+    }
+
+    fn enter(app: &mut App, state: GameState) {
+        app.world.resource_mut::<NextState<GameState>>().set(state);
+    }
+
+    fn run(app: &mut App, frames: u32) {
+        for _ in 0..frames {
+            app.update();
+        }
+    }
+
+    fn now(app: &App) -> f32 {
+        app.world.resource::<PlayClock>().now()
+    }
+
+    #[test]
+    fn clock_stands_still_while_paused() {
+        let mut app = clock_app();
+        enter(&mut app, GameState::Playing);
+        run(&mut app, 10);
+        let before_pause = now(&app);
+        assert!((before_pause - 10.0 * STEP).abs() < 1e-4);
+
+        enter(&mut app, GameState::Paused);
+        run(&mut app, 50);
+        assert_eq!(now(&app), before_pause);
+
+        enter(&mut app, GameState::Playing);
+        run(&mut app, 5);
+        assert!((now(&app) - 15.0 * STEP).abs() < 1e-4);
+    }
+
+    #[test]
+    fn level_time_excludes_pauses() {
+        let mut app = clock_app();
+        enter(&mut app, GameState::Playing);
+        run(&mut app, 5);
+        app.world.resource_mut::<PlayClock>().start_level();
+        run(&mut app, 3);
+        enter(&mut app, GameState::Paused);
+        run(&mut app, 30);
+        enter(&mut app, GameState::Playing);
+        run(&mut app, 2);
+        assert!((app.world.resource::<PlayClock>().level_time() - 5.0 * STEP).abs() < 1e-4);
+    }
+
+    #[test]
+    fn timer_snapshots_name_the_timers_that_moved() {
+        let mut clock = PlayClock::default();
+        let mut level_timer = LevelTimer(crate::countdown::Countdown::new(60.0));
+        let mut effects = PowerUpEffects::default();
+        effects.laser_timer.restart(10.0);
+        effects.slow_motion_timer.restart(10.0);
+        let before = GameTimers::capture(&clock, &level_timer, &effects);
+        assert_game_time!(frozen, before, GameTimers::capture(&clock, &level_timer, &effects));
+
+        clock.elapsed += STEP;
+        level_timer.0.tick(STEP);
+        let after = GameTimers::capture(&clock, &level_timer, &effects);
+        assert_eq!(after.compare(&before, true), ["play_clock", "level_timer"]);
+        assert_eq!(after.compare(&before, false), ["laser", "slow_motion"]);
+
+        effects.laser_timer.tick(STEP);
+        effects.slow_motion_timer.tick(STEP);
+        assert_game_time!(advanced, before, GameTimers::capture(&clock, &level_timer, &effects));
+    }
+
+    #[test]
+    #[should_panic(expected = "gameplay timers moved: [\"play_clock\"]")]
+    fn frozen_assertion_reports_a_moving_clock() {
+        let mut clock = PlayClock::default();
+        let level_timer = LevelTimer(crate::countdown::Countdown::new(60.0));
+        let effects = PowerUpEffects::default();
+        let before = GameTimers::capture(&clock, &level_timer, &effects);
+        clock.elapsed += STEP;
+        assert_game_time!(frozen, before, GameTimers::capture(&clock, &level_timer, &effects));
+    }
+
+    #[test]
+    fn combo_needs_a_paddle_contact_in_this_level() {
+        let mut clock = PlayClock {
+            elapsed: 12.0,
+            level_started_at: 0.0,
+        };
+        clock.start_level();
+        assert!(!HazardRules::counts_for_combo(None, &clock));
+        assert!(!HazardRules::counts_for_combo(Some(&LastPaddleContact { time: 11.9 }), &clock));
+        assert!(HazardRules::counts_for_combo(Some(&LastPaddleContact { time: 12.0 }), &clock));
+        clock.elapsed += 3.0;
+        assert!(HazardRules::counts_for_combo(Some(&LastPaddleContact { time: 14.0 }), &clock));
+    }
+
+    #[test]
+    fn contact_made_before_a_pause_still_counts_after_it() {
+        let mut app = clock_app();
+        enter(&mut app, GameState::Playing);
+        run(&mut app, 2);
+        app.world.resource_mut::<PlayClock>().start_level();
+        run(&mut app, 4);
+        let contact = LastPaddleContact { time: now(&app) };
+        enter(&mut app, GameState::Paused);
+        run(&mut app, 100);
+        enter(&mut app, GameState::Playing);
+        run(&mut app, 1);
+        let clock = app.world.resource::<PlayClock>();
+        assert!(HazardRules::counts_for_combo(Some(&contact), clock));
+        // 暂停的 10 秒没有算进距离上次接触的时间
+        assert!((clock.now() - contact.time - STEP).abs() < 1e-4);
+    }
+}
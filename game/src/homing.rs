@@ -0,0 +1,281 @@
+use bevy::prelude::*;
+
+use crate::fonts::{text_style, FontAssets, FontKind};
+use crate::i18n::Locale;
+use crate::launch::HeldBall;
+use crate::shield::{GeneratorChain, Shield};
+use crate::sticky::StuckBall;
+use crate::{Ball, Brick, BrickType, GameEntity, PowerUpEffects, BRICK_SIZE, PADDLE_Y};
+
+// 追踪球的持续时间
+pub const HOMING_DURATION: f32 = 8.0;
+// 随机掉落时先以这个概率直接抽到追踪球，比其他道具（各约 10%）少见
+pub const HOMING_DROP_WEIGHT: f64 = 0.04;
+pub const HOMING_COLOR: Color = Color::rgb(1.0, 0.35, 0.6);
+// 每秒最多转过的角度，球只能慢慢拐弯，不会原地掉头
+const MAX_TURN_RATE: f32 = std::f32::consts::FRAC_PI_2;
+// 同时有穿透球时转向减半：穿透球打中砖块不会弹开，全速追踪会一路穿过整面墙
+const PENETRATING_TURN_SCALE: f32 = 0.5;
+// 每隔多久重新找一次最近的砖块；目标被打碎时下一帧就换
+const RETARGET_INTERVAL: f32 = 0.25;
+// 准星的半径和四条刻度线的长度
+const RETICLE_RADIUS: f32 = BRICK_SIZE.y;
+const RETICLE_TICK: f32 = 8.0;
+
+// 追踪球正在追的砖块；砖块不会移动，记下位置后移动时不用再查询
+#[derive(Component)]
+pub struct HomingTarget {
+    brick: Entity,
+    position: Vec2,
+}
+
+#[derive(Component)]
+pub struct HomingText;
+
+// 可以作为目标的砖块：还没被打碎、可以破坏、没有受护盾保护
+fn targetable(brick: &Brick, shield: Option<&Shield>, chain: &GeneratorChain) -> bool {
+    brick.health > 0 && !matches!(brick.brick_type, BrickType::Unbreakable) && chain.deflects(shield).is_none()
+}
+
+// 把速度朝目标方向转过最多 max_angle（弧度），速度大小不变
+pub fn steer(velocity: Vec2, to_target: Vec2, max_angle: f32) -> Vec2 {
+    if velocity.length_squared() < f32::EPSILON || to_target.length_squared() < f32::EPSILON {
+        return velocity;
+    }
+    let angle = velocity.angle_between(to_target).clamp(-max_angle, max_angle);
+    Vec2::from_angle(angle).rotate(velocity)
+}
+
+// 球低于挡板（正常方向下的坐标）时不再追踪，不能靠追踪把漏掉的球拉回来
+pub fn homing_allowed(ball_y: f32) -> bool {
+    ball_y >= PADDLE_Y
+}
+
+// 这一帧最多转过的角度
+pub fn turn_limit(penetrating: bool, delta: f32) -> f32 {
+    let scale = if penetrating { PENETRATING_TURN_SCALE } else { 1.0 };
+    MAX_TURN_RATE * scale * delta
+}
+
+// 追踪球期间给每个飞行中的球找最近的砖块：目标失效时立即换，其余每隔 RETARGET_INTERVAL 重新找一次。
+// 效果结束时去掉所有目标
+pub fn update_homing_targets(
+    mut commands: Commands,
+    time: Res<Time>,
+    power_effects: Res<PowerUpEffects>,
+    chain: Res<GeneratorChain>,
+    mut since_retarget: Local<f32>,
+    balls: Query<(Entity, &Transform, Option<&HomingTarget>), (With<Ball>, Without<HeldBall>, Without<StuckBall>)>,
+    bricks: Query<(Entity, &Transform, &Brick, Option<&Shield>), Without<Ball>>,
+) {
+    if !power_effects.homing {
+        for (entity, _, target) in balls.iter() {
+            if target.is_some() {
+                commands.entity(entity).remove::<HomingTarget>();
+            }
+        }
+        return;
+    }
+
+    *since_retarget += time.delta_seconds();
+    let retarget_all = *since_retarget >= RETARGET_INTERVAL;
+    if retarget_all {
+        *since_retarget = 0.0;
+    }
+
+    for (entity, transform, target) in balls.iter() {
+        let still_valid = target.is_some_and(|target| {
+            bricks.get(target.brick).is_ok_and(|(_, _, brick, shield)| targetable(brick, shield, &chain))
+        });
+        if still_valid && !retarget_all {
+            continue;
+        }
+
+        let position = transform.translation.truncate();
+        let nearest = bricks
+            .iter()
+            .filter(|(_, _, brick, shield)| targetable(brick, *shield, &chain))
+            .map(|(brick, brick_transform, _, _)| (brick, brick_transform.translation.truncate()))
+            .min_by(|a, b| a.1.distance_squared(position).total_cmp(&b.1.distance_squared(position)));
+        match nearest {
+            Some((brick, position)) => {
+                if target.map(|target| target.brick) != Some(brick) {
+                    commands.entity(entity).insert(HomingTarget { brick, position });
+                }
+            }
+            None => {
+                if target.is_some() {
+                    commands.entity(entity).remove::<HomingTarget>();
+                }
+            }
+        }
+    }
+}
+
+// 球移动前调用：朝目标转一点，球低于挡板时不转
+pub fn steer_ball(ball: &mut Ball, position: Vec2, oriented_y: f32, target: &HomingTarget, max_angle: f32) {
+    if homing_allowed(oriented_y) {
+        ball.velocity = steer(ball.velocity, target.position - position, max_angle);
+    }
+}
+
+// 在每个目标砖块上画一个准星：圆圈加上四条朝内的刻度线
+pub fn draw_homing_reticles(
+    mut gizmos: Gizmos,
+    power_effects: Res<PowerUpEffects>,
+    targets: Query<&HomingTarget, (With<Ball>, Without<HeldBall>, Without<StuckBall>)>,
+) {
+    if !power_effects.homing {
+        return;
+    }
+    for target in targets.iter() {
+        gizmos.circle_2d(target.position, RETICLE_RADIUS, HOMING_COLOR);
+        for direction in [Vec2::X, Vec2::NEG_X, Vec2::Y, Vec2::NEG_Y] {
+            let outer = target.position + direction * (RETICLE_RADIUS + RETICLE_TICK / 2.0);
+            gizmos.line_2d(outer, outer - direction * RETICLE_TICK, HOMING_COLOR);
+        }
+    }
+}
+
+// 追踪球剩余时间，在慢动作状态右边
+pub fn spawn_homing_text(commands: &mut Commands, fonts: &FontAssets) {
+    commands.spawn((
+        TextBundle::from_section("", text_style(fonts, FontKind::Mono, 25.0, HOMING_COLOR)).with_style(Style {
+            position_type: PositionType::Absolute,
+            left: Val::Px(450.0),
+            bottom: Val::Px(10.0),
+            ..default()
+        }),
+        HomingText,
+        GameEntity,
+    ));
+}
+
+pub fn update_homing_text(
+    power_effects: Res<PowerUpEffects>,
+    locale: Res<Locale>,
+    mut texts: Query<&mut Text, With<HomingText>>,
+) {
+    let Ok(mut text) = texts.get_single_mut() else {
+        return;
+    };
+    let remaining = power_effects.homing_timer.remaining();
+    let value = if power_effects.homing {
+        locale.tr("hud.homing", &[("seconds", &format!("{:.1}", remaining))])
+    } else {
+        String::new()
+    };
+    if text.sections[0].value != value {
+        text.sections[0].value = value;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::f32::consts::FRAC_PI_2;
+
+    const SPEED: f32 = 400.0;
+
+    #[test]
+    fn steering_turns_at_most_the_limit_and_keeps_the_speed() {
+        let velocity = Vec2::new(0.0, SPEED);
+        // 目标在正右方，要转 90 度，这一帧只能转 0.1 弧度
+        let steered = steer(velocity, Vec2::X, 0.1);
+        assert!((steered.length() - SPEED).abs() < 1e-3);
+        assert!((velocity.angle_between(steered) + 0.1).abs() < 1e-5);
+        // 向左转同样受限
+        let steered = steer(velocity, Vec2::NEG_X, 0.1);
+        assert!((velocity.angle_between(steered) - 0.1).abs() < 1e-5);
+        // 正后方的目标也不能原地掉头
+        let steered = steer(velocity, Vec2::NEG_Y, 0.1);
+        assert!(velocity.angle_between(steered).abs() <= 0.1 + 1e-5);
+    }
+
+    #[test]
+    fn steering_within_the_limit_points_straight_at_the_target() {
+        let velocity = Vec2::new(SPEED, SPEED);
+        let steered = steer(velocity, Vec2::new(1.0, 1.2), 0.5);
+        assert!((steered.normalize() - Vec2::new(1.0, 1.2).normalize()).length() < 1e-5);
+        assert!((steered.length() - velocity.length()).abs() < 1e-3);
+    }
+
+    #[test]
+    fn steering_ignores_a_still_ball_or_a_target_on_the_ball() {
+        assert_eq!(steer(Vec2::ZERO, Vec2::X, 0.5), Vec2::ZERO);
+        assert_eq!(steer(Vec2::new(0.0, SPEED), Vec2::ZERO, 0.5), Vec2::new(0.0, SPEED));
+    }
+
+    #[test]
+    fn turn_rate_is_a_quarter_turn_per_second_and_halved_when_penetrating() {
+        assert!((turn_limit(false, 1.0) - FRAC_PI_2).abs() < 1e-6);
+        assert!((turn_limit(true, 1.0) - FRAC_PI_2 / 2.0).abs() < 1e-6);
+        // 一秒内分成很多帧也转不过 90 度
+        let mut velocity = Vec2::new(0.0, SPEED);
+        for _ in 0..120 {
+            velocity = steer(velocity, Vec2::NEG_Y, turn_limit(false, 1.0 / 120.0));
+        }
+        assert!((Vec2::new(0.0, SPEED).angle_between(velocity).abs() - FRAC_PI_2).abs() < 1e-3);
+    }
+
+    #[test]
+    fn balls_below_the_paddle_are_not_steered() {
+        assert!(homing_allowed(PADDLE_Y));
+        assert!(homing_allowed(0.0));
+        assert!(!homing_allowed(PADDLE_Y - 1.0));
+
+        let target = HomingTarget {
+            brick: Entity::PLACEHOLDER,
+            position: Vec2::new(200.0, 200.0),
+        };
+        let velocity = Vec2::new(0.0, -SPEED);
+        let mut ball = Ball { velocity };
+        steer_ball(&mut ball, Vec2::new(0.0, PADDLE_Y - 10.0), PADDLE_Y - 10.0, &target, 0.5);
+        assert_eq!(ball.velocity, velocity);
+        steer_ball(&mut ball, Vec2::new(0.0, PADDLE_Y + 10.0), PADDLE_Y + 10.0, &target, 0.5);
+        assert_ne!(ball.velocity, velocity);
+    }
+
+    fn brick(app: &mut App, position: Vec2, brick_type: BrickType) -> Entity {
+        app.world
+            .spawn((
+                Transform::from_translation(position.extend(0.0)),
+                Brick {
+                    brick_type,
+                    health: 1,
+                    shade: 1.0,
+                    guaranteed_drop: None,
+                    golden: false,
+                },
+            ))
+            .id()
+    }
+
+    fn target_of(app: &mut App) -> Option<Entity> {
+        app.world.query::<&HomingTarget>().iter(&app.world).next().map(|target| target.brick)
+    }
+
+    #[test]
+    fn targets_the_nearest_breakable_brick_and_retargets_when_it_breaks() {
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins)
+            .insert_resource(PowerUpEffects { homing: true, ..default() })
+            .init_resource::<GeneratorChain>()
+            .add_systems(Update, update_homing_targets);
+        app.world.spawn((Transform::default(), Ball { velocity: Vec2::new(0.0, SPEED) }));
+        brick(&mut app, Vec2::new(0.0, 50.0), BrickType::Unbreakable);
+        let near = brick(&mut app, Vec2::new(0.0, 100.0), BrickType::Normal);
+        let far = brick(&mut app, Vec2::new(0.0, 200.0), BrickType::Normal);
+
+        app.update();
+        assert_eq!(target_of(&mut app), Some(near));
+        app.world.despawn(near);
+        app.update();
+        assert_eq!(target_of(&mut app), Some(far));
+
+        // 效果结束后去掉目标
+        app.world.resource_mut::<PowerUpEffects>().homing = false;
+        app.update();
+        assert_eq!(target_of(&mut app), None);
+    }
+}
@@ -0,0 +1,136 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::fmt::Display;
+use std::sync::Mutex;
+
+use crate::fonts::FontAssets;
+use crate::settings::Settings;
+
+// 编译时嵌入的语言包，格式为 key -> 文本 的 RON 映射
+const EN_BUNDLE: &str = include_str!("../assets/locales/en.ron");
+const ZH_CN_BUNDLE: &str = include_str!("../assets/locales/zh-CN.ron");
+
+// 界面语言
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Language {
+    #[serde(rename = "en")]
+    En,
+    #[serde(rename = "zh-CN")]
+    ZhCn,
+}
+
+impl Language {
+    pub const ALL: [Language; 2] = [Language::En, Language::ZhCn];
+
+    pub fn code(&self) -> &'static str {
+        match self {
+            Language::En => "en",
+            Language::ZhCn => "zh-CN",
+        }
+    }
+
+    // 语言名称始终用该语言本身显示，方便在看不懂当前语言时切换
+    pub fn native_name(&self) -> &'static str {
+        match self {
+            Language::En => "English",
+            Language::ZhCn => "简体中文",
+        }
+    }
+
+    // 是否需要 CJK 字体
+    pub fn uses_cjk(&self) -> bool {
+        matches!(self, Language::ZhCn)
+    }
+
+    fn bundle(&self) -> &'static str {
+        match self {
+            Language::En => EN_BUNDLE,
+            Language::ZhCn => ZH_CN_BUNDLE,
+        }
+    }
+
+    pub fn parse_bundle(&self) -> Result<HashMap<String, String>, ron::error::SpannedError> {
+        ron::from_str(self.bundle())
+    }
+
+    // 根据系统语言选择，不支持的语言使用英文
+    pub fn detect() -> Self {
+        match sys_locale::get_locale() {
+            Some(locale) if locale.to_lowercase().starts_with("zh") => Language::ZhCn,
+            _ => Language::En,
+        }
+    }
+}
+
+// 当前语言的文本表
+#[derive(Resource)]
+pub struct Locale {
+    language: Language,
+    strings: HashMap<String, String>,
+    // 已经提示过的缺失 key，每个只输出一次
+    missing: Mutex<HashSet<String>>,
+}
+
+impl Locale {
+    pub fn new(language: Language) -> Self {
+        Self {
+            language,
+            strings: load_bundle(language),
+            missing: Mutex::new(HashSet::new()),
+        }
+    }
+
+    pub fn language(&self) -> Language {
+        self.language
+    }
+
+    pub fn set_language(&mut self, language: Language) {
+        *self = Self::new(language);
+    }
+
+    // 查找文本并替换 {name} 占位符；缺失的 key 直接显示 key 本身
+    pub fn tr(&self, key: &str, args: &[(&str, &dyn Display)]) -> String {
+        let Some(template) = self.strings.get(key) else {
+            if let Ok(mut missing) = self.missing.lock() {
+                if missing.insert(key.to_string()) {
+                    eprintln!("Missing translation for '{}' in {}", key, self.language.code());
+                }
+            }
+            return key.to_string();
+        };
+
+        let mut text = template.clone();
+        for (name, value) in args {
+            text = text.replace(&format!("{{{}}}", name), &value.to_string());
+        }
+        text
+    }
+}
+
+fn load_bundle(language: Language) -> HashMap<String, String> {
+    match language.parse_bundle() {
+        Ok(strings) => strings,
+        Err(e) => {
+            eprintln!("Failed to parse {} locale bundle: {}", language.code(), e);
+            HashMap::new()
+        }
+    }
+}
+
+// 以名称存储的类型（砖块、道具、难度等）对应的文本 key，例如 ("powerup", "Multi Ball") -> "powerup.multi_ball"
+pub fn name_key(prefix: &str, name: &str) -> String {
+    format!("{}.{}", prefix, name.to_lowercase().replace(' ', "_"))
+}
+
+// 设置变化（包括切换档案）后同步语言和字体，在界面构建之前运行
+pub fn sync_language(settings: Res<Settings>, mut locale: ResMut<Locale>, mut fonts: ResMut<FontAssets>) {
+    if !settings.is_changed() {
+        return;
+    }
+    let language = settings.language();
+    if locale.language() != language {
+        locale.set_language(language);
+        fonts.set_cjk(language.uses_cjk());
+    }
+}
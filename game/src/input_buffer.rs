@@ -0,0 +1,198 @@
+use bevy::prelude::*;
+
+use crate::gamepad::GamepadButtons;
+use crate::GameState;
+
+// 提前按下的动作保留多久（秒）：在动作变得可用之前这么短的时间内按下也算数
+pub const BUFFER_WINDOW: f32 = 0.12;
+
+// 可以提前按下的动作
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InputAction {
+    // 空格或手柄 A：发射停在挡板上的球，或者发射激光
+    Primary,
+}
+
+impl InputAction {
+    const ALL: [InputAction; 1] = [InputAction::Primary];
+
+    fn key(self) -> KeyCode {
+        match self {
+            InputAction::Primary => KeyCode::Space,
+        }
+    }
+
+    fn button(self) -> GamepadButtonType {
+        match self {
+            InputAction::Primary => GamepadButtonType::South,
+        }
+    }
+}
+
+// 最近按下的动作和按下时间。使用动作的系统通过 consume_if_buffered 读取，
+// 不直接看 just_pressed，这样按早了一点也不会丢失，同一次按下也只会触发一次
+#[derive(Resource, Debug, Default)]
+pub struct InputBuffer {
+    presses: Vec<(InputAction, f32)>,
+    now: f32,
+}
+
+impl InputBuffer {
+    fn record(&mut self, action: InputAction) {
+        self.presses.push((action, self.now));
+    }
+
+    // window 秒内按下过 action 时消耗最早的那一次并返回 true
+    pub fn consume_if_buffered(&mut self, action: InputAction, window: f32) -> bool {
+        let now = self.now;
+        let Some(index) = self
+            .presses
+            .iter()
+            .position(|&(pressed, at)| pressed == action && now - at <= window)
+        else {
+            return false;
+        };
+        self.presses.remove(index);
+        true
+    }
+
+    pub fn clear(&mut self) {
+        self.presses.clear();
+    }
+}
+
+// 记录本帧按下的动作，丢弃过期的；状态切换时清空，暂停前或界面上按下的键不会带进游戏
+pub fn buffer_actions(
+    time: Res<Time>,
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    gamepad: GamepadButtons,
+    mut transitions: EventReader<StateTransitionEvent<GameState>>,
+    mut buffer: ResMut<InputBuffer>,
+) {
+    if transitions.read().count() > 0 {
+        buffer.clear();
+    }
+    buffer.now = time.elapsed_seconds();
+    let now = buffer.now;
+    buffer.presses.retain(|&(_, at)| now - at <= BUFFER_WINDOW);
+    for action in InputAction::ALL {
+        if keyboard_input.just_pressed(action.key()) || gamepad.just_pressed(action.button()) {
+            buffer.record(action);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bevy::time::TimeUpdateStrategy;
+    use std::time::Duration;
+
+    const FRAME: f32 = 0.02;
+
+    // 只有缓冲系统的无窗口 App，每帧固定前进 FRAME 秒
+    fn buffer_app() -> App {
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins)
+            .insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_secs_f32(FRAME)))
+            .init_state::<GameState>()
+            .init_resource::<ButtonInput<KeyCode>>()
+            .init_resource::<ButtonInput<GamepadButton>>()
+            .init_resource::<Gamepads>()
+            .init_resource::<InputBuffer>()
+            .add_systems(Update, buffer_actions);
+        app.world.resource_mut::<NextState<GameState>>().set(GameState::Playing);
+        app.update();
+        app.update();
+        app
+    }
+
+    // 这一帧按下空格，帧结束后松开（没有 InputPlugin，需要手动清除 just_pressed）
+    fn tap(app: &mut App) {
+        app.world.resource_mut::<ButtonInput<KeyCode>>().press(KeyCode::Space);
+        app.update();
+        let mut keys = app.world.resource_mut::<ButtonInput<KeyCode>>();
+        keys.release(KeyCode::Space);
+        keys.clear();
+    }
+
+    fn run(app: &mut App, frames: u32) {
+        for _ in 0..frames {
+            app.update();
+        }
+    }
+
+    fn consume(app: &mut App) -> bool {
+        app.world
+            .resource_mut::<InputBuffer>()
+            .consume_if_buffered(InputAction::Primary, BUFFER_WINDOW)
+    }
+
+    fn set_state(app: &mut App, state: GameState) {
+        app.world.resource_mut::<NextState<GameState>>().set(state);
+    }
+
+    #[test]
+    fn press_within_the_window_is_accepted_once() {
+        let mut app = buffer_app();
+        tap(&mut app);
+        // 100 ms 后动作才变得可用
+        run(&mut app, 5);
+        assert!(consume(&mut app));
+        assert!(!consume(&mut app));
+    }
+
+    #[test]
+    fn press_older_than_the_window_is_dropped() {
+        let mut app = buffer_app();
+        tap(&mut app);
+        run(&mut app, 7);
+        assert!(!consume(&mut app));
+    }
+
+    #[test]
+    fn each_press_is_consumed_separately() {
+        let mut app = buffer_app();
+        tap(&mut app);
+        run(&mut app, 1);
+        tap(&mut app);
+        assert!(consume(&mut app));
+        assert!(consume(&mut app));
+        assert!(!consume(&mut app));
+    }
+
+    #[test]
+    fn press_before_a_pause_is_discarded_on_resume() {
+        let mut app = buffer_app();
+        tap(&mut app);
+        set_state(&mut app, GameState::Paused);
+        app.update();
+        // 马上继续，按下的时间仍在窗口内，但已经跨过了暂停
+        set_state(&mut app, GameState::Playing);
+        app.update();
+        assert!(!consume(&mut app));
+    }
+
+    #[test]
+    fn press_that_resumes_from_the_pause_menu_does_not_leak_into_play() {
+        let mut app = buffer_app();
+        set_state(&mut app, GameState::Paused);
+        app.update();
+        // 暂停菜单中按空格继续，下一帧切回游戏
+        tap(&mut app);
+        set_state(&mut app, GameState::Playing);
+        app.update();
+        assert!(!consume(&mut app));
+        // 继续之后的按键照常缓冲
+        tap(&mut app);
+        assert!(consume(&mut app));
+    }
+
+    #[test]
+    fn clear_drops_everything() {
+        let mut buffer = InputBuffer::default();
+        buffer.record(InputAction::Primary);
+        buffer.clear();
+        assert!(!buffer.consume_if_buffered(InputAction::Primary, BUFFER_WINDOW));
+    }
+}
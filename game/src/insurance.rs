@@ -0,0 +1,211 @@
+use bevy::prelude::*;
+
+use crate::fonts::{text_style, FontAssets, FontKind};
+use crate::i18n::Locale;
+use crate::profile::Profile;
+use crate::profiles::ProfilePaths;
+use crate::GameEntity;
+
+// 每局最多购买的保险代币数和单价（金币）
+pub const MAX_TOKENS: u8 = 2;
+pub const TOKEN_PRICE: u32 = 30;
+// 每多少分换一枚金币，在一局结束时存入钱包
+const POINTS_PER_COIN: u32 = 100;
+
+const TOKEN_COLOR: Color = Color::rgb(1.0, 0.8, 0.3);
+const USED_TOKEN_COLOR: Color = Color::rgba(0.5, 0.5, 0.5, 0.4);
+
+// 本局剩余的保险代币：丢掉最后一个球时先用掉一枚代币，不扣生命。
+// 开局时买下的数量记在 RunStats::insurance_bought
+#[derive(Resource, Default, Debug, Clone, Copy)]
+pub struct Insurance(pub u8);
+
+impl Insurance {
+    // 有代币时用掉一枚并返回 true
+    pub fn consume(&mut self) -> bool {
+        if self.0 == 0 {
+            return false;
+        }
+        self.0 -= 1;
+        true
+    }
+}
+
+// 难度选择界面上的购买数量文本
+#[derive(Component)]
+pub struct InsuranceOfferText;
+
+// HUD 上的代币图标，用掉的变灰
+#[derive(Component)]
+pub struct InsuranceIcon(pub u8);
+
+pub fn cost(tokens: u8) -> u32 {
+    tokens as u32 * TOKEN_PRICE
+}
+
+// 没用掉的代币在一局结束时按半价退回
+pub fn refund(unused: u8) -> u32 {
+    cost(unused) / 2
+}
+
+pub fn coins_for_score(score: u32) -> u32 {
+    score / POINTS_PER_COIN
+}
+
+// 钱包里的金币够买的最多代币数
+pub fn affordable(coins: u32) -> u8 {
+    (coins / TOKEN_PRICE).min(MAX_TOKENS as u32) as u8
+}
+
+// 开局时付款；钱不够时按买得起的数量购买，返回实际买到的代币数
+pub fn purchase(paths: &ProfilePaths, wanted: u8) -> u8 {
+    if wanted == 0 {
+        return 0;
+    }
+    let mut profile = Profile::load(paths);
+    let tokens = wanted.min(affordable(profile.coins));
+    if tokens == 0 {
+        return 0;
+    }
+    profile.coins -= cost(tokens);
+    if let Err(e) = profile.save(paths) {
+        eprintln!("Failed to save profile: {}", e);
+        return 0;
+    }
+    tokens
+}
+
+// 一局结束时结算：按分数发放金币并退回没用掉的代币。
+// 中途放弃的局不结算，已经买下的代币也不退
+pub fn settle_run(paths: &ProfilePaths, score: u32, insurance: Insurance) {
+    let earned = coins_for_score(score) + refund(insurance.0);
+    if earned == 0 {
+        return;
+    }
+    let mut profile = Profile::load(paths);
+    profile.coins += earned;
+    if let Err(e) = profile.save(paths) {
+        eprintln!("Failed to save profile: {}", e);
+    }
+}
+
+// 购买行的文字：数量、单价和钱包余额
+pub fn offer_label(locale: &Locale, tokens: u8, coins: u32) -> String {
+    locale.tr(
+        "insurance.offer",
+        &[
+            ("tokens", &tokens),
+            ("max", &MAX_TOKENS),
+            ("price", &TOKEN_PRICE),
+            ("coins", &coins),
+        ],
+    )
+}
+
+pub fn spawn_offer_text(parent: &mut ChildBuilder, fonts: &FontAssets, locale: &Locale, tokens: u8, coins: u32) {
+    parent.spawn((
+        TextBundle::from_section(
+            offer_label(locale, tokens, coins),
+            text_style(fonts, FontKind::Regular, 20.0, TOKEN_COLOR),
+        )
+        .with_style(Style {
+            margin: UiRect::top(Val::Px(20.0)),
+            ..default()
+        }),
+        InsuranceOfferText,
+    ));
+}
+
+// 右下角每枚买下的代币一个图标
+pub fn spawn_insurance_hud(commands: &mut Commands, bought: u8) {
+    if bought == 0 {
+        return;
+    }
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    right: Val::Px(10.0),
+                    bottom: Val::Px(10.0),
+                    column_gap: Val::Px(6.0),
+                    ..default()
+                },
+                ..default()
+            },
+            GameEntity,
+        ))
+        .with_children(|parent| {
+            for index in 0..bought {
+                parent.spawn((
+                    NodeBundle {
+                        style: Style {
+                            width: Val::Px(18.0),
+                            height: Val::Px(18.0),
+                            ..default()
+                        },
+                        background_color: BackgroundColor(TOKEN_COLOR),
+                        ..default()
+                    },
+                    InsuranceIcon(index),
+                ));
+            }
+        });
+}
+
+// 已经用掉的代币图标变灰，从右往左
+pub fn update_insurance_hud(insurance: Res<Insurance>, mut icons: Query<(&InsuranceIcon, &mut BackgroundColor)>) {
+    for (icon, mut color) in icons.iter_mut() {
+        color.0 = if icon.0 < insurance.0 { TOKEN_COLOR } else { USED_TOKEN_COLOR };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cost_and_half_price_refund() {
+        assert_eq!(cost(0), 0);
+        assert_eq!(cost(MAX_TOKENS), MAX_TOKENS as u32 * TOKEN_PRICE);
+        assert_eq!(refund(0), 0);
+        assert_eq!(refund(1), TOKEN_PRICE / 2);
+        assert_eq!(refund(2), TOKEN_PRICE);
+    }
+
+    #[test]
+    fn coins_round_down_per_hundred_points() {
+        assert_eq!(coins_for_score(0), 0);
+        assert_eq!(coins_for_score(99), 0);
+        assert_eq!(coins_for_score(100), 1);
+        assert_eq!(coins_for_score(12_345), 123);
+    }
+
+    #[test]
+    fn affordable_is_capped_by_wallet_and_max_tokens() {
+        assert_eq!(affordable(0), 0);
+        assert_eq!(affordable(TOKEN_PRICE - 1), 0);
+        assert_eq!(affordable(TOKEN_PRICE), 1);
+        assert_eq!(affordable(TOKEN_PRICE * 2 - 1), 1);
+        assert_eq!(affordable(TOKEN_PRICE * 2), 2);
+        assert_eq!(affordable(u32::MAX), MAX_TOKENS);
+    }
+
+    #[test]
+    fn buying_and_refunding_never_gains_coins() {
+        for tokens in 0..=MAX_TOKENS {
+            for unused in 0..=tokens {
+                assert!(refund(unused) <= cost(tokens));
+            }
+        }
+    }
+
+    #[test]
+    fn consume_stops_at_zero() {
+        let mut insurance = Insurance(2);
+        assert!(insurance.consume());
+        assert!(insurance.consume());
+        assert!(!insurance.consume());
+        assert_eq!(insurance.0, 0);
+    }
+}
@@ -0,0 +1,278 @@
+use bevy::prelude::*;
+
+use crate::board_snapshot::{spawn_board_diagram, BoardSnapshot};
+use crate::curse::Curse;
+use crate::fonts::{text_style, FontAssets, FontKind};
+use crate::i18n::{name_key, Locale};
+use crate::weekly_event::EventRun;
+use crate::{generate_layout, BrickType, DifficultySettings, GameState, Layout, Level, RunSeed};
+
+// 没有按键时自动开始的秒数
+const INTERLUDE_DURATION: f32 = 4.0;
+
+// 关卡预览界面，elapsed 为已显示的秒数
+#[derive(Component, Default)]
+pub struct LevelInterludeUI {
+    elapsed: f32,
+}
+
+#[derive(Component)]
+pub struct InterludeCountdownText;
+
+// 下一关会用到的特殊规则，按显示顺序
+fn level_modifiers(level: u32, layout: &Layout, time_limit: Option<f32>, locale: &Locale) -> Vec<String> {
+    let mut modifiers = Vec::new();
+
+    if let Some(time_limit) = time_limit {
+        modifiers.push(locale.tr("interlude.time_limit", &[("seconds", &(time_limit as u32))]));
+    }
+
+    // 本关第一次出现的砖块类型
+    let mut new_types: Vec<BrickType> = Vec::new();
+    for spec in &layout.bricks {
+        if spec.brick_type.first_level() == level && !new_types.contains(&spec.brick_type) {
+            new_types.push(spec.brick_type);
+        }
+    }
+    for brick_type in new_types {
+        let name = locale.tr(&name_key("brick", brick_type.name()), &[]);
+        modifiers.push(locale.tr("interlude.new_brick", &[("brick", &name)]));
+    }
+
+    let armored = layout.bricks.iter().filter(|spec| spec.armor.is_some()).count();
+    if armored > 0 {
+        modifiers.push(locale.tr("interlude.armored", &[("count", &armored)]));
+    }
+
+    let generators = layout.bricks.iter().filter(|spec| spec.generator.is_some()).count();
+    if generators > 0 {
+        modifiers.push(locale.tr("interlude.generators", &[("count", &generators)]));
+    }
+
+    modifiers
+}
+
+// 关卡之间的预览：关卡号、用同一种子提前生成的砖块布局和特殊规则
+pub fn setup_level_interlude(
+    mut commands: Commands,
+    level: Res<Level>,
+    run_seed: Res<RunSeed>,
+    difficulty_settings: Res<DifficultySettings>,
+    fonts: Res<FontAssets>,
+    locale: Res<Locale>,
+    event_run: Res<EventRun>,
+    curse: Res<Curse>,
+) {
+    // 与 setup_game 使用同一个种子，预览和实际生成的砖块一致
+    let mut layout = generate_layout(level.0, run_seed.0);
+    let time_limit = curse.time_limit(difficulty_settings.time_limit);
+    let mut modifiers = level_modifiers(level.0, &layout, time_limit, &locale);
+    if let Some(kind) = curse.kind() {
+        let label = locale.tr(kind.label_key(), &[]);
+        modifiers.push(locale.tr("interlude.curse", &[("curse", &label)]));
+    }
+    if let Some(event) = &event_run.0 {
+        let modifier = locale.tr(event.modifier.label_key(), &[]);
+        modifiers.push(locale.tr("interlude.event", &[("modifier", &modifier)]));
+    }
+    // 迷雾下的砖块在预览中同样不显示
+    layout.bricks.retain(|spec| !event_run.fogged(spec.cell.0));
+    let snapshot = BoardSnapshot::from_layout(&layout);
+
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    width: Val::Percent(100.0),
+                    height: Val::Percent(100.0),
+                    flex_direction: FlexDirection::Column,
+                    justify_content: JustifyContent::Center,
+                    align_items: AlignItems::Center,
+                    row_gap: Val::Px(12.0),
+                    ..default()
+                },
+                background_color: BackgroundColor(Color::rgb(0.1, 0.1, 0.15)),
+                ..default()
+            },
+            LevelInterludeUI::default(),
+        ))
+        .with_children(|parent| {
+            parent.spawn(TextBundle::from_section(
+                locale.tr("interlude.title", &[("level", &level.0)]),
+                text_style(&fonts, FontKind::Bold, 64.0, Color::WHITE),
+            ));
+
+            spawn_board_diagram(parent, &snapshot);
+
+            for modifier in &modifiers {
+                parent.spawn(TextBundle::from_section(
+                    modifier.as_str(),
+                    text_style(&fonts, FontKind::Regular, 22.0, Color::rgb(1.0, 0.85, 0.2)),
+                ));
+            }
+
+            parent.spawn((
+                TextBundle::from_section(
+                    locale.tr("interlude.hint", &[("seconds", &(INTERLUDE_DURATION as u32))]),
+                    text_style(&fonts, FontKind::Regular, 20.0, Color::rgb(0.6, 0.6, 0.6)),
+                ).with_style(Style {
+                    margin: UiRect::top(Val::Px(20.0)),
+                    ..default()
+                }),
+                InterludeCountdownText,
+            ));
+        });
+}
+
+// 按任意键（或手柄按键）立即开始，否则倒计时结束后自动开始
+pub fn level_interlude_system(
+    time: Res<Time>,
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    gamepad_input: Res<ButtonInput<GamepadButton>>,
+    locale: Res<Locale>,
+    mut next_state: ResMut<NextState<GameState>>,
+    mut ui_query: Query<&mut LevelInterludeUI>,
+    mut text_query: Query<&mut Text, With<InterludeCountdownText>>,
+) {
+    let Ok(mut interlude) = ui_query.get_single_mut() else {
+        return;
+    };
+    interlude.elapsed += time.delta_seconds();
+
+    let pressed = keyboard_input.get_just_pressed().next().is_some() || gamepad_input.get_just_pressed().next().is_some();
+    if pressed || interlude.elapsed >= INTERLUDE_DURATION {
+        next_state.set(GameState::Playing);
+        return;
+    }
+
+    let remaining = (INTERLUDE_DURATION - interlude.elapsed).ceil() as u32;
+    for mut text in text_query.iter_mut() {
+        text.sections[0].value = locale.tr("interlude.hint", &[("seconds", &remaining)]);
+    }
+}
+
+pub fn cleanup_level_interlude(mut commands: Commands, query: Query<Entity, With<LevelInterludeUI>>) {
+    for entity in query.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::i18n::Language;
+    use crate::{BrickSpec, Side};
+    use bevy::time::TimeUpdateStrategy;
+    use std::time::Duration;
+
+    fn spec(cell: (usize, usize), brick_type: BrickType) -> BrickSpec {
+        BrickSpec {
+            cell,
+            brick_type,
+            health: 1,
+            armor: None,
+            guaranteed_drop: None,
+            golden: false,
+            generator: None,
+        }
+    }
+
+    #[test]
+    fn modifiers_list_the_time_limit_new_bricks_armor_and_generators() {
+        let locale = Locale::new(Language::En);
+        let layout = Layout {
+            bricks: vec![
+                spec((0, 0), BrickType::Normal),
+                spec((0, 1), BrickType::Ice),
+                BrickSpec { armor: Some(Side::Top), ..spec((0, 2), BrickType::Hard) },
+                spec((0, 3), BrickType::Unbreakable),
+                spec((0, 4), BrickType::Ice),
+                BrickSpec { generator: Some(0), ..spec((1, 0), BrickType::Hard) },
+            ],
+            ..default()
+        };
+        assert_eq!(
+            level_modifiers(2, &layout, Some(90.0), &locale),
+            [
+                "Time limit: 90s",
+                "New brick: Ice",
+                "New brick: Unbreakable",
+                "Armored bricks: 1",
+                "1 shield generators: destroy them left to right",
+            ],
+        );
+        // 同样的砖块在后面的关卡不算新出现
+        assert_eq!(
+            level_modifiers(3, &layout, None, &locale),
+            ["Armored bricks: 1", "1 shield generators: destroy them left to right"],
+        );
+    }
+
+    #[test]
+    fn a_plain_level_has_no_modifiers() {
+        let locale = Locale::new(Language::En);
+        let layout = Layout {
+            bricks: vec![spec((0, 0), BrickType::Normal), spec((0, 1), BrickType::Hard)],
+            ..default()
+        };
+        assert!(level_modifiers(3, &layout, None, &locale).is_empty());
+    }
+
+    fn interlude_app() -> App {
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins)
+            .insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_millis(250)))
+            .init_state::<GameState>()
+            .insert_resource(NextState(Some(GameState::LevelInterlude)))
+            .init_resource::<ButtonInput<KeyCode>>()
+            .init_resource::<ButtonInput<GamepadButton>>()
+            .insert_resource(Locale::new(Language::En))
+            .add_systems(Update, level_interlude_system.run_if(in_state(GameState::LevelInterlude)));
+        app.world.spawn(LevelInterludeUI::default());
+        app.world.spawn((Text::from_section("", TextStyle::default()), InterludeCountdownText));
+        app.update();
+        app
+    }
+
+    fn state(app: &App) -> GameState {
+        *app.world.resource::<State<GameState>>().get()
+    }
+
+    fn hint(app: &mut App) -> String {
+        let mut texts = app.world.query_filtered::<&Text, With<InterludeCountdownText>>();
+        texts.single(&app.world).sections[0].value.clone()
+    }
+
+    #[test]
+    fn play_starts_by_itself_after_the_countdown() {
+        let mut app = interlude_app();
+        app.update();
+        assert_eq!(hint(&mut app), "Press any key to start (4)");
+        // 第一帧没有经过时间，之后每帧 0.25 秒
+        let frames = (INTERLUDE_DURATION / 0.25) as usize;
+        for _ in 1..frames - 1 {
+            app.update();
+        }
+        assert_eq!(state(&app), GameState::LevelInterlude);
+        assert_eq!(hint(&mut app), "Press any key to start (1)");
+        app.update();
+        app.update();
+        assert_eq!(state(&app), GameState::Playing);
+    }
+
+    #[test]
+    fn any_key_or_button_starts_play_at_once() {
+        let mut app = interlude_app();
+        app.world.resource_mut::<ButtonInput<KeyCode>>().press(KeyCode::KeyQ);
+        app.update();
+        app.update();
+        assert_eq!(state(&app), GameState::Playing);
+
+        let mut app = interlude_app();
+        let button = GamepadButton::new(Gamepad::new(0), GamepadButtonType::South);
+        app.world.resource_mut::<ButtonInput<GamepadButton>>().press(button);
+        app.update();
+        app.update();
+        assert_eq!(state(&app), GameState::Playing);
+    }
+}
@@ -0,0 +1,536 @@
+use bevy::app::AppExit;
+use bevy::prelude::*;
+
+use crate::fonts::{text_style, FontAssets, FontKind};
+use crate::i18n::Locale;
+use crate::rank_movement::LeaderboardHistory;
+use crate::{
+    start_new_run, Difficulty, DifficultySettings, GameEntity, GameState, PlayerName, RunResources, BALL_COLOR,
+    BALL_SIZE, BALL_SPEED, PADDLE_COLOR, PADDLE_SIZE, PADDLE_SPEED, PADDLE_Y, WINDOW_HEIGHT, WINDOW_WIDTH,
+};
+
+// 菜单界面无人操作多久后回到待机画面（秒）
+const IDLE_TIMEOUT: f32 = 30.0;
+// 退出组合键（左 Ctrl + 左 Shift + F12）需要按住的秒数
+const QUIT_HOLD_TIME: f32 = 3.0;
+// 待机画面提示文字的闪烁周期（秒）
+const PROMPT_BLINK: f32 = 0.6;
+// 场地标签的最大长度
+const MAX_VENUE_LENGTH: usize = 64;
+
+// 街机展台模式的配置，由 --kiosk[=场地] 启动参数决定
+#[derive(Resource, Debug, Clone)]
+pub struct KioskConfig {
+    pub enabled: bool,
+    // 提交分数时附带的场地标签
+    pub venue: Option<String>,
+    // 展台模式固定使用的难度
+    pub difficulty: Difficulty,
+}
+
+impl Default for KioskConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            venue: None,
+            difficulty: Difficulty::Medium,
+        }
+    }
+}
+
+impl KioskConfig {
+    // 解析启动参数：--kiosk 开启展台模式，--kiosk=场地 同时设置场地标签
+    pub fn from_args(args: impl Iterator<Item = String>) -> Self {
+        let mut config = Self::default();
+        for arg in args {
+            if arg == "--kiosk" {
+                config.enabled = true;
+            } else if let Some(venue) = arg.strip_prefix("--kiosk=") {
+                config.enabled = true;
+                let venue = venue.trim();
+                if !venue.is_empty() {
+                    config.venue = Some(venue.chars().take(MAX_VENUE_LENGTH).collect());
+                }
+            }
+        }
+        config
+    }
+
+    // 输入名字之后进入的界面：展台模式直接开始游戏
+    pub fn after_name_entry(&self) -> GameState {
+        if self.enabled {
+            GameState::Playing
+        } else {
+            GameState::DifficultySelect
+        }
+    }
+}
+
+pub fn kiosk_enabled(kiosk: Res<KioskConfig>) -> bool {
+    kiosk.enabled
+}
+
+pub fn kiosk_disabled(kiosk: Res<KioskConfig>) -> bool {
+    !kiosk.enabled
+}
+
+// 无操作计时：有输入时清零，达到 IDLE_TIMEOUT 时返回 true 并重新计时
+#[derive(Debug, Default)]
+pub struct IdleTimer {
+    idle: f32,
+}
+
+impl IdleTimer {
+    pub fn tick(&mut self, active: bool, delta: f32) -> bool {
+        if active {
+            self.idle = 0.0;
+            return false;
+        }
+        self.idle += delta;
+        if self.idle >= IDLE_TIMEOUT {
+            self.idle = 0.0;
+            true
+        } else {
+            false
+        }
+    }
+
+    pub fn reset(&mut self) {
+        self.idle = 0.0;
+    }
+}
+
+// 退出保护：组合键连续按住 QUIT_HOLD_TIME 秒才允许退出，中途松开重新计时
+#[derive(Debug, Default)]
+pub struct QuitGuard {
+    held: f32,
+}
+
+impl QuitGuard {
+    pub fn tick(&mut self, combo_held: bool, delta: f32) -> bool {
+        if !combo_held {
+            self.held = 0.0;
+            return false;
+        }
+        self.held += delta;
+        self.held >= QUIT_HOLD_TIME
+    }
+}
+
+// 展台模式的运行状态
+#[derive(Resource, Default)]
+pub struct KioskSession {
+    idle: IdleTimer,
+    quit: QuitGuard,
+    // 本次运行开始过的局数，用于生成玩家名字
+    players: u32,
+}
+
+// 自动生成的三字符玩家名字：P01、P02……
+fn generated_name(players: u32) -> String {
+    format!("P{:02}", players % 100)
+}
+
+// 待机画面不计入的界面：游戏进行中和关卡之间的过场
+fn counts_as_menu(state: &GameState) -> bool {
+    !matches!(
+        state,
+        GameState::Attract
+            | GameState::Playing
+            | GameState::NextLevel
+            | GameState::LevelInterlude
+            | GameState::DeathSequence
+            | GameState::LifeReplay
+    )
+}
+
+fn any_input(
+    keyboard_input: &ButtonInput<KeyCode>,
+    gamepad_input: &ButtonInput<GamepadButton>,
+    mouse_input: &ButtonInput<MouseButton>,
+) -> bool {
+    keyboard_input.get_just_pressed().next().is_some()
+        || gamepad_input.get_just_pressed().next().is_some()
+        || mouse_input.get_just_pressed().next().is_some()
+}
+
+// 待机画面
+#[derive(Component)]
+pub struct AttractUI;
+
+#[derive(Component)]
+pub struct AttractPrompt;
+
+// 演示用的球和自动跟随的挡板
+#[derive(Component)]
+pub struct DemoBall {
+    velocity: Vec2,
+}
+
+#[derive(Component)]
+pub struct DemoPaddle;
+
+// 主菜单在展台模式下由待机画面代替
+pub fn redirect_main_menu(mut next_state: ResMut<NextState<GameState>>) {
+    next_state.set(GameState::Attract);
+}
+
+// 进入待机画面时清除上一位玩家留下的局内状态
+pub fn setup_attract(
+    mut commands: Commands,
+    kiosk: Res<KioskConfig>,
+    fonts: Res<FontAssets>,
+    locale: Res<Locale>,
+    mut run: RunResources,
+    mut rank_history: ResMut<LeaderboardHistory>,
+    mut session: ResMut<KioskSession>,
+    cameras: Query<(), With<Camera>>,
+) {
+    *run.difficulty_settings = DifficultySettings::new(kiosk.difficulty);
+    start_new_run(&mut run, None);
+    *rank_history = LeaderboardHistory::default();
+    session.idle.reset();
+
+    // 待机画面之后的界面沿用这个摄像机
+    if cameras.is_empty() {
+        commands.spawn(Camera2dBundle::default());
+    }
+
+    commands.spawn((
+        SpriteBundle {
+            sprite: Sprite {
+                color: PADDLE_COLOR,
+                ..default()
+            },
+            transform: Transform {
+                translation: Vec3::new(0.0, PADDLE_Y, 0.0),
+                scale: Vec3::new(PADDLE_SIZE.x, PADDLE_SIZE.y, 1.0),
+                ..default()
+            },
+            ..default()
+        },
+        DemoPaddle,
+        AttractUI,
+    ));
+    commands.spawn((
+        SpriteBundle {
+            sprite: Sprite {
+                color: BALL_COLOR,
+                ..default()
+            },
+            transform: Transform {
+                translation: Vec3::new(0.0, PADDLE_Y + 50.0, 0.0),
+                scale: Vec3::new(BALL_SIZE.x, BALL_SIZE.y, 1.0),
+                ..default()
+            },
+            ..default()
+        },
+        DemoBall {
+            velocity: Vec2::new(0.6, 0.8) * BALL_SPEED,
+        },
+        AttractUI,
+    ));
+
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    width: Val::Percent(100.0),
+                    height: Val::Percent(100.0),
+                    flex_direction: FlexDirection::Column,
+                    justify_content: JustifyContent::Center,
+                    align_items: AlignItems::Center,
+                    ..default()
+                },
+                ..default()
+            },
+            AttractUI,
+        ))
+        .with_children(|parent| {
+            parent.spawn(TextBundle::from_section(
+                locale.tr("menu.title", &[]),
+                text_style(&fonts, FontKind::Bold, 64.0, Color::WHITE),
+            ));
+
+            if let Some(venue) = &kiosk.venue {
+                parent.spawn(TextBundle::from_section(
+                    venue.as_str(),
+                    text_style(&fonts, FontKind::Regular, 24.0, Color::rgb(0.5, 0.7, 0.9)),
+                ).with_style(Style {
+                    margin: UiRect::top(Val::Px(10.0)),
+                    ..default()
+                }));
+            }
+
+            parent.spawn((
+                TextBundle::from_section(
+                    locale.tr("attract.press_start", &[]),
+                    text_style(&fonts, FontKind::Bold, 30.0, Color::rgb(1.0, 0.85, 0.2)),
+                ).with_style(Style {
+                    margin: UiRect::top(Val::Px(50.0)),
+                    ..default()
+                }),
+                AttractPrompt,
+            ));
+        });
+}
+
+// 演示动画；任意按键以固定难度开始新的一局，先输入街机式名字
+pub fn attract_system(
+    time: Res<Time>,
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    gamepad_input: Res<ButtonInput<GamepadButton>>,
+    mouse_input: Res<ButtonInput<MouseButton>>,
+    mut session: ResMut<KioskSession>,
+    mut player_name: ResMut<PlayerName>,
+    mut next_state: ResMut<NextState<GameState>>,
+    mut run: RunResources,
+    mut ball_query: Query<(&mut Transform, &mut DemoBall), Without<DemoPaddle>>,
+    mut paddle_query: Query<&mut Transform, With<DemoPaddle>>,
+    mut prompt_query: Query<&mut Visibility, With<AttractPrompt>>,
+) {
+    if any_input(&keyboard_input, &gamepad_input, &mouse_input) {
+        session.players += 1;
+        player_name.0 = generated_name(session.players);
+        start_new_run(&mut run, None);
+        next_state.set(GameState::EnterName);
+        return;
+    }
+
+    let delta = time.delta_seconds();
+    let half_width = WINDOW_WIDTH / 2.0 - BALL_SIZE.x / 2.0;
+    let half_height = WINDOW_HEIGHT / 2.0 - BALL_SIZE.y / 2.0;
+    let paddle_top = PADDLE_Y + (PADDLE_SIZE.y + BALL_SIZE.y) / 2.0;
+
+    let mut ball_x = 0.0;
+    for (mut transform, mut ball) in ball_query.iter_mut() {
+        transform.translation += (ball.velocity * delta).extend(0.0);
+        if transform.translation.x.abs() > half_width {
+            transform.translation.x = transform.translation.x.clamp(-half_width, half_width);
+            ball.velocity.x = -ball.velocity.x;
+        }
+        if transform.translation.y > half_height {
+            transform.translation.y = half_height;
+            ball.velocity.y = -ball.velocity.y.abs();
+        } else if transform.translation.y < paddle_top && ball.velocity.y < 0.0 {
+            // 挡板总能接住，演示永远不会丢球
+            transform.translation.y = paddle_top;
+            ball.velocity.y = ball.velocity.y.abs();
+        }
+        ball_x = transform.translation.x;
+    }
+
+    let max_x = WINDOW_WIDTH / 2.0 - PADDLE_SIZE.x / 2.0;
+    for mut transform in paddle_query.iter_mut() {
+        let step = (ball_x - transform.translation.x).clamp(-PADDLE_SPEED * delta, PADDLE_SPEED * delta);
+        transform.translation.x = (transform.translation.x + step).clamp(-max_x, max_x);
+    }
+
+    let visible = ((time.elapsed_seconds() / PROMPT_BLINK) as u32).is_multiple_of(2);
+    for mut visibility in prompt_query.iter_mut() {
+        *visibility = if visible { Visibility::Inherited } else { Visibility::Hidden };
+    }
+}
+
+pub fn cleanup_attract(mut commands: Commands, query: Query<Entity, With<AttractUI>>) {
+    for entity in query.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+// 菜单界面无人操作 IDLE_TIMEOUT 秒后回到待机画面；暂停中的局一并结束
+pub fn idle_reset_system(
+    mut commands: Commands,
+    time: Res<Time>,
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    gamepad_input: Res<ButtonInput<GamepadButton>>,
+    mouse_input: Res<ButtonInput<MouseButton>>,
+    state: Res<State<GameState>>,
+    mut session: ResMut<KioskSession>,
+    mut next_state: ResMut<NextState<GameState>>,
+    game_entities: Query<Entity, With<GameEntity>>,
+) {
+    if state.is_changed() || !counts_as_menu(state.get()) {
+        session.idle.reset();
+        return;
+    }
+
+    let active = any_input(&keyboard_input, &gamepad_input, &mouse_input);
+    if session.idle.tick(active, time.delta_seconds()) {
+        for entity in game_entities.iter() {
+            commands.entity(entity).despawn_recursive();
+        }
+        next_state.set(GameState::Attract);
+    }
+}
+
+// 展台模式下 ESC 和关闭窗口都不会退出，只有按住隐藏的组合键才退出
+pub fn quit_combo_system(
+    time: Res<Time>,
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    mut session: ResMut<KioskSession>,
+    mut exit: EventWriter<AppExit>,
+) {
+    let combo_held = keyboard_input.all_pressed([KeyCode::ControlLeft, KeyCode::ShiftLeft, KeyCode::F12]);
+    if session.quit.tick(combo_held, time.delta_seconds()) {
+        exit.send(AppExit);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bevy::ecs::event::ManualEventReader;
+    use bevy::time::TimeUpdateStrategy;
+    use std::time::Duration;
+
+    // 不超过 Time<Virtual> 默认的单帧上限 0.25 秒
+    const FRAME: f32 = 0.25;
+
+    fn args(args: &[&str]) -> KioskConfig {
+        KioskConfig::from_args(args.iter().map(|arg| arg.to_string()))
+    }
+
+    // 只有展台系统的无窗口 App，每帧固定前进 FRAME 秒
+    fn kiosk_app() -> App {
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins)
+            .insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_secs_f32(FRAME)))
+            .init_state::<GameState>()
+            .init_resource::<ButtonInput<KeyCode>>()
+            .init_resource::<ButtonInput<GamepadButton>>()
+            .init_resource::<ButtonInput<MouseButton>>()
+            .init_resource::<KioskSession>()
+            .add_systems(Update, (idle_reset_system, quit_combo_system));
+        app
+    }
+
+    fn enter(app: &mut App, state: GameState) {
+        app.world.resource_mut::<NextState<GameState>>().set(state);
+        app.update();
+    }
+
+    fn run_seconds(app: &mut App, seconds: f32) {
+        for _ in 0..(seconds / FRAME).round() as u32 {
+            app.update();
+        }
+    }
+
+    fn state(app: &App) -> GameState {
+        *app.world.resource::<State<GameState>>().get()
+    }
+
+    // 这一帧按下一个键，帧结束后松开（没有 InputPlugin，需要手动清除 just_pressed）
+    fn tap(app: &mut App, key: KeyCode) {
+        app.world.resource_mut::<ButtonInput<KeyCode>>().press(key);
+        app.update();
+        let mut keys = app.world.resource_mut::<ButtonInput<KeyCode>>();
+        keys.release(key);
+        keys.clear();
+    }
+
+    #[test]
+    fn kiosk_flag_sets_the_venue() {
+        assert!(!args(&["breakout"]).enabled);
+        let plain = args(&["breakout", "--kiosk"]);
+        assert!(plain.enabled);
+        assert_eq!(plain.venue, None);
+        assert_eq!(args(&["--kiosk=  Hall B "]).venue.as_deref(), Some("Hall B"));
+        assert_eq!(args(&["--kiosk="]).venue, None);
+        assert_eq!(args(&[&format!("--kiosk={}", "x".repeat(100))]).venue.unwrap().len(), MAX_VENUE_LENGTH);
+        assert_eq!(args(&["--kiosk"]).after_name_entry(), GameState::Playing);
+        assert_eq!(args(&[]).after_name_entry(), GameState::DifficultySelect);
+    }
+
+    #[test]
+    fn generated_names_stay_three_characters() {
+        assert_eq!(generated_name(1), "P01");
+        assert_eq!(generated_name(42), "P42");
+        assert_eq!(generated_name(100), "P00");
+    }
+
+    #[test]
+    fn quit_guard_needs_an_unbroken_hold() {
+        let mut guard = QuitGuard::default();
+        assert!(!guard.tick(true, 2.5));
+        // 中途松开重新计时
+        assert!(!guard.tick(false, 0.1));
+        assert!(!guard.tick(true, 2.5));
+        assert!(guard.tick(true, 0.5));
+    }
+
+    #[test]
+    fn idle_timer_fires_once_per_timeout_and_input_restarts_it() {
+        let mut timer = IdleTimer::default();
+        assert!(!timer.tick(false, IDLE_TIMEOUT - 1.0));
+        assert!(!timer.tick(true, 0.0));
+        assert!(!timer.tick(false, IDLE_TIMEOUT - 1.0));
+        assert!(timer.tick(false, 1.0));
+        assert!(!timer.tick(false, 1.0));
+    }
+
+    #[test]
+    fn idle_menus_return_to_attract_and_clear_the_run() {
+        let mut app = kiosk_app();
+        enter(&mut app, GameState::GameOver);
+        let leftover = app.world.spawn(GameEntity).id();
+
+        run_seconds(&mut app, IDLE_TIMEOUT - 1.0);
+        assert_eq!(state(&app), GameState::GameOver);
+        run_seconds(&mut app, 2.0);
+        assert_eq!(state(&app), GameState::Attract);
+        assert!(app.world.get_entity(leftover).is_none());
+    }
+
+    #[test]
+    fn input_on_a_menu_restarts_the_idle_countdown() {
+        let mut app = kiosk_app();
+        enter(&mut app, GameState::Leaderboard);
+        run_seconds(&mut app, 20.0);
+        tap(&mut app, KeyCode::ArrowDown);
+        run_seconds(&mut app, 20.0);
+        assert_eq!(state(&app), GameState::Leaderboard);
+        run_seconds(&mut app, 11.0);
+        assert_eq!(state(&app), GameState::Attract);
+    }
+
+    #[test]
+    fn gameplay_never_counts_as_idle() {
+        let mut app = kiosk_app();
+        enter(&mut app, GameState::Playing);
+        run_seconds(&mut app, IDLE_TIMEOUT * 2.0);
+        assert_eq!(state(&app), GameState::Playing);
+
+        // 切到菜单后重新计时，不沿用游戏中经过的时间
+        enter(&mut app, GameState::Paused);
+        run_seconds(&mut app, IDLE_TIMEOUT - 1.0);
+        assert_eq!(state(&app), GameState::Paused);
+    }
+
+    #[test]
+    fn only_the_held_combo_exits() {
+        let mut app = kiosk_app();
+        let mut exits = ManualEventReader::<AppExit>::default();
+        let mut exited = |app: &App| exits.read(app.world.resource::<Events<AppExit>>()).count();
+
+        // ESC 不会退出
+        for _ in 0..20 {
+            tap(&mut app, KeyCode::Escape);
+        }
+        assert_eq!(exited(&app), 0);
+
+        let mut keys = app.world.resource_mut::<ButtonInput<KeyCode>>();
+        for key in [KeyCode::ControlLeft, KeyCode::ShiftLeft, KeyCode::F12] {
+            keys.press(key);
+        }
+        run_seconds(&mut app, QUIT_HOLD_TIME - 0.5);
+        app.world.resource_mut::<ButtonInput<KeyCode>>().release(KeyCode::F12);
+        app.update();
+        app.world.resource_mut::<ButtonInput<KeyCode>>().press(KeyCode::F12);
+        run_seconds(&mut app, QUIT_HOLD_TIME - 0.5);
+        assert_eq!(exited(&app), 0);
+
+        run_seconds(&mut app, 0.5);
+        assert!(exited(&app) > 0);
+    }
+}
@@ -0,0 +1,173 @@
+use bevy::prelude::*;
+use rand::Rng;
+
+use crate::bonus::Orientation;
+use crate::hazard_rules::{LastPaddleContact, PlayClock};
+use crate::input_buffer::{InputAction, InputBuffer, BUFFER_WINDOW};
+use crate::paddle::{self, Paddle};
+use crate::{
+    Ball, DifficultySettings, PowerUpEffects, Settings, BALL_SIZE, BALL_SPEED, PADDLE_SIZE, WINDOW_HEIGHT, WINDOW_WIDTH,
+};
+
+// 预览线总长度
+const PREVIEW_LENGTH: f32 = 500.0;
+// 预览点的间距与半径
+const PREVIEW_DOT_SPACING: f32 = 18.0;
+const PREVIEW_DOT_RADIUS: f32 = 2.5;
+// 预览点沿轨迹移动的速度
+const PREVIEW_DOT_SPEED: f32 = 40.0;
+
+// 停在挡板上等待发射的球，offset 为相对挡板中心的横向位置
+#[derive(Component)]
+pub struct HeldBall {
+    pub offset: f32,
+}
+
+impl HeldBall {
+    // 随机停在挡板中间一半的位置上；随机数取自 GameRng，回放时位置相同
+    pub fn random(rng: &mut impl Rng) -> Self {
+        Self {
+            offset: rng.gen_range(-0.25..0.25) * PADDLE_SIZE.x,
+        }
+    }
+}
+
+// 发射方向：与挡板接球相同，越靠边越斜；预览和实际发射都使用它
+pub fn launch_direction(offset: f32, paddle_width: f32) -> Vec2 {
+    let hit_position = (offset / (paddle_width / 2.0)).clamp(-1.0, 1.0);
+    Vec2::new(hit_position * 0.75, 1.0).normalize()
+}
+
+// 球停在挡板上方（翻转时在下方），跟随挡板移动
+pub fn hold_balls_on_paddle(
+    paddle_query: Query<&Transform, With<Paddle>>,
+    mut ball_query: Query<(&mut Transform, &HeldBall), Without<Paddle>>,
+    power_effects: Res<PowerUpEffects>,
+    orientation: Res<Orientation>,
+) {
+    let paddle_transform = match paddle_query.get_single() {
+        Ok(transform) => transform,
+        Err(e) => {
+            paddle::warn_paddle_query("hold_balls_on_paddle", e);
+            return;
+        }
+    };
+    let half_paddle = PADDLE_SIZE.x * power_effects.paddle_size_modifier / 2.0;
+    for (mut transform, held) in ball_query.iter_mut() {
+        transform.translation.x = paddle_transform.translation.x + held.offset.clamp(-half_paddle, half_paddle);
+        transform.translation.y = paddle_transform.translation.y + (PADDLE_SIZE.y + BALL_SIZE.y) / 2.0 * orientation.sign();
+    }
+}
+
+// 按空格发射；球刚回到挡板上之前按下的空格也算
+pub fn release_held_balls(
+    mut commands: Commands,
+    mut input_buffer: ResMut<InputBuffer>,
+    mut ball_query: Query<(Entity, &HeldBall, &mut Ball)>,
+    power_effects: Res<PowerUpEffects>,
+    difficulty_settings: Res<DifficultySettings>,
+    play_clock: Res<PlayClock>,
+    orientation: Res<Orientation>,
+) {
+    if ball_query.is_empty() || !input_buffer.consume_if_buffered(InputAction::Primary, BUFFER_WINDOW) {
+        return;
+    }
+
+    let paddle_width = PADDLE_SIZE.x * power_effects.paddle_size_modifier;
+    for (entity, held, mut ball) in ball_query.iter_mut() {
+        ball.velocity = orientation.apply(launch_direction(held.offset, paddle_width))
+            * BALL_SPEED
+            * difficulty_settings.ball_speed_modifier;
+        // 从挡板发射也算一次挡板接触
+        commands
+            .entity(entity)
+            .remove::<HeldBall>()
+            .insert(LastPaddleContact { time: play_clock.now() });
+    }
+}
+
+// 计算发射轨迹：从球出发，经过第一次撞墙反弹后继续延伸
+fn preview_segments(start: Vec2, direction: Vec2) -> Vec<(Vec2, Vec2)> {
+    let max_x = WINDOW_WIDTH / 2.0 - BALL_SIZE.x / 2.0;
+    let max_y = WINDOW_HEIGHT / 2.0 - BALL_SIZE.y / 2.0;
+
+    let mut segments = Vec::new();
+    let mut position = start;
+    let mut direction = direction;
+    let mut remaining = PREVIEW_LENGTH;
+
+    for _ in 0..2 {
+        let to_side = if direction.x > 0.0 {
+            (max_x - position.x) / direction.x
+        } else if direction.x < 0.0 {
+            (-max_x - position.x) / direction.x
+        } else {
+            f32::INFINITY
+        };
+        let to_top = if direction.y > 0.0 {
+            (max_y - position.y) / direction.y
+        } else {
+            f32::INFINITY
+        };
+
+        let distance = to_side.min(to_top).max(0.0);
+        if distance >= remaining {
+            segments.push((position, position + direction * remaining));
+            break;
+        }
+
+        let hit = position + direction * distance;
+        segments.push((position, hit));
+        if to_side < to_top {
+            direction.x = -direction.x;
+        } else {
+            direction.y = -direction.y;
+        }
+        position = hit;
+        remaining -= distance;
+    }
+    segments
+}
+
+// 绘制瞄准预览；减少动态效果时显示静态虚线
+pub fn draw_aim_preview(
+    mut gizmos: Gizmos,
+    ball_query: Query<(&Transform, &HeldBall)>,
+    power_effects: Res<PowerUpEffects>,
+    settings: Res<Settings>,
+    clock: Res<PlayClock>,
+    orientation: Res<Orientation>,
+) {
+    let paddle_width = PADDLE_SIZE.x * power_effects.paddle_size_modifier;
+    let color = Color::rgba(1.0, 1.0, 1.0, 0.6);
+
+    for (transform, held) in ball_query.iter() {
+        // 在正常方向下计算轨迹，再转换回当前方向
+        let start = orientation.apply(transform.translation.truncate());
+        let segments = preview_segments(start, launch_direction(held.offset, paddle_width))
+            .into_iter()
+            .map(|(from, to)| (orientation.apply(from), orientation.apply(to)));
+
+        if settings.reduce_motion {
+            for (from, to) in segments {
+                gizmos.line_2d(from, to, color);
+            }
+            continue;
+        }
+
+        // 点沿轨迹向前流动，越远越淡
+        let phase = (clock.now() * PREVIEW_DOT_SPEED) % PREVIEW_DOT_SPACING;
+        let mut travelled = 0.0;
+        for (from, to) in segments {
+            let length = from.distance(to);
+            let direction = (to - from).normalize_or_zero();
+            let mut along = (phase - travelled).rem_euclid(PREVIEW_DOT_SPACING);
+            while along < length {
+                let fade = 1.0 - (travelled + along) / PREVIEW_LENGTH;
+                gizmos.circle_2d(from + direction * along, PREVIEW_DOT_RADIUS, color.with_a(0.6 * fade));
+                along += PREVIEW_DOT_SPACING;
+            }
+            travelled += length;
+        }
+    }
+}
@@ -0,0 +1,257 @@
+use std::collections::VecDeque;
+use std::fmt;
+
+use crate::{
+    BrickSpec, BrickType, Layout, BALL_SIZE, BRICK_COLUMNS, BRICK_ROWS, BRICK_SIZE, BRICK_START_Y, GAP_SIZE,
+    WINDOW_HEIGHT, WINDOW_WIDTH,
+};
+
+// 可破坏砖块至少要占这个比例
+const MIN_BREAKABLE_RATIO: f32 = 0.3;
+// 布局不合格时最多换几次种子重新生成，之后使用固定模板
+pub const MAX_REGENERATIONS: u64 = 5;
+
+// 布局不合格的原因，显示在性能面板上
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LayoutRejection {
+    TooFewBreakable { breakable: usize, total: usize },
+    // 被不可破坏砖块完全围住的可破坏砖块，cell 是第一块的位置
+    Sealed { count: usize, cell: (usize, usize) },
+}
+
+impl fmt::Display for LayoutRejection {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            LayoutRejection::TooFewBreakable { breakable, total } => write!(
+                f,
+                "only {}/{} bricks breakable (< {:.0}%)",
+                breakable,
+                total,
+                MIN_BREAKABLE_RATIO * 100.0
+            ),
+            LayoutRejection::Sealed { count, cell } => {
+                write!(f, "{} bricks sealed in, first at row {} col {}", count, cell.0, cell.1)
+            }
+        }
+    }
+}
+
+fn is_breakable(spec: &BrickSpec) -> bool {
+    !matches!(spec.brick_type, BrickType::Unbreakable)
+}
+
+// 检查生成的布局：可破坏砖块足够多，并且每一块都打得到
+pub fn validate_layout(layout: &Layout) -> Result<(), LayoutRejection> {
+    let total = layout.bricks.len();
+    let breakable = layout.bricks.iter().filter(|spec| is_breakable(spec)).count();
+    if (breakable as f32) < total as f32 * MIN_BREAKABLE_RATIO {
+        return Err(LayoutRejection::TooFewBreakable { breakable, total });
+    }
+    let sealed = sealed_bricks(layout);
+    match sealed.first() {
+        Some(&cell) => Err(LayoutRejection::Sealed {
+            count: sealed.len(),
+            cell,
+        }),
+        None => Ok(()),
+    }
+}
+
+// 球能从哪些边进入砖块区域：下方总是空的，两侧和上方要留出比球宽的空隙才算
+fn open_edges() -> (bool, bool) {
+    let grid_width = BRICK_COLUMNS as f32 * (BRICK_SIZE.x + GAP_SIZE) - GAP_SIZE;
+    let side_clearance = (WINDOW_WIDTH - grid_width) / 2.0;
+    let top_clearance = WINDOW_HEIGHT / 2.0 - (BRICK_START_Y + BRICK_SIZE.y / 2.0);
+    (side_clearance >= BALL_SIZE.x, top_clearance >= BALL_SIZE.y)
+}
+
+// 打不到的可破坏砖块（按行列排序）。保守的格子连通性检查：
+// 可破坏砖块迟早会被打掉，所以球能穿过空格和可破坏砖块，只有不可破坏砖块挡路；
+// 砖块之间的缝隙比球窄，不算通路
+fn sealed_bricks(layout: &Layout) -> Vec<(usize, usize)> {
+    if GAP_SIZE >= BALL_SIZE.x.min(BALL_SIZE.y) {
+        return Vec::new();
+    }
+    // None 为空格，Some(true) 为可破坏砖块
+    let mut grid = [[None; BRICK_COLUMNS]; BRICK_ROWS];
+    for spec in &layout.bricks {
+        let (row, col) = spec.cell;
+        if row < BRICK_ROWS && col < BRICK_COLUMNS {
+            grid[row][col] = Some(is_breakable(spec));
+        }
+    }
+    let passable = |row: usize, col: usize| grid[row][col] != Some(false);
+
+    let (open_sides, open_top) = open_edges();
+    let mut reached = [[false; BRICK_COLUMNS]; BRICK_ROWS];
+    let mut queue = VecDeque::new();
+    let mut enter = |row: usize, col: usize, reached: &mut [[bool; BRICK_COLUMNS]; BRICK_ROWS]| {
+        if passable(row, col) && !reached[row][col] {
+            reached[row][col] = true;
+            queue.push_back((row, col));
+        }
+    };
+    for col in 0..BRICK_COLUMNS {
+        enter(BRICK_ROWS - 1, col, &mut reached);
+        if open_top {
+            enter(0, col, &mut reached);
+        }
+    }
+    if open_sides {
+        for row in 0..BRICK_ROWS {
+            enter(row, 0, &mut reached);
+            enter(row, BRICK_COLUMNS - 1, &mut reached);
+        }
+    }
+
+    while let Some((row, col)) = queue.pop_front() {
+        let neighbours = [
+            (row.wrapping_sub(1), col),
+            (row + 1, col),
+            (row, col.wrapping_sub(1)),
+            (row, col + 1),
+        ];
+        for (next_row, next_col) in neighbours {
+            if next_row < BRICK_ROWS
+                && next_col < BRICK_COLUMNS
+                && passable(next_row, next_col)
+                && !reached[next_row][next_col]
+            {
+                reached[next_row][next_col] = true;
+                queue.push_back((next_row, next_col));
+            }
+        }
+    }
+
+    let mut sealed: Vec<(usize, usize)> = layout
+        .bricks
+        .iter()
+        .filter(|spec| is_breakable(spec))
+        .map(|spec| spec.cell)
+        .filter(|&(row, col)| row < BRICK_ROWS && col < BRICK_COLUMNS && !reached[row][col])
+        .collect();
+    sealed.sort_unstable();
+    sealed
+}
+
+// 第 attempt 次重新生成使用的种子；第 0 次就是原种子，合格的布局不受影响
+pub fn regeneration_seed(seed: u64, attempt: u64) -> u64 {
+    seed ^ attempt.wrapping_mul(0xD6E8_FEB8_6659_FD93)
+}
+
+// 多次生成都不合格时使用的固定模板：没有不可破坏砖块，最上面一行是坚硬砖块，第二关起中间一行是冰砖
+pub fn fallback_bricks(level: u32) -> Vec<BrickSpec> {
+    let hard_health = if level >= 3 { 3 } else { 2 };
+    let mut bricks = Vec::with_capacity(BRICK_ROWS * BRICK_COLUMNS);
+    for row in 0..BRICK_ROWS {
+        for col in 0..BRICK_COLUMNS {
+            let (brick_type, health) = match row {
+                0 => (BrickType::Hard, hard_health),
+                2 if level >= 2 => (BrickType::Ice, 1),
+                _ => (BrickType::Normal, 1),
+            };
+            bricks.push(BrickSpec {
+                cell: (row, col),
+                brick_type,
+                health,
+                armor: None,
+                guaranteed_drop: None,
+                golden: false,
+                generator: None,
+            });
+        }
+    }
+    bricks
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // 按每格的类型生成整面砖墙；walls 中的格子是不可破坏砖块，其余是普通砖块
+    fn layout_with_walls(walls: &[(usize, usize)]) -> Layout {
+        let mut bricks = fallback_bricks(1);
+        for spec in &mut bricks {
+            if walls.contains(&spec.cell) {
+                spec.brick_type = BrickType::Unbreakable;
+                spec.health = -1;
+            }
+        }
+        Layout { bricks, ..Default::default() }
+    }
+
+    fn all_cells() -> impl Iterator<Item = (usize, usize)> {
+        (0..BRICK_ROWS).flat_map(|row| (0..BRICK_COLUMNS).map(move |col| (row, col)))
+    }
+
+    #[test]
+    fn fallback_layouts_always_pass() {
+        for level in 1..=10 {
+            let layout = Layout {
+                bricks: fallback_bricks(level),
+                ..Default::default()
+            };
+            assert_eq!(validate_layout(&layout), Ok(()), "level {}", level);
+        }
+    }
+
+    #[test]
+    fn a_wall_of_unbreakable_bricks_is_rejected() {
+        let walls: Vec<_> = all_cells().collect();
+        assert_eq!(
+            validate_layout(&layout_with_walls(&walls)),
+            Err(LayoutRejection::TooFewBreakable { breakable: 0, total: 60 })
+        );
+    }
+
+    #[test]
+    fn breakable_ratio_boundary_is_inclusive() {
+        // 最下面一行和倒数第二行的 8 块可破坏：18/60 正好 30%
+        let keep = |(row, col): (usize, usize)| row == BRICK_ROWS - 1 || (row == BRICK_ROWS - 2 && col < 8);
+        let walls: Vec<_> = all_cells().filter(|&cell| !keep(cell)).collect();
+        assert_eq!(validate_layout(&layout_with_walls(&walls)), Ok(()));
+
+        let mut walls = walls;
+        walls.push((BRICK_ROWS - 2, 7));
+        assert_eq!(
+            validate_layout(&layout_with_walls(&walls)),
+            Err(LayoutRejection::TooFewBreakable { breakable: 17, total: 60 })
+        );
+    }
+
+    #[test]
+    fn a_brick_walled_in_on_four_sides_is_sealed() {
+        // 斜角上的格子是空的也一样：砖块之间的缝隙比球窄，只能横竖移动
+        let walls = [(1, 4), (3, 4), (2, 3), (2, 5)];
+        assert_eq!(
+            validate_layout(&layout_with_walls(&walls)),
+            Err(LayoutRejection::Sealed { count: 1, cell: (2, 4) })
+        );
+    }
+
+    #[test]
+    fn a_walled_pocket_reports_every_sealed_brick() {
+        // 2x2 的口袋，左上角是 (2, 3)
+        let walls = [(1, 3), (1, 4), (4, 3), (4, 4), (2, 2), (3, 2), (2, 5), (3, 5)];
+        assert_eq!(
+            validate_layout(&layout_with_walls(&walls)),
+            Err(LayoutRejection::Sealed { count: 4, cell: (2, 3) })
+        );
+    }
+
+    #[test]
+    fn pockets_on_the_edge_of_the_grid_are_reachable_from_outside() {
+        // 两侧和上方都留出了比球宽的空隙
+        let left = [(1, 0), (3, 0), (2, 1)];
+        assert_eq!(validate_layout(&layout_with_walls(&left)), Ok(()));
+        let top = [(0, 3), (0, 5), (1, 4)];
+        assert_eq!(validate_layout(&layout_with_walls(&top)), Ok(()));
+    }
+
+    #[test]
+    fn bricks_outside_the_grid_are_ignored() {
+        let mut layout = layout_with_walls(&[]);
+        layout.bricks[0].cell = (BRICK_ROWS + 3, BRICK_COLUMNS + 3);
+        assert_eq!(validate_layout(&layout), Ok(()));
+    }
+}
@@ -0,0 +1,179 @@
+use serde::Deserialize;
+
+use crate::{BrickSpec, BrickType, Layout, PowerUpType, BRICK_COLUMNS, BRICK_ROWS};
+
+// 手工设计的关卡文件（RON）：每行一个字符串，每个字符一块砖；quota 为配额关的百分比，省略时要打完全部可破坏砖块
+#[derive(Debug, Deserialize)]
+pub struct LevelFile {
+    pub name: String,
+    #[serde(default)]
+    pub quota: Option<u32>,
+    pub rows: Vec<String>,
+    // 保底道具，按道具名称填写
+    #[serde(default)]
+    pub drops: Vec<LevelDrop>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct LevelDrop {
+    pub cell: (usize, usize),
+    pub power: String,
+}
+
+// 砖块字符对应的类型和耐久
+fn brick_for(symbol: char) -> Option<Option<(BrickType, i32)>> {
+    let brick = match symbol {
+        '.' => return Some(None),
+        'N' => (BrickType::Normal, 1),
+        'H' => (BrickType::Hard, 2),
+        'U' => (BrickType::Unbreakable, -1),
+        'I' => (BrickType::Ice, 1),
+        'R' => (BrickType::Rubber, 2),
+        'S' => (BrickType::Sticky, 2),
+        'B' => (BrickType::TimedBomb, 1),
+        'E' => (BrickType::Explosive, 1),
+        _ => return None,
+    };
+    Some(Some(brick))
+}
+
+impl LevelFile {
+    pub fn parse(source: &str) -> Result<Self, String> {
+        ron::from_str(source).map_err(|e| e.to_string())
+    }
+
+    // 转换为布局；行列数必须和随机关卡一致，配额在 1 到 100 之间，保底道具只能放在可破坏的砖块上
+    pub fn to_layout(&self) -> Result<Layout, String> {
+        if self.rows.len() != BRICK_ROWS {
+            return Err(format!("{}: expected {} rows, found {}", self.name, BRICK_ROWS, self.rows.len()));
+        }
+        if let Some(quota) = self.quota {
+            if !(1..=100).contains(&quota) {
+                return Err(format!("{}: quota must be between 1 and 100, found {}", self.name, quota));
+            }
+        }
+
+        let mut bricks = Vec::with_capacity(BRICK_ROWS * BRICK_COLUMNS);
+        for (row, line) in self.rows.iter().enumerate() {
+            if line.chars().count() != BRICK_COLUMNS {
+                return Err(format!("{}: row {} must have {} bricks", self.name, row, BRICK_COLUMNS));
+            }
+            for (col, symbol) in line.chars().enumerate() {
+                let brick = brick_for(symbol)
+                    .ok_or_else(|| format!("{}: unknown brick '{}' at row {}, column {}", self.name, symbol, row, col))?;
+                if let Some((brick_type, health)) = brick {
+                    bricks.push(BrickSpec {
+                        cell: (row, col),
+                        brick_type,
+                        health,
+                        armor: None,
+                        guaranteed_drop: None,
+                        golden: false,
+                        generator: None,
+                    });
+                }
+            }
+        }
+
+        for drop in &self.drops {
+            let power_type = PowerUpType::ALL
+                .into_iter()
+                .find(|power_type| power_type.name() == drop.power)
+                .ok_or_else(|| format!("{}: unknown power-up '{}'", self.name, drop.power))?;
+            let spec = bricks
+                .iter_mut()
+                .find(|spec| spec.cell == drop.cell && spec.brick_type != BrickType::Unbreakable)
+                .ok_or_else(|| format!("{}: drop at {:?} is not on a breakable brick", self.name, drop.cell))?;
+            spec.guaranteed_drop = Some(power_type);
+        }
+
+        Ok(Layout {
+            bricks,
+            quota: self.quota,
+        })
+    }
+}
+
+// 解析内嵌的关卡文件
+pub fn load_layout(source: &str) -> Result<Layout, String> {
+    LevelFile::parse(source)?.to_layout()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn level(quota: &str, rows: &[&str], drops: &str) -> String {
+        let rows: Vec<String> = rows.iter().map(|row| format!("\"{}\"", row)).collect();
+        format!("(name: \"Test\", {} rows: [{}], drops: [{}])", quota, rows.join(", "), drops)
+    }
+
+    const FULL: [&str; 6] = ["NNNNNNNNNN"; 6];
+
+    #[test]
+    fn quota_defaults_to_off() {
+        let layout = load_layout(&level("", &FULL, "")).unwrap();
+        assert_eq!(layout.quota, None);
+        assert_eq!(layout.bricks.len(), BRICK_ROWS * BRICK_COLUMNS);
+        let layout = load_layout(&level("quota: Some(70),", &FULL, "")).unwrap();
+        assert_eq!(layout.quota, Some(70));
+    }
+
+    #[test]
+    fn symbols_map_to_brick_types_and_empty_cells_are_skipped() {
+        let rows = ["NHUIRSBE..", "NNNNNNNNNN", "NNNNNNNNNN", "NNNNNNNNNN", "NNNNNNNNNN", "NNNNNNNNNN"];
+        let layout = load_layout(&level("", &rows, "")).unwrap();
+        assert_eq!(layout.bricks.len(), BRICK_ROWS * BRICK_COLUMNS - 2);
+        let first_row: Vec<(BrickType, i32)> =
+            layout.bricks.iter().take(8).map(|spec| (spec.brick_type, spec.health)).collect();
+        assert_eq!(
+            first_row,
+            vec![
+                (BrickType::Normal, 1),
+                (BrickType::Hard, 2),
+                (BrickType::Unbreakable, -1),
+                (BrickType::Ice, 1),
+                (BrickType::Rubber, 2),
+                (BrickType::Sticky, 2),
+                (BrickType::TimedBomb, 1),
+                (BrickType::Explosive, 1),
+            ]
+        );
+        assert_eq!(layout.bricks[8].cell, (1, 0));
+    }
+
+    #[test]
+    fn malformed_levels_are_rejected() {
+        assert!(load_layout(&level("", &FULL[..5], "")).is_err());
+        let mut short = FULL;
+        short[2] = "NNNNNNNNN";
+        assert!(load_layout(&level("", &short, "")).is_err());
+        let mut unknown = FULL;
+        unknown[0] = "NNNNXNNNNN";
+        let error = load_layout(&level("", &unknown, "")).err().unwrap();
+        assert!(error.contains("unknown brick 'X' at row 0, column 4"), "{}", error);
+        assert!(load_layout(&level("quota: Some(0),", &FULL, "")).is_err());
+        assert!(load_layout(&level("quota: Some(101),", &FULL, "")).is_err());
+        assert!(load_layout(&level("", &FULL, "(cell: (0, 0), power: \"Jetpack\")")).is_err());
+        let mut walled = FULL;
+        walled[0] = "UNNNNNNNNN";
+        assert!(load_layout(&level("", &walled, "(cell: (0, 0), power: \"Multi Ball\")")).is_err());
+    }
+
+    #[test]
+    fn shipped_quota_level_is_valid() {
+        let file = LevelFile::parse(crate::quota::QUOTA_LEVEL_FILE).unwrap();
+        assert_eq!(file.quota, Some(85));
+        let layout = file.to_layout().unwrap();
+        assert_eq!(layout.quota, Some(85));
+        assert!(crate::layout_check::validate_layout(&layout).is_ok());
+        let unbreakable = layout.bricks.iter().filter(|spec| spec.brick_type == BrickType::Unbreakable).count();
+        assert_eq!(unbreakable, 6);
+        let drops: Vec<((usize, usize), PowerUpType)> = layout
+            .bricks
+            .iter()
+            .filter_map(|spec| spec.guaranteed_drop.map(|drop| (spec.cell, drop)))
+            .collect();
+        assert_eq!(drops, vec![((3, 6), PowerUpType::LaserGun), ((4, 3), PowerUpType::MultiBall)]);
+    }
+}
@@ -0,0 +1,729 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::api::CreateScoreRequest;
+use crate::fonts::{text_style, FontAssets, FontKind};
+use crate::i18n::{name_key, Locale};
+use crate::network::{ApiCommand, Network};
+use crate::profile::Profile;
+use crate::profiles::ProfilePaths;
+use crate::rank_result::RankText;
+use crate::{
+    start_new_run, Difficulty, DifficultySettings, GameState, Level, PlayerName, RunResources, RunSubmitted, Score,
+    Settings,
+};
+
+// 奖牌门槛配置，随游戏一起编译
+const MEDALS_CONFIG: &str = include_str!("../assets/medals.ron");
+// 提交时的模式名
+pub use breakout_shared::LEVEL_MODE;
+// 选关界面每行的格子数和至少显示的格子数
+const TILE_COLUMNS: usize = 5;
+const MIN_TILES: usize = 10;
+
+const TILE_COLOR: Color = Color::rgba(0.2, 0.2, 0.25, 0.8);
+const LOCKED_COLOR: Color = Color::rgba(0.15, 0.15, 0.18, 0.8);
+const SELECTED_COLOR: Color = Color::rgb(1.0, 0.85, 0.2);
+const HINT_COLOR: Color = Color::rgb(0.6, 0.6, 0.6);
+
+// 单关挑战的奖牌，按好坏排序
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum Medal {
+    Bronze,
+    Silver,
+    Gold,
+}
+
+impl Medal {
+    // 从好到坏，评定时取第一个达到门槛的
+    const BEST_FIRST: [Medal; 3] = [Medal::Gold, Medal::Silver, Medal::Bronze];
+
+    fn label_key(&self) -> &'static str {
+        match self {
+            Medal::Bronze => "medal.bronze",
+            Medal::Silver => "medal.silver",
+            Medal::Gold => "medal.gold",
+        }
+    }
+
+    fn color(&self) -> Color {
+        match self {
+            Medal::Bronze => Color::rgb(0.8, 0.5, 0.2),
+            Medal::Silver => Color::rgb(0.75, 0.75, 0.8),
+            Medal::Gold => Color::rgb(1.0, 0.85, 0.2),
+        }
+    }
+}
+
+// 一枚奖牌的门槛
+#[derive(Debug, Clone, Copy, Deserialize)]
+struct MedalRequirement {
+    score: u32,
+    seconds: f32,
+}
+
+#[derive(Debug, Clone, Copy, Deserialize)]
+struct LevelMedals {
+    bronze: MedalRequirement,
+    silver: MedalRequirement,
+    gold: MedalRequirement,
+}
+
+impl LevelMedals {
+    fn requirement(&self, medal: Medal) -> MedalRequirement {
+        match medal {
+            Medal::Bronze => self.bronze,
+            Medal::Silver => self.silver,
+            Medal::Gold => self.gold,
+        }
+    }
+}
+
+// 各关的奖牌门槛，从 assets/medals.ron 读取；解析失败时没有奖牌
+#[derive(Resource, Debug, Default, Deserialize)]
+pub struct MedalThresholds {
+    levels: Vec<LevelMedals>,
+}
+
+impl MedalThresholds {
+    pub fn load() -> Self {
+        match Self::parse() {
+            Ok(thresholds) => thresholds,
+            Err(e) => {
+                eprintln!("Failed to parse medal thresholds: {}", e);
+                Self::default()
+            }
+        }
+    }
+
+    pub fn parse() -> Result<Self, ron::error::SpannedError> {
+        ron::from_str(MEDALS_CONFIG)
+    }
+
+    // 一次通关得到的奖牌；超出列表的关卡使用最后一项
+    fn medal_for(&self, level: u32, score: u32, seconds: f32) -> Option<Medal> {
+        let index = (level.max(1) as usize - 1).min(self.levels.len().checked_sub(1)?);
+        let medals = &self.levels[index];
+        Medal::BEST_FIRST.into_iter().find(|medal| {
+            let requirement = medals.requirement(*medal);
+            score >= requirement.score && seconds <= requirement.seconds
+        })
+    }
+}
+
+// 档案中某一关在某个难度下的最好成绩
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LevelRecord {
+    pub best_score: u32,
+    // 最快通关用时（秒）
+    pub best_time: Option<f32>,
+    pub medal: Option<Medal>,
+    pub clears: u32,
+}
+
+// 档案中关卡记录的键
+pub fn level_key(level: u32, difficulty: Difficulty) -> String {
+    format!("{}|{}", level, difficulty.name())
+}
+
+// 普通局（包括每日挑战和活动）从第一关连续往下打；单关挑战只打选中的一关，通关或失败后显示单关结算；
+// 淘汰赛的一轮也只打一关，结束后回到对阵表；和朋友比赛像普通局一样连续往下打，结束后显示比赛结果
+#[derive(Resource, Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum GameMode {
+    #[default]
+    Campaign,
+    SingleLevel,
+    Tournament,
+    Race,
+}
+
+impl GameMode {
+    // 丢掉最后一条命或时间用完后进入的界面
+    pub fn run_over_state(self) -> GameState {
+        match self {
+            GameMode::Campaign => GameState::GameOver,
+            GameMode::SingleLevel => GameState::LevelResult,
+            GameMode::Tournament => GameState::TournamentBracket,
+            GameMode::Race => GameState::RaceResult,
+        }
+    }
+
+    // 打通一关后进入的界面
+    pub fn clear_state(self) -> GameState {
+        match self {
+            GameMode::Campaign | GameMode::Race => GameState::Victory,
+            GameMode::SingleLevel => GameState::LevelResult,
+            GameMode::Tournament => GameState::TournamentBracket,
+        }
+    }
+}
+
+// 一次通关的结果
+#[derive(Debug, Clone, Copy)]
+pub struct LevelClear {
+    score: u32,
+    seconds: f32,
+    medal: Option<Medal>,
+    new_best_score: bool,
+    new_best_time: bool,
+    new_medal: bool,
+}
+
+// 当前这一关：开始时的总分用来计算这一关的得分；通关结果供单关结算界面读取，失败时为空
+#[derive(Resource, Debug, Default)]
+pub struct LevelAttempt {
+    start_score: u32,
+    clear: Option<LevelClear>,
+}
+
+impl LevelAttempt {
+    // 每一关开始时调用
+    pub fn begin(&mut self, score: u32) {
+        self.start_score = score;
+        self.clear = None;
+    }
+}
+
+// 通关时写入档案：更新这一关的最好成绩和奖牌，普通局还会解锁下一关
+pub fn record_level_clear(
+    paths: &ProfilePaths,
+    thresholds: &MedalThresholds,
+    attempt: &mut LevelAttempt,
+    level: u32,
+    difficulty: Difficulty,
+    total_score: u32,
+    seconds: f32,
+    mode: GameMode,
+) {
+    let score = total_score.saturating_sub(attempt.start_score);
+    let medal = thresholds.medal_for(level, score, seconds);
+    let mut profile = Profile::load(paths);
+    let record = profile.level_results.entry(level_key(level, difficulty)).or_default();
+    let clear = LevelClear {
+        score,
+        seconds,
+        medal,
+        new_best_score: record.clears == 0 || score > record.best_score,
+        new_best_time: record.best_time.is_none_or(|best| seconds < best),
+        new_medal: medal > record.medal,
+    };
+    record.best_score = record.best_score.max(score);
+    record.best_time = Some(record.best_time.map_or(seconds, |best| best.min(seconds)));
+    record.medal = record.medal.max(medal);
+    record.clears += 1;
+    if matches!(mode, GameMode::Campaign | GameMode::Race) {
+        profile.highest_cleared_level = profile.highest_cleared_level.max(level);
+    }
+    if let Err(e) = profile.save(paths) {
+        eprintln!("Failed to save profile: {}", e);
+    }
+    attempt.clear = Some(clear);
+}
+
+// 开始单关挑战：难度由调用方先设置好
+pub fn start_level_run(run: &mut RunResources, level: u32) {
+    start_new_run(run, None);
+    run.level.0 = level;
+    *run.game_mode = GameMode::SingleLevel;
+}
+
+fn format_seconds(seconds: f32) -> String {
+    let seconds = seconds.round() as u32;
+    format!("{}:{:02}", seconds / 60, seconds % 60)
+}
+
+// 选关界面的难度和选中的格子，返回选关界面时保留
+#[derive(Resource, Debug, Default)]
+pub struct LevelSelectState {
+    difficulty: Option<Difficulty>,
+    selected: usize,
+}
+
+#[derive(Component)]
+pub struct LevelSelectUI;
+
+#[derive(Component)]
+pub struct LevelTile {
+    index: usize,
+    level: u32,
+    unlocked: bool,
+}
+
+// 已解锁的关卡数：第一关总是解锁，之后每关需要在普通局中打通前一关；
+// 记录通关之前的档案按游戏结束时到达的关卡推算
+fn unlocked_levels(profile: &Profile) -> usize {
+    let reached = profile.game_over_levels.keys().max().copied().unwrap_or(1);
+    profile.highest_cleared_level.max(reached.saturating_sub(1)) as usize + 1
+}
+
+// 显示的格子数：已解锁的关卡加上至少一个锁住的关卡，补满整行
+fn tile_count(unlocked: usize) -> usize {
+    (unlocked + 1).max(MIN_TILES).div_ceil(TILE_COLUMNS) * TILE_COLUMNS
+}
+
+pub fn setup_level_select(
+    mut commands: Commands,
+    fonts: Res<FontAssets>,
+    locale: Res<Locale>,
+    paths: Res<ProfilePaths>,
+    settings: Res<Settings>,
+    mut state: ResMut<LevelSelectState>,
+) {
+    let profile = Profile::load(&paths);
+    // 第一次打开时使用上一局的难度，选中最新解锁的关卡
+    if state.difficulty.is_none() {
+        state.difficulty = Some(settings.last_difficulty);
+        state.selected = unlocked_levels(&profile) - 1;
+    }
+    state.selected = state.selected.min(tile_count(unlocked_levels(&profile)) - 1);
+    spawn_level_select(&mut commands, &fonts, &locale, &profile, &state);
+}
+
+fn spawn_level_select(
+    commands: &mut Commands,
+    fonts: &FontAssets,
+    locale: &Locale,
+    profile: &Profile,
+    state: &LevelSelectState,
+) {
+    let difficulty = state.difficulty.unwrap_or(Difficulty::Medium);
+    let unlocked = unlocked_levels(profile);
+
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    width: Val::Percent(100.0),
+                    height: Val::Percent(100.0),
+                    flex_direction: FlexDirection::Column,
+                    justify_content: JustifyContent::Center,
+                    align_items: AlignItems::Center,
+                    ..default()
+                },
+                background_color: BackgroundColor(Color::rgb(0.1, 0.1, 0.15)),
+                ..default()
+            },
+            LevelSelectUI,
+        ))
+        .with_children(|parent| {
+            parent.spawn(TextBundle::from_section(
+                locale.tr("levelselect.title", &[]),
+                text_style(fonts, FontKind::Bold, 60.0, Color::WHITE),
+            ));
+
+            let difficulty_name = locale.tr(&name_key("difficulty", difficulty.name()), &[]);
+            parent.spawn(
+                TextBundle::from_section(
+                    locale.tr("levelselect.difficulty", &[("difficulty", &difficulty_name)]),
+                    text_style(fonts, FontKind::Regular, 24.0, difficulty.color()),
+                )
+                .with_style(Style {
+                    margin: UiRect::top(Val::Px(10.0)),
+                    ..default()
+                }),
+            );
+
+            parent
+                .spawn(NodeBundle {
+                    style: Style {
+                        width: Val::Px(TILE_COLUMNS as f32 * 160.0),
+                        margin: UiRect::top(Val::Px(25.0)),
+                        flex_wrap: FlexWrap::Wrap,
+                        justify_content: JustifyContent::Center,
+                        column_gap: Val::Px(10.0),
+                        row_gap: Val::Px(10.0),
+                        ..default()
+                    },
+                    ..default()
+                })
+                .with_children(|parent| {
+                    for index in 0..tile_count(unlocked) {
+                        let level = index as u32 + 1;
+                        let record = profile.level_results.get(&level_key(level, difficulty));
+                        spawn_tile(parent, fonts, locale, index, index < unlocked, record);
+                    }
+                });
+
+            parent.spawn(
+                TextBundle::from_section(
+                    locale.tr("levelselect.hint", &[]),
+                    text_style(fonts, FontKind::Regular, 20.0, HINT_COLOR),
+                )
+                .with_style(Style {
+                    margin: UiRect::top(Val::Px(25.0)),
+                    ..default()
+                }),
+            );
+        });
+}
+
+fn spawn_tile(
+    parent: &mut ChildBuilder,
+    fonts: &FontAssets,
+    locale: &Locale,
+    index: usize,
+    unlocked: bool,
+    record: Option<&LevelRecord>,
+) {
+    let level = index as u32 + 1;
+    parent
+        .spawn((
+            ButtonBundle {
+                style: Style {
+                    width: Val::Px(150.0),
+                    height: Val::Px(104.0),
+                    padding: UiRect::all(Val::Px(8.0)),
+                    border: UiRect::all(Val::Px(2.0)),
+                    flex_direction: FlexDirection::Column,
+                    align_items: AlignItems::Center,
+                    row_gap: Val::Px(4.0),
+                    ..default()
+                },
+                background_color: BackgroundColor(if unlocked { TILE_COLOR } else { LOCKED_COLOR }),
+                border_color: BorderColor(Color::NONE),
+                ..default()
+            },
+            LevelTile { index, level, unlocked },
+        ))
+        .with_children(|parent| {
+            let title_color = if unlocked { Color::WHITE } else { HINT_COLOR };
+            parent.spawn(TextBundle::from_section(
+                locale.tr("levelselect.level", &[("level", &level)]),
+                text_style(fonts, FontKind::Bold, 22.0, title_color),
+            ));
+
+            if !unlocked {
+                spawn_padlock(parent);
+                parent.spawn(
+                    TextBundle::from_section(
+                        locale.tr("levelselect.locked", &[("level", &(level - 1))]),
+                        text_style(fonts, FontKind::Regular, 13.0, HINT_COLOR),
+                    )
+                    .with_text_justify(JustifyText::Center),
+                );
+                return;
+            }
+
+            let Some(record) = record.filter(|record| record.clears > 0) else {
+                parent.spawn(TextBundle::from_section(
+                    locale.tr("levelselect.not_cleared", &[]),
+                    text_style(fonts, FontKind::Regular, 16.0, HINT_COLOR),
+                ));
+                return;
+            };
+            parent.spawn(TextBundle::from_section(
+                locale.tr("levelselect.best_score", &[("score", &record.best_score)]),
+                text_style(fonts, FontKind::Mono, 16.0, Color::WHITE),
+            ));
+            if let Some(best_time) = record.best_time {
+                parent.spawn(TextBundle::from_section(
+                    locale.tr("levelselect.best_time", &[("time", &format_seconds(best_time))]),
+                    text_style(fonts, FontKind::Mono, 16.0, Color::WHITE),
+                ));
+            }
+            if let Some(medal) = record.medal {
+                spawn_medal(parent, fonts, locale, medal, 14.0);
+            }
+        });
+}
+
+// 奖牌：色块加名称
+fn spawn_medal(parent: &mut ChildBuilder, fonts: &FontAssets, locale: &Locale, medal: Medal, size: f32) {
+    parent
+        .spawn(NodeBundle {
+            style: Style {
+                align_items: AlignItems::Center,
+                column_gap: Val::Px(6.0),
+                ..default()
+            },
+            ..default()
+        })
+        .with_children(|parent| {
+            parent.spawn(NodeBundle {
+                style: Style {
+                    width: Val::Px(size),
+                    height: Val::Px(size),
+                    ..default()
+                },
+                background_color: BackgroundColor(medal.color()),
+                ..default()
+            });
+            parent.spawn(TextBundle::from_section(
+                locale.tr(medal.label_key(), &[]),
+                text_style(fonts, FontKind::Bold, size + 2.0, medal.color()),
+            ));
+        });
+}
+
+// 用两个节点画一把锁：上面是锁环，下面是锁身
+fn spawn_padlock(parent: &mut ChildBuilder) {
+    parent
+        .spawn(NodeBundle {
+            style: Style {
+                flex_direction: FlexDirection::Column,
+                align_items: AlignItems::Center,
+                ..default()
+            },
+            ..default()
+        })
+        .with_children(|parent| {
+            parent.spawn(NodeBundle {
+                style: Style {
+                    width: Val::Px(14.0),
+                    height: Val::Px(10.0),
+                    border: UiRect {
+                        left: Val::Px(3.0),
+                        right: Val::Px(3.0),
+                        top: Val::Px(3.0),
+                        bottom: Val::Px(0.0),
+                    },
+                    ..default()
+                },
+                border_color: BorderColor(HINT_COLOR),
+                ..default()
+            });
+            parent.spawn(NodeBundle {
+                style: Style {
+                    width: Val::Px(22.0),
+                    height: Val::Px(16.0),
+                    ..default()
+                },
+                background_color: BackgroundColor(HINT_COLOR),
+                ..default()
+            });
+        });
+}
+
+// 方向键移动选择，回车或空格开始，Tab 切换难度，ESC 返回主菜单；鼠标悬停选择，点击开始
+pub fn level_select_system(
+    mut commands: Commands,
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    fonts: Res<FontAssets>,
+    locale: Res<Locale>,
+    paths: Res<ProfilePaths>,
+    mut state: ResMut<LevelSelectState>,
+    mut next_state: ResMut<NextState<GameState>>,
+    mut run: RunResources,
+    mut tile_query: Query<(&Interaction, &LevelTile, &mut BorderColor)>,
+    ui_query: Query<Entity, With<LevelSelectUI>>,
+) {
+    if keyboard_input.just_pressed(KeyCode::Escape) {
+        next_state.set(GameState::MainMenu);
+        return;
+    }
+
+    if keyboard_input.just_pressed(KeyCode::Tab) {
+        let current = state.difficulty.unwrap_or(Difficulty::Medium);
+        let index = Difficulty::ALL.iter().position(|d| *d == current).unwrap_or(0);
+        state.difficulty = Some(Difficulty::ALL[(index + 1) % Difficulty::ALL.len()]);
+        for entity in ui_query.iter() {
+            commands.entity(entity).despawn_recursive();
+        }
+        spawn_level_select(&mut commands, &fonts, &locale, &Profile::load(&paths), &state);
+        return;
+    }
+
+    let count = tile_query.iter().count();
+    if count == 0 {
+        return;
+    }
+    let mut start = false;
+    if keyboard_input.just_pressed(KeyCode::ArrowLeft) {
+        state.selected = state.selected.saturating_sub(1);
+    } else if keyboard_input.just_pressed(KeyCode::ArrowRight) {
+        state.selected = (state.selected + 1).min(count - 1);
+    } else if keyboard_input.just_pressed(KeyCode::ArrowUp) {
+        state.selected = state.selected.saturating_sub(TILE_COLUMNS);
+    } else if keyboard_input.just_pressed(KeyCode::ArrowDown) {
+        state.selected = (state.selected + TILE_COLUMNS).min(count - 1);
+    } else if keyboard_input.any_just_pressed([KeyCode::Enter, KeyCode::Space]) {
+        start = true;
+    }
+
+    for (interaction, tile, _) in tile_query.iter() {
+        match interaction {
+            Interaction::Pressed => {
+                state.selected = tile.index;
+                start = true;
+            }
+            Interaction::Hovered => state.selected = tile.index,
+            Interaction::None => {}
+        }
+    }
+
+    let mut chosen = None;
+    for (_, tile, mut border_color) in tile_query.iter_mut() {
+        let selected = tile.index == state.selected;
+        border_color.0 = if selected { SELECTED_COLOR } else { Color::NONE };
+        if selected && start && tile.unlocked {
+            chosen = Some(tile.level);
+        }
+    }
+
+    if let Some(level) = chosen {
+        *run.difficulty_settings = DifficultySettings::new(state.difficulty.unwrap_or(Difficulty::Medium));
+        start_level_run(&mut run, level);
+        next_state.set(GameState::Playing);
+    }
+}
+
+pub fn cleanup_level_select(mut commands: Commands, query: Query<Entity, With<LevelSelectUI>>) {
+    for entity in query.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+#[derive(Component)]
+pub struct LevelResultUI;
+
+// 单关挑战结束：显示这一关的得分、用时和奖牌；通关时提交到这一关的榜单
+pub fn setup_level_result(
+    mut commands: Commands,
+    fonts: Res<FontAssets>,
+    locale: Res<Locale>,
+    level: Res<Level>,
+    score: Res<Score>,
+    difficulty_settings: Res<DifficultySettings>,
+    attempt: Res<LevelAttempt>,
+    network: Res<Network>,
+    player_name: Res<PlayerName>,
+    settings: Res<Settings>,
+    mut run_submitted: ResMut<RunSubmitted>,
+) {
+    let difficulty = difficulty_settings.difficulty;
+    let clear = attempt.clear;
+    // 只有通关才提交，重试会开始新的一局；只保存在本机时不提交
+    let submit = clear.is_some() && !run_submitted.0;
+    let submission_name = settings.submission_name(&player_name.0);
+    if let (Some(clear), Some(submission_name)) = (clear.filter(|_| submit), submission_name) {
+        run_submitted.0 = true;
+        network.send(ApiCommand::SubmitScore(CreateScoreRequest {
+            player_name: submission_name,
+            score: clear.score,
+            level: level.0,
+            difficulty: difficulty.name().to_string(),
+            idempotency_key: None,
+            venue: None,
+            mode: Some(LEVEL_MODE.to_string()),
+            seed: None,
+            daily_date: None,
+            event_id: None,
+            tournament_id: None,
+            abandoned: false,
+            assisted: false,
+            curse: None,
+        }));
+    }
+
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    width: Val::Percent(100.0),
+                    height: Val::Percent(100.0),
+                    flex_direction: FlexDirection::Column,
+                    justify_content: JustifyContent::Center,
+                    align_items: AlignItems::Center,
+                    row_gap: Val::Px(10.0),
+                    ..default()
+                },
+                background_color: BackgroundColor(Color::rgb(0.1, 0.1, 0.15)),
+                ..default()
+            },
+            LevelResultUI,
+        ))
+        .with_children(|parent| {
+            let (title_key, title_color) = match clear {
+                Some(_) => ("levelresult.cleared", Color::rgb(0.2, 0.8, 0.2)),
+                None => ("levelresult.failed", Color::rgb(0.8, 0.2, 0.2)),
+            };
+            parent.spawn(TextBundle::from_section(
+                locale.tr(title_key, &[("level", &level.0)]),
+                text_style(&fonts, FontKind::Bold, 60.0, title_color),
+            ));
+            parent.spawn(TextBundle::from_section(
+                locale.tr(&name_key("difficulty", difficulty.name()), &[]),
+                text_style(&fonts, FontKind::Regular, 24.0, difficulty.color()),
+            ));
+
+            let Some(clear) = clear else {
+                parent.spawn(TextBundle::from_section(
+                    locale.tr("levelresult.score", &[("score", &score.0)]),
+                    text_style(&fonts, FontKind::Mono, 36.0, Color::WHITE),
+                ));
+                return;
+            };
+
+            parent.spawn(TextBundle::from_section(
+                locale.tr("levelresult.score", &[("score", &clear.score)]),
+                text_style(&fonts, FontKind::Mono, 36.0, Color::WHITE),
+            ));
+            parent.spawn(TextBundle::from_section(
+                locale.tr("levelresult.time", &[("time", &format_seconds(clear.seconds))]),
+                text_style(&fonts, FontKind::Mono, 28.0, Color::WHITE),
+            ));
+            match clear.medal {
+                Some(medal) => spawn_medal(parent, &fonts, &locale, medal, 24.0),
+                None => {
+                    parent.spawn(TextBundle::from_section(
+                        locale.tr("levelresult.no_medal", &[]),
+                        text_style(&fonts, FontKind::Regular, 22.0, HINT_COLOR),
+                    ));
+                }
+            }
+
+            let records = [
+                (clear.new_medal, "levelresult.new_medal"),
+                (clear.new_best_score, "levelresult.new_best_score"),
+                (clear.new_best_time, "levelresult.new_best_time"),
+            ];
+            for (_, key) in records.iter().filter(|(new, _)| *new) {
+                parent.spawn(TextBundle::from_section(
+                    locale.tr(key, &[]),
+                    text_style(&fonts, FontKind::Bold, 22.0, SELECTED_COLOR),
+                ));
+            }
+
+            // 提交返回后显示在这一关榜单上的名次
+            if submit {
+                parent.spawn((
+                    TextBundle::from_section("", text_style(&fonts, FontKind::Regular, 22.0, Color::WHITE)),
+                    RankText,
+                ));
+            }
+        })
+        .with_children(|parent| {
+            parent.spawn(
+                TextBundle::from_section(
+                    locale.tr("levelresult.hint", &[]),
+                    text_style(&fonts, FontKind::Regular, 20.0, HINT_COLOR),
+                )
+                .with_style(Style {
+                    margin: UiRect::top(Val::Px(30.0)),
+                    ..default()
+                }),
+            );
+        });
+}
+
+// 回车重新挑战这一关，ESC 返回选关界面
+pub fn level_result_system(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    mut next_state: ResMut<NextState<GameState>>,
+    mut run: RunResources,
+) {
+    if keyboard_input.just_pressed(KeyCode::Enter) {
+        let level = run.level.0;
+        start_level_run(&mut run, level);
+        next_state.set(GameState::Playing);
+    } else if keyboard_input.just_pressed(KeyCode::Escape) {
+        next_state.set(GameState::LevelSelect);
+    }
+}
+
+pub fn cleanup_level_result(mut commands: Commands, query: Query<Entity, With<LevelResultUI>>) {
+    for entity in query.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+}
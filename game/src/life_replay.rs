@@ -0,0 +1,371 @@
+use bevy::prelude::*;
+use std::collections::VecDeque;
+
+use crate::cosmetics::Cosmetics;
+use crate::fonts::{text_style, FontAssets, FontKind};
+use crate::i18n::Locale;
+use crate::launch::HeldBall;
+use crate::level_select::GameMode;
+use crate::paddle::Paddle;
+use crate::{Ball, GameEntity, GameState, GameplayEvent, Settings, WINDOW_HEIGHT, WINDOW_WIDTH};
+
+// 保留最近多少秒的状态
+const BUFFER_SECONDS: f32 = 3.0;
+// 回放速度
+const PLAYBACK_SPEED: f32 = 0.5;
+// 每帧最多记录几个球，多球时内存也有上限
+const MAX_RECORDED_BALLS: usize = 8;
+
+// 回放画面：压暗原画面，幽灵精灵画在遮罩上方
+const DIM_COLOR: Color = Color::rgba(0.0, 0.0, 0.0, 0.6);
+const DIM_Z: f32 = 50.0;
+const GHOST_Z: f32 = 51.0;
+const GHOST_ALPHA: f32 = 0.75;
+// 球后方的速度拖尾
+const STREAK_LENGTH: f32 = 28.0;
+const STREAK_WIDTH: f32 = 3.0;
+const STREAK_SECONDS: f32 = 0.05;
+
+// 一个固定帧的挡板和球
+#[derive(Debug, Clone)]
+struct StateSnapshot {
+    paddle: Option<Transform>,
+    balls: Vec<(Transform, Vec2)>,
+}
+
+// 最近几秒的游戏状态，每个固定帧记录一次，超过容量时丢弃最旧的
+#[derive(Resource, Default)]
+pub struct RollingStateBuffer {
+    snapshots: VecDeque<StateSnapshot>,
+}
+
+impl RollingStateBuffer {
+    pub fn clear(&mut self) {
+        self.snapshots.clear();
+    }
+}
+
+// 丢命时截下的片段和回放进度
+#[derive(Resource, Default)]
+pub struct LifeReplay {
+    clip: Vec<StateSnapshot>,
+    // 片段的帧间隔（秒）
+    step: f32,
+    // 已回放的录制时间（秒）
+    playhead: f32,
+    // 回放结束后回到的状态
+    return_to: Option<GameState>,
+}
+
+impl LifeReplay {
+    fn offered(&self) -> bool {
+        !self.clip.is_empty()
+    }
+}
+
+// "[V] 查看回放" 提示
+#[derive(Component)]
+pub struct ReplayOffer;
+
+// 回放时的遮罩、幽灵和标题，退出回放时全部移除
+#[derive(Component)]
+pub struct ReplayScene;
+
+#[derive(Component)]
+pub struct GhostPaddle;
+
+// 幽灵球和拖尾，index 对应快照中的第几个球
+#[derive(Component)]
+pub struct GhostBall(usize);
+
+#[derive(Component)]
+pub struct GhostStreak(usize);
+
+// 每个固定帧记录挡板和球；容量按固定帧间隔换算成 3 秒
+pub fn record_state(
+    mut buffer: ResMut<RollingStateBuffer>,
+    fixed_time: Res<Time<Fixed>>,
+    paddle_query: Query<&Transform, With<Paddle>>,
+    ball_query: Query<(&Transform, &Ball)>,
+) {
+    let capacity = (BUFFER_SECONDS / fixed_time.timestep().as_secs_f32()).ceil() as usize;
+    while buffer.snapshots.len() >= capacity.max(1) {
+        buffer.snapshots.pop_front();
+    }
+    buffer.snapshots.push_back(StateSnapshot {
+        paddle: paddle_query.get_single().ok().copied(),
+        balls: ball_query
+            .iter()
+            .take(MAX_RECORDED_BALLS)
+            .map(|(transform, ball)| (*transform, ball.velocity))
+            .collect(),
+    });
+}
+
+// 丢命时截下缓冲区作为回放片段并显示提示；减少动态效果和比赛模式下不提供回放
+pub fn capture_on_life_lost(
+    mut commands: Commands,
+    mut gameplay_events: EventReader<GameplayEvent>,
+    buffer: Res<RollingStateBuffer>,
+    fixed_time: Res<Time<Fixed>>,
+    mut replay: ResMut<LifeReplay>,
+    settings: Res<Settings>,
+    game_mode: Res<GameMode>,
+    fonts: Res<FontAssets>,
+    locale: Res<Locale>,
+    offers: Query<Entity, With<ReplayOffer>>,
+) {
+    if !gameplay_events.read().any(|event| matches!(event, GameplayEvent::LifeLost)) {
+        return;
+    }
+    if settings.reduce_motion || *game_mode == GameMode::Race || buffer.snapshots.is_empty() {
+        return;
+    }
+    *replay = LifeReplay {
+        clip: buffer.snapshots.iter().cloned().collect(),
+        step: fixed_time.timestep().as_secs_f32(),
+        playhead: 0.0,
+        return_to: None,
+    };
+    if !offers.is_empty() {
+        return;
+    }
+    commands.spawn((
+        TextBundle::from_section(
+            locale.tr("replay.offer", &[]),
+            text_style(&fonts, FontKind::Bold, 22.0, Color::rgba(1.0, 1.0, 1.0, 0.85)),
+        )
+        .with_style(Style {
+            position_type: PositionType::Absolute,
+            bottom: Val::Percent(40.0),
+            width: Val::Percent(100.0),
+            justify_content: JustifyContent::Center,
+            ..default()
+        })
+        .with_text_justify(JustifyText::Center),
+        ReplayOffer,
+        GameEntity,
+    ));
+}
+
+fn close_offer(commands: &mut Commands, replay: &mut LifeReplay, offers: &Query<Entity, With<ReplayOffer>>) {
+    replay.clip.clear();
+    for entity in offers.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+// 球停在挡板上时按 V 查看回放，回放后继续等待发射；球发射后提示消失
+pub fn offer_input_playing(
+    mut commands: Commands,
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    mut replay: ResMut<LifeReplay>,
+    mut next_state: ResMut<NextState<GameState>>,
+    held_balls: Query<(), With<HeldBall>>,
+    offers: Query<Entity, With<ReplayOffer>>,
+) {
+    if !replay.offered() {
+        return;
+    }
+    if held_balls.is_empty() {
+        close_offer(&mut commands, &mut replay, &offers);
+        return;
+    }
+    if keyboard_input.just_pressed(KeyCode::KeyV) {
+        replay.return_to = Some(GameState::Playing);
+        next_state.set(GameState::LifeReplay);
+    }
+}
+
+// 死亡镜头中按 V 查看回放，回放后进入结算；在死亡镜头的任意键跳过之后运行，覆盖它设置的状态
+pub fn offer_input_death_sequence(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    mut replay: ResMut<LifeReplay>,
+    game_mode: Res<GameMode>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    if replay.offered() && keyboard_input.just_pressed(KeyCode::KeyV) {
+        replay.return_to = Some(game_mode.run_over_state());
+        next_state.set(GameState::LifeReplay);
+    }
+}
+
+// 进入回放：压暗画面，生成不参与碰撞的幽灵精灵和标题
+pub fn setup_life_replay(
+    mut commands: Commands,
+    mut replay: ResMut<LifeReplay>,
+    cosmetics: Res<Cosmetics>,
+    fonts: Res<FontAssets>,
+    locale: Res<Locale>,
+    offers: Query<Entity, With<ReplayOffer>>,
+) {
+    for entity in offers.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+    replay.playhead = 0.0;
+
+    commands.spawn((
+        SpriteBundle {
+            sprite: Sprite {
+                color: DIM_COLOR,
+                custom_size: Some(Vec2::new(WINDOW_WIDTH * 2.0, WINDOW_HEIGHT * 2.0)),
+                ..default()
+            },
+            transform: Transform::from_xyz(0.0, 0.0, DIM_Z),
+            ..default()
+        },
+        ReplayScene,
+        GameEntity,
+    ));
+
+    let mut paddle_color = cosmetics.paddle.base;
+    paddle_color.set_a(GHOST_ALPHA);
+    commands.spawn((
+        SpriteBundle {
+            sprite: Sprite {
+                color: paddle_color,
+                ..default()
+            },
+            visibility: Visibility::Hidden,
+            ..default()
+        },
+        GhostPaddle,
+        ReplayScene,
+        GameEntity,
+    ));
+
+    let ball_count = replay.clip.iter().map(|snapshot| snapshot.balls.len()).max().unwrap_or(0);
+    let mut ball_color = cosmetics.ball.base;
+    ball_color.set_a(GHOST_ALPHA);
+    let mut streak_color = ball_color;
+    streak_color.set_a(GHOST_ALPHA * 0.5);
+    for index in 0..ball_count {
+        commands.spawn((
+            SpriteBundle {
+                sprite: Sprite {
+                    color: ball_color,
+                    ..default()
+                },
+                visibility: Visibility::Hidden,
+                ..default()
+            },
+            GhostBall(index),
+            ReplayScene,
+            GameEntity,
+        ));
+        commands.spawn((
+            SpriteBundle {
+                sprite: Sprite {
+                    color: streak_color,
+                    custom_size: Some(Vec2::new(STREAK_LENGTH, STREAK_WIDTH)),
+                    ..default()
+                },
+                visibility: Visibility::Hidden,
+                ..default()
+            },
+            GhostStreak(index),
+            ReplayScene,
+            GameEntity,
+        ));
+    }
+
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    top: Val::Px(60.0),
+                    width: Val::Percent(100.0),
+                    flex_direction: FlexDirection::Column,
+                    align_items: AlignItems::Center,
+                    ..default()
+                },
+                ..default()
+            },
+            ReplayScene,
+            GameEntity,
+        ))
+        .with_children(|parent| {
+            parent.spawn(TextBundle::from_section(
+                locale.tr("replay.title", &[]),
+                text_style(&fonts, FontKind::Bold, 30.0, Color::WHITE),
+            ));
+            parent.spawn(TextBundle::from_section(
+                locale.tr("replay.skip", &[]),
+                text_style(&fonts, FontKind::Regular, 18.0, Color::rgb(0.7, 0.7, 0.7)),
+            ));
+        });
+}
+
+// 按真实时间半速推进，把对应的快照画到幽灵精灵上；放完或按键跳过后返回
+pub fn life_replay_system(
+    real_time: Res<Time<Real>>,
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    mut replay: ResMut<LifeReplay>,
+    mut next_state: ResMut<NextState<GameState>>,
+    mut paddle_query: Query<(&mut Transform, &mut Visibility), With<GhostPaddle>>,
+    mut ball_query: Query<(&mut Transform, &mut Visibility, &GhostBall), Without<GhostPaddle>>,
+    mut streak_query: Query<
+        (&mut Transform, &mut Visibility, &GhostStreak),
+        (Without<GhostPaddle>, Without<GhostBall>),
+    >,
+) {
+    let skipped = [KeyCode::KeyV, KeyCode::Escape, KeyCode::Space]
+        .into_iter()
+        .any(|key| keyboard_input.just_pressed(key));
+    replay.playhead += real_time.delta_seconds() * PLAYBACK_SPEED;
+    let index = (replay.playhead / replay.step.max(f32::EPSILON)) as usize;
+    let Some(snapshot) = replay.clip.get(index).filter(|_| !skipped) else {
+        next_state.set(replay.return_to.unwrap_or(GameState::Playing));
+        return;
+    };
+
+    for (mut transform, mut visibility) in paddle_query.iter_mut() {
+        match snapshot.paddle {
+            Some(paddle) => {
+                *transform = paddle;
+                transform.translation.z = GHOST_Z;
+                *visibility = Visibility::Visible;
+            }
+            None => *visibility = Visibility::Hidden,
+        }
+    }
+    for (mut transform, mut visibility, ghost) in ball_query.iter_mut() {
+        match snapshot.balls.get(ghost.0) {
+            Some((ball, _)) => {
+                *transform = *ball;
+                transform.translation.z = GHOST_Z + 1.0;
+                *visibility = Visibility::Visible;
+            }
+            None => *visibility = Visibility::Hidden,
+        }
+    }
+    for (mut transform, mut visibility, streak) in streak_query.iter_mut() {
+        match snapshot.balls.get(streak.0) {
+            Some((ball, velocity)) if *velocity != Vec2::ZERO => {
+                // 拖尾沿速度反方向，长度不超过球最近走过的距离
+                let length = (velocity.length() * STREAK_SECONDS).min(STREAK_LENGTH);
+                let behind = ball.translation.truncate() - velocity.normalize() * length / 2.0;
+                *transform = Transform {
+                    translation: behind.extend(GHOST_Z),
+                    rotation: Quat::from_rotation_z(velocity.y.atan2(velocity.x)),
+                    scale: Vec3::new(length / STREAK_LENGTH, 1.0, 1.0),
+                };
+                *visibility = Visibility::Visible;
+            }
+            _ => *visibility = Visibility::Hidden,
+        }
+    }
+}
+
+// 离开回放时移除幽灵；片段只能看一次
+pub fn cleanup_life_replay(
+    mut commands: Commands,
+    mut replay: ResMut<LifeReplay>,
+    scene: Query<Entity, With<ReplayScene>>,
+) {
+    for entity in scene.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+    *replay = LifeReplay::default();
+}
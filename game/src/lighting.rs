@@ -0,0 +1,88 @@
+use bevy::prelude::*;
+
+use crate::hazard_rules::PlayClock;
+use crate::seasonal::SeasonalTheme;
+use crate::team_colors::TeamTint;
+use crate::{Brick, GameplayEvent, Settings, BRICK_SIZE, GAP_SIZE, GOLDEN_BRICK_COLOR};
+
+// 光照影响的范围（格）
+const LIGHT_RADIUS_CELLS: f32 = 1.5;
+// 相邻砖块获得的最大亮度，随距离减弱
+const LIGHT_FLASH_STRENGTH: f32 = 0.5;
+// 亮度上限，叠加多次也不会变成纯白
+const MAX_BRIGHTNESS: f32 = 0.6;
+// 亮度从满值衰减到 0 的时间（秒）
+const LIGHT_DECAY_TIME: f32 = 0.3;
+// 金砖颜色缓慢明暗起伏的周期（秒）和幅度
+const GOLDEN_PULSE_PERIOD: f32 = 1.6;
+const GOLDEN_PULSE_AMOUNT: f32 = 0.15;
+
+// 砖块当前被照亮的程度，0 表示不受光照
+#[derive(Component, Default)]
+pub struct Brightness(pub f32);
+
+// 砖块被摧毁时照亮附近的砖块；减少动态效果时不闪光
+pub fn emit_destruction_light(
+    mut gameplay_events: EventReader<GameplayEvent>,
+    settings: Res<Settings>,
+    mut bricks: Query<(&Transform, &mut Brightness), With<Brick>>,
+) {
+    for event in gameplay_events.read() {
+        let GameplayEvent::BrickDestroyed { position, .. } = event else {
+            continue;
+        };
+        if settings.reduce_motion {
+            continue;
+        }
+
+        let cell_size = BRICK_SIZE + Vec2::splat(GAP_SIZE);
+        for (transform, mut brightness) in bricks.iter_mut() {
+            // 以格为单位的距离，被摧毁的砖块本身（距离为 0）不参与
+            let distance = ((transform.translation.xy() - position.xy()) / cell_size).length();
+            if !(0.5..=LIGHT_RADIUS_CELLS).contains(&distance) {
+                continue;
+            }
+            let boost = LIGHT_FLASH_STRENGTH * (1.0 - distance / (LIGHT_RADIUS_CELLS + 0.5));
+            brightness.0 = (brightness.0 + boost).min(MAX_BRIGHTNESS);
+        }
+    }
+}
+
+// 砖块颜色的唯一写入者：类型颜色（节日主题下为主题配色，冷暖活动叠加队伍颜色，金砖为起伏的金色）× 受损变暗，再叠加光照亮度
+pub fn brick_visuals(
+    time: Res<Time>,
+    clock: Res<PlayClock>,
+    settings: Res<Settings>,
+    theme: Res<SeasonalTheme>,
+    mut bricks: Query<(&Brick, &mut Brightness, &mut Sprite, Option<&TeamTint>)>,
+) {
+    let decay = LIGHT_FLASH_STRENGTH / LIGHT_DECAY_TIME * time.delta_seconds();
+    for (brick, mut brightness, mut sprite, tint) in bricks.iter_mut() {
+        if brightness.0 > 0.0 {
+            brightness.0 = (brightness.0 - decay).max(0.0);
+        }
+
+        // 金砖固定为金色并缓慢起伏，不受主题和队伍颜色影响；减少动态效果时不起伏
+        let (base, pulse) = if brick.golden {
+            let phase = clock.now() / GOLDEN_PULSE_PERIOD * std::f32::consts::TAU;
+            let pulse = if settings.reduce_motion {
+                1.0
+            } else {
+                1.0 - GOLDEN_PULSE_AMOUNT * (0.5 + 0.5 * phase.sin())
+            };
+            (GOLDEN_BRICK_COLOR, pulse)
+        } else {
+            let base = theme.brick_color(brick.brick_type);
+            (tint.map_or(base, |tint| tint.tint(base)), 1.0)
+        };
+        // 向白色靠近 brightness 的比例，亮度有上限所以不会完全变白
+        let lit = |channel: f32| {
+            let shaded = channel * brick.shade * pulse;
+            shaded + (1.0 - shaded) * brightness.0
+        };
+        let color = Color::rgb(lit(base.r()), lit(base.g()), lit(base.b()));
+        if sprite.color != color {
+            sprite.color = color;
+        }
+    }
+}
@@ -1,11 +1,142 @@
+use bevy::ecs::system::SystemParam;
+use bevy::input::mouse::{MouseScrollUnit, MouseWheel};
 use bevy::prelude::*;
+use bevy::window::PrimaryWindow;
 use rand::prelude::*;
+use rand::rngs::StdRng;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
 
+mod admin;
 mod api;
-use api::{ApiClient, LeaderboardResponse};
+mod arcade_name;
+mod audio;
+mod balance;
+mod barrier;
+mod board_snapshot;
+mod bomb;
+mod boss;
+mod bonus;
+mod changelog;
+mod cosmetics;
+mod countdown;
+mod curse;
+mod explosion;
+mod daily;
+mod death_cam;
+mod determinism;
+mod ending;
+mod feed;
+mod fixed_step;
+mod fonts;
+mod game_rng;
+mod gamepad;
+mod governor;
+mod hazard_rules;
+mod homing;
+mod i18n;
+mod input_buffer;
+mod insurance;
+mod interlude;
+mod kiosk;
+mod launch;
+mod life_replay;
+mod layout_check;
+mod level_file;
+mod level_select;
+mod lighting;
+mod motion_trails;
+mod network;
+mod paddle;
+mod paddle_feedback;
+mod pending_run;
+mod perf;
+mod persistence;
+mod privacy;
+mod profile;
+mod profiles;
+mod quota;
+mod race;
+mod rank_movement;
+mod rank_result;
+mod rivals;
+mod release;
+mod remote_flags;
+mod replay;
+mod resume;
+mod safe_despawn;
+mod seasonal;
+mod settings;
+mod shield;
+mod smoke;
+mod stick;
+mod sticky;
+mod team_colors;
+mod tilt;
+mod tournament;
+// 模拟球轨迹：纯函数，不接触 ECS，性能面板的调试层和测试使用
+mod trace;
+mod virtual_list;
+mod weekly_event;
+use api::{CreateScoreRequest, LeaderboardResponse};
+use breakout_shared::{FLAG_DAILY_CHALLENGE, FLAG_RACES, FLAG_WEEKLY_EVENT};
+use arcade_name::{ArcadeNameEntry, LastInputDevice, NameEntryMode};
+use audio::SoundEvent;
+use balance::{BalanceReport, DifficultyBalance};
+use barrier::Barrier;
+use board_snapshot::{BoardSnapshot, BrickDestructionLog};
+use bonus::{BonusStage, Orientation};
+use boss::BossStage;
+use changelog::Changelog;
+use cosmetics::{Cosmetics, CosmeticsMenuState, Skin};
+use daily::DailyRun;
+use pending_run::{RunMirrorHandle, RunRecovery};
+use privacy::ScorePrivacy;
+use resume::ResumeKind;
+use countdown::Countdown;
+use curse::Curse;
+use explosion::{Explosion, ExplosionSource};
+use feed::EventFeed;
+use fonts::{text_style, FontAssets, FontKind};
+use hazard_rules::{HazardRules, LastPaddleContact, PlayClock};
+use game_rng::GameRng;
+use homing::HomingTarget;
+use i18n::{name_key, Locale};
+use input_buffer::{InputAction, InputBuffer};
+use kiosk::{KioskConfig, KioskSession};
+use launch::HeldBall;
+use life_replay::{LifeReplay, RollingStateBuffer};
+use level_select::{GameMode, LevelAttempt, LevelSelectState, MedalThresholds};
+use lighting::Brightness;
+use motion_trails::WallImpact;
+use network::{ApiCommand, ApiResponse, Network, NetworkPlugin, ServerStatus};
+use paddle::{Paddle, PaddlePlugin, PaddleSet, PaddleVelocity};
+use gamepad::GamepadButtons;
+use insurance::{Insurance, InsuranceOfferText};
+use profile::{Profile, RunStats};
+use profiles::{ProfilePaths, ProfilePickerState, ProfileRegistry};
+use race::{RaceLobby, RaceSession};
+use rank_movement::{LeaderboardHistory, RankIndicator};
+use remote_flags::{FlagGated, RemoteFlags};
+use safe_despawn::SafeDespawnExt;
+use team_colors::{HotTeam, TeamTint};
+use weekly_event::{CurrentEvent, EventRun};
+use rank_result::{RankText, RankToastPending};
+use rivals::RunRivals;
+use replay::ReplayRecorder;
+use seasonal::SeasonalTheme;
+use settings::{auto_ui_scale, Settings, SettingsItem};
+use quota::BrickQuota;
+use shield::{GeneratorChain, Shield};
+use governor::{EffectBudget, EffectKind};
+use stick::StickSmoothing;
+use sticky::StuckBall;
+use tilt::PaddleTilt;
+use tournament::{Tournament, TournamentEntry};
+use virtual_list::VirtualList;
 
 // 碰撞检测
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy)]
 enum Collision {
     Left,
     Right,
@@ -52,16 +183,46 @@ const WINDOW_HEIGHT: f32 = 600.0;
 const PADDLE_SIZE: Vec2 = Vec2::new(120.0, 20.0);
 const PADDLE_SPEED: f32 = 500.0;
 const PADDLE_Y: f32 = -250.0;
+// 完美击球区的宽度（占默认挡板宽度的比例）；按像素固定，加长挡板不会让它变宽
+const PERFECT_ZONE: f32 = 0.15;
+// 底部减速带的高度（像素）和最深处的减速比例
+const SLOW_ZONE_HEIGHT: f32 = 60.0;
+const SLOW_ZONE_MAX_SLOWDOWN: f32 = 0.25;
+// 减速带由几条透明度递增的色带组成，越靠近底边越明显
+const SLOW_ZONE_STRIPS: usize = 6;
+const SLOW_ZONE_MAX_ALPHA: f32 = 0.12;
+// 完美击球的奖励分数和弹回时的加速
+const PERFECT_BONUS: u32 = 2;
+const PERFECT_SPEED_BOOST: f32 = 1.1;
 
 // 球设置
 const BALL_SIZE: Vec2 = Vec2::new(20.0, 20.0);
 const BALL_SPEED: f32 = 400.0;
+// 球的最高速度（橡胶砖加速不会超过它）
+const MAX_BALL_SPEED: f32 = BALL_SPEED * 2.0;
+// 竖直方向速度至少占球速的比例，球不会在两侧墙壁之间几乎水平地来回弹
+const MIN_VERTICAL_RATIO: f32 = 0.25;
+
+// 球打碎砖块时掉落道具的概率
+const POWERUP_DROP_CHANCE: f64 = 0.2;
+// 限时道具的持续时间（秒）
+const PENETRATING_DURATION: f32 = 10.0;
+const LASER_DURATION: f32 = 15.0;
+const SLOW_MOTION_DURATION: f32 = 8.0;
+const FIREBALL_DURATION: f32 = 10.0;
+const FIREBALL_COLOR: Color = Color::rgb(1.0, 0.5, 0.1);
+// 慢动作期间球、激光、道具和粒子的时间倍率（挡板不受影响）
+const SLOW_MOTION_SCALE: f32 = 0.5;
+// 连续拾取多少个有利道具后获得护符，抵消下一个不利道具
+const WARD_STREAK: u32 = 3;
+const WARD_COLOR: Color = Color::rgb(0.6, 0.85, 1.0);
 
 // 砖块设置
 const BRICK_SIZE: Vec2 = Vec2::new(75.0, 30.0);
 const BRICK_ROWS: usize = 6;
 const BRICK_COLUMNS: usize = 10;
 const GAP_SIZE: f32 = 5.0;
+const BRICK_START_Y: f32 = 200.0;
 
 // 激光设置
 const LASER_SIZE: Vec2 = Vec2::new(5.0, 20.0);
@@ -72,9 +233,21 @@ const BACKGROUND_COLOR: Color = Color::rgb(0.1, 0.1, 0.15);
 const PADDLE_COLOR: Color = Color::rgb(0.3, 0.7, 1.0);
 const BALL_COLOR: Color = Color::rgb(1.0, 0.9, 0.7);
 const NORMAL_BRICK_COLOR: Color = Color::rgb(0.8, 0.3, 0.3);
+const GOLDEN_BRICK_COLOR: Color = Color::rgb(1.0, 0.78, 0.2);
 const HARD_BRICK_COLOR: Color = Color::rgb(0.5, 0.2, 0.2);
 const UNBREAKABLE_BRICK_COLOR: Color = Color::rgb(0.3, 0.3, 0.3);
+const ICE_BRICK_COLOR: Color = Color::rgb(0.75, 0.9, 1.0);
+const RUBBER_BRICK_COLOR: Color = Color::rgb(0.15, 0.4, 0.2);
+const STICKY_BRICK_COLOR: Color = Color::rgb(0.55, 0.25, 0.7);
+const EXPLOSIVE_BRICK_COLOR: Color = Color::rgb(0.95, 0.75, 0.1);
+const TIMED_BOMB_BRICK_COLOR: Color = Color::rgb(0.95, 0.15, 0.1);
 const LASER_COLOR: Color = Color::rgb(1.0, 0.2, 0.2);
+const ARMOR_PLATE_COLOR: Color = Color::rgb(0.75, 0.78, 0.85);
+
+// 装甲板厚度（像素）
+const ARMOR_PLATE_THICKNESS: f32 = 5.0;
+// 第二关起坚硬砖块带装甲板的概率（百分比）
+const ARMOR_PLATE_CHANCE: u32 = 30;
 
 // 游戏状态
 #[derive(Debug, Clone, Copy, Default, Eq, PartialEq, Hash, States)]
@@ -87,18 +260,58 @@ enum GameState {
     GameOver,
     Victory,
     NextLevel,
+    LevelInterlude, // 关卡之间的预览
     EnterName,      // 新增：输入玩家名称
+    Profile,        // 本地终身统计
     Leaderboard,    // 新增：显示排行榜
+    Settings,       // 设置界面
+    DeathSequence,  // 最后一条命丢失后的慢镜头
+    LifeReplay,     // 丢命前几秒的慢速回放
+    ProfileSelect,  // 选择玩家档案
+    Changelog,      // 更新日志
+    Attract,        // 展台模式的待机演示画面
+    Cosmetics,      // 球和挡板的外观
+    DailyChallenge, // 每日挑战的当日榜单
+    RecoverRun,     // 询问如何处理上次没有正常结束的局
+    LevelSelect,    // 单关挑战的选关界面
+    LevelResult,    // 单关挑战的结算
+    TournamentSetup,    // 淘汰赛输入选手名字
+    TournamentBracket,  // 淘汰赛对阵表
+    TournamentChampion, // 淘汰赛冠军
+    RaceLobby,          // 和朋友比赛：创建或加入
+    RaceResult,         // 和朋友比赛的结果
+    CurseChoice,        // 第三关之前选择诅咒
+    GameComplete,       // 普通局打完最后一关的首领：统计、字幕
 }
 
 // 难度等级
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 enum Difficulty {
     Easy,
     Medium,
     Hard,
 }
 
+impl Difficulty {
+    const ALL: [Difficulty; 3] = [Difficulty::Easy, Difficulty::Medium, Difficulty::Hard];
+
+    fn name(&self) -> &'static str {
+        match self {
+            Difficulty::Easy => "Easy",
+            Difficulty::Medium => "Medium",
+            Difficulty::Hard => "Hard",
+        }
+    }
+
+    fn color(&self) -> Color {
+        match self {
+            Difficulty::Easy => Color::rgb(0.2, 0.8, 0.2),
+            Difficulty::Medium => Color::rgb(0.8, 0.8, 0.2),
+            Difficulty::Hard => Color::rgb(0.8, 0.2, 0.2),
+        }
+    }
+}
+
 // 难度设置
 #[derive(Resource)]
 struct DifficultySettings {
@@ -108,6 +321,9 @@ struct DifficultySettings {
     paddle_speed_modifier: f32,
     reset_lives_on_level: bool,
     time_limit: Option<f32>, // 困难模式的时间限制（秒）
+    edge_grace: f32, // 挡板边缘容错宽度（像素），0 表示关闭
+    paddle_tilt: bool, // 是否允许倾斜挡板
+    slow_zone: bool, // 底部减速带：下落的球进入后逐渐减速
 }
 
 impl DifficultySettings {
@@ -120,6 +336,9 @@ impl DifficultySettings {
                 paddle_speed_modifier: 1.0,
                 reset_lives_on_level: true,
                 time_limit: None,
+                edge_grace: 10.0,
+                paddle_tilt: false,
+                slow_zone: true,
             },
             Difficulty::Medium => Self {
                 difficulty,
@@ -128,6 +347,9 @@ impl DifficultySettings {
                 paddle_speed_modifier: 1.20,  // 稍微加快挡板速度
                 reset_lives_on_level: false,
                 time_limit: None,
+                edge_grace: 6.0,
+                paddle_tilt: true,
+                slow_zone: false,
             },
             Difficulty::Hard => Self {
                 difficulty,
@@ -136,6 +358,9 @@ impl DifficultySettings {
                 paddle_speed_modifier: 1.8,   // 更快的挡板速度
                 reset_lives_on_level: false,
                 time_limit: Some(180.0), // 3分钟每关
+                edge_grace: 0.0,
+                paddle_tilt: true,
+                slow_zone: false,
             },
         }
     }
@@ -146,9 +371,6 @@ impl DifficultySettings {
 struct GameInitialized(bool);
 
 // 组件定义
-#[derive(Component)]
-struct Paddle;
-
 #[derive(Component)]
 struct Ball {
     velocity: Vec2,
@@ -158,22 +380,250 @@ struct Ball {
 struct Brick {
     brick_type: BrickType,
     health: i32,
+    // 受损后颜色变暗的系数，由 lighting::brick_visuals 与类型颜色合成
+    shade: f32,
+    // 保底道具：被打碎时一定掉落这个道具，不再随机判定
+    guaranteed_drop: Option<PowerUpType>,
+    // 金砖：得分更高，被打碎时一定掉落一个随机的有利道具
+    golden: bool,
+}
+
+impl Brick {
+    fn points(&self) -> u32 {
+        if self.golden {
+            GOLDEN_BRICK_POINTS
+        } else {
+            self.brick_type.points()
+        }
+    }
+
+    fn laser_points(&self) -> u32 {
+        self.points() * 3 / 2
+    }
+}
+
+// 砖块的一面
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Side {
+    Top,
+    Bottom,
+    Left,
+    Right,
+}
+
+impl Side {
+    const ALL: [Side; 4] = [Side::Top, Side::Bottom, Side::Left, Side::Right];
+
+    // collide(球, 砖块) 的结果对应砖块被击中的一面：Left/Right 是球的一侧，Top/Bottom 是砖块的一侧
+    fn struck_by(collision: Collision) -> Side {
+        match collision {
+            Collision::Top => Side::Top,
+            Collision::Bottom => Side::Bottom,
+            Collision::Left => Side::Right,
+            Collision::Right => Side::Left,
+        }
+    }
 }
 
-#[derive(Component, Clone, Copy)]
+// 装甲板：从这一面击中砖块只会反弹，不造成伤害
+#[derive(Component)]
+struct ArmorPlate(Side);
+
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq)]
 enum BrickType {
     Normal,
     Hard,
     Unbreakable,
+    // 冰砖：反弹角度更平
+    Ice,
+    // 橡胶砖：反弹时加速
+    Rubber,
+    // 粘性砖：抓住球片刻后向随机方向弹出
+    Sticky,
+    // 定时炸弹：倒计时结束前没有打碎就会爆炸
+    TimedBomb,
+    // 爆炸砖：被打碎时炸伤周围的砖块，可以连锁
+    Explosive,
+}
+
+// 第三关起每块砖有这个百分比的机会是爆炸砖
+const EXPLOSIVE_BRICK_PERCENT: u32 = 6;
+// 冰砖把反弹后的法向分量减少 40%
+const ICE_NORMAL_DAMPING: f32 = 0.6;
+// 橡胶砖反弹加速 15%
+const RUBBER_SPEED_BOOST: f32 = 1.15;
+
+impl BrickType {
+    const ALL: [BrickType; 8] = [
+        BrickType::Normal,
+        BrickType::Hard,
+        BrickType::Unbreakable,
+        BrickType::Ice,
+        BrickType::Rubber,
+        BrickType::Sticky,
+        BrickType::TimedBomb,
+        BrickType::Explosive,
+    ];
+
+    fn name(&self) -> &'static str {
+        match self {
+            BrickType::Normal => "Normal",
+            BrickType::Hard => "Hard",
+            BrickType::Unbreakable => "Unbreakable",
+            BrickType::Ice => "Ice",
+            BrickType::Rubber => "Rubber",
+            BrickType::Sticky => "Sticky",
+            BrickType::TimedBomb => "Timed Bomb",
+            BrickType::Explosive => "Explosive",
+        }
+    }
+
+    fn color(&self) -> Color {
+        match self {
+            BrickType::Normal => NORMAL_BRICK_COLOR,
+            BrickType::Hard => HARD_BRICK_COLOR,
+            BrickType::Unbreakable => UNBREAKABLE_BRICK_COLOR,
+            BrickType::Ice => ICE_BRICK_COLOR,
+            BrickType::Rubber => RUBBER_BRICK_COLOR,
+            BrickType::Sticky => STICKY_BRICK_COLOR,
+            BrickType::TimedBomb => TIMED_BOMB_BRICK_COLOR,
+            BrickType::Explosive => EXPLOSIVE_BRICK_COLOR,
+        }
+    }
+
+    // 第一次出现的关卡
+    fn first_level(&self) -> u32 {
+        match self {
+            BrickType::Normal | BrickType::Hard => 1,
+            BrickType::Unbreakable | BrickType::Ice => 2,
+            BrickType::Rubber | BrickType::Explosive => 3,
+            BrickType::Sticky | BrickType::TimedBomb => 4,
+        }
+    }
+
+    // 被球打碎时的得分（激光打碎为 1.5 倍）
+    fn points(&self) -> u32 {
+        match self {
+            BrickType::Normal | BrickType::Ice => 10,
+            BrickType::Explosive => 15,
+            BrickType::Hard | BrickType::Rubber | BrickType::Sticky => 20,
+            BrickType::TimedBomb => 40,
+            BrickType::Unbreakable => 0,
+        }
+    }
+
+    fn laser_points(&self) -> u32 {
+        self.points() * 3 / 2
+    }
+
+    // 球撞到砖块后的速度
+    fn bounce_response(&self, velocity: Vec2, collision: Collision) -> Vec2 {
+        let mut reflected = velocity;
+        match collision {
+            Collision::Left | Collision::Right => reflected.x = -reflected.x,
+            Collision::Top | Collision::Bottom => reflected.y = -reflected.y,
+        }
+
+        match self {
+            BrickType::Ice => {
+                // 只压低法向分量，再恢复原来的速度大小
+                match collision {
+                    Collision::Left | Collision::Right => reflected.x *= ICE_NORMAL_DAMPING,
+                    Collision::Top | Collision::Bottom => reflected.y *= ICE_NORMAL_DAMPING,
+                }
+                reflected.normalize_or_zero() * velocity.length()
+            }
+            BrickType::Rubber => {
+                let speed = (velocity.length() * RUBBER_SPEED_BOOST).min(MAX_BALL_SPEED).max(velocity.length());
+                reflected.normalize_or_zero() * speed
+            }
+            _ => reflected,
+        }
+    }
 }
 
 #[derive(Component)]
 struct PowerUp {
     power_type: PowerUpType,
     velocity: Vec2,
+    motion: DropMotion,
+    // 生成后经过的秒数，用于摆动
+    age: f32,
 }
 
-#[derive(Clone, Copy)]
+// 道具默认下落速度
+const POWERUP_FALL_SPEED: f32 = 150.0;
+// 道具的大小
+const POWERUP_SIZE: Vec2 = Vec2::new(30.0, 15.0);
+// 抛物线道具的重力和最大下落速度
+const POWERUP_GRAVITY: f32 = 320.0;
+const POWERUP_MAX_FALL_SPEED: f32 = 230.0;
+
+// 道具下落的轨迹类型，参数在生成时随机决定
+#[derive(Debug, Clone, Copy)]
+enum DropMotion {
+    // 匀速直线下落
+    Straight,
+    // 下落时左右正弦摆动
+    Drift { amplitude: f32, frequency: f32, phase: f32 },
+    // 带横向初速度，被重力拉成抛物线
+    Arc,
+    // 下落较慢，并带轻微的上下浮动
+    Floaty { amplitude: f32, frequency: f32 },
+}
+
+impl DropMotion {
+    // 按道具类型随机选择轨迹和初速度；负面道具偏向直线快速下落，方便躲开
+    fn roll(power_type: PowerUpType, rng: &mut impl Rng) -> (Self, Vec2) {
+        let roll = rng.gen_range(0..100);
+        if power_type.is_negative() {
+            return if roll < 70 {
+                (DropMotion::Straight, Vec2::new(0.0, -rng.gen_range(180.0..220.0)))
+            } else {
+                (DropMotion::drift(rng), Vec2::new(0.0, -rng.gen_range(160.0..190.0)))
+            };
+        }
+
+        match roll {
+            0..=24 => (DropMotion::Straight, Vec2::new(0.0, -POWERUP_FALL_SPEED)),
+            25..=54 => (DropMotion::drift(rng), Vec2::new(0.0, -POWERUP_FALL_SPEED)),
+            55..=79 => {
+                let side = if rng.gen_bool(0.5) { 1.0 } else { -1.0 };
+                (DropMotion::Arc, Vec2::new(side * rng.gen_range(60.0..140.0), rng.gen_range(60.0..120.0)))
+            }
+            _ => (
+                DropMotion::Floaty {
+                    amplitude: rng.gen_range(15.0..25.0),
+                    frequency: rng.gen_range(5.0..7.0),
+                },
+                Vec2::new(0.0, -rng.gen_range(80.0..100.0)),
+            ),
+        }
+    }
+
+    fn drift(rng: &mut impl Rng) -> Self {
+        DropMotion::Drift {
+            amplitude: rng.gen_range(20.0..45.0),
+            frequency: rng.gen_range(2.0..4.0),
+            phase: rng.gen_range(0.0..std::f32::consts::TAU),
+        }
+    }
+
+    // 本帧的位移速度：基础速度加上摆动分量（对正弦位移求导）
+    fn frame_velocity(&self, velocity: Vec2, age: f32) -> Vec2 {
+        match *self {
+            DropMotion::Straight | DropMotion::Arc => velocity,
+            DropMotion::Drift { amplitude, frequency, phase } => {
+                velocity + Vec2::new(amplitude * frequency * (frequency * age + phase).cos(), 0.0)
+            }
+            DropMotion::Floaty { amplitude, frequency } => {
+                velocity + Vec2::new(0.0, amplitude * frequency * (frequency * age).cos())
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 enum PowerUpType {
     PaddleExpand,
     PaddleShrink,
@@ -182,6 +632,145 @@ enum PowerUpType {
     MultiBall,
     PenetratingBall,
     LaserGun,
+    Barrier,
+    SlowMotion,
+    Fireball,
+    HomingBall,
+}
+
+impl PowerUpType {
+    const ALL: [PowerUpType; 11] = [
+        PowerUpType::PaddleExpand,
+        PowerUpType::PaddleShrink,
+        PowerUpType::BallSpeedUp,
+        PowerUpType::BallSpeedDown,
+        PowerUpType::MultiBall,
+        PowerUpType::PenetratingBall,
+        PowerUpType::LaserGun,
+        PowerUpType::Barrier,
+        PowerUpType::SlowMotion,
+        PowerUpType::Fireball,
+        PowerUpType::HomingBall,
+    ];
+
+    fn name(&self) -> &'static str {
+        match self {
+            PowerUpType::PaddleExpand => "Paddle Expand",
+            PowerUpType::PaddleShrink => "Paddle Shrink",
+            PowerUpType::BallSpeedUp => "Ball Speed Up",
+            PowerUpType::BallSpeedDown => "Ball Speed Down",
+            PowerUpType::MultiBall => "Multi Ball",
+            PowerUpType::PenetratingBall => "Penetrating Ball",
+            PowerUpType::LaserGun => "Laser Gun",
+            PowerUpType::Barrier => "Barrier",
+            PowerUpType::SlowMotion => "Slow Motion",
+            PowerUpType::Fireball => "Fireball",
+            PowerUpType::HomingBall => "Homing Ball",
+        }
+    }
+
+    fn color(&self) -> Color {
+        match self {
+            PowerUpType::PaddleExpand => Color::rgb(0.2, 0.8, 0.2),
+            PowerUpType::PaddleShrink => Color::rgb(0.8, 0.2, 0.2),
+            PowerUpType::BallSpeedUp => Color::rgb(0.8, 0.8, 0.2),
+            PowerUpType::BallSpeedDown => Color::rgb(0.2, 0.2, 0.8),
+            PowerUpType::MultiBall => Color::rgb(0.8, 0.2, 0.8),
+            PowerUpType::PenetratingBall => Color::rgb(0.8, 0.5, 0.2),
+            PowerUpType::LaserGun => Color::rgb(0.2, 0.8, 0.8),
+            PowerUpType::Barrier => Color::rgb(0.4, 1.0, 0.6),
+            PowerUpType::SlowMotion => Color::rgb(0.6, 0.5, 1.0),
+            PowerUpType::Fireball => FIREBALL_COLOR,
+            PowerUpType::HomingBall => homing::HOMING_COLOR,
+        }
+    }
+
+    // 对玩家不利的道具
+    fn is_negative(&self) -> bool {
+        matches!(self, PowerUpType::PaddleShrink | PowerUpType::BallSpeedUp)
+    }
+
+    // 限时道具的持续时间，其余道具持续到本关结束或立即生效
+    fn duration(&self) -> Option<f32> {
+        match self {
+            PowerUpType::PenetratingBall => Some(PENETRATING_DURATION),
+            PowerUpType::LaserGun => Some(LASER_DURATION),
+            PowerUpType::Barrier => Some(barrier::BARRIER_DURATION),
+            PowerUpType::SlowMotion => Some(SLOW_MOTION_DURATION),
+            PowerUpType::Fireball => Some(FIREBALL_DURATION),
+            PowerUpType::HomingBall => Some(homing::HOMING_DURATION),
+            _ => None,
+        }
+    }
+}
+
+// 游戏过程中的事件，音效、统计和事件栏都从这里读取
+#[derive(Event, Clone, Copy)]
+enum GameplayEvent {
+    BrickDestroyed {
+        brick_type: BrickType,
+        points: u32,
+        position: Vec3,
+        // 是否计入连击
+        combo: bool,
+    },
+    BrickDamaged {
+        position: Vec3,
+    },
+    // 球打在砖块的装甲面上
+    ArmorDeflected {
+        position: Vec3,
+    },
+    // 打在受护盾保护的砖块上；locked 表示打在还没轮到的发生器上
+    ShieldDeflected {
+        position: Vec3,
+        locked: bool,
+    },
+    PaddleHit {
+        position: Vec3,
+        // 撞击时球的实际速度
+        speed: f32,
+        // 击中位置相对挡板中心的偏移，-1 为左端、1 为右端
+        offset: f32,
+        // 是否落在完美击球区
+        perfect: bool,
+    },
+    // 球擦过挡板边缘，被容错规则救回
+    EdgeSave {
+        position: Vec3,
+    },
+    PowerUpCollected {
+        power_type: PowerUpType,
+        position: Vec3,
+    },
+    // 连续拾取有利道具获得了护符
+    WardGained,
+    // 护符抵消了不利道具，道具本身不算拾取
+    DebuffWarded {
+        power_type: PowerUpType,
+        position: Vec3,
+    },
+    LaserFired {
+        position: Vec3,
+    },
+    LifeLost,
+    // 保险代币代替了这次丢命
+    InsuranceUsed,
+    // 定时炸弹倒计时结束爆炸，扣分并中断连击
+    BombDetonated {
+        position: Vec3,
+        penalty: u32,
+    },
+    // 首领被击败，获得奖励分数
+    BossDefeated {
+        position: Vec3,
+        bonus: u32,
+    },
+    // 一轮爆炸打碎的砖块达到连锁长度
+    ChainReaction {
+        position: Vec3,
+        length: u32,
+    },
 }
 
 #[derive(Component)]
@@ -210,15 +799,67 @@ struct TimerText;
 #[derive(Component)]
 struct LaserText;
 
+#[derive(Component)]
+struct SlowMotionText;
+
+// 护符图标，有护符时显示
+#[derive(Component)]
+struct WardIcon;
+
 #[derive(Component)]
 struct MainMenuUI;
 
 #[derive(Component)]
 struct DifficultyUI;
 
+// 难度选项行，保存在 Difficulty::ALL 中的索引
+#[derive(Component)]
+struct DifficultyOption(usize);
+
 #[derive(Component)]
 struct GameOverUI;
 
+#[derive(Component)]
+struct ProfileUI;
+
+// 排行榜中可滚动的分数列表，position 为当前滚动偏移
+#[derive(Component, Default)]
+struct LeaderboardScrollList {
+    position: f32,
+}
+
+// 排行榜中复用的一行，index 为当前显示的是 LeaderboardData 中的第几条
+#[derive(Component, Default)]
+struct LeaderboardRow {
+    index: Option<usize>,
+}
+
+// 排行榜右侧的最近提交列表
+#[derive(Component)]
+struct RecentScoresList;
+
+// 新出现的最近提交从右侧滑入，elapsed 为已播放的秒数
+#[derive(Component, Default)]
+struct SlideIn {
+    elapsed: f32,
+}
+
+// 档案界面中可滚动的内容列表，position 为当前滚动偏移
+#[derive(Component, Default)]
+struct ProfileScrollList {
+    position: f32,
+}
+
+#[derive(Component)]
+struct SubmitStatusText;
+
+#[derive(Component)]
+struct LevelPercentileText;
+
+// 游戏结束菜单选项，保存在 GameOverAction::available 中的索引
+#[derive(Component)]
+struct GameOverOption(usize);
+
 #[derive(Component)]
 struct VictoryUI;
 
@@ -238,6 +879,13 @@ struct LeaderboardUI;
 #[derive(Component)]
 struct NameInputText;
 
+#[derive(Component)]
+struct SettingsUI;
+
+// 设置条目文本，保存条目索引
+#[derive(Component)]
+struct SettingsItemText(usize);
+
 // 资源定义
 #[derive(Resource)]
 struct Score(u32);
@@ -249,16 +897,30 @@ struct Level(u32);
 struct Lives(u32);
 
 #[derive(Resource)]
-struct LevelTimer(f32);
+struct LevelTimer(Countdown);
+
+// 本局随机种子，决定每关的砖块布局
+#[derive(Resource)]
+struct RunSeed(u64);
 
 #[derive(Resource)]
 struct PowerUpEffects {
     paddle_size_modifier: f32,
     ball_speed_modifier: f32,
     penetrating_ball: bool,
-    penetrating_timer: f32,
+    penetrating_timer: Countdown,
     has_laser: bool,
-    laser_timer: f32,
+    laser_timer: Countdown,
+    // 慢动作剩余时间，按真实时间计时
+    slow_motion_timer: Countdown,
+    fireball: bool,
+    fireball_timer: Countdown,
+    homing: bool,
+    homing_timer: Countdown,
+    // 连续拾取的有利道具数，拾取不利道具或丢命时清零
+    good_streak: u32,
+    // 护符：抵消下一个不利道具，不带到下一关
+    warded: bool,
 }
 
 impl Default for PowerUpEffects {
@@ -267,22 +929,179 @@ impl Default for PowerUpEffects {
             paddle_size_modifier: 1.0,
             ball_speed_modifier: 1.0,
             penetrating_ball: false,
-            penetrating_timer: 0.0,
+            penetrating_timer: Countdown::default(),
             has_laser: false,
-            laser_timer: 0.0,
+            laser_timer: Countdown::default(),
+            slow_motion_timer: Countdown::default(),
+            fireball: false,
+            fireball_timer: Countdown::default(),
+            homing: false,
+            homing_timer: Countdown::default(),
+            good_streak: 0,
+            warded: false,
+        }
+    }
+}
+
+impl PowerUpEffects {
+    // 每关开始时的效果：诅咒改变挡板宽度和球速的基准
+    fn with_curse(curse: &Curse) -> Self {
+        Self {
+            paddle_size_modifier: curse.paddle_size_modifier(),
+            ball_speed_modifier: curse.ball_speed_modifier(),
+            ..default()
         }
     }
 }
 
+// 场上物体（球、激光、道具、粒子）的时间倍率，由 update_powerup_timers 按慢动作道具设置
+#[derive(Resource)]
+struct TimeScale(f32);
+
+impl Default for TimeScale {
+    fn default() -> Self {
+        Self(1.0)
+    }
+}
+
+impl TimeScale {
+    fn delta(&self, time: &Time) -> f32 {
+        time.delta_seconds() * self.0
+    }
+}
+
+// 一局游戏需要重置的资源
+#[derive(SystemParam)]
+struct RunResources<'w> {
+    level: ResMut<'w, Level>,
+    score: ResMut<'w, Score>,
+    lives: ResMut<'w, Lives>,
+    power_effects: ResMut<'w, PowerUpEffects>,
+    game_initialized: ResMut<'w, GameInitialized>,
+    run_seed: ResMut<'w, RunSeed>,
+    recorder: ResMut<'w, ReplayRecorder>,
+    stats: ResMut<'w, RunStats>,
+    feed: ResMut<'w, EventFeed>,
+    difficulty_settings: ResMut<'w, DifficultySettings>,
+    run_submitted: ResMut<'w, RunSubmitted>,
+    daily_run: ResMut<'w, DailyRun>,
+    event_run: ResMut<'w, EventRun>,
+    bonus: ResMut<'w, BonusStage>,
+    boss: ResMut<'w, BossStage>,
+    orientation: ResMut<'w, Orientation>,
+    game_mode: ResMut<'w, GameMode>,
+    insurance: ResMut<'w, Insurance>,
+    hot_team: ResMut<'w, HotTeam>,
+    curse: ResMut<'w, Curse>,
+}
+
+// 开始新的一局：重置所有局内资源，seed 为 None 时随机生成新种子
+fn start_new_run(run: &mut RunResources, seed: Option<u64>) {
+    run.level.0 = 1;
+    run.score.0 = 0;
+    run.lives.0 = run.difficulty_settings.lives;
+    *run.power_effects = PowerUpEffects::default();
+    run.game_initialized.0 = false;
+    run.run_seed.0 = seed.unwrap_or_else(rand::random);
+    run.recorder.reset();
+    run.stats.reset();
+    run.feed.clear();
+    run.run_submitted.0 = false;
+    run.daily_run.0 = None;
+    run.event_run.0 = None;
+    run.bonus.finish();
+    run.boss.finish();
+    *run.orientation = Orientation::Normal;
+    *run.game_mode = GameMode::Campaign;
+    // 保险代币只在难度选择界面购买，其他方式开始的局没有
+    *run.insurance = Insurance::default();
+    *run.hot_team = HotTeam::default();
+    run.curse.clear();
+}
+
+// 本局是否已经提交过分数；只有开始新的一局才会清除，重复进入结束界面不会再次提交
+#[derive(Resource, Default)]
+struct RunSubmitted(bool);
+
 // 新增资源
 #[derive(Resource)]
 struct PlayerName(String);
 
-#[derive(Resource)]
-struct ApiClientResource(ApiClient);
+// 名字最多的字符数
+const MAX_NAME_LENGTH: usize = 20;
 
-#[derive(Resource)]
-struct LeaderboardData(Option<LeaderboardResponse>);
+// 名字允许的字符
+fn is_name_char(ch: char) -> bool {
+    ch.is_alphanumeric() || ch == ' '
+}
+
+// 校验并整理输入的名字，为空时返回 None
+fn normalize_player_name(text: &str) -> Option<String> {
+    let name = text.trim();
+    if name.is_empty() || name.chars().count() > MAX_NAME_LENGTH || !name.chars().all(is_name_char) {
+        return None;
+    }
+    Some(name.to_string())
+}
+
+// 排行榜数据的加载状态
+#[derive(Resource, Default)]
+enum LeaderboardData {
+    #[default]
+    Loading,
+    Loaded(LeaderboardResponse),
+    Failed,
+}
+
+// 排行榜每页条数
+const LEADERBOARD_PAGE_SIZE: usize = 10;
+// 距离列表底部小于该距离时加载下一页
+const LEADERBOARD_LOAD_MARGIN: f32 = 60.0;
+// 排行榜每行占用的高度（行高加上行间距）和可见范围外多准备的行数
+const LEADERBOARD_ROW_HEIGHT: f32 = 40.0;
+const LEADERBOARD_ROW_GAP: f32 = 5.0;
+const LEADERBOARD_OVERSCAN: usize = 3;
+// 玩家自己的成绩所在行
+const OWN_ENTRY_BACKGROUND: Color = Color::rgba(0.2, 0.45, 0.9, 0.35);
+const OWN_ENTRY_NAME_COLOR: Color = Color::rgb(0.5, 0.8, 1.0);
+
+// 最近提交的分数（不分难度），在排行榜界面定期刷新
+#[derive(Resource, Default)]
+struct RecentScores {
+    // 第一次请求返回前为 None
+    scores: Option<Vec<api::Score>>,
+    failed: bool,
+    // 距离下次刷新的秒数
+    refresh: f32,
+}
+
+// 最近提交显示的条数
+const RECENT_SCORES_LIMIT: usize = 10;
+// 最近提交的刷新间隔（秒）
+const RECENT_SCORES_REFRESH_INTERVAL: f32 = 20.0;
+// 新条目滑入动画的时长（秒）
+const SLIDE_IN_DURATION: f32 = 0.4;
+
+// 排行榜赛季选项（"current"、历史赛季 "YYYY-MM"、"all"）
+#[derive(Resource, Default)]
+struct LeaderboardSeasons {
+    options: Vec<String>,
+    selected: usize,
+    current_label: Option<String>,
+}
+
+impl LeaderboardSeasons {
+    fn selected_label(&self, locale: &Locale) -> String {
+        match self.options.get(self.selected).map(String::as_str) {
+            Some("current") | None => match &self.current_label {
+                Some(current) => locale.tr("leaderboard.season_current_named", &[("season", current)]),
+                None => locale.tr("leaderboard.season_current", &[]),
+            },
+            Some("all") => locale.tr("leaderboard.season_all", &[]),
+            Some(season) => season.to_string(),
+        }
+    }
+}
 
 #[derive(Resource)]
 struct NameInput {
@@ -301,7 +1120,62 @@ impl Default for NameInput {
     }
 }
 
+// 设置界面当前选中的条目
+#[derive(Resource, Default)]
+struct SettingsMenuState {
+    selected: usize,
+}
+
+// 难度选择界面当前高亮的选项
+#[derive(Resource, Default)]
+struct DifficultyMenuState {
+    selected: usize,
+    // 这一局要购买的保险代币数，以及进入界面时钱包里的金币
+    insurance: u8,
+    coins: u32,
+}
+
+// 游戏结束界面当前高亮的选项
+#[derive(Resource, Default)]
+struct GameOverMenuState {
+    selected: usize,
+}
+
 fn main() {
+    // --balance-report：输出各难度实际生效的数值后退出，不创建窗口
+    if std::env::args().any(|arg| arg == "--balance-report") {
+        print!("{}", balance::BalanceReport::current().to_markdown());
+        return;
+    }
+    // --version、--self-check 等发布用的命令，同样不创建窗口
+    if let Some(exit_code) = release::run_cli_command() {
+        std::process::exit(exit_code);
+    }
+
+    // 首次启动还没有档案时先进入档案选择
+    let registry = ProfileRegistry::load();
+    let paths = registry.active_paths();
+    let player_name = registry
+        .active_profile()
+        .map(|profile| profile.name.clone())
+        .unwrap_or_else(|| "Player".to_string());
+    let settings = Settings::load(&paths);
+    let locale = Locale::new(settings.language());
+    let seasonal_theme = SeasonalTheme::new(settings.holiday_theme);
+    // 展台模式直接进入待机画面
+    let kiosk = KioskConfig::from_args(std::env::args());
+    let smoke_test = std::env::args().any(|arg| arg == smoke::SMOKE_TEST_FLAG);
+    // 崩溃时也要把进行中的一局写到磁盘
+    let run_mirror = RunMirrorHandle::default();
+    pending_run::install_panic_hook(&run_mirror);
+    let initial_state = if kiosk.enabled {
+        GameState::Attract
+    } else if registry.active_profile().is_some() {
+        GameState::MainMenu
+    } else {
+        GameState::ProfileSelect
+    };
+
     App::new()
         .add_plugins(DefaultPlugins.set(WindowPlugin {
             primary_window: Some(Window {
@@ -310,168 +1184,623 @@ fn main() {
                 resizable: false,
                 ..default()
             }),
+            // 展台模式忽略关闭窗口的请求
+            close_when_requested: !kiosk.enabled,
             ..default()
         }).set(AssetPlugin {
             // 确保资源能正确加载
             ..default()
         }))
-        .init_state::<GameState>()
+        .insert_state(initial_state)
         .insert_resource(ClearColor(BACKGROUND_COLOR))
         .insert_resource(Score(0))
         .insert_resource(Level(1))
         .insert_resource(Lives(3))
-        .insert_resource(LevelTimer(0.0))
+        .insert_resource(LevelTimer(Countdown::default()))
         .insert_resource(PowerUpEffects::default())
+        .init_resource::<TimeScale>()
         .insert_resource(DifficultySettings::new(Difficulty::Medium))
         .insert_resource(GameInitialized(false))
-        .insert_resource(PlayerName("Player".to_string()))
-        .insert_resource(ApiClientResource(ApiClient::new()))
-        .insert_resource(LeaderboardData(None))
+        .insert_resource(PlayerName(player_name))
+        .insert_resource(kiosk)
+        .init_resource::<KioskSession>()
+        .add_plugins(NetworkPlugin)
+        .add_plugins(PaddlePlugin)
+        .add_plugins(fixed_step::FixedStepPlugin)
+        .add_plugins(perf::PerfHudPlugin)
+        .add_plugins(determinism::DeterminismAuditPlugin)
+        .add_plugins(governor::GovernorPlugin)
+        .add_plugins(smoke::SmokeTestPlugin { enabled: smoke_test })
+        .insert_resource(LeaderboardData::default())
+        .insert_resource(LeaderboardSeasons::default())
+        .init_resource::<RecentScores>()
+        .init_resource::<LeaderboardHistory>()
+        .init_resource::<StickSmoothing>()
+        .init_resource::<LastInputDevice>()
+        .init_resource::<NameEntryMode>()
+        .init_resource::<ArcadeNameEntry>()
         .insert_resource(NameInput::default())
+        .insert_resource(settings)
+        .insert_resource(locale)
+        .insert_resource(seasonal_theme)
+        .insert_resource(paths)
+        .insert_resource(registry)
+        .insert_resource(ProfilePickerState::default())
+        .init_resource::<Changelog>()
+        .init_resource::<Cosmetics>()
+        .init_resource::<CosmeticsMenuState>()
+        .insert_resource(SettingsMenuState::default())
+        .insert_resource(DifficultyMenuState::default())
+        .insert_resource(GameOverMenuState::default())
+        .insert_resource(RunSeed(0))
+        .init_resource::<GameRng>()
+        .insert_resource(ReplayRecorder::default())
+        .insert_resource(death_cam::DeathSequence::default())
+        .init_resource::<RollingStateBuffer>()
+        .init_resource::<LifeReplay>()
+        .init_resource::<admin::AdminMode>()
+        .insert_resource(RunStats::default())
+        .insert_resource(EventFeed::default())
+        .insert_resource(BrickDestructionLog::default())
+        .insert_resource(PlayClock::default())
+        .insert_resource(RunSubmitted::default())
+        .insert_resource(DailyRun::default())
+        .init_resource::<CurrentEvent>()
+        .init_resource::<EventRun>()
+        .init_resource::<HotTeam>()
+        .insert_resource(MedalThresholds::load())
+        .init_resource::<GameMode>()
+        .init_resource::<LevelAttempt>()
+        .init_resource::<LevelSelectState>()
+        .init_resource::<GeneratorChain>()
+        .init_resource::<BrickQuota>()
+        .init_resource::<Tournament>()
+        .init_resource::<TournamentEntry>()
+        .init_resource::<RaceLobby>()
+        .init_resource::<RaceSession>()
+        .init_resource::<RunRivals>()
+        .insert_resource(run_mirror)
+        .init_resource::<RunRecovery>()
+        .init_resource::<RankToastPending>()
+        .insert_resource(BoardSnapshot::default())
+        .init_resource::<Orientation>()
+        .init_resource::<BonusStage>()
+        .init_resource::<BossStage>()
+        .init_resource::<Curse>()
+        .init_resource::<ResumeKind>()
+        .init_resource::<curse::CurseOffer>()
+        .init_resource::<Insurance>()
+        .init_resource::<InputBuffer>()
+        .init_resource::<FontAssets>()
+        .add_event::<SoundEvent>()
+        .add_event::<GameplayEvent>()
+        .add_event::<Explosion>()
+        .add_event::<WallImpact>()
+        // 全局系统
+        .add_systems(Startup, (audio::setup_audio, weekly_event::request_weekly_event))
+        // 在状态切换构建界面之前同步语言
+        .add_systems(PreUpdate, i18n::sync_language)
+        .add_systems(
+            Update,
+            (
+                apply_ui_scale,
+                arcade_name::track_input_device,
+                cosmetics::sync_cosmetics,
+                audio::gameplay_sounds,
+                audio::play_sound_events,
+                profile::record_gameplay_stats,
+                pending_run::sync_run_mirror,
+                weekly_event::receive_weekly_event,
+            ),
+        )
+        .add_systems(Last, pending_run::flush_on_exit)
+        // 游戏中的发射按键先进入缓冲，发射球和激光都从缓冲中读取
+        .add_systems(
+            Update,
+            input_buffer::buffer_actions
+                .before(launch::release_held_balls)
+                .before(laser_shooting),
+        )
         // 菜单系统
-        .add_systems(OnEnter(GameState::MainMenu), setup_main_menu)
-        .add_systems(Update, main_menu_system.run_if(in_state(GameState::MainMenu)))
+        .add_systems(OnEnter(GameState::MainMenu), setup_main_menu.run_if(kiosk::kiosk_disabled))
+        .add_systems(
+            Update,
+            (main_menu_system, weekly_event::update_event_banner)
+                .run_if(in_state(GameState::MainMenu).and_then(kiosk::kiosk_disabled)),
+        )
         .add_systems(OnExit(GameState::MainMenu), cleanup_main_menu)
+        // 上次没有正常结束的局：询问补交还是丢弃
+        .add_systems(OnEnter(GameState::MainMenu), pending_run::redirect_pending_run.run_if(kiosk::kiosk_disabled))
+        .add_systems(OnEnter(GameState::RecoverRun), pending_run::setup_recover_screen)
+        .add_systems(Update, pending_run::recover_screen_system.run_if(in_state(GameState::RecoverRun)))
+        .add_systems(OnExit(GameState::RecoverRun), pending_run::cleanup_recover_screen)
+        // 提交返回的排行榜名次：结算界面上计数显示，已经离开时回到主菜单再提示
+        .add_systems(
+            Update,
+            (
+                rank_result::receive_submission_rank,
+                rank_result::animate_rank_counter,
+                rank_result::animate_celebration,
+            ),
+        )
+        .add_systems(OnEnter(GameState::MainMenu), rank_result::queue_rank_toast.run_if(kiosk::kiosk_disabled))
+        .add_systems(
+            Update,
+            (rank_result::show_rank_toast, rank_result::fade_rank_toast).run_if(in_state(GameState::MainMenu)),
+        )
+        .add_systems(OnExit(GameState::MainMenu), rank_result::cleanup_rank_toast)
+        // 对手推荐：开局前取一次对手列表，提交后提示超过了谁
+        .add_systems(OnEnter(GameState::Playing), rivals::request_run_rivals.run_if(resume::full_setup))
+        .add_systems(Update, (rivals::receive_run_rivals, rivals::fade_rival_toast))
+        // 展台模式：待机画面代替主菜单，无人操作时回到待机画面
+        .add_systems(OnEnter(GameState::MainMenu), kiosk::redirect_main_menu.run_if(kiosk::kiosk_enabled))
+        .add_systems(OnEnter(GameState::Attract), kiosk::setup_attract)
+        .add_systems(Update, kiosk::attract_system.run_if(in_state(GameState::Attract)))
+        .add_systems(OnExit(GameState::Attract), kiosk::cleanup_attract)
+        .add_systems(
+            Update,
+            (kiosk::idle_reset_system, kiosk::quit_combo_system).run_if(kiosk::kiosk_enabled),
+        )
         // 难度选择系统
         .add_systems(OnEnter(GameState::DifficultySelect), setup_difficulty_menu)
         .add_systems(Update, difficulty_menu_system.run_if(in_state(GameState::DifficultySelect)))
         .add_systems(OnExit(GameState::DifficultySelect), cleanup_difficulty_menu)
         // 输入名称系统
         .add_systems(OnEnter(GameState::EnterName), setup_enter_name)
-        .add_systems(Update, (enter_name_system, update_cursor).run_if(in_state(GameState::EnterName)))
-        .add_systems(OnExit(GameState::EnterName), cleanup_enter_name)
-        // 游戏系统
-        .add_systems(OnEnter(GameState::Playing), setup_game_conditional)
         .add_systems(
             Update,
+            (enter_name_system, update_cursor)
+                .run_if(in_state(GameState::EnterName).and_then(resource_equals(NameEntryMode::Text))),
+        )
+        .add_systems(
+            Update,
+            arcade_name::arcade_name_system
+                .run_if(in_state(GameState::EnterName).and_then(resource_equals(NameEntryMode::Arcade))),
+        )
+        .add_systems(OnExit(GameState::EnterName), cleanup_enter_name)
+        // 游戏系统：新的一局、下一关走完整的初始化，从暂停继续只检查状态并在有球飞行时倒计时
+        .add_systems(
+            OnEnter(GameState::Playing),
+            setup_game_conditional.before(PaddleSet::Spawn).run_if(resume::full_setup),
+        )
+        .add_systems(
+            OnEnter(GameState::Playing),
+            (resume::validate_after_resume, resume::start_resume_countdown).chain().run_if(resume::from_pause),
+        )
+        .add_systems(Update, resume::resume_countdown_system.run_if(in_state(GameState::Playing)))
+        .add_systems(OnExit(GameState::Playing), resume::leave_playing)
+        // 物理按固定帧运行：球、激光和道具的移动与碰撞，以及依赖碰撞结果的胜利判断和关卡、道具计时。
+        // 游戏时间暂停时（暂停后继续的倒计时）固定帧也不会运行
+        .add_systems(
+            FixedUpdate,
             (
-                paddle_movement,
                 ball_movement,
-                ball_collision,
+                ball_collision.after(ball_movement),
                 powerup_movement,
-                powerup_collision,
+                powerup_collision.after(powerup_movement),
+                update_powerup_timers.after(powerup_collision),
+                barrier::update_barrier.after(powerup_collision),
+                homing::update_homing_targets.after(update_powerup_timers).before(ball_movement),
+                laser_movement,
+                // 三者都从 GameRng 取随机数，顺序固定，同样的输入取到同样的随机数
+                laser_collision.after(laser_movement).after(ball_collision),
+                sticky::release_stuck_balls.after(laser_collision),
+                // 先结算本帧打碎的砖块（命令在排序边界应用），再判断胜利，最后才看时间是否用完
+                check_victory
+                    .after(ball_collision)
+                    .after(laser_collision)
+                    .after(sticky::release_stuck_balls),
+                update_level_timer.after(check_victory),
+            )
+                .run_if(in_state(GameState::Playing).and_then(fixed_step::no_transition_pending)),
+        )
+        // 丢命事件只由 ball_collision 发出，读取它的系统（这里和下面的回放截取）排在同一个固定帧里。
+        // ball_collision 丢掉最后一条命时已经设置了状态切换，这些系统不能再用 no_transition_pending
+        .add_systems(FixedUpdate, reset_ward_streak.after(ball_collision).run_if(in_state(GameState::Playing)))
+        // 下面在 Update 中读取物理结果的系统（球的颜色、停在挡板上的球、配额关的提示）只用于显示。
+        // Main 调度里 RunFixedMainLoop 在 Update 之前，运行时这一帧的固定帧都已经跑完，读到的是最新结果
+        .add_systems(
+            Update,
+            (
+                paddle_movement,
                 particle_system,
-                update_powerup_timers,
-                update_level_timer,
-                check_victory,
                 update_ui,
+                update_slow_motion_text,
+                update_fireball_color,
+                insurance::update_insurance_hud,
                 pause_game_input,
                 laser_shooting,
-                laser_movement,
-                laser_collision,
             )
                 .run_if(in_state(GameState::Playing)),
         )
-        // 暂停系统
-        .add_systems(OnEnter(GameState::Paused), setup_pause_menu)
-        .add_systems(Update, pause_menu_system.run_if(in_state(GameState::Paused)))
-        .add_systems(OnExit(GameState::Paused), cleanup_pause_menu)
-        // 游戏结束系统
-        .add_systems(OnEnter(GameState::GameOver), (cleanup_game, setup_game_over))
-        .add_systems(Update, game_over_system.run_if(in_state(GameState::GameOver)))
-        .add_systems(OnExit(GameState::GameOver), cleanup_game_over)
-        // 胜利系统
-        .add_systems(OnEnter(GameState::Victory), setup_victory)
-        .add_systems(Update, victory_system.run_if(in_state(GameState::Victory)))
-        .add_systems(OnExit(GameState::Victory), cleanup_victory)
-        // 下一关系统
-        .add_systems(OnEnter(GameState::NextLevel), (cleanup_game, next_level_setup))
-        // 排行榜系统
-        .add_systems(OnEnter(GameState::Leaderboard), setup_leaderboard)
-        .add_systems(Update, leaderboard_system.run_if(in_state(GameState::Leaderboard)))
-        .add_systems(OnExit(GameState::Leaderboard), cleanup_leaderboard)
-        .run();
-}
-
-// 设置主菜单
-fn setup_main_menu(mut commands: Commands, mut game_initialized: ResMut<GameInitialized>) {
-    game_initialized.0 = false;
-    commands.spawn(Camera2dBundle::default());
-
-    commands
-        .spawn((
-            NodeBundle {
-                style: Style {
-                    width: Val::Percent(100.0),
-                    height: Val::Percent(100.0),
-                    flex_direction: FlexDirection::Column,
-                    justify_content: JustifyContent::Center,
-                    align_items: AlignItems::Center,
-                    ..default()
-                },
-                background_color: BackgroundColor(Color::rgb(0.1, 0.1, 0.15)),
-                ..default()
-            },
-            MainMenuUI,
-        ))
-        .with_children(|parent| {
-            parent.spawn(TextBundle::from_section(
-                "SUPER BREAKOUT",
-                TextStyle {
-                    font_size: 80.0,
-                    color: Color::WHITE,
-                    ..default()
-                },
-            ));
-            
-            parent.spawn(TextBundle::from_section(
-                "Press SPACE to Start",
-                TextStyle {
-                    font_size: 30.0,
-                    color: Color::rgb(0.7, 0.7, 0.7),
-                    ..default()
-                },
-            ).with_style(Style {
-                margin: UiRect::top(Val::Px(50.0)),
-                ..default()
-            }));
-
-            parent.spawn(TextBundle::from_section(
-                "Press L to View Leaderboard",
-                TextStyle {
-                    font_size: 25.0,
-                    color: Color::rgb(0.5, 0.7, 0.9),
-                    ..default()
-                },
-            ).with_style(Style {
-                margin: UiRect::top(Val::Px(20.0)),
-                ..default()
-            }));
+        .add_systems(
+            FixedUpdate,
+            (replay::record_paddle_trace, replay::ghost_playback)
+                .run_if(in_state(GameState::Playing).and_then(bonus::bonus_inactive).and_then(boss::boss_inactive)),
+        )
+        // 护盾发生器关：先推进发生器顺序，再判断胜利
+        .add_systems(
+            FixedUpdate,
+            shield::advance_generator_chain
+                .after(ball_collision)
+                .after(laser_collision)
+                .after(explosion::resolve_explosions)
+                .before(check_victory)
+                .run_if(
+                    in_state(GameState::Playing)
+                        .and_then(fixed_step::no_transition_pending)
+                        .and_then(shield::chain_active),
+                ),
+        )
+        .add_systems(
+            Update,
+            (shield::animate_shields, shield::update_shield_hud)
+                .run_if(in_state(GameState::Playing).and_then(shield::chain_active)),
+        )
+        .add_systems(
+            Update,
+            (shield::show_locked_popups, shield::animate_locked_popups)
+                .chain()
+                .run_if(in_state(GameState::Playing)),
+        )
+        // 追踪球：目标在固定帧里移动前选好，准星画在目标砖块上
+        .add_systems(
+            Update,
+            (homing::draw_homing_reticles, homing::update_homing_text).run_if(in_state(GameState::Playing)),
+        )
+        // 高速时的可读性辅助：挡板残影和墙上的撞击印记
+        .add_systems(
+            Update,
+            (motion_trails::paddle_afterimages.after(paddle_movement), motion_trails::wall_decals)
+                .run_if(in_state(GameState::Playing)),
+        )
+        // 定时炸弹：在球和激光之后计时，本帧已经打碎的炸弹不会再爆炸；
+        // 火球、爆炸砖和炸弹的爆炸最后一起结算，结果在胜利判断前生效
+        .add_systems(Update, bomb::attach_bomb_labels.run_if(in_state(GameState::Playing)))
+        .add_systems(
+            FixedUpdate,
+            (
+                bomb::tick_bombs
+                    .after(ball_collision)
+                    .after(laser_collision)
+                    .after(sticky::release_stuck_balls),
+                explosion::resolve_explosions
+                    .after(bomb::tick_bombs)
+                    .before(check_victory),
+            )
+                .run_if(in_state(GameState::Playing).and_then(fixed_step::no_transition_pending)),
+        )
+        // 首领关：首领移动、补砖块，球和激光的伤害在胜利判断前结算
+        .add_systems(
+            FixedUpdate,
+            (
+                boss::move_boss,
+                boss::boss_hits
+                    .after(ball_collision)
+                    .after(laser_collision)
+                    .before(check_victory),
+            )
+                .run_if(
+                    in_state(GameState::Playing)
+                        .and_then(fixed_step::no_transition_pending)
+                        .and_then(boss::boss_active),
+                ),
+        )
+        .add_systems(
+            Update,
+            boss::update_boss_health_bar.run_if(in_state(GameState::Playing).and_then(boss::boss_active)),
+        )
+        // 配额关：在所有打碎砖块的系统之后累计块数，达成时在胜利判断前开始消散
+        .add_systems(
+            FixedUpdate,
+            (
+                quota::count_destroyed_bricks
+                    .after(ball_collision)
+                    .after(laser_collision)
+                    .after(sticky::release_stuck_balls)
+                    .after(explosion::resolve_explosions),
+                quota::start_dissolve.after(quota::count_destroyed_bricks).before(check_victory),
+            )
+                .run_if(
+                    in_state(GameState::Playing)
+                        .and_then(fixed_step::no_transition_pending)
+                        .and_then(quota::quota_active),
+                ),
+        )
+        .add_systems(
+            Update,
+            (quota::dissolve_bricks, quota::update_quota_hud)
+                .run_if(in_state(GameState::Playing).and_then(quota::quota_active)),
+        )
+        // 奖励关的计时和提示；计时和关卡计时一样按固定帧
+        .add_systems(
+            FixedUpdate,
+            bonus::tick_bonus_stage
+                .after(check_victory)
+                .run_if(
+                    in_state(GameState::Playing)
+                        .and_then(fixed_step::no_transition_pending)
+                        .and_then(bonus::bonus_active),
+                ),
+        )
+        .add_systems(
+            Update,
+            bonus::update_bonus_hud.run_if(in_state(GameState::Playing).and_then(bonus::bonus_active)),
+        )
+        .add_systems(
+            Update,
+            (
+                profile::track_play_time,
+                feed::collect_feed_events,
+                feed::update_event_feed,
+                board_snapshot::record_brick_destruction,
+                hazard_rules::advance_play_clock,
+                team_colors::tick_hot_team,
+                team_colors::update_team_banner,
+                weekly_event::reveal_fogged_bricks,
+            )
+                .run_if(in_state(GameState::Playing)),
+        )
+        .add_systems(
+            Update,
+            (lighting::emit_destruction_light, lighting::brick_visuals)
+                .chain()
+                .run_if(in_state(GameState::Playing)),
+        )
+        // 节日主题：设置变化后重新确定主题，游戏中背景飘落粒子
+        .add_systems(Update, seasonal::sync_seasonal_theme)
+        .add_systems(
+            Update,
+            seasonal::ambient_particles
+                .after(seasonal::sync_seasonal_theme)
+                .run_if(in_state(GameState::Playing)),
+        )
+        .add_systems(
+            Update,
+            (
+                paddle_feedback::react_to_paddle_hits,
+                paddle_feedback::animate_paddle_feedback,
+                paddle_feedback::animate_perfect_popups,
+            )
+                .chain()
+                .run_if(in_state(GameState::Playing)),
+        )
+        .add_systems(
+            Update,
+            (explosion::show_chain_popups, explosion::animate_chain_popups)
+                .chain()
+                .run_if(in_state(GameState::Playing)),
+        )
+        .add_systems(
+            Update,
+            (launch::hold_balls_on_paddle, launch::release_held_balls, launch::draw_aim_preview)
+                .chain()
+                .after(paddle_movement)
+                .run_if(in_state(GameState::Playing)),
+        )
+        // 死亡镜头系统
+        .add_systems(OnEnter(GameState::DeathSequence), death_cam::setup_death_sequence)
+        .add_systems(
+            Update,
+            (death_cam::death_sequence_system, particle_system)
+                .run_if(in_state(GameState::DeathSequence)),
+        )
+        .add_systems(OnExit(GameState::DeathSequence), death_cam::cleanup_death_sequence)
+        // 丢命后的慢速回放
+        .add_systems(
+            FixedUpdate,
+            life_replay::record_state.run_if(in_state(GameState::Playing).and_then(bonus::bonus_inactive)),
+        )
+        // 回放片段正好截在丢命的那个固定帧
+        .add_systems(
+            FixedUpdate,
+            life_replay::capture_on_life_lost
+                .after(ball_collision)
+                .after(life_replay::record_state)
+                .run_if(in_state(GameState::Playing)),
+        )
+        // 丢掉最后一条命的那一帧已经要进入死亡镜头，场上没有停在挡板上的球，不跳过会把刚出现的回放提示关掉
+        .add_systems(
+            Update,
+            life_replay::offer_input_playing
+                .run_if(in_state(GameState::Playing).and_then(fixed_step::no_transition_pending)),
+        )
+        .add_systems(
+            Update,
+            life_replay::offer_input_death_sequence
+                .after(death_cam::death_sequence_system)
+                .run_if(in_state(GameState::DeathSequence)),
+        )
+        .add_systems(OnEnter(GameState::LifeReplay), life_replay::setup_life_replay)
+        .add_systems(Update, life_replay::life_replay_system.run_if(in_state(GameState::LifeReplay)))
+        .add_systems(OnExit(GameState::LifeReplay), life_replay::cleanup_life_replay)
+        // 暂停系统
+        .add_systems(OnEnter(GameState::Paused), setup_pause_menu)
+        .add_systems(Update, pause_menu_system.run_if(in_state(GameState::Paused)))
+        .add_systems(OnExit(GameState::Paused), cleanup_pause_menu)
+        // 游戏结束系统
+        .add_systems(
+            OnEnter(GameState::GameOver),
+            (board_snapshot::capture_board_snapshot, cleanup_game, setup_game_over).chain(),
+        )
+        .add_systems(
+            Update,
+            (game_over_system, game_over_responses).run_if(in_state(GameState::GameOver)),
+        )
+        .add_systems(OnExit(GameState::GameOver), (cleanup_game_over, cancel_network_requests))
+        // 胜利系统
+        .add_systems(OnEnter(GameState::Victory), setup_victory)
+        .add_systems(Update, victory_system.run_if(in_state(GameState::Victory)))
+        .add_systems(OnExit(GameState::Victory), cleanup_victory)
+        // 通关画面：按时间线显示统计和字幕，最后选择提交还是回到主菜单
+        .add_systems(OnEnter(GameState::GameComplete), (cleanup_game, ending::setup_ending).chain())
+        .add_systems(
+            Update,
+            (ending::ending_timeline_system, ending::show_ending, ending::ending_menu_system)
+                .chain()
+                .run_if(in_state(GameState::GameComplete)),
+        )
+        .add_systems(OnExit(GameState::GameComplete), ending::cleanup_ending)
+        // 下一关系统
+        .add_systems(OnEnter(GameState::NextLevel), (cleanup_game, next_level_setup))
+        .add_systems(OnEnter(GameState::LevelInterlude), interlude::setup_level_interlude)
+        .add_systems(Update, interlude::level_interlude_system.run_if(in_state(GameState::LevelInterlude)))
+        .add_systems(OnExit(GameState::LevelInterlude), interlude::cleanup_level_interlude)
+        .add_systems(OnEnter(GameState::CurseChoice), curse::setup_curse_choice)
+        .add_systems(Update, curse::curse_choice_system.run_if(in_state(GameState::CurseChoice)))
+        .add_systems(OnExit(GameState::CurseChoice), curse::cleanup_curse_choice)
+        // 排行榜系统
+        .add_systems(OnEnter(GameState::Leaderboard), setup_leaderboard)
+        .add_systems(
+            Update,
+            (
+                leaderboard_system,
+                leaderboard_responses,
+                recent_scores_feed,
+                animate_slide_in,
+                // 在淡出之前改写行，新写入的排名变化提示在同一帧得到正确的透明度
+                recycle_leaderboard_rows
+                    .after(leaderboard_system)
+                    .after(leaderboard_responses)
+                    .before(rank_movement::fade_rank_indicators),
+                rank_movement::fade_rank_indicators,
+            )
+                .run_if(in_state(GameState::Leaderboard)),
+        )
+        // 管理模式：没有配置管理密钥时完全不运行
+        .add_systems(
+            Update,
+            (
+                admin::toggle_admin_mode,
+                admin::admin_input,
+                admin::admin_responses,
+                admin::show_admin_affordances,
+                admin::fade_admin_toast,
+            )
+                .chain()
+                .after(leaderboard_system)
+                .before(recycle_leaderboard_rows)
+                .run_if(in_state(GameState::Leaderboard).and_then(admin::admin_available)),
+        )
+        .add_systems(
+            OnExit(GameState::Leaderboard),
+            (cleanup_leaderboard, cancel_network_requests, admin::cleanup_admin_mode),
+        )
+        // 设置系统
+        .add_systems(OnEnter(GameState::Settings), setup_settings_menu)
+        .add_systems(
+            Update,
+            (settings_menu_system, stick::update_stick_preview).run_if(in_state(GameState::Settings)),
+        )
+        .add_systems(OnExit(GameState::Settings), cleanup_settings_menu)
+        // 档案系统
+        .add_systems(OnEnter(GameState::Profile), (setup_profile_screen, rivals::request_profile_rivals))
+        .add_systems(
+            Update,
+            (profile_screen_system, rivals::update_rivals_panel).run_if(in_state(GameState::Profile)),
+        )
+        .add_systems(OnExit(GameState::Profile), cleanup_profile_screen)
+        // 外观选择
+        .add_systems(OnEnter(GameState::Cosmetics), cosmetics::setup_cosmetics_screen)
+        .add_systems(Update, cosmetics::cosmetics_screen_system.run_if(in_state(GameState::Cosmetics)))
+        .add_systems(OnExit(GameState::Cosmetics), cosmetics::cleanup_cosmetics_screen)
+        .add_systems(OnEnter(GameState::DailyChallenge), daily::setup_daily_screen)
+        .add_systems(
+            Update,
+            (daily::daily_screen_system, daily::daily_responses).run_if(in_state(GameState::DailyChallenge)),
+        )
+        .add_systems(OnExit(GameState::DailyChallenge), daily::cleanup_daily_screen)
+        // 单关挑战：选关界面和结算
+        .add_systems(OnEnter(GameState::LevelSelect), level_select::setup_level_select)
+        .add_systems(Update, level_select::level_select_system.run_if(in_state(GameState::LevelSelect)))
+        .add_systems(OnExit(GameState::LevelSelect), level_select::cleanup_level_select)
+        .add_systems(OnEnter(GameState::LevelResult), (cleanup_game, level_select::setup_level_result).chain())
+        .add_systems(Update, level_select::level_result_system.run_if(in_state(GameState::LevelResult)))
+        .add_systems(OnExit(GameState::LevelResult), level_select::cleanup_level_result)
+        .add_systems(OnEnter(GameState::TournamentSetup), tournament::setup_tournament_entry)
+        .add_systems(
+            Update,
+            (tournament::tournament_entry_system, update_cursor).run_if(in_state(GameState::TournamentSetup)),
+        )
+        .add_systems(OnExit(GameState::TournamentSetup), tournament::cleanup_tournament_ui)
+        .add_systems(
+            OnEnter(GameState::TournamentBracket),
+            (cleanup_game, tournament::finish_turn, tournament::setup_bracket).chain(),
+        )
+        .add_systems(Update, tournament::bracket_system.run_if(in_state(GameState::TournamentBracket)))
+        .add_systems(OnExit(GameState::TournamentBracket), tournament::cleanup_tournament_ui)
+        .add_systems(OnEnter(GameState::TournamentChampion), tournament::setup_champion)
+        .add_systems(Update, tournament::champion_system.run_if(in_state(GameState::TournamentChampion)))
+        .add_systems(OnExit(GameState::TournamentChampion), tournament::cleanup_tournament_ui)
+        // 和朋友比赛：大厅、局内进度同步和对手比分、结果
+        .add_systems(OnEnter(GameState::RaceLobby), race::setup_race_lobby)
+        .add_systems(
+            Update,
+            (race::race_lobby_system, race::race_lobby_responses).run_if(in_state(GameState::RaceLobby)),
+        )
+        .add_systems(OnExit(GameState::RaceLobby), race::cleanup_race_ui)
+        .add_systems(
+            OnEnter(GameState::Playing),
+            race::spawn_race_hud.after(setup_game_conditional).run_if(race::race_active.and_then(resume::full_setup)),
+        )
+        .add_systems(Update, (race::sync_race_progress.run_if(race::race_active), race::receive_race_progress))
+        .add_systems(
+            Update,
+            race::update_race_hud.run_if(in_state(GameState::Playing).and_then(race::race_active)),
+        )
+        .add_systems(
+            OnEnter(GameState::RaceResult),
+            (cleanup_game, race::finish_race, race::setup_race_result).chain(),
+        )
+        .add_systems(Update, race::race_result_system.run_if(in_state(GameState::RaceResult)))
+        .add_systems(OnExit(GameState::RaceResult), race::cleanup_race_ui)
+        .add_systems(OnEnter(GameState::MainMenu), race::forfeit_race)
+        // 玩家档案选择
+        .add_systems(OnEnter(GameState::ProfileSelect), profiles::setup_profile_picker)
+        .add_systems(Update, profiles::profile_picker_system.run_if(in_state(GameState::ProfileSelect)))
+        .add_systems(OnExit(GameState::ProfileSelect), profiles::cleanup_profile_picker)
+        // 更新日志
+        .add_systems(OnEnter(GameState::Changelog), changelog::setup_changelog_screen)
+        .add_systems(Update, changelog::changelog_screen_system.run_if(in_state(GameState::Changelog)))
+        .add_systems(OnExit(GameState::Changelog), changelog::cleanup_changelog_screen)
+        .run();
+}
 
-            parent.spawn(TextBundle::from_section(
-                "Controls:\nArrow Keys or A/D: Move paddle\nSPACE: Shoot laser (when available)\nESC: Pause game\nCollect power-ups for special abilities",
-                TextStyle {
-                    font_size: 20.0,
-                    color: Color::rgb(0.6, 0.6, 0.6),
-                    ..default()
-                },
-            ).with_style(Style {
-                margin: UiRect::top(Val::Px(80.0)),
-                ..default()
-            }));
-        });
+// 根据窗口高度计算自动 UI 缩放
+fn window_auto_scale(windows: &Query<&Window, With<PrimaryWindow>>) -> f32 {
+    windows
+        .get_single()
+        .map(|window| auto_ui_scale(window.height(), WINDOW_HEIGHT))
+        .unwrap_or(1.0)
 }
 
-// 主菜单系统
-fn main_menu_system(
-    keyboard_input: Res<ButtonInput<KeyCode>>,
-    mut next_state: ResMut<NextState<GameState>>,
+// 应用 UI 缩放（设置变化时实时生效）
+fn apply_ui_scale(
+    settings: Res<Settings>,
+    windows: Query<&Window, With<PrimaryWindow>>,
+    mut ui_scale: ResMut<UiScale>,
 ) {
-    if keyboard_input.just_pressed(KeyCode::Space) {
-        next_state.set(GameState::EnterName);  // 先输入名称
-    } else if keyboard_input.just_pressed(KeyCode::KeyL) {
-        next_state.set(GameState::Leaderboard);  // 查看排行榜
+    let scale = settings.effective_ui_scale(window_auto_scale(&windows));
+    if (ui_scale.0 - scale).abs() > f32::EPSILON {
+        ui_scale.0 = scale;
     }
 }
 
-// 清理主菜单
-fn cleanup_main_menu(mut commands: Commands, query: Query<Entity, With<MainMenuUI>>) {
-    for entity in query.iter() {
-        commands.entity(entity).despawn_recursive();
-    }
-}
-// 设置难度选择菜单
-fn setup_difficulty_menu(mut commands: Commands) {
+// 设置主菜单
+fn setup_main_menu(
+    mut commands: Commands,
+    mut game_initialized: ResMut<GameInitialized>,
+    fonts: Res<FontAssets>,
+    locale: Res<Locale>,
+    registry: Res<ProfileRegistry>,
+    paths: Res<ProfilePaths>,
+    theme: Res<SeasonalTheme>,
+) {
+    let unseen_changes = changelog::has_unseen_changes(Profile::load(&paths).last_seen_version.as_deref());
+
+    game_initialized.0 = false;
+    commands.spawn(Camera2dBundle::default());
+
     commands
         .spawn((
             NodeBundle {
@@ -486,88 +1815,486 @@ fn setup_difficulty_menu(mut commands: Commands) {
                 background_color: BackgroundColor(Color::rgb(0.1, 0.1, 0.15)),
                 ..default()
             },
-            DifficultyUI,
+            MainMenuUI,
         ))
         .with_children(|parent| {
-            parent.spawn(TextBundle::from_section(
-                "SELECT DIFFICULTY",
-                TextStyle {
-                    font_size: 60.0,
-                    color: Color::WHITE,
+            // 节日期间标题上方的装饰
+            if let Some(holiday) = theme.holiday {
+                parent.spawn(TextBundle::from_section(
+                    locale.tr(holiday.title_key(), &[]),
+                    text_style(&fonts, FontKind::Bold, 26.0, holiday.title_color()),
+                ).with_style(Style {
+                    margin: UiRect::bottom(Val::Px(6.0)),
                     ..default()
-                },
+                }));
+            }
+
+            parent.spawn(TextBundle::from_section(
+                locale.tr("menu.title", &[]),
+                text_style(&fonts, FontKind::Bold, 64.0, Color::WHITE),
             ));
+
+            if let Some(profile) = registry.active_profile() {
+                parent.spawn(TextBundle::from_section(
+                    locale.tr("menu.playing_as", &[("name", &profile.name)]),
+                    text_style(&fonts, FontKind::Regular, 24.0, profile.avatar_color()),
+                ).with_style(Style {
+                    margin: UiRect::top(Val::Px(10.0)),
+                    ..default()
+                }));
+            }
+
+            // 版本更新后提示查看更新日志
+            if unseen_changes {
+                parent
+                    .spawn(NodeBundle {
+                        style: Style {
+                            margin: UiRect::top(Val::Px(15.0)),
+                            padding: UiRect::axes(Val::Px(12.0), Val::Px(4.0)),
+                            ..default()
+                        },
+                        background_color: BackgroundColor(Color::rgb(0.8, 0.5, 0.1)),
+                        ..default()
+                    })
+                    .with_children(|parent| {
+                        parent.spawn(TextBundle::from_section(
+                            locale.tr("menu.whats_new", &[("version", &changelog::CURRENT_VERSION)]),
+                            text_style(&fonts, FontKind::Bold, 20.0, Color::WHITE),
+                        ));
+                    });
+            }
+
+            // 本周活动，服务器返回后才有内容
+            weekly_event::spawn_event_banner(parent, &fonts);
             
             parent.spawn(TextBundle::from_section(
-                "[1] EASY - 5 Lives, Slower Ball, Lives Reset Each Level",
-                TextStyle {
-                    font_size: 25.0,
-                    color: Color::rgb(0.2, 0.8, 0.2),
-                    ..default()
-                },
+                locale.tr("menu.start", &[]),
+                text_style(&fonts, FontKind::Regular, 30.0, Color::rgb(0.7, 0.7, 0.7)),
             ).with_style(Style {
                 margin: UiRect::top(Val::Px(50.0)),
                 ..default()
             }));
 
             parent.spawn(TextBundle::from_section(
-                "[2] MEDIUM - 3 Lives, Normal Ball, Faster Paddle",
-                TextStyle {
-                    font_size: 25.0,
-                    color: Color::rgb(0.8, 0.8, 0.2),
-                    ..default()
-                },
+                locale.tr("menu.leaderboard", &[]),
+                text_style(&fonts, FontKind::Regular, 25.0, Color::rgb(0.5, 0.7, 0.9)),
             ).with_style(Style {
                 margin: UiRect::top(Val::Px(20.0)),
                 ..default()
             }));
 
             parent.spawn(TextBundle::from_section(
-                "[3] HARD - 3 Lives, Very Fast Ball & Paddle, Time Limit",
-                TextStyle {
-                    font_size: 25.0,
-                    color: Color::rgb(0.8, 0.2, 0.2),
+                locale.tr("menu.settings", &[]),
+                text_style(&fonts, FontKind::Regular, 25.0, Color::rgb(0.5, 0.7, 0.9)),
+            ).with_style(Style {
+                margin: UiRect::top(Val::Px(10.0)),
+                ..default()
+            }));
+
+            parent.spawn(TextBundle::from_section(
+                locale.tr("menu.profile", &[]),
+                text_style(&fonts, FontKind::Regular, 25.0, Color::rgb(0.5, 0.7, 0.9)),
+            ).with_style(Style {
+                margin: UiRect::top(Val::Px(10.0)),
+                ..default()
+            }));
+
+            parent.spawn(TextBundle::from_section(
+                locale.tr("menu.change_name", &[]),
+                text_style(&fonts, FontKind::Regular, 25.0, Color::rgb(0.5, 0.7, 0.9)),
+            ).with_style(Style {
+                margin: UiRect::top(Val::Px(10.0)),
+                ..default()
+            }));
+
+            parent.spawn((
+                TextBundle::from_section(
+                    locale.tr("menu.daily", &[]),
+                    text_style(&fonts, FontKind::Regular, 25.0, Color::rgb(1.0, 0.85, 0.2)),
+                ).with_style(Style {
+                    margin: UiRect::top(Val::Px(10.0)),
                     ..default()
-                },
+                }),
+                FlagGated(FLAG_DAILY_CHALLENGE),
+            ));
+
+            parent.spawn(TextBundle::from_section(
+                locale.tr("menu.level_select", &[]),
+                text_style(&fonts, FontKind::Regular, 25.0, Color::rgb(0.8, 0.5, 0.2)),
             ).with_style(Style {
-                margin: UiRect::top(Val::Px(20.0)),
+                margin: UiRect::top(Val::Px(10.0)),
                 ..default()
             }));
 
             parent.spawn(TextBundle::from_section(
-                "Press 1, 2, or 3 to select",
-                TextStyle {
-                    font_size: 20.0,
-                    color: Color::rgb(0.6, 0.6, 0.6),
+                locale.tr("menu.tournament", &[]),
+                text_style(&fonts, FontKind::Regular, 25.0, Color::rgb(0.2, 0.8, 0.8)),
+            ).with_style(Style {
+                margin: UiRect::top(Val::Px(10.0)),
+                ..default()
+            }));
+
+            parent.spawn((
+                TextBundle::from_section(
+                    locale.tr("menu.race", &[]),
+                    text_style(&fonts, FontKind::Regular, 25.0, Color::rgb(1.0, 0.55, 0.2)),
+                ).with_style(Style {
+                    margin: UiRect::top(Val::Px(10.0)),
                     ..default()
-                },
+                }),
+                FlagGated(FLAG_RACES),
+            ));
+
+            parent.spawn(TextBundle::from_section(
+                locale.tr("menu.controls", &[]),
+                text_style(&fonts, FontKind::Regular, 20.0, Color::rgb(0.6, 0.6, 0.6)),
             ).with_style(Style {
-                margin: UiRect::top(Val::Px(50.0)),
+                margin: UiRect::top(Val::Px(40.0)),
+                ..default()
+            }));
+
+            // 右下角的版本号，反馈问题时可以直接看到对应的提交
+            parent.spawn(TextBundle::from_section(
+                release::version_line(),
+                text_style(&fonts, FontKind::Mono, 14.0, Color::rgb(0.4, 0.4, 0.45)),
+            ).with_style(Style {
+                position_type: PositionType::Absolute,
+                right: Val::Px(10.0),
+                bottom: Val::Px(8.0),
                 ..default()
             }));
         });
 }
 
-// 难度选择系统
-fn difficulty_menu_system(
+// 主菜单系统
+fn main_menu_system(
     keyboard_input: Res<ButtonInput<KeyCode>>,
+    gamepad: GamepadButtons,
+    registry: Res<ProfileRegistry>,
+    current_event: Res<CurrentEvent>,
+    remote_flags: Res<RemoteFlags>,
+    paths: Res<ProfilePaths>,
+    mut tournament: ResMut<Tournament>,
     mut next_state: ResMut<NextState<GameState>>,
-    mut difficulty_settings: ResMut<DifficultySettings>,
-    mut lives: ResMut<Lives>,
+    mut run: RunResources,
 ) {
-    if keyboard_input.just_pressed(KeyCode::Digit1) || keyboard_input.just_pressed(KeyCode::Numpad1) {
-        *difficulty_settings = DifficultySettings::new(Difficulty::Easy);
-        lives.0 = difficulty_settings.lives;
-        next_state.set(GameState::Playing);
-    } else if keyboard_input.just_pressed(KeyCode::Digit2) || keyboard_input.just_pressed(KeyCode::Numpad2) {
-        *difficulty_settings = DifficultySettings::new(Difficulty::Medium);
-        lives.0 = difficulty_settings.lives;
-        next_state.set(GameState::Playing);
-    } else if keyboard_input.just_pressed(KeyCode::Digit3) || keyboard_input.just_pressed(KeyCode::Numpad3) {
-        *difficulty_settings = DifficultySettings::new(Difficulty::Hard);
-        lives.0 = difficulty_settings.lives;
-        next_state.set(GameState::Playing);
-    }
+    // 手柄：A/START 开始，Y 排行榜，X 设置，BACK 档案，十字键左右分别是单关挑战和每日挑战，上是本周活动
+    let start = keyboard_input.just_pressed(KeyCode::Space)
+        || gamepad.just_pressed(GamepadButtonType::South)
+        || gamepad.just_pressed(GamepadButtonType::Start);
+    if start {
+        // 有档案时直接使用档案名称，否则先输入名称
+        if registry.active_profile().is_some() {
+            next_state.set(GameState::DifficultySelect);
+        } else {
+            next_state.set(GameState::EnterName);
+        }
+    } else if keyboard_input.just_pressed(KeyCode::KeyN) {
+        next_state.set(GameState::EnterName);  // 修改玩家名称
+    } else if keyboard_input.just_pressed(KeyCode::KeyW) {
+        next_state.set(GameState::Changelog);  // 查看更新日志
+    } else if keyboard_input.just_pressed(KeyCode::KeyL) || gamepad.just_pressed(GamepadButtonType::North) {
+        next_state.set(GameState::Leaderboard);  // 查看排行榜
+    } else if keyboard_input.just_pressed(KeyCode::KeyS) || gamepad.just_pressed(GamepadButtonType::West) {
+        next_state.set(GameState::Settings);  // 打开设置
+    } else if keyboard_input.just_pressed(KeyCode::KeyP) || gamepad.just_pressed(GamepadButtonType::Select) {
+        next_state.set(GameState::Profile);  // 查看本地档案
+    } else if (keyboard_input.just_pressed(KeyCode::KeyD) || gamepad.just_pressed(GamepadButtonType::DPadRight))
+        && remote_flags.enabled(FLAG_DAILY_CHALLENGE)
+    {
+        // 每日挑战同样需要名称才能上榜
+        if registry.active_profile().is_some() {
+            next_state.set(GameState::DailyChallenge);
+        } else {
+            next_state.set(GameState::EnterName);
+        }
+    } else if keyboard_input.just_pressed(KeyCode::KeyG) || gamepad.just_pressed(GamepadButtonType::DPadLeft) {
+        // 单关挑战的成绩同样要上榜
+        if registry.active_profile().is_some() {
+            next_state.set(GameState::LevelSelect);
+        } else {
+            next_state.set(GameState::EnterName);
+        }
+    } else if keyboard_input.just_pressed(KeyCode::KeyT) {
+        // 本地淘汰赛，选手名字在比赛开始前输入，不需要档案
+        next_state.set(tournament::open_tournament(&paths, &mut tournament));
+    } else if keyboard_input.just_pressed(KeyCode::KeyR) && remote_flags.enabled(FLAG_RACES) {
+        // 和朋友比赛，服务器按名字区分双方
+        if registry.active_profile().is_some() {
+            next_state.set(GameState::RaceLobby);
+        } else {
+            next_state.set(GameState::EnterName);
+        }
+    } else if (keyboard_input.just_pressed(KeyCode::KeyE) || gamepad.just_pressed(GamepadButtonType::DPadUp))
+        && current_event.0.is_some()
+        && remote_flags.enabled(FLAG_WEEKLY_EVENT)
+    {
+        // 本周活动，同样需要名称才能上榜
+        if registry.active_profile().is_none() {
+            next_state.set(GameState::EnterName);
+        } else if weekly_event::start_event_run(&mut run, &current_event) {
+            next_state.set(GameState::Playing);
+        }
+    }
+}
+
+// 清理主菜单
+fn cleanup_main_menu(mut commands: Commands, query: Query<Entity, With<MainMenuUI>>) {
+    for entity in query.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+// 设置难度选择菜单
+fn setup_difficulty_menu(
+    mut commands: Commands,
+    fonts: Res<FontAssets>,
+    locale: Res<Locale>,
+    settings: Res<Settings>,
+    paths: Res<ProfilePaths>,
+    mut menu_state: ResMut<DifficultyMenuState>,
+) {
+    // 默认选中上一局使用的难度
+    menu_state.selected = Difficulty::ALL
+        .iter()
+        .position(|difficulty| *difficulty == settings.last_difficulty)
+        .unwrap_or(1);
+    menu_state.coins = Profile::load(&paths).coins;
+    menu_state.insurance = menu_state.insurance.min(insurance::affordable(menu_state.coins));
+
+    // 说明文字和数值条都来自平衡性报告，与 --balance-report 的输出一致
+    let report = BalanceReport::current();
+
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    width: Val::Percent(100.0),
+                    height: Val::Percent(100.0),
+                    flex_direction: FlexDirection::Column,
+                    justify_content: JustifyContent::Center,
+                    align_items: AlignItems::Center,
+                    ..default()
+                },
+                background_color: BackgroundColor(Color::rgb(0.1, 0.1, 0.15)),
+                ..default()
+            },
+            DifficultyUI,
+        ))
+        .with_children(|parent| {
+            parent.spawn(TextBundle::from_section(
+                locale.tr("difficulty.title", &[]),
+                text_style(&fonts, FontKind::Bold, 60.0, Color::WHITE),
+            ));
+
+            for (index, balance) in report.difficulties.iter().enumerate() {
+                let difficulty = balance.difficulty;
+                parent
+                    .spawn((
+                        ButtonBundle {
+                            style: Style {
+                                width: Val::Percent(70.0),
+                                margin: UiRect::top(Val::Px(if index == 0 { 30.0 } else { 10.0 })),
+                                padding: UiRect::all(Val::Px(8.0)),
+                                border: UiRect::all(Val::Px(2.0)),
+                                justify_content: JustifyContent::SpaceBetween,
+                                align_items: AlignItems::Center,
+                                ..default()
+                            },
+                            background_color: BackgroundColor(Color::rgba(0.2, 0.2, 0.25, 0.8)),
+                            border_color: BorderColor(Color::NONE),
+                            ..default()
+                        },
+                        DifficultyOption(index),
+                    ))
+                    .with_children(|parent| {
+                        // 左侧：名称与说明
+                        parent
+                            .spawn(NodeBundle {
+                                style: Style {
+                                    flex_direction: FlexDirection::Column,
+                                    width: Val::Percent(45.0),
+                                    ..default()
+                                },
+                                ..default()
+                            })
+                            .with_children(|parent| {
+                                parent.spawn(TextBundle::from_section(
+                                    format!("[{}] {}", index + 1, locale.tr(&name_key("difficulty", difficulty.name()), &[]).to_uppercase()),
+                                    text_style(&fonts, FontKind::Regular, 25.0, difficulty.color()),
+                                ));
+                                parent.spawn(TextBundle::from_section(
+                                    difficulty_summary(balance, &locale),
+                                    text_style(&fonts, FontKind::Regular, 14.0, Color::rgb(0.6, 0.6, 0.6)),
+                                ));
+                            });
+
+                        // 右侧：数值条
+                        parent
+                            .spawn(NodeBundle {
+                                style: Style {
+                                    flex_direction: FlexDirection::Column,
+                                    width: Val::Percent(50.0),
+                                    ..default()
+                                },
+                                ..default()
+                            })
+                            .with_children(|parent| {
+                                for (label_key, fraction) in report.stat_bars(balance) {
+                                    spawn_stat_bar(parent, &fonts, &locale.tr(label_key, &[]), fraction, difficulty.color());
+                                }
+                            });
+                    });
+            }
+
+            insurance::spawn_offer_text(parent, &fonts, &locale, menu_state.insurance, menu_state.coins);
+
+            parent.spawn(TextBundle::from_section(
+                locale.tr("difficulty.hint", &[]),
+                text_style(&fonts, FontKind::Regular, 20.0, Color::rgb(0.6, 0.6, 0.6)),
+            ).with_style(Style {
+                margin: UiRect::top(Val::Px(30.0)),
+                ..default()
+            }));
+        });
+}
+
+// 根据实际难度参数生成说明文字
+fn difficulty_summary(balance: &DifficultyBalance, locale: &Locale) -> String {
+    let mut parts = vec![
+        locale.tr("difficulty.summary_lives", &[("lives", &balance.lives)]),
+        locale.tr("difficulty.summary_ball", &[("speed", &format!("{:.0}", balance.ball_speed))]),
+        locale.tr("difficulty.summary_paddle", &[("speed", &format!("{:.0}", balance.paddle_speed))]),
+    ];
+    if balance.reset_lives_on_level {
+        parts.push(locale.tr("difficulty.summary_reset_lives", &[]));
+    }
+    if let Some(time_limit) = balance.time_limit {
+        parts.push(locale.tr("difficulty.summary_time_limit", &[("seconds", &(time_limit as u32))]));
+    }
+    parts.join(&locale.tr("list.separator", &[]))
+}
+
+// 生成一行数值条
+fn spawn_stat_bar(parent: &mut ChildBuilder, fonts: &FontAssets, label: &str, fraction: f32, color: Color) {
+    parent
+        .spawn(NodeBundle {
+            style: Style {
+                align_items: AlignItems::Center,
+                margin: UiRect::vertical(Val::Px(2.0)),
+                ..default()
+            },
+            ..default()
+        })
+        .with_children(|parent| {
+            parent.spawn(TextBundle::from_section(
+                label,
+                text_style(fonts, FontKind::Regular, 14.0, Color::rgb(0.7, 0.7, 0.7)),
+            ).with_style(Style {
+                width: Val::Percent(30.0),
+                ..default()
+            }));
+
+            parent
+                .spawn(NodeBundle {
+                    style: Style {
+                        width: Val::Percent(70.0),
+                        height: Val::Px(8.0),
+                        ..default()
+                    },
+                    background_color: BackgroundColor(Color::rgb(0.15, 0.15, 0.2)),
+                    ..default()
+                })
+                .with_children(|parent| {
+                    parent.spawn(NodeBundle {
+                        style: Style {
+                            width: Val::Percent(fraction.clamp(0.0, 1.0) * 100.0),
+                            height: Val::Percent(100.0),
+                            ..default()
+                        },
+                        background_color: BackgroundColor(color),
+                        ..default()
+                    });
+                });
+        });
+}
+
+// 难度选择系统
+fn difficulty_menu_system(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    mut next_state: ResMut<NextState<GameState>>,
+    mut run: RunResources,
+    mut settings: ResMut<Settings>,
+    paths: Res<ProfilePaths>,
+    mut menu_state: ResMut<DifficultyMenuState>,
+    mut option_query: Query<(Ref<Interaction>, &DifficultyOption, &mut BorderColor)>,
+    mut offer_query: Query<&mut Text, With<InsuranceOfferText>>,
+    locale: Res<Locale>,
+    gamepad: GamepadButtons,
+) {
+    let option_count = Difficulty::ALL.len();
+    let mut chosen = None;
+
+    // 手柄：十字键上下选择，A 确认，左右调整保险代币
+    if keyboard_input.just_pressed(KeyCode::ArrowUp) || gamepad.just_pressed(GamepadButtonType::DPadUp) {
+        menu_state.selected = (menu_state.selected + option_count - 1) % option_count;
+    } else if keyboard_input.just_pressed(KeyCode::ArrowDown) || gamepad.just_pressed(GamepadButtonType::DPadDown) {
+        menu_state.selected = (menu_state.selected + 1) % option_count;
+    } else if keyboard_input.just_pressed(KeyCode::Enter) || gamepad.just_pressed(GamepadButtonType::South) {
+        chosen = Some(menu_state.selected);
+    }
+
+    // 左右键调整要购买的保险代币数，不能超过钱包买得起的数量
+    let insurance = menu_state.insurance;
+    if keyboard_input.just_pressed(KeyCode::ArrowLeft) || gamepad.just_pressed(GamepadButtonType::DPadLeft) {
+        menu_state.insurance = insurance.saturating_sub(1);
+    } else if keyboard_input.just_pressed(KeyCode::ArrowRight) || gamepad.just_pressed(GamepadButtonType::DPadRight) {
+        menu_state.insurance = (insurance + 1).min(insurance::affordable(menu_state.coins));
+    }
+    if menu_state.insurance != insurance {
+        for mut text in offer_query.iter_mut() {
+            text.sections[0].value = insurance::offer_label(&locale, menu_state.insurance, menu_state.coins);
+        }
+    }
+
+    let number_keys = [
+        (KeyCode::Digit1, KeyCode::Numpad1),
+        (KeyCode::Digit2, KeyCode::Numpad2),
+        (KeyCode::Digit3, KeyCode::Numpad3),
+    ];
+    for (index, (digit, numpad)) in number_keys.iter().enumerate() {
+        if keyboard_input.just_pressed(*digit) || keyboard_input.just_pressed(*numpad) {
+            chosen = Some(index);
+        }
+    }
+
+    // 鼠标悬停高亮，点击选择；悬停只在刚移上去时改选择，停着不动的鼠标不会抢走键盘和手柄的选择
+    for (interaction, option, _) in option_query.iter() {
+        match *interaction {
+            Interaction::Pressed => chosen = Some(option.0),
+            Interaction::Hovered if interaction.is_changed() => menu_state.selected = option.0,
+            _ => {}
+        }
+    }
+
+    for (_, option, mut border_color) in option_query.iter_mut() {
+        border_color.0 = if option.0 == menu_state.selected {
+            Difficulty::ALL[option.0].color()
+        } else {
+            Color::NONE
+        };
+    }
+
+    if let Some(index) = chosen {
+        let difficulty = Difficulty::ALL[index];
+        *run.difficulty_settings = DifficultySettings::new(difficulty);
+        settings.last_difficulty = difficulty;
+        settings.save(&paths);
+        start_new_run(&mut run, None);
+        let bought = insurance::purchase(&paths, menu_state.insurance);
+        run.insurance.0 = bought;
+        run.stats.insurance_bought = bought;
+        next_state.set(GameState::Playing);
+    }
 }
 
 // 清理难度选择菜单
@@ -578,10 +2305,29 @@ fn cleanup_difficulty_menu(mut commands: Commands, query: Query<Entity, With<Dif
 }
 
 // 设置输入名称界面
-fn setup_enter_name(mut commands: Commands, mut name_input: ResMut<NameInput>) {
-    name_input.text.clear();
+fn setup_enter_name(
+    mut commands: Commands,
+    mut name_input: ResMut<NameInput>,
+    mut arcade_entry: ResMut<ArcadeNameEntry>,
+    mut entry_mode: ResMut<NameEntryMode>,
+    player_name: Res<PlayerName>,
+    settings: Res<Settings>,
+    last_input: Res<LastInputDevice>,
+    kiosk: Res<KioskConfig>,
+    fonts: Res<FontAssets>,
+    locale: Res<Locale>,
+) {
+    name_input.text = player_name.0.clone();
     name_input.cursor_visible = true;
     name_input.cursor_timer = 0.0;
+
+    // 键盘默认使用文本输入；设置开启、最近使用手柄或展台模式时改用街机式输入
+    *entry_mode = if kiosk.enabled || settings.arcade_name_entry || *last_input == LastInputDevice::Gamepad {
+        NameEntryMode::Arcade
+    } else {
+        NameEntryMode::Text
+    };
+    *arcade_entry = ArcadeNameEntry::from_name(&player_name.0);
     
     commands
         .spawn((
@@ -601,14 +2347,30 @@ fn setup_enter_name(mut commands: Commands, mut name_input: ResMut<NameInput>) {
         ))
         .with_children(|parent| {
             parent.spawn(TextBundle::from_section(
-                "ENTER YOUR NAME",
-                TextStyle {
-                    font_size: 60.0,
-                    color: Color::WHITE,
-                    ..default()
-                },
+                locale.tr("name.title", &[]),
+                text_style(&fonts, FontKind::Bold, 60.0, Color::WHITE),
             ));
             
+            if *entry_mode == NameEntryMode::Arcade {
+                arcade_name::spawn_initial_slots(parent, &fonts);
+                spawn_anonymous_name_hint(parent, &fonts, &locale, &settings);
+                parent.spawn(TextBundle::from_section(
+                    locale.tr("name.arcade_hint", &[]),
+                    text_style(&fonts, FontKind::Regular, 20.0, Color::rgb(0.6, 0.6, 0.6)),
+                ).with_style(Style {
+                    margin: UiRect::top(Val::Px(30.0)),
+                    ..default()
+                }));
+                parent.spawn(TextBundle::from_section(
+                    locale.tr("name.skip", &[]),
+                    text_style(&fonts, FontKind::Regular, 18.0, Color::rgb(0.5, 0.5, 0.5)),
+                ).with_style(Style {
+                    margin: UiRect::top(Val::Px(10.0)),
+                    ..default()
+                }));
+                return;
+            }
+
             // 名称输入框
             parent
                 .spawn(NodeBundle {
@@ -630,35 +2392,24 @@ fn setup_enter_name(mut commands: Commands, mut name_input: ResMut<NameInput>) {
                     parent.spawn((
                         TextBundle::from_section(
                             "",
-                            TextStyle {
-                                font_size: 40.0,
-                                color: Color::WHITE,
-                                ..default()
-                            },
+                            text_style(&fonts, FontKind::Bold, 40.0, Color::WHITE),
                         ),
                         NameInputText,
                     ));
                 });
+            spawn_anonymous_name_hint(parent, &fonts, &locale, &settings);
             
             parent.spawn(TextBundle::from_section(
-                "Type your name and press ENTER",
-                TextStyle {
-                    font_size: 20.0,
-                    color: Color::rgb(0.6, 0.6, 0.6),
-                    ..default()
-                },
+                locale.tr("name.hint", &[]),
+                text_style(&fonts, FontKind::Regular, 20.0, Color::rgb(0.6, 0.6, 0.6)),
             ).with_style(Style {
                 margin: UiRect::top(Val::Px(30.0)),
                 ..default()
             }));
             
             parent.spawn(TextBundle::from_section(
-                "Press ESC to skip",
-                TextStyle {
-                    font_size: 18.0,
-                    color: Color::rgb(0.5, 0.5, 0.5),
-                    ..default()
-                },
+                locale.tr("name.skip", &[]),
+                text_style(&fonts, FontKind::Regular, 18.0, Color::rgb(0.5, 0.5, 0.5)),
             ).with_style(Style {
                 margin: UiRect::top(Val::Px(10.0)),
                 ..default()
@@ -666,6 +2417,20 @@ fn setup_enter_name(mut commands: Commands, mut name_input: ResMut<NameInput>) {
         });
 }
 
+// 匿名提交时在输入框下方显示榜单上实际使用的代号
+fn spawn_anonymous_name_hint(parent: &mut ChildBuilder, fonts: &FontAssets, locale: &Locale, settings: &Settings) {
+    if settings.score_privacy != ScorePrivacy::Anonymous {
+        return;
+    }
+    parent.spawn(TextBundle::from_section(
+        locale.tr("name.anonymous_as", &[("handle", &settings.public_name(""))]),
+        text_style(fonts, FontKind::Regular, 20.0, Color::rgb(0.5, 0.7, 0.9)),
+    ).with_style(Style {
+        margin: UiRect::top(Val::Px(15.0)),
+        ..default()
+    }));
+}
+
 // 处理名称输入
 fn enter_name_system(
     mut char_events: EventReader<ReceivedCharacter>,
@@ -679,10 +2444,8 @@ fn enter_name_system(
     for event in char_events.read() {
         // 将 SmolStr 转换为 char
         if let Some(ch) = event.char.as_str().chars().next() {
-            if ch.is_alphanumeric() || ch == ' ' {
-                if name_input.text.len() < 20 {
-                    name_input.text.push(ch);
-                }
+            if is_name_char(ch) && name_input.text.chars().count() < MAX_NAME_LENGTH {
+                name_input.text.push(ch);
             }
         }
     }
@@ -692,9 +2455,11 @@ fn enter_name_system(
         name_input.text.pop();
     }
     
-    if keyboard.just_pressed(KeyCode::Enter) && !name_input.text.trim().is_empty() {
-        player_name.0 = name_input.text.trim().to_string();
-        next_state.set(GameState::DifficultySelect);
+    if keyboard.just_pressed(KeyCode::Enter) {
+        if let Some(name) = normalize_player_name(&name_input.text) {
+            player_name.0 = name;
+            next_state.set(GameState::DifficultySelect);
+        }
     }
     
     if keyboard.just_pressed(KeyCode::Escape) {
@@ -742,10 +2507,34 @@ fn setup_game_conditional(
     level_timer: ResMut<LevelTimer>,
     level: Res<Level>,
     difficulty_settings: Res<DifficultySettings>,
+    run_seed: Res<RunSeed>,
+    settings: Res<Settings>,
+    fonts: Res<FontAssets>,
+    brick_log: ResMut<BrickDestructionLog>,
+    paths: Res<ProfilePaths>,
+    play_clock: ResMut<PlayClock>,
+    cosmetics: Res<Cosmetics>,
+    stage: (
+        Res<Orientation>,
+        Res<BonusStage>,
+        Res<BossStage>,
+        Res<EventRun>,
+        ResMut<LevelAttempt>,
+        ResMut<GeneratorChain>,
+        Res<SeasonalTheme>,
+        Res<RunStats>,
+        Res<Locale>,
+        Res<Curse>,
+        ResMut<BrickQuota>,
+    ),
+    game_rng: ResMut<GameRng>,
     mut game_initialized: ResMut<GameInitialized>,
 ) {
     if !game_initialized.0 {
-        setup_game(commands, score, lives, level_timer, level, difficulty_settings);
+        setup_game(
+            commands, score, lives, level_timer, level, difficulty_settings, run_seed, settings, fonts, brick_log, paths,
+            play_clock, cosmetics, stage, game_rng,
+        );
         game_initialized.0 = true;
     }
 }
@@ -758,7 +2547,33 @@ fn setup_game(
     mut level_timer: ResMut<LevelTimer>,
     level: Res<Level>,
     difficulty_settings: Res<DifficultySettings>,
+    run_seed: Res<RunSeed>,
+    settings: Res<Settings>,
+    fonts: Res<FontAssets>,
+    mut brick_log: ResMut<BrickDestructionLog>,
+    paths: Res<ProfilePaths>,
+    mut play_clock: ResMut<PlayClock>,
+    cosmetics: Res<Cosmetics>,
+    (orientation, bonus, boss_stage, event_run, mut attempt, mut chain, theme, stats, locale, curse, mut quota): (
+        Res<Orientation>,
+        Res<BonusStage>,
+        Res<BossStage>,
+        Res<EventRun>,
+        ResMut<LevelAttempt>,
+        ResMut<GeneratorChain>,
+        Res<SeasonalTheme>,
+        Res<RunStats>,
+        Res<Locale>,
+        Res<Curse>,
+        ResMut<BrickQuota>,
+    ),
+    mut game_rng: ResMut<GameRng>,
 ) {
+    // 新关卡的格子重新开始记录，局内随机数重新播种
+    brick_log.clear();
+    play_clock.start_level();
+    game_rng.reseed(run_seed.0, level.0);
+
     // 重置分数和生命（新游戏时）
     if level.0 == 1 {
         score.0 = 0;
@@ -767,225 +2582,454 @@ fn setup_game(
         // Easy模式下每关重置生命
         lives.0 = difficulty_settings.lives;
     }
+    attempt.begin(score.0);
 
-    // 重置计时器
-    if let Some(time_limit) = difficulty_settings.time_limit {
-        level_timer.0 = time_limit;
+    // 重置计时器（困难模式或限时诅咒）
+    let time_limit = curse.time_limit(difficulty_settings.time_limit);
+    if let Some(time_limit) = time_limit {
+        level_timer.0.restart(time_limit);
     }
 
     // 创建相机
     commands.spawn((Camera2dBundle::default(), GameEntity));
 
-    // 创建挡板
-    commands.spawn((
-        SpriteBundle {
-            sprite: Sprite {
-                color: PADDLE_COLOR,
-                ..default()
-            },
-            transform: Transform {
-                translation: Vec3::new(0.0, PADDLE_Y, 0.0),
-                scale: Vec3::new(PADDLE_SIZE.x, PADDLE_SIZE.y, 1.0),
-                ..default()
-            },
-            ..default()
-        },
-        Paddle,
-        GameEntity,
-    ));
+    // 挡板由 PaddlePlugin 在这之后生成
 
-    // 创建球
-    let mut rng = rand::thread_rng();
-    let ball_direction = Vec2::new(
-        if rng.gen_bool(0.5) { 1.0 } else { -1.0 },
-        1.0,
-    ).normalize();
+    // 简单难度的底部减速带
+    if difficulty_settings.slow_zone {
+        spawn_slow_zone(&mut commands, *orientation);
+    }
 
-    commands.spawn((
+    // 创建球，先停在挡板上等待发射
+    let mut ball = commands.spawn((
         SpriteBundle {
             sprite: Sprite {
-                color: BALL_COLOR,
+                color: cosmetics.ball.base,
                 ..default()
             },
             transform: Transform {
-                translation: Vec3::new(0.0, -200.0, 0.0),
+                translation: orientation.apply(Vec2::new(0.0, -200.0)).extend(0.0),
                 scale: Vec3::new(BALL_SIZE.x, BALL_SIZE.y, 1.0),
                 ..default()
             },
             ..default()
         },
-        Ball {
-            velocity: ball_direction * BALL_SPEED * difficulty_settings.ball_speed_modifier,
-        },
+        Ball { velocity: Vec2::ZERO },
+        HeldBall::random(&mut *game_rng),
         GameEntity,
     ));
-
-    // 创建砖块
-    spawn_bricks(&mut commands, level.0);
+    cosmetics.ball.decorate(&mut ball);
+
+    // 创建砖块；奖励关使用固定布局，没有幽灵挡板
+    if bonus.is_active() {
+        let layout = bonus::bonus_layout();
+        *chain = GeneratorChain::from_layout(&layout);
+        *quota = BrickQuota::from_layout(&layout);
+        spawn_bricks(&mut commands, &layout, *orientation, &event_run, &theme);
+        bonus::spawn_bonus_hud(&mut commands, &fonts, *orientation);
+    } else if boss_stage.is_active() {
+        // 首领关没有砖块墙，砖块由首领陆续补上
+        *chain = GeneratorChain::from_layout(&Layout::default());
+        *quota = BrickQuota::from_layout(&Layout::default());
+        boss::spawn_boss(&mut commands, &fonts, &locale);
+    } else {
+        // 幽灵挡板（仅在种子与个人最佳一致时显示）
+        replay::spawn_ghost_paddle(&mut commands, &paths, &settings, &difficulty_settings, &run_seed, level.0);
+        let mut layout = generate_layout(level.0, run_seed.0);
+        event_run.adjust_drops(&mut layout.bricks);
+        *chain = GeneratorChain::from_layout(&layout);
+        *quota = BrickQuota::from_layout(&layout);
+        spawn_bricks(&mut commands, &layout, *orientation, &event_run, &theme);
+        // 护盾发生器关的提示箭头和进度
+        if chain.is_active() {
+            shield::spawn_generator_arrow(&mut commands);
+            shield::spawn_shield_hud(&mut commands, &fonts);
+        }
+        // 配额关的进度
+        if quota.is_active() {
+            quota::spawn_quota_hud(&mut commands, &fonts);
+        }
+    }
 
     // UI
-    setup_ui(&mut commands, &difficulty_settings);
+    setup_ui(&mut commands, &fonts, time_limit.is_some(), *orientation);
+    insurance::spawn_insurance_hud(&mut commands, stats.insurance_bought);
+    curse::spawn_curse_badge(&mut commands, &fonts, &locale, &curse);
+    if event_run.team_colors() {
+        team_colors::spawn_team_banner(&mut commands, &fonts);
+    }
 }
 
-// 生成砖块
-fn spawn_bricks(commands: &mut Commands, level: u32) {
-    let mut rng = rand::thread_rng();
-    let total_width = BRICK_COLUMNS as f32 * (BRICK_SIZE.x + GAP_SIZE) - GAP_SIZE;
-    let start_x = -total_width / 2.0 + BRICK_SIZE.x / 2.0;
-    let start_y = 200.0;
+// 关卡布局中的一块砖
+#[derive(Clone, Copy)]
+struct BrickSpec {
+    cell: (usize, usize),
+    brick_type: BrickType,
+    health: i32,
+    armor: Option<Side>,
+    guaranteed_drop: Option<PowerUpType>,
+    golden: bool,
+    // 护盾发生器关的发生器，数字是摧毁顺序
+    generator: Option<usize>,
+}
 
+// 一关的砖块布局
+#[derive(Default)]
+struct Layout {
+    bricks: Vec<BrickSpec>,
+    // 配额关：打碎这个百分比的可破坏砖块就过关；默认为空，要打完全部可破坏砖块
+    quota: Option<u32>,
+}
+
+// 生成关卡布局（同一种子和关卡得到相同布局），不接触 ECS，关卡预览和生成砖块共用
+fn generate_layout(level: u32, seed: u64) -> Layout {
+    generate_checked_layout(level, seed).0
+}
+
+// 生成并检查布局：不合格时换种子重新生成，都不合格时使用固定模板；同时返回每次被拒绝的原因
+fn generate_checked_layout(level: u32, seed: u64) -> (Layout, Vec<layout_check::LayoutRejection>) {
+    if level == shield::SHIELD_LEVEL {
+        return (shield::shield_layout(), Vec::new());
+    }
+    if level == quota::QUOTA_LEVEL {
+        return (quota::quota_layout(), Vec::new());
+    }
+    let mut rejections = Vec::new();
+    for attempt in 0..=layout_check::MAX_REGENERATIONS {
+        let attempt_seed = layout_check::regeneration_seed(seed, attempt);
+        let layout = random_layout(level, attempt_seed);
+        match layout_check::validate_layout(&layout) {
+            Ok(()) => return (layout, rejections),
+            Err(rejection) => rejections.push(rejection),
+        }
+    }
+    let mut bricks = layout_check::fallback_bricks(level);
+    let mut drop_rng = StdRng::seed_from_u64(seed.rotate_left(17) ^ (level as u64).wrapping_mul(0x1656_67B1_9E37_79F9));
+    assign_guaranteed_drops(&mut bricks, &mut drop_rng);
+    assign_golden_bricks(&mut bricks, &mut drop_rng);
+    (Layout { bricks, ..default() }, rejections)
+}
+
+// 按种子随机生成一关的砖块
+fn random_layout(level: u32, seed: u64) -> Layout {
+    let mut rng = StdRng::seed_from_u64(seed ^ (level as u64).wrapping_mul(0x9E37_79B9_7F4A_7C15));
+    // 装甲使用单独的随机数，同一种子的砖块布局保持不变
+    let mut armor_rng = StdRng::seed_from_u64(!seed ^ (level as u64).wrapping_mul(0xC2B2_AE3D_27D4_EB4F));
+    let mut bricks = Vec::with_capacity(BRICK_ROWS * BRICK_COLUMNS);
     for row in 0..BRICK_ROWS {
         for col in 0..BRICK_COLUMNS {
-            let x = start_x + col as f32 * (BRICK_SIZE.x + GAP_SIZE);
-            let y = start_y - row as f32 * (BRICK_SIZE.y + GAP_SIZE);
-
             // 根据关卡生成不同类型的砖块
-            let (brick_type, color, health) = match level {
+            let (brick_type, health) = match level {
                 1 => {
                     // 第一关：大部分普通砖块
                     if rng.gen_range(0..100) < 10 {
-                        (BrickType::Hard, HARD_BRICK_COLOR, 2)
+                        (BrickType::Hard, 2)
                     } else {
-                        (BrickType::Normal, NORMAL_BRICK_COLOR, 1)
+                        (BrickType::Normal, 1)
                     }
                 }
                 2 => {
-                    // 第二关：混合砖块
+                    // 第二关：混合砖块，开始出现冰砖
                     let rand_val = rng.gen_range(0..100);
                     if rand_val < 5 {
-                        (BrickType::Unbreakable, UNBREAKABLE_BRICK_COLOR, -1)
+                        (BrickType::Unbreakable, -1)
                     } else if rand_val < 30 {
-                        (BrickType::Hard, HARD_BRICK_COLOR, 2)
+                        (BrickType::Hard, 2)
+                    } else if rand_val < 38 {
+                        (BrickType::Ice, 1)
                     } else {
-                        (BrickType::Normal, NORMAL_BRICK_COLOR, 1)
+                        (BrickType::Normal, 1)
                     }
                 }
                 _ => {
-                    // 第三关及以后：更多困难砖块
+                    // 第三关及以后：更多困难砖块，加入冰砖、橡胶砖和少量爆炸砖，第四关起出现粘性砖
                     let rand_val = rng.gen_range(0..100);
                     if rand_val < 10 {
-                        (BrickType::Unbreakable, UNBREAKABLE_BRICK_COLOR, -1)
-                    } else if rand_val < 50 {
-                        (BrickType::Hard, HARD_BRICK_COLOR, 3)
+                        (BrickType::Unbreakable, -1)
+                    } else if rand_val < 45 {
+                        (BrickType::Hard, 3)
+                    } else if rand_val < 55 {
+                        (BrickType::Ice, 1)
+                    } else if rand_val < 65 {
+                        (BrickType::Rubber, 2)
+                    } else if level >= 4 && rand_val < 73 {
+                        (BrickType::Sticky, 2)
+                    } else if rand_val >= 100 - EXPLOSIVE_BRICK_PERCENT {
+                        (BrickType::Explosive, 1)
                     } else {
-                        (BrickType::Normal, NORMAL_BRICK_COLOR, 1)
+                        (BrickType::Normal, 1)
                     }
                 }
             };
 
-            commands.spawn((
-                SpriteBundle {
-                    sprite: Sprite {
-                        color,
-                        ..default()
-                    },
-                    transform: Transform {
-                        translation: Vec3::new(x, y, 0.0),
-                        scale: Vec3::new(BRICK_SIZE.x, BRICK_SIZE.y, 1.0),
-                        ..default()
-                    },
-                    ..default()
-                },
-                Brick { brick_type, health },
-                GameEntity,
-            ));
+            // 第二关起部分坚硬砖块的一面带装甲
+            let armor = (level >= 2
+                && matches!(brick_type, BrickType::Hard)
+                && armor_rng.gen_range(0..100) < ARMOR_PLATE_CHANCE)
+                .then(|| Side::ALL[armor_rng.gen_range(0..Side::ALL.len())]);
+
+            bricks.push(BrickSpec {
+                cell: (row, col),
+                brick_type,
+                health,
+                armor,
+                guaranteed_drop: None,
+                golden: false,
+                generator: None,
+            });
+        }
+    }
+    // 第四关起最多把一块普通砖换成定时炸弹，同样使用单独的随机数
+    if level >= BrickType::TimedBomb.first_level() {
+        let mut bomb_rng = StdRng::seed_from_u64(seed.rotate_right(23) ^ (level as u64).wrapping_mul(0x27D4_EB2F_1656_67C5));
+        let normals: Vec<usize> = (0..bricks.len())
+            .filter(|&i| matches!(bricks[i].brick_type, BrickType::Normal))
+            .collect();
+        if let Some(&index) = normals.choose(&mut bomb_rng) {
+            bricks[index].brick_type = BrickType::TimedBomb;
         }
     }
+    // 保底道具同样使用单独的随机数
+    let mut drop_rng = StdRng::seed_from_u64(seed.rotate_left(17) ^ (level as u64).wrapping_mul(0x1656_67B1_9E37_79F9));
+    assign_guaranteed_drops(&mut bricks, &mut drop_rng);
+    assign_golden_bricks(&mut bricks, &mut drop_rng);
+    Layout { bricks, ..default() }
 }
 
-// 设置UI
-fn setup_ui(commands: &mut Commands, difficulty_settings: &DifficultySettings) {
-    // 分数文本
-    commands.spawn((
-        TextBundle::from_section(
-            "Score: 0",
-            TextStyle {
-                font_size: 30.0,
-                color: Color::WHITE,
-                ..default()
-            },
-        )
-        .with_style(Style {
-            position_type: PositionType::Absolute,
-            left: Val::Px(10.0),
-            top: Val::Px(10.0),
-            ..default()
-        }),
-        ScoreText,
-        GameEntity,
-    ));
-
-    // 关卡文本
-    commands.spawn((
-        TextBundle::from_section(
-            "Level: 1",
-            TextStyle {
-                font_size: 30.0,
-                color: Color::WHITE,
-                ..default()
-            },
-        )
-        .with_style(Style {
-            position_type: PositionType::Absolute,
-            left: Val::Px(WINDOW_WIDTH / 2.0 - 50.0),
-            top: Val::Px(10.0),
-            ..default()
-        }),
-        LevelText,
-        GameEntity,
-    ));
+// 每关有一到两块金砖（普通砖），打碎得分固定
+const GOLDEN_BRICKS_PER_LEVEL: std::ops::RangeInclusive<usize> = 1..=2;
+const GOLDEN_BRICK_POINTS: u32 = 50;
+
+// 每关一定包含的保底道具：一个影响球的和一个武器
+const GUARANTEED_DROPS: [PowerUpType; 2] = [PowerUpType::MultiBall, PowerUpType::LaserGun];
+// 可破坏砖块每多这么多块，额外保底一个有利道具
+const BRICKS_PER_EXTRA_DROP: usize = 20;
+const EXTRA_DROPS: [PowerUpType; 5] = [
+    PowerUpType::PaddleExpand,
+    PowerUpType::BallSpeedDown,
+    PowerUpType::MultiBall,
+    PowerUpType::PenetratingBall,
+    PowerUpType::LaserGun,
+];
+
+// 在布局中挑选保底道具砖：跳过不可破坏砖块和会自己爆炸的定时炸弹，优先中间几行，同一行内随机
+fn assign_guaranteed_drops(bricks: &mut [BrickSpec], rng: &mut StdRng) {
+    let mut candidates: Vec<usize> = (0..bricks.len())
+        .filter(|&i| !matches!(bricks[i].brick_type, BrickType::Unbreakable | BrickType::TimedBomb))
+        .collect();
+    candidates.shuffle(rng);
+    // 稳定排序，离中间行越近越靠前，同样距离的砖块保持随机顺序
+    let middle = (BRICK_ROWS - 1) as f32 / 2.0;
+    candidates.sort_by(|&a, &b| {
+        let distance = |i: usize| (bricks[i].cell.0 as f32 - middle).abs();
+        distance(a).total_cmp(&distance(b))
+    });
+
+    let extra = candidates.len() / BRICKS_PER_EXTRA_DROP;
+    let drops = GUARANTEED_DROPS
+        .into_iter()
+        .chain((0..extra).map(|_| EXTRA_DROPS[rng.gen_range(0..EXTRA_DROPS.len())]));
+    for (index, power_type) in candidates.into_iter().zip(drops) {
+        bricks[index].guaranteed_drop = Some(power_type);
+    }
+}
 
-    // 生命文本
-    commands.spawn((
-        TextBundle::from_section(
-            "Lives: 3",
-            TextStyle {
-                font_size: 30.0,
-                color: Color::WHITE,
-                ..default()
-            },
-        )
-        .with_style(Style {
-            position_type: PositionType::Absolute,
-            right: Val::Px(10.0),
-            top: Val::Px(10.0),
-            ..default()
-        }),
-        LivesText,
-        GameEntity,
-    ));
+// 在没有保底道具的普通砖中随机挑一到两块作为金砖
+fn assign_golden_bricks(bricks: &mut [BrickSpec], rng: &mut StdRng) {
+    let candidates: Vec<usize> = (0..bricks.len())
+        .filter(|&i| matches!(bricks[i].brick_type, BrickType::Normal) && bricks[i].guaranteed_drop.is_none())
+        .collect();
+    let count = rng.gen_range(GOLDEN_BRICKS_PER_LEVEL);
+    for index in candidates.choose_multiple(rng, count) {
+        bricks[*index].golden = true;
+    }
+}
 
-    // 如果是困难模式，添加计时器文本
-    if difficulty_settings.difficulty == Difficulty::Hard {
-        commands.spawn((
-            TextBundle::from_section(
-                "Time: 180",
-                TextStyle {
-                    font_size: 30.0,
-                    color: Color::rgb(0.8, 0.2, 0.2),
+// 按布局生成砖块，翻转时第一行在最下面；活动的迷雾下上面几行先隐藏
+fn spawn_bricks(
+    commands: &mut Commands,
+    layout: &Layout,
+    orientation: Orientation,
+    event_run: &EventRun,
+    theme: &SeasonalTheme,
+) {
+    let generator_rows = shield::generator_rows(layout);
+    for spec in &layout.bricks {
+        let position = orientation.apply(brick_position(spec.cell.0, spec.cell.1));
+        let mut brick = commands.spawn((
+            SpriteBundle {
+                sprite: Sprite {
+                    color: theme.brick_color(spec.brick_type),
+                    ..default()
+                },
+                transform: Transform {
+                    translation: position.extend(0.0),
+                    scale: Vec3::new(BRICK_SIZE.x, BRICK_SIZE.y, 1.0),
                     ..default()
                 },
-            )
-            .with_style(Style {
-                position_type: PositionType::Absolute,
-                left: Val::Px(WINDOW_WIDTH / 2.0 - 50.0),
-                top: Val::Px(50.0),
                 ..default()
-            }),
-            TimerText,
+            },
+            Brick {
+                brick_type: spec.brick_type,
+                health: spec.health,
+                shade: 1.0,
+                guaranteed_drop: spec.guaranteed_drop,
+                golden: spec.golden,
+            },
+            Brightness::default(),
             GameEntity,
         ));
-    }
 
-    // 激光状态文本
-    commands.spawn((
-        TextBundle::from_section(
-            "",
-            TextStyle {
-                font_size: 25.0,
-                color: Color::rgb(0.2, 0.8, 0.8),
+        if event_run.fogged(spec.cell.0) {
+            brick.insert((Visibility::Hidden, weekly_event::Fogged));
+        }
+        // 冷暖砖块活动：不可破坏的砖块不计分，不分队
+        if event_run.team_colors() && spec.brick_type != BrickType::Unbreakable {
+            brick.insert(TeamTint::for_column(spec.cell.1));
+        }
+        if spec.brick_type == BrickType::TimedBomb {
+            brick.insert(bomb::BombTimer::default());
+        }
+
+        if let Some(side) = spec.armor {
+            brick
+                .insert(ArmorPlate(side))
+                .with_children(|parent| spawn_armor_plate(parent, side));
+        }
+
+        shield::attach(&mut brick, spec, &generator_rows);
+        theme.decorate_brick(&mut brick, spec.brick_type);
+    }
+}
+
+// 在砖块带装甲的一面画一条加厚的边（子实体使用砖块的单位坐标）
+fn spawn_armor_plate(parent: &mut ChildBuilder, side: Side) {
+    let thickness = Vec2::new(ARMOR_PLATE_THICKNESS / BRICK_SIZE.x, ARMOR_PLATE_THICKNESS / BRICK_SIZE.y);
+    let (offset, scale) = match side {
+        Side::Top => (Vec2::new(0.0, 0.5 - thickness.y / 2.0), Vec2::new(1.0, thickness.y)),
+        Side::Bottom => (Vec2::new(0.0, thickness.y / 2.0 - 0.5), Vec2::new(1.0, thickness.y)),
+        Side::Left => (Vec2::new(thickness.x / 2.0 - 0.5, 0.0), Vec2::new(thickness.x, 1.0)),
+        Side::Right => (Vec2::new(0.5 - thickness.x / 2.0, 0.0), Vec2::new(thickness.x, 1.0)),
+    };
+    parent.spawn(SpriteBundle {
+        sprite: Sprite {
+            color: ARMOR_PLATE_COLOR,
+            ..default()
+        },
+        transform: Transform {
+            translation: offset.extend(0.1),
+            scale: scale.extend(1.0),
+            ..default()
+        },
+        ..default()
+    });
+}
+
+// 砖块格子 (row, col) 的中心位置
+fn brick_position(row: usize, col: usize) -> Vec2 {
+    let total_width = BRICK_COLUMNS as f32 * (BRICK_SIZE.x + GAP_SIZE) - GAP_SIZE;
+    let start_x = -total_width / 2.0 + BRICK_SIZE.x / 2.0;
+    Vec2::new(
+        start_x + col as f32 * (BRICK_SIZE.x + GAP_SIZE),
+        BRICK_START_Y - row as f32 * (BRICK_SIZE.y + GAP_SIZE),
+    )
+}
+
+// 根据位置反查砖块所在的格子
+fn brick_cell(position: Vec3) -> Option<(usize, usize)> {
+    let origin = brick_position(0, 0);
+    let col = ((position.x - origin.x) / (BRICK_SIZE.x + GAP_SIZE)).round();
+    let row = ((origin.y - position.y) / (BRICK_SIZE.y + GAP_SIZE)).round();
+    if col < 0.0 || row < 0.0 || col as usize >= BRICK_COLUMNS || row as usize >= BRICK_ROWS {
+        return None;
+    }
+    Some((row as usize, col as usize))
+}
+
+// 设置UI
+fn setup_ui(commands: &mut Commands, fonts: &FontAssets, timed: bool, orientation: Orientation) {
+    // 顶部状态栏：分数、关卡、生命用 flex 布局排列，随 UI 缩放自适应；翻转时挡板在上面，状态栏移到底部
+    let (top, bottom) = match orientation {
+        Orientation::Normal => (Val::Px(10.0), Val::Auto),
+        Orientation::Flipped => (Val::Auto, Val::Px(10.0)),
+    };
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    width: Val::Percent(100.0),
+                    top,
+                    bottom,
+                    padding: UiRect::horizontal(Val::Px(10.0)),
+                    justify_content: JustifyContent::SpaceBetween,
+                    align_items: AlignItems::FlexStart,
+                    ..default()
+                },
                 ..default()
             },
+            GameEntity,
+        ))
+        .with_children(|parent| {
+            // 分数文本（内容由 update_ui 按当前语言填写）
+            parent.spawn((
+                TextBundle::from_section(
+                    "",
+                    text_style(fonts, FontKind::Mono, 30.0, Color::WHITE),
+                ),
+                ScoreText,
+            ));
+
+            // 关卡文本与计时器居中排列
+            parent
+                .spawn(NodeBundle {
+                    style: Style {
+                        flex_direction: FlexDirection::Column,
+                        align_items: AlignItems::Center,
+                        ..default()
+                    },
+                    ..default()
+                })
+                .with_children(|parent| {
+                    parent.spawn((
+                        TextBundle::from_section(
+                            "",
+                            text_style(fonts, FontKind::Regular, 30.0, Color::WHITE),
+                        ),
+                        LevelText,
+                    ));
+
+                    // 有时间限制时（困难模式或限时诅咒）添加计时器文本
+                    if timed {
+                        parent.spawn((
+                            TextBundle::from_section(
+                                "",
+                                text_style(fonts, FontKind::Mono, 30.0, Color::rgb(0.8, 0.2, 0.2)),
+                            )
+                            .with_style(Style {
+                                margin: UiRect::top(Val::Px(10.0)),
+                                ..default()
+                            }),
+                            TimerText,
+                        ));
+                    }
+                });
+
+            // 生命文本
+            parent.spawn((
+                TextBundle::from_section(
+                    "",
+                    text_style(fonts, FontKind::Mono, 30.0, Color::WHITE),
+                ),
+                LivesText,
+            ));
+        });
+
+    // 激光状态文本
+    commands.spawn((
+        TextBundle::from_section(
+            "",
+            text_style(fonts, FontKind::Mono, 25.0, Color::rgb(0.2, 0.8, 0.8)),
         )
         .with_style(Style {
             position_type: PositionType::Absolute,
@@ -996,6 +3040,61 @@ fn setup_ui(commands: &mut Commands, difficulty_settings: &DifficultySettings) {
         LaserText,
         GameEntity,
     ));
+
+    // 慢动作剩余时间，与激光状态并排
+    commands.spawn((
+        TextBundle::from_section(
+            "",
+            text_style(fonts, FontKind::Mono, 25.0, PowerUpType::SlowMotion.color()),
+        )
+        .with_style(Style {
+            position_type: PositionType::Absolute,
+            left: Val::Px(240.0),
+            bottom: Val::Px(10.0),
+            ..default()
+        }),
+        SlowMotionText,
+        GameEntity,
+    ));
+    homing::spawn_homing_text(commands, fonts);
+
+    // 护符图标：激光状态上方的小盾牌
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    left: Val::Px(10.0),
+                    bottom: Val::Px(45.0),
+                    width: Val::Px(22.0),
+                    height: Val::Px(26.0),
+                    border: UiRect::all(Val::Px(2.0)),
+                    justify_content: JustifyContent::Center,
+                    align_items: AlignItems::Center,
+                    ..default()
+                },
+                border_color: BorderColor(WARD_COLOR),
+                background_color: BackgroundColor(WARD_COLOR.with_a(0.3)),
+                visibility: Visibility::Hidden,
+                ..default()
+            },
+            WardIcon,
+            GameEntity,
+        ))
+        .with_children(|parent| {
+            parent.spawn(NodeBundle {
+                style: Style {
+                    width: Val::Px(6.0),
+                    height: Val::Px(12.0),
+                    ..default()
+                },
+                background_color: BackgroundColor(WARD_COLOR),
+                ..default()
+            });
+        });
+
+    // 分数下方的事件栏
+    feed::spawn_event_feed(commands, fonts);
 }
 
 // 更新UI
@@ -1005,122 +3104,222 @@ fn update_ui(
     lives: Res<Lives>,
     level_timer: Res<LevelTimer>,
     power_effects: Res<PowerUpEffects>,
-    difficulty_settings: Res<DifficultySettings>,
+    locale: Res<Locale>,
     mut score_query: Query<&mut Text, (With<ScoreText>, Without<LevelText>, Without<LivesText>, Without<TimerText>, Without<LaserText>)>,
     mut level_query: Query<&mut Text, (With<LevelText>, Without<ScoreText>, Without<LivesText>, Without<TimerText>, Without<LaserText>)>,
     mut lives_query: Query<&mut Text, (With<LivesText>, Without<ScoreText>, Without<LevelText>, Without<TimerText>, Without<LaserText>)>,
     mut timer_query: Query<&mut Text, (With<TimerText>, Without<ScoreText>, Without<LevelText>, Without<LivesText>, Without<LaserText>)>,
     mut laser_query: Query<&mut Text, (With<LaserText>, Without<ScoreText>, Without<LevelText>, Without<LivesText>, Without<TimerText>)>,
+    mut ward_query: Query<&mut Visibility, With<WardIcon>>,
 ) {
+    perf::perf_scope!("update_ui");
     if let Ok(mut text) = score_query.get_single_mut() {
-        text.sections[0].value = format!("Score: {}", score.0);
+        text.sections[0].value = locale.tr("hud.score", &[("score", &score.0)]);
     }
     if let Ok(mut text) = level_query.get_single_mut() {
-        text.sections[0].value = format!("Level: {}", level.0);
+        text.sections[0].value = locale.tr("hud.level", &[("level", &level.0)]);
     }
     if let Ok(mut text) = lives_query.get_single_mut() {
-        text.sections[0].value = format!("Lives: {}", lives.0);
+        text.sections[0].value = locale.tr("hud.lives", &[("lives", &lives.0)]);
     }
     
-    // 更新计时器文本（仅限困难模式）
-    if difficulty_settings.difficulty == Difficulty::Hard {
-        if let Ok(mut text) = timer_query.get_single_mut() {
-            text.sections[0].value = format!("Time: {}", level_timer.0.ceil() as i32);
-        }
+    // 更新计时器文本（只在有时间限制的关卡存在）
+    if let Ok(mut text) = timer_query.get_single_mut() {
+        text.sections[0].value = locale.tr("hud.time", &[("seconds", &(level_timer.0.remaining().ceil() as i32))]);
     }
 
     // 更新激光状态文本
     if let Ok(mut text) = laser_query.get_single_mut() {
         if power_effects.has_laser {
-            text.sections[0].value = format!("LASER: {:.1}s", power_effects.laser_timer);
+            text.sections[0].value = locale.tr("hud.laser", &[("seconds", &format!("{:.1}", power_effects.laser_timer.remaining()))]);
         } else {
             text.sections[0].value = String::new();
         }
     }
+
+    if let Ok(mut visibility) = ward_query.get_single_mut() {
+        *visibility = if power_effects.warded { Visibility::Inherited } else { Visibility::Hidden };
+    }
+}
+
+// 更新慢动作状态文本
+fn update_slow_motion_text(
+    power_effects: Res<PowerUpEffects>,
+    locale: Res<Locale>,
+    mut texts: Query<&mut Text, With<SlowMotionText>>,
+) {
+    let Ok(mut text) = texts.get_single_mut() else {
+        return;
+    };
+    let remaining = power_effects.slow_motion_timer.remaining();
+    let value = if remaining > 0.0 {
+        locale.tr("hud.slow_motion", &[("seconds", &format!("{:.1}", remaining))])
+    } else {
+        String::new()
+    };
+    if text.sections[0].value != value {
+        text.sections[0].value = value;
+    }
 }
 
-// 更新关卡计时器
+// 更新关卡计时器；在 check_victory 之后运行，最后一帧清空砖块算作胜利
 fn update_level_timer(
     time: Res<Time>,
     mut level_timer: ResMut<LevelTimer>,
     difficulty_settings: Res<DifficultySettings>,
-    mut next_state: ResMut<NextState<GameState>>,
+    curse: Res<Curse>,
+    bonus: Res<BonusStage>,
+    game_mode: Res<GameMode>,
+    (state, mut next_state): (Res<State<GameState>>, ResMut<NextState<GameState>>),
+    released_balls: Query<(), (With<Ball>, Without<HeldBall>)>,
 ) {
-    if difficulty_settings.difficulty == Difficulty::Hard {
-        if level_timer.0 > 0.0 {
-            level_timer.0 -= time.delta_seconds();
-            if level_timer.0 <= 0.0 {
-                level_timer.0 = 0.0;
-                next_state.set(GameState::GameOver);
-            }
-        }
+    hazard_rules::assert_uses_game_time!(state, "update_level_timer");
+    // 只有困难模式和限时诅咒有时间限制；奖励关有自己的计时，不会因为时间用完而结束游戏
+    if curse.time_limit(difficulty_settings.time_limit).is_none() || bonus.is_active() {
+        return;
+    }
+    // 所有球都停在挡板上等待发射时不计时，开局和丢命后可以从容发球
+    if released_balls.is_empty() {
+        return;
+    }
+    level_timer.0.tick(time.delta_seconds());
+    let victory_pending = next_state.0 == Some(game_mode.clear_state());
+    if level_timer.0.just_finished() && !victory_pending {
+        next_state.set(game_mode.run_over_state());
     }
 }
 
 // 挡板移动
 fn paddle_movement(
     keyboard_input: Res<ButtonInput<KeyCode>>,
-    mut paddle_query: Query<&mut Transform, With<Paddle>>,
+    gamepad: GamepadButtons,
+    gamepads: Res<Gamepads>,
+    axes: Res<Axis<GamepadAxis>>,
+    settings: Res<Settings>,
+    mut stick_smoothing: ResMut<StickSmoothing>,
+    mut paddle_query: Query<(&mut Transform, &mut PaddleTilt, &mut PaddleVelocity), With<Paddle>>,
     time: Res<Time>,
     power_effects: Res<PowerUpEffects>,
     difficulty_settings: Res<DifficultySettings>,
 ) {
-    if let Ok(mut transform) = paddle_query.get_single_mut() {
-        let mut direction = 0.0;
-
-        if keyboard_input.pressed(KeyCode::ArrowLeft) || keyboard_input.pressed(KeyCode::KeyA) {
-            direction -= 1.0;
-        }
-        if keyboard_input.pressed(KeyCode::ArrowRight) || keyboard_input.pressed(KeyCode::KeyD) {
-            direction += 1.0;
+    let (mut transform, mut tilt, mut velocity) = match paddle_query.get_single_mut() {
+        Ok(paddle) => paddle,
+        Err(e) => {
+            paddle::warn_paddle_query("paddle_movement", e);
+            return;
         }
+    };
+    // 手柄十字键与方向键相同
+    let left = keyboard_input.any_pressed([KeyCode::ArrowLeft, KeyCode::KeyA]) || gamepad.pressed(GamepadButtonType::DPadLeft);
+    let right =
+        keyboard_input.any_pressed([KeyCode::ArrowRight, KeyCode::KeyD]) || gamepad.pressed(GamepadButtonType::DPadRight);
+    if keyboard_input.any_just_pressed([KeyCode::ArrowLeft, KeyCode::KeyA]) || gamepad.just_pressed(GamepadButtonType::DPadLeft) {
+        tilt.press(-1.0);
+    }
+    if keyboard_input.any_just_pressed([KeyCode::ArrowRight, KeyCode::KeyD])
+        || gamepad.just_pressed(GamepadButtonType::DPadRight)
+    {
+        tilt.press(1.0);
+    }
 
-        let paddle_width = PADDLE_SIZE.x * power_effects.paddle_size_modifier;
-        let half_paddle = paddle_width / 2.0;
-        let boundary = WINDOW_WIDTH / 2.0 - half_paddle;
+    // 同时按住左右键时挡板不移动，改为朝最近按下的一侧倾斜；Q/E（手柄 LB/RB）直接倾斜
+    let mut direction = 0.0;
+    let mut tilt_target = 0.0;
+    if left && right {
+        tilt_target = tilt.both_keys_target();
+    } else if left {
+        direction = -1.0;
+    } else if right {
+        direction = 1.0;
+    }
+    if keyboard_input.pressed(KeyCode::KeyQ) || gamepad.pressed(GamepadButtonType::LeftTrigger) {
+        tilt_target = 1.0;
+    } else if keyboard_input.pressed(KeyCode::KeyE) || gamepad.pressed(GamepadButtonType::RightTrigger) {
+        tilt_target = -1.0;
+    }
+    if !difficulty_settings.paddle_tilt {
+        tilt_target = 0.0;
+    }
 
-        transform.translation.x += direction * PADDLE_SPEED * difficulty_settings.paddle_speed_modifier * time.delta_seconds();
-        transform.translation.x = transform.translation.x.clamp(-boundary, boundary);
-        transform.scale.x = paddle_width;
+    // 没有按方向键时用手柄左摇杆：速度按推动量缩放，按设置处理死区和曲线后平滑目标速度；
+    // 键盘输入不经过平滑，保持数字控制的干脆手感
+    if left || right {
+        stick_smoothing.0 = 0.0;
+    } else {
+        let target = stick::process_axis(stick::raw_stick_x(&gamepads, &axes), &settings.stick);
+        stick_smoothing.0 = stick::smooth(stick_smoothing.0, target, settings.stick.smoothing, time.delta_seconds());
+        direction = stick_smoothing.0;
     }
+    tilt.update(tilt_target, time.delta_seconds());
+    transform.rotation = Quat::from_rotation_z(tilt.angle);
+
+    // 倾斜后的挡板四角也不能超出墙壁
+    let paddle_width = PADDLE_SIZE.x * power_effects.paddle_size_modifier;
+    let boundary = WINDOW_WIDTH / 2.0 - tilt::tilted_half_width(Vec2::new(paddle_width, PADDLE_SIZE.y), tilt.angle);
+
+    let previous_x = transform.translation.x;
+    transform.translation.x += direction * PADDLE_SPEED * difficulty_settings.paddle_speed_modifier * time.delta_seconds();
+    transform.translation.x = transform.translation.x.clamp(-boundary, boundary);
+    transform.scale.x = paddle_width;
+    // 按实际位移计算，撞墙停住时挡板没有速度
+    velocity.0 = if time.delta_seconds() > 0.0 {
+        (transform.translation.x - previous_x) / time.delta_seconds()
+    } else {
+        0.0
+    };
 }
 
 // 激光射击系统
 fn laser_shooting(
     mut commands: Commands,
-    keyboard_input: Res<ButtonInput<KeyCode>>,
+    mut input_buffer: ResMut<InputBuffer>,
     power_effects: Res<PowerUpEffects>,
     paddle_query: Query<&Transform, With<Paddle>>,
+    held_balls: Query<(), With<HeldBall>>,
+    mut gameplay_events: EventWriter<GameplayEvent>,
+    orientation: Res<Orientation>,
 ) {
-    if power_effects.has_laser && keyboard_input.just_pressed(KeyCode::Space) {
-        if let Ok(paddle_transform) = paddle_query.get_single() {
-            let paddle_width = PADDLE_SIZE.x * power_effects.paddle_size_modifier;
-            
-            // 从挡板两端发射激光
-            for offset in [-paddle_width / 3.0, paddle_width / 3.0] {
-                commands.spawn((
-                    SpriteBundle {
-                        sprite: Sprite {
-                            color: LASER_COLOR,
-                            ..default()
-                        },
-                        transform: Transform {
-                            translation: Vec3::new(
-                                paddle_transform.translation.x + offset,
-                                paddle_transform.translation.y + PADDLE_SIZE.y,
-                                0.0,
-                            ),
-                            scale: Vec3::new(LASER_SIZE.x, LASER_SIZE.y, 1.0),
-                            ..default()
-                        },
+    // 球还停在挡板上时空格用于发射
+    if power_effects.has_laser
+        && held_balls.is_empty()
+        && input_buffer.consume_if_buffered(InputAction::Primary, input_buffer::BUFFER_WINDOW)
+    {
+        let paddle_transform = match paddle_query.get_single() {
+            Ok(transform) => transform,
+            Err(e) => {
+                paddle::warn_paddle_query("laser_shooting", e);
+                return;
+            }
+        };
+        let paddle_width = PADDLE_SIZE.x * power_effects.paddle_size_modifier;
+        gameplay_events.send(GameplayEvent::LaserFired {
+            position: paddle_transform.translation,
+        });
+        
+        // 从挡板两端发射激光
+        for offset in [-paddle_width / 3.0, paddle_width / 3.0] {
+            commands.spawn((
+                SpriteBundle {
+                    sprite: Sprite {
+                        color: LASER_COLOR,
                         ..default()
                     },
-                    Laser {
-                        velocity: Vec2::new(0.0, LASER_SPEED),
+                    transform: Transform {
+                        translation: Vec3::new(
+                            paddle_transform.translation.x + offset,
+                            paddle_transform.translation.y + PADDLE_SIZE.y * orientation.sign(),
+                            0.0,
+                        ),
+                        scale: Vec3::new(LASER_SIZE.x, LASER_SIZE.y, 1.0),
+                        ..default()
                     },
-                    GameEntity,
-                ));
-            }
+                    ..default()
+                },
+                Laser {
+                    velocity: orientation.apply(Vec2::new(0.0, LASER_SPEED)),
+                },
+                GameEntity,
+            ));
         }
     }
 }
@@ -1130,13 +3329,16 @@ fn laser_movement(
     mut commands: Commands,
     mut lasers: Query<(Entity, &mut Transform, &Laser)>,
     time: Res<Time>,
+    time_scale: Res<TimeScale>,
+    orientation: Res<Orientation>,
 ) {
+    let delta = time_scale.delta(&time);
     for (entity, mut transform, laser) in lasers.iter_mut() {
-        transform.translation += laser.velocity.extend(0.0) * time.delta_seconds();
+        transform.translation += laser.velocity.extend(0.0) * delta;
         
-        // 如果激光超出屏幕顶部，删除它
-        if transform.translation.y > WINDOW_HEIGHT / 2.0 + 50.0 {
-            commands.entity(entity).despawn();
+        // 如果激光超出屏幕顶部（翻转时为底部），删除它
+        if transform.translation.y * orientation.sign() > WINDOW_HEIGHT / 2.0 + 50.0 {
+            commands.safe_despawn(entity);
         }
     }
 }
@@ -1145,20 +3347,44 @@ fn laser_movement(
 fn laser_collision(
     mut commands: Commands,
     lasers: Query<(Entity, &Transform), With<Laser>>,
-    mut bricks: Query<(Entity, &Transform, &mut Brick, &mut Sprite), Without<Laser>>,
+    mut bricks: Query<
+        (Entity, &Transform, &mut Brick, Option<&ArmorPlate>, Option<&Shield>, Option<&TeamTint>),
+        Without<Laser>,
+    >,
     mut score: ResMut<Score>,
+    mut gameplay_events: EventWriter<GameplayEvent>,
+    budget: Res<EffectBudget>,
+    (orientation, bonus, chain, hot_team): (Res<Orientation>, Res<BonusStage>, Res<GeneratorChain>, Res<HotTeam>),
+    curse: Res<Curse>,
+    mut explosions: EventWriter<Explosion>,
+    mut game_rng: ResMut<GameRng>,
 ) {
+    perf::perf_scope!("laser_collision");
+    // 激光总是从挡板一侧击中砖块
+    let struck_side = match *orientation {
+        Orientation::Normal => Side::Bottom,
+        Orientation::Flipped => Side::Top,
+    };
     for (laser_entity, laser_transform) in lasers.iter() {
-        for (brick_entity, brick_transform, mut brick, mut sprite) in bricks.iter_mut() {
-            if let Some(_) = collide(
-                laser_transform.translation,
-                LASER_SIZE,
-                brick_transform.translation,
-                BRICK_SIZE,
-            ) {
-                // 激光不能破坏不可破坏的砖块
-                if matches!(brick.brick_type, BrickType::Unbreakable) {
-                    commands.entity(laser_entity).despawn();
+        for (brick_entity, brick_transform, mut brick, armor, shield, tint) in bricks.iter_mut() {
+            // 本帧已经被打碎、等待删除的砖块不再计分
+            if brick.health <= 0 {
+                continue;
+            }
+            if collide(laser_transform.translation, LASER_SIZE, brick_transform.translation, BRICK_SIZE).is_some() {
+                // 激光不能破坏不可破坏的砖块，也打不穿朝向挡板一侧的装甲
+                if matches!(brick.brick_type, BrickType::Unbreakable) || armor.is_some_and(|plate| plate.0 == struck_side) {
+                    commands.safe_despawn(laser_entity);
+                    break;
+                }
+
+                // 护盾同样挡住激光
+                if let Some(locked) = chain.deflects(shield) {
+                    gameplay_events.send(GameplayEvent::ShieldDeflected {
+                        position: brick_transform.translation,
+                        locked,
+                    });
+                    commands.safe_despawn(laser_entity);
                     break;
                 }
 
@@ -1166,29 +3392,41 @@ fn laser_collision(
                 brick.health -= 2;
 
                 if brick.health <= 0 {
-                    // 销毁砖块
-                    commands.entity(brick_entity).despawn();
+                    // 销毁砖块（连同装甲板）
+                    commands.safe_despawn_recursive(brick_entity);
                     
                     // 增加分数
-                    match brick.brick_type {
-                        BrickType::Normal => score.0 += 15, // 激光破坏获得更多分数
-                        BrickType::Hard => score.0 += 30,
-                        _ => {}
-                    }
+                    // 激光破坏获得更多分数
+                    let points = curse.apply(hot_team.apply(
+                        brick.laser_points() * bonus.score_multiplier() * chain.score_multiplier(),
+                        tint,
+                    ));
+                    score.0 += points;
 
                     // 生成粒子效果
-                    spawn_particles(&mut commands, brick_transform.translation, brick_transform.scale);
+                    spawn_particles(&mut commands, &budget, brick_transform.translation, brick_transform.scale);
+                    // 激光由挡板发射，总是计入连击
+                    gameplay_events.send(GameplayEvent::BrickDestroyed {
+                        brick_type: brick.brick_type,
+                        points,
+                        position: brick_transform.translation,
+                        combo: true,
+                    });
+                    // 激光打碎的砖块不随机掉落道具，但保底道具和金砖照常掉落
+                    drop_certain_powerup(&mut commands, &mut game_rng, &brick, brick_transform.translation, true);
+                    if brick.brick_type == BrickType::Explosive {
+                        explosions.send(Explosion::brick(brick_transform.translation, true, ExplosionSource::Laser));
+                    }
                 } else {
-                    // 更新砖块颜色表示受损
-                    sprite.color = Color::rgb(
-                        sprite.color.r() * 0.6,
-                        sprite.color.g() * 0.6,
-                        sprite.color.b() * 0.6,
-                    );
+                    gameplay_events.send(GameplayEvent::BrickDamaged {
+                        position: brick_transform.translation,
+                    });
+                    // 砖块变暗表示受损
+                    brick.shade *= 0.6;
                 }
 
                 // 激光击中后消失
-                commands.entity(laser_entity).despawn();
+                commands.safe_despawn(laser_entity);
                 break;
             }
         }
@@ -1197,105 +3435,290 @@ fn laser_collision(
 
 // 球移动
 fn ball_movement(
-    mut ball_query: Query<(&mut Transform, &Ball)>,
+    mut ball_query: Query<(&mut Transform, &mut Ball, Option<&HomingTarget>), (Without<HeldBall>, Without<StuckBall>)>,
     time: Res<Time>,
+    time_scale: Res<TimeScale>,
     power_effects: Res<PowerUpEffects>,
     difficulty_settings: Res<DifficultySettings>,
+    orientation: Res<Orientation>,
 ) {
-    for (mut transform, ball) in ball_query.iter_mut() {
-        let velocity = ball.velocity * power_effects.ball_speed_modifier * difficulty_settings.ball_speed_modifier;
-        transform.translation += velocity.extend(0.0) * time.delta_seconds();
+    let delta = time_scale.delta(&time);
+    for (mut transform, mut ball, homing_target) in ball_query.iter_mut() {
+        // 追踪球：先朝目标转一点，再做竖直速度的下限检查
+        if let Some(target) = homing_target.filter(|_| power_effects.homing) {
+            let position = transform.translation.truncate();
+            let max_angle = homing::turn_limit(power_effects.penetrating_ball, delta);
+            homing::steer_ball(&mut ball, position, orientation.apply(position).y, target, max_angle);
+        }
+        let corrected = orientation.apply(enforce_min_vertical(orientation.apply(ball.velocity)));
+        if corrected != ball.velocity {
+            ball.velocity = corrected;
+        }
+        let mut velocity =
+            ball.velocity * power_effects.ball_speed_modifier * difficulty_settings.ball_speed_modifier;
+        // 减速带只改变这一帧的位移，不改变 Ball 的速度，离开减速带后恢复原速
+        if difficulty_settings.slow_zone {
+            let position = orientation.apply(transform.translation.truncate());
+            velocity *= slow_zone_scale(position.y, orientation.apply(ball.velocity).y);
+        }
+        transform.translation += velocity.extend(0.0) * delta;
+    }
+}
+
+// 发球和普通挡板反弹后的球速（不含道具的临时倍率，道具倍率在移动时再乘上）
+fn serve_speed(difficulty_settings: &DifficultySettings) -> f32 {
+    BALL_SPEED * difficulty_settings.ball_speed_modifier
+}
+
+// 竖直速度太小时把一部分水平速度转到竖直方向，速度大小不变（正常方向下的坐标）。
+// 竖直速度恰好为 0 时朝远离挡板的方向
+fn enforce_min_vertical(velocity: Vec2) -> Vec2 {
+    let speed = velocity.length();
+    let min_y = speed * MIN_VERTICAL_RATIO;
+    if speed < f32::EPSILON || velocity.y.abs() >= min_y {
+        return velocity;
+    }
+    let y = if velocity.y < 0.0 { -min_y } else { min_y };
+    Vec2::new((speed * speed - min_y * min_y).sqrt().copysign(velocity.x), y)
+}
+
+// 底部减速带的速度倍率（正常方向下的坐标）：越深入减速越多，最深处慢 25%；
+// 上升的球不减速，球不会被困在减速带里
+fn slow_zone_scale(y: f32, velocity_y: f32) -> f32 {
+    if velocity_y >= 0.0 {
+        return 1.0;
+    }
+    let top = -WINDOW_HEIGHT / 2.0 + SLOW_ZONE_HEIGHT;
+    let depth = ((top - y) / SLOW_ZONE_HEIGHT).clamp(0.0, 1.0);
+    1.0 - SLOW_ZONE_MAX_SLOWDOWN * depth
+}
+
+// 减速带的提示：底边上方一条由淡到浓的半透明色带
+fn spawn_slow_zone(commands: &mut Commands, orientation: Orientation) {
+    let strip_height = SLOW_ZONE_HEIGHT / SLOW_ZONE_STRIPS as f32;
+    for strip in 0..SLOW_ZONE_STRIPS {
+        // strip 0 贴着底边，最不透明
+        let alpha = SLOW_ZONE_MAX_ALPHA * (SLOW_ZONE_STRIPS - strip) as f32 / SLOW_ZONE_STRIPS as f32;
+        let y = -WINDOW_HEIGHT / 2.0 + strip_height * (strip as f32 + 0.5);
+        commands.spawn((
+            SpriteBundle {
+                sprite: Sprite {
+                    color: Color::rgba(0.4, 0.7, 1.0, alpha),
+                    custom_size: Some(Vec2::new(WINDOW_WIDTH, strip_height)),
+                    ..default()
+                },
+                transform: Transform::from_translation(orientation.apply(Vec2::new(0.0, y)).extend(-1.0)),
+                ..default()
+            },
+            GameEntity,
+        ));
     }
 }
 
+// 击中位置是否落在完美击球区；偏移按当前挡板宽度归一化，所以挡板越长阈值越小
+fn is_perfect_hit(offset: f32, paddle_width: f32) -> bool {
+    offset.abs() <= PERFECT_ZONE * PADDLE_SIZE.x / paddle_width
+}
+
 // 球碰撞检测
 fn ball_collision(
     mut commands: Commands,
-    mut ball_query: Query<(Entity, &mut Transform, &mut Ball)>,
-    paddle_query: Query<&Transform, (With<Paddle>, Without<Ball>)>,
-    mut brick_query: Query<(Entity, &Transform, &mut Brick, &mut Sprite), Without<Ball>>,
+    mut ball_query: Query<
+        (Entity, &mut Transform, &mut Ball, Option<&LastPaddleContact>),
+        (Without<HeldBall>, Without<StuckBall>),
+    >,
+    all_balls: Query<(), With<Ball>>,
+    paddle_query: Query<(&Transform, &PaddleTilt, &PaddleVelocity), (With<Paddle>, Without<Ball>)>,
+    mut brick_query: Query<
+        (Entity, &Transform, &mut Brick, Option<&ArmorPlate>, Option<&Shield>, Option<&TeamTint>),
+        Without<Ball>,
+    >,
     mut score: ResMut<Score>,
     mut lives: ResMut<Lives>,
     mut next_state: ResMut<NextState<GameState>>,
     power_effects: Res<PowerUpEffects>,
     difficulty_settings: Res<DifficultySettings>,
+    (time, time_scale): (Res<Time>, Res<TimeScale>),
+    play_clock: Res<PlayClock>,
+    mut gameplay_events: EventWriter<GameplayEvent>,
+    budget: Res<EffectBudget>,
+    (orientation, bonus, event_run, chain): (Res<Orientation>, Res<BonusStage>, Res<EventRun>, Res<GeneratorChain>),
+    (mut insurance, barriers, hot_team, curse, mut explosions, mut wall_impacts, mut game_rng): (
+        ResMut<Insurance>,
+        Query<(), With<Barrier>>,
+        Res<HotTeam>,
+        Res<Curse>,
+        EventWriter<Explosion>,
+        EventWriter<WallImpact>,
+        ResMut<GameRng>,
+    ),
 ) {
-    // 安全获取挡板
-    let paddle_result = paddle_query.get_single();
-    if paddle_result.is_err() {
-        return; // 如果没有挡板，直接返回
-    }
-    let paddle_transform = paddle_result.unwrap();
+    perf::perf_scope!("ball_collision");
+    let (paddle_transform, paddle_tilt, paddle_velocity) = match paddle_query.get_single() {
+        Ok(paddle) => paddle,
+        Err(e) => {
+            paddle::warn_paddle_query("ball_collision", e);
+            return;
+        }
+    };
     let paddle_width = PADDLE_SIZE.x * power_effects.paddle_size_modifier;
+    // 挡板相关的判定都在正常方向下计算；上下翻转后倾斜角也反向
+    let paddle_center = orientation.apply(paddle_transform.translation.truncate());
+    let paddle_angle = paddle_tilt.angle * orientation.sign();
     
-    let total_balls = ball_query.iter().count();
+    // 停在挡板上或被粘性砖抓住的球也算在场上
+    let total_balls = all_balls.iter().count();
     let mut balls_to_remove = Vec::new();
 
-    for (ball_entity, mut ball_transform, mut ball) in ball_query.iter_mut() {
+    for (ball_entity, mut ball_transform, mut ball, contact) in ball_query.iter_mut() {
+        let mut contact = contact.copied();
+
         // 墙壁碰撞
         let half_width = WINDOW_WIDTH / 2.0;
         let half_height = WINDOW_HEIGHT / 2.0;
 
+        // 撞墙时在接触点留下印记（只在朝墙运动、真正反弹时）
         if ball_transform.translation.x < -half_width + BALL_SIZE.x / 2.0 {
             ball_transform.translation.x = -half_width + BALL_SIZE.x / 2.0;
+            if ball.velocity.x < 0.0 {
+                wall_impacts.send(WallImpact {
+                    position: Vec2::new(-half_width, ball_transform.translation.y),
+                    side_wall: true,
+                });
+            }
             ball.velocity.x = ball.velocity.x.abs();
         } else if ball_transform.translation.x > half_width - BALL_SIZE.x / 2.0 {
             ball_transform.translation.x = half_width - BALL_SIZE.x / 2.0;
+            if ball.velocity.x > 0.0 {
+                wall_impacts.send(WallImpact {
+                    position: Vec2::new(half_width, ball_transform.translation.y),
+                    side_wall: true,
+                });
+            }
             ball.velocity.x = -ball.velocity.x.abs();
         }
 
-        if ball_transform.translation.y > half_height - BALL_SIZE.y / 2.0 {
-            ball_transform.translation.y = half_height - BALL_SIZE.y / 2.0;
-            ball.velocity.y = -ball.velocity.y.abs();
+        // 远离挡板的一侧是墙，挡板一侧是出界
+        let sign = orientation.sign();
+        if ball_transform.translation.y * sign > half_height - BALL_SIZE.y / 2.0 {
+            ball_transform.translation.y = (half_height - BALL_SIZE.y / 2.0) * sign;
+            if ball.velocity.y * sign > 0.0 {
+                wall_impacts.send(WallImpact {
+                    position: Vec2::new(ball_transform.translation.x, half_height * sign),
+                    side_wall: false,
+                });
+            }
+            ball.velocity.y = -ball.velocity.y.abs() * sign;
+        }
+
+        // 安全网还在时球在挡板下方弹回，不会出界
+        if !barriers.is_empty() {
+            let mut position = orientation.apply(ball_transform.translation.truncate());
+            let mut velocity = orientation.apply(ball.velocity);
+            if barrier::bounce(&mut position, &mut velocity) {
+                ball_transform.translation.y = orientation.apply(position).y;
+                ball.velocity = orientation.apply(velocity);
+            }
         }
 
         // 底部边界
-        if ball_transform.translation.y < -half_height {
+        if ball_transform.translation.y * sign < -half_height {
             if total_balls > 1 {
                 // 如果还有其他球，只删除这个球
                 balls_to_remove.push(ball_entity);
+            } else if bonus.is_active() {
+                // 奖励关不会丢命，球重新停在挡板上
+                ball.velocity = Vec2::ZERO;
+                commands.entity(ball_entity).insert(HeldBall::random(&mut *game_rng));
+                continue;
+            } else if insurance.consume() {
+                // 用掉一枚保险代币代替丢命，球重新停在挡板上
+                gameplay_events.send(GameplayEvent::InsuranceUsed);
+                ball.velocity = Vec2::ZERO;
+                commands.entity(ball_entity).insert(HeldBall::random(&mut *game_rng));
+                continue;
             } else {
                 // 这是最后一个球
+                gameplay_events.send(GameplayEvent::LifeLost);
                 if lives.0 == 1 {
-                    // 最后一条命，直接游戏结束
-                    next_state.set(GameState::GameOver);
+                    // 最后一条命，进入死亡镜头后游戏结束
+                    next_state.set(GameState::DeathSequence);
                 } else {
-                    // 还有生命，扣除一条并重置
+                    // 还有生命，扣除一条，球重新停在挡板上
                     lives.0 = lives.0.saturating_sub(1);
-                    // 重置球位置
-                    ball_transform.translation = Vec3::new(0.0, -200.0, 0.0);
-                    ball.velocity = Vec2::new(
-                        if rand::random() { 1.0 } else { -1.0 },
-                        1.0,
-                    ).normalize() * BALL_SPEED * difficulty_settings.ball_speed_modifier;
+                    ball.velocity = Vec2::ZERO;
+                    commands.entity(ball_entity).insert(HeldBall::random(&mut *game_rng));
+                    continue;
                 }
             }
         }
 
-        // 挡板碰撞
-        if let Some(collision) = collide(
-            ball_transform.translation,
-            BALL_SIZE,
-            paddle_transform.translation,
+        // 挡板碰撞（考虑挡板倾斜）
+        if let Some(bounce) = tilt::paddle_bounce(
+            orientation.apply(ball_transform.translation.truncate()),
+            orientation.apply(ball.velocity),
+            paddle_center,
             Vec2::new(paddle_width, PADDLE_SIZE.y),
+            paddle_angle,
         ) {
-            match collision {
-                Collision::Left | Collision::Right => {
-                    ball.velocity.x = -ball.velocity.x;
-                }
-                Collision::Top | Collision::Bottom => {
-                    ball.velocity.y = ball.velocity.y.abs();
-                    
-                    // 根据击中位置调整球的横向速度
-                    let hit_position = (ball_transform.translation.x - paddle_transform.translation.x) 
-                        / (paddle_width / 2.0);
-                    ball.velocity.x = hit_position * BALL_SPEED * 0.75;
-                }
+            let impact_speed =
+                ball.velocity.length() * power_effects.ball_speed_modifier * difficulty_settings.ball_speed_modifier;
+            // 完美击球：加分，并以稍快的速度沿挡板法线弹回
+            let perfect = bounce.on_surface && is_perfect_hit(bounce.offset, paddle_width);
+            if perfect {
+                score.0 += curse.apply(PERFECT_BONUS);
+                let speed = (ball.velocity.length() * PERFECT_SPEED_BOOST).min(MAX_BALL_SPEED);
+                ball.velocity = orientation.apply(tilt::straight_return(speed, paddle_angle));
+            } else {
+                // 挡板只改变方向，球速回到发球时的大小；否则沿挡板表面的速度和砖块加速会让球速在一局里越跑越偏
+                let velocity = bounce.velocity.normalize_or_zero() * serve_speed(&difficulty_settings);
+                // 从挡板表面弹开时带上挡板的移动速度
+                ball.velocity = orientation.apply(if bounce.on_surface {
+                    tilt::add_spin(velocity, paddle_velocity.0)
+                } else {
+                    velocity
+                });
+            }
+            gameplay_events.send(GameplayEvent::PaddleHit {
+                position: ball_transform.translation,
+                speed: impact_speed,
+                offset: bounce.offset,
+                perfect,
+            });
+            let paddle_contact = LastPaddleContact { time: play_clock.now() };
+            commands.entity(ball_entity).insert(paddle_contact);
+            contact = Some(paddle_contact);
+        } else if ball.velocity.y * sign < 0.0 {
+            // 边缘容错：用本帧的移动轨迹判断球是否刚好从挡板边缘外侧擦过
+            let velocity = ball.velocity * power_effects.ball_speed_modifier * difficulty_settings.ball_speed_modifier;
+            let current = orientation.apply(ball_transform.translation.truncate());
+            let previous = current - orientation.apply(velocity) * time_scale.delta(&time);
+            if let Some(side) = edge_save_side(
+                previous,
+                current,
+                paddle_center,
+                paddle_width,
+                difficulty_settings.edge_grace,
+            ) {
+                // 向外侧陡峭反弹
+                ball.velocity =
+                    orientation.apply(Vec2::new(side * 0.8, 0.6).normalize() * serve_speed(&difficulty_settings));
+                ball_transform.translation.y = paddle_transform.translation.y + (PADDLE_SIZE.y + BALL_SIZE.y) / 2.0 * sign;
+                gameplay_events.send(GameplayEvent::EdgeSave {
+                    position: ball_transform.translation,
+                });
+                let paddle_contact = LastPaddleContact { time: play_clock.now() };
+                commands.entity(ball_entity).insert(paddle_contact);
+                contact = Some(paddle_contact);
             }
         }
 
         // 砖块碰撞
-        for (brick_entity, brick_transform, mut brick, mut sprite) in brick_query.iter_mut() {
+        for (brick_entity, brick_transform, mut brick, armor, shield, tint) in brick_query.iter_mut() {
+            // 本帧已经被激光或另一个球打碎、等待删除的砖块
+            if brick.health <= 0 {
+                continue;
+            }
             if let Some(collision) = collide(
                 ball_transform.translation,
                 BALL_SIZE,
@@ -1304,26 +3727,44 @@ fn ball_collision(
             ) {
                 // 不可破坏砖块
                 if matches!(brick.brick_type, BrickType::Unbreakable) {
-                    match collision {
-                        Collision::Left | Collision::Right => {
-                            ball.velocity.x = -ball.velocity.x;
-                        }
-                        Collision::Top | Collision::Bottom => {
-                            ball.velocity.y = -ball.velocity.y;
-                        }
-                    }
+                    ball.velocity = brick.brick_type.bounce_response(ball.velocity, collision);
+                    continue;
+                }
+
+                // 受护盾保护的砖块和还没轮到的发生器只反弹，穿透球也打不穿
+                if let Some(locked) = chain.deflects(shield) {
+                    ball.velocity = brick.brick_type.bounce_response(ball.velocity, collision);
+                    gameplay_events.send(GameplayEvent::ShieldDeflected {
+                        position: brick_transform.translation,
+                        locked,
+                    });
+                    continue;
+                }
+
+                // 击中装甲面只反弹不造成伤害，穿透球无视装甲
+                if !power_effects.penetrating_ball && armor.is_some_and(|plate| plate.0 == Side::struck_by(collision)) {
+                    ball.velocity = brick.brick_type.bounce_response(ball.velocity, collision);
+                    gameplay_events.send(GameplayEvent::ArmorDeflected {
+                        position: brick_transform.translation,
+                    });
                     continue;
                 }
 
-                // 穿透球效果
+                // 粘性砖抓住球，弹出时才造成伤害；穿透球不会被抓住
+                if matches!(brick.brick_type, BrickType::Sticky) && !power_effects.penetrating_ball {
+                    commands.entity(ball_entity).insert(StuckBall {
+                        by: brick_entity,
+                        release_at: play_clock.now() + sticky::STICKY_HOLD_TIME,
+                        anchor: ball_transform.translation,
+                    });
+                    break;
+                }
+
+                // 穿透球直接穿过，冰砖和橡胶砖的效果也不触发
                 if !power_effects.penetrating_ball {
-                    match collision {
-                        Collision::Left | Collision::Right => {
-                            ball.velocity.x = -ball.velocity.x;
-                        }
-                        Collision::Top | Collision::Bottom => {
-                            ball.velocity.y = -ball.velocity.y;
-                        }
+                    ball.velocity = brick.brick_type.bounce_response(ball.velocity, collision);
+                    if matches!(brick.brick_type, BrickType::Ice) {
+                        spawn_frost_puff(&mut commands, &budget, brick_transform.translation);
                     }
                 }
 
@@ -1331,48 +3772,86 @@ fn ball_collision(
                 brick.health -= 1;
 
                 if brick.health <= 0 {
-                    // 销毁砖块
-                    commands.entity(brick_entity).despawn();
+                    // 销毁砖块（连同装甲板）
+                    commands.safe_despawn_recursive(brick_entity);
                     
                     // 增加分数
-                    match brick.brick_type {
-                        BrickType::Normal => score.0 += 10,
-                        BrickType::Hard => score.0 += 20,
-                        _ => {}
-                    }
+                    let points = curse.apply(hot_team.apply(
+                        brick.points() * bonus.score_multiplier() * chain.score_multiplier(),
+                        tint,
+                    ));
+                    score.0 += points;
 
                     // 生成粒子效果
-                    spawn_particles(&mut commands, brick_transform.translation, brick_transform.scale);
-
-                    // 概率生成道具
-                    if rand::thread_rng().gen_bool(0.2) {
-                        spawn_powerup(&mut commands, brick_transform.translation);
+                    spawn_particles(&mut commands, &budget, brick_transform.translation, brick_transform.scale);
+                    gameplay_events.send(GameplayEvent::BrickDestroyed {
+                        brick_type: brick.brick_type,
+                        points,
+                        position: brick_transform.translation,
+                        combo: HazardRules::counts_for_combo(contact.as_ref(), &play_clock),
+                    });
+
+                    // 保底道具或概率生成道具
+                    drop_powerup(&mut commands, &mut game_rng, &brick, brick_transform.translation, &event_run, &curse);
+                    // 爆炸砖和火球都会波及周围的砖块；火球只波及一轮，除非又炸到爆炸砖
+                    let combo = HazardRules::counts_for_combo(contact.as_ref(), &play_clock);
+                    if brick.brick_type == BrickType::Explosive {
+                        explosions.send(Explosion::brick(brick_transform.translation, combo, ExplosionSource::Ball(ball_entity)));
+                    } else if power_effects.fireball {
+                        explosions.send(Explosion::fireball(brick_transform.translation, combo, ball_entity));
                     }
                 } else {
-                    // 更新砖块颜色表示受损
-                    sprite.color = Color::rgb(
-                        sprite.color.r() * 0.8,
-                        sprite.color.g() * 0.8,
-                        sprite.color.b() * 0.8,
-                    );
+                    gameplay_events.send(GameplayEvent::BrickDamaged {
+                        position: brick_transform.translation,
+                    });
+                    // 砖块变暗表示受损
+                    brick.shade *= 0.8;
                 }
 
                 break;
             }
         }
+
     }
     
-    // 删除需要移除的球
+    // 删除需要移除的球（连同外观花纹）
     for entity in balls_to_remove {
-        commands.entity(entity).despawn();
+        commands.safe_despawn_recursive(entity);
+    }
+}
+
+// 下落的球在本帧穿过挡板顶面时，若穿过点位于挡板外侧 grace 像素以内，
+// 返回所在一侧（-1.0 左，1.0 右）；正常命中或完全错过时返回 None
+fn edge_save_side(previous: Vec2, current: Vec2, paddle: Vec2, paddle_width: f32, grace: f32) -> Option<f32> {
+    if grace <= 0.0 {
+        return None;
+    }
+
+    // 比较球底边与挡板顶面
+    let plane = paddle.y + PADDLE_SIZE.y / 2.0;
+    let previous_bottom = previous.y - BALL_SIZE.y / 2.0;
+    let current_bottom = current.y - BALL_SIZE.y / 2.0;
+    if previous_bottom < plane || current_bottom >= plane {
+        return None;
+    }
+
+    // 穿过平面时的 x 坐标
+    let t = (previous_bottom - plane) / (previous_bottom - current_bottom);
+    let crossing_x = previous.x + (current.x - previous.x) * t;
+    let offset = crossing_x - paddle.x;
+    let outside = offset.abs() - (paddle_width + BALL_SIZE.x) / 2.0;
+    if outside > 0.0 && outside <= grace {
+        Some(offset.signum())
+    } else {
+        None
     }
 }
 
-// 生成粒子效果
-fn spawn_particles(commands: &mut Commands, position: Vec3, scale: Vec3) {
+// 生成粒子效果，数量由当前的特效预算决定
+fn spawn_particles(commands: &mut Commands, budget: &EffectBudget, position: Vec3, scale: Vec3) {
     let mut rng = rand::thread_rng();
     
-    for _ in 0..10 {
+    for _ in 0..budget.particle_count(EffectKind::Burst, 10) {
         let velocity = Vec2::new(
             rng.gen_range(-200.0..200.0),
             rng.gen_range(-200.0..200.0),
@@ -1404,48 +3883,162 @@ fn spawn_particles(commands: &mut Commands, position: Vec3, scale: Vec3) {
     }
 }
 
-// 粒子系统更新
-fn particle_system(
-    mut commands: Commands,
-    mut particles: Query<(Entity, &mut Transform, &mut Particle, &mut Sprite)>,
-    time: Res<Time>,
-) {
-    for (entity, mut transform, mut particle, mut sprite) in particles.iter_mut() {
-        particle.lifetime -= time.delta_seconds();
-        
-        if particle.lifetime <= 0.0 {
-            commands.entity(entity).despawn();
-        } else {
-            transform.translation += particle.velocity.extend(0.0) * time.delta_seconds();
-            transform.scale *= 0.95;
-            sprite.color.set_a(particle.lifetime);
-        }
-    }
+// 冰砖反弹时的一小团霜雾
+fn spawn_frost_puff(commands: &mut Commands, budget: &EffectBudget, position: Vec3) {
+    let mut rng = rand::thread_rng();
+
+    for _ in 0..budget.particle_count(EffectKind::Minor, 6) {
+        let velocity = Vec2::new(
+            rng.gen_range(-80.0..80.0),
+            rng.gen_range(-80.0..80.0),
+        );
+
+        commands.spawn((
+            SpriteBundle {
+                sprite: Sprite {
+                    color: Color::rgba(0.85, 0.95, 1.0, 0.8),
+                    ..default()
+                },
+                transform: Transform {
+                    translation: position,
+                    scale: Vec3::new(6.0, 6.0, 1.0),
+                    ..default()
+                },
+                ..default()
+            },
+            Particle {
+                velocity,
+                lifetime: 0.4,
+            },
+            GameEntity,
+        ));
+    }
 }
 
-// 生成道具
-fn spawn_powerup(commands: &mut Commands, position: Vec3) {
+// 护符抵消不利道具时在挡板上碎开
+fn spawn_ward_shatter(commands: &mut Commands, budget: &EffectBudget, position: Vec3) {
     let mut rng = rand::thread_rng();
-    
-    let power_type = match rng.gen_range(0..7) {
+
+    for _ in 0..budget.particle_count(EffectKind::Burst, 12) {
+        let angle = rng.gen_range(0.0..std::f32::consts::PI);
+        let velocity = Vec2::from_angle(angle) * rng.gen_range(120.0..260.0);
+
+        commands.spawn((
+            SpriteBundle {
+                sprite: Sprite {
+                    color: WARD_COLOR,
+                    ..default()
+                },
+                transform: Transform {
+                    translation: position,
+                    rotation: Quat::from_rotation_z(angle),
+                    scale: Vec3::new(10.0, 4.0, 1.0),
+                },
+                ..default()
+            },
+            Particle {
+                velocity,
+                lifetime: 0.6,
+            },
+            GameEntity,
+        ));
+    }
+}
+
+// 粒子系统更新
+fn particle_system(
+    mut commands: Commands,
+    mut particles: Query<(Entity, &mut Transform, &mut Particle, &mut Sprite)>,
+    time: Res<Time>,
+    time_scale: Res<TimeScale>,
+) {
+    perf::perf_scope!("particle_system");
+    let delta = time_scale.delta(&time);
+    for (entity, mut transform, mut particle, mut sprite) in particles.iter_mut() {
+        particle.lifetime -= delta;
+        
+        if particle.lifetime <= 0.0 {
+            commands.safe_despawn(entity);
+        } else {
+            transform.translation += particle.velocity.extend(0.0) * delta;
+            transform.scale *= 0.95;
+            sprite.color.set_a(particle.lifetime);
+        }
+    }
+}
+
+// 砖块被打碎时的掉落：保底砖块一定掉落指定道具，其余按概率（活动和诅咒可能调整）随机掉落
+fn drop_powerup(
+    commands: &mut Commands,
+    rng: &mut GameRng,
+    brick: &Brick,
+    position: Vec3,
+    event_run: &EventRun,
+    curse: &Curse,
+) {
+    if drop_certain_powerup(commands, rng, brick, position, event_run.lasers_enabled()) {
+        return;
+    }
+    if rng.gen_bool(curse.drop_chance(event_run.drop_chance(POWERUP_DROP_CHANCE))) {
+        spawn_powerup(commands, rng, position, event_run.lasers_enabled(), false);
+    }
+}
+
+// 一定掉落的道具：保底道具砖掉落指定的道具，金砖掉落一个随机的有利道具；掉落了返回 true
+fn drop_certain_powerup(
+    commands: &mut Commands,
+    rng: &mut GameRng,
+    brick: &Brick,
+    position: Vec3,
+    lasers: bool,
+) -> bool {
+    if let Some(power_type) = brick.guaranteed_drop {
+        spawn_powerup_of(commands, rng, position, power_type);
+    } else if brick.golden {
+        spawn_powerup(commands, rng, position, lasers, true);
+    } else {
+        return false;
+    }
+    true
+}
+
+// 生成随机道具；没有激光时不会抽到激光，positive_only 时不会抽到不利道具
+fn spawn_powerup(commands: &mut Commands, rng: &mut GameRng, position: Vec3, lasers: bool, positive_only: bool) {
+    let power_type = loop {
+        let power_type = random_powerup_type(rng, lasers);
+        if !(positive_only && power_type.is_negative()) {
+            break power_type;
+        }
+    };
+    spawn_powerup_of(commands, rng, position, power_type);
+}
+
+fn random_powerup_type(rng: &mut GameRng, lasers: bool) -> PowerUpType {
+    // 追踪球很强，先单独按较低的概率抽
+    if rng.gen_bool(homing::HOMING_DROP_WEIGHT) {
+        return PowerUpType::HomingBall;
+    }
+    let kinds = if lasers { 10 } else { 9 };
+    match rng.gen_range(0..kinds) {
         0 => PowerUpType::PaddleExpand,
         1 => PowerUpType::PaddleShrink,
         2 => PowerUpType::BallSpeedUp,
         3 => PowerUpType::BallSpeedDown,
         4 => PowerUpType::MultiBall,
         5 => PowerUpType::PenetratingBall,
+        6 => PowerUpType::Barrier,
+        7 => PowerUpType::SlowMotion,
+        8 => PowerUpType::Fireball,
         _ => PowerUpType::LaserGun,
-    };
+    }
+}
 
-    let color = match power_type {
-        PowerUpType::PaddleExpand => Color::rgb(0.2, 0.8, 0.2),
-        PowerUpType::PaddleShrink => Color::rgb(0.8, 0.2, 0.2),
-        PowerUpType::BallSpeedUp => Color::rgb(0.8, 0.8, 0.2),
-        PowerUpType::BallSpeedDown => Color::rgb(0.2, 0.2, 0.8),
-        PowerUpType::MultiBall => Color::rgb(0.8, 0.2, 0.8),
-        PowerUpType::PenetratingBall => Color::rgb(0.8, 0.5, 0.2),
-        PowerUpType::LaserGun => Color::rgb(0.2, 0.8, 0.8),
-    };
+// 生成指定类型的道具
+fn spawn_powerup_of(commands: &mut Commands, rng: &mut GameRng, position: Vec3, power_type: PowerUpType) {
+    let color = power_type.color();
+
+    let (motion, velocity) = DropMotion::roll(power_type, rng);
+    debug!("Spawned {} power-up with {:?} motion", power_type.name(), motion);
 
     commands.spawn((
         SpriteBundle {
@@ -1455,14 +4048,16 @@ fn spawn_powerup(commands: &mut Commands, position: Vec3) {
             },
             transform: Transform {
                 translation: position,
-                scale: Vec3::new(30.0, 15.0, 1.0),
+                scale: POWERUP_SIZE.extend(1.0),
                 ..default()
             },
             ..default()
         },
         PowerUp {
             power_type,
-            velocity: Vec2::new(0.0, -150.0),
+            velocity,
+            motion,
+            age: 0.0,
         },
         GameEntity,
     ));
@@ -1471,16 +4066,75 @@ fn spawn_powerup(commands: &mut Commands, position: Vec3) {
 // 道具移动
 fn powerup_movement(
     mut commands: Commands,
-    mut powerups: Query<(Entity, &mut Transform, &PowerUp)>,
+    mut powerups: Query<(Entity, &mut Transform, &mut PowerUp)>,
     time: Res<Time>,
+    time_scale: Res<TimeScale>,
+    orientation: Res<Orientation>,
 ) {
-    for (entity, mut transform, powerup) in powerups.iter_mut() {
-        transform.translation += powerup.velocity.extend(0.0) * time.delta_seconds();
+    let delta = time_scale.delta(&time);
+    let boundary = WINDOW_WIDTH / 2.0 - POWERUP_SIZE.x / 2.0;
+
+    for (entity, mut transform, mut powerup) in powerups.iter_mut() {
+        powerup.age += delta;
+        if let DropMotion::Arc = powerup.motion {
+            powerup.velocity.y = (powerup.velocity.y - POWERUP_GRAVITY * delta).max(-POWERUP_MAX_FALL_SPEED);
+        }
+        // 道具总是朝挡板一侧落下
+        let velocity = orientation.apply(powerup.motion.frame_velocity(powerup.velocity, powerup.age));
+        transform.translation += velocity.extend(0.0) * delta;
+
+        // 碰到左右墙壁时反弹横向速度
+        if transform.translation.x.abs() > boundary {
+            transform.translation.x = transform.translation.x.clamp(-boundary, boundary);
+            if powerup.velocity.x * transform.translation.x > 0.0 {
+                powerup.velocity.x = -powerup.velocity.x;
+            }
+        }
         
         // 移出屏幕后删除
-        if transform.translation.y < -WINDOW_HEIGHT / 2.0 - 50.0 {
-            commands.entity(entity).despawn();
+        if transform.translation.y * orientation.sign() < -WINDOW_HEIGHT / 2.0 - 50.0 {
+            commands.safe_despawn(entity);
+        }
+    }
+}
+
+// 从一颗球分裂出 count 颗新球，方向以原速度为中心呈扇形展开
+fn split_ball(
+    commands: &mut Commands,
+    source_transform: &Transform,
+    source_ball: &Ball,
+    contact: Option<&LastPaddleContact>,
+    count: usize,
+    skin: &Skin,
+) {
+    for i in 0..count {
+        let angle = (i as f32 - (count as f32 - 1.0) / 2.0) * 0.5;
+        let new_velocity = Vec2::new(
+            source_ball.velocity.x * angle.cos() - source_ball.velocity.y * angle.sin(),
+            source_ball.velocity.x * angle.sin() + source_ball.velocity.y * angle.cos(),
+        );
+
+        let mut new_ball = commands.spawn((
+            SpriteBundle {
+                sprite: Sprite {
+                    color: skin.base,
+                    ..default()
+                },
+                transform: Transform {
+                    translation: source_transform.translation,
+                    scale: Vec3::new(BALL_SIZE.x, BALL_SIZE.y, 1.0),
+                    ..default()
+                },
+                ..default()
+            },
+            Ball { velocity: new_velocity },
+            GameEntity,
+        ));
+        // 分裂出的球继承原来那颗球的挡板接触
+        if let Some(contact) = contact {
+            new_ball.insert(*contact);
         }
+        skin.decorate(&mut new_ball);
     }
 }
 
@@ -1490,23 +4144,57 @@ fn powerup_collision(
     powerups: Query<(Entity, &Transform, &PowerUp)>,
     paddle_query: Query<&Transform, With<Paddle>>,
     mut power_effects: ResMut<PowerUpEffects>,
-    ball_query: Query<(&Transform, &Ball)>,
+    ball_query: Query<(&Transform, &Ball, Option<&LastPaddleContact>), Without<HeldBall>>,
+    mut gameplay_events: EventWriter<GameplayEvent>,
+    cosmetics: Res<Cosmetics>,
+    budget: Res<EffectBudget>,
+    mut barriers: Query<&mut Barrier>,
+    orientation: Res<Orientation>,
 ) {
-    // 安全获取挡板
-    let paddle_result = paddle_query.get_single();
-    if paddle_result.is_err() {
-        return; // 如果没有挡板，直接返回
-    }
-    let paddle_transform = paddle_result.unwrap();
+    let paddle_transform = match paddle_query.get_single() {
+        Ok(transform) => transform,
+        Err(e) => {
+            paddle::warn_paddle_query("powerup_collision", e);
+            return;
+        }
+    };
     let paddle_width = PADDLE_SIZE.x * power_effects.paddle_size_modifier;
 
     for (powerup_entity, powerup_transform, powerup) in powerups.iter() {
         if collide(
             powerup_transform.translation,
-            Vec2::new(30.0, 15.0),
+            POWERUP_SIZE,
             paddle_transform.translation,
             Vec2::new(paddle_width, PADDLE_SIZE.y),
         ).is_some() {
+            commands.safe_despawn(powerup_entity);
+
+            // 护符抵消不利道具：没有效果，也不计入拾取
+            if powerup.power_type.is_negative() && power_effects.warded {
+                power_effects.warded = false;
+                power_effects.good_streak = 0;
+                gameplay_events.send(GameplayEvent::DebuffWarded {
+                    power_type: powerup.power_type,
+                    position: powerup_transform.translation,
+                });
+                spawn_ward_shatter(&mut commands, &budget, paddle_transform.translation);
+                continue;
+            }
+            if powerup.power_type.is_negative() {
+                power_effects.good_streak = 0;
+            } else {
+                power_effects.good_streak += 1;
+                if power_effects.good_streak >= WARD_STREAK && !power_effects.warded {
+                    power_effects.good_streak = 0;
+                    power_effects.warded = true;
+                    gameplay_events.send(GameplayEvent::WardGained);
+                }
+            }
+
+            gameplay_events.send(GameplayEvent::PowerUpCollected {
+                power_type: powerup.power_type,
+                position: powerup_transform.translation,
+            });
             // 应用道具效果
             match powerup.power_type {
                 PowerUpType::PaddleExpand => {
@@ -1522,65 +4210,87 @@ fn powerup_collision(
                     power_effects.ball_speed_modifier = (power_effects.ball_speed_modifier * 0.7).max(0.5);
                 }
                 PowerUpType::MultiBall => {
-                    // 生成额外的球
-                    if let Ok((ball_transform, ball)) = ball_query.get_single() {
-                        for i in 0..2 {
-                            let angle = (i as f32 - 0.5) * 0.5;
-                            let new_velocity = Vec2::new(
-                                ball.velocity.x * angle.cos() - ball.velocity.y * angle.sin(),
-                                ball.velocity.x * angle.sin() + ball.velocity.y * angle.cos(),
-                            );
-                            
-                            commands.spawn((
-                                SpriteBundle {
-                                    sprite: Sprite {
-                                        color: BALL_COLOR,
-                                        ..default()
-                                    },
-                                    transform: Transform {
-                                        translation: ball_transform.translation,
-                                        scale: Vec3::new(BALL_SIZE.x, BALL_SIZE.y, 1.0),
-                                        ..default()
-                                    },
-                                    ..default()
-                                },
-                                Ball { velocity: new_velocity },
-                                GameEntity,
-                            ));
-                        }
+                    // 生成额外的球，数量受特效预算限制
+                    if let Ok((ball_transform, ball, contact)) = ball_query.get_single() {
+                        let count = budget.ball_splits(2, 1);
+                        split_ball(&mut commands, ball_transform, ball, contact, count, cosmetics.ball);
                     }
                 }
                 PowerUpType::PenetratingBall => {
                     power_effects.penetrating_ball = true;
-                    power_effects.penetrating_timer = 10.0;
+                    power_effects.penetrating_timer.restart(PENETRATING_DURATION);
                 }
                 PowerUpType::LaserGun => {
                     power_effects.has_laser = true;
-                    power_effects.laser_timer = 15.0;
+                    power_effects.laser_timer.restart(LASER_DURATION);
+                }
+                PowerUpType::Barrier => {
+                    barrier::raise_barrier(&mut commands, &mut barriers, *orientation);
+                }
+                PowerUpType::SlowMotion => {
+                    power_effects.slow_motion_timer.restart(SLOW_MOTION_DURATION);
+                }
+                PowerUpType::Fireball => {
+                    power_effects.fireball = true;
+                    power_effects.fireball_timer.restart(FIREBALL_DURATION);
+                }
+                PowerUpType::HomingBall => {
+                    power_effects.homing = true;
+                    power_effects.homing_timer.restart(homing::HOMING_DURATION);
                 }
             }
+        }
+    }
+}
 
-            commands.entity(powerup_entity).despawn();
+// 丢命时有利道具的连续计数清零，已经得到的护符保留到本关结束
+fn reset_ward_streak(mut gameplay_events: EventReader<GameplayEvent>, mut power_effects: ResMut<PowerUpEffects>) {
+    for event in gameplay_events.read() {
+        if matches!(event, GameplayEvent::LifeLost) {
+            power_effects.good_streak = 0;
         }
     }
 }
 
-// 更新道具计时器
+// 更新道具计时器；在 powerup_collision 之后运行，本帧拾取的刷新立即生效。
+// 计时器都按真实时间计时，慢动作不会延长道具
 fn update_powerup_timers(
     mut power_effects: ResMut<PowerUpEffects>,
+    mut time_scale: ResMut<TimeScale>,
     time: Res<Time>,
+    state: Res<State<GameState>>,
 ) {
-    if power_effects.penetrating_ball {
-        power_effects.penetrating_timer -= time.delta_seconds();
-        if power_effects.penetrating_timer <= 0.0 {
-            power_effects.penetrating_ball = false;
-        }
+    hazard_rules::assert_uses_game_time!(state, "update_powerup_timers");
+    let delta = time.delta_seconds();
+    if power_effects.penetrating_timer.tick(delta) {
+        power_effects.penetrating_ball = false;
+    }
+    if power_effects.laser_timer.tick(delta) {
+        power_effects.has_laser = false;
     }
+    if power_effects.fireball_timer.tick(delta) {
+        power_effects.fireball = false;
+    }
+    if power_effects.homing_timer.tick(delta) {
+        power_effects.homing = false;
+    }
+    power_effects.slow_motion_timer.tick(delta);
+    let scale = if power_effects.slow_motion_timer.remaining() > 0.0 { SLOW_MOTION_SCALE } else { 1.0 };
+    if time_scale.0 != scale {
+        time_scale.0 = scale;
+    }
+}
 
-    if power_effects.has_laser {
-        power_effects.laser_timer -= time.delta_seconds();
-        if power_effects.laser_timer <= 0.0 {
-            power_effects.has_laser = false;
+// 火球期间球变成橙色，结束后恢复外观颜色
+fn update_fireball_color(
+    power_effects: Res<PowerUpEffects>,
+    cosmetics: Res<Cosmetics>,
+    mut balls: Query<&mut Sprite, With<Ball>>,
+) {
+    let color = if power_effects.fireball { FIREBALL_COLOR } else { cosmetics.ball.base };
+    for mut sprite in balls.iter_mut() {
+        if sprite.color != color {
+            sprite.color = color;
         }
     }
 }
@@ -1588,14 +4298,61 @@ fn update_powerup_timers(
 // 检查胜利条件
 fn check_victory(
     bricks: Query<&Brick>,
+    (bonus, chain): (Res<BonusStage>, Res<GeneratorChain>),
+    paths: Res<ProfilePaths>,
+    thresholds: Res<MedalThresholds>,
+    mut attempt: ResMut<LevelAttempt>,
+    (level, score, difficulty_settings, play_clock, game_mode): (
+        Res<Level>,
+        Res<Score>,
+        Res<DifficultySettings>,
+        Res<PlayClock>,
+        Res<GameMode>,
+    ),
+    (boss_stage, bosses): (Res<BossStage>, Query<(), With<boss::Boss>>),
+    dissolving: Query<(), With<quota::Dissolving>>,
     mut next_state: ResMut<NextState<GameState>>,
 ) {
+    // 首领关只看首领：首领消失就过关，不管还剩多少砖块；这一关的记录已经在打完正常关卡时保存过。
+    // 普通局打败最后一关之后的首领就通关
+    if boss_stage.is_active() {
+        if bosses.is_empty() && ending::campaign_complete(level.0, *game_mode) {
+            next_state.set(GameState::GameComplete);
+        } else if bosses.is_empty() {
+            next_state.set(GameState::Victory);
+        }
+        return;
+    }
+    // 配额关达成后剩余砖块已经去掉了 Brick，等它们消散完再过关
+    if !dissolving.is_empty() {
+        return;
+    }
+
     let has_breakable_bricks = bricks.iter().any(|brick| 
         !matches!(brick.brick_type, BrickType::Unbreakable)
     );
 
-    if !has_breakable_bricks {
-        next_state.set(GameState::Victory);
+    // 护盾发生器关全部发生器被摧毁后，打完剩余砖块或奖励时间用完都算过关
+    if !has_breakable_bricks || chain.window_expired() {
+        // 奖励关提前打完也直接进入下一关
+        if bonus.is_active() {
+            next_state.set(GameState::NextLevel);
+            return;
+        }
+        // 记录这一关的最好成绩和奖牌；淘汰赛是别的玩家在打，不计入档案
+        if *game_mode != GameMode::Tournament {
+            level_select::record_level_clear(
+                &paths,
+                &thresholds,
+                &mut attempt,
+                level.0,
+                difficulty_settings.difficulty,
+                score.0,
+                play_clock.level_time(),
+                *game_mode,
+            );
+        }
+        next_state.set(game_mode.clear_state());
     }
 }
 
@@ -1604,11 +4361,52 @@ fn cleanup_game(
     mut commands: Commands,
     entities: Query<Entity, With<GameEntity>>,
     mut game_initialized: ResMut<GameInitialized>,
+    mut state_buffer: ResMut<RollingStateBuffer>,
 ) {
     for entity in entities.iter() {
-        commands.entity(entity).despawn_recursive();
+        commands.safe_despawn_recursive(entity);
     }
     game_initialized.0 = false;
+    // 回放缓冲区只属于当前这一关
+    state_buffer.clear();
+}
+
+// 游戏结束菜单
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum GameOverAction {
+    Retry,
+    RetrySeed,
+    ChangeDifficulty,
+    Leaderboard,
+    MainMenu,
+}
+
+impl GameOverAction {
+    const ALL: [GameOverAction; 5] = [
+        GameOverAction::Retry,
+        GameOverAction::RetrySeed,
+        GameOverAction::ChangeDifficulty,
+        GameOverAction::Leaderboard,
+        GameOverAction::MainMenu,
+    ];
+
+    fn label_key(&self) -> &'static str {
+        match self {
+            GameOverAction::Retry => "gameover.retry",
+            GameOverAction::RetrySeed => "gameover.retry_seed",
+            GameOverAction::ChangeDifficulty => "gameover.change_difficulty",
+            GameOverAction::Leaderboard => "gameover.leaderboard",
+            GameOverAction::MainMenu => "gameover.main_menu",
+        }
+    }
+
+    // 当前可用的选项；展台模式固定难度，不能更换
+    fn available(kiosk: &KioskConfig) -> Vec<GameOverAction> {
+        GameOverAction::ALL
+            .into_iter()
+            .filter(|action| !(kiosk.enabled && *action == GameOverAction::ChangeDifficulty))
+            .collect()
+    }
 }
 
 // 游戏结束界面
@@ -1618,21 +4416,71 @@ fn setup_game_over(
     level: Res<Level>,
     difficulty_settings: Res<DifficultySettings>,
     player_name: Res<PlayerName>,
-    api_client: Res<ApiClientResource>,
+    network: Res<Network>,
+    run_seed: Res<RunSeed>,
+    recorder: Res<ReplayRecorder>,
+    stats: Res<RunStats>,
+    fonts: Res<FontAssets>,
+    locale: Res<Locale>,
+    board_snapshot: Res<BoardSnapshot>,
+    paths: Res<ProfilePaths>,
+    (kiosk, daily_run, event_run, insurance, settings): (
+        Res<KioskConfig>,
+        Res<DailyRun>,
+        Res<EventRun>,
+        Res<Insurance>,
+        Res<Settings>,
+    ),
+    mut run_submitted: ResMut<RunSubmitted>,
+    mut menu_state: ResMut<GameOverMenuState>,
 ) {
-    let difficulty_text = match difficulty_settings.difficulty {
-        Difficulty::Easy => "Easy",
-        Difficulty::Medium => "Medium",
-        Difficulty::Hard => "Hard",
-    };
+    let difficulty = difficulty_settings.difficulty;
+    let coins_earned = insurance::coins_for_score(score.0) + insurance::refund(insurance.0);
+
+    // 每局只结算一次：保存幽灵轨迹、累加档案并提交分数
+    let already_submitted = run_submitted.0;
+    let mut status_key = "gameover.already_submitted";
+    if !already_submitted {
+        run_submitted.0 = true;
+        replay::save_ghost_if_best(&paths, &recorder, &run_seed, &score, &difficulty_settings);
+        profile::record_run(&paths, &stats, score.0, level.0, difficulty);
+        insurance::settle_run(&paths, score.0, *insurance);
+        // 每日挑战只提交比当天本机最好成绩更高的分数；只保存在本机时不提交
+        let submission_name = settings.submission_name(&player_name.0);
+        if !daily::should_submit(&paths, &daily_run, score.0) {
+            status_key = "gameover.daily_not_improved";
+        } else if let Some(submission_name) = submission_name {
+            status_key = "gameover.submitting";
+            network.send(ApiCommand::SubmitScore(CreateScoreRequest {
+                player_name: submission_name,
+                score: score.0,
+                level: level.0,
+                difficulty: difficulty.name().to_string(),
+                idempotency_key: None,
+                venue: kiosk.venue.clone(),
+                mode: match (&daily_run.0, &event_run.0) {
+                    (Some(_), _) => Some(daily::DAILY_MODE.to_string()),
+                    (None, Some(_)) => Some(weekly_event::EVENT_MODE.to_string()),
+                    (None, None) => None,
+                },
+                seed: daily_run.0.map(|_| run_seed.0),
+                daily_date: daily_run.0.map(daily::date_key),
+                event_id: event_run.0.as_ref().map(|event| event.id.clone()),
+                tournament_id: None,
+                abandoned: false,
+                assisted: stats.insurance_bought > 0,
+                curse: stats.curse.clone(),
+            }));
+        } else {
+            status_key = "gameover.local_only";
+        }
+    }
+    // 关卡分布每次进入都重新获取
+    network.send(ApiCommand::GetLevelHistogram {
+        difficulty: difficulty.name().to_string(),
+    });
 
-    // 提交分数到服务器
-    api_client.0.submit_score_async(
-        player_name.0.clone(),
-        score.0,
-        level.0,
-        difficulty_text.to_string(),
-    );
+    menu_state.selected = 0;
 
     commands
         .spawn((
@@ -1652,92 +4500,289 @@ fn setup_game_over(
         ))
         .with_children(|parent| {
             parent.spawn(TextBundle::from_section(
-                "GAME OVER",
-                TextStyle {
-                    font_size: 60.0,
-                    color: Color::rgb(0.8, 0.2, 0.2),
-                    ..default()
-                },
+                locale.tr("gameover.title", &[]),
+                text_style(&fonts, FontKind::Bold, 60.0, Color::rgb(0.8, 0.2, 0.2)),
             ));
-            
-            parent.spawn(TextBundle::from_section(
-                format!("{}'s Score: {} ({})", player_name.0, score.0, difficulty_text.to_uppercase()),
-                TextStyle {
-                    font_size: 40.0,
-                    color: Color::WHITE,
-                    ..default()
-                },
-            ).with_style(Style {
-                margin: UiRect::top(Val::Px(30.0)),
-                ..default()
-            }));
 
-            parent.spawn(TextBundle::from_section(
-                "Score submitted to leaderboard!",
-                TextStyle {
-                    font_size: 20.0,
-                    color: Color::rgb(0.2, 0.8, 0.2),
+            // 左侧本局总结，右侧菜单
+            parent
+                .spawn(NodeBundle {
+                    style: Style {
+                        flex_direction: FlexDirection::Row,
+                        align_items: AlignItems::Center,
+                        column_gap: Val::Px(40.0),
+                        margin: UiRect::top(Val::Px(30.0)),
+                        ..default()
+                    },
                     ..default()
-                },
-            ).with_style(Style {
-                margin: UiRect::top(Val::Px(20.0)),
-                ..default()
-            }));
+                })
+                .with_children(|parent| {
+                    parent
+                        .spawn(NodeBundle {
+                            style: Style {
+                                flex_direction: FlexDirection::Column,
+                                width: Val::Px(280.0),
+                                padding: UiRect::all(Val::Px(15.0)),
+                                row_gap: Val::Px(8.0),
+                                ..default()
+                            },
+                            background_color: BackgroundColor(Color::rgba(0.2, 0.2, 0.25, 0.8)),
+                            ..default()
+                        })
+                        .with_children(|parent| {
+                            parent.spawn(TextBundle::from_section(
+                                locale.tr("gameover.run_title", &[("name", &player_name.0)]),
+                                text_style(&fonts, FontKind::Bold, 25.0, Color::WHITE),
+                            ));
 
-            parent.spawn(TextBundle::from_section(
-                "Press SPACE to return to menu",
-                TextStyle {
-                    font_size: 25.0,
-                    color: Color::rgb(0.7, 0.7, 0.7),
-                    ..default()
-                },
-            ).with_style(Style {
-                margin: UiRect::top(Val::Px(40.0)),
-                ..default()
-            }));
+                            let rows = [
+                                ("gameover.score", score.0.to_string(), Color::WHITE),
+                                (
+                                    "gameover.difficulty",
+                                    locale.tr(&name_key("difficulty", difficulty.name()), &[]).to_uppercase(),
+                                    difficulty.color(),
+                                ),
+                                ("gameover.level_reached", level.0.to_string(), Color::WHITE),
+                                ("gameover.seed", format!("{:016X}", run_seed.0), Color::rgb(0.6, 0.6, 0.6)),
+                                ("gameover.coins", format!("+{}", coins_earned), Color::rgb(1.0, 0.8, 0.3)),
+                            ];
+                            for (label, value, color) in rows {
+                                parent
+                                    .spawn(NodeBundle {
+                                        style: Style {
+                                            justify_content: JustifyContent::SpaceBetween,
+                                            ..default()
+                                        },
+                                        ..default()
+                                    })
+                                    .with_children(|parent| {
+                                        parent.spawn(TextBundle::from_section(
+                                            locale.tr(label, &[]),
+                                            text_style(&fonts, FontKind::Regular, 18.0, Color::rgb(0.7, 0.7, 0.7)),
+                                        ));
+                                        parent.spawn(TextBundle::from_section(
+                                            value,
+                                            text_style(&fonts, FontKind::Mono, 18.0, color),
+                                        ));
+                                    });
+                            }
+
+                            // 结束时的棋盘
+                            parent.spawn(TextBundle::from_section(
+                                locale.tr("gameover.final_board", &[]),
+                                text_style(&fonts, FontKind::Regular, 16.0, Color::rgb(0.7, 0.7, 0.7)),
+                            ).with_style(Style {
+                                margin: UiRect::top(Val::Px(6.0)),
+                                ..default()
+                            }));
+                            board_snapshot::spawn_board_diagram(parent, &board_snapshot);
+
+                            parent.spawn((
+                                TextBundle::from_section(
+                                    locale.tr(status_key, &[]),
+                                    text_style(&fonts, FontKind::Regular, 16.0, Color::rgb(0.7, 0.7, 0.7)),
+                                ).with_style(Style {
+                                    margin: UiRect::top(Val::Px(10.0)),
+                                    ..default()
+                                }),
+                                SubmitStatusText,
+                            ));
+
+                            // 提交返回名次后填入
+                            parent.spawn((
+                                TextBundle::from_section(
+                                    "",
+                                    text_style(&fonts, FontKind::Bold, 18.0, Color::WHITE),
+                                ),
+                                RankText,
+                            ));
+
+                            // 关卡分布返回后填入
+                            parent.spawn((
+                                TextBundle::from_section(
+                                    "",
+                                    text_style(&fonts, FontKind::Regular, 16.0, Color::rgb(0.5, 0.7, 0.9)),
+                                ),
+                                LevelPercentileText,
+                            ));
+                        });
+
+                    parent
+                        .spawn(NodeBundle {
+                            style: Style {
+                                flex_direction: FlexDirection::Column,
+                                row_gap: Val::Px(10.0),
+                                ..default()
+                            },
+                            ..default()
+                        })
+                        .with_children(|parent| {
+                            for (index, action) in GameOverAction::available(&kiosk).iter().enumerate() {
+                                parent
+                                    .spawn((
+                                        ButtonBundle {
+                                            style: Style {
+                                                width: Val::Px(260.0),
+                                                padding: UiRect::all(Val::Px(8.0)),
+                                                border: UiRect::all(Val::Px(2.0)),
+                                                justify_content: JustifyContent::Center,
+                                                ..default()
+                                            },
+                                            background_color: BackgroundColor(Color::rgba(0.2, 0.2, 0.25, 0.8)),
+                                            border_color: BorderColor(Color::NONE),
+                                            ..default()
+                                        },
+                                        GameOverOption(index),
+                                    ))
+                                    .with_children(|parent| {
+                                        parent.spawn(TextBundle::from_section(
+                                            locale.tr(action.label_key(), &[]),
+                                            text_style(&fonts, FontKind::Regular, 22.0, Color::WHITE),
+                                        ));
+                                    });
+                            }
+                        });
+                });
 
             parent.spawn(TextBundle::from_section(
-                "Press L to view leaderboard",
-                TextStyle {
-                    font_size: 20.0,
-                    color: Color::rgb(0.5, 0.7, 0.9),
-                    ..default()
-                },
+                locale.tr("gameover.hint", &[]),
+                text_style(&fonts, FontKind::Regular, 18.0, Color::rgb(0.5, 0.5, 0.5)),
             ).with_style(Style {
-                margin: UiRect::top(Val::Px(15.0)),
+                margin: UiRect::top(Val::Px(30.0)),
                 ..default()
             }));
         });
 }
 
+// 处理分数提交结果和关卡分布
+fn game_over_responses(
+    mut responses: EventReader<ApiResponse>,
+    level: Res<Level>,
+    locale: Res<Locale>,
+    server_status: Res<ServerStatus>,
+    mut status_query: Query<&mut Text, (With<SubmitStatusText>, Without<LevelPercentileText>)>,
+    mut percentile_query: Query<&mut Text, (With<LevelPercentileText>, Without<SubmitStatusText>)>,
+) {
+    for response in responses.read() {
+        match response {
+            ApiResponse::ScoreSubmitted(result) => {
+                let (message, color) = match result {
+                    // 服务器配置了公开地址时附上分享链接
+                    Ok(score) => {
+                        let message = match &score.share_url {
+                            Some(url) => locale.tr("gameover.submitted_share", &[("url", url)]),
+                            None => locale.tr("gameover.submitted", &[]),
+                        };
+                        (message, Color::rgb(0.2, 0.8, 0.2))
+                    }
+                    Err(e) => {
+                        eprintln!("Error submitting score: {}", e);
+                        let message_key = if *server_status == ServerStatus::BackingOff {
+                            "gameover.submit_backing_off"
+                        } else {
+                            "gameover.submit_failed"
+                        };
+                        (locale.tr(message_key, &[]), Color::rgb(0.8, 0.2, 0.2))
+                    }
+                };
+                for mut text in status_query.iter_mut() {
+                    text.sections[0].value = message.clone();
+                    text.sections[0].style.color = color;
+                }
+            }
+            // 获取失败或样本太少时不显示
+            ApiResponse::LevelHistogram(Ok(histogram)) => {
+                if let Some(percentage) = histogram.beaten_percentage(level.0) {
+                    for mut text in percentile_query.iter_mut() {
+                        text.sections[0].value = locale.tr("gameover.percentile", &[("percent", &format!("{:.0}", percentage))]);
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
 // 游戏结束系统
 fn game_over_system(
     keyboard_input: Res<ButtonInput<KeyCode>>,
+    gamepad: GamepadButtons,
     mut next_state: ResMut<NextState<GameState>>,
-    mut level: ResMut<Level>,
-    mut power_effects: ResMut<PowerUpEffects>,
-    mut lives: ResMut<Lives>,
-    difficulty_settings: Res<DifficultySettings>,
+    mut run: RunResources,
+    mut menu_state: ResMut<GameOverMenuState>,
+    kiosk: Res<KioskConfig>,
+    mut option_query: Query<(Ref<Interaction>, &GameOverOption, &mut BorderColor)>,
 ) {
-    if keyboard_input.just_pressed(KeyCode::Space) {
-        level.0 = 1;
-        *power_effects = PowerUpEffects::default();
-        lives.0 = difficulty_settings.lives;
-        next_state.set(GameState::MainMenu);
-    } else if keyboard_input.just_pressed(KeyCode::KeyL) {
-        next_state.set(GameState::Leaderboard);
+    let actions = GameOverAction::available(&kiosk);
+    let option_count = actions.len();
+    let mut chosen = None;
+
+    // 手柄：十字键选择，南键确认
+    if keyboard_input.just_pressed(KeyCode::ArrowUp) || gamepad.just_pressed(GamepadButtonType::DPadUp) {
+        menu_state.selected = (menu_state.selected + option_count - 1) % option_count;
+    } else if keyboard_input.just_pressed(KeyCode::ArrowDown) || gamepad.just_pressed(GamepadButtonType::DPadDown) {
+        menu_state.selected = (menu_state.selected + 1) % option_count;
+    } else if keyboard_input.just_pressed(KeyCode::Enter)
+        || keyboard_input.just_pressed(KeyCode::Space)
+        || gamepad.just_pressed(GamepadButtonType::South)
+    {
+        chosen = Some(menu_state.selected);
     }
-}
 
-// 清理游戏结束界面
-fn cleanup_game_over(mut commands: Commands, query: Query<Entity, With<GameOverUI>>) {
-    for entity in query.iter() {
-        commands.entity(entity).despawn_recursive();
+    // 鼠标悬停高亮，点击选择；悬停只在刚移上去时改选择，停着不动的鼠标不会抢走键盘和手柄的选择
+    for (interaction, option, _) in option_query.iter() {
+        match *interaction {
+            Interaction::Pressed => chosen = Some(option.0),
+            Interaction::Hovered if interaction.is_changed() => menu_state.selected = option.0,
+            _ => {}
+        }
+    }
+
+    for (_, option, mut border_color) in option_query.iter_mut() {
+        border_color.0 = if option.0 == menu_state.selected {
+            Color::rgb(1.0, 1.0, 0.0)
+        } else {
+            Color::NONE
+        };
+    }
+
+    let Some(index) = chosen else {
+        return;
+    };
+    match actions[index] {
+        GameOverAction::Retry => {
+            start_new_run(&mut run, None);
+            next_state.set(GameState::Playing);
+        }
+        GameOverAction::RetrySeed => {
+            let seed = run.run_seed.0;
+            start_new_run(&mut run, Some(seed));
+            next_state.set(GameState::Playing);
+        }
+        GameOverAction::ChangeDifficulty => next_state.set(GameState::DifficultySelect),
+        GameOverAction::Leaderboard => next_state.set(GameState::Leaderboard),
+        GameOverAction::MainMenu => {
+            start_new_run(&mut run, None);
+            next_state.set(GameState::MainMenu);
+        }
+    }
+}
+
+// 清理游戏结束界面
+fn cleanup_game_over(mut commands: Commands, query: Query<Entity, With<GameOverUI>>) {
+    for entity in query.iter() {
+        commands.entity(entity).despawn_recursive();
     }
 }
 
 // 胜利界面
-fn setup_victory(mut commands: Commands, score: Res<Score>, level: Res<Level>) {
+fn setup_victory(
+    mut commands: Commands,
+    score: Res<Score>,
+    level: Res<Level>,
+    fonts: Res<FontAssets>,
+    locale: Res<Locale>,
+) {
     commands
         .spawn((
             NodeBundle {
@@ -1756,45 +4801,29 @@ fn setup_victory(mut commands: Commands, score: Res<Score>, level: Res<Level>) {
         ))
         .with_children(|parent| {
             parent.spawn(TextBundle::from_section(
-                "LEVEL COMPLETE!",
-                TextStyle {
-                    font_size: 60.0,
-                    color: Color::rgb(0.2, 0.8, 0.2),
-                    ..default()
-                },
+                locale.tr("victory.title", &[]),
+                text_style(&fonts, FontKind::Bold, 60.0, Color::rgb(0.2, 0.8, 0.2)),
             ));
             
             parent.spawn(TextBundle::from_section(
-                format!("Current Score: {}", score.0),
-                TextStyle {
-                    font_size: 40.0,
-                    color: Color::WHITE,
-                    ..default()
-                },
+                locale.tr("victory.score", &[("score", &score.0)]),
+                text_style(&fonts, FontKind::Mono, 40.0, Color::WHITE),
             ).with_style(Style {
                 margin: UiRect::top(Val::Px(30.0)),
                 ..default()
             }));
 
             parent.spawn(TextBundle::from_section(
-                format!("Level {} Completed", level.0),
-                TextStyle {
-                    font_size: 30.0,
-                    color: Color::rgb(0.8, 0.8, 0.2),
-                    ..default()
-                },
+                locale.tr("victory.level_completed", &[("level", &level.0)]),
+                text_style(&fonts, FontKind::Regular, 30.0, Color::rgb(0.8, 0.8, 0.2)),
             ).with_style(Style {
                 margin: UiRect::top(Val::Px(20.0)),
                 ..default()
             }));
 
             parent.spawn(TextBundle::from_section(
-                "Press SPACE for next level",
-                TextStyle {
-                    font_size: 25.0,
-                    color: Color::rgb(0.7, 0.7, 0.7),
-                    ..default()
-                },
+                locale.tr("victory.next", &[]),
+                text_style(&fonts, FontKind::Regular, 25.0, Color::rgb(0.7, 0.7, 0.7)),
             ).with_style(Style {
                 margin: UiRect::top(Val::Px(50.0)),
                 ..default()
@@ -1825,25 +4854,58 @@ fn next_level_setup(
     mut next_state: ResMut<NextState<GameState>>,
     mut power_effects: ResMut<PowerUpEffects>,
     mut game_initialized: ResMut<GameInitialized>,
+    mut bonus: ResMut<BonusStage>,
+    mut boss: ResMut<BossStage>,
+    mut orientation: ResMut<Orientation>,
+    game_mode: Res<GameMode>,
+    curse: Res<Curse>,
 ) {
-    level.0 += 1;
-    *power_effects = PowerUpEffects::default();
+    *power_effects = PowerUpEffects::with_curse(&curse);
     game_initialized.0 = false;  // 重置初始化状态
-    next_state.set(GameState::Playing);
+
+    // 每隔几关先迎战首领（在奖励关之前），同样不显示预览，关卡数不变
+    if boss.is_active() {
+        boss.finish();
+    } else if !bonus.is_active() && BossStage::due_after(level.0) {
+        boss.start();
+        *orientation = Orientation::Normal;
+        next_state.set(GameState::Playing);
+        return;
+    }
+
+    // 每隔几关先进入上下翻转的奖励关，不显示预览，关卡数不变
+    if !bonus.is_active() && BonusStage::due_after(level.0) {
+        bonus.start();
+        *orientation = Orientation::Flipped;
+        next_state.set(GameState::Playing);
+        return;
+    }
+    bonus.finish();
+    *orientation = Orientation::Normal;
+
+    level.0 += 1;
+    // 普通局打到第三关前先选择诅咒，之后同样显示预览
+    if level.0 == curse::CURSE_LEVEL && *game_mode == GameMode::Campaign {
+        next_state.set(GameState::CurseChoice);
+        return;
+    }
+    // 先显示下一关的预览
+    next_state.set(GameState::LevelInterlude);
 }
 
 // 暂停游戏输入检测
 fn pause_game_input(
     keyboard_input: Res<ButtonInput<KeyCode>>,
+    gamepad: GamepadButtons,
     mut next_state: ResMut<NextState<GameState>>,
 ) {
-    if keyboard_input.just_pressed(KeyCode::Escape) {
+    if keyboard_input.just_pressed(KeyCode::Escape) || gamepad.just_pressed(GamepadButtonType::Start) {
         next_state.set(GameState::Paused);
     }
 }
 
 // 设置暂停菜单
-fn setup_pause_menu(mut commands: Commands) {
+fn setup_pause_menu(mut commands: Commands, fonts: Res<FontAssets>, locale: Res<Locale>) {
     commands
         .spawn((
             NodeBundle {
@@ -1863,57 +4925,37 @@ fn setup_pause_menu(mut commands: Commands) {
         ))
         .with_children(|parent| {
             parent.spawn(TextBundle::from_section(
-                "PAUSED",
-                TextStyle {
-                    font_size: 80.0,
-                    color: Color::WHITE,
-                    ..default()
-                },
+                locale.tr("pause.title", &[]),
+                text_style(&fonts, FontKind::Bold, 80.0, Color::WHITE),
             ));
 
             parent.spawn(TextBundle::from_section(
-                "[R] Resume Game",
-                TextStyle {
-                    font_size: 30.0,
-                    color: Color::rgb(0.2, 0.8, 0.2),
-                    ..default()
-                },
+                locale.tr("pause.resume", &[]),
+                text_style(&fonts, FontKind::Regular, 30.0, Color::rgb(0.2, 0.8, 0.2)),
             ).with_style(Style {
                 margin: UiRect::top(Val::Px(50.0)),
                 ..default()
             }));
 
             parent.spawn(TextBundle::from_section(
-                "[N] New Game",
-                TextStyle {
-                    font_size: 30.0,
-                    color: Color::rgb(0.8, 0.8, 0.2),
-                    ..default()
-                },
+                locale.tr("pause.new_game", &[]),
+                text_style(&fonts, FontKind::Regular, 30.0, Color::rgb(0.8, 0.8, 0.2)),
             ).with_style(Style {
                 margin: UiRect::top(Val::Px(20.0)),
                 ..default()
             }));
 
             parent.spawn(TextBundle::from_section(
-                "[M] Main Menu",
-                TextStyle {
-                    font_size: 30.0,
-                    color: Color::rgb(0.8, 0.2, 0.2),
-                    ..default()
-                },
+                locale.tr("pause.main_menu", &[]),
+                text_style(&fonts, FontKind::Regular, 30.0, Color::rgb(0.8, 0.2, 0.2)),
             ).with_style(Style {
                 margin: UiRect::top(Val::Px(20.0)),
                 ..default()
             }));
 
             parent.spawn(TextBundle::from_section(
-                "Press ESC to resume",
-                TextStyle {
-                    font_size: 20.0,
-                    color: Color::rgb(0.6, 0.6, 0.6),
-                    ..default()
-                },
+                locale.tr("pause.hint", &[]),
+                text_style(&fonts, FontKind::Regular, 20.0, Color::rgb(0.6, 0.6, 0.6)),
             ).with_style(Style {
                 margin: UiRect::top(Val::Px(50.0)),
                 ..default()
@@ -1924,42 +4966,48 @@ fn setup_pause_menu(mut commands: Commands) {
 // 暂停菜单系统
 fn pause_menu_system(
     keyboard_input: Res<ButtonInput<KeyCode>>,
+    gamepad: GamepadButtons,
     mut next_state: ResMut<NextState<GameState>>,
-    mut level: ResMut<Level>,
-    mut score: ResMut<Score>,
-    mut lives: ResMut<Lives>,
-    mut power_effects: ResMut<PowerUpEffects>,
-    difficulty_settings: Res<DifficultySettings>,
+    mut run: RunResources,
     mut commands: Commands,
     game_entities: Query<Entity, With<GameEntity>>,
-    mut game_initialized: ResMut<GameInitialized>,
+    (mut resume_kind, mut virtual_time): (ResMut<ResumeKind>, ResMut<Time<Virtual>>),
 ) {
-    if keyboard_input.just_pressed(KeyCode::Escape) || keyboard_input.just_pressed(KeyCode::KeyR) {
+    // 手柄：START 或 B 继续，Y 重新开始，BACK 返回主菜单
+    if keyboard_input.just_pressed(KeyCode::Escape)
+        || keyboard_input.just_pressed(KeyCode::KeyR)
+        || gamepad.just_pressed(GamepadButtonType::Start)
+        || gamepad.just_pressed(GamepadButtonType::East)
+    {
         // 继续游戏
+        resume::continue_from_pause(&mut resume_kind, &mut virtual_time);
         next_state.set(GameState::Playing);
-    } else if keyboard_input.just_pressed(KeyCode::KeyN) {
-        // 重新开始游戏 - 先清理现有游戏实体
+    } else if (keyboard_input.just_pressed(KeyCode::KeyN) || gamepad.just_pressed(GamepadButtonType::North))
+        && *run.game_mode != GameMode::Race
+    {
+        // 重新开始游戏（和朋友比赛时不能重来） - 先清理现有游戏实体
         for entity in game_entities.iter() {
             commands.entity(entity).despawn_recursive();
         }
         
-        level.0 = 1;
-        score.0 = 0;
-        lives.0 = difficulty_settings.lives;
-        *power_effects = PowerUpEffects::default();
-        game_initialized.0 = false;  // 重置初始化状态
+        // 单关挑战重新开始同一关
+        let (level, seed) = (run.level.0, run.run_seed.0);
+        match *run.game_mode {
+            GameMode::SingleLevel => level_select::start_level_run(&mut run, level),
+            // 淘汰赛重新开始同一局，种子不变
+            GameMode::Tournament => tournament::start_turn(&mut run, level, seed),
+            GameMode::Campaign => start_new_run(&mut run, None),
+            // 上面已经排除
+            GameMode::Race => {}
+        }
         next_state.set(GameState::Playing);
-    } else if keyboard_input.just_pressed(KeyCode::KeyM) {
+    } else if keyboard_input.just_pressed(KeyCode::KeyM) || gamepad.just_pressed(GamepadButtonType::Select) {
         // 返回主菜单 - 先清理现有游戏实体
         for entity in game_entities.iter() {
             commands.entity(entity).despawn_recursive();
         }
         
-        level.0 = 1;
-        score.0 = 0;
-        lives.0 = difficulty_settings.lives;
-        *power_effects = PowerUpEffects::default();
-        game_initialized.0 = false;  // 重置初始化状态
+        start_new_run(&mut run, None);
         next_state.set(GameState::MainMenu);
     }
 }
@@ -1974,28 +5022,76 @@ fn cleanup_pause_menu(mut commands: Commands, query: Query<Entity, With<PauseUI>
 // 设置排行榜界面
 fn setup_leaderboard(
     mut commands: Commands,
-    api_client: Res<ApiClientResource>,
+    network: Res<Network>,
     mut leaderboard_data: ResMut<LeaderboardData>,
+    mut seasons: ResMut<LeaderboardSeasons>,
+    mut recent_scores: ResMut<RecentScores>,
     difficulty_settings: Res<DifficultySettings>,
+    fonts: Res<FontAssets>,
+    locale: Res<Locale>,
+    server_status: Res<ServerStatus>,
 ) {
-    // 获取排行榜数据
-    let difficulty_filter = match difficulty_settings.difficulty {
-        Difficulty::Easy => "Easy",
-        Difficulty::Medium => "Medium",
-        Difficulty::Hard => "Hard",
+    // 赛季列表返回前只有当前赛季和全部赛季两个选项
+    seasons.selected = 0;
+    seasons.options = vec!["current".to_string(), "all".to_string()];
+    seasons.current_label = None;
+    network.send(ApiCommand::GetSeasons);
+
+    *recent_scores = RecentScores {
+        refresh: RECENT_SCORES_REFRESH_INTERVAL,
+        ..default()
     };
-    
-    // 尝试从API获取数据
-    match api_client.0.get_leaderboard(Some(10), Some(difficulty_filter)) {
-        Ok(data) => {
-            leaderboard_data.0 = Some(data);
-        }
-        Err(e) => {
-            eprintln!("Failed to fetch leaderboard: {}", e);
-            leaderboard_data.0 = None;
-        }
-    }
-    
+    network.send(ApiCommand::GetRecentScores { limit: RECENT_SCORES_LIMIT });
+
+    request_leaderboard(&network, &mut leaderboard_data, &seasons, &difficulty_settings);
+    spawn_leaderboard_ui(
+        &mut commands,
+        &fonts,
+        &locale,
+        &leaderboard_data,
+        &seasons,
+        &recent_scores,
+        &difficulty_settings,
+        *server_status,
+    );
+}
+
+// 排名变化按（难度, 赛季）分别记录
+fn leaderboard_key(seasons: &LeaderboardSeasons, difficulty_settings: &DifficultySettings) -> (String, String) {
+    let season = seasons.options.get(seasons.selected).cloned().unwrap_or_else(|| "current".to_string());
+    (difficulty_settings.difficulty.name().to_string(), season)
+}
+
+// 请求当前选中赛季的排行榜
+fn request_leaderboard(
+    network: &Network,
+    leaderboard_data: &mut LeaderboardData,
+    seasons: &LeaderboardSeasons,
+    difficulty_settings: &DifficultySettings,
+) {
+    *leaderboard_data = LeaderboardData::Loading;
+    network.send(ApiCommand::GetLeaderboard {
+        limit: Some(LEADERBOARD_PAGE_SIZE),
+        difficulty: Some(difficulty_settings.difficulty.name().to_string()),
+        season: seasons.options.get(seasons.selected).cloned(),
+        cursor: None,
+    });
+}
+
+// 根据当前数据构建排行榜界面
+fn spawn_leaderboard_ui(
+    commands: &mut Commands,
+    fonts: &FontAssets,
+    locale: &Locale,
+    leaderboard_data: &LeaderboardData,
+    seasons: &LeaderboardSeasons,
+    recent_scores: &RecentScores,
+    difficulty_settings: &DifficultySettings,
+    server_status: ServerStatus,
+) {
+    perf::perf_scope!("leaderboard_build");
+    let difficulty_filter = locale.tr(&name_key("difficulty", difficulty_settings.difficulty.name()), &[]);
+
     commands
         .spawn((
             NodeBundle {
@@ -2014,227 +5110,2440 @@ fn setup_leaderboard(
         ))
         .with_children(|parent| {
             parent.spawn(TextBundle::from_section(
-                format!("LEADERBOARD - {}", difficulty_filter.to_uppercase()),
-                TextStyle {
-                    font_size: 60.0,
-                    color: Color::WHITE,
-                    ..default()
-                },
+                locale.tr("leaderboard.title", &[("difficulty", &difficulty_filter.to_uppercase())]),
+                text_style(fonts, FontKind::Bold, 60.0, Color::WHITE),
             ));
+
+            // 赛季选择行
+            parent.spawn(TextBundle::from_section(
+                locale.tr("leaderboard.season", &[("season", &seasons.selected_label(locale))]),
+                text_style(fonts, FontKind::Regular, 22.0, Color::rgb(0.5, 0.7, 0.9)),
+            ).with_style(Style {
+                margin: UiRect::top(Val::Px(10.0)),
+                ..default()
+            }));
             
-            // 排行榜容器
+            // 排行榜和最近提交两列并排，列宽按百分比分配，超出部分各自裁剪；
+            // 高度最多占六成，最大缩放下窗口放不下标题和提示时收缩
             parent
                 .spawn(NodeBundle {
                     style: Style {
-                        width: Val::Px(600.0),
-                        height: Val::Px(400.0),
-                        margin: UiRect::top(Val::Px(40.0)),
-                        padding: UiRect::all(Val::Px(20.0)),
-                        flex_direction: FlexDirection::Column,
+                        width: Val::Percent(90.0),
+                        flex_basis: Val::Percent(60.0),
+                        min_height: Val::Px(0.0),
+                        margin: UiRect::top(Val::Px(20.0)),
+                        column_gap: Val::Percent(2.0),
                         ..default()
                     },
-                    background_color: BackgroundColor(Color::rgba(0.2, 0.2, 0.25, 0.8)),
                     ..default()
                 })
                 .with_children(|parent| {
-                    // 表头
+                    // 排行榜容器
                     parent
                         .spawn(NodeBundle {
                             style: Style {
-                                width: Val::Percent(100.0),
-                                height: Val::Px(40.0),
-                                justify_content: JustifyContent::SpaceBetween,
-                                align_items: AlignItems::Center,
-                                padding: UiRect::horizontal(Val::Px(10.0)),
-                                margin: UiRect::bottom(Val::Px(10.0)),
+                                flex_grow: 1.0,
+                                flex_basis: Val::Px(0.0),
+                                min_width: Val::Px(0.0),
+                                height: Val::Percent(100.0),
+                                padding: UiRect::all(Val::Px(20.0)),
+                                flex_direction: FlexDirection::Column,
+                                overflow: Overflow::clip(),
                                 ..default()
                             },
+                            background_color: BackgroundColor(Color::rgba(0.2, 0.2, 0.25, 0.8)),
                             ..default()
                         })
                         .with_children(|parent| {
-                            parent.spawn(TextBundle::from_section(
-                                "RANK",
-                                TextStyle {
-                                    font_size: 20.0,
-                                    color: Color::rgb(0.7, 0.7, 0.7),
-                                    ..default()
-                                },
-                            ).with_style(Style {
-                                width: Val::Px(60.0),
-                                ..default()
-                            }));
-                            
-                            parent.spawn(TextBundle::from_section(
-                                "NAME",
-                                TextStyle {
-                                    font_size: 20.0,
-                                    color: Color::rgb(0.7, 0.7, 0.7),
-                                    ..default()
-                                },
-                            ).with_style(Style {
-                                width: Val::Px(200.0),
-                                ..default()
-                            }));
-                            
-                            parent.spawn(TextBundle::from_section(
-                                "SCORE",
-                                TextStyle {
-                                    font_size: 20.0,
-                                    color: Color::rgb(0.7, 0.7, 0.7),
-                                    ..default()
-                                },
-                            ).with_style(Style {
-                                width: Val::Px(100.0),
-                                ..default()
-                            }));
-                            
-                            parent.spawn(TextBundle::from_section(
-                                "LEVEL",
-                                TextStyle {
-                                    font_size: 20.0,
-                                    color: Color::rgb(0.7, 0.7, 0.7),
-                                    ..default()
-                                },
-                            ).with_style(Style {
-                                width: Val::Px(60.0),
-                                ..default()
-                            }));
-                        });
-                    
-                    // 排行榜数据
-                    if let Some(ref data) = leaderboard_data.0 {
-                        for score in &data.scores {
+                            // 表头
                             parent
                                 .spawn(NodeBundle {
                                     style: Style {
                                         width: Val::Percent(100.0),
-                                        height: Val::Px(35.0),
+                                        min_height: Val::Px(40.0),
                                         justify_content: JustifyContent::SpaceBetween,
                                         align_items: AlignItems::Center,
                                         padding: UiRect::horizontal(Val::Px(10.0)),
-                                        margin: UiRect::bottom(Val::Px(5.0)),
+                                        margin: UiRect::bottom(Val::Px(10.0)),
                                         ..default()
                                     },
-                                    background_color: BackgroundColor(
-                                        if score.rank == Some(1) {
-                                            Color::rgba(0.8, 0.7, 0.0, 0.2)
-                                        } else if score.rank == Some(2) {
-                                            Color::rgba(0.7, 0.7, 0.7, 0.2)
-                                        } else if score.rank == Some(3) {
-                                            Color::rgba(0.7, 0.4, 0.0, 0.2)
-                                        } else {
-                                            Color::rgba(0.3, 0.3, 0.35, 0.3)
-                                        }
-                                    ),
                                     ..default()
                                 })
                                 .with_children(|parent| {
-                                    // Rank
                                     parent.spawn(TextBundle::from_section(
-                                        format!("#{}", score.rank.unwrap_or(0)),
-                                        TextStyle {
-                                            font_size: 24.0,
-                                            color: if score.rank == Some(1) {
-                                                Color::rgb(1.0, 0.85, 0.0)
-                                            } else if score.rank == Some(2) {
-                                                Color::rgb(0.75, 0.75, 0.75)
-                                            } else if score.rank == Some(3) {
-                                                Color::rgb(0.8, 0.5, 0.2)
-                                            } else {
-                                                Color::WHITE
-                                            },
-                                            ..default()
-                                        },
+                                        locale.tr("leaderboard.rank", &[]),
+                                        text_style(fonts, FontKind::Bold, 20.0, Color::rgb(0.7, 0.7, 0.7)),
                                     ).with_style(Style {
-                                        width: Val::Px(60.0),
+                                        width: Val::Percent(15.0),
                                         ..default()
                                     }));
-                                    
-                                    // Name
+                            
                                     parent.spawn(TextBundle::from_section(
-                                        &score.player_name,
-                                        TextStyle {
-                                            font_size: 22.0,
-                                            color: Color::WHITE,
-                                            ..default()
-                                        },
+                                        locale.tr("leaderboard.name", &[]),
+                                        text_style(fonts, FontKind::Bold, 20.0, Color::rgb(0.7, 0.7, 0.7)),
                                     ).with_style(Style {
-                                        width: Val::Px(200.0),
+                                        width: Val::Percent(45.0),
                                         ..default()
                                     }));
-                                    
-                                    // Score
+                            
                                     parent.spawn(TextBundle::from_section(
-                                        score.score.to_string(),
-                                        TextStyle {
-                                            font_size: 24.0,
-                                            color: Color::rgb(0.2, 0.8, 0.2),
-                                            ..default()
-                                        },
+                                        locale.tr("leaderboard.score", &[]),
+                                        text_style(fonts, FontKind::Bold, 20.0, Color::rgb(0.7, 0.7, 0.7)),
                                     ).with_style(Style {
-                                        width: Val::Px(100.0),
+                                        width: Val::Percent(25.0),
                                         ..default()
                                     }));
-                                    
-                                    // Level
+                            
                                     parent.spawn(TextBundle::from_section(
-                                        score.level.to_string(),
-                                        TextStyle {
-                                            font_size: 22.0,
-                                            color: Color::rgb(0.7, 0.7, 0.7),
-                                            ..default()
-                                        },
+                                        locale.tr("leaderboard.level", &[]),
+                                        text_style(fonts, FontKind::Bold, 20.0, Color::rgb(0.7, 0.7, 0.7)),
                                     ).with_style(Style {
-                                        width: Val::Px(60.0),
+                                        width: Val::Percent(15.0),
                                         ..default()
                                     }));
                                 });
-                        }
-                    } else {
-                        parent.spawn(TextBundle::from_section(
-                            "Failed to load leaderboard data.\nMake sure the server is running.",
-                            TextStyle {
-                                font_size: 20.0,
-                                color: Color::rgb(0.8, 0.2, 0.2),
-                                ..default()
-                            },
-                        ).with_style(Style {
-                            margin: UiRect::top(Val::Px(50.0)),
-                            ..default()
-                        }));
-                    }
+                    
+                            // 排行榜数据
+                            match leaderboard_data {
+                                LeaderboardData::Loaded(data) => {
+                                    // 裁剪区域，内部列表通过 top 偏移滚动，接近底部时加载下一页。
+                                    // 列表按全部行的高度布局，行由 recycle_leaderboard_rows 只为可见范围生成
+                                    let content_height =
+                                        VirtualList::new(LEADERBOARD_ROW_HEIGHT, 0.0, 0).content_height(data.scores.len());
+                                    parent
+                                        .spawn(NodeBundle {
+                                            style: Style {
+                                                width: Val::Percent(100.0),
+                                                flex_grow: 1.0,
+                                                overflow: Overflow::clip(),
+                                                ..default()
+                                            },
+                                            ..default()
+                                        })
+                                        .with_children(|parent| {
+                                            parent.spawn((
+                                                NodeBundle {
+                                                    style: Style {
+                                                        width: Val::Percent(100.0),
+                                                        height: Val::Px(content_height),
+                                                        ..default()
+                                                    },
+                                                    ..default()
+                                                },
+                                                LeaderboardScrollList::default(),
+                                            ));
+                                        });
+                                }
+                                LeaderboardData::Loading => {
+                                    parent.spawn(TextBundle::from_section(
+                                        locale.tr("leaderboard.loading", &[]),
+                                        text_style(fonts, FontKind::Regular, 20.0, Color::rgb(0.7, 0.7, 0.7)),
+                                    ).with_style(Style {
+                                        margin: UiRect::top(Val::Px(50.0)),
+                                        ..default()
+                                    }));
+                                }
+                                LeaderboardData::Failed => {
+                                    // 熔断中时说明稍后会自动恢复
+                                    let message_key = if server_status == ServerStatus::BackingOff {
+                                        "leaderboard.backing_off"
+                                    } else {
+                                        "leaderboard.failed"
+                                    };
+                                    parent.spawn(TextBundle::from_section(
+                                        locale.tr(message_key, &[]),
+                                        text_style(fonts, FontKind::Regular, 20.0, Color::rgb(0.8, 0.2, 0.2)),
+                                    ).with_style(Style {
+                                        margin: UiRect::top(Val::Px(50.0)),
+                                        ..default()
+                                    }));
+                                }
+                            }
+                        });
+
+                    spawn_recent_scores_column(parent, fonts, locale, recent_scores);
                 });
             
             parent.spawn(TextBundle::from_section(
-                "Press SPACE to return to menu",
-                TextStyle {
-                    font_size: 25.0,
-                    color: Color::rgb(0.7, 0.7, 0.7),
+                locale.tr("leaderboard.hint", &[]),
+                text_style(fonts, FontKind::Regular, 25.0, Color::rgb(0.7, 0.7, 0.7)),
+            ).with_style(Style {
+                margin: UiRect::top(Val::Px(20.0)),
+                ..default()
+            }));
+        });
+}
+
+// 排行榜中一个可复用的空行：排名、名字、分数和关卡四个文字，内容由 fill_leaderboard_row 写入；
+// 最后是管理模式的删除按钮
+fn spawn_leaderboard_row_slot(parent: &mut ChildBuilder, fonts: &FontAssets) {
+    parent
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    width: Val::Percent(100.0),
+                    height: Val::Px(LEADERBOARD_ROW_HEIGHT - LEADERBOARD_ROW_GAP),
+                    justify_content: JustifyContent::SpaceBetween,
+                    align_items: AlignItems::Center,
+                    padding: UiRect::horizontal(Val::Px(10.0)),
                     ..default()
                 },
+                visibility: Visibility::Hidden,
+                ..default()
+            },
+            LeaderboardRow::default(),
+        ))
+        .with_children(|parent| {
+            let cells = [
+                (FontKind::Mono, 24.0, 15.0),
+                (FontKind::Regular, 22.0, 45.0),
+                (FontKind::Mono, 24.0, 25.0),
+                (FontKind::Mono, 22.0, 15.0),
+            ];
+            for (index, (kind, size, width)) in cells.into_iter().enumerate() {
+                let mut cell = parent.spawn(
+                    TextBundle::from_section("", text_style(fonts, kind, size, Color::WHITE)).with_style(Style {
+                        width: Val::Percent(width),
+                        ..default()
+                    }),
+                );
+                // 排名文字始终带有标识，排名变化提示按它淡出
+                if index == 0 {
+                    cell.insert(RankIndicator(String::new()));
+                }
+            }
+            admin::spawn_delete_button(parent, fonts);
+        });
+}
+
+// 把一条成绩写入复用的行：位置、背景和所有文字都重新设置，不保留上一条成绩的样式
+fn fill_leaderboard_row(
+    fonts: &FontAssets,
+    locale: &Locale,
+    rank_history: &LeaderboardHistory,
+    own_name: &str,
+    index: usize,
+    score: &api::Score,
+    style: &mut Style,
+    background: &mut BackgroundColor,
+    cells: [Mut<Text>; 4],
+    indicator: &mut RankIndicator,
+) {
+    let own_entry = !own_name.is_empty() && score.player_name == own_name;
+    style.top = Val::Px(index as f32 * LEADERBOARD_ROW_HEIGHT);
+    background.0 = if own_entry {
+        OWN_ENTRY_BACKGROUND
+    } else {
+        match score.rank {
+            Some(1) => Color::rgba(0.8, 0.7, 0.0, 0.2),
+            Some(2) => Color::rgba(0.7, 0.7, 0.7, 0.2),
+            Some(3) => Color::rgba(0.7, 0.4, 0.0, 0.2),
+            _ => Color::rgba(0.3, 0.3, 0.35, 0.3),
+        }
+    };
+    let rank_color = match score.rank {
+        Some(1) => Color::rgb(1.0, 0.85, 0.0),
+        Some(2) => Color::rgb(0.75, 0.75, 0.75),
+        Some(3) => Color::rgb(0.8, 0.5, 0.2),
+        _ => Color::WHITE,
+    };
+    let name_color = if own_entry { OWN_ENTRY_NAME_COLOR } else { Color::WHITE };
+
+    let [mut rank, mut name, mut value, mut level] = cells;
+    // 排名变化（▲/▼/新上榜）跟在排名后面，几秒后淡出
+    rank.sections = vec![TextSection::new(
+        format!("#{}", score.rank.unwrap_or(0)),
+        text_style(fonts, FontKind::Mono, 24.0, rank_color),
+    )];
+    if let Some(movement) = rank_history.movement(score) {
+        rank.sections.push(rank_movement::indicator_section(fonts, locale, movement));
+    }
+    indicator.0 = rank_movement::score_key(score);
+    name.sections = vec![TextSection::new(
+        score.player_name.clone(),
+        text_style(fonts, FontKind::Regular, 22.0, name_color),
+    )];
+    value.sections = vec![TextSection::new(
+        score.score.to_string(),
+        text_style(fonts, FontKind::Mono, 24.0, Color::rgb(0.2, 0.8, 0.2)),
+    )];
+    level.sections = vec![TextSection::new(
+        score.level.to_string(),
+        text_style(fonts, FontKind::Mono, 22.0, Color::rgb(0.7, 0.7, 0.7)),
+    )];
+}
+
+// 排行榜虚拟化：按滚动位置计算可见范围，只为这些成绩准备界面行；
+// 滚动时改写移出范围的行，数据变化（翻页、刷新）时改写全部行
+fn recycle_leaderboard_rows(
+    mut commands: Commands,
+    fonts: Res<FontAssets>,
+    locale: Res<Locale>,
+    leaderboard_data: Res<LeaderboardData>,
+    rank_history: Res<LeaderboardHistory>,
+    player_name: Res<PlayerName>,
+    settings: Res<Settings>,
+    mut list_query: Query<(Entity, &LeaderboardScrollList, &mut Style, &Parent, Option<&Children>)>,
+    node_query: Query<&Node>,
+    mut row_query: Query<
+        (&mut LeaderboardRow, &mut Style, &mut BackgroundColor, &mut Visibility, &Children),
+        Without<LeaderboardScrollList>,
+    >,
+    mut text_query: Query<&mut Text>,
+    mut indicator_query: Query<&mut RankIndicator>,
+) {
+    perf::perf_scope!("leaderboard_rows");
+    let LeaderboardData::Loaded(data) = leaderboard_data.as_ref() else {
+        return;
+    };
+    let data_changed = leaderboard_data.is_changed();
+    let len = data.scores.len();
+    // 匿名提交的成绩在榜单上是代号，按代号找自己的成绩
+    let own_name = settings.public_name(&player_name.0);
+
+    for (list_entity, list, mut list_style, parent, children) in list_query.iter_mut() {
+        let viewport_height = node_query.get(parent.get()).map(|node| node.size().y).unwrap_or(0.0);
+        let virtual_list = VirtualList::new(LEADERBOARD_ROW_HEIGHT, viewport_height, LEADERBOARD_OVERSCAN);
+        let content_height = Val::Px(virtual_list.content_height(len));
+        if list_style.height != content_height {
+            list_style.height = content_height;
+        }
+
+        // 行数不够时补齐，新行在下一帧填入内容
+        let pool = children.map_or(0, |children| children.len());
+        let needed = virtual_list.pool_size().min(len);
+        if pool < needed {
+            commands.entity(list_entity).with_children(|parent| {
+                for _ in pool..needed {
+                    spawn_leaderboard_row_slot(parent, &fonts);
+                }
+            });
+        }
+        let Some(children) = children else {
+            continue;
+        };
+
+        let range = virtual_list.visible_range(-list.position, len);
+        for (slot, row_entity) in children.iter().enumerate() {
+            let Ok((mut row, mut style, mut background, mut visibility, cells)) = row_query.get_mut(*row_entity)
+            else {
+                continue;
+            };
+            let wanted = VirtualList::row_for_slot(&range, slot, pool);
+            if wanted == row.index && !data_changed {
+                continue;
+            }
+            row.index = wanted;
+            let Some(index) = wanted else {
+                *visibility = Visibility::Hidden;
+                continue;
+            };
+            let &[rank_cell, name_cell, score_cell, level_cell, ..] = &cells[..] else {
+                continue;
+            };
+            let Ok(cell_texts) = text_query.get_many_mut([rank_cell, name_cell, score_cell, level_cell]) else {
+                continue;
+            };
+            let Ok(mut indicator) = indicator_query.get_mut(rank_cell) else {
+                continue;
+            };
+            fill_leaderboard_row(
+                &fonts,
+                &locale,
+                &rank_history,
+                &own_name,
+                index,
+                &data.scores[index],
+                &mut style,
+                &mut background,
+                cell_texts,
+                &mut indicator,
+            );
+            *visibility = Visibility::Inherited;
+        }
+    }
+}
+
+// 最近提交列，排在排行榜右侧
+fn spawn_recent_scores_column(
+    parent: &mut ChildBuilder,
+    fonts: &FontAssets,
+    locale: &Locale,
+    recent_scores: &RecentScores,
+) {
+    parent
+        .spawn(NodeBundle {
+            style: Style {
+                width: Val::Percent(32.0),
+                min_width: Val::Px(0.0),
+                height: Val::Percent(100.0),
+                padding: UiRect::all(Val::Px(15.0)),
+                flex_direction: FlexDirection::Column,
+                overflow: Overflow::clip(),
+                ..default()
+            },
+            background_color: BackgroundColor(Color::rgba(0.2, 0.2, 0.25, 0.8)),
+            ..default()
+        })
+        .with_children(|parent| {
+            parent.spawn(TextBundle::from_section(
+                locale.tr("recent.title", &[]),
+                text_style(fonts, FontKind::Bold, 20.0, Color::rgb(0.7, 0.7, 0.7)),
             ).with_style(Style {
-                margin: UiRect::top(Val::Px(40.0)),
+                margin: UiRect::bottom(Val::Px(10.0)),
                 ..default()
             }));
+
+            parent
+                .spawn((
+                    NodeBundle {
+                        style: Style {
+                            width: Val::Percent(100.0),
+                            flex_grow: 1.0,
+                            flex_direction: FlexDirection::Column,
+                            overflow: Overflow::clip(),
+                            ..default()
+                        },
+                        ..default()
+                    },
+                    RecentScoresList,
+                ))
+                .with_children(|parent| {
+                    spawn_recent_scores_entries(parent, fonts, locale, recent_scores, &HashSet::new());
+                });
         });
 }
 
-// 排行榜系统
-fn leaderboard_system(
-    keyboard_input: Res<ButtonInput<KeyCode>>,
-    mut next_state: ResMut<NextState<GameState>>,
+// 最近提交列表的内容；fresh 中的条目播放滑入动画
+fn spawn_recent_scores_entries(
+    parent: &mut ChildBuilder,
+    fonts: &FontAssets,
+    locale: &Locale,
+    recent_scores: &RecentScores,
+    fresh: &HashSet<String>,
 ) {
-    if keyboard_input.just_pressed(KeyCode::Space) {
-        next_state.set(GameState::MainMenu);
+    let message_key = match &recent_scores.scores {
+        Some(scores) if !scores.is_empty() => {
+            let now = chrono::Utc::now();
+            for score in scores {
+                let slide_in = score.id.as_ref().is_some_and(|id| fresh.contains(id));
+                spawn_recent_score_row(parent, fonts, locale, score, now, slide_in);
+            }
+            return;
+        }
+        Some(_) => "recent.empty",
+        None if recent_scores.failed => "recent.failed",
+        None => "leaderboard.loading",
+    };
+    parent.spawn(TextBundle::from_section(
+        locale.tr(message_key, &[]),
+        text_style(fonts, FontKind::Regular, 16.0, Color::rgb(0.6, 0.6, 0.6)),
+    ));
+}
+
+// 最近提交中的一行：名字和分数，下面是难度和提交时间
+fn spawn_recent_score_row(
+    parent: &mut ChildBuilder,
+    fonts: &FontAssets,
+    locale: &Locale,
+    score: &api::Score,
+    now: chrono::DateTime<chrono::Utc>,
+    slide_in: bool,
+) {
+    let mut row = parent.spawn(NodeBundle {
+        style: Style {
+            width: Val::Percent(100.0),
+            flex_direction: FlexDirection::Column,
+            padding: UiRect::axes(Val::Px(8.0), Val::Px(4.0)),
+            margin: UiRect::bottom(Val::Px(4.0)),
+            // 滑入动画从列宽之外开始
+            left: if slide_in { Val::Percent(100.0) } else { Val::Px(0.0) },
+            ..default()
+        },
+        background_color: BackgroundColor(Color::rgba(0.3, 0.3, 0.35, 0.3)),
+        ..default()
+    });
+    if slide_in {
+        row.insert(SlideIn::default());
+    }
+
+    row.with_children(|parent| {
+        parent
+            .spawn(NodeBundle {
+                style: Style {
+                    width: Val::Percent(100.0),
+                    justify_content: JustifyContent::SpaceBetween,
+                    column_gap: Val::Px(6.0),
+                    ..default()
+                },
+                ..default()
+            })
+            .with_children(|parent| {
+                parent.spawn(TextBundle::from_section(
+                    &score.player_name,
+                    text_style(fonts, FontKind::Regular, 17.0, Color::WHITE),
+                ));
+                parent.spawn(TextBundle::from_section(
+                    format_thousands(score.score),
+                    text_style(fonts, FontKind::Mono, 17.0, Color::rgb(0.2, 0.8, 0.2)),
+                ));
+            });
+
+        let difficulty = locale.tr(&name_key("difficulty", &score.difficulty), &[]);
+        let detail = match score.created_at.as_deref().and_then(|created_at| relative_time(created_at, now, locale)) {
+            Some(ago) => format!("{} · {}", difficulty, ago),
+            None => difficulty,
+        };
+        parent.spawn(TextBundle::from_section(
+            detail,
+            text_style(fonts, FontKind::Regular, 14.0, Color::rgb(0.6, 0.6, 0.6)),
+        ));
+    });
+}
+
+// 分数加千位分隔符，例如 1240 -> "1,240"
+fn format_thousands(value: u32) -> String {
+    let digits = value.to_string();
+    let mut grouped = String::with_capacity(digits.len() + digits.len() / 3);
+    for (i, digit) in digits.chars().enumerate() {
+        if i > 0 && (digits.len() - i).is_multiple_of(3) {
+            grouped.push(',');
+        }
+        grouped.push(digit);
     }
+    grouped
 }
 
-// 清理排行榜界面
-fn cleanup_leaderboard(
+// 提交时间距现在多久，例如 "2m ago"；时间无法解析时返回 None
+fn relative_time(created_at: &str, now: chrono::DateTime<chrono::Utc>, locale: &Locale) -> Option<String> {
+    let created_at = chrono::DateTime::parse_from_rfc3339(created_at).ok()?;
+    let minutes = (now - created_at.with_timezone(&chrono::Utc)).num_minutes().max(0);
+    Some(if minutes < 1 {
+        locale.tr("recent.just_now", &[])
+    } else if minutes < 60 {
+        locale.tr("recent.minutes_ago", &[("minutes", &minutes)])
+    } else if minutes < 60 * 24 {
+        locale.tr("recent.hours_ago", &[("hours", &(minutes / 60))])
+    } else {
+        locale.tr("recent.days_ago", &[("days", &(minutes / (60 * 24)))])
+    })
+}
+
+// 定期刷新最近提交，只重建右侧列表，不影响排行榜的滚动位置
+fn recent_scores_feed(
     mut commands: Commands,
-    query: Query<Entity, With<LeaderboardUI>>,
+    time: Res<Time>,
+    network: Res<Network>,
+    mut responses: EventReader<ApiResponse>,
+    mut recent_scores: ResMut<RecentScores>,
+    fonts: Res<FontAssets>,
+    locale: Res<Locale>,
+    list_query: Query<Entity, With<RecentScoresList>>,
 ) {
-    for entity in query.iter() {
-        commands.entity(entity).despawn_recursive();
+    recent_scores.refresh -= time.delta_seconds();
+    if recent_scores.refresh <= 0.0 {
+        recent_scores.refresh = RECENT_SCORES_REFRESH_INTERVAL;
+        network.send(ApiCommand::GetRecentScores { limit: RECENT_SCORES_LIMIT });
     }
-}
\ No newline at end of file
+
+    let mut fresh = HashSet::new();
+    let mut changed = false;
+    for response in responses.read() {
+        match response {
+            ApiResponse::RecentScores(Ok(scores)) => {
+                // 第一次加载时整列直接显示，之后新出现的条目才滑入
+                if let Some(previous) = &recent_scores.scores {
+                    let known: HashSet<&String> = previous.iter().filter_map(|score| score.id.as_ref()).collect();
+                    fresh.extend(
+                        scores
+                            .iter()
+                            .filter_map(|score| score.id.clone())
+                            .filter(|id| !known.contains(id)),
+                    );
+                }
+                recent_scores.scores = Some(scores.clone());
+                recent_scores.failed = false;
+                changed = true;
+            }
+            ApiResponse::RecentScores(Err(e)) => {
+                eprintln!("Failed to fetch recent scores: {}", e);
+                // 已有数据时保留旧列表，下次刷新再试
+                if recent_scores.scores.is_none() {
+                    recent_scores.failed = true;
+                    changed = true;
+                }
+            }
+            _ => {}
+        }
+    }
+
+    if changed {
+        for list in list_query.iter() {
+            commands.entity(list).despawn_descendants().with_children(|parent| {
+                spawn_recent_scores_entries(parent, &fonts, &locale, &recent_scores, &fresh);
+            });
+        }
+    }
+}
+
+// 滑入动画：从右侧减速移动到原位
+fn animate_slide_in(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut query: Query<(Entity, &mut SlideIn, &mut Style)>,
+) {
+    for (entity, mut slide, mut style) in query.iter_mut() {
+        slide.elapsed += time.delta_seconds();
+        let t = (slide.elapsed / SLIDE_IN_DURATION).min(1.0);
+        let eased = 1.0 - (1.0 - t).powi(3);
+        style.left = Val::Percent((1.0 - eased) * 100.0);
+        if t >= 1.0 {
+            commands.entity(entity).remove::<SlideIn>();
+        }
+    }
+}
+
+// 排行榜系统
+fn leaderboard_system(
+    mut commands: Commands,
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    mut mouse_wheel: EventReader<MouseWheel>,
+    time: Res<Time>,
+    mut next_state: ResMut<NextState<GameState>>,
+    network: Res<Network>,
+    mut leaderboard_data: ResMut<LeaderboardData>,
+    mut seasons: ResMut<LeaderboardSeasons>,
+    recent_scores: Res<RecentScores>,
+    difficulty_settings: Res<DifficultySettings>,
+    fonts: Res<FontAssets>,
+    locale: Res<Locale>,
+    server_status: Res<ServerStatus>,
+    ui_query: Query<Entity, With<LeaderboardUI>>,
+    mut list_query: Query<(&mut LeaderboardScrollList, &mut Style, &Parent, &Node)>,
+    node_query: Query<&Node>,
+) {
+    if keyboard_input.just_pressed(KeyCode::Space) {
+        next_state.set(GameState::MainMenu);
+        return;
+    }
+
+    // 滚动列表，接近底部时用游标请求下一页
+    let delta = read_scroll_input(&keyboard_input, &mut mouse_wheel, &time);
+
+    for (mut list, mut style, parent, list_node) in list_query.iter_mut() {
+        let visible_height = node_query.get(parent.get()).map(|node| node.size().y).unwrap_or(0.0);
+        let max_scroll = (list_node.size().y - visible_height).max(0.0);
+        list.position = (list.position + delta).clamp(-max_scroll, 0.0);
+        style.top = Val::Px(list.position);
+
+        if max_scroll + list.position < LEADERBOARD_LOAD_MARGIN {
+            // 取走游标，下一页返回前不会重复请求；游标不影响显示，不触发行的改写
+            if let LeaderboardData::Loaded(data) = leaderboard_data.bypass_change_detection() {
+                if let Some(cursor) = data.next_cursor.take() {
+                    network.send(ApiCommand::GetLeaderboard {
+                        limit: Some(LEADERBOARD_PAGE_SIZE),
+                        difficulty: Some(difficulty_settings.difficulty.name().to_string()),
+                        season: seasons.options.get(seasons.selected).cloned(),
+                        cursor: Some(cursor),
+                    });
+                }
+            }
+        }
+    }
+
+    // 切换赛季或按 R 刷新后重新请求并重建界面
+    let count = seasons.options.len().max(1);
+    let previous = seasons.selected;
+    if keyboard_input.just_pressed(KeyCode::ArrowLeft) {
+        seasons.selected = (seasons.selected + count - 1) % count;
+    } else if keyboard_input.just_pressed(KeyCode::ArrowRight) {
+        seasons.selected = (seasons.selected + 1) % count;
+    }
+
+    if seasons.selected != previous || keyboard_input.just_pressed(KeyCode::KeyR) {
+        for entity in ui_query.iter() {
+            commands.entity(entity).despawn_recursive();
+        }
+        request_leaderboard(&network, &mut leaderboard_data, &seasons, &difficulty_settings);
+        spawn_leaderboard_ui(
+            &mut commands,
+            &fonts,
+            &locale,
+            &leaderboard_data,
+            &seasons,
+            &recent_scores,
+            &difficulty_settings,
+            *server_status,
+        );
+    }
+}
+
+// 处理排行榜和赛季列表的响应
+fn leaderboard_responses(
+    mut commands: Commands,
+    mut responses: EventReader<ApiResponse>,
+    mut leaderboard_data: ResMut<LeaderboardData>,
+    mut seasons: ResMut<LeaderboardSeasons>,
+    recent_scores: Res<RecentScores>,
+    mut rank_history: ResMut<LeaderboardHistory>,
+    difficulty_settings: Res<DifficultySettings>,
+    fonts: Res<FontAssets>,
+    locale: Res<Locale>,
+    server_status: Res<ServerStatus>,
+    ui_query: Query<Entity, With<LeaderboardUI>>,
+) {
+    perf::perf_scope!("leaderboard_responses");
+    let mut changed = false;
+    for response in responses.read() {
+        match response {
+            ApiResponse::Leaderboard(Ok(data)) => {
+                rank_history.refreshed(leaderboard_key(&seasons, &difficulty_settings), &data.scores);
+                *leaderboard_data = LeaderboardData::Loaded(data.clone());
+                changed = true;
+            }
+            // 下一页直接追加到数据末尾，保持滚动位置；可见的行由 recycle_leaderboard_rows 改写
+            ApiResponse::LeaderboardPage(Ok(page)) => {
+                if let LeaderboardData::Loaded(data) = leaderboard_data.as_mut() {
+                    rank_history.page_loaded(leaderboard_key(&seasons, &difficulty_settings), &page.scores);
+                    data.scores.extend(page.scores.iter().cloned());
+                    data.total = page.total;
+                    data.next_cursor = page.next_cursor.clone();
+                }
+            }
+            ApiResponse::LeaderboardPage(Err(e)) => {
+                eprintln!("Failed to fetch next leaderboard page: {}", e);
+            }
+            ApiResponse::Leaderboard(Err(e)) => {
+                eprintln!("Failed to fetch leaderboard: {}", e);
+                *leaderboard_data = LeaderboardData::Failed;
+                changed = true;
+            }
+            // 当前赛季在前，历史赛季其次，最后是全部赛季；保持原来的选择
+            ApiResponse::Seasons(Ok(response)) => {
+                let selected = seasons.options.get(seasons.selected).cloned();
+                let mut options = vec!["current".to_string()];
+                options.extend(
+                    response
+                        .seasons
+                        .iter()
+                        .map(|summary| summary.season.clone())
+                        .filter(|season| *season != response.current),
+                );
+                options.push("all".to_string());
+                seasons.selected = selected
+                    .and_then(|selected| options.iter().position(|option| *option == selected))
+                    .unwrap_or(0);
+                seasons.options = options;
+                seasons.current_label = Some(response.current.clone());
+                changed = true;
+            }
+            ApiResponse::Seasons(Err(e)) => eprintln!("Failed to fetch seasons: {}", e),
+            _ => {}
+        }
+    }
+
+    if changed {
+        for entity in ui_query.iter() {
+            commands.entity(entity).despawn_recursive();
+        }
+        spawn_leaderboard_ui(
+            &mut commands,
+            &fonts,
+            &locale,
+            &leaderboard_data,
+            &seasons,
+            &recent_scores,
+            &difficulty_settings,
+            *server_status,
+        );
+    }
+}
+
+// 离开界面时丢弃尚未返回的请求
+fn cancel_network_requests(network: Res<Network>) {
+    network.cancel_pending();
+}
+
+// 清理排行榜界面
+fn cleanup_leaderboard(
+    mut commands: Commands,
+    query: Query<Entity, With<LeaderboardUI>>,
+) {
+    for entity in query.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+// 设置界面
+fn setup_settings_menu(
+    mut commands: Commands,
+    mut menu_state: ResMut<SettingsMenuState>,
+    settings: Res<Settings>,
+    fonts: Res<FontAssets>,
+    locale: Res<Locale>,
+    windows: Query<&Window, With<PrimaryWindow>>,
+) {
+    menu_state.selected = 0;
+    spawn_settings_menu(&mut commands, &fonts, &locale, &settings, window_auto_scale(&windows), menu_state.selected);
+}
+
+// 构建设置界面，切换语言后会重新构建
+fn spawn_settings_menu(
+    commands: &mut Commands,
+    fonts: &FontAssets,
+    locale: &Locale,
+    settings: &Settings,
+    auto_scale: f32,
+    selected: usize,
+) {
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    width: Val::Percent(100.0),
+                    height: Val::Percent(100.0),
+                    flex_direction: FlexDirection::Column,
+                    justify_content: JustifyContent::Center,
+                    align_items: AlignItems::Center,
+                    ..default()
+                },
+                background_color: BackgroundColor(Color::rgb(0.1, 0.1, 0.15)),
+                ..default()
+            },
+            SettingsUI,
+        ))
+        .with_children(|parent| {
+            parent.spawn(TextBundle::from_section(
+                locale.tr("settings.title", &[]),
+                text_style(fonts, FontKind::Bold, 48.0, Color::WHITE),
+            ));
+
+            // 条目较多，字号和间距比其他菜单小一些
+            for (index, item) in SettingsItem::ALL.iter().enumerate() {
+                parent.spawn((
+                    TextBundle::from_section(
+                        settings_item_text(*item, index == selected, settings, auto_scale, locale),
+                        text_style(fonts, FontKind::Regular, 24.0, settings_item_color(index == selected)),
+                    ).with_style(Style {
+                        margin: UiRect::top(Val::Px(if index == 0 { 24.0 } else { 6.0 })),
+                        ..default()
+                    }),
+                    SettingsItemText(index),
+                ));
+            }
+
+            // 摇杆预览：原始值和处理后的数值
+            stick::spawn_stick_preview(parent, fonts, locale);
+
+            parent.spawn(TextBundle::from_section(
+                locale.tr("settings.hint", &[]),
+                text_style(fonts, FontKind::Regular, 20.0, Color::rgb(0.6, 0.6, 0.6)),
+            ).with_style(Style {
+                margin: UiRect::top(Val::Px(20.0)),
+                ..default()
+            }));
+        });
+}
+
+// 设置条目的显示文字
+fn settings_item_text(item: SettingsItem, selected: bool, settings: &Settings, auto_scale: f32, locale: &Locale) -> String {
+    format!(
+        "{} {}: < {} >",
+        if selected { ">" } else { " " },
+        locale.tr(item.label_key(), &[]),
+        item.value_text(settings, auto_scale, locale),
+    )
+}
+
+fn settings_item_color(selected: bool) -> Color {
+    if selected {
+        Color::rgb(1.0, 0.85, 0.2)
+    } else {
+        Color::WHITE
+    }
+}
+
+// 设置界面系统
+fn settings_menu_system(
+    mut commands: Commands,
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    mut next_state: ResMut<NextState<GameState>>,
+    mut menu_state: ResMut<SettingsMenuState>,
+    mut settings: ResMut<Settings>,
+    paths: Res<ProfilePaths>,
+    mut locale: ResMut<Locale>,
+    mut fonts: ResMut<FontAssets>,
+    windows: Query<&Window, With<PrimaryWindow>>,
+    ui_query: Query<Entity, With<SettingsUI>>,
+    mut item_query: Query<(&mut Text, &SettingsItemText)>,
+) {
+    let item_count = SettingsItem::ALL.len();
+
+    if keyboard_input.just_pressed(KeyCode::ArrowUp) {
+        menu_state.selected = (menu_state.selected + item_count - 1) % item_count;
+    } else if keyboard_input.just_pressed(KeyCode::ArrowDown) {
+        menu_state.selected = (menu_state.selected + 1) % item_count;
+    }
+
+    let mut direction = 0;
+    if keyboard_input.just_pressed(KeyCode::ArrowLeft) {
+        direction = -1;
+    } else if keyboard_input.just_pressed(KeyCode::ArrowRight) || keyboard_input.just_pressed(KeyCode::Enter) {
+        direction = 1;
+    }
+
+    let auto_scale = window_auto_scale(&windows);
+    if direction != 0 {
+        SettingsItem::ALL[menu_state.selected].adjust(&mut settings, direction);
+        settings.save(&paths);
+
+        // 语言变化时立即切换并按新语言重建界面
+        let language = settings.language();
+        if locale.language() != language {
+            locale.set_language(language);
+            fonts.set_cjk(language.uses_cjk());
+            for entity in ui_query.iter() {
+                commands.entity(entity).despawn_recursive();
+            }
+            spawn_settings_menu(&mut commands, &fonts, &locale, &settings, auto_scale, menu_state.selected);
+            return;
+        }
+    }
+
+    if keyboard_input.just_pressed(KeyCode::Escape) || keyboard_input.just_pressed(KeyCode::Space) {
+        next_state.set(GameState::MainMenu);
+    } else if keyboard_input.just_pressed(KeyCode::KeyP) {
+        next_state.set(GameState::ProfileSelect);
+    }
+
+    // 更新条目显示
+    for (mut text, item) in item_query.iter_mut() {
+        let selected = item.0 == menu_state.selected;
+        text.sections[0].value = settings_item_text(SettingsItem::ALL[item.0], selected, &settings, auto_scale, &locale);
+        text.sections[0].style.color = settings_item_color(selected);
+    }
+}
+
+// 清理设置界面
+fn cleanup_settings_menu(mut commands: Commands, query: Query<Entity, With<SettingsUI>>) {
+    for entity in query.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+// 档案界面
+fn setup_profile_screen(
+    mut commands: Commands,
+    fonts: Res<FontAssets>,
+    locale: Res<Locale>,
+    paths: Res<ProfilePaths>,
+    player_name: Res<PlayerName>,
+    difficulty_settings: Res<DifficultySettings>,
+) {
+    let profile = Profile::load(&paths);
+
+    // 每个分区：标题 + (名称, 数值) 行；没有数据时显示占位行
+    let mut sections: Vec<(&str, Vec<(String, String)>)> = Vec::new();
+
+    let total_seconds = profile.total_play_time as u32;
+    sections.push((
+        "profile.overview",
+        vec![
+            (
+                locale.tr("profile.total_play_time", &[]),
+                format!("{}:{:02}:{:02}", total_seconds / 3600, total_seconds / 60 % 60, total_seconds % 60),
+            ),
+            (locale.tr("profile.total_runs", &[]), profile.total_runs.to_string()),
+            (locale.tr("profile.best_combo", &[]), profile.best_combo.to_string()),
+            (locale.tr("profile.debuffs_warded", &[]), profile.debuffs_warded.to_string()),
+            (
+                locale.tr("profile.favorite_powerup", &[]),
+                profile
+                    .favorite_powerup()
+                    .map(|name| locale.tr(&name_key("powerup", name), &[]))
+                    .unwrap_or_else(|| "-".to_string()),
+            ),
+        ],
+    ));
+
+    sections.push((
+        "profile.best_scores",
+        Difficulty::ALL
+            .iter()
+            .map(|difficulty| {
+                let best = profile
+                    .best_scores
+                    .get(difficulty.name())
+                    .map(|score| score.to_string())
+                    .unwrap_or_else(|| "-".to_string());
+                (locale.tr(&name_key("difficulty", difficulty.name()), &[]), best)
+            })
+            .collect(),
+    ));
+
+    sections.push((
+        "profile.bricks_destroyed",
+        profile
+            .bricks_destroyed
+            .iter()
+            .map(|(name, count)| (locale.tr(&name_key("brick", name), &[]), count.to_string()))
+            .collect(),
+    ));
+
+    sections.push((
+        "profile.powerups_collected",
+        profile
+            .powerups_collected
+            .iter()
+            .map(|(name, count)| (locale.tr(&name_key("powerup", name), &[]), count.to_string()))
+            .collect(),
+    ));
+
+    let most_game_overs = profile.game_over_levels.values().copied().max().unwrap_or(0);
+    sections.push((
+        "profile.game_over_levels",
+        profile
+            .game_over_levels
+            .iter()
+            .map(|(level, count)| {
+                let bar_length = (*count as usize * 20).div_ceil(most_game_overs.max(1) as usize);
+                (locale.tr("profile.level", &[("level", level)]), format!("{} {}", "|".repeat(bar_length), count))
+            })
+            .collect(),
+    ));
+
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    width: Val::Percent(100.0),
+                    height: Val::Percent(100.0),
+                    flex_direction: FlexDirection::Column,
+                    justify_content: JustifyContent::Center,
+                    align_items: AlignItems::Center,
+                    ..default()
+                },
+                background_color: BackgroundColor(Color::rgb(0.1, 0.1, 0.15)),
+                ..default()
+            },
+            ProfileUI,
+        ))
+        .with_children(|parent| {
+            parent.spawn(TextBundle::from_section(
+                locale.tr("profile.title", &[]),
+                text_style(&fonts, FontKind::Bold, 60.0, Color::WHITE),
+            ));
+
+            // 裁剪区域，内部列表通过 top 偏移滚动
+            parent
+                .spawn(NodeBundle {
+                    style: Style {
+                        width: Val::Percent(60.0),
+                        height: Val::Percent(65.0),
+                        margin: UiRect::top(Val::Px(20.0)),
+                        overflow: Overflow::clip(),
+                        ..default()
+                    },
+                    background_color: BackgroundColor(Color::rgba(0.2, 0.2, 0.25, 0.8)),
+                    ..default()
+                })
+                .with_children(|parent| {
+                    parent
+                        .spawn((
+                            NodeBundle {
+                                style: Style {
+                                    width: Val::Percent(100.0),
+                                    flex_direction: FlexDirection::Column,
+                                    padding: UiRect::all(Val::Px(15.0)),
+                                    ..default()
+                                },
+                                ..default()
+                            },
+                            ProfileScrollList::default(),
+                        ))
+                        .with_children(|parent| {
+                            for (title_key, rows) in sections {
+                                parent.spawn(TextBundle::from_section(
+                                    locale.tr(title_key, &[]),
+                                    text_style(&fonts, FontKind::Bold, 26.0, Color::rgb(1.0, 0.85, 0.2)),
+                                ).with_style(Style {
+                                    margin: UiRect::vertical(Val::Px(8.0)),
+                                    ..default()
+                                }));
+
+                                if rows.is_empty() {
+                                    parent.spawn(TextBundle::from_section(
+                                        locale.tr("profile.no_data", &[]),
+                                        text_style(&fonts, FontKind::Regular, 18.0, Color::rgb(0.5, 0.5, 0.5)),
+                                    ));
+                                }
+
+                                for (label, value) in rows {
+                                    parent
+                                        .spawn(NodeBundle {
+                                            style: Style {
+                                                justify_content: JustifyContent::SpaceBetween,
+                                                min_height: Val::Px(24.0),
+                                                ..default()
+                                            },
+                                            ..default()
+                                        })
+                                        .with_children(|parent| {
+                                            parent.spawn(TextBundle::from_section(
+                                                label,
+                                                text_style(&fonts, FontKind::Regular, 20.0, Color::rgb(0.8, 0.8, 0.8)),
+                                            ));
+                                            parent.spawn(TextBundle::from_section(
+                                                value,
+                                                text_style(&fonts, FontKind::Mono, 20.0, Color::WHITE),
+                                            ));
+                                        });
+                                }
+                            }
+
+                            // 服务器上的对手推荐，响应返回后填入
+                            rivals::spawn_rivals_panel(
+                                parent,
+                                &fonts,
+                                &locale,
+                                &player_name.0,
+                                &difficulty_settings,
+                            );
+                        });
+                });
+
+            parent.spawn(TextBundle::from_section(
+                locale.tr("profile.hint", &[]),
+                text_style(&fonts, FontKind::Regular, 20.0, Color::rgb(0.6, 0.6, 0.6)),
+            ).with_style(Style {
+                margin: UiRect::top(Val::Px(20.0)),
+                ..default()
+            }));
+        });
+}
+
+// 读取鼠标滚轮和上下方向键的滚动量（向上为正）
+fn read_scroll_input(
+    keyboard_input: &ButtonInput<KeyCode>,
+    mouse_wheel: &mut EventReader<MouseWheel>,
+    time: &Time,
+) -> f32 {
+    let mut delta = 0.0;
+    for event in mouse_wheel.read() {
+        delta += match event.unit {
+            MouseScrollUnit::Line => event.y * 24.0,
+            MouseScrollUnit::Pixel => event.y,
+        };
+    }
+    if keyboard_input.pressed(KeyCode::ArrowUp) {
+        delta += 400.0 * time.delta_seconds();
+    }
+    if keyboard_input.pressed(KeyCode::ArrowDown) {
+        delta -= 400.0 * time.delta_seconds();
+    }
+    delta
+}
+
+// 档案界面系统：滚动与返回
+fn profile_screen_system(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    mut mouse_wheel: EventReader<MouseWheel>,
+    time: Res<Time>,
+    mut next_state: ResMut<NextState<GameState>>,
+    mut list_query: Query<(&mut ProfileScrollList, &mut Style, &Parent, &Node)>,
+    node_query: Query<&Node>,
+) {
+    if keyboard_input.just_pressed(KeyCode::Escape) || keyboard_input.just_pressed(KeyCode::Space) {
+        next_state.set(GameState::MainMenu);
+        return;
+    }
+    if keyboard_input.just_pressed(KeyCode::KeyC) {
+        next_state.set(GameState::Cosmetics);
+        return;
+    }
+
+    let delta = read_scroll_input(&keyboard_input, &mut mouse_wheel, &time);
+
+    for (mut list, mut style, parent, list_node) in list_query.iter_mut() {
+        let visible_height = node_query.get(parent.get()).map(|node| node.size().y).unwrap_or(0.0);
+        let max_scroll = (list_node.size().y - visible_height).max(0.0);
+        list.position = (list.position + delta).clamp(-max_scroll, 0.0);
+        style.top = Val::Px(list.position);
+    }
+}
+
+// 清理档案界面
+fn cleanup_profile_screen(mut commands: Commands, query: Query<Entity, With<ProfileUI>>) {
+    for entity in query.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bevy::ecs::system::RunSystemOnce;
+    use std::collections::HashMap;
+    use std::time::Duration;
+
+    // 挡板顶面在 y = -240；球从 y = -225 落到 -235 时，底边正好在这一步的一半穿过顶面
+    const PADDLE: Vec2 = Vec2::new(0.0, -250.0);
+    const GRACE: f32 = 6.0;
+
+    fn falling(x: f32) -> (Vec2, Vec2) {
+        (Vec2::new(x, -225.0), Vec2::new(x, -235.0))
+    }
+
+    #[test]
+    fn edge_save_catches_a_ball_just_past_either_edge() {
+        // 挡板半宽加球半宽是 70，超出 3 在宽限内
+        let (previous, current) = falling(73.0);
+        assert_eq!(edge_save_side(previous, current, PADDLE, PADDLE_SIZE.x, GRACE), Some(1.0));
+        let (previous, current) = falling(-73.0);
+        assert_eq!(edge_save_side(previous, current, PADDLE, PADDLE_SIZE.x, GRACE), Some(-1.0));
+    }
+
+    #[test]
+    fn edge_save_window_is_outside_the_edge_and_inclusive_of_the_grace() {
+        // 正好碰到边缘时球还在挡板上，由普通碰撞处理
+        let (previous, current) = falling(70.0);
+        assert_eq!(edge_save_side(previous, current, PADDLE, PADDLE_SIZE.x, GRACE), None);
+        let (previous, current) = falling(76.0);
+        assert_eq!(edge_save_side(previous, current, PADDLE, PADDLE_SIZE.x, GRACE), Some(1.0));
+        let (previous, current) = falling(76.5);
+        assert_eq!(edge_save_side(previous, current, PADDLE, PADDLE_SIZE.x, GRACE), None);
+    }
+
+    #[test]
+    fn edge_save_uses_the_x_where_the_ball_crosses_the_paddle_plane() {
+        // 从 60 斜着落到 90，穿过顶面时在 75：只超出 5
+        let previous = Vec2::new(60.0, -225.0);
+        let current = Vec2::new(90.0, -235.0);
+        assert_eq!(edge_save_side(previous, current, PADDLE, PADDLE_SIZE.x, GRACE), Some(1.0));
+        // 反过来从 90 落到 60，穿过时同样在 75
+        assert_eq!(
+            edge_save_side(Vec2::new(90.0, -225.0), Vec2::new(60.0, -235.0), PADDLE, PADDLE_SIZE.x, GRACE),
+            Some(1.0)
+        );
+    }
+
+    #[test]
+    fn edge_save_needs_the_ball_to_cross_the_plane_this_step() {
+        // 还在顶面上方
+        let above = (Vec2::new(73.0, -200.0), Vec2::new(73.0, -220.0));
+        assert_eq!(edge_save_side(above.0, above.1, PADDLE, PADDLE_SIZE.x, GRACE), None);
+        // 上一步已经在顶面下方
+        let below = (Vec2::new(73.0, -235.0), Vec2::new(73.0, -245.0));
+        assert_eq!(edge_save_side(below.0, below.1, PADDLE, PADDLE_SIZE.x, GRACE), None);
+    }
+
+    #[test]
+    fn edge_save_is_off_without_grace() {
+        let (previous, current) = falling(73.0);
+        assert_eq!(edge_save_side(previous, current, PADDLE, PADDLE_SIZE.x, 0.0), None);
+    }
+
+    fn assert_close(actual: Vec2, expected: Vec2) {
+        assert!((actual - expected).length() < 1e-3, "{:?} != {:?}", actual, expected);
+    }
+
+    #[test]
+    fn plain_bricks_mirror_the_normal_component() {
+        let velocity = Vec2::new(120.0, 300.0);
+        assert_close(BrickType::Normal.bounce_response(velocity, Collision::Bottom), Vec2::new(120.0, -300.0));
+        assert_close(BrickType::Hard.bounce_response(velocity, Collision::Left), Vec2::new(-120.0, 300.0));
+    }
+
+    #[test]
+    fn ice_damps_the_normal_component_but_keeps_the_speed() {
+        let velocity = Vec2::new(300.0, 400.0);
+        let bounced = BrickType::Ice.bounce_response(velocity, Collision::Bottom);
+        assert!((bounced.length() - velocity.length()).abs() < 1e-3);
+        // 法向分量压低 40% 后再恢复速度大小：球更贴着砖块表面滑出去
+        let expected = Vec2::new(300.0, -400.0 * ICE_NORMAL_DAMPING).normalize() * velocity.length();
+        assert_close(bounced, expected);
+        assert!(bounced.y.abs() < velocity.y.abs());
+    }
+
+    #[test]
+    fn ice_damps_horizontal_normals_on_side_hits() {
+        let velocity = Vec2::new(400.0, 300.0);
+        let bounced = BrickType::Ice.bounce_response(velocity, Collision::Left);
+        let expected = Vec2::new(-400.0 * ICE_NORMAL_DAMPING, 300.0).normalize() * velocity.length();
+        assert_close(bounced, expected);
+    }
+
+    #[test]
+    fn rubber_speeds_the_ball_up_along_the_mirrored_direction() {
+        let velocity = Vec2::new(0.0, BALL_SPEED);
+        let bounced = BrickType::Rubber.bounce_response(velocity, Collision::Bottom);
+        assert_close(bounced, Vec2::new(0.0, -BALL_SPEED * RUBBER_SPEED_BOOST));
+    }
+
+    #[test]
+    fn rubber_is_capped_at_max_speed_and_never_slows_the_ball() {
+        let near_cap = Vec2::new(0.0, MAX_BALL_SPEED * 0.95);
+        let bounced = BrickType::Rubber.bounce_response(near_cap, Collision::Bottom);
+        assert!((bounced.length() - MAX_BALL_SPEED).abs() < 1e-3);
+        // 已经超过上限（例如其他效果加速）的球也不会被弹慢
+        let over_cap = Vec2::new(0.0, MAX_BALL_SPEED * 1.2);
+        let bounced = BrickType::Rubber.bounce_response(over_cap, Collision::Bottom);
+        assert!((bounced.length() - over_cap.length()).abs() < 1e-3);
+    }
+
+    // 竖直分量不足四分之一的速度被扶正：大小和两个分量的方向都不变
+    #[test]
+    fn shallow_velocities_get_a_quarter_of_the_speed_vertically() {
+        for velocity in [
+            Vec2::new(400.0, 10.0),
+            Vec2::new(-400.0, -10.0),
+            Vec2::new(-250.0, 60.0),
+            Vec2::new(300.0, -0.5),
+        ] {
+            let corrected = enforce_min_vertical(velocity);
+            assert!((corrected.length() - velocity.length()).abs() < 1e-3, "{velocity} -> {corrected}");
+            assert!((corrected.y.abs() - velocity.length() * MIN_VERTICAL_RATIO).abs() < 1e-3, "{velocity} -> {corrected}");
+            assert_eq!(corrected.x.signum(), velocity.x.signum(), "{velocity} -> {corrected}");
+            assert_eq!(corrected.y.signum(), velocity.y.signum(), "{velocity} -> {corrected}");
+        }
+        // 完全水平的球朝远离挡板的方向扶正
+        let corrected = enforce_min_vertical(Vec2::new(-BALL_SPEED, 0.0));
+        assert_close(corrected, Vec2::new(-(1.0 - MIN_VERTICAL_RATIO.powi(2)).sqrt(), MIN_VERTICAL_RATIO) * BALL_SPEED);
+    }
+
+    #[test]
+    fn steep_and_still_velocities_are_left_alone() {
+        // 竖直分量是速度的 0.28，刚好超过下限
+        for velocity in [Vec2::new(384.0, 112.0), Vec2::new(-300.0, -400.0), Vec2::new(0.0, BALL_SPEED), Vec2::ZERO] {
+            assert_eq!(enforce_min_vertical(velocity), velocity);
+        }
+    }
+
+    // 普通挡板反弹后球速回到发球速度，之前被砖块加快的部分不会一直带下去
+    #[test]
+    fn paddle_hits_restore_the_serve_speed() {
+        let step = Duration::from_secs_f64(1.0 / fixed_step::PHYSICS_HZ);
+        let mut app = physics_app(step);
+        let difficulty_settings = DifficultySettings::new(Difficulty::Hard);
+        let expected = BALL_SPEED * difficulty_settings.ball_speed_modifier;
+        app.insert_resource(difficulty_settings);
+        let (mut transform, mut ball) = app.world.query::<(&mut Transform, &mut Ball)>().single_mut(&mut app.world);
+        transform.translation = Vec3::new(-120.0, Orientation::Normal.paddle_y() + 30.0, 1.0);
+        ball.velocity = Vec2::new(0.3, -1.0).normalize() * MAX_BALL_SPEED;
+
+        let mut velocity = ball.velocity;
+        for _ in 0..30 {
+            app.update();
+            velocity = app.world.query::<&Ball>().single(&app.world).velocity;
+            if velocity.y > 0.0 {
+                break;
+            }
+        }
+        assert!(velocity.y > 0.0, "the ball should have bounced off the paddle");
+        assert!((velocity.length() - expected).abs() < 1e-2, "{} != {}", velocity.length(), expected);
+    }
+
+    const SLOW_ZONE_TOP: f32 = -WINDOW_HEIGHT / 2.0 + SLOW_ZONE_HEIGHT;
+
+    #[test]
+    fn slow_zone_leaves_balls_above_it_alone() {
+        assert_eq!(slow_zone_scale(0.0, -BALL_SPEED), 1.0);
+        assert_eq!(slow_zone_scale(SLOW_ZONE_TOP, -BALL_SPEED), 1.0);
+    }
+
+    #[test]
+    fn slow_zone_slows_deeper_balls_more() {
+        let halfway = slow_zone_scale(SLOW_ZONE_TOP - SLOW_ZONE_HEIGHT / 2.0, -BALL_SPEED);
+        assert!((halfway - (1.0 - SLOW_ZONE_MAX_SLOWDOWN / 2.0)).abs() < 1e-6);
+        let bottom = slow_zone_scale(-WINDOW_HEIGHT / 2.0, -BALL_SPEED);
+        assert!((bottom - (1.0 - SLOW_ZONE_MAX_SLOWDOWN)).abs() < 1e-6);
+        // 越过底边（丢球前的最后一帧）也不会减速更多
+        assert_eq!(slow_zone_scale(-WINDOW_HEIGHT, -BALL_SPEED), bottom);
+    }
+
+    #[test]
+    fn slow_zone_never_slows_a_rising_ball() {
+        let bottom = -WINDOW_HEIGHT / 2.0;
+        assert_eq!(slow_zone_scale(bottom, BALL_SPEED), 1.0);
+        assert_eq!(slow_zone_scale(SLOW_ZONE_TOP - SLOW_ZONE_HEIGHT / 2.0, 1.0), 1.0);
+        // 水平移动的球按上升处理，不会卡在减速带里
+        assert_eq!(slow_zone_scale(bottom, 0.0), 1.0);
+    }
+
+    fn test_profile_dir(name: &str) -> std::path::PathBuf {
+        std::env::temp_dir().join(format!("breakout-test-{}-{}", name, std::process::id()))
+    }
+
+    // 关卡计时和胜利判断按游戏中的顺序放在固定帧里；ExpiryCount 记录倒计时报告结束的次数
+    #[derive(Resource, Default)]
+    struct ExpiryCount(u32);
+
+    fn count_expiry(level_timer: Res<LevelTimer>, mut count: ResMut<ExpiryCount>) {
+        if level_timer.0.just_finished() {
+            count.0 += 1;
+        }
+    }
+
+    fn level_timer_app(name: &str, seconds_left: f32, frame: f32) -> App {
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins)
+            .insert_resource(bevy::time::TimeUpdateStrategy::ManualDuration(Duration::from_secs_f32(frame)))
+            .insert_resource(Time::<Fixed>::from_hz(fixed_step::PHYSICS_HZ))
+            .init_state::<GameState>()
+            .insert_resource(LevelTimer(Countdown::new(seconds_left)))
+            .insert_resource(DifficultySettings::new(Difficulty::Hard))
+            .insert_resource(ProfilePaths::in_dir(&test_profile_dir(name)))
+            .insert_resource(Level(1))
+            .insert_resource(Score(0))
+            .init_resource::<Curse>()
+            .init_resource::<BonusStage>()
+            .init_resource::<GameMode>()
+            .init_resource::<GeneratorChain>()
+            .init_resource::<MedalThresholds>()
+            .init_resource::<LevelAttempt>()
+            .init_resource::<PlayClock>()
+            .init_resource::<BossStage>()
+            .init_resource::<ExpiryCount>()
+            .add_systems(
+                FixedUpdate,
+                (check_victory, update_level_timer.after(check_victory), count_expiry.after(update_level_timer))
+                    .run_if(in_state(GameState::Playing).and_then(fixed_step::no_transition_pending)),
+            );
+        // 限制单帧时长的上限调高，让一帧真的跨过 frame 秒
+        app.world
+            .resource_mut::<Time<Virtual>>()
+            .set_max_delta(Duration::from_secs_f32(frame.max(0.25)));
+        app.world.spawn(Ball { velocity: Vec2::new(0.0, BALL_SPEED) });
+        app.world.resource_mut::<NextState<GameState>>().set(GameState::Playing);
+        app
+    }
+
+    fn spawn_brick(app: &mut App) {
+        app.world.spawn(Brick {
+            brick_type: BrickType::Normal,
+            health: 1,
+            shade: 1.0,
+            guaranteed_drop: None,
+            golden: false,
+        });
+    }
+
+    #[test]
+    fn clearing_the_field_on_the_final_tick_counts_as_a_win() {
+        // 剩余时间不到一个固定帧，最后一块砖在同一帧被打碎
+        let mut app = level_timer_app("final-tick", 0.5 / fixed_step::PHYSICS_HZ as f32, 0.02);
+        // 第一帧进入 Playing，第二帧跑固定帧，第三帧切换状态
+        for _ in 0..3 {
+            app.update();
+        }
+        assert_eq!(app.world.resource::<ExpiryCount>().0, 1);
+        assert_eq!(app.world.resource::<LevelTimer>().0.remaining(), 0.0);
+        assert_eq!(*app.world.resource::<State<GameState>>().get(), GameState::Victory);
+        let _ = std::fs::remove_dir_all(test_profile_dir("final-tick"));
+    }
+
+    // 首领关没有首领实体就算打败；只有最后一关之后的首领结束整局
+    #[test]
+    fn beating_the_final_boss_completes_the_campaign() {
+        for (level, expected) in [(5, GameState::Victory), (ending::FINAL_LEVEL, GameState::GameComplete)] {
+            let mut app = level_timer_app("final-boss", 60.0, 0.02);
+            app.insert_resource(Level(level));
+            app.world.resource_mut::<BossStage>().start();
+            for _ in 0..3 {
+                app.update();
+            }
+            assert_eq!(*app.world.resource::<State<GameState>>().get(), expected, "boss after level {}", level);
+        }
+        let _ = std::fs::remove_dir_all(test_profile_dir("final-boss"));
+    }
+
+    // 第 9 关和第 10 关打完砖块都照常过关；之后依次是奖励关、第 10 关和最后的首领，一直停留在游戏中
+    #[test]
+    fn levels_nine_and_ten_keep_the_campaign_going() {
+        for level in [ending::FINAL_LEVEL - 1, ending::FINAL_LEVEL] {
+            let mut app = level_timer_app("before-final", 60.0, 0.02);
+            app.insert_resource(Level(level));
+            for _ in 0..3 {
+                app.update();
+            }
+            assert_eq!(*app.world.resource::<State<GameState>>().get(), GameState::Victory, "level {}", level);
+        }
+
+        let mut app = level_timer_app("before-final", 60.0, 0.02);
+        app.insert_resource(Level(ending::FINAL_LEVEL - 1))
+            .insert_resource(GameInitialized(true))
+            .init_resource::<PowerUpEffects>()
+            .init_resource::<Orientation>();
+        let advance = |app: &mut App| {
+            app.world.run_system_once(next_level_setup);
+            app.update();
+            (app.world.resource::<Level>().0, *app.world.resource::<State<GameState>>().get())
+        };
+        assert_eq!(advance(&mut app), (ending::FINAL_LEVEL - 1, GameState::Playing));
+        assert!(app.world.resource::<BonusStage>().is_active());
+        assert_eq!(advance(&mut app), (ending::FINAL_LEVEL, GameState::LevelInterlude));
+        assert_eq!(advance(&mut app), (ending::FINAL_LEVEL, GameState::Playing));
+        assert!(app.world.resource::<BossStage>().is_active());
+        let _ = std::fs::remove_dir_all(test_profile_dir("before-final"));
+    }
+
+    // 单关挑战、比赛和淘汰赛打败第 10 关之后的首领只算过关
+    #[test]
+    fn the_final_boss_only_ends_the_campaign() {
+        for game_mode in [GameMode::SingleLevel, GameMode::Tournament, GameMode::Race] {
+            let mut app = level_timer_app("final-boss-modes", 60.0, 0.02);
+            app.insert_resource(Level(ending::FINAL_LEVEL)).insert_resource(game_mode);
+            app.world.resource_mut::<BossStage>().start();
+            for _ in 0..3 {
+                app.update();
+            }
+            assert_eq!(*app.world.resource::<State<GameState>>().get(), GameState::Victory, "{:?}", game_mode);
+        }
+        let _ = std::fs::remove_dir_all(test_profile_dir("final-boss-modes"));
+    }
+
+    #[test]
+    fn timer_running_out_with_bricks_left_ends_the_run() {
+        let mut app = level_timer_app("time-up", 0.5 / fixed_step::PHYSICS_HZ as f32, 0.02);
+        spawn_brick(&mut app);
+        for _ in 0..3 {
+            app.update();
+        }
+        assert_eq!(*app.world.resource::<State<GameState>>().get(), GameState::GameOver);
+    }
+
+    #[test]
+    fn two_second_frame_expires_the_level_timer_once() {
+        let mut app = level_timer_app("long-frame", 1.0, 0.02);
+        spawn_brick(&mut app);
+        app.update();
+        app.insert_resource(bevy::time::TimeUpdateStrategy::ManualDuration(Duration::from_secs(2)));
+        app.world.resource_mut::<Time<Virtual>>().set_max_delta(Duration::from_secs(2));
+        app.update();
+        app.update();
+        assert_eq!(app.world.resource::<ExpiryCount>().0, 1);
+        assert_eq!(app.world.resource::<LevelTimer>().0.remaining(), 0.0);
+        assert_eq!(*app.world.resource::<State<GameState>>().get(), GameState::GameOver);
+    }
+
+    // 暂停 10 秒（模拟时间）期间，局内计时一个都不能动；回到 Playing 后继续走
+    #[test]
+    fn ten_seconds_of_pause_leave_gameplay_timers_untouched() {
+        let frame = 1.0 / 60.0;
+        let mut app = level_timer_app("pause-freeze", 60.0, frame);
+        spawn_brick(&mut app);
+        let mut effects = PowerUpEffects {
+            has_laser: true,
+            ..default()
+        };
+        effects.laser_timer.restart(30.0);
+        effects.slow_motion_timer.restart(30.0);
+        app.insert_resource(effects)
+            .init_resource::<TimeScale>()
+            .add_systems(Update, hazard_rules::advance_play_clock.run_if(in_state(GameState::Playing)))
+            .add_systems(
+                FixedUpdate,
+                update_powerup_timers
+                    .before(check_victory)
+                    .run_if(in_state(GameState::Playing).and_then(fixed_step::no_transition_pending)),
+            );
+        let timers = |app: &App| {
+            let world = &app.world;
+            hazard_rules::GameTimers::capture(world.resource(), world.resource(), world.resource())
+        };
+        let start = timers(&app);
+        for _ in 0..60 {
+            app.update();
+        }
+        let before_pause = timers(&app);
+        hazard_rules::assert_game_time!(advanced, start, before_pause);
+
+        app.world.resource_mut::<NextState<GameState>>().set(GameState::Paused);
+        for _ in 0..600 {
+            app.update();
+        }
+        assert_eq!(*app.world.resource::<State<GameState>>().get(), GameState::Paused);
+        assert!(app.world.resource::<Time<Real>>().elapsed_seconds() >= 10.0);
+        hazard_rules::assert_game_time!(frozen, before_pause, timers(&app));
+
+        app.world.resource_mut::<NextState<GameState>>().set(GameState::Playing);
+        for _ in 0..60 {
+            app.update();
+        }
+        hazard_rules::assert_game_time!(advanced, before_pause, timers(&app));
+        let _ = std::fs::remove_dir_all(test_profile_dir("pause-freeze"));
+    }
+
+    // 球、挡板和几排砖块，只有固定帧里的球移动和碰撞，按 PHYSICS_HZ 运行
+    fn physics_app(frame: Duration) -> App {
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins)
+            .insert_resource(bevy::time::TimeUpdateStrategy::ManualDuration(frame))
+            .insert_resource(Time::<Fixed>::from_hz(fixed_step::PHYSICS_HZ))
+            .init_state::<GameState>()
+            .insert_resource(Score(0))
+            .insert_resource(Lives(3))
+            .insert_resource(DifficultySettings::new(Difficulty::Medium))
+            // 挡板横跨整个场地，球不会掉出去
+            .insert_resource(PowerUpEffects {
+                paddle_size_modifier: WINDOW_WIDTH / PADDLE_SIZE.x,
+                ..default()
+            })
+            .init_resource::<TimeScale>()
+            .init_resource::<PlayClock>()
+            .init_resource::<EffectBudget>()
+            .init_resource::<Orientation>()
+            .init_resource::<BonusStage>()
+            .init_resource::<EventRun>()
+            .init_resource::<GeneratorChain>()
+            .init_resource::<Insurance>()
+            .init_resource::<HotTeam>()
+            .init_resource::<Curse>()
+            .init_resource::<GameRng>()
+            .add_event::<GameplayEvent>()
+            .add_event::<Explosion>()
+            .add_event::<WallImpact>()
+            .add_systems(
+                FixedUpdate,
+                (ball_movement, ball_collision.after(ball_movement))
+                    .run_if(in_state(GameState::Playing).and_then(fixed_step::no_transition_pending)),
+            );
+        app.world
+            .resource_mut::<Time<Virtual>>()
+            .set_max_delta(Duration::from_secs(2));
+        app.world.spawn((
+            Transform::from_xyz(0.0, Orientation::Normal.paddle_y(), 0.0),
+            Paddle,
+            PaddleTilt::default(),
+            PaddleVelocity::default(),
+        ));
+        for row in 0..4 {
+            for col in 0..BRICK_COLUMNS {
+                app.world.spawn((
+                    Transform::from_translation(brick_position(row, col).extend(0.0)).with_scale(BRICK_SIZE.extend(1.0)),
+                    Brick {
+                        brick_type: BrickType::Normal,
+                        health: 1,
+                        shade: 1.0,
+                        guaranteed_drop: None,
+                        golden: false,
+                    },
+                ));
+            }
+        }
+        app.world.spawn((
+            Transform::from_xyz(-120.0, -150.0, 1.0),
+            Ball { velocity: Vec2::new(0.6, 1.0).normalize() * BALL_SPEED },
+        ));
+        app.world.resource_mut::<NextState<GameState>>().set(GameState::Playing);
+        app
+    }
+
+    // 分数、剩下的砖块和球的位置速度
+    fn physics_outcome(app: &mut App) -> (u32, Vec<(Vec3, i32)>, Vec3, Vec2) {
+        let mut bricks: Vec<(Vec3, i32)> = app
+            .world
+            .query::<(&Transform, &Brick)>()
+            .iter(&app.world)
+            .map(|(transform, brick)| (transform.translation, brick.health))
+            .collect();
+        bricks.sort_by(|a, b| a.0.x.total_cmp(&b.0.x).then(a.0.y.total_cmp(&b.0.y)));
+        let (transform, ball) = app.world.query::<(&Transform, &Ball)>().single(&app.world);
+        (app.world.resource::<Score>().0, bricks, transform.translation, ball.velocity)
+    }
+
+    #[test]
+    fn one_two_second_frame_collides_like_240_small_steps() {
+        let step = Duration::from_secs_f64(1.0 / fixed_step::PHYSICS_HZ);
+        let mut small_steps = physics_app(step);
+        let mut long_frame = physics_app(step);
+        // 第一帧进入 Playing
+        small_steps.update();
+        long_frame.update();
+
+        for _ in 0..240 {
+            small_steps.update();
+        }
+        long_frame.insert_resource(bevy::time::TimeUpdateStrategy::ManualDuration(step * 240));
+        long_frame.update();
+
+        let expected = physics_outcome(&mut small_steps);
+        assert!(expected.0 > 0, "the ball should have broken some bricks in 2 seconds");
+        assert_eq!(physics_outcome(&mut long_frame), expected);
+        assert_eq!(
+            small_steps.world.resource::<Time<Fixed>>().elapsed(),
+            long_frame.world.resource::<Time<Fixed>>().elapsed()
+        );
+    }
+
+    // 脚本化的一局：挡板按脚本跟随球，每隔 AUDIT_INTERVAL 个固定帧采样一次；perturb_at 那一帧多取一个随机数
+    #[derive(Resource, Default)]
+    struct ScriptedRun {
+        tick: usize,
+        perturb_at: Option<usize>,
+        samples: Vec<determinism::StateChecksum>,
+    }
+
+    fn scripted_paddle(
+        run: Res<ScriptedRun>,
+        mut game_rng: ResMut<GameRng>,
+        mut paddle_query: Query<&mut Transform, With<Paddle>>,
+        ball_query: Query<&Transform, (With<Ball>, Without<Paddle>)>,
+    ) {
+        if run.perturb_at == Some(run.tick) {
+            game_rng.next_u32();
+        }
+        // 跟随最低的球，再叠加一段来回的偏移，让球从挡板不同位置弹开
+        let lowest = ball_query.iter().min_by(|a, b| a.translation.y.total_cmp(&b.translation.y));
+        if let (Ok(mut paddle), Some(ball)) = (paddle_query.get_single_mut(), lowest) {
+            paddle.translation.x = ball.translation.x + 30.0 * (run.tick as f32 * 0.05).sin();
+        }
+    }
+
+    fn sample_scripted_run(
+        mut run: ResMut<ScriptedRun>,
+        game_rng: Res<GameRng>,
+        (run_seed, score, lives): (Res<RunSeed>, Res<Score>, Res<Lives>),
+        paddle_query: Query<&Transform, With<Paddle>>,
+        ball_query: Query<(&Transform, &Ball)>,
+        brick_query: Query<(&Transform, &Brick)>,
+    ) {
+        if run.tick.is_multiple_of(determinism::AUDIT_INTERVAL) {
+            let checksum = determinism::StateChecksum::sample(
+                run.tick,
+                run_seed.0,
+                &game_rng,
+                paddle_query.get_single().ok(),
+                ball_query.iter(),
+                brick_query.iter(),
+                [score.0, lives.0, 1],
+            );
+            run.samples.push(checksum);
+        }
+        run.tick += 1;
+    }
+
+    // 30 秒的脚本化一局，带道具掉落和拾取，返回每次采样
+    fn scripted_run(perturb_at: Option<usize>) -> Vec<determinism::StateChecksum> {
+        let step = Duration::from_secs_f64(1.0 / fixed_step::PHYSICS_HZ);
+        let mut app = physics_app(step);
+        app.insert_resource(RunSeed(0x5EED_1492))
+            .insert_resource(PowerUpEffects::default())
+            .insert_resource(ScriptedRun { perturb_at, ..default() })
+            .init_resource::<Cosmetics>()
+            .add_systems(
+                FixedUpdate,
+                (
+                    scripted_paddle.before(ball_collision),
+                    powerup_movement,
+                    powerup_collision.after(powerup_movement).after(scripted_paddle),
+                    sample_scripted_run.after(ball_collision).after(powerup_collision),
+                )
+                    .run_if(in_state(GameState::Playing).and_then(fixed_step::no_transition_pending)),
+            );
+        app.world.resource_mut::<GameRng>().reseed(0x5EED_1492, 1);
+        // 第一帧进入 Playing，之后每帧正好一个固定帧
+        app.update();
+        for _ in 0..30 * fixed_step::PHYSICS_HZ as usize {
+            app.update();
+        }
+        app.world.remove_resource::<ScriptedRun>().unwrap().samples
+    }
+
+    #[test]
+    fn scripted_run_replays_without_divergence() {
+        let recorded = scripted_run(None);
+        assert_eq!(recorded.len(), 30 * fixed_step::PHYSICS_HZ as usize / determinism::AUDIT_INTERVAL);
+        // 这一局确实打碎了砖块、取过随机数
+        assert_ne!(recorded.first().unwrap().bricks, recorded.last().unwrap().bricks);
+        assert_ne!(recorded.first().unwrap().rng, recorded.last().unwrap().rng);
+
+        let replayed = scripted_run(None);
+        assert_eq!(replayed.len(), recorded.len());
+        for (expected, actual) in recorded.iter().zip(&replayed) {
+            if let Some(divergence) = determinism::find_divergence(expected, actual) {
+                panic!("replay diverged: {:?}", divergence);
+            }
+        }
+    }
+
+    #[test]
+    fn extra_rng_draw_is_reported_as_the_first_divergence() {
+        let recorded = scripted_run(None);
+        let perturbed = scripted_run(Some(1000));
+        let divergence = recorded
+            .iter()
+            .zip(&perturbed)
+            .find_map(|(expected, actual)| determinism::find_divergence(expected, actual))
+            .expect("an extra RNG draw should be detected");
+        // 多取的随机数在下一次采样时就能看出，而且是第一个不一致的字段
+        assert_eq!(divergence.tick, 1020);
+        assert_eq!(divergence.differing[0].0, "rng");
+    }
+
+    // 界面布局检查：用 bevy_ui 的 taffy 布局直接算出各节点的位置。测试里没有字体，
+    // 文本按每个字符 0.6 个字号宽（中文 1 个字号）、1.2 个字号高估算，偏大一些
+    fn estimated_text_size(text: &Text) -> Vec2 {
+        let width = text
+            .sections
+            .iter()
+            .map(|section| {
+                let em: f32 = section.value.chars().map(|c| if c.is_ascii() { 0.6 } else { 1.0 }).sum();
+                em * section.style.font_size
+            })
+            .sum();
+        let height = text.sections.iter().map(|section| section.style.font_size * 1.2).fold(0.0, f32::max);
+        Vec2::new(width, height)
+    }
+
+    fn ui_app(language: i18n::Language) -> App {
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, AssetPlugin::default()))
+            .init_asset::<Font>()
+            .insert_resource(Locale::new(language));
+        app.init_resource::<FontAssets>();
+        app
+    }
+
+    // 在 window 大小（物理像素）、缩放为 scale 的窗口中排版，返回每个节点的矩形和它必须留在其中的区域。
+    // 先按单行排一次，放不下的文本按可用宽度折成多行再排一次
+    fn layout_rects(world: &mut World, scale: f32, window: Vec2) -> Vec<(Entity, Rect, Rect)> {
+        let mut surface = bevy::ui::UiSurface::default();
+        let context = bevy::ui::LayoutContext {
+            scale_factor: scale,
+            physical_size: window,
+            min_size: window.min_element(),
+            max_size: window.max_element(),
+        };
+        let nodes: Vec<(Entity, Style, Option<Vec2>)> = world
+            .query::<(Entity, &Style, Option<&Text>)>()
+            .iter(world)
+            .map(|(entity, style, text)| (entity, style.clone(), text.map(estimated_text_size)))
+            .collect();
+        let mut lines: HashMap<Entity, f32> = HashMap::new();
+        let roots: Vec<Entity> = world
+            .query_filtered::<Entity, (With<Style>, Without<Parent>)>()
+            .iter(world)
+            .collect();
+        let camera = world.spawn_empty().id();
+        for pass in 0..2 {
+            for (entity, style, text_size) in &nodes {
+                let mut style = style.clone();
+                if let Some(size) = text_size {
+                    if style.width == Val::Auto {
+                        style.width = Val::Px(size.x);
+                        style.max_width = Val::Percent(100.0);
+                    }
+                    if style.height == Val::Auto {
+                        style.height = Val::Px(size.y * lines.get(entity).copied().unwrap_or(1.0));
+                    }
+                    style.flex_shrink = 0.0;
+                }
+                surface.upsert_node(*entity, &style, &context);
+            }
+            if pass == 0 {
+                for (entity, children) in world.query_filtered::<(Entity, &Children), With<Style>>().iter(world) {
+                    surface.update_children(entity, children);
+                }
+                surface.set_camera_children(camera, roots.iter().copied());
+            }
+            surface.compute_camera_layout(camera, window.as_uvec2());
+            for (entity, _, text_size) in &nodes {
+                if let Some(size) = text_size {
+                    let width = surface.get_layout(*entity).unwrap().size.width;
+                    lines.insert(*entity, (size.x * scale / width.max(1.0)).ceil().max(1.0));
+                }
+            }
+        }
+
+        let mut rects = Vec::new();
+        let mut pending: Vec<(Entity, Vec2, Rect)> = roots
+            .into_iter()
+            .map(|root| (root, Vec2::ZERO, Rect::from_corners(Vec2::ZERO, window)))
+            .collect();
+        while let Some((entity, origin, clip)) = pending.pop() {
+            let layout = surface.get_layout(entity).unwrap();
+            let min = origin + Vec2::new(layout.location.x, layout.location.y);
+            let rect = Rect::from_corners(min, min + Vec2::new(layout.size.width, layout.size.height));
+            rects.push((entity, rect, clip));
+            // 裁剪节点里的内容本来就可以超出（列表靠它滚动），只检查裁剪节点本身
+            let clips = world.get::<Style>(entity).is_some_and(|style| style.overflow != Overflow::visible());
+            let child_clip = if clips { Rect::new(f32::MIN, f32::MIN, f32::MAX, f32::MAX) } else { clip };
+            if let Some(children) = world.get::<Children>(entity) {
+                pending.extend(children.iter().map(|child| (*child, min, child_clip)));
+            }
+        }
+        rects
+    }
+
+    // 裁剪区域以外的节点都完整显示在窗口内
+    fn assert_fits(world: &mut World, screen: &str, scale: f32, window: Vec2) {
+        for (entity, rect, clip) in layout_rects(world, scale, window) {
+            let fits = rect.min.x >= clip.min.x - 0.5
+                && rect.min.y >= clip.min.y - 0.5
+                && rect.max.x <= clip.max.x + 0.5
+                && rect.max.y <= clip.max.y + 0.5;
+            let text = world.get::<Text>(entity).map(|text| text.sections[0].value.clone());
+            assert!(
+                fits,
+                "{} at {}x in a {}x{} window: {:?} {:?} is outside {:?}",
+                screen, scale, window.x, window.y, text, rect, clip
+            );
+        }
+    }
+
+    fn ui_scales() -> [f32; 2] {
+        [settings::MIN_UI_SCALE, settings::MAX_UI_SCALE]
+    }
+
+    // 最小缩放对应的最小窗口和默认窗口，以及最大缩放对应的窗口
+    fn windows_for(scale: f32) -> [Vec2; 2] {
+        let default = Vec2::new(WINDOW_WIDTH, WINDOW_HEIGHT);
+        [default, default * scale]
+    }
+
+    fn spawn_with_commands(app: &mut App, spawn: impl FnOnce(&mut Commands, &FontAssets, &Locale)) {
+        let mut queue = bevy::ecs::system::CommandQueue::default();
+        let world = &app.world;
+        let mut commands = Commands::new(&mut queue, world);
+        spawn(&mut commands, world.resource::<FontAssets>(), world.resource::<Locale>());
+        queue.apply(&mut app.world);
+    }
+
+    fn fill_text<T: Component>(app: &mut App, value: String) {
+        let mut texts = app.world.query_filtered::<&mut Text, With<T>>();
+        for mut text in texts.iter_mut(&mut app.world) {
+            text.sections[0].value = value.clone();
+        }
+    }
+
+    #[test]
+    fn leaderboard_fits_at_min_and_max_ui_scale() {
+        for language in i18n::Language::ALL {
+            for scale in ui_scales() {
+                for window in windows_for(scale) {
+                    let mut app = ui_app(language);
+                    spawn_with_commands(&mut app, |commands, fonts, locale| {
+                        let seasons = LeaderboardSeasons {
+                            current_label: Some("2025-06".to_string()),
+                            ..default()
+                        };
+                        spawn_leaderboard_ui(
+                            commands,
+                            fonts,
+                            locale,
+                            &LeaderboardData::Loading,
+                            &seasons,
+                            &RecentScores::default(),
+                            &DifficultySettings::new(Difficulty::Medium),
+                            ServerStatus::BackingOff,
+                        );
+                    });
+                    assert_fits(&mut app.world, "leaderboard", scale, window);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn pause_menu_over_the_hud_fits_at_min_and_max_ui_scale() {
+        for language in i18n::Language::ALL {
+            for scale in ui_scales() {
+                for window in windows_for(scale) {
+                    let mut app = ui_app(language);
+                    spawn_with_commands(&mut app, |commands, fonts, _| {
+                        setup_ui(commands, fonts, true, Orientation::Normal);
+                    });
+                    // 状态栏按最长的情况填写
+                    let locale = Locale::new(language);
+                    fill_text::<ScoreText>(&mut app, locale.tr("hud.score", &[("score", &9_999_999)]));
+                    fill_text::<LevelText>(&mut app, locale.tr("hud.level", &[("level", &99)]));
+                    fill_text::<LivesText>(&mut app, locale.tr("hud.lives", &[("lives", &9)]));
+                    fill_text::<TimerText>(&mut app, locale.tr("hud.time", &[("seconds", &180)]));
+                    fill_text::<LaserText>(&mut app, locale.tr("hud.laser", &[("seconds", &"10.0")]));
+                    fill_text::<SlowMotionText>(&mut app, locale.tr("hud.slow_motion", &[("seconds", &"10.0")]));
+                    app.world.run_system_once(setup_pause_menu);
+                    assert_fits(&mut app.world, "pause menu", scale, window);
+                }
+            }
+        }
+    }
+
+    // 统计、字幕和选项都还没显示时同样参与排版，一次检查完整的结尾
+    #[test]
+    fn ending_fits_at_min_and_max_ui_scale() {
+        for language in i18n::Language::ALL {
+            for scale in ui_scales() {
+                for window in windows_for(scale) {
+                    let mut app = ui_app(language);
+                    app.insert_resource(Score(9_999_999))
+                        .insert_resource(Level(ending::FINAL_LEVEL))
+                        .insert_resource(DifficultySettings::new(Difficulty::Medium))
+                        .init_resource::<RunStats>()
+                        .init_resource::<Settings>();
+                    app.world.run_system_once(ending::setup_ending);
+                    assert_fits(&mut app.world, "ending", scale, window);
+                }
+            }
+        }
+    }
+
+    // 暂停菜单的重新开始：真实的 pause_menu_system 和 PaddlePlugin，setup_game 换成只标记已初始化
+    fn pause_menu_app() -> App {
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins)
+            .init_state::<GameState>()
+            .init_resource::<ButtonInput<KeyCode>>()
+            .init_resource::<Gamepads>()
+            .init_resource::<ButtonInput<GamepadButton>>()
+            .init_resource::<ResumeKind>()
+            .insert_resource(GameInitialized(false))
+            .insert_resource(Level(1))
+            .insert_resource(Score(0))
+            .insert_resource(Lives(3))
+            .insert_resource(DifficultySettings::new(Difficulty::Medium))
+            .insert_resource(RunSeed(1))
+            .init_resource::<PowerUpEffects>()
+            .init_resource::<ReplayRecorder>()
+            .init_resource::<RunStats>()
+            .init_resource::<EventFeed>()
+            .init_resource::<RunSubmitted>()
+            .init_resource::<DailyRun>()
+            .init_resource::<EventRun>()
+            .init_resource::<BonusStage>()
+            .init_resource::<BossStage>()
+            .init_resource::<Orientation>()
+            .init_resource::<GameMode>()
+            .init_resource::<Insurance>()
+            .init_resource::<HotTeam>()
+            .init_resource::<Curse>()
+            .init_resource::<Cosmetics>()
+            .add_plugins(PaddlePlugin)
+            .add_systems(
+                OnEnter(GameState::Playing),
+                (|mut initialized: ResMut<GameInitialized>| initialized.0 = true)
+                    .before(PaddleSet::Spawn)
+                    .run_if(resume::full_setup),
+            )
+            .add_systems(OnExit(GameState::Playing), resume::leave_playing)
+            .add_systems(Update, pause_menu_system.run_if(in_state(GameState::Paused)));
+        app.world.resource_mut::<NextState<GameState>>().set(GameState::Playing);
+        app
+    }
+
+    // 跑一帧并检查挡板数：任何一帧都不能有两个，Playing 时恰好一个
+    fn update_checking_paddles(app: &mut App) -> Vec<Entity> {
+        app.update();
+        let paddles: Vec<Entity> = app.world.query_filtered::<Entity, With<Paddle>>().iter(&app.world).collect();
+        let state = *app.world.resource::<State<GameState>>().get();
+        assert!(paddles.len() <= 1, "{} paddles in {:?}", paddles.len(), state);
+        if state == GameState::Playing {
+            assert_eq!(paddles.len(), 1, "no paddle while playing");
+        }
+        paddles
+    }
+
+    fn press_in_pause_menu(app: &mut App, key: KeyCode) {
+        app.world.resource_mut::<NextState<GameState>>().set(GameState::Paused);
+        for _ in 0..3 {
+            update_checking_paddles(app);
+        }
+        assert_eq!(*app.world.resource::<State<GameState>>().get(), GameState::Paused);
+        app.world.resource_mut::<ButtonInput<KeyCode>>().press(key);
+        update_checking_paddles(app);
+        app.world.resource_mut::<ButtonInput<KeyCode>>().release(key);
+        app.world.resource_mut::<ButtonInput<KeyCode>>().clear();
+    }
+
+    #[test]
+    fn new_game_from_the_pause_menu_never_has_two_paddles() {
+        let mut app = pause_menu_app();
+        let first = update_checking_paddles(&mut app);
+        assert_eq!(first.len(), 1);
+
+        press_in_pause_menu(&mut app, KeyCode::KeyN);
+        let mut replaced = Vec::new();
+        for _ in 0..10 {
+            replaced = update_checking_paddles(&mut app);
+        }
+        assert_eq!(*app.world.resource::<State<GameState>>().get(), GameState::Playing);
+        assert_eq!(replaced.len(), 1);
+        assert_ne!(replaced, first, "the old paddle should be replaced by a new one");
+        assert!(app.world.get_entity(first[0]).is_none());
+    }
+
+    #[test]
+    fn continuing_from_the_pause_menu_keeps_the_paddle() {
+        let mut app = pause_menu_app();
+        let first = update_checking_paddles(&mut app);
+
+        press_in_pause_menu(&mut app, KeyCode::Escape);
+        let mut kept = Vec::new();
+        for _ in 0..10 {
+            kept = update_checking_paddles(&mut app);
+        }
+        assert_eq!(*app.world.resource::<State<GameState>>().get(), GameState::Playing);
+        assert_eq!(kept, first);
+    }
+
+    // 完整初始化跑了几次
+    #[derive(Resource, Default)]
+    struct FullSetups(u32);
+
+    fn resume_countdowns(app: &mut App) -> usize {
+        app.world.query::<&resume::ResumeCountdown>().iter(&app.world).count()
+    }
+
+    // 有球在飞时暂停再继续 20 次：每次只有一个倒计时，没有多出来的实体，不会重新初始化，分数和生命不变
+    #[test]
+    fn twenty_pauses_and_resumes_leave_the_run_unchanged() {
+        let mut app = pause_menu_app();
+        app.add_plugins(AssetPlugin::default())
+            .init_asset::<Font>()
+            .insert_resource(Locale::new(i18n::Language::En))
+            .insert_resource(Score(1234))
+            .insert_resource(Lives(2))
+            .init_resource::<FullSetups>()
+            .add_systems(
+                OnEnter(GameState::Playing),
+                (
+                    (|mut setups: ResMut<FullSetups>| setups.0 += 1).run_if(resume::full_setup),
+                    (resume::validate_after_resume, resume::start_resume_countdown).chain().run_if(resume::from_pause),
+                ),
+            )
+            .add_systems(Update, resume::resume_countdown_system.run_if(in_state(GameState::Playing)));
+        app.init_resource::<FontAssets>();
+        app.world.spawn((
+            Transform::from_xyz(0.0, 0.0, 1.0),
+            Ball { velocity: Vec2::new(0.6, 1.0).normalize() * BALL_SPEED },
+        ));
+        update_checking_paddles(&mut app);
+        assert_eq!(app.world.resource::<FullSetups>().0, 1);
+        let entities = app.world.entities().len();
+
+        for round in 0..20 {
+            press_in_pause_menu(&mut app, KeyCode::Escape);
+            for _ in 0..3 {
+                update_checking_paddles(&mut app);
+            }
+            assert_eq!(*app.world.resource::<State<GameState>>().get(), GameState::Playing, "round {}", round);
+            assert_eq!(resume_countdowns(&mut app), 1, "round {}", round);
+            // 倒计时本身是一个节点加一行文字
+            assert_eq!(app.world.entities().len(), entities + 2, "round {}", round);
+        }
+        assert_eq!(app.world.resource::<FullSetups>().0, 1);
+        assert_eq!(app.world.resource::<Score>().0, 1234);
+        assert_eq!(app.world.resource::<Lives>().0, 2);
+        assert!(app.world.resource::<Time<Virtual>>().is_paused(), "the countdown holds game time");
+    }
+
+    // 统计测试期间输出的警告（Bevy 删除不存在的实体时会输出警告）
+    struct WarningCounter(std::sync::Arc<std::sync::atomic::AtomicUsize>);
+
+    impl<S: bevy::utils::tracing::Subscriber> bevy::log::tracing_subscriber::Layer<S> for WarningCounter {
+        fn on_event(
+            &self,
+            event: &bevy::utils::tracing::Event<'_>,
+            _ctx: bevy::log::tracing_subscriber::layer::Context<'_, S>,
+        ) {
+            if *event.metadata().level() <= bevy::utils::tracing::Level::WARN {
+                self.0.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+            }
+        }
+    }
+
+    // 在 f 运行期间统计警告数
+    fn count_warnings(f: impl FnOnce()) -> usize {
+        use bevy::log::tracing_subscriber::layer::SubscriberExt;
+        let warnings = std::sync::Arc::new(std::sync::atomic::AtomicUsize::new(0));
+        let subscriber = bevy::log::tracing_subscriber::registry().with(WarningCounter(warnings.clone()));
+        bevy::utils::tracing::subscriber::with_default(subscriber, f);
+        warnings.load(std::sync::atomic::Ordering::Relaxed)
+    }
+
+    // 只有一块砖，同一帧里有 balls 个球和 lasers 道激光同时打中它
+    fn brick_kill_app(brick: Brick, balls: usize, lasers: usize) -> App {
+        let step = Duration::from_secs_f64(1.0 / fixed_step::PHYSICS_HZ);
+        let mut app = physics_app(step);
+        app.add_systems(
+            FixedUpdate,
+            laser_collision
+                .after(ball_collision)
+                .run_if(in_state(GameState::Playing).and_then(fixed_step::no_transition_pending)),
+        );
+        let existing: Vec<Entity> = app
+            .world
+            .query_filtered::<Entity, Or<(With<Brick>, With<Ball>)>>()
+            .iter(&app.world)
+            .collect();
+        for entity in existing {
+            app.world.despawn(entity);
+        }
+        let position = brick_position(2, 3);
+        app.world.spawn((
+            Transform::from_translation(position.extend(0.0)).with_scale(BRICK_SIZE.extend(1.0)),
+            brick,
+        ));
+        for _ in 0..balls {
+            let ball_position = position - Vec2::new(0.0, (BRICK_SIZE.y + BALL_SIZE.y) / 2.0 - 2.0);
+            app.world.spawn((
+                Transform::from_translation(ball_position.extend(1.0)),
+                Ball { velocity: Vec2::new(0.0, BALL_SPEED) },
+            ));
+        }
+        for _ in 0..lasers {
+            app.world.spawn((Transform::from_translation(position.extend(1.0)), Laser { velocity: Vec2::ZERO }));
+        }
+        app
+    }
+
+    fn one_hit_brick(guaranteed_drop: Option<PowerUpType>) -> Brick {
+        Brick {
+            brick_type: BrickType::Normal,
+            health: 1,
+            shade: 1.0,
+            guaranteed_drop,
+            golden: false,
+        }
+    }
+
+    // 返回得分和警告数
+    fn same_frame_kill(balls: usize, lasers: usize) -> (u32, usize) {
+        let mut app = brick_kill_app(one_hit_brick(None), balls, lasers);
+        let warnings = count_warnings(|| {
+            for _ in 0..3 {
+                app.update();
+            }
+        });
+        assert_eq!(app.world.query::<&Brick>().iter(&app.world).count(), 0, "the brick should be destroyed");
+        (app.world.resource::<Score>().0, warnings)
+    }
+
+    #[test]
+    fn warning_counter_sees_despawn_warnings() {
+        let mut world = World::new();
+        let gone = world.spawn_empty().id();
+        world.despawn(gone);
+        assert_eq!(count_warnings(|| assert!(!world.despawn(gone))), 1);
+    }
+
+    // 激光和球、两道激光或两个球在同一帧打碎同一块砖：只计一次分，也不会因为重复删除输出警告
+    #[test]
+    fn lasers_and_balls_killing_one_brick_score_it_once() {
+        let (ball_only, _) = same_frame_kill(1, 0);
+        let (laser_only, _) = same_frame_kill(0, 1);
+        assert!(ball_only > 0 && laser_only > 0);
+        assert_eq!(same_frame_kill(0, 2), (laser_only, 0));
+        assert_eq!(same_frame_kill(2, 0), (ball_only, 0));
+        // 球先结算，砖块只算球的分
+        assert_eq!(same_frame_kill(1, 2), (ball_only, 0));
+    }
+
+    fn carriers(layout: &Layout) -> Vec<((usize, usize), PowerUpType)> {
+        layout
+            .bricks
+            .iter()
+            .filter_map(|spec| spec.guaranteed_drop.map(|power_type| (spec.cell, power_type)))
+            .collect()
+    }
+
+    #[test]
+    fn every_layout_carries_a_multiball_and_a_laser() {
+        for level in 1..=12 {
+            for seed in 0..100 {
+                let layout = generate_layout(level, seed);
+                let drops = carriers(&layout);
+                for required in GUARANTEED_DROPS {
+                    assert!(
+                        drops.iter().any(|&(_, power_type)| power_type == required),
+                        "level {level} seed {seed} has no {}",
+                        required.name()
+                    );
+                }
+                for spec in layout.bricks.iter().filter(|spec| spec.guaranteed_drop.is_some()) {
+                    assert!(
+                        !matches!(spec.brick_type, BrickType::Unbreakable | BrickType::TimedBomb),
+                        "level {level} seed {seed} hides a drop in {:?}",
+                        spec.cell
+                    );
+                }
+                // 手工设计的关卡自带保底道具，随机关卡按可破坏砖块数额外保底
+                if level != shield::SHIELD_LEVEL && level != quota::QUOTA_LEVEL {
+                    let breakable = layout
+                        .bricks
+                        .iter()
+                        .filter(|spec| !matches!(spec.brick_type, BrickType::Unbreakable | BrickType::TimedBomb))
+                        .count();
+                    assert_eq!(drops.len(), GUARANTEED_DROPS.len() + breakable / BRICKS_PER_EXTRA_DROP);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn carriers_are_reproducible_and_prefer_the_middle_rows() {
+        for seed in 0..50 {
+            let drops = carriers(&generate_layout(1, seed));
+            assert_eq!(drops, carriers(&generate_layout(1, seed)));
+            // 第一关没有不可破坏砖块，保底道具都落在最中间的两行
+            assert!(drops.iter().all(|&((row, _), _)| row == BRICK_ROWS / 2 - 1 || row == BRICK_ROWS / 2), "{drops:?}");
+        }
+        let first = carriers(&generate_layout(1, 0));
+        assert!((1..50).any(|seed| carriers(&generate_layout(1, seed)) != first), "carriers should depend on the seed");
+    }
+
+    // 布局里每块砖的格子、类型、生命和装甲，按格子排序
+    type BrickSummary = ((usize, usize), BrickType, i32, Option<Side>);
+
+    fn layout_summary(layout: &Layout) -> Vec<BrickSummary> {
+        let mut bricks: Vec<BrickSummary> =
+            layout.bricks.iter().map(|spec| (spec.cell, spec.brick_type, spec.health, spec.armor)).collect();
+        bricks.sort_by_key(|brick| brick.0);
+        bricks
+    }
+
+    #[test]
+    fn generated_layouts_depend_only_on_level_and_seed() {
+        for level in 1..=ending::FINAL_LEVEL {
+            for seed in [0, 7, 12345] {
+                let layout = layout_summary(&generate_layout(level, seed));
+                assert_eq!(layout, layout_summary(&generate_layout(level, seed)), "level {}", level);
+            }
+        }
+        let first = layout_summary(&generate_layout(3, 0));
+        assert!((1..20).any(|seed| layout_summary(&generate_layout(3, seed)) != first), "layouts depend on the seed");
+        assert_ne!(layout_summary(&generate_layout(2, 5)), layout_summary(&generate_layout(3, 5)));
+    }
+
+    // 关卡预览用 generate_layout 提前生成，spawn_bricks 按同一份布局生成砖块，两者必须一致
+    #[test]
+    fn spawned_bricks_match_the_previewed_layout() {
+        for (level, seed) in [(1, 3), (2, 11), (4, 99), (shield::SHIELD_LEVEL, 5), (quota::QUOTA_LEVEL, 5)] {
+            let layout = generate_layout(level, seed);
+            let mut world = World::new();
+            let mut queue = bevy::ecs::system::CommandQueue::default();
+            let mut commands = Commands::new(&mut queue, &world);
+            let theme = SeasonalTheme::new(Some(false));
+            spawn_bricks(&mut commands, &layout, Orientation::Normal, &EventRun::default(), &theme);
+            queue.apply(&mut world);
+
+            let mut spawned: Vec<BrickSummary> = world
+                .query::<(&Brick, &Transform, Option<&ArmorPlate>)>()
+                .iter(&world)
+                .map(|(brick, transform, armor)| {
+                    let cell = brick_cell(transform.translation).expect("bricks sit on the grid");
+                    (cell, brick.brick_type, brick.health, armor.map(|plate| plate.0))
+                })
+                .collect();
+            spawned.sort_by_key(|brick| brick.0);
+            assert_eq!(spawned, layout_summary(&layout), "level {} seed {}", level, seed);
+        }
+    }
+
+    // 一块带装甲的坚硬砖块（生命 2），装甲在 plate 一面
+    fn armored_brick_app(plate: Side) -> (App, Vec3) {
+        let hard = Brick {
+            brick_type: BrickType::Hard,
+            health: 2,
+            ..one_hit_brick(None)
+        };
+        let mut app = brick_kill_app(hard, 0, 0);
+        let (brick, transform) = app.world.query_filtered::<(Entity, &Transform), With<Brick>>().single(&app.world);
+        let position = transform.translation;
+        app.world.entity_mut(brick).insert(ArmorPlate(plate));
+        (app, position)
+    }
+
+    // 离砖块 from 一面 1 像素、正朝砖块飞去的球，下一步就会撞上，反弹后的下一步离开砖块
+    fn ball_from(brick: Vec3, from: Side) -> (Vec3, Vec2) {
+        let direction = match from {
+            Side::Top => Vec2::Y,
+            Side::Bottom => Vec2::NEG_Y,
+            Side::Left => Vec2::NEG_X,
+            Side::Right => Vec2::X,
+        };
+        let offset = direction * ((BRICK_SIZE + BALL_SIZE) / 2.0 + 1.0);
+        ((brick.xy() + offset).extend(1.0), -direction * BALL_SPEED)
+    }
+
+    // 运行几帧后砖块剩下的生命值，砖块被打碎时为 None
+    fn brick_health_after_hit(app: &mut App) -> Option<i32> {
+        for _ in 0..3 {
+            app.update();
+        }
+        app.world.query::<&Brick>().iter(&app.world).next().map(|brick| brick.health)
+    }
+
+    fn ball_hits_armor(plate: Side, from: Side, penetrating: bool) -> Option<i32> {
+        let (mut app, brick) = armored_brick_app(plate);
+        app.world.resource_mut::<PowerUpEffects>().penetrating_ball = penetrating;
+        let (position, velocity) = ball_from(brick, from);
+        app.world.spawn((Transform::from_translation(position), Ball { velocity }));
+        brick_health_after_hit(&mut app)
+    }
+
+    #[test]
+    fn collisions_map_to_the_struck_side_of_the_brick() {
+        let brick = brick_position(2, 3).extend(0.0);
+        for from in Side::ALL {
+            let (position, velocity) = ball_from(brick, from);
+            let position = position + (velocity.normalize() * 3.0).extend(0.0);
+            let collision = collide(position, BALL_SIZE, brick, BRICK_SIZE).expect("touching");
+            assert_eq!(Side::struck_by(collision), from);
+        }
+    }
+
+    // 每种装甲方向和击中方向的组合：只有击中装甲面时不掉血
+    #[test]
+    fn armor_only_blocks_hits_on_the_plated_side() {
+        for plate in Side::ALL {
+            for from in Side::ALL {
+                let expected = if plate == from { Some(2) } else { Some(1) };
+                assert_eq!(ball_hits_armor(plate, from, false), expected, "plate {plate:?}, hit from {from:?}");
+            }
+        }
+    }
+
+    #[test]
+    fn penetrating_balls_ignore_armor() {
+        for side in Side::ALL {
+            let health = ball_hits_armor(side, side, true);
+            assert!(health.is_none_or(|health| health < 2), "plate {side:?}: {health:?}");
+        }
+    }
+
+    // 激光总是从下面击中砖块，只有底面的装甲挡得住
+    #[test]
+    fn lasers_are_stopped_only_by_bottom_armor() {
+        for plate in Side::ALL {
+            let (mut app, brick) = armored_brick_app(plate);
+            app.add_systems(
+                FixedUpdate,
+                laser_collision.run_if(in_state(GameState::Playing).and_then(fixed_step::no_transition_pending)),
+            );
+            app.world.spawn((Transform::from_translation(brick), Laser { velocity: Vec2::ZERO }));
+            let expected = if plate == Side::Bottom { Some(2) } else { None };
+            assert_eq!(brick_health_after_hit(&mut app), expected, "plate {plate:?}");
+            assert_eq!(app.world.query::<&Laser>().iter(&app.world).count(), 0);
+        }
+    }
+
+    fn dropped_power_ups(app: &mut App) -> Vec<PowerUpType> {
+        for _ in 0..3 {
+            app.update();
+        }
+        app.world.query::<&PowerUp>().iter(&app.world).map(|power_up| power_up.power_type).collect()
+    }
+
+    // 保底道具砖被打碎时一定掉落指定道具，球和激光都一样；激光打碎普通砖不掉落
+    #[test]
+    fn destroyed_carriers_drop_their_power_up() {
+        let mut ball_kill = brick_kill_app(one_hit_brick(Some(PowerUpType::MultiBall)), 1, 0);
+        assert_eq!(dropped_power_ups(&mut ball_kill), [PowerUpType::MultiBall]);
+        let mut laser_kill = brick_kill_app(one_hit_brick(Some(PowerUpType::LaserGun)), 0, 1);
+        assert_eq!(dropped_power_ups(&mut laser_kill), [PowerUpType::LaserGun]);
+        let mut plain_laser_kill = brick_kill_app(one_hit_brick(None), 0, 1);
+        assert_eq!(dropped_power_ups(&mut plain_laser_kill), []);
+    }
+}
@@ -0,0 +1,180 @@
+use bevy::prelude::*;
+
+use crate::governor::EffectBudget;
+use crate::paddle::Paddle;
+use crate::settings::Settings;
+use crate::{DifficultySettings, GameEntity, PADDLE_SPEED};
+
+// 挡板速度超过最大速度的这个比例时留下残影
+const AFTERIMAGE_SPEED_RATIO: f32 = 0.6;
+// 残影最多同时存在几个、隔多久留一个、多久淡出
+const MAX_AFTERIMAGES: usize = 3;
+const AFTERIMAGE_INTERVAL: f32 = 0.04;
+const AFTERIMAGE_LIFETIME: f32 = 0.12;
+const AFTERIMAGE_ALPHA: f32 = 0.35;
+// 一帧内移动超过这个倍数的最大速度是换关或重新开始时的瞬移，不算移动
+const TELEPORT_SPEED_RATIO: f32 = 3.0;
+
+// 墙上的撞击印记最多同时存在几个，以及淡出时间
+const MAX_DECALS: usize = 12;
+const DECAL_LIFETIME: f32 = 1.5;
+const DECAL_LENGTH: f32 = 18.0;
+const DECAL_THICKNESS: f32 = 4.0;
+const DECAL_COLOR: Color = Color::rgba(0.6, 0.95, 1.0, 0.8);
+
+// 球撞到左右墙或顶墙，ball_collision 在反弹时发送；位置是墙上的接触点
+#[derive(Event, Debug, Clone, Copy)]
+pub struct WallImpact {
+    pub position: Vec2,
+    // 左右两侧的墙为真，顶墙为假
+    pub side_wall: bool,
+}
+
+// 挡板的残影，从最多 MAX_AFTERIMAGES 个实体中循环使用
+#[derive(Component)]
+pub struct Afterimage {
+    age: f32,
+}
+
+// 墙上的撞击印记，同样循环使用
+#[derive(Component)]
+pub struct WallDecal {
+    age: f32,
+}
+
+// 减少动态效果和最低画质档位下关闭残影和印记
+fn trails_enabled(settings: &Settings, budget: &EffectBudget) -> bool {
+    !settings.reduce_motion && budget.minor_effects()
+}
+
+// 挡板快速移动时每隔一小段时间在原位置留下一个淡出的残影；
+// 数量已满时重用最旧的那个
+pub fn paddle_afterimages(
+    mut commands: Commands,
+    time: Res<Time>,
+    settings: Res<Settings>,
+    budget: Res<EffectBudget>,
+    difficulty_settings: Res<DifficultySettings>,
+    paddles: Query<(&Transform, &Sprite), With<Paddle>>,
+    mut afterimages: Query<(&mut Afterimage, &mut Transform, &mut Sprite, &mut Visibility), Without<Paddle>>,
+    mut last_x: Local<Option<f32>>,
+    mut since_last: Local<f32>,
+) {
+    let delta = time.delta_seconds();
+    for (mut afterimage, _, mut sprite, mut visibility) in afterimages.iter_mut() {
+        afterimage.age += delta;
+        let fade = 1.0 - afterimage.age / AFTERIMAGE_LIFETIME;
+        if fade <= 0.0 {
+            *visibility = Visibility::Hidden;
+        } else {
+            sprite.color.set_a(AFTERIMAGE_ALPHA * fade);
+        }
+    }
+
+    let Ok((paddle_transform, paddle_sprite)) = paddles.get_single() else {
+        *last_x = None;
+        return;
+    };
+    let x = paddle_transform.translation.x;
+    let previous = last_x.replace(x);
+    *since_last += delta;
+    let Some(previous) = previous else {
+        return;
+    };
+    if delta <= 0.0 || !trails_enabled(&settings, &budget) {
+        return;
+    }
+    let max_speed = PADDLE_SPEED * difficulty_settings.paddle_speed_modifier;
+    let speed = (x - previous).abs() / delta;
+    if speed < max_speed * AFTERIMAGE_SPEED_RATIO || speed > max_speed * TELEPORT_SPEED_RATIO {
+        return;
+    }
+    if *since_last < AFTERIMAGE_INTERVAL {
+        return;
+    }
+    *since_last = 0.0;
+
+    let mut color = paddle_sprite.color;
+    color.set_a(AFTERIMAGE_ALPHA);
+    let transform = Transform {
+        translation: paddle_transform.translation - Vec3::Z * 0.1,
+        ..*paddle_transform
+    };
+    if afterimages.iter().len() < MAX_AFTERIMAGES {
+        commands.spawn((
+            SpriteBundle {
+                sprite: Sprite { color, ..default() },
+                transform,
+                ..default()
+            },
+            Afterimage { age: 0.0 },
+            GameEntity,
+        ));
+        return;
+    }
+    let oldest = afterimages.iter_mut().max_by(|(a, ..), (b, ..)| a.age.total_cmp(&b.age));
+    if let Some((mut afterimage, mut old_transform, mut sprite, mut visibility)) = oldest {
+        afterimage.age = 0.0;
+        *old_transform = transform;
+        sprite.color = color;
+        *visibility = Visibility::Inherited;
+    }
+}
+
+// 在墙上的接触点留下发光的印记并逐渐淡出，数量已满时重用最旧的印记
+pub fn wall_decals(
+    mut commands: Commands,
+    time: Res<Time>,
+    settings: Res<Settings>,
+    budget: Res<EffectBudget>,
+    mut impacts: EventReader<WallImpact>,
+    mut decals: Query<(&mut WallDecal, &mut Transform, &mut Sprite, &mut Visibility)>,
+) {
+    let delta = time.delta_seconds();
+    for (mut decal, _, mut sprite, mut visibility) in decals.iter_mut() {
+        decal.age += delta;
+        let fade = 1.0 - decal.age / DECAL_LIFETIME;
+        if fade <= 0.0 {
+            *visibility = Visibility::Hidden;
+        } else {
+            sprite.color.set_a(DECAL_COLOR.a() * fade);
+        }
+    }
+
+    if !trails_enabled(&settings, &budget) {
+        impacts.clear();
+        return;
+    }
+    let mut count = decals.iter().len();
+    for impact in impacts.read() {
+        let size = if impact.side_wall {
+            Vec2::new(DECAL_THICKNESS, DECAL_LENGTH)
+        } else {
+            Vec2::new(DECAL_LENGTH, DECAL_THICKNESS)
+        };
+        let transform = Transform::from_translation(impact.position.extend(0.3)).with_scale(size.extend(1.0));
+        if count < MAX_DECALS {
+            count += 1;
+            commands.spawn((
+                SpriteBundle {
+                    sprite: Sprite {
+                        color: DECAL_COLOR,
+                        ..default()
+                    },
+                    transform,
+                    ..default()
+                },
+                WallDecal { age: 0.0 },
+                GameEntity,
+            ));
+            continue;
+        }
+        let oldest = decals.iter_mut().max_by(|(a, ..), (b, ..)| a.age.total_cmp(&b.age));
+        if let Some((mut decal, mut old_transform, mut sprite, mut visibility)) = oldest {
+            decal.age = 0.0;
+            *old_transform = transform;
+            sprite.color = DECAL_COLOR;
+            *visibility = Visibility::Inherited;
+        }
+    }
+}
@@ -0,0 +1,786 @@
+use bevy::prelude::*;
+use rand::Rng;
+use std::error::Error;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use crate::api::{
+    ApiClient, CreateScoreRequest, DailyResponse, FlagsResponse, LeaderboardResponse, LevelHistogram, RaceProgressRequest,
+    RaceResponse, RivalsResponse, Score, SeasonsResponse, EventResponse,
+};
+use crate::remote_flags::{self, RemoteFlags};
+use crate::GameState;
+
+// 发给后台网络线程的请求
+#[derive(Debug)]
+pub enum ApiCommand {
+    SubmitScore(CreateScoreRequest),
+    GetLeaderboard {
+        limit: Option<usize>,
+        difficulty: Option<String>,
+        season: Option<String>,
+        // 有游标时请求的是下一页
+        cursor: Option<String>,
+    },
+    GetSeasons,
+    GetRecentScores {
+        limit: usize,
+    },
+    GetLevelHistogram {
+        difficulty: String,
+    },
+    GetDaily {
+        date: String,
+        player_name: String,
+        limit: usize,
+    },
+    GetEvent,
+    GetFlags,
+    CreateRace {
+        player_name: String,
+    },
+    JoinRace {
+        code: String,
+        player_name: String,
+    },
+    // 比赛中定期上报进度，同时取回对手的进度
+    RaceProgress {
+        code: String,
+        request: RaceProgressRequest,
+    },
+    GetRace {
+        code: String,
+    },
+    GetRivals {
+        player_name: String,
+        difficulty: String,
+    },
+    // 管理员操作，只有配置了管理密钥时才会发出
+    DeleteScore {
+        id: String,
+    },
+    RenameScore {
+        id: String,
+        player_name: String,
+    },
+}
+
+impl ApiCommand {
+    // 只读请求在取消后可以直接跳过，提交分数和比赛请求必须执行
+    fn is_read_only(&self) -> bool {
+        !matches!(
+            self,
+            ApiCommand::SubmitScore(_)
+                | ApiCommand::CreateRace { .. }
+                | ApiCommand::JoinRace { .. }
+                | ApiCommand::RaceProgress { .. }
+                | ApiCommand::DeleteScore { .. }
+                | ApiCommand::RenameScore { .. }
+        )
+    }
+
+    // 游戏进行中也要立即发送的请求；其余请求推迟到离开游戏状态后再发
+    fn is_essential(&self) -> bool {
+        matches!(self, ApiCommand::SubmitScore(_) | ApiCommand::RaceProgress { .. })
+    }
+}
+
+// 每帧最多分发的响应数，一次返回多个响应时分摊到后面几帧
+const MAX_RESPONSES_PER_FRAME: usize = 2;
+
+// 只读请求最多尝试的次数
+const MAX_ATTEMPTS: u32 = 3;
+// 重试的基础间隔和上限，每次翻倍
+const RETRY_BASE_DELAY: Duration = Duration::from_millis(250);
+const RETRY_MAX_DELAY: Duration = Duration::from_secs(2);
+// 连续失败多少次后熔断，以及熔断持续时间
+const BREAKER_FAILURE_THRESHOLD: u32 = 5;
+const BREAKER_OPEN_DURATION: Duration = Duration::from_secs(60);
+
+// 服务器连接状态，供界面显示
+#[derive(Resource, Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ServerStatus {
+    #[default]
+    Unknown,
+    Online,
+    Unreachable,
+    // 熔断中，暂时不发请求
+    BackingOff,
+}
+
+#[derive(Debug, Clone, Copy)]
+enum BreakerState {
+    Closed,
+    Open { until: Instant },
+    // 熔断时间结束后只放行一个探测请求
+    HalfOpen,
+}
+
+// 熔断器：连续失败后一段时间内直接失败，避免服务器抖动时反复请求
+#[derive(Debug)]
+struct CircuitBreaker {
+    state: BreakerState,
+    consecutive_failures: u32,
+}
+
+impl CircuitBreaker {
+    fn new() -> Self {
+        Self {
+            state: BreakerState::Closed,
+            consecutive_failures: 0,
+        }
+    }
+
+    fn allow(&mut self, now: Instant) -> bool {
+        match self.state {
+            BreakerState::Closed | BreakerState::HalfOpen => true,
+            BreakerState::Open { until } if now >= until => {
+                self.state = BreakerState::HalfOpen;
+                true
+            }
+            BreakerState::Open { .. } => false,
+        }
+    }
+
+    fn is_probing(&self) -> bool {
+        matches!(self.state, BreakerState::HalfOpen)
+    }
+
+    fn record_success(&mut self) {
+        self.state = BreakerState::Closed;
+        self.consecutive_failures = 0;
+    }
+
+    fn record_failure(&mut self, now: Instant) {
+        self.consecutive_failures += 1;
+        if self.is_probing() || self.consecutive_failures >= BREAKER_FAILURE_THRESHOLD {
+            self.state = BreakerState::Open {
+                until: now + BREAKER_OPEN_DURATION,
+            };
+        }
+    }
+
+    fn status(&self) -> ServerStatus {
+        match self.state {
+            BreakerState::Open { .. } | BreakerState::HalfOpen => ServerStatus::BackingOff,
+            BreakerState::Closed if self.consecutive_failures > 0 => ServerStatus::Unreachable,
+            BreakerState::Closed => ServerStatus::Online,
+        }
+    }
+}
+
+// 第 attempt 次失败后的等待时间：指数退避，再随机取后一半避免同时重试
+fn backoff_delay(attempt: u32, jitter: f64) -> Duration {
+    let delay = RETRY_BASE_DELAY.saturating_mul(1 << attempt.min(16)).min(RETRY_MAX_DELAY);
+    delay.mul_f64(0.5 + 0.5 * jitter.clamp(0.0, 1.0))
+}
+
+// 重试策略用到的时间和随机数，测试中换成脚本化的时钟
+trait RetryClock {
+    fn now(&self) -> Instant;
+    fn sleep(&mut self, delay: Duration);
+    fn jitter(&mut self) -> f64;
+}
+
+struct SystemClock;
+
+impl RetryClock for SystemClock {
+    fn now(&self) -> Instant {
+        Instant::now()
+    }
+
+    fn sleep(&mut self, delay: Duration) {
+        std::thread::sleep(delay);
+    }
+
+    fn jitter(&mut self) -> f64 {
+        rand::thread_rng().gen()
+    }
+}
+
+// 后台线程中的重试与熔断策略
+struct Resilience<C: RetryClock = SystemClock> {
+    breaker: CircuitBreaker,
+    status: Arc<Mutex<ServerStatus>>,
+    clock: C,
+}
+
+impl<C: RetryClock> Resilience<C> {
+    // 只读请求失败时重试，提交分数只尝试一次；熔断期间直接失败
+    fn run<T>(&mut self, retryable: bool, mut request: impl FnMut() -> Result<T, Box<dyn Error>>) -> Result<T, String> {
+        if !self.breaker.allow(self.clock.now()) {
+            return Err("Server temporarily unavailable, backing off".to_string());
+        }
+
+        let attempts = if retryable && !self.breaker.is_probing() { MAX_ATTEMPTS } else { 1 };
+        let mut last_error = String::new();
+        for attempt in 0..attempts {
+            match request() {
+                Ok(value) => {
+                    self.breaker.record_success();
+                    self.publish();
+                    return Ok(value);
+                }
+                Err(e) => {
+                    last_error = e.to_string();
+                    if attempt + 1 < attempts {
+                        let jitter = self.clock.jitter();
+                        self.clock.sleep(backoff_delay(attempt, jitter));
+                    }
+                }
+            }
+        }
+
+        self.breaker.record_failure(self.clock.now());
+        self.publish();
+        Err(last_error)
+    }
+
+    fn publish(&self) {
+        if let Ok(mut status) = self.status.lock() {
+            *status = self.breaker.status();
+        }
+    }
+}
+
+// 后台线程返回的结果，由 drain_api_responses 作为事件分发
+#[derive(Event, Debug)]
+pub enum ApiResponse {
+    // 保存的记录，带有它在所属赛季和难度榜单上的排名
+    ScoreSubmitted(Result<Score, String>),
+    Leaderboard(Result<LeaderboardResponse, String>),
+    LeaderboardPage(Result<LeaderboardResponse, String>),
+    Seasons(Result<SeasonsResponse, String>),
+    RecentScores(Result<Vec<Score>, String>),
+    LevelHistogram(Result<LevelHistogram, String>),
+    Daily(Result<DailyResponse, String>),
+    Event(Result<Option<EventResponse>, String>),
+    Flags(Result<FlagsResponse, String>),
+    RaceCreated(Result<RaceResponse, String>),
+    RaceJoined(Result<RaceResponse, String>),
+    RaceProgress(Result<RaceResponse, String>),
+    Race(Result<RaceResponse, String>),
+    Rivals(Result<RivalsResponse, String>),
+    // 带上成绩 id，界面据此确认或撤销对应的修改
+    ScoreDeleted { id: String, result: Result<(), String> },
+    ScoreRenamed { id: String, result: Result<(), String> },
+}
+
+// 网络请求计数，显示在性能面板中
+#[derive(Resource, Debug, Default)]
+pub struct NetworkStats {
+    // 因游戏进行中被推迟的请求总数
+    pub deferred: u64,
+    // 已分发的响应总数
+    pub processed: u64,
+    // 当前仍在等待的推迟请求
+    pub queued: usize,
+}
+
+// 与后台网络线程通信的通道
+#[derive(Resource)]
+pub struct Network {
+    commands: Sender<(u64, ApiCommand)>,
+    // 响应带上请求的代数以及请求是否只读，只有只读请求的响应会因过期被丢弃
+    responses: Mutex<Receiver<(u64, bool, ApiResponse)>>,
+    // 游戏进行中暂停非必要请求，暂停期间的请求保存在 deferred 中
+    background_paused: AtomicBool,
+    deferred: Mutex<Vec<(u64, ApiCommand)>>,
+    deferred_total: AtomicU64,
+    // 离开界面时递增，旧代的响应会被丢弃
+    generation: Arc<AtomicU64>,
+    // 后台线程更新的连接状态
+    status: Arc<Mutex<ServerStatus>>,
+    // 最近一次提交的分数，冒烟测试用来检查结算内容
+    last_submission: Mutex<Option<CreateScoreRequest>>,
+    // 客户端配置了管理密钥
+    admin_available: bool,
+}
+
+impl Network {
+    pub fn spawn(client: ApiClient) -> Self {
+        let (command_sender, command_receiver) = mpsc::channel::<(u64, ApiCommand)>();
+        let (response_sender, response_receiver) = mpsc::channel();
+        let generation = Arc::new(AtomicU64::new(0));
+        let status = Arc::new(Mutex::new(ServerStatus::default()));
+        let admin_available = client.has_admin_key();
+
+        let worker_generation = generation.clone();
+        let mut resilience = Resilience {
+            breaker: CircuitBreaker::new(),
+            status: status.clone(),
+            clock: SystemClock,
+        };
+        std::thread::spawn(move || {
+            // 按发送顺序逐个处理请求
+            for (command_generation, command) in command_receiver {
+                let read_only = command.is_read_only();
+                if read_only && command_generation != worker_generation.load(Ordering::SeqCst) {
+                    continue;
+                }
+                let response = run_command(&client, &mut resilience, command);
+                if response_sender.send((command_generation, read_only, response)).is_err() {
+                    break;
+                }
+            }
+        });
+
+        Self {
+            commands: command_sender,
+            responses: Mutex::new(response_receiver),
+            background_paused: AtomicBool::new(false),
+            deferred: Mutex::new(Vec::new()),
+            deferred_total: AtomicU64::new(0),
+            generation,
+            status,
+            last_submission: Mutex::new(None),
+            admin_available,
+        }
+    }
+
+    pub fn admin_available(&self) -> bool {
+        self.admin_available
+    }
+
+    pub fn last_submission(&self) -> Option<CreateScoreRequest> {
+        self.last_submission.lock().ok().and_then(|last| last.clone())
+    }
+
+    pub fn send(&self, command: ApiCommand) {
+        let generation = self.generation.load(Ordering::SeqCst);
+        if let ApiCommand::SubmitScore(request) = &command {
+            if let Ok(mut last) = self.last_submission.lock() {
+                *last = Some(request.clone());
+            }
+        }
+        if !command.is_essential() && self.background_paused.load(Ordering::SeqCst) {
+            if let Ok(mut deferred) = self.deferred.lock() {
+                deferred.push((generation, command));
+                self.deferred_total.fetch_add(1, Ordering::Relaxed);
+                return;
+            }
+        }
+        self.dispatch(generation, command);
+    }
+
+    fn dispatch(&self, generation: u64, command: ApiCommand) {
+        if self.commands.send((generation, command)).is_err() {
+            eprintln!("Network worker has stopped");
+        }
+    }
+
+    // 恢复后台请求时按原来的顺序发出推迟的请求，已取消的那一代由后台线程跳过
+    fn set_background_paused(&self, paused: bool) {
+        self.background_paused.store(paused, Ordering::SeqCst);
+        if paused {
+            return;
+        }
+        let deferred = self.deferred.lock().map(|mut deferred| std::mem::take(&mut *deferred)).unwrap_or_default();
+        for (generation, command) in deferred {
+            self.dispatch(generation, command);
+        }
+    }
+
+    // 取消所有尚未返回的请求
+    pub fn cancel_pending(&self) {
+        self.generation.fetch_add(1, Ordering::SeqCst);
+    }
+}
+
+fn run_command(client: &ApiClient, resilience: &mut Resilience, command: ApiCommand) -> ApiResponse {
+    let retryable = command.is_read_only();
+    match command {
+        ApiCommand::SubmitScore(request) => {
+            ApiResponse::ScoreSubmitted(resilience.run(retryable, || client.submit_score(&request)))
+        }
+        ApiCommand::GetLeaderboard { limit, difficulty, season, cursor } => {
+            let result = resilience.run(retryable, || {
+                client.get_leaderboard(limit, difficulty.as_deref(), season.as_deref(), cursor.as_deref())
+            });
+            if cursor.is_some() {
+                ApiResponse::LeaderboardPage(result)
+            } else {
+                ApiResponse::Leaderboard(result)
+            }
+        }
+        ApiCommand::GetSeasons => ApiResponse::Seasons(resilience.run(retryable, || client.get_seasons())),
+        ApiCommand::GetRecentScores { limit } => {
+            ApiResponse::RecentScores(resilience.run(retryable, || client.get_recent_scores(limit)))
+        }
+        ApiCommand::GetLevelHistogram { difficulty } => {
+            ApiResponse::LevelHistogram(resilience.run(retryable, || client.get_level_histogram(&difficulty)))
+        }
+        ApiCommand::GetDaily { date, player_name, limit } => {
+            ApiResponse::Daily(resilience.run(retryable, || client.get_daily(&date, &player_name, limit)))
+        }
+        ApiCommand::GetEvent => ApiResponse::Event(resilience.run(retryable, || client.get_event())),
+        ApiCommand::GetFlags => ApiResponse::Flags(resilience.run(retryable, || client.get_flags())),
+        ApiCommand::CreateRace { player_name } => {
+            ApiResponse::RaceCreated(resilience.run(retryable, || client.create_race(&player_name)))
+        }
+        ApiCommand::JoinRace { code, player_name } => {
+            ApiResponse::RaceJoined(resilience.run(retryable, || client.join_race(&code, &player_name)))
+        }
+        ApiCommand::RaceProgress { code, request } => {
+            ApiResponse::RaceProgress(resilience.run(retryable, || client.post_race_progress(&code, &request)))
+        }
+        ApiCommand::GetRace { code } => ApiResponse::Race(resilience.run(retryable, || client.get_race(&code))),
+        ApiCommand::GetRivals { player_name, difficulty } => {
+            ApiResponse::Rivals(resilience.run(retryable, || client.get_rivals(&player_name, &difficulty)))
+        }
+        ApiCommand::DeleteScore { id } => {
+            let result = resilience.run(retryable, || client.delete_score(&id));
+            ApiResponse::ScoreDeleted { id, result }
+        }
+        ApiCommand::RenameScore { id, player_name } => {
+            let result = resilience.run(retryable, || client.rename_score(&id, &player_name));
+            ApiResponse::ScoreRenamed { id, result }
+        }
+    }
+}
+
+// 进入游戏状态时暂停非必要请求，离开后补发
+fn apply_network_policy(state: Res<State<GameState>>, network: Res<Network>) {
+    let paused = *state.get() == GameState::Playing;
+    if paused != network.background_paused.load(Ordering::SeqCst) {
+        network.set_background_paused(paused);
+    }
+}
+
+// 每帧取出已完成的响应并作为事件发送（最多 MAX_RESPONSES_PER_FRAME 个），同时同步连接状态
+pub fn drain_api_responses(
+    network: Res<Network>,
+    mut server_status: ResMut<ServerStatus>,
+    mut stats: ResMut<NetworkStats>,
+    mut events: EventWriter<ApiResponse>,
+) {
+    if let Ok(status) = network.status.lock() {
+        server_status.set_if_neq(*status);
+    }
+
+    let current = network.generation.load(Ordering::SeqCst);
+    let responses = network.responses.lock().unwrap();
+    let mut sent = 0;
+    while sent < MAX_RESPONSES_PER_FRAME {
+        let Ok((generation, read_only, response)) = responses.try_recv() else {
+            break;
+        };
+        // 过期的只读响应直接丢弃，不占用本帧的名额；提交分数、比赛和管理操作的结果总是送达
+        if !read_only || generation == current {
+            events.send(response);
+            sent += 1;
+        }
+    }
+
+    stats.processed += sent as u64;
+    stats.deferred = network.deferred_total.load(Ordering::Relaxed);
+    stats.queued = network.deferred.lock().map(|deferred| deferred.len()).unwrap_or(0);
+}
+
+// 统一管理所有服务器请求的插件
+pub struct NetworkPlugin;
+
+impl Plugin for NetworkPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(Network::spawn(ApiClient::new()))
+            .init_resource::<ServerStatus>()
+            .init_resource::<NetworkStats>()
+            .init_resource::<RemoteFlags>()
+            .add_event::<ApiResponse>()
+            .add_systems(Startup, remote_flags::request_remote_flags)
+            .add_systems(PreUpdate, (apply_network_policy, drain_api_responses).chain())
+            .add_systems(Update, (remote_flags::receive_remote_flags, remote_flags::apply_flag_gates).chain());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{Read, Write};
+    use std::net::TcpListener;
+
+    fn submission() -> CreateScoreRequest {
+        CreateScoreRequest {
+            player_name: "ALICE".to_string(),
+            score: 1200,
+            level: 3,
+            difficulty: "Medium".to_string(),
+            idempotency_key: None,
+            venue: None,
+            mode: None,
+            seed: None,
+            daily_date: None,
+            event_id: None,
+            tournament_id: None,
+            abandoned: false,
+            assisted: false,
+            curse: None,
+        }
+    }
+
+    // 脚本化的时钟：sleep 直接把时间往后拨，抖动固定为 1（取满额的等待）
+    struct ScriptedClock {
+        now: Instant,
+        slept: Vec<Duration>,
+    }
+
+    impl RetryClock for ScriptedClock {
+        fn now(&self) -> Instant {
+            self.now
+        }
+
+        fn sleep(&mut self, delay: Duration) {
+            self.now += delay;
+            self.slept.push(delay);
+        }
+
+        fn jitter(&mut self) -> f64 {
+            1.0
+        }
+    }
+
+    fn scripted_resilience() -> Resilience<ScriptedClock> {
+        Resilience {
+            breaker: CircuitBreaker::new(),
+            status: Arc::new(Mutex::new(ServerStatus::Unknown)),
+            clock: ScriptedClock {
+                now: Instant::now(),
+                slept: Vec::new(),
+            },
+        }
+    }
+
+    // 假的传输层：按顺序返回脚本里的结果（true 为成功），记录调用次数
+    fn run_script(
+        resilience: &mut Resilience<ScriptedClock>,
+        retryable: bool,
+        script: &[bool],
+    ) -> (Result<(), String>, usize) {
+        let mut calls = 0;
+        let result = resilience.run(retryable, || {
+            calls += 1;
+            match script.get(calls - 1) {
+                Some(true) => Ok(()),
+                _ => Err(format!("attempt {} failed", calls).into()),
+            }
+        });
+        (result, calls)
+    }
+
+    fn status(resilience: &Resilience<ScriptedClock>) -> ServerStatus {
+        *resilience.status.lock().unwrap()
+    }
+
+    #[test]
+    fn backoff_doubles_up_to_the_cap_with_jitter_in_the_upper_half() {
+        assert_eq!(backoff_delay(0, 1.0), RETRY_BASE_DELAY);
+        assert_eq!(backoff_delay(1, 1.0), RETRY_BASE_DELAY * 2);
+        assert_eq!(backoff_delay(2, 1.0), RETRY_BASE_DELAY * 4);
+        assert_eq!(backoff_delay(10, 1.0), RETRY_MAX_DELAY);
+        assert_eq!(backoff_delay(0, 0.0), RETRY_BASE_DELAY / 2);
+        assert_eq!(backoff_delay(0, 7.0), RETRY_BASE_DELAY);
+    }
+
+    #[test]
+    fn read_only_requests_retry_with_backoff() {
+        let mut resilience = scripted_resilience();
+        assert_eq!(run_script(&mut resilience, true, &[false, false, true]), (Ok(()), 3));
+        assert_eq!(resilience.clock.slept, [backoff_delay(0, 1.0), backoff_delay(1, 1.0)]);
+        assert_eq!(status(&resilience), ServerStatus::Online);
+
+        // 三次都失败时返回最后一次的错误，不再多等
+        let (result, calls) = run_script(&mut resilience, true, &[]);
+        assert_eq!((result, calls), (Err("attempt 3 failed".to_string()), MAX_ATTEMPTS as usize));
+        assert_eq!(resilience.clock.slept.len(), 4);
+        assert_eq!(status(&resilience), ServerStatus::Unreachable);
+    }
+
+    #[test]
+    fn submissions_are_tried_once() {
+        let mut resilience = scripted_resilience();
+        assert_eq!(run_script(&mut resilience, false, &[false, true]).1, 1);
+        assert!(resilience.clock.slept.is_empty());
+    }
+
+    #[test]
+    fn breaker_opens_after_five_failures_and_probes_once_after_a_minute() {
+        let mut resilience = scripted_resilience();
+        for _ in 0..BREAKER_FAILURE_THRESHOLD {
+            assert!(run_script(&mut resilience, false, &[]).0.is_err());
+        }
+        assert_eq!(status(&resilience), ServerStatus::BackingOff);
+
+        // 熔断期间不调用传输层
+        resilience.clock.now += BREAKER_OPEN_DURATION - Duration::from_secs(1);
+        assert_eq!(run_script(&mut resilience, true, &[true]).1, 0);
+
+        // 一分钟后只放行一个探测请求，只读请求也只试一次；探测失败重新熔断
+        resilience.clock.now += Duration::from_secs(1);
+        assert_eq!(run_script(&mut resilience, true, &[false, true]).1, 1);
+        assert_eq!(status(&resilience), ServerStatus::BackingOff);
+        assert_eq!(run_script(&mut resilience, true, &[true]).1, 0);
+
+        // 探测成功后恢复正常
+        resilience.clock.now += BREAKER_OPEN_DURATION;
+        assert_eq!(run_script(&mut resilience, true, &[true]), (Ok(()), 1));
+        assert_eq!(status(&resilience), ServerStatus::Online);
+        assert_eq!(run_script(&mut resilience, true, &[false, true]), (Ok(()), 2));
+    }
+
+    #[test]
+    fn cancelling_keeps_the_in_flight_submission() {
+        // 本地假服务器：收到请求后等测试取消完再回复 503
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap();
+        let (cancelled, wait_cancelled) = mpsc::channel::<()>();
+        let (received, wait_received) = mpsc::channel::<()>();
+        std::thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut buffer = [0u8; 4096];
+            let _ = stream.read(&mut buffer);
+            received.send(()).unwrap();
+            wait_cancelled.recv().unwrap();
+            let _ = stream.write_all(b"HTTP/1.1 503 Service Unavailable\r\ncontent-length: 0\r\nconnection: close\r\n\r\n");
+        });
+
+        let mut app = App::new();
+        app.insert_resource(Network::spawn(ApiClient::with_base_url(&format!("http://{address}/api"))))
+            .init_resource::<ServerStatus>()
+            .init_resource::<NetworkStats>()
+            .add_event::<ApiResponse>()
+            .add_systems(Update, drain_api_responses);
+
+        let network = app.world.resource::<Network>();
+        network.send(ApiCommand::SubmitScore(submission()));
+        // 排在提交后面的只读请求在取消后应被跳过
+        network.send(ApiCommand::GetSeasons);
+        wait_received.recv_timeout(Duration::from_secs(5)).unwrap();
+        network.cancel_pending();
+        cancelled.send(()).unwrap();
+
+        let deadline = Instant::now() + Duration::from_secs(5);
+        let mut delivered = Vec::new();
+        while Instant::now() < deadline && delivered.is_empty() {
+            app.update();
+            let events = app.world.resource::<Events<ApiResponse>>();
+            delivered.extend(events.get_reader().read(events).map(|response| format!("{response:?}")));
+            std::thread::sleep(Duration::from_millis(10));
+        }
+        // 再多等几帧，确认过期的只读响应没有被送达
+        for _ in 0..10 {
+            app.update();
+            std::thread::sleep(Duration::from_millis(10));
+        }
+        assert_eq!(delivered.len(), 1, "{delivered:?}");
+        assert!(delivered[0].starts_with("ScoreSubmitted(Err("), "{}", delivered[0]);
+        assert_eq!(app.world.resource::<NetworkStats>().processed, 1);
+    }
+
+    // 不启动后台线程的 Network：测试直接读取发出的请求，并自己塞入响应
+    fn channel_network() -> (Network, Receiver<(u64, ApiCommand)>, Sender<(u64, bool, ApiResponse)>) {
+        let (command_sender, command_receiver) = mpsc::channel();
+        let (response_sender, response_receiver) = mpsc::channel();
+        let network = Network {
+            commands: command_sender,
+            responses: Mutex::new(response_receiver),
+            background_paused: AtomicBool::new(false),
+            deferred: Mutex::new(Vec::new()),
+            deferred_total: AtomicU64::new(0),
+            generation: Arc::new(AtomicU64::new(0)),
+            status: Arc::new(Mutex::new(ServerStatus::default())),
+            last_submission: Mutex::new(None),
+            admin_available: false,
+        };
+        (network, command_receiver, response_sender)
+    }
+
+    fn policy_app(network: Network) -> App {
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins)
+            .init_state::<GameState>()
+            .insert_resource(network)
+            .init_resource::<ServerStatus>()
+            .init_resource::<NetworkStats>()
+            .add_event::<ApiResponse>()
+            .add_systems(PreUpdate, (apply_network_policy, drain_api_responses).chain());
+        app
+    }
+
+    fn enter(app: &mut App, state: GameState) {
+        app.world.resource_mut::<NextState<GameState>>().set(state);
+        // 状态在 PreUpdate 之后切换，策略在下一帧才看到
+        app.update();
+        app.update();
+    }
+
+    // 已发给后台线程的请求名称，按发出顺序
+    fn dispatched(commands: &Receiver<(u64, ApiCommand)>) -> Vec<String> {
+        commands
+            .try_iter()
+            .map(|(_, command)| format!("{command:?}").split(['(', ' ']).next().unwrap().to_string())
+            .collect()
+    }
+
+    #[test]
+    fn background_requests_wait_until_the_level_is_left() {
+        let (network, commands, _responses) = channel_network();
+        let mut app = policy_app(network);
+        enter(&mut app, GameState::Playing);
+
+        let network = app.world.resource::<Network>();
+        network.send(ApiCommand::GetSeasons);
+        network.send(ApiCommand::SubmitScore(submission()));
+        network.send(ApiCommand::GetFlags);
+        assert_eq!(dispatched(&commands), ["SubmitScore"]);
+        app.update();
+        let stats = app.world.resource::<NetworkStats>();
+        assert_eq!((stats.deferred, stats.queued), (2, 2));
+
+        // 离开游戏状态后按原顺序补发
+        enter(&mut app, GameState::GameOver);
+        assert_eq!(dispatched(&commands), ["GetSeasons", "GetFlags"]);
+        let stats = app.world.resource::<NetworkStats>();
+        assert_eq!((stats.deferred, stats.queued), (2, 0));
+
+        // 不在游戏中时直接发出
+        app.world.resource::<Network>().send(ApiCommand::GetEvent);
+        assert_eq!(dispatched(&commands), ["GetEvent"]);
+    }
+
+    #[test]
+    fn deferred_requests_keep_their_generation() {
+        let (network, commands, _responses) = channel_network();
+        let mut app = policy_app(network);
+        enter(&mut app, GameState::Playing);
+        app.world.resource::<Network>().send(ApiCommand::GetSeasons);
+        app.world.resource::<Network>().cancel_pending();
+        enter(&mut app, GameState::MainMenu);
+
+        // 后台线程据此跳过已取消的请求
+        let generations: Vec<u64> = commands.try_iter().map(|(generation, _)| generation).collect();
+        assert_eq!(generations, [0]);
+    }
+
+    #[test]
+    fn at_most_two_responses_are_dispatched_per_frame() {
+        let (network, _commands, responses) = channel_network();
+        let mut app = policy_app(network);
+        for _ in 0..5 {
+            responses.send((0, true, ApiResponse::Seasons(Err("offline".to_string())))).unwrap();
+        }
+        // 过期的只读响应被丢弃，不占本帧的名额
+        responses.send((7, true, ApiResponse::Seasons(Err("stale".to_string())))).unwrap();
+
+        let mut reader = app.world.resource::<Events<ApiResponse>>().get_reader();
+        let mut per_frame = Vec::new();
+        for _ in 0..4 {
+            app.update();
+            let events = app.world.resource::<Events<ApiResponse>>();
+            per_frame.push(reader.read(events).count());
+        }
+        assert_eq!(per_frame, [2, 2, 1, 0]);
+        assert_eq!(app.world.resource::<NetworkStats>().processed, 5);
+    }
+}
@@ -0,0 +1,119 @@
+use bevy::ecs::query::QuerySingleError;
+use bevy::prelude::*;
+use std::collections::BTreeMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use crate::bonus::Orientation;
+use crate::cosmetics::Cosmetics;
+use crate::tilt::PaddleTilt;
+use crate::{paddle_feedback, resume, GameInitialized, GameState, PADDLE_SIZE};
+
+// 同一个系统的挡板警告最多每隔这么久打印一次
+const WARNING_INTERVAL: Duration = Duration::from_secs(5);
+
+// 挡板：只由 PaddlePlugin 生成和销毁，单人游戏中进行的一局里始终恰好有一个
+#[derive(Component)]
+pub struct Paddle;
+
+// 挡板上一帧的实际水平速度（像素/秒），撞墙停住时为 0；球从挡板表面弹开时带上一部分
+#[derive(Component, Default)]
+pub struct PaddleVelocity(pub f32);
+
+// 挡板的生成和销毁；一局的初始化要排在 Spawn 之前
+#[derive(SystemSet, Debug, Clone, PartialEq, Eq, Hash)]
+pub enum PaddleSet {
+    Spawn,
+    Despawn,
+}
+
+pub struct PaddlePlugin;
+
+impl Plugin for PaddlePlugin {
+    fn build(&self, app: &mut App) {
+        // 销毁在 PostUpdate，生成在下一帧进入 Playing 时，新旧挡板不会同时存在
+        app.add_systems(OnEnter(GameState::Playing), spawn_paddle.in_set(PaddleSet::Spawn).run_if(resume::full_setup))
+            .add_systems(PostUpdate, despawn_paddle.in_set(PaddleSet::Despawn))
+            .add_systems(Update, assert_single_paddle.run_if(in_state(GameState::Playing)));
+    }
+}
+
+// 这些状态下这一局还在屏幕上，保留挡板
+fn keeps_paddle(state: &GameState) -> bool {
+    matches!(
+        state,
+        GameState::Playing
+            | GameState::Paused
+            | GameState::DeathSequence
+            | GameState::LifeReplay
+            | GameState::Victory
+    )
+}
+
+// 进入 Playing 时还没有挡板就生成一个；从暂停返回时不运行，挡板还在，不会重复生成
+fn spawn_paddle(
+    mut commands: Commands,
+    game_initialized: Res<GameInitialized>,
+    cosmetics: Res<Cosmetics>,
+    orientation: Res<Orientation>,
+    paddles: Query<(), With<Paddle>>,
+) {
+    if !game_initialized.0 || !paddles.is_empty() {
+        return;
+    }
+
+    // 颜色和花纹取决于档案选择的外观
+    let mut paddle = commands.spawn((
+        SpriteBundle {
+            sprite: Sprite {
+                color: cosmetics.paddle.base,
+                ..default()
+            },
+            transform: Transform {
+                translation: Vec3::new(0.0, orientation.paddle_y(), 0.0),
+                scale: Vec3::new(PADDLE_SIZE.x, PADDLE_SIZE.y, 1.0),
+                ..default()
+            },
+            ..default()
+        },
+        Paddle,
+        PaddleTilt::default(),
+        PaddleVelocity::default(),
+    ));
+    cosmetics.paddle.decorate(&mut paddle);
+    paddle_feedback::attach(&mut paddle);
+}
+
+// 这一局被清理（或重新开始）后，或者离开了这一局所在的界面，销毁挡板
+fn despawn_paddle(
+    mut commands: Commands,
+    state: Res<State<GameState>>,
+    game_initialized: Res<GameInitialized>,
+    paddles: Query<Entity, With<Paddle>>,
+) {
+    if game_initialized.0 && keeps_paddle(state.get()) {
+        return;
+    }
+    for entity in paddles.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+// 调试版本中检查 Playing 时恰好有一个挡板
+fn assert_single_paddle(paddles: Query<(), With<Paddle>>) {
+    let count = paddles.iter().count();
+    debug_assert_eq!(count, 1, "expected exactly one paddle while playing, found {}", count);
+}
+
+// 找不到挡板或有多个挡板时打印警告；每个系统单独限流，避免每帧刷屏
+pub fn warn_paddle_query(system: &'static str, error: QuerySingleError) {
+    static LAST_WARNING: Mutex<BTreeMap<&str, Instant>> = Mutex::new(BTreeMap::new());
+
+    let now = Instant::now();
+    let mut last_warning = LAST_WARNING.lock().unwrap();
+    if last_warning.get(system).is_some_and(|last| now.duration_since(*last) < WARNING_INTERVAL) {
+        return;
+    }
+    last_warning.insert(system, now);
+    eprintln!("Warning: {} skipped, paddle query failed: {}", system, error);
+}
@@ -0,0 +1,135 @@
+use bevy::ecs::system::EntityCommands;
+use bevy::prelude::*;
+
+use crate::fonts::{text_style, FontAssets, FontKind};
+use crate::i18n::Locale;
+use crate::{GameEntity, GameplayEvent, Paddle, Settings, BALL_SPEED, MAX_BALL_SPEED, PADDLE_SIZE, PERFECT_BONUS};
+
+// 闪光和压扁从满值恢复所需的时间（秒）
+const FEEDBACK_DECAY_TIME: f32 = 0.25;
+// 最用力的撞击让挡板压扁的比例
+const MAX_SQUASH: f32 = 0.35;
+// 闪光层的最大不透明度
+const MAX_FLASH_ALPHA: f32 = 0.7;
+// "PERFECT" 提示的持续时间、上升速度和与球的距离
+const POPUP_LIFETIME: f32 = 0.8;
+const POPUP_RISE_SPEED: f32 = 60.0;
+const POPUP_OFFSET: f32 = 30.0;
+
+// 撞击强度：默认球速的一半以下为 0，达到最高速度时为 1
+pub fn impact_intensity(speed: f32) -> f32 {
+    let slow = BALL_SPEED * 0.5;
+    ((speed - slow) / (MAX_BALL_SPEED - slow)).clamp(0.0, 1.0)
+}
+
+// 挡板被击中后的闪光和压扁程度，0 到 1，随时间衰减
+#[derive(Component, Default)]
+pub struct PaddleFeedback {
+    flash: f32,
+    squash: f32,
+}
+
+// 盖在挡板最上层的白色闪光层（子精灵，随挡板缩放和倾斜）
+#[derive(Component)]
+pub struct PaddleFlash;
+
+// 完美击球的提示文字
+#[derive(Component)]
+pub struct PerfectPopup {
+    age: f32,
+}
+
+// 给挡板加上反馈状态和闪光层；外观花纹之后调用，闪光层才会盖在花纹上面
+pub fn attach(paddle: &mut EntityCommands) {
+    paddle.insert(PaddleFeedback::default()).with_children(|parent| {
+        parent.spawn((
+            SpriteBundle {
+                sprite: Sprite {
+                    color: Color::rgba(1.0, 1.0, 1.0, 0.0),
+                    ..default()
+                },
+                transform: Transform::from_xyz(0.0, 0.0, 0.2),
+                ..default()
+            },
+            PaddleFlash,
+        ));
+    });
+}
+
+// 挡板被击中：撞得越快闪光越亮、压得越扁；完美击球时在球的位置显示提示
+pub fn react_to_paddle_hits(
+    mut commands: Commands,
+    mut gameplay_events: EventReader<GameplayEvent>,
+    settings: Res<Settings>,
+    fonts: Res<FontAssets>,
+    locale: Res<Locale>,
+    mut paddles: Query<&mut PaddleFeedback, With<Paddle>>,
+) {
+    for event in gameplay_events.read() {
+        let GameplayEvent::PaddleHit { position, speed, offset, perfect } = *event else {
+            continue;
+        };
+        let intensity = impact_intensity(speed);
+        for mut feedback in paddles.iter_mut() {
+            feedback.flash = feedback.flash.max(0.3 + 0.7 * intensity);
+            // 打在中间压得最扁，打在两端只压扁一半；减少动态效果时只闪光不变形
+            if !settings.reduce_motion {
+                feedback.squash = feedback.squash.max(intensity * (1.0 - 0.5 * offset.abs()));
+            }
+        }
+
+        if perfect {
+            commands.spawn((
+                Text2dBundle {
+                    text: Text::from_section(
+                        locale.tr("popup.perfect", &[("points", &PERFECT_BONUS)]),
+                        text_style(&fonts, FontKind::Bold, 22.0, Color::rgb(1.0, 0.85, 0.2)),
+                    ),
+                    transform: Transform::from_translation(position + Vec3::new(0.0, POPUP_OFFSET, 5.0)),
+                    ..default()
+                },
+                PerfectPopup { age: 0.0 },
+                GameEntity,
+            ));
+        }
+    }
+}
+
+// 闪光和压扁逐渐恢复；挡板宽度由移动系统写入，这里只改高度
+pub fn animate_paddle_feedback(
+    time: Res<Time>,
+    mut paddles: Query<(&mut PaddleFeedback, &mut Transform, &Children), With<Paddle>>,
+    mut flashes: Query<&mut Sprite, With<PaddleFlash>>,
+) {
+    let decay = time.delta_seconds() / FEEDBACK_DECAY_TIME;
+    for (mut feedback, mut transform, children) in paddles.iter_mut() {
+        feedback.flash = (feedback.flash - decay).max(0.0);
+        feedback.squash = (feedback.squash - decay).max(0.0);
+        transform.scale.y = PADDLE_SIZE.y * (1.0 - MAX_SQUASH * feedback.squash);
+
+        for child in children.iter() {
+            if let Ok(mut sprite) = flashes.get_mut(*child) {
+                sprite.color.set_a(feedback.flash * MAX_FLASH_ALPHA);
+            }
+        }
+    }
+}
+
+// 提示文字上升并淡出
+pub fn animate_perfect_popups(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut popups: Query<(Entity, &mut PerfectPopup, &mut Transform, &mut Text)>,
+) {
+    for (entity, mut popup, mut transform, mut text) in popups.iter_mut() {
+        popup.age += time.delta_seconds();
+        if popup.age >= POPUP_LIFETIME {
+            commands.entity(entity).despawn();
+            continue;
+        }
+        transform.translation.y += POPUP_RISE_SPEED * time.delta_seconds();
+        for section in text.sections.iter_mut() {
+            section.style.color.set_a(1.0 - popup.age / POPUP_LIFETIME);
+        }
+    }
+}
@@ -0,0 +1,609 @@
+use bevy::app::AppExit;
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+use crate::api::CreateScoreRequest;
+use crate::fonts::{text_style, FontAssets, FontKind};
+use crate::i18n::{name_key, Locale};
+use crate::network::{ApiCommand, ApiResponse, Network};
+use crate::persistence::{self, load_versioned, save_versioned, Versioned};
+use crate::level_select::GameMode;
+use crate::privacy::ScorePrivacy;
+use crate::profile::RunStats;
+use crate::profiles::ProfilePaths;
+use crate::{DifficultySettings, GameState, Level, PlayerName, RunSeed, Score, Settings};
+
+// 镜像多久同步一次（秒）
+const MIRROR_SYNC_INTERVAL: f32 = 1.0;
+
+// 没有正常结束的一局，写入 pending_run.json，下次启动时询问是否补交
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PendingRun {
+    pub player_name: String,
+    pub score: u32,
+    pub level: u32,
+    pub difficulty: String,
+    pub seed: u64,
+    pub duration: f32,
+    // 买了保险代币的局，补交时同样标记
+    #[serde(default)]
+    pub assisted: bool,
+    // 选择了诅咒的局，补交时同样带上
+    #[serde(default)]
+    pub curse: Option<String>,
+}
+
+impl Versioned for PendingRun {
+    const VERSION: u32 = 1;
+    const LABEL: &'static str = "pending run";
+    const PRETTY: bool = false;
+
+    fn migrate(version: u32, data: serde_json::Value) -> Result<serde_json::Value, String> {
+        match version {
+            // v0：没有外层格式的旧快照，字段与 v1 相同
+            0 => Ok(data),
+            _ => persistence::unknown_version(version),
+        }
+    }
+}
+
+impl PendingRun {
+    pub fn load(path: &Path) -> Option<Self> {
+        load_versioned(path)
+    }
+
+    fn save(&self, path: &Path) -> std::io::Result<()> {
+        save_versioned(path, self)
+    }
+
+    pub fn discard(path: &Path) {
+        if let Err(e) = std::fs::remove_file(path) {
+            if e.kind() != std::io::ErrorKind::NotFound {
+                eprintln!("Failed to remove pending run: {}", e);
+            }
+        }
+    }
+}
+
+// 当前这一局关键数值的副本。panic hook 里不能访问 Bevy 的 World，只能读这里
+#[derive(Debug, Default)]
+pub struct RunMirror {
+    path: PathBuf,
+    run: Option<PendingRun>,
+}
+
+impl RunMirror {
+    // 同步写出快照；不在一局中时什么也不做
+    fn flush(&self) {
+        if let Some(run) = &self.run {
+            if let Err(e) = run.save(&self.path) {
+                eprintln!("Failed to save pending run: {}", e);
+            }
+        }
+    }
+}
+
+#[derive(Resource, Clone, Default)]
+pub struct RunMirrorHandle(Arc<Mutex<RunMirror>>);
+
+// 在默认的 panic 输出之前写出快照。持锁时发生 panic 的话拿不到锁，只能放弃
+pub fn install_panic_hook(mirror: &RunMirrorHandle) {
+    let mirror = mirror.0.clone();
+    let previous = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        if let Ok(mirror) = mirror.try_lock() {
+            mirror.flush();
+        }
+        previous(info);
+    }));
+}
+
+// 一局进行中的状态，包括关卡之间的过渡画面
+fn in_run(state: &GameState) -> bool {
+    matches!(
+        state,
+        GameState::Playing
+            | GameState::Paused
+            | GameState::Victory
+            | GameState::NextLevel
+            | GameState::LevelInterlude
+            | GameState::CurseChoice
+            | GameState::DeathSequence
+            | GameState::LifeReplay
+            | GameState::GameComplete
+    )
+}
+
+// 每秒把当前这一局复制到镜像；离开一局时立即清空，正常结束的局不会被当成未完成
+pub fn sync_run_mirror(
+    time: Res<Time>,
+    state: Res<State<GameState>>,
+    mirror: Res<RunMirrorHandle>,
+    mut since_sync: Local<f32>,
+    paths: Res<ProfilePaths>,
+    player_name: Res<PlayerName>,
+    score: Res<Score>,
+    level: Res<Level>,
+    difficulty_settings: Res<DifficultySettings>,
+    run_seed: Res<RunSeed>,
+    stats: Res<RunStats>,
+    game_mode: Res<GameMode>,
+) {
+    // 单关挑战和淘汰赛不会提交中途退出的分数，没有需要补交的局
+    if !in_run(state.get()) || *game_mode != GameMode::Campaign {
+        // 下次进入一局时立即同步
+        *since_sync = MIRROR_SYNC_INTERVAL;
+        let mut mirror = mirror.0.lock().unwrap();
+        mirror.run = None;
+        return;
+    }
+
+    *since_sync += time.delta_seconds();
+    if *since_sync < MIRROR_SYNC_INTERVAL {
+        return;
+    }
+    *since_sync = 0.0;
+
+    let mut mirror = mirror.0.lock().unwrap();
+    mirror.path = paths.pending_run();
+    mirror.run = Some(PendingRun {
+        player_name: player_name.0.clone(),
+        score: score.0,
+        level: level.0,
+        difficulty: difficulty_settings.difficulty.name().to_string(),
+        seed: run_seed.0,
+        duration: stats.play_time,
+        assisted: stats.insurance_bought > 0,
+        curse: stats.curse.clone(),
+    });
+}
+
+// 关闭窗口或退出程序时写出快照
+pub fn flush_on_exit(mut exits: EventReader<AppExit>, mirror: Res<RunMirrorHandle>) {
+    if exits.read().next().is_some() {
+        mirror.0.lock().unwrap().flush();
+    }
+}
+
+// 进入主菜单时发现未完成的局，先询问如何处理
+pub fn redirect_pending_run(
+    paths: Res<ProfilePaths>,
+    mut recovery: ResMut<RunRecovery>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    if let Some(run) = PendingRun::load(&paths.pending_run()) {
+        *recovery = RunRecovery {
+            run: Some(run),
+            submitting: false,
+        };
+        next_state.set(GameState::RecoverRun);
+    }
+}
+
+// 询问界面正在处理的未完成局
+#[derive(Resource, Default)]
+pub struct RunRecovery {
+    run: Option<PendingRun>,
+    // 已发送补交请求，等待服务器响应
+    submitting: bool,
+}
+
+#[derive(Component)]
+pub struct RecoverRunUI;
+
+#[derive(Component)]
+pub struct RecoverStatusText;
+
+pub fn setup_recover_screen(
+    mut commands: Commands,
+    recovery: Res<RunRecovery>,
+    settings: Res<Settings>,
+    fonts: Res<FontAssets>,
+    locale: Res<Locale>,
+) {
+    let Some(run) = &recovery.run else {
+        return;
+    };
+    // 未完成的局留下之后改成了只保存在本机：不再补交，只能确认丢弃
+    let (explain_key, hint_key) = if settings.score_privacy == ScorePrivacy::LocalOnly {
+        ("recover.local_only", "recover.local_only_hint")
+    } else {
+        ("recover.explain", "recover.hint")
+    };
+    let seconds = run.duration as u32;
+    let duration = format!("{}:{:02}", seconds / 60, seconds % 60);
+
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    width: Val::Percent(100.0),
+                    height: Val::Percent(100.0),
+                    flex_direction: FlexDirection::Column,
+                    justify_content: JustifyContent::Center,
+                    align_items: AlignItems::Center,
+                    ..default()
+                },
+                background_color: BackgroundColor(Color::rgb(0.1, 0.1, 0.15)),
+                ..default()
+            },
+            RecoverRunUI,
+        ))
+        .with_children(|parent| {
+            parent.spawn(TextBundle::from_section(
+                locale.tr("recover.title", &[]),
+                text_style(&fonts, FontKind::Bold, 50.0, Color::rgb(1.0, 0.85, 0.2)),
+            ));
+
+            parent.spawn(TextBundle::from_section(
+                locale.tr(
+                    "recover.details",
+                    &[
+                        ("name", &run.player_name),
+                        ("score", &run.score),
+                        ("level", &run.level),
+                        ("difficulty", &locale.tr(&name_key("difficulty", &run.difficulty), &[])),
+                        ("duration", &duration),
+                    ],
+                ),
+                text_style(&fonts, FontKind::Regular, 24.0, Color::WHITE),
+            ).with_style(Style {
+                margin: UiRect::top(Val::Px(30.0)),
+                ..default()
+            }));
+
+            parent.spawn(TextBundle::from_section(
+                locale.tr(explain_key, &[]),
+                text_style(&fonts, FontKind::Regular, 18.0, Color::rgb(0.6, 0.6, 0.6)),
+            ).with_style(Style {
+                margin: UiRect::top(Val::Px(20.0)),
+                ..default()
+            }));
+
+            parent.spawn((
+                TextBundle::from_section(
+                    "",
+                    text_style(&fonts, FontKind::Regular, 20.0, Color::rgb(0.7, 0.7, 0.7)),
+                ).with_style(Style {
+                    margin: UiRect::top(Val::Px(20.0)),
+                    ..default()
+                }),
+                RecoverStatusText,
+            ));
+
+            parent.spawn(TextBundle::from_section(
+                locale.tr(hint_key, &[]),
+                text_style(&fonts, FontKind::Regular, 20.0, Color::rgb(0.6, 0.6, 0.6)),
+            ).with_style(Style {
+                margin: UiRect::top(Val::Px(30.0)),
+                ..default()
+            }));
+        });
+}
+
+// ENTER 补交为未完成局，ESC 丢弃；补交成功后才删除文件，失败时可以重试。
+// 只保存在本机时 ENTER 和 ESC 都是确认丢弃；匿名时用代号补交
+pub fn recover_screen_system(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    network: Res<Network>,
+    settings: Res<Settings>,
+    paths: Res<ProfilePaths>,
+    locale: Res<Locale>,
+    mut recovery: ResMut<RunRecovery>,
+    mut responses: EventReader<ApiResponse>,
+    mut next_state: ResMut<NextState<GameState>>,
+    mut status_query: Query<&mut Text, With<RecoverStatusText>>,
+) {
+    let mut status = None;
+    for response in responses.read() {
+        let ApiResponse::ScoreSubmitted(result) = response else {
+            continue;
+        };
+        if !recovery.submitting {
+            continue;
+        }
+        recovery.submitting = false;
+        match result {
+            Ok(_) => {
+                PendingRun::discard(&paths.pending_run());
+                recovery.run = None;
+                next_state.set(GameState::MainMenu);
+            }
+            Err(e) => {
+                eprintln!("Failed to submit abandoned run: {}", e);
+                status = Some(("recover.failed", Color::rgb(0.8, 0.2, 0.2)));
+            }
+        }
+    }
+
+    if recovery.submitting {
+        // 等待响应时不接受按键
+    } else if keyboard_input.just_pressed(KeyCode::Enter) && settings.score_privacy != ScorePrivacy::LocalOnly {
+        if let Some(run) = &recovery.run {
+            network.send(ApiCommand::SubmitScore(CreateScoreRequest {
+                player_name: settings.public_name(&run.player_name),
+                score: run.score,
+                level: run.level,
+                difficulty: run.difficulty.clone(),
+                idempotency_key: None,
+                venue: None,
+                mode: None,
+                seed: None,
+                daily_date: None,
+                event_id: None,
+                tournament_id: None,
+                abandoned: true,
+                assisted: run.assisted,
+                curse: run.curse.clone(),
+            }));
+            recovery.submitting = true;
+            status = Some(("recover.submitting", Color::rgb(0.7, 0.7, 0.7)));
+        }
+    } else if keyboard_input.any_just_pressed([KeyCode::Enter, KeyCode::Escape]) {
+        PendingRun::discard(&paths.pending_run());
+        recovery.run = None;
+        next_state.set(GameState::MainMenu);
+    }
+
+    if let Some((key, color)) = status {
+        for mut text in status_query.iter_mut() {
+            text.sections[0].value = locale.tr(key, &[]);
+            text.sections[0].style.color = color;
+        }
+    }
+}
+
+pub fn cleanup_recover_screen(mut commands: Commands, query: Query<Entity, With<RecoverRunUI>>) {
+    for entity in query.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::ApiClient;
+    use crate::i18n::Language;
+    use crate::Difficulty;
+    use bevy::time::TimeUpdateStrategy;
+    use std::time::Duration;
+
+    const FRAME: f32 = 0.25;
+
+    fn test_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("breakout-pending-run-{}-{}", name, std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        dir
+    }
+
+    fn pending_run(score: u32) -> PendingRun {
+        PendingRun {
+            player_name: "ALICE".to_string(),
+            score,
+            level: 4,
+            difficulty: "Hard".to_string(),
+            seed: 7,
+            duration: 95.0,
+            assisted: true,
+            curse: None,
+        }
+    }
+
+    fn enter(app: &mut App, state: GameState) {
+        app.world.resource_mut::<NextState<GameState>>().set(state);
+        app.update();
+    }
+
+    fn mirrored(app: &App) -> Option<(u32, u32)> {
+        let mirror = app.world.resource::<RunMirrorHandle>().0.lock().unwrap();
+        mirror.run.as_ref().map(|run| (run.score, run.level))
+    }
+
+    // 只有镜像同步和退出时写出快照的 App，每帧固定前进 FRAME 秒
+    fn mirror_app(dir: &Path) -> App {
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins)
+            .insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_secs_f32(FRAME)))
+            .init_state::<GameState>()
+            .init_resource::<RunMirrorHandle>()
+            .insert_resource(ProfilePaths::in_dir(dir))
+            .insert_resource(PlayerName("ALICE".to_string()))
+            .insert_resource(Score(0))
+            .insert_resource(Level(1))
+            .insert_resource(DifficultySettings::new(Difficulty::Hard))
+            .insert_resource(RunSeed(7))
+            .init_resource::<RunStats>()
+            .init_resource::<GameMode>()
+            .add_systems(Update, sync_run_mirror)
+            .add_systems(Last, flush_on_exit);
+        app
+    }
+
+    #[test]
+    fn mirror_follows_the_run_once_a_second() {
+        let dir = test_dir("mirror");
+        let mut app = mirror_app(&dir);
+        app.update();
+        assert_eq!(mirrored(&app), None);
+
+        // 进入一局后立即同步，之后每秒一次
+        enter(&mut app, GameState::Playing);
+        assert_eq!(mirrored(&app), Some((0, 1)));
+        app.world.resource_mut::<Score>().0 = 500;
+        app.world.resource_mut::<Level>().0 = 2;
+        app.update();
+        assert_eq!(mirrored(&app), Some((0, 1)));
+        for _ in 0..(MIRROR_SYNC_INTERVAL / FRAME) as usize {
+            app.update();
+        }
+        assert_eq!(mirrored(&app), Some((500, 2)));
+
+        // 暂停和关卡之间仍在一局中
+        app.world.resource_mut::<Score>().0 = 800;
+        enter(&mut app, GameState::Paused);
+        enter(&mut app, GameState::LevelInterlude);
+        for _ in 0..(MIRROR_SYNC_INTERVAL / FRAME) as usize {
+            app.update();
+        }
+        assert_eq!(mirrored(&app), Some((800, 2)));
+
+        // 正常结束的局立即清空
+        enter(&mut app, GameState::GameOver);
+        assert_eq!(mirrored(&app), None);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn single_level_runs_are_never_mirrored() {
+        let dir = test_dir("single-level");
+        let mut app = mirror_app(&dir);
+        app.insert_resource(GameMode::SingleLevel);
+        app.update();
+        enter(&mut app, GameState::Playing);
+        for _ in 0..10 {
+            app.update();
+        }
+        assert_eq!(mirrored(&app), None);
+    }
+
+    #[test]
+    fn exiting_mid_run_writes_the_snapshot() {
+        let dir = test_dir("exit");
+        let mut app = mirror_app(&dir);
+        app.insert_resource(Score(1200));
+        app.update();
+        enter(&mut app, GameState::Playing);
+        app.world.send_event(AppExit);
+        app.update();
+
+        let saved = PendingRun::load(&ProfilePaths::in_dir(&dir).pending_run()).expect("snapshot written");
+        assert_eq!((saved.player_name.as_str(), saved.score, saved.difficulty.as_str()), ("ALICE", 1200, "Hard"));
+        assert_eq!(saved.seed, 7);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn exiting_from_a_menu_writes_nothing() {
+        let dir = test_dir("exit-menu");
+        let mut app = mirror_app(&dir);
+        app.update();
+        app.world.send_event(AppExit);
+        app.update();
+        assert!(!ProfilePaths::in_dir(&dir).pending_run().exists());
+    }
+
+    // 主菜单发现未完成的局时进入询问界面；网络线程只作为请求的去处，响应由测试直接发送
+    fn recovery_app(dir: &Path, settings: Settings) -> App {
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins)
+            .init_state::<GameState>()
+            .init_resource::<ButtonInput<KeyCode>>()
+            .insert_resource(Network::spawn(ApiClient::with_base_url("http://127.0.0.1:9/api")))
+            .insert_resource(settings)
+            .insert_resource(ProfilePaths::in_dir(dir))
+            .insert_resource(Locale::new(Language::En))
+            .init_resource::<RunRecovery>()
+            .add_event::<ApiResponse>()
+            .add_systems(OnEnter(GameState::MainMenu), redirect_pending_run)
+            .add_systems(Update, recover_screen_system.run_if(in_state(GameState::RecoverRun)));
+        app
+    }
+
+    fn recovery_with_pending_run(name: &str, settings: Settings) -> (App, PathBuf) {
+        let dir = test_dir(name);
+        let path = ProfilePaths::in_dir(&dir).pending_run();
+        pending_run(900).save(&path).unwrap();
+        let mut app = recovery_app(&dir, settings);
+        app.update();
+        app.update();
+        assert_eq!(state(&app), GameState::RecoverRun);
+        (app, path)
+    }
+
+    fn state(app: &App) -> GameState {
+        *app.world.resource::<State<GameState>>().get()
+    }
+
+    fn press(app: &mut App, key: KeyCode) {
+        app.world.resource_mut::<ButtonInput<KeyCode>>().press(key);
+        app.update();
+        let mut input = app.world.resource_mut::<ButtonInput<KeyCode>>();
+        input.release(key);
+        input.clear();
+    }
+
+    fn submitted(points: u32) -> ApiResponse {
+        ApiResponse::ScoreSubmitted(Ok(crate::api::Score {
+            id: Some("run".to_string()),
+            player_name: "ALICE".to_string(),
+            score: points,
+            level: 4,
+            difficulty: "Hard".to_string(),
+            created_at: None,
+            rank: None,
+            season: None,
+            mode: None,
+            share_url: None,
+        }))
+    }
+
+    #[test]
+    fn no_pending_run_stays_on_the_main_menu() {
+        let dir = test_dir("none");
+        let mut app = recovery_app(&dir, Settings::default());
+        app.update();
+        app.update();
+        assert_eq!(state(&app), GameState::MainMenu);
+    }
+
+    #[test]
+    fn escape_discards_the_pending_run() {
+        let (mut app, path) = recovery_with_pending_run("discard", Settings::default());
+        press(&mut app, KeyCode::Escape);
+        app.update();
+        assert_eq!(state(&app), GameState::MainMenu);
+        assert!(!path.exists());
+        assert!(app.world.resource::<Network>().last_submission().is_none());
+        let _ = std::fs::remove_dir_all(path.parent().unwrap());
+    }
+
+    #[test]
+    fn enter_submits_an_abandoned_run_and_keeps_the_file_until_it_is_accepted() {
+        let (mut app, path) = recovery_with_pending_run("submit", Settings::default());
+        press(&mut app, KeyCode::Enter);
+        let request = app.world.resource::<Network>().last_submission().expect("submitted");
+        assert!(request.abandoned && request.assisted);
+        assert_eq!((request.player_name.as_str(), request.score, request.level), ("ALICE", 900, 4));
+
+        // 提交失败：留在询问界面，文件保留，可以重试
+        app.world.send_event(ApiResponse::ScoreSubmitted(Err("offline".to_string())));
+        app.update();
+        assert_eq!(state(&app), GameState::RecoverRun);
+        assert!(path.exists());
+
+        press(&mut app, KeyCode::Enter);
+        app.world.send_event(submitted(900));
+        app.update();
+        app.update();
+        assert_eq!(state(&app), GameState::MainMenu);
+        assert!(!path.exists());
+        let _ = std::fs::remove_dir_all(path.parent().unwrap());
+    }
+
+    #[test]
+    fn local_only_profiles_can_only_discard() {
+        let settings = Settings {
+            score_privacy: ScorePrivacy::LocalOnly,
+            ..Settings::default()
+        };
+        let (mut app, path) = recovery_with_pending_run("local-only", settings);
+        press(&mut app, KeyCode::Enter);
+        app.update();
+        assert_eq!(state(&app), GameState::MainMenu);
+        assert!(!path.exists());
+        assert!(app.world.resource::<Network>().last_submission().is_none());
+        let _ = std::fs::remove_dir_all(path.parent().unwrap());
+    }
+}
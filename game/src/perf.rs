@@ -0,0 +1,479 @@
+use bevy::prelude::*;
+
+// 在耗时系统开头调用：开启 perf-hud 特性时进入 tracing span 并记录耗时，否则展开为空
+macro_rules! perf_scope {
+    ($name:literal) => {
+        #[cfg(feature = "perf-hud")]
+        let _perf_scope = crate::perf::ScopeTimer::start($name);
+    };
+}
+pub(crate) use perf_scope;
+
+// 性能面板插件，未开启 perf-hud 特性时不注册任何内容
+pub struct PerfHudPlugin;
+
+impl Plugin for PerfHudPlugin {
+    #[cfg(feature = "perf-hud")]
+    fn build(&self, app: &mut App) {
+        hud::build(app);
+    }
+
+    #[cfg(not(feature = "perf-hud"))]
+    fn build(&self, _app: &mut App) {}
+}
+
+#[cfg(feature = "perf-hud")]
+pub use hud::ScopeTimer;
+
+#[cfg(feature = "perf-hud")]
+mod hud {
+    use bevy::diagnostic::{DiagnosticsStore, FrameTimeDiagnosticsPlugin};
+    use bevy::utils::tracing::span::EnteredSpan;
+    use bevy::prelude::*;
+    use std::collections::{HashMap, VecDeque};
+    use std::sync::Mutex;
+    use std::time::Instant;
+
+    use bevy::window::PrimaryWindow;
+
+    use crate::fonts::{text_style, FontAssets, FontKind};
+    use crate::governor::PerformanceGovernor;
+    use crate::network::NetworkStats;
+    use crate::trace::{trace_ball_into, BallTrace};
+    use crate::{
+        brick_cell, generate_checked_layout, ArmorPlate, Brick, BrickSpec, GameInitialized, Layout, Level, RunSeed,
+        BRICK_SIZE,
+    };
+
+    // 统计最近多少帧
+    const HISTORY_FRAMES: usize = 300;
+    // 单个系统超过这个耗时（毫秒）时高亮
+    const SYSTEM_BUDGET_MS: f32 = 2.0;
+    // 整帧超过这个耗时（毫秒）时输出警告
+    const FRAME_BUDGET_MS: f32 = 33.0;
+    // 条形图满格对应的毫秒数
+    const BAR_FULL_MS: f32 = 8.0;
+    const BAR_WIDTH: f32 = 160.0;
+    // 面板刷新间隔，避免面板本身成为开销
+    const REFRESH_INTERVAL: f32 = 0.25;
+    // 模拟球最多追踪的反弹次数
+    const TRACE_BOUNCES: usize = 10;
+
+    // 当前帧内各个系统的耗时，系统可能并行运行所以用锁
+    static FRAME_SAMPLES: Mutex<Vec<(&'static str, f32)>> = Mutex::new(Vec::new());
+
+    // 作用域计时器，离开作用域时记录耗时
+    pub struct ScopeTimer {
+        name: &'static str,
+        started: Instant,
+        _span: EnteredSpan,
+    }
+
+    impl ScopeTimer {
+        pub fn start(name: &'static str) -> Self {
+            Self {
+                name,
+                started: Instant::now(),
+                _span: info_span!("system", name).entered(),
+            }
+        }
+    }
+
+    impl Drop for ScopeTimer {
+        fn drop(&mut self) {
+            let elapsed_ms = self.started.elapsed().as_secs_f32() * 1000.0;
+            if let Ok(mut samples) = FRAME_SAMPLES.lock() {
+                samples.push((self.name, elapsed_ms));
+            }
+        }
+    }
+
+    struct PerfFrame {
+        duration_ms: f32,
+        systems: HashMap<&'static str, f32>,
+    }
+
+    // 最近若干帧的耗时记录
+    #[derive(Resource, Default)]
+    struct PerfHistory {
+        frames: VecDeque<PerfFrame>,
+        last_frame_end: Option<Instant>,
+    }
+
+    impl PerfHistory {
+        // 每个系统的平均耗时和最差一帧的耗时，按平均值从高到低排列
+        fn system_stats(&self) -> Vec<(&'static str, f32, f32)> {
+            let mut totals: HashMap<&'static str, (f32, f32)> = HashMap::new();
+            for frame in &self.frames {
+                for (name, ms) in &frame.systems {
+                    let entry = totals.entry(name).or_default();
+                    entry.0 += ms;
+                    entry.1 = entry.1.max(*ms);
+                }
+            }
+            let frame_count = self.frames.len().max(1) as f32;
+            let mut stats: Vec<_> = totals
+                .into_iter()
+                .map(|(name, (total, worst))| (name, total / frame_count, worst))
+                .collect();
+            stats.sort_by(|a, b| b.1.total_cmp(&a.1));
+            stats
+        }
+    }
+
+    #[derive(Resource, Default)]
+    struct PerfHudState {
+        visible: bool,
+        refresh: f32,
+    }
+
+    #[derive(Component)]
+    struct PerfHudUI;
+
+    // 模拟轨迹层：F4 开关，按住鼠标左键拖动，从按下的位置沿拖动方向发射模拟球
+    #[derive(Resource, Default)]
+    struct TraceOverlay {
+        enabled: bool,
+        drag_start: Option<Vec2>,
+    }
+
+    pub fn build(app: &mut App) {
+        // 帧时间统计由 GovernorPlugin 注册
+        app.init_resource::<PerfHistory>()
+            .init_resource::<PerfHudState>()
+            .init_resource::<TraceOverlay>()
+            .add_systems(Update, (toggle_perf_hud, update_perf_hud, draw_guaranteed_drops).chain())
+            .add_systems(Update, draw_ball_trace)
+            .add_systems(Last, collect_frame_samples);
+    }
+
+    // 每帧结束时收集各系统耗时，整帧过慢时输出警告
+    fn collect_frame_samples(mut history: ResMut<PerfHistory>) {
+        let now = Instant::now();
+        let samples = FRAME_SAMPLES.lock().map(|mut samples| std::mem::take(&mut *samples)).unwrap_or_default();
+        let Some(last_frame_end) = history.last_frame_end.replace(now) else {
+            return;
+        };
+
+        let mut systems: HashMap<&'static str, f32> = HashMap::new();
+        for (name, ms) in samples {
+            *systems.entry(name).or_default() += ms;
+        }
+        let duration_ms = (now - last_frame_end).as_secs_f32() * 1000.0;
+
+        if duration_ms > FRAME_BUDGET_MS {
+            let mut slowest: Vec<_> = systems.iter().map(|(name, ms)| (*name, *ms)).collect();
+            slowest.sort_by(|a, b| b.1.total_cmp(&a.1));
+            let offenders: Vec<String> = slowest.iter().take(3).map(|(name, ms)| format!("{}={:.2}ms", name, ms)).collect();
+            warn!(frame_ms = duration_ms, systems = ?offenders, "slow frame");
+        }
+
+        history.frames.push_back(PerfFrame { duration_ms, systems });
+        while history.frames.len() > HISTORY_FRAMES {
+            history.frames.pop_front();
+        }
+    }
+
+    // F3 切换性能面板
+    fn toggle_perf_hud(
+        mut commands: Commands,
+        keyboard_input: Res<ButtonInput<KeyCode>>,
+        mut state: ResMut<PerfHudState>,
+        hud_query: Query<Entity, With<PerfHudUI>>,
+    ) {
+        if !keyboard_input.just_pressed(KeyCode::F3) {
+            return;
+        }
+        state.visible = !state.visible;
+        state.refresh = 0.0;
+        if !state.visible {
+            for entity in hud_query.iter() {
+                commands.entity(entity).despawn_recursive();
+            }
+        }
+    }
+
+    // 面板打开时用道具颜色框出保底道具砖，正常游玩时不显示
+    fn draw_guaranteed_drops(state: Res<PerfHudState>, mut gizmos: Gizmos, bricks: Query<(&Transform, &crate::Brick)>) {
+        if !state.visible {
+            return;
+        }
+        for (transform, brick) in bricks.iter() {
+            if let Some(power_type) = brick.guaranteed_drop {
+                gizmos.rect_2d(transform.translation.truncate(), 0.0, crate::BRICK_SIZE + 4.0, power_type.color());
+            }
+        }
+    }
+
+    // 按当前场上的砖块追踪模拟球的路径；布局和结果缓冲区跨帧复用，拖动时不分配内存
+    fn draw_ball_trace(
+        keyboard_input: Res<ButtonInput<KeyCode>>,
+        mouse_input: Res<ButtonInput<MouseButton>>,
+        windows: Query<&Window, With<PrimaryWindow>>,
+        cameras: Query<(&Camera, &GlobalTransform)>,
+        bricks: Query<(&Transform, &Brick, Option<&ArmorPlate>)>,
+        mut overlay: ResMut<TraceOverlay>,
+        mut layout: Local<Layout>,
+        mut trace: Local<BallTrace>,
+        mut gizmos: Gizmos,
+    ) {
+        if keyboard_input.just_pressed(KeyCode::F4) {
+            overlay.enabled = !overlay.enabled;
+            overlay.drag_start = None;
+        }
+        if !overlay.enabled {
+            return;
+        }
+
+        let cursor = windows.get_single().ok().and_then(|window| window.cursor_position()).and_then(|cursor| {
+            let (camera, camera_transform) = cameras.iter().next()?;
+            camera.viewport_to_world_2d(camera_transform, cursor)
+        });
+        if mouse_input.just_pressed(MouseButton::Left) {
+            overlay.drag_start = cursor;
+        }
+        if !mouse_input.pressed(MouseButton::Left) {
+            overlay.drag_start = None;
+        }
+        let (Some(start), Some(cursor)) = (overlay.drag_start, cursor) else {
+            return;
+        };
+
+        layout.bricks.clear();
+        layout.bricks.extend(bricks.iter().filter_map(|(transform, brick, armor)| {
+            Some(BrickSpec {
+                cell: brick_cell(transform.translation)?,
+                brick_type: brick.brick_type,
+                health: brick.health,
+                armor: armor.map(|plate| plate.0),
+                guaranteed_drop: brick.guaranteed_drop,
+                golden: brick.golden,
+                generator: None,
+            })
+        }));
+        trace_ball_into(&layout, start, cursor - start, TRACE_BOUNCES, &mut trace);
+
+        gizmos.circle_2d(start, crate::BALL_SIZE.x / 2.0, Color::WHITE);
+        gizmos.line_2d(start, cursor, Color::rgba(1.0, 1.0, 1.0, 0.3));
+        for segment in &trace.segments {
+            gizmos.line_2d(segment.from, segment.to, Color::rgb(0.3, 0.9, 1.0));
+        }
+        for &index in &trace.damaged {
+            let (row, col) = layout.bricks[index].cell;
+            let color = if trace.destroyed.contains(&index) {
+                Color::rgb(1.0, 0.3, 0.3)
+            } else {
+                Color::rgb(1.0, 0.7, 0.2)
+            };
+            gizmos.rect_2d(crate::brick_position(row, col), 0.0, BRICK_SIZE + 4.0, color);
+        }
+    }
+
+    // 定期重建面板内容
+    fn update_perf_hud(
+        mut commands: Commands,
+        time: Res<Time>,
+        mut state: ResMut<PerfHudState>,
+        history: Res<PerfHistory>,
+        diagnostics: Res<DiagnosticsStore>,
+        network_stats: Res<NetworkStats>,
+        governor: Res<PerformanceGovernor>,
+        fonts: Res<FontAssets>,
+        (level, run_seed, game_initialized): (Res<Level>, Res<RunSeed>, Res<GameInitialized>),
+        hud_query: Query<Entity, With<PerfHudUI>>,
+    ) {
+        if !state.visible {
+            return;
+        }
+        state.refresh -= time.delta_seconds();
+        if state.refresh > 0.0 {
+            return;
+        }
+        state.refresh = REFRESH_INTERVAL;
+
+        for entity in hud_query.iter() {
+            commands.entity(entity).despawn_recursive();
+        }
+
+        let fps = diagnostics
+            .get(&FrameTimeDiagnosticsPlugin::FPS)
+            .and_then(|fps| fps.smoothed())
+            .unwrap_or(0.0);
+        let worst_frame = history.frames.iter().map(|frame| frame.duration_ms).fold(0.0, f32::max);
+        let stats = history.system_stats();
+        // 布局生成是确定的，按当前关卡和种子重新生成一次就能知道哪些布局被拒绝了
+        let layout_lines: Vec<String> = if game_initialized.0 {
+            let (_, rejections) = generate_checked_layout(level.0, run_seed.0);
+            let mut lines: Vec<String> = rejections
+                .iter()
+                .enumerate()
+                .map(|(attempt, rejection)| format!("layout attempt {} rejected: {}", attempt, rejection))
+                .collect();
+            if rejections.len() > crate::layout_check::MAX_REGENERATIONS as usize {
+                lines.push("layout: using fallback template".to_string());
+            } else if lines.is_empty() {
+                lines.push("layout: ok".to_string());
+            }
+            lines
+        } else {
+            Vec::new()
+        };
+
+        commands
+            .spawn((
+                NodeBundle {
+                    style: Style {
+                        position_type: PositionType::Absolute,
+                        top: Val::Px(10.0),
+                        right: Val::Px(10.0),
+                        flex_direction: FlexDirection::Column,
+                        padding: UiRect::all(Val::Px(8.0)),
+                        row_gap: Val::Px(3.0),
+                        ..default()
+                    },
+                    background_color: BackgroundColor(Color::rgba(0.0, 0.0, 0.0, 0.75)),
+                    z_index: ZIndex::Global(200),
+                    ..default()
+                },
+                PerfHudUI,
+            ))
+            .with_children(|parent| {
+                parent.spawn(TextBundle::from_section(
+                    format!("FPS {:.0}  worst frame {:.1}ms ({} frames)", fps, worst_frame, history.frames.len()),
+                    text_style(&fonts, FontKind::Mono, 14.0, Color::WHITE),
+                ));
+                parent.spawn(TextBundle::from_section(
+                    format!(
+                        "net: {} processed  {} deferred  {} queued",
+                        network_stats.processed, network_stats.deferred, network_stats.queued
+                    ),
+                    text_style(&fonts, FontKind::Mono, 12.0, Color::rgb(0.8, 0.8, 0.8)),
+                ));
+                parent.spawn(TextBundle::from_section(
+                    format!(
+                        "quality: {:?} ({})",
+                        governor.tier,
+                        if governor.pinned { "pinned" } else { "auto" }
+                    ),
+                    text_style(&fonts, FontKind::Mono, 12.0, Color::rgb(0.8, 0.8, 0.8)),
+                ));
+                for line in layout_lines {
+                    parent.spawn(TextBundle::from_section(
+                        line,
+                        text_style(&fonts, FontKind::Mono, 12.0, Color::rgb(0.9, 0.7, 0.3)),
+                    ));
+                }
+
+                for (name, average, worst) in stats {
+                    parent
+                        .spawn(NodeBundle {
+                            style: Style {
+                                align_items: AlignItems::Center,
+                                column_gap: Val::Px(6.0),
+                                ..default()
+                            },
+                            ..default()
+                        })
+                        .with_children(|parent| {
+                            let over_budget = worst > SYSTEM_BUDGET_MS;
+                            let label_color = if over_budget { Color::rgb(1.0, 0.4, 0.4) } else { Color::rgb(0.8, 0.8, 0.8) };
+                            parent.spawn(TextBundle::from_section(
+                                name,
+                                text_style(&fonts, FontKind::Mono, 12.0, label_color),
+                            ).with_style(Style {
+                                width: Val::Px(150.0),
+                                ..default()
+                            }));
+
+                            // 平均耗时和最差一帧各一根条
+                            parent
+                                .spawn(NodeBundle {
+                                    style: Style {
+                                        width: Val::Px(BAR_WIDTH),
+                                        flex_direction: FlexDirection::Column,
+                                        row_gap: Val::Px(1.0),
+                                        ..default()
+                                    },
+                                    background_color: BackgroundColor(Color::rgba(1.0, 1.0, 1.0, 0.08)),
+                                    ..default()
+                                })
+                                .with_children(|parent| {
+                                    for (ms, base_color) in [
+                                        (average, Color::rgb(0.4, 0.8, 0.4)),
+                                        (worst, Color::rgb(0.9, 0.7, 0.3)),
+                                    ] {
+                                        let color = if ms > SYSTEM_BUDGET_MS { Color::rgb(0.95, 0.3, 0.3) } else { base_color };
+                                        parent.spawn(NodeBundle {
+                                            style: Style {
+                                                width: Val::Px((ms / BAR_FULL_MS).min(1.0) * BAR_WIDTH),
+                                                height: Val::Px(5.0),
+                                                ..default()
+                                            },
+                                            background_color: BackgroundColor(color),
+                                            ..default()
+                                        });
+                                    }
+                                });
+
+                            parent.spawn(TextBundle::from_section(
+                                format!("{:.2}/{:.2}ms", average, worst),
+                                text_style(&fonts, FontKind::Mono, 12.0, label_color),
+                            ));
+                        });
+                }
+            });
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+        use std::time::Duration;
+
+        // 带计时作用域的假系统，每帧耗时至少 1 毫秒
+        fn timed_system() {
+            crate::perf::perf_scope!("perf_test_system");
+            std::thread::sleep(Duration::from_millis(1));
+        }
+
+        // 各系统的耗时写在全局缓冲里，只有这一个测试收集，所以放在同一个测试中
+        #[test]
+        fn stepping_the_app_populates_the_diagnostics() {
+            let mut app = App::new();
+            app.add_plugins((MinimalPlugins, FrameTimeDiagnosticsPlugin))
+                .init_resource::<PerfHistory>()
+                .add_systems(Update, timed_system)
+                .add_systems(Last, collect_frame_samples);
+            for _ in 0..5 {
+                app.update();
+            }
+
+            // 第一帧只记下帧结束的时间
+            let history = app.world.resource::<PerfHistory>();
+            assert_eq!(history.frames.len(), 4);
+            assert!(history.frames.iter().all(|frame| frame.duration_ms >= 1.0));
+            // 同时运行的其他测试的计时也会写进缓冲，只检查这个系统
+            let (_, average, worst) = history
+                .system_stats()
+                .into_iter()
+                .find(|(name, _, _)| *name == "perf_test_system")
+                .expect("timed system recorded");
+            assert!(average >= 1.0 && worst >= average, "{average} {worst}");
+
+            let diagnostics = app.world.resource::<DiagnosticsStore>();
+            let frame_time = diagnostics.get(&FrameTimeDiagnosticsPlugin::FRAME_TIME).unwrap();
+            assert!(frame_time.measurement().is_some());
+
+            // 只保留最近 HISTORY_FRAMES 帧
+            app.world.resource_mut::<PerfHistory>().frames.extend((0..HISTORY_FRAMES).map(|_| PerfFrame {
+                duration_ms: 0.0,
+                systems: HashMap::new(),
+            }));
+            for _ in 0..3 {
+                app.update();
+            }
+            let history = app.world.resource::<PerfHistory>();
+            assert_eq!(history.frames.len(), HISTORY_FRAMES);
+            assert!(history.frames.back().unwrap().duration_ms >= 1.0);
+        }
+    }
+}
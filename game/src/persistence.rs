@@ -0,0 +1,331 @@
+use chrono::Local;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::path::{Path, PathBuf};
+
+// 所有本地文件共用的格式：{ "version": N, "data": ... }
+// 读取时从文件的版本逐级迁移到当前版本，保存时总是写当前版本
+pub trait Versioned: Serialize + DeserializeOwned {
+    // 当前格式版本
+    const VERSION: u32;
+    // 出错时日志中使用的文件描述
+    const LABEL: &'static str;
+    // 回放之类的大文件不缩进
+    const PRETTY: bool = true;
+
+    // 把 version 版本的数据升级到 version + 1
+    fn migrate(version: u32, data: Value) -> Result<Value, String>;
+
+    // 没有外层格式的旧文件是哪个版本
+    fn legacy_version(_data: &Value) -> u32 {
+        0
+    }
+}
+
+#[derive(Serialize)]
+struct EnvelopeRef<'a, T> {
+    version: u32,
+    data: &'a T,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Envelope {
+    version: u32,
+    data: Value,
+}
+
+// 读取并迁移到当前版本。文件不存在时返回 None；
+// 无法读取的文件改名为 .corrupt-<时间> 保留下来，不会被之后的保存覆盖，同样返回 None
+pub fn load_versioned<T: Versioned>(path: &Path) -> Option<T> {
+    let content = match std::fs::read_to_string(path) {
+        Ok(content) => content,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return None,
+        Err(e) => {
+            eprintln!("Failed to read {}: {}", T::LABEL, e);
+            return None;
+        }
+    };
+    match decode::<T>(&content) {
+        Ok(value) => Some(value),
+        Err(e) => {
+            eprintln!("Failed to load {} from {}: {}", T::LABEL, path.display(), e);
+            quarantine(path);
+            None
+        }
+    }
+}
+
+fn decode<T: Versioned>(content: &str) -> Result<T, String> {
+    let value: Value = serde_json::from_str(content).map_err(|e| e.to_string())?;
+    let (mut version, mut data) = match serde_json::from_value::<Envelope>(value.clone()) {
+        Ok(envelope) => (envelope.version, envelope.data),
+        Err(_) => (T::legacy_version(&value), value),
+    };
+    if version > T::VERSION {
+        return Err(format!("version {} is newer than supported {}", version, T::VERSION));
+    }
+    while version < T::VERSION {
+        data = T::migrate(version, data).map_err(|e| format!("migrating from version {}: {}", version, e))?;
+        version += 1;
+    }
+    serde_json::from_value(data).map_err(|e| e.to_string())
+}
+
+// 把读不了的文件改名保留，方便玩家反馈问题
+fn quarantine(path: &Path) {
+    let target = corrupt_path(path);
+    match std::fs::rename(path, &target) {
+        Ok(()) => eprintln!("Moved unreadable file to {}", target.display()),
+        Err(e) => eprintln!("Failed to move unreadable file {}: {}", path.display(), e),
+    }
+}
+
+fn corrupt_path(path: &Path) -> PathBuf {
+    let file_name = path.file_name().map(|name| name.to_string_lossy().into_owned()).unwrap_or_default();
+    path.with_file_name(format!("{}.corrupt-{}", file_name, Local::now().format("%Y%m%d-%H%M%S")))
+}
+
+// 以当前版本保存；先写临时文件再重命名，写一半时不会损坏原文件
+pub fn save_versioned<T: Versioned>(path: &Path, value: &T) -> std::io::Result<()> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    let envelope = EnvelopeRef {
+        version: T::VERSION,
+        data: value,
+    };
+    let content = if T::PRETTY {
+        serde_json::to_string_pretty(&envelope)
+    } else {
+        serde_json::to_string(&envelope)
+    }
+    .map_err(std::io::Error::other)?;
+    let mut tmp_name = path.as_os_str().to_owned();
+    tmp_name.push(".tmp");
+    let tmp_path = PathBuf::from(tmp_name);
+    std::fs::write(&tmp_path, content)?;
+    std::fs::rename(&tmp_path, path)
+}
+
+// 迁移函数收到没有对应步骤的版本
+pub fn unknown_version(version: u32) -> Result<Value, String> {
+    Err(format!("no migration from version {}", version))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    // 版本 0：没有外层格式，名字叫 player；版本 1 改名为 name；版本 2 加上 lives
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Sample {
+        name: String,
+        lives: u32,
+    }
+
+    impl Versioned for Sample {
+        const VERSION: u32 = 2;
+        const LABEL: &'static str = "sample";
+
+        fn migrate(version: u32, mut data: Value) -> Result<Value, String> {
+            match version {
+                0 => {
+                    let player = data.get("player").cloned().ok_or("missing player")?;
+                    Ok(json!({ "name": player }))
+                }
+                1 => {
+                    data["lives"] = json!(3);
+                    Ok(data)
+                }
+                _ => unknown_version(version),
+            }
+        }
+    }
+
+    // 每个测试使用自己的空目录，测试结束（包括失败）时删除
+    struct ScratchDir(PathBuf);
+
+    impl std::ops::Deref for ScratchDir {
+        type Target = Path;
+
+        fn deref(&self) -> &Path {
+            &self.0
+        }
+    }
+
+    impl Drop for ScratchDir {
+        fn drop(&mut self) {
+            let _ = std::fs::remove_dir_all(&self.0);
+        }
+    }
+
+    fn scratch_dir(test: &str) -> ScratchDir {
+        let dir = std::env::temp_dir().join(format!("breakout-persistence-{}-{}", std::process::id(), test));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        ScratchDir(dir)
+    }
+
+    fn file_names(dir: &Path) -> Vec<String> {
+        let mut names: Vec<String> = std::fs::read_dir(dir)
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().to_string_lossy().into_owned())
+            .collect();
+        names.sort();
+        names
+    }
+
+    fn assert_quarantined(dir: &Path, original: &str, content: &str) {
+        let names = file_names(dir);
+        assert_eq!(names.len(), 1, "{:?}", names);
+        assert!(names[0].starts_with(&format!("{}.corrupt-", original)), "{:?}", names);
+        assert_eq!(std::fs::read_to_string(dir.join(&names[0])).unwrap(), content);
+    }
+
+    #[test]
+    fn missing_file_loads_as_none_without_touching_the_directory() {
+        let dir = scratch_dir("missing");
+        assert_eq!(load_versioned::<Sample>(&dir.join("sample.json")), None);
+        assert!(file_names(&dir).is_empty());
+    }
+
+    #[test]
+    fn saved_files_round_trip_at_the_current_version() {
+        let dir = scratch_dir("round-trip");
+        let path = dir.join("sample.json");
+        let sample = Sample {
+            name: "Ada".to_string(),
+            lives: 5,
+        };
+        save_versioned(&path, &sample).unwrap();
+        assert_eq!(file_names(&dir), ["sample.json"]);
+        let saved: Value = serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+        assert_eq!(saved["version"], json!(2));
+        assert_eq!(load_versioned::<Sample>(&path), Some(sample));
+    }
+
+    #[test]
+    fn legacy_files_migrate_through_every_step() {
+        let dir = scratch_dir("legacy");
+        let path = dir.join("sample.json");
+        std::fs::write(&path, r#"{ "player": "Ada" }"#).unwrap();
+        let expected = Sample {
+            name: "Ada".to_string(),
+            lives: 3,
+        };
+        assert_eq!(load_versioned::<Sample>(&path), Some(expected));
+    }
+
+    #[test]
+    fn enveloped_files_migrate_from_their_own_version() {
+        let dir = scratch_dir("enveloped");
+        let path = dir.join("sample.json");
+        std::fs::write(&path, r#"{ "version": 1, "data": { "name": "Ada" } }"#).unwrap();
+        let expected = Sample {
+            name: "Ada".to_string(),
+            lives: 3,
+        };
+        assert_eq!(load_versioned::<Sample>(&path), Some(expected));
+    }
+
+    #[test]
+    fn unparseable_files_are_renamed_aside() {
+        let dir = scratch_dir("corrupt");
+        let path = dir.join("sample.json");
+        std::fs::write(&path, "{ not json").unwrap();
+        assert_eq!(load_versioned::<Sample>(&path), None);
+        assert_quarantined(&dir, "sample.json", "{ not json");
+    }
+
+    #[test]
+    fn files_from_a_newer_version_are_renamed_aside() {
+        let dir = scratch_dir("newer");
+        let path = dir.join("sample.json");
+        let content = r#"{ "version": 3, "data": { "name": "Ada", "lives": 3 } }"#;
+        std::fs::write(&path, content).unwrap();
+        assert_eq!(load_versioned::<Sample>(&path), None);
+        assert_quarantined(&dir, "sample.json", content);
+    }
+
+    #[test]
+    fn failed_migrations_are_renamed_aside() {
+        let dir = scratch_dir("failed-migration");
+        let path = dir.join("sample.json");
+        let content = r#"{ "version": 0, "data": { "someone": "Ada" } }"#;
+        std::fs::write(&path, content).unwrap();
+        assert_eq!(load_versioned::<Sample>(&path), None);
+        assert_quarantined(&dir, "sample.json", content);
+    }
+
+    // 性质：文件停在任何一个旧版本，读出来都和从最旧的格式逐级迁移的结果相同；保存后按当前版本读回也不变。
+    // oldest 是最旧版本（0）的数据，中间各版本的数据由迁移函数逐级得到
+    fn assert_every_version_migrates<T: Versioned>(oldest: Value) {
+        let dir = scratch_dir(&format!("every-version-{}", T::LABEL.replace(' ', "-")));
+        let path = dir.join("file.json");
+        let expected = decode::<T>(&json!({ "version": 0, "data": oldest }).to_string())
+            .unwrap_or_else(|e| panic!("{} version 0: {}", T::LABEL, e));
+        let expected = serde_json::to_value(&expected).unwrap();
+
+        let mut data = oldest;
+        for version in 0..=T::VERSION {
+            std::fs::write(&path, json!({ "version": version, "data": data }).to_string()).unwrap();
+            let loaded = load_versioned::<T>(&path).unwrap_or_else(|| panic!("{} version {} loads", T::LABEL, version));
+            assert_eq!(serde_json::to_value(&loaded).unwrap(), expected, "{} from version {}", T::LABEL, version);
+
+            save_versioned(&path, &loaded).unwrap();
+            let saved: Value = serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+            assert_eq!(saved["version"], json!(T::VERSION));
+            let reloaded = load_versioned::<T>(&path).unwrap();
+            let reloaded = serde_json::to_value(&reloaded).unwrap();
+            assert_eq!(reloaded, expected, "{} saved from version {}", T::LABEL, version);
+
+            if version < T::VERSION {
+                data = T::migrate(version, data).unwrap_or_else(|e| panic!("{} version {}: {}", T::LABEL, version, e));
+            }
+        }
+        // 迁移到当前版本以后没有下一步
+        assert!(T::migrate(T::VERSION, data).is_err());
+    }
+
+    #[test]
+    fn every_old_sample_version_migrates_to_the_current_one() {
+        for name in ["Ada", "", "名字", "O'Brien \"quoted\"", &"x".repeat(300)] {
+            assert_every_version_migrates::<Sample>(json!({ "player": name }));
+        }
+    }
+
+    #[test]
+    fn every_old_version_of_the_saved_files_migrates_to_the_current_one() {
+        use crate::pending_run::PendingRun;
+        use crate::profile::Profile;
+        use crate::profiles::ProfileRegistry;
+        use crate::replay::GhostTrace;
+        use crate::settings::Settings;
+
+        assert_every_version_migrates::<Settings>(serde_json::to_value(Settings::default()).unwrap());
+        assert_every_version_migrates::<Profile>(serde_json::to_value(Profile::default()).unwrap());
+        assert_every_version_migrates::<ProfileRegistry>(json!({
+            "active": "p1",
+            "profiles": [{ "id": "p1", "name": "Ada", "color": [1.0, 0.5, 0.0] }],
+            "next_id": 2,
+        }));
+        assert_every_version_migrates::<PendingRun>(json!({
+            "player_name": "Ada",
+            "score": 1200,
+            "level": 3,
+            "difficulty": "Medium",
+            "seed": 7,
+            "duration": 95.5,
+        }));
+        // 最早的轨迹没有记录频率
+        assert_every_version_migrates::<GhostTrace>(json!({
+            "seed": 7,
+            "difficulty": "Medium",
+            "score": 1200,
+            "levels": [[0.0, 1.5, -3.25]],
+        }));
+    }
+}
@@ -0,0 +1,42 @@
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+// 提交分数时公开多少信息
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ScorePrivacy {
+    // 用输入的名字提交到服务器
+    #[default]
+    Full,
+    // 用由档案随机编号得到的固定代号提交，不会上传输入的名字
+    Anonymous,
+    // 不提交，只记录在本机的档案里
+    LocalOnly,
+}
+
+impl ScorePrivacy {
+    pub const ALL: [ScorePrivacy; 3] = [ScorePrivacy::Full, ScorePrivacy::Anonymous, ScorePrivacy::LocalOnly];
+
+    pub fn label_key(&self) -> &'static str {
+        match self {
+            ScorePrivacy::Full => "privacy.full",
+            ScorePrivacy::Anonymous => "privacy.anonymous",
+            ScorePrivacy::LocalOnly => "privacy.local_only",
+        }
+    }
+
+    pub fn step(self, direction: i32) -> Self {
+        let current = Self::ALL.iter().position(|privacy| *privacy == self).unwrap_or(0) as i32;
+        Self::ALL[(current + direction).rem_euclid(Self::ALL.len() as i32) as usize]
+    }
+}
+
+// 档案的随机编号：设置文件里没有时重新生成，切换隐私选项时随设置一起保存，之后保持不变
+pub fn random_anonymous_id() -> u32 {
+    rand::thread_rng().gen()
+}
+
+// 匿名提交时使用的代号，例如 Player-7F3A；同一个档案总是得到同一个代号
+pub fn anonymous_handle(id: u32) -> String {
+    // 高低两半异或，编号的每一位都会影响代号
+    format!("Player-{:04X}", (id >> 16) ^ (id & 0xFFFF))
+}
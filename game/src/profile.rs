@@ -0,0 +1,220 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+use crate::level_select::LevelRecord;
+use crate::persistence::{self, load_versioned, save_versioned, Versioned};
+use crate::profiles::ProfilePaths;
+use crate::rank_result::RankResult;
+use crate::tournament::Bracket;
+use crate::{Difficulty, GameplayEvent};
+
+// 当前这一局的统计，结束时累加到本地档案
+#[derive(Resource, Default, Debug, Clone)]
+pub struct RunStats {
+    pub play_time: f32,
+    pub bricks_destroyed: BTreeMap<String, u32>,
+    pub powerups_collected: BTreeMap<String, u32>,
+    // 连击：两次挡板接球之间打碎的砖块数
+    pub combo: u32,
+    pub best_combo: u32,
+    // 被挡板边缘容错救回的次数
+    pub edge_saves: u32,
+    // 落在挡板完美击球区的次数
+    pub perfect_hits: u32,
+    // 护符抵消的不利道具数
+    pub debuffs_warded: u32,
+    // 开局时在难度选择界面买下的保险代币数
+    pub insurance_bought: u8,
+    // 第三关前选择的诅咒，没有选择时为空
+    pub curse: Option<String>,
+    // 一轮爆炸打碎砖块数的最大值（只记录达到连锁长度的）
+    pub largest_chain: u32,
+}
+
+impl RunStats {
+    pub fn reset(&mut self) {
+        *self = Self::default();
+    }
+
+    pub fn record_brick(&mut self, brick_type: &str, combo: bool) {
+        *self.bricks_destroyed.entry(brick_type.to_string()).or_insert(0) += 1;
+        if combo {
+            self.combo += 1;
+            self.best_combo = self.best_combo.max(self.combo);
+        }
+    }
+
+    pub fn record_powerup(&mut self, power_type: &str) {
+        *self.powerups_collected.entry(power_type.to_string()).or_insert(0) += 1;
+    }
+
+    // 球碰到挡板时连击结束
+    pub fn end_combo(&mut self) {
+        self.combo = 0;
+    }
+}
+
+// 本机累计的终身统计
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Profile {
+    pub total_play_time: f32,
+    pub total_runs: u32,
+    pub bricks_destroyed: BTreeMap<String, u32>,
+    pub powerups_collected: BTreeMap<String, u32>,
+    pub best_combo: u32,
+    // 按难度名称记录最高分
+    pub best_scores: BTreeMap<String, u32>,
+    // 游戏结束时所在关卡的分布
+    pub game_over_levels: BTreeMap<u32, u32>,
+    // 上次查看更新日志时的游戏版本
+    pub last_seen_version: Option<String>,
+    // 选择的球和挡板外观，为空时使用默认外观
+    pub ball_skin: Option<String>,
+    pub paddle_skin: Option<String>,
+    // 每日挑战按日期（UTC）记录本机提交过的最高分
+    pub daily_best: BTreeMap<String, u32>,
+    pub perfect_hits: u32,
+    // 按“赛季|难度”记录提交后得到的最好排行榜名次
+    pub best_ranks: BTreeMap<String, u32>,
+    // 离开结算界面后才返回的名次，下次进入主菜单时提示
+    pub unseen_rank: Option<RankResult>,
+    // 单关挑战按“关卡|难度”记录最好成绩和奖牌
+    pub level_results: BTreeMap<String, LevelRecord>,
+    // 普通局中打通的最高关卡，决定选关界面解锁到哪一关
+    pub highest_cleared_level: u32,
+    // 还没结束的本地淘汰赛，中途退出后可以继续
+    pub tournament: Option<Bracket>,
+    // 护符抵消过的不利道具总数
+    pub debuffs_warded: u32,
+    // 钱包：一局结束时按分数发放的金币，用来购买保险代币
+    pub coins: u32,
+}
+
+impl Default for Profile {
+    fn default() -> Self {
+        Self {
+            total_play_time: 0.0,
+            total_runs: 0,
+            bricks_destroyed: BTreeMap::new(),
+            powerups_collected: BTreeMap::new(),
+            best_combo: 0,
+            best_scores: BTreeMap::new(),
+            game_over_levels: BTreeMap::new(),
+            last_seen_version: None,
+            ball_skin: None,
+            paddle_skin: None,
+            daily_best: BTreeMap::new(),
+            perfect_hits: 0,
+            best_ranks: BTreeMap::new(),
+            unseen_rank: None,
+            level_results: BTreeMap::new(),
+            highest_cleared_level: 0,
+            tournament: None,
+            debuffs_warded: 0,
+            coins: 0,
+        }
+    }
+}
+
+impl Profile {
+    // 读取档案，旧版本先迁移；文件不存在时返回空档案
+    pub fn load(paths: &ProfilePaths) -> Self {
+        load_versioned(&paths.stats()).unwrap_or_default()
+    }
+
+    pub fn save(&self, paths: &ProfilePaths) -> std::io::Result<()> {
+        save_versioned(&paths.stats(), self)
+    }
+
+    // 把一局的统计累加进档案
+    pub fn absorb_run(&mut self, stats: &RunStats, score: u32, level: u32, difficulty: Difficulty) {
+        self.total_play_time += stats.play_time;
+        self.total_runs += 1;
+        for (brick_type, count) in &stats.bricks_destroyed {
+            *self.bricks_destroyed.entry(brick_type.clone()).or_insert(0) += count;
+        }
+        for (power_type, count) in &stats.powerups_collected {
+            *self.powerups_collected.entry(power_type.clone()).or_insert(0) += count;
+        }
+        self.best_combo = self.best_combo.max(stats.best_combo);
+        self.perfect_hits += stats.perfect_hits;
+        self.debuffs_warded += stats.debuffs_warded;
+        let best = self.best_scores.entry(difficulty.name().to_string()).or_insert(0);
+        *best = (*best).max(score);
+        *self.game_over_levels.entry(level).or_insert(0) += 1;
+    }
+
+    // 收集次数最多的道具
+    pub fn favorite_powerup(&self) -> Option<&str> {
+        self.powerups_collected
+            .iter()
+            .max_by_key(|(_, count)| **count)
+            .map(|(name, _)| name.as_str())
+    }
+}
+
+// 以后新增版本时在 migrate 中按顺序追加迁移步骤
+impl Versioned for Profile {
+    const VERSION: u32 = 2;
+    const LABEL: &'static str = "profile";
+
+    fn migrate(version: u32, mut data: serde_json::Value) -> Result<serde_json::Value, String> {
+        match version {
+            // v0 -> v1：早期档案没有版本号，缺失的字段由 serde(default) 补齐
+            0 => Ok(data),
+            // v1 -> v2：版本号从档案内的 version 字段移到外层
+            1 => {
+                if let Some(object) = data.as_object_mut() {
+                    object.remove("version");
+                }
+                Ok(data)
+            }
+            _ => persistence::unknown_version(version),
+        }
+    }
+
+    // 外层格式之前的档案自己带有 version 字段（v1），更早的没有（v0）
+    fn legacy_version(data: &serde_json::Value) -> u32 {
+        data.get("version").and_then(|v| v.as_u64()).unwrap_or(0) as u32
+    }
+}
+
+// 一局结束时写入档案
+pub fn record_run(paths: &ProfilePaths, stats: &RunStats, score: u32, level: u32, difficulty: Difficulty) {
+    let mut profile = Profile::load(paths);
+    profile.absorb_run(stats, score, level, difficulty);
+    if let Err(e) = profile.save(paths) {
+        eprintln!("Failed to save profile: {}", e);
+    }
+}
+
+// 累计本局游戏时间
+pub fn track_play_time(time: Res<Time>, mut stats: ResMut<RunStats>) {
+    stats.play_time += time.delta_seconds();
+}
+
+// 根据游戏事件更新本局统计
+pub fn record_gameplay_stats(mut events: EventReader<GameplayEvent>, mut stats: ResMut<RunStats>) {
+    for event in events.read() {
+        match event {
+            GameplayEvent::BrickDestroyed { brick_type, combo, .. } => stats.record_brick(brick_type.name(), *combo),
+            GameplayEvent::PowerUpCollected { power_type, .. } => stats.record_powerup(power_type.name()),
+            GameplayEvent::PaddleHit { perfect, .. } => {
+                if *perfect {
+                    stats.perfect_hits += 1;
+                }
+                stats.end_combo();
+            }
+            GameplayEvent::DebuffWarded { .. } => stats.debuffs_warded += 1,
+            GameplayEvent::EdgeSave { .. } => {
+                stats.edge_saves += 1;
+                stats.end_combo();
+            }
+            GameplayEvent::BombDetonated { .. } => stats.end_combo(),
+            GameplayEvent::ChainReaction { length, .. } => stats.largest_chain = stats.largest_chain.max(*length),
+            _ => {}
+        }
+    }
+}
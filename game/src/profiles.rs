@@ -0,0 +1,456 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+use crate::fonts::{text_style, FontAssets, FontKind};
+use crate::i18n::Locale;
+use crate::persistence::{self, load_versioned, save_versioned, Versioned};
+use crate::{GameState, PlayerName, Settings};
+
+// 所有玩家档案所在目录，以及记录档案列表和当前档案的根配置
+const PROFILES_DIR: &str = "profiles";
+const REGISTRY_FILE: &str = "profiles.json";
+// 每个档案目录下的文件名（旧版本直接放在工作目录下）
+const SETTINGS_FILE: &str = "settings.json";
+const STATS_FILE: &str = "profile.json";
+const REPLAY_DIR: &str = "replays";
+const PENDING_RUN_FILE: &str = "pending_run.json";
+// 名称最大长度
+const MAX_NAME_LEN: usize = 20;
+// 新建档案时依次分配的头像颜色
+const AVATAR_COLORS: [[f32; 3]; 6] = [
+    [0.3, 0.7, 1.0],
+    [1.0, 0.5, 0.3],
+    [0.4, 0.9, 0.4],
+    [0.9, 0.4, 0.9],
+    [1.0, 0.85, 0.2],
+    [0.4, 0.9, 0.9],
+];
+
+// 当前档案的本地文件路径，所有读写都通过它
+#[derive(Resource, Debug, Clone)]
+pub struct ProfilePaths {
+    root: PathBuf,
+}
+
+impl Default for ProfilePaths {
+    // 还没有选择档案时使用旧版本的位置（工作目录）
+    fn default() -> Self {
+        Self { root: PathBuf::from(".") }
+    }
+}
+
+impl ProfilePaths {
+    fn for_profile(id: &str) -> Self {
+        Self {
+            root: Path::new(PROFILES_DIR).join(id),
+        }
+    }
+
+    // 测试用：档案文件放在指定目录，不碰工作目录
+    #[cfg(test)]
+    pub fn in_dir(root: &Path) -> Self {
+        Self { root: root.to_path_buf() }
+    }
+
+    pub fn settings(&self) -> PathBuf {
+        self.root.join(SETTINGS_FILE)
+    }
+
+    pub fn stats(&self) -> PathBuf {
+        self.root.join(STATS_FILE)
+    }
+
+    pub fn replays(&self) -> PathBuf {
+        self.root.join(REPLAY_DIR)
+    }
+
+    pub fn pending_run(&self) -> PathBuf {
+        self.root.join(PENDING_RUN_FILE)
+    }
+}
+
+// 一个玩家档案
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PlayerProfile {
+    pub id: String,
+    pub name: String,
+    pub color: [f32; 3],
+}
+
+impl PlayerProfile {
+    pub fn avatar_color(&self) -> Color {
+        Color::rgb(self.color[0], self.color[1], self.color[2])
+    }
+}
+
+// 本机上的所有档案和当前使用的档案
+#[derive(Resource, Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ProfileRegistry {
+    pub active: Option<String>,
+    pub profiles: Vec<PlayerProfile>,
+    // 下一个档案的编号，删除后不会复用
+    next_id: u32,
+}
+
+impl Versioned for ProfileRegistry {
+    const VERSION: u32 = 1;
+    const LABEL: &'static str = "profile registry";
+
+    fn migrate(version: u32, data: serde_json::Value) -> Result<serde_json::Value, String> {
+        match version {
+            // v0：没有外层格式的旧档案列表
+            0 => Ok(data),
+            _ => persistence::unknown_version(version),
+        }
+    }
+}
+
+impl ProfileRegistry {
+    fn path() -> PathBuf {
+        Path::new(PROFILES_DIR).join(REGISTRY_FILE)
+    }
+
+    // 读取档案列表，文件不存在时返回空列表
+    pub fn load() -> Self {
+        load_versioned(&Self::path()).unwrap_or_default()
+    }
+
+    pub fn save(&self) {
+        if let Err(e) = save_versioned(&Self::path(), self) {
+            eprintln!("Failed to save profile registry: {}", e);
+        }
+    }
+
+    pub fn active_profile(&self) -> Option<&PlayerProfile> {
+        let active = self.active.as_ref()?;
+        self.profiles.iter().find(|profile| &profile.id == active)
+    }
+
+    pub fn active_paths(&self) -> ProfilePaths {
+        self.active_profile()
+            .map(|profile| ProfilePaths::for_profile(&profile.id))
+            .unwrap_or_default()
+    }
+
+    // 新建档案；第一个档案会接管旧版本留在工作目录下的文件
+    fn create(&mut self, name: &str) -> PlayerProfile {
+        let profile = PlayerProfile {
+            id: format!("p{}", self.next_id),
+            name: name.to_string(),
+            color: AVATAR_COLORS[self.next_id as usize % AVATAR_COLORS.len()],
+        };
+        self.next_id += 1;
+
+        let paths = ProfilePaths::for_profile(&profile.id);
+        if let Err(e) = std::fs::create_dir_all(&paths.root) {
+            eprintln!("Failed to create profile directory: {}", e);
+        }
+        if self.profiles.is_empty() {
+            migrate_legacy_files(&paths);
+        }
+
+        self.profiles.push(profile.clone());
+        self.save();
+        profile
+    }
+
+    // 删除档案及其目录下的所有文件
+    fn delete(&mut self, id: &str) {
+        let paths = ProfilePaths::for_profile(id);
+        if let Err(e) = std::fs::remove_dir_all(&paths.root) {
+            eprintln!("Failed to delete profile directory: {}", e);
+        }
+        self.profiles.retain(|profile| profile.id != id);
+        if self.active.as_deref() == Some(id) {
+            self.active = None;
+        }
+        self.save();
+    }
+}
+
+// 把旧版本的设置、统计和回放移动到档案目录下
+fn migrate_legacy_files(paths: &ProfilePaths) {
+    let legacy = ProfilePaths::default();
+    for (from, to) in [
+        (legacy.settings(), paths.settings()),
+        (legacy.stats(), paths.stats()),
+        (legacy.replays(), paths.replays()),
+    ] {
+        if from.exists() {
+            if let Err(e) = std::fs::rename(&from, &to) {
+                eprintln!("Failed to move {} into profile: {}", from.display(), e);
+            }
+        }
+    }
+}
+
+// 档案选择界面的输入模式
+#[derive(Debug, Clone, Default, PartialEq)]
+enum PickerMode {
+    #[default]
+    Browse,
+    // 输入新档案名称
+    NewProfile(String),
+    // 输入档案名称确认删除
+    ConfirmDelete(String),
+}
+
+#[derive(Resource, Default)]
+pub struct ProfilePickerState {
+    selected: usize,
+    mode: PickerMode,
+}
+
+#[derive(Component)]
+pub struct ProfilePickerUI;
+
+// 进入档案选择界面
+pub fn setup_profile_picker(
+    mut commands: Commands,
+    cameras: Query<(), With<Camera>>,
+    registry: Res<ProfileRegistry>,
+    fonts: Res<FontAssets>,
+    locale: Res<Locale>,
+    mut picker: ResMut<ProfilePickerState>,
+) {
+    // 首次启动时还没有主菜单的相机
+    if cameras.is_empty() {
+        commands.spawn((Camera2dBundle::default(), ProfilePickerUI));
+    }
+
+    picker.mode = PickerMode::Browse;
+    picker.selected = registry
+        .active
+        .as_ref()
+        .and_then(|active| registry.profiles.iter().position(|profile| &profile.id == active))
+        .unwrap_or(0);
+    spawn_picker_ui(&mut commands, &fonts, &locale, &registry, &picker);
+}
+
+fn spawn_picker_ui(
+    commands: &mut Commands,
+    fonts: &FontAssets,
+    locale: &Locale,
+    registry: &ProfileRegistry,
+    picker: &ProfilePickerState,
+) {
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    width: Val::Percent(100.0),
+                    height: Val::Percent(100.0),
+                    flex_direction: FlexDirection::Column,
+                    justify_content: JustifyContent::Center,
+                    align_items: AlignItems::Center,
+                    row_gap: Val::Px(10.0),
+                    ..default()
+                },
+                background_color: BackgroundColor(Color::rgb(0.1, 0.1, 0.15)),
+                ..default()
+            },
+            ProfilePickerUI,
+        ))
+        .with_children(|parent| {
+            parent.spawn(TextBundle::from_section(
+                locale.tr("profiles.title", &[]),
+                text_style(fonts, FontKind::Bold, 50.0, Color::WHITE),
+            ).with_style(Style {
+                margin: UiRect::bottom(Val::Px(30.0)),
+                ..default()
+            }));
+
+            // 已有档案，最后一项是新建档案
+            for index in 0..=registry.profiles.len() {
+                let selected = index == picker.selected;
+                let (label, color) = match registry.profiles.get(index) {
+                    Some(profile) => (profile.name.clone(), profile.avatar_color()),
+                    None => (locale.tr("profiles.new_profile", &[]), Color::rgb(0.5, 0.5, 0.5)),
+                };
+                parent
+                    .spawn(NodeBundle {
+                        style: Style {
+                            width: Val::Px(360.0),
+                            padding: UiRect::all(Val::Px(8.0)),
+                            border: UiRect::all(Val::Px(2.0)),
+                            align_items: AlignItems::Center,
+                            column_gap: Val::Px(15.0),
+                            ..default()
+                        },
+                        background_color: BackgroundColor(Color::rgba(0.2, 0.2, 0.25, 0.8)),
+                        border_color: BorderColor(if selected { Color::rgb(1.0, 1.0, 0.0) } else { Color::NONE }),
+                        ..default()
+                    })
+                    .with_children(|parent| {
+                        // 头像色块
+                        parent.spawn(NodeBundle {
+                            style: Style {
+                                width: Val::Px(30.0),
+                                height: Val::Px(30.0),
+                                ..default()
+                            },
+                            background_color: BackgroundColor(color),
+                            ..default()
+                        });
+                        parent.spawn(TextBundle::from_section(
+                            label,
+                            text_style(fonts, FontKind::Regular, 26.0, Color::WHITE),
+                        ));
+                    });
+            }
+
+            let (prompt, hint) = match &picker.mode {
+                PickerMode::Browse => (
+                    String::new(),
+                    locale.tr("profiles.browse_hint", &[]),
+                ),
+                PickerMode::NewProfile(name) => (
+                    locale.tr("profiles.new_prompt", &[("name", name)]),
+                    locale.tr("profiles.new_hint", &[]),
+                ),
+                PickerMode::ConfirmDelete(typed) => (
+                    locale.tr(
+                        "profiles.delete_prompt",
+                        &[
+                            (
+                                "name",
+                                &registry.profiles.get(picker.selected).map(|profile| profile.name.as_str()).unwrap_or(""),
+                            ),
+                            ("typed", typed),
+                        ],
+                    ),
+                    locale.tr("profiles.delete_hint", &[]),
+                ),
+            };
+            parent.spawn(TextBundle::from_section(
+                prompt,
+                text_style(fonts, FontKind::Mono, 24.0, Color::rgb(1.0, 0.85, 0.2)),
+            ).with_style(Style {
+                margin: UiRect::top(Val::Px(20.0)),
+                ..default()
+            }));
+            parent.spawn(TextBundle::from_section(
+                hint,
+                text_style(fonts, FontKind::Regular, 18.0, Color::rgb(0.6, 0.6, 0.6)),
+            ));
+        });
+}
+
+// 档案选择界面的输入处理
+pub fn profile_picker_system(
+    mut commands: Commands,
+    keyboard: Res<ButtonInput<KeyCode>>,
+    mut char_events: EventReader<ReceivedCharacter>,
+    mut next_state: ResMut<NextState<GameState>>,
+    mut picker: ResMut<ProfilePickerState>,
+    mut registry: ResMut<ProfileRegistry>,
+    mut paths: ResMut<ProfilePaths>,
+    mut settings: ResMut<Settings>,
+    mut player_name: ResMut<PlayerName>,
+    fonts: Res<FontAssets>,
+    locale: Res<Locale>,
+    ui_query: Query<Entity, (With<ProfilePickerUI>, With<Node>)>,
+) {
+    let typed: String = char_events
+        .read()
+        .filter_map(|event| event.char.as_str().chars().next())
+        .filter(|ch| ch.is_alphanumeric() || *ch == ' ')
+        .collect();
+    let changed = !typed.is_empty() || keyboard.get_just_pressed().next().is_some();
+    let entry_count = registry.profiles.len() + 1;
+
+    match picker.mode.clone() {
+        PickerMode::Browse => {
+            if keyboard.just_pressed(KeyCode::ArrowUp) {
+                picker.selected = (picker.selected + entry_count - 1) % entry_count;
+            } else if keyboard.just_pressed(KeyCode::ArrowDown) {
+                picker.selected = (picker.selected + 1) % entry_count;
+            } else if keyboard.just_pressed(KeyCode::Enter) {
+                match registry.profiles.get(picker.selected).cloned() {
+                    Some(profile) => {
+                        activate_profile(&profile, &mut registry, &mut paths, &mut settings, &mut player_name);
+                        next_state.set(GameState::MainMenu);
+                    }
+                    None => picker.mode = PickerMode::NewProfile(String::new()),
+                }
+            } else if keyboard.just_pressed(KeyCode::Delete) && picker.selected < registry.profiles.len() {
+                picker.mode = PickerMode::ConfirmDelete(String::new());
+            } else if keyboard.just_pressed(KeyCode::Escape) && registry.active_profile().is_some() {
+                next_state.set(GameState::MainMenu);
+            }
+        }
+        PickerMode::NewProfile(mut name) => {
+            edit_text(&mut name, &typed, &keyboard);
+            if keyboard.just_pressed(KeyCode::Escape) {
+                picker.mode = PickerMode::Browse;
+            } else if keyboard.just_pressed(KeyCode::Enter) && !name.trim().is_empty() {
+                let profile = registry.create(name.trim());
+                activate_profile(&profile, &mut registry, &mut paths, &mut settings, &mut player_name);
+                next_state.set(GameState::MainMenu);
+            } else {
+                picker.mode = PickerMode::NewProfile(name);
+            }
+        }
+        PickerMode::ConfirmDelete(mut confirmation) => {
+            edit_text(&mut confirmation, &typed, &keyboard);
+            let target = registry.profiles.get(picker.selected).cloned();
+            if keyboard.just_pressed(KeyCode::Escape) {
+                picker.mode = PickerMode::Browse;
+            } else if keyboard.just_pressed(KeyCode::Enter) {
+                // 必须完整输入档案名称才会删除
+                if let Some(profile) = target.filter(|profile| profile.name == confirmation) {
+                    registry.delete(&profile.id);
+                    if registry.active.is_none() {
+                        *paths = ProfilePaths::default();
+                    }
+                    picker.selected = 0;
+                    picker.mode = PickerMode::Browse;
+                }
+            } else {
+                picker.mode = PickerMode::ConfirmDelete(confirmation);
+            }
+        }
+    }
+
+    if changed {
+        for entity in ui_query.iter() {
+            commands.entity(entity).despawn_recursive();
+        }
+        spawn_picker_ui(&mut commands, &fonts, &locale, &registry, &picker);
+    }
+}
+
+fn edit_text(text: &mut String, typed: &str, keyboard: &ButtonInput<KeyCode>) {
+    for ch in typed.chars() {
+        if text.chars().count() < MAX_NAME_LEN {
+            text.push(ch);
+        }
+    }
+    if keyboard.just_pressed(KeyCode::Backspace) {
+        text.pop();
+    }
+}
+
+// 切换到某个档案：读取它的设置，玩家名称默认使用档案名称
+fn activate_profile(
+    profile: &PlayerProfile,
+    registry: &mut ProfileRegistry,
+    paths: &mut ProfilePaths,
+    settings: &mut Settings,
+    player_name: &mut PlayerName,
+) {
+    registry.active = Some(profile.id.clone());
+    registry.save();
+    *paths = ProfilePaths::for_profile(&profile.id);
+    *settings = Settings::load(paths);
+    player_name.0 = profile.name.clone();
+}
+
+// 离开档案选择界面
+pub fn cleanup_profile_picker(mut commands: Commands, query: Query<Entity, With<ProfilePickerUI>>) {
+    for entity in query.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+}
@@ -0,0 +1,242 @@
+use bevy::prelude::*;
+use rand::Rng;
+
+use crate::bomb::BombTimer;
+use crate::curse::Curse;
+use crate::fonts::{text_style, FontAssets, FontKind};
+use crate::governor::{EffectBudget, EffectKind};
+use crate::i18n::Locale;
+use crate::safe_despawn::SafeDespawnExt;
+use crate::{layout_check, level_file};
+use crate::{Brick, BrickType, GameEntity, GameplayEvent, Layout, Particle, Score};
+
+// 配额关出现的关卡和它的关卡文件
+pub const QUOTA_LEVEL: u32 = 7;
+pub const QUOTA_LEVEL_FILE: &str = include_str!("../assets/levels/quota_pockets.ron");
+// 配额关需要打碎的可破坏砖块比例（百分比）
+const QUOTA_PERCENT: u32 = 85;
+// 达成配额后剩余砖块按分数的这个比例给清场奖励（百分比）
+const CLEANUP_BONUS_PERCENT: u32 = 25;
+// 消散从左到右依次进行：每块砖比前一块晚开始的时间，以及每块砖消散的时长（秒）
+const CASCADE_STEP: f32 = 0.06;
+const DISSOLVE_TIME: f32 = 0.35;
+
+const QUOTA_COLOR: Color = Color::rgb(0.55, 0.9, 0.5);
+
+// 当前关卡的砖块配额；普通关卡没有配额，要打完全部可破坏砖块
+#[derive(Resource, Debug, Default)]
+pub struct BrickQuota {
+    percent: Option<u32>,
+    // 关卡开始时的可破坏砖块数和已经打碎的块数（包括自己爆炸的定时炸弹）
+    total: u32,
+    destroyed: u32,
+    // 达成配额时给出的清场奖励；达成前为空
+    cleanup_bonus: Option<u32>,
+}
+
+impl BrickQuota {
+    pub fn from_layout(layout: &Layout) -> Self {
+        Self {
+            percent: layout.quota,
+            total: layout
+                .bricks
+                .iter()
+                .filter(|spec| !matches!(spec.brick_type, BrickType::Unbreakable))
+                .count() as u32,
+            ..default()
+        }
+    }
+
+    pub fn is_active(&self) -> bool {
+        self.percent.is_some()
+    }
+
+    // 需要打碎的块数，向上取整
+    fn required(&self) -> u32 {
+        self.percent.map_or(self.total, |percent| (self.total * percent).div_ceil(100))
+    }
+
+    fn met(&self) -> bool {
+        self.is_active() && self.destroyed >= self.required()
+    }
+
+    // 已经达成配额，剩余砖块正在消散或已经消散完
+    pub fn reached(&self) -> bool {
+        self.cleanup_bonus.is_some()
+    }
+}
+
+pub fn quota_active(quota: Res<BrickQuota>) -> bool {
+    quota.is_active()
+}
+
+// 正在消散的砖块：已经去掉 Brick，球和激光不再打到它，也不会掉落道具
+#[derive(Component)]
+pub struct Dissolving {
+    delay: f32,
+    elapsed: f32,
+    started: bool,
+    scale: Vec3,
+}
+
+#[derive(Component)]
+pub struct QuotaBannerText;
+
+// 手工设计的配额关：左上和右上角各有三块坚硬砖块藏在不可破坏的砖块后面，只能从旁边的缺口打进去。
+// 配额留下的余量正好够跳过这两个角落。关卡文件解析失败时使用固定模板，仍然是配额关
+pub fn quota_layout() -> Layout {
+    level_file::load_layout(QUOTA_LEVEL_FILE).unwrap_or_else(|e| {
+        eprintln!("Failed to load quota level: {}", e);
+        Layout {
+            bricks: layout_check::fallback_bricks(QUOTA_LEVEL),
+            quota: Some(QUOTA_PERCENT),
+        }
+    })
+}
+
+// 屏幕上方的配额进度
+pub fn spawn_quota_hud(commands: &mut Commands, fonts: &FontAssets) {
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    width: Val::Percent(100.0),
+                    top: Val::Px(50.0),
+                    justify_content: JustifyContent::Center,
+                    ..default()
+                },
+                ..default()
+            },
+            GameEntity,
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                TextBundle::from_section("", text_style(fonts, FontKind::Bold, 22.0, QUOTA_COLOR)),
+                QuotaBannerText,
+            ));
+        });
+}
+
+// 从砖块事件累计打碎的块数，不用每帧重新数砖块
+pub fn count_destroyed_bricks(mut quota: ResMut<BrickQuota>, mut gameplay_events: EventReader<GameplayEvent>) {
+    let destroyed = gameplay_events
+        .read()
+        .filter(|event| {
+            matches!(event, GameplayEvent::BrickDestroyed { .. } | GameplayEvent::BombDetonated { .. })
+        })
+        .count() as u32;
+    if destroyed > 0 {
+        quota.destroyed = (quota.destroyed + destroyed).min(quota.total);
+    }
+}
+
+// 达成配额时剩余的砖块（包括不可破坏的）从左到右依次消散，可破坏砖块按分数的 CLEANUP_BONUS_PERCENT 给奖励
+pub fn start_dissolve(
+    mut commands: Commands,
+    mut quota: ResMut<BrickQuota>,
+    mut score: ResMut<Score>,
+    curse: Res<Curse>,
+    bricks: Query<(Entity, &Transform, &Brick)>,
+) {
+    if quota.reached() || !quota.met() {
+        return;
+    }
+    let mut remaining: Vec<(Entity, &Transform, &Brick)> = bricks.iter().collect();
+    remaining.sort_by(|a, b| {
+        let (a, b) = (a.1.translation, b.1.translation);
+        a.x.total_cmp(&b.x).then(b.y.total_cmp(&a.y))
+    });
+    let mut value = 0;
+    for (index, (entity, transform, brick)) in remaining.into_iter().enumerate() {
+        if !matches!(brick.brick_type, BrickType::Unbreakable) {
+            value += brick.points();
+        }
+        commands.entity(entity).remove::<(Brick, BombTimer)>().insert(Dissolving {
+            delay: index as f32 * CASCADE_STEP,
+            elapsed: 0.0,
+            started: false,
+            scale: transform.scale,
+        });
+    }
+    let bonus = curse.apply(value * CLEANUP_BONUS_PERCENT / 100);
+    score.0 += bonus;
+    quota.cleanup_bonus = Some(bonus);
+}
+
+// 消散动画：砖块缩小变淡，开始时冒出一小团粒子（按次要特效计算，不是打碎砖块时的爆开）
+pub fn dissolve_bricks(
+    mut commands: Commands,
+    time: Res<Time>,
+    budget: Res<EffectBudget>,
+    mut bricks: Query<(Entity, &mut Dissolving, &mut Transform, &mut Sprite)>,
+) {
+    for (entity, mut dissolving, mut transform, mut sprite) in bricks.iter_mut() {
+        dissolving.elapsed += time.delta_seconds();
+        if dissolving.elapsed < dissolving.delay {
+            continue;
+        }
+        if !dissolving.started {
+            dissolving.started = true;
+            spawn_dissolve_puff(&mut commands, &budget, transform.translation, sprite.color);
+        }
+        let progress = ((dissolving.elapsed - dissolving.delay) / DISSOLVE_TIME).min(1.0);
+        if progress >= 1.0 {
+            commands.safe_despawn_recursive(entity);
+            continue;
+        }
+        transform.scale = dissolving.scale * (1.0 - progress);
+        sprite.color.set_a(1.0 - progress);
+    }
+}
+
+fn spawn_dissolve_puff(commands: &mut Commands, budget: &EffectBudget, position: Vec3, color: Color) {
+    let mut rng = rand::thread_rng();
+
+    for _ in 0..budget.particle_count(EffectKind::Minor, 4) {
+        let velocity = Vec2::new(rng.gen_range(-60.0..60.0), rng.gen_range(20.0..80.0));
+
+        commands.spawn((
+            SpriteBundle {
+                sprite: Sprite {
+                    color: color.with_a(0.6),
+                    ..default()
+                },
+                transform: Transform {
+                    translation: position,
+                    scale: Vec3::new(5.0, 5.0, 1.0),
+                    ..default()
+                },
+                ..default()
+            },
+            Particle {
+                velocity,
+                lifetime: 0.5,
+            },
+            GameEntity,
+        ));
+    }
+}
+
+pub fn update_quota_hud(
+    quota: Res<BrickQuota>,
+    locale: Res<Locale>,
+    mut banners: Query<&mut Text, With<QuotaBannerText>>,
+) {
+    let message = match quota.cleanup_bonus {
+        Some(bonus) => locale.tr("quota.cleared", &[("bonus", &bonus)]),
+        None => locale.tr(
+            "quota.banner",
+            &[
+                ("destroyed", &quota.destroyed),
+                ("total", &quota.total),
+                ("percent", &quota.percent.unwrap_or(100)),
+            ],
+        ),
+    };
+    for mut text in banners.iter_mut() {
+        if text.sections[0].value != message {
+            text.sections[0].value = message.clone();
+        }
+    }
+}
@@ -0,0 +1,878 @@
+use bevy::prelude::*;
+use std::time::{Duration, Instant};
+
+use crate::api::{RaceProgress, RaceProgressRequest, RaceResponse};
+use crate::bonus::Orientation;
+use crate::fonts::{text_style, FontAssets, FontKind};
+use crate::i18n::Locale;
+use crate::level_select::GameMode;
+use crate::network::{ApiCommand, ApiResponse, Network};
+use crate::profile::{self, RunStats};
+use crate::profiles::ProfilePaths;
+use crate::{
+    start_new_run, Difficulty, DifficultySettings, GameEntity, GameState, Level, PlayerName, RunResources,
+    RunSubmitted, Score,
+};
+
+// 比赛使用每日挑战的种子，难度也和每日挑战一样
+const RACE_DIFFICULTY: Difficulty = Difficulty::Medium;
+// 加入码的长度，与服务器一致
+const CODE_LENGTH: usize = 6;
+// 上报进度和轮询比赛状态的间隔（秒）
+const SYNC_INTERVAL: f32 = 2.0;
+// 请求这么久没有返回就当作丢失，允许发下一个
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(6);
+// 对手的进度超过这么久没有更新时提示正在重连
+const STALE_AFTER: Duration = Duration::from_secs(6);
+// 按对手最近的得分速度向前推算的最长时间
+const MAX_EXTRAPOLATION: f32 = 3.0;
+// 比分滚动追上目标的速度
+const TICKER_RATE: f32 = 6.0;
+
+const YOUR_COLOR: Color = Color::rgb(0.3, 0.8, 0.4);
+const OPPONENT_COLOR: Color = Color::rgb(1.0, 0.55, 0.2);
+
+// 对手的进度：保存服务器最近一次返回的数据和它对应的本地时间，
+// 两次请求之间按对手最近的得分速度推算，比分滚动显示，不会因为网络延迟一跳一跳
+#[derive(Debug, Default)]
+struct OpponentTrack {
+    name: Option<String>,
+    score: u32,
+    level: u32,
+    finished: bool,
+    // 对手上报这条进度时的本地时间（扣除了服务器上的等待和一半往返时间）
+    sampled_at: Option<Instant>,
+    // 每秒得分
+    rate: f32,
+    // 比分滚动当前显示的值
+    displayed: f32,
+}
+
+impl OpponentTrack {
+    fn observe(&mut self, progress: &RaceProgress, sampled_at: Instant) {
+        if let Some(previous) = self.sampled_at {
+            let elapsed = sampled_at.saturating_duration_since(previous).as_secs_f32();
+            if progress.score <= self.score {
+                self.rate = 0.0;
+            } else if elapsed > 0.5 {
+                self.rate = (progress.score - self.score) as f32 / elapsed;
+            }
+        }
+        // 乱序返回的旧进度不会让比分倒退
+        self.score = self.score.max(progress.score);
+        self.level = self.level.max(progress.level);
+        self.finished |= progress.finished;
+        self.name = Some(progress.player_name.clone());
+        self.sampled_at = Some(self.sampled_at.map_or(sampled_at, |previous| previous.max(sampled_at)));
+    }
+
+    // 对手此刻大概的分数；已经结束时就是最终分数
+    fn estimate(&self, now: Instant) -> f32 {
+        let Some(sampled_at) = self.sampled_at.filter(|_| !self.finished) else {
+            return self.score as f32;
+        };
+        let ahead = now.saturating_duration_since(sampled_at).as_secs_f32().min(MAX_EXTRAPOLATION);
+        self.score as f32 + self.rate * ahead
+    }
+
+    fn is_stale(&self, now: Instant) -> bool {
+        !self.finished && self.sampled_at.is_some_and(|sampled_at| now.saturating_duration_since(sampled_at) > STALE_AFTER)
+    }
+}
+
+// 正在进行的比赛；没有比赛时 code 为空
+#[derive(Resource, Debug, Default)]
+pub struct RaceSession {
+    code: Option<String>,
+    player_name: String,
+    seed: u64,
+    // 已经开始打了（创建者在等对手加入时还没开始）
+    started: bool,
+    // 已经上报了结束
+    finished: bool,
+    // 服务器最近一次返回的比赛状态
+    state: Option<RaceResponse>,
+    opponent: OpponentTrack,
+    since_sync: f32,
+    // 尚未返回的请求和它的发送时间；超时后不再等待，错过的轮询由下一次补上
+    in_flight: Option<Instant>,
+    // 连续失败的请求数
+    missed: u32,
+    // 最近一次上报的分数和关卡，中途退出时用来上报结束
+    last_reported: (u32, u32),
+}
+
+impl RaceSession {
+    fn begin(&mut self, state: &RaceResponse, player_name: &str) {
+        *self = RaceSession {
+            code: Some(state.id.clone()),
+            player_name: player_name.to_string(),
+            seed: state.seed,
+            ..default()
+        };
+        self.apply(state, Instant::now(), Duration::ZERO);
+    }
+
+    // 记录服务器返回的状态；round_trip 是这次请求的往返时间
+    fn apply(&mut self, state: &RaceResponse, received_at: Instant, round_trip: Duration) {
+        if self.code.as_deref() != Some(state.id.as_str()) {
+            return;
+        }
+        if let Some(progress) = state.players.iter().find(|progress| progress.player_name != self.player_name) {
+            let age = Duration::from_millis(progress.age_ms.max(0) as u64) + round_trip / 2;
+            let sampled_at = received_at.checked_sub(age).unwrap_or(received_at);
+            self.opponent.observe(progress, sampled_at);
+        }
+        self.missed = 0;
+        self.state = Some(state.clone());
+    }
+
+    // 到了同步时间且没有在等待上一个请求时返回 true
+    fn due(&mut self, delta: f32, now: Instant) -> bool {
+        self.since_sync += delta;
+        if self.in_flight.is_some_and(|sent| now.duration_since(sent) < REQUEST_TIMEOUT) {
+            return false;
+        }
+        if self.in_flight.take().is_some() {
+            self.missed += 1;
+        }
+        if self.since_sync < SYNC_INTERVAL {
+            return false;
+        }
+        self.since_sync = 0.0;
+        self.in_flight = Some(now);
+        true
+    }
+
+    fn round_trip(&mut self, now: Instant) -> Duration {
+        self.in_flight.take().map_or(Duration::ZERO, |sent| now.duration_since(sent))
+    }
+
+    fn report(&mut self, network: &Network, score: u32, level: u32, finished: bool) {
+        let Some(code) = self.code.clone() else {
+            return;
+        };
+        self.last_reported = (score, level);
+        network.send(ApiCommand::RaceProgress {
+            code,
+            request: RaceProgressRequest {
+                player_name: self.player_name.clone(),
+                score,
+                level,
+                finished,
+            },
+        });
+    }
+
+    // 服务器已经记下了自己的结束
+    fn reported_finished(&self) -> bool {
+        self.state.as_ref().is_some_and(|state| {
+            state
+                .players
+                .iter()
+                .any(|progress| progress.player_name == self.player_name && progress.finished)
+        })
+    }
+
+    fn opponent_joined(&self) -> bool {
+        self.state.as_ref().is_some_and(|state| state.players.len() > 1)
+    }
+}
+
+// 开始比赛的一局：难度固定，种子由服务器给出，双方完全相同
+fn start_race_run(run: &mut RunResources, session: &mut RaceSession) {
+    *run.difficulty_settings = DifficultySettings::new(RACE_DIFFICULTY);
+    start_new_run(run, Some(session.seed));
+    *run.game_mode = GameMode::Race;
+    session.started = true;
+    session.since_sync = 0.0;
+}
+
+pub fn race_active(session: Res<RaceSession>, game_mode: Res<GameMode>) -> bool {
+    session.started && *game_mode == GameMode::Race
+}
+
+// 大厅：创建比赛后等待对手，或输入朋友的加入码
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+enum LobbyStage {
+    #[default]
+    Choose,
+    Creating,
+    Waiting,
+    EnterCode,
+    Joining,
+}
+
+#[derive(Resource, Debug, Default)]
+pub struct RaceLobby {
+    stage: LobbyStage,
+    code_input: String,
+    error: Option<String>,
+}
+
+#[derive(Component)]
+pub struct RaceUI;
+
+#[derive(Component)]
+pub struct RaceLobbyText;
+
+pub fn setup_race_lobby(
+    mut commands: Commands,
+    fonts: Res<FontAssets>,
+    locale: Res<Locale>,
+    mut lobby: ResMut<RaceLobby>,
+    mut session: ResMut<RaceSession>,
+) {
+    *lobby = RaceLobby::default();
+    *session = RaceSession::default();
+
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    width: Val::Percent(100.0),
+                    height: Val::Percent(100.0),
+                    flex_direction: FlexDirection::Column,
+                    justify_content: JustifyContent::Center,
+                    align_items: AlignItems::Center,
+                    ..default()
+                },
+                background_color: BackgroundColor(Color::rgb(0.1, 0.1, 0.15)),
+                ..default()
+            },
+            RaceUI,
+        ))
+        .with_children(|parent| {
+            parent.spawn(TextBundle::from_section(
+                locale.tr("race.title", &[]),
+                text_style(&fonts, FontKind::Bold, 60.0, Color::WHITE),
+            ));
+
+            parent.spawn(TextBundle::from_section(
+                locale.tr("race.rules", &[]),
+                text_style(&fonts, FontKind::Regular, 20.0, Color::rgb(0.5, 0.7, 0.9)),
+            ).with_style(Style {
+                margin: UiRect::top(Val::Px(10.0)),
+                ..default()
+            }));
+
+            parent.spawn((
+                TextBundle::from_section("", text_style(&fonts, FontKind::Regular, 26.0, Color::WHITE))
+                    .with_text_justify(JustifyText::Center)
+                    .with_style(Style {
+                        margin: UiRect::top(Val::Px(40.0)),
+                        ..default()
+                    }),
+                RaceLobbyText,
+            ));
+        });
+}
+
+fn lobby_text(locale: &Locale, lobby: &RaceLobby, session: &RaceSession) -> String {
+    let code = session.code.as_deref().unwrap_or("");
+    let mut text = match lobby.stage {
+        LobbyStage::Choose => locale.tr("race.choose", &[]),
+        LobbyStage::Creating => locale.tr("race.creating", &[]),
+        LobbyStage::Waiting => locale.tr("race.waiting", &[("code", &code)]),
+        LobbyStage::EnterCode => {
+            let typed = format!("{:_<width$}", lobby.code_input, width = CODE_LENGTH);
+            locale.tr("race.enter_code", &[("code", &typed)])
+        }
+        LobbyStage::Joining => locale.tr("race.joining", &[("code", &lobby.code_input)]),
+    };
+    if let Some(error) = &lobby.error {
+        text = format!("{}\n\n{}", locale.tr("race.failed", &[("error", error)]), text);
+    }
+    text
+}
+
+pub fn race_lobby_system(
+    time: Res<Time>,
+    keyboard: Res<ButtonInput<KeyCode>>,
+    mut char_events: EventReader<ReceivedCharacter>,
+    locale: Res<Locale>,
+    network: Res<Network>,
+    player_name: Res<PlayerName>,
+    mut lobby: ResMut<RaceLobby>,
+    mut session: ResMut<RaceSession>,
+    mut next_state: ResMut<NextState<GameState>>,
+    mut texts: Query<&mut Text, With<RaceLobbyText>>,
+) {
+    let typed: Vec<char> = char_events.read().flat_map(|event| event.char.chars()).collect();
+    match lobby.stage {
+        LobbyStage::Choose => {
+            if keyboard.just_pressed(KeyCode::Escape) {
+                next_state.set(GameState::MainMenu);
+            } else if keyboard.just_pressed(KeyCode::KeyC) {
+                lobby.stage = LobbyStage::Creating;
+                lobby.error = None;
+                network.send(ApiCommand::CreateRace {
+                    player_name: player_name.0.clone(),
+                });
+            } else if keyboard.just_pressed(KeyCode::KeyJ) {
+                lobby.stage = LobbyStage::EnterCode;
+                lobby.code_input.clear();
+                lobby.error = None;
+            }
+        }
+        LobbyStage::EnterCode => {
+            for ch in typed {
+                if ch.is_ascii_alphanumeric() && lobby.code_input.len() < CODE_LENGTH {
+                    lobby.code_input.push(ch.to_ascii_uppercase());
+                }
+            }
+            if keyboard.just_pressed(KeyCode::Backspace) {
+                lobby.code_input.pop();
+            }
+            if keyboard.just_pressed(KeyCode::Escape) {
+                lobby.stage = LobbyStage::Choose;
+            } else if keyboard.just_pressed(KeyCode::Enter) && lobby.code_input.len() == CODE_LENGTH {
+                lobby.stage = LobbyStage::Joining;
+                lobby.error = None;
+                network.send(ApiCommand::JoinRace {
+                    code: lobby.code_input.clone(),
+                    player_name: player_name.0.clone(),
+                });
+            }
+        }
+        LobbyStage::Waiting => {
+            // 放弃等待：比赛留在服务器上，没人加入时由服务器清理
+            if keyboard.just_pressed(KeyCode::Escape) {
+                *session = RaceSession::default();
+                lobby.stage = LobbyStage::Choose;
+            } else if let Some(code) = session.code.clone() {
+                if session.due(time.delta_seconds(), Instant::now()) {
+                    network.send(ApiCommand::GetRace { code });
+                }
+            }
+        }
+        // 等待服务器响应
+        LobbyStage::Creating | LobbyStage::Joining => {}
+    }
+
+    let message = lobby_text(&locale, &lobby, &session);
+    for mut text in texts.iter_mut() {
+        if text.sections[0].value != message {
+            text.sections[0].value = message.clone();
+        }
+    }
+}
+
+// 创建或加入成功后等对手到齐，到齐后双方同时开始
+pub fn race_lobby_responses(
+    mut responses: EventReader<ApiResponse>,
+    player_name: Res<PlayerName>,
+    mut lobby: ResMut<RaceLobby>,
+    mut session: ResMut<RaceSession>,
+    mut next_state: ResMut<NextState<GameState>>,
+    mut run: RunResources,
+) {
+    for response in responses.read() {
+        let result = match (response, lobby.stage) {
+            (ApiResponse::RaceCreated(result), LobbyStage::Creating)
+            | (ApiResponse::RaceJoined(result), LobbyStage::Joining) => result,
+            (ApiResponse::Race(Ok(state)), LobbyStage::Waiting) => {
+                let now = Instant::now();
+                let round_trip = session.round_trip(now);
+                session.apply(state, now, round_trip);
+                if session.opponent_joined() {
+                    start_race_run(&mut run, &mut session);
+                    next_state.set(GameState::Playing);
+                }
+                continue;
+            }
+            (ApiResponse::Race(Err(e)), LobbyStage::Waiting) => {
+                eprintln!("Failed to poll race: {}", e);
+                session.in_flight = None;
+                session.missed += 1;
+                continue;
+            }
+            _ => continue,
+        };
+        match result {
+            Ok(state) => {
+                session.begin(state, &player_name.0);
+                if lobby.stage == LobbyStage::Joining && session.opponent_joined() {
+                    start_race_run(&mut run, &mut session);
+                    next_state.set(GameState::Playing);
+                } else {
+                    lobby.stage = LobbyStage::Waiting;
+                }
+            }
+            Err(e) => {
+                eprintln!("Race request failed: {}", e);
+                lobby.error = Some(e.clone());
+                lobby.stage = LobbyStage::Choose;
+            }
+        }
+    }
+}
+
+pub fn cleanup_race_ui(mut commands: Commands, query: Query<Entity, With<RaceUI>>) {
+    for entity in query.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+// 比赛中每隔一段时间上报自己的进度；上一个请求还没返回时跳过这一次
+pub fn sync_race_progress(
+    time: Res<Time>,
+    network: Res<Network>,
+    score: Res<Score>,
+    level: Res<Level>,
+    mut session: ResMut<RaceSession>,
+) {
+    if session.finished || !session.due(time.delta_seconds(), Instant::now()) {
+        return;
+    }
+    session.report(&network, score.0, level.0, false);
+}
+
+// 任何界面收到的比赛进度都记录下来，失败只计数，下一次同步再试
+pub fn receive_race_progress(mut responses: EventReader<ApiResponse>, mut session: ResMut<RaceSession>) {
+    for response in responses.read() {
+        let ApiResponse::RaceProgress(result) = response else {
+            continue;
+        };
+        let now = Instant::now();
+        let round_trip = session.round_trip(now);
+        match result {
+            Ok(state) => session.apply(state, now, round_trip),
+            Err(e) => {
+                eprintln!("Failed to sync race progress: {}", e);
+                session.missed += 1;
+            }
+        }
+    }
+}
+
+// 对手的比分和双方的进度条
+#[derive(Component)]
+pub struct RaceHud;
+
+#[derive(Component)]
+pub struct RaceTickerText;
+
+#[derive(Component)]
+pub struct RaceBar {
+    opponent: bool,
+}
+
+// 每一关开始时在状态栏下方生成；从暂停返回时还在，不重复生成
+pub fn spawn_race_hud(
+    mut commands: Commands,
+    fonts: Res<FontAssets>,
+    orientation: Res<Orientation>,
+    huds: Query<(), With<RaceHud>>,
+) {
+    if !huds.is_empty() {
+        return;
+    }
+    let edge = Val::Px(48.0);
+    let (top, bottom) = match *orientation {
+        Orientation::Normal => (edge, Val::Auto),
+        Orientation::Flipped => (Val::Auto, edge),
+    };
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    left: Val::Px(10.0),
+                    top,
+                    bottom,
+                    width: Val::Px(220.0),
+                    flex_direction: FlexDirection::Column,
+                    row_gap: Val::Px(3.0),
+                    ..default()
+                },
+                ..default()
+            },
+            RaceHud,
+            GameEntity,
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                TextBundle::from_section("", text_style(&fonts, FontKind::Mono, 18.0, OPPONENT_COLOR)),
+                RaceTickerText,
+            ));
+            for (opponent, color) in [(false, YOUR_COLOR), (true, OPPONENT_COLOR)] {
+                parent
+                    .spawn(NodeBundle {
+                        style: Style {
+                            width: Val::Percent(100.0),
+                            height: Val::Px(4.0),
+                            ..default()
+                        },
+                        background_color: BackgroundColor(Color::rgba(1.0, 1.0, 1.0, 0.15)),
+                        ..default()
+                    })
+                    .with_children(|parent| {
+                        parent.spawn((
+                            NodeBundle {
+                                style: Style {
+                                    width: Val::Percent(0.0),
+                                    height: Val::Percent(100.0),
+                                    ..default()
+                                },
+                                background_color: BackgroundColor(color),
+                                ..default()
+                            },
+                            RaceBar { opponent },
+                        ));
+                    });
+            }
+        });
+}
+
+// 对手比分向推算值滚动（只增不减），进度条按双方中较高的分数缩放
+pub fn update_race_hud(
+    time: Res<Time>,
+    locale: Res<Locale>,
+    score: Res<Score>,
+    mut session: ResMut<RaceSession>,
+    mut tickers: Query<&mut Text, With<RaceTickerText>>,
+    mut bars: Query<(&RaceBar, &mut Style)>,
+) {
+    let now = Instant::now();
+    let session = &mut *session;
+    let target = session.opponent.estimate(now);
+    let track = &mut session.opponent;
+    let blend = 1.0 - (-TICKER_RATE * time.delta_seconds()).exp();
+    track.displayed = (track.displayed + (target - track.displayed) * blend).max(track.displayed);
+    let opponent_score = track.displayed.round() as u32;
+
+    let message = match &track.name {
+        None => locale.tr("race.hud_waiting", &[]),
+        Some(name) => {
+            let key = if track.finished {
+                "race.hud_finished"
+            } else if track.is_stale(now) || session.missed >= 2 {
+                "race.hud_stale"
+            } else {
+                "race.hud"
+            };
+            let diff = score.0 as i64 - opponent_score as i64;
+            locale.tr(
+                key,
+                &[
+                    ("name", name),
+                    ("level", &track.level),
+                    ("score", &opponent_score),
+                    ("diff", &format!("{:+}", diff)),
+                ],
+            )
+        }
+    };
+    for mut text in tickers.iter_mut() {
+        if text.sections[0].value != message {
+            text.sections[0].value = message.clone();
+        }
+    }
+
+    let top = score.0.max(opponent_score).max(1) as f32;
+    for (bar, mut style) in bars.iter_mut() {
+        let value = if bar.opponent { opponent_score } else { score.0 };
+        style.width = Val::Percent(value as f32 / top * 100.0);
+    }
+}
+
+// 丢掉最后一条命或时间用完：上报最终成绩并记入档案
+pub fn finish_race(
+    network: Res<Network>,
+    paths: Res<ProfilePaths>,
+    score: Res<Score>,
+    level: Res<Level>,
+    stats: Res<RunStats>,
+    mut run_submitted: ResMut<RunSubmitted>,
+    mut session: ResMut<RaceSession>,
+) {
+    if session.code.is_none() || session.finished {
+        return;
+    }
+    session.finished = true;
+    session.in_flight = Some(Instant::now());
+    session.since_sync = 0.0;
+    session.report(&network, score.0, level.0, true);
+    if !run_submitted.0 {
+        run_submitted.0 = true;
+        profile::record_run(&paths, &stats, score.0, level.0, RACE_DIFFICULTY);
+    }
+}
+
+#[derive(Component)]
+pub struct RaceResultText;
+
+pub fn setup_race_result(mut commands: Commands, fonts: Res<FontAssets>, locale: Res<Locale>) {
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    width: Val::Percent(100.0),
+                    height: Val::Percent(100.0),
+                    flex_direction: FlexDirection::Column,
+                    justify_content: JustifyContent::Center,
+                    align_items: AlignItems::Center,
+                    ..default()
+                },
+                background_color: BackgroundColor(Color::rgb(0.1, 0.1, 0.15)),
+                ..default()
+            },
+            RaceUI,
+        ))
+        .with_children(|parent| {
+            parent.spawn(TextBundle::from_section(
+                locale.tr("race.result_title", &[]),
+                text_style(&fonts, FontKind::Bold, 60.0, Color::WHITE),
+            ));
+
+            parent.spawn((
+                TextBundle::from_section("", text_style(&fonts, FontKind::Mono, 26.0, Color::WHITE))
+                    .with_text_justify(JustifyText::Center)
+                    .with_style(Style {
+                        margin: UiRect::top(Val::Px(30.0)),
+                        ..default()
+                    }),
+                RaceResultText,
+            ));
+
+            parent.spawn(TextBundle::from_section(
+                locale.tr("race.result_hint", &[]),
+                text_style(&fonts, FontKind::Regular, 20.0, Color::rgb(0.6, 0.6, 0.6)),
+            ).with_style(Style {
+                margin: UiRect::top(Val::Px(40.0)),
+                ..default()
+            }));
+        });
+}
+
+fn result_row(locale: &Locale, progress: &RaceProgress) -> String {
+    locale.tr(
+        "race.result_row",
+        &[
+            ("name", &format!("{:<20}", progress.player_name)),
+            ("level", &format!("{:<3}", progress.level)),
+            ("score", &format!("{:>8}", progress.score)),
+        ],
+    )
+}
+
+fn result_text(locale: &Locale, session: &RaceSession) -> String {
+    let Some(state) = &session.state else {
+        return locale.tr("race.offline", &[]);
+    };
+    let mut lines: Vec<String> = state.players.iter().map(|progress| result_row(locale, progress)).collect();
+    let opponent = state.players.iter().find(|progress| progress.player_name != session.player_name);
+    let verdict = match opponent {
+        None => locale.tr("race.no_opponent", &[]),
+        Some(opponent) if !state.finished => locale.tr("race.waiting_finish", &[("name", &opponent.player_name)]),
+        Some(opponent) => match &state.winner {
+            None => locale.tr("race.tie", &[]),
+            Some(winner) if *winner == session.player_name => locale.tr("race.win", &[]),
+            Some(_) => locale.tr("race.lose", &[("name", &opponent.player_name)]),
+        },
+    };
+    lines.push(String::new());
+    lines.push(verdict);
+    if session.missed >= 2 {
+        lines.push(locale.tr("race.offline", &[]));
+    }
+    lines.join("\n")
+}
+
+// 对手还没结束时继续轮询，结束后显示胜负；ENTER 或 ESC 回到主菜单
+pub fn race_result_system(
+    time: Res<Time>,
+    keyboard: Res<ButtonInput<KeyCode>>,
+    locale: Res<Locale>,
+    network: Res<Network>,
+    mut responses: EventReader<ApiResponse>,
+    mut session: ResMut<RaceSession>,
+    mut next_state: ResMut<NextState<GameState>>,
+    mut run: RunResources,
+    mut texts: Query<&mut Text, With<RaceResultText>>,
+) {
+    for response in responses.read() {
+        let ApiResponse::Race(result) = response else {
+            continue;
+        };
+        let now = Instant::now();
+        let round_trip = session.round_trip(now);
+        match result {
+            Ok(state) => session.apply(state, now, round_trip),
+            Err(e) => {
+                eprintln!("Failed to poll race: {}", e);
+                session.missed += 1;
+            }
+        }
+    }
+
+    // 结束的上报丢失时重新上报，否则对手会一直等下去
+    let done = session.state.as_ref().is_some_and(|state| state.finished);
+    if !done && session.due(time.delta_seconds(), Instant::now()) {
+        if session.reported_finished() {
+            if let Some(code) = session.code.clone() {
+                network.send(ApiCommand::GetRace { code });
+            }
+        } else {
+            let (score, level) = session.last_reported;
+            session.report(&network, score, level, true);
+        }
+    }
+
+    let message = result_text(&locale, &session);
+    for mut text in texts.iter_mut() {
+        if text.sections[0].value != message {
+            text.sections[0].value = message.clone();
+        }
+    }
+
+    if keyboard.any_just_pressed([KeyCode::Enter, KeyCode::Escape]) {
+        start_new_run(&mut run, None);
+        *session = RaceSession::default();
+        next_state.set(GameState::MainMenu);
+    }
+}
+
+// 从暂停菜单回到主菜单时放弃比赛：以最后一次上报的成绩结束，对手不用一直等下去
+pub fn forfeit_race(network: Res<Network>, mut session: ResMut<RaceSession>) {
+    if session.started && !session.finished {
+        let (score, level) = session.last_reported;
+        session.report(&network, score, level, true);
+    }
+    *session = RaceSession::default();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn progress(player_name: &str, score: u32, finished: bool, age_ms: i64) -> RaceProgress {
+        RaceProgress {
+            player_name: player_name.to_string(),
+            score,
+            level: 1,
+            finished,
+            updated_at: String::new(),
+            age_ms,
+        }
+    }
+
+    fn race(players: Vec<RaceProgress>) -> RaceResponse {
+        RaceResponse {
+            id: "ABC234".to_string(),
+            seed: 42,
+            daily_date: "2025-06-01".to_string(),
+            created_at: String::new(),
+            finished: false,
+            winner: None,
+            players,
+        }
+    }
+
+    fn session(players: Vec<RaceProgress>) -> RaceSession {
+        let mut session = RaceSession::default();
+        session.begin(&race(players), "ME");
+        session
+    }
+
+    #[test]
+    fn the_opponent_score_is_extrapolated_from_their_recent_rate() {
+        let start = Instant::now();
+        let mut track = OpponentTrack::default();
+        track.observe(&progress("YOU", 100, false, 0), start);
+        assert_eq!(track.estimate(start + Duration::from_secs(1)), 100.0, "one sample has no rate yet");
+
+        track.observe(&progress("YOU", 300, false, 0), start + Duration::from_secs(2));
+        assert_eq!(track.rate, 100.0);
+        assert_eq!(track.estimate(start + Duration::from_secs(3)), 400.0);
+        // 推算的时间有上限，断线时不会一直涨
+        let far = start + Duration::from_secs(60);
+        assert_eq!(track.estimate(far), 300.0 + 100.0 * MAX_EXTRAPOLATION);
+
+        // 结束后就是最终分数
+        track.observe(&progress("YOU", 350, true, 0), start + Duration::from_secs(3));
+        assert_eq!(track.estimate(far), 350.0);
+    }
+
+    #[test]
+    fn late_or_flat_samples_never_move_the_score_backwards() {
+        let start = Instant::now();
+        let mut track = OpponentTrack::default();
+        track.observe(&progress("YOU", 100, false, 0), start);
+        track.observe(&progress("YOU", 300, false, 0), start + Duration::from_secs(2));
+        // 乱序到达的旧进度：分数不倒退，采样时间也不倒退，速度归零
+        track.observe(&progress("YOU", 200, false, 0), start + Duration::from_secs(1));
+        assert_eq!((track.score, track.rate), (300, 0.0));
+        assert_eq!(track.sampled_at, Some(start + Duration::from_secs(2)));
+        assert_eq!(track.estimate(start + Duration::from_secs(3)), 300.0);
+    }
+
+    #[test]
+    fn a_silent_opponent_goes_stale_unless_finished() {
+        let start = Instant::now();
+        let mut track = OpponentTrack::default();
+        assert!(!track.is_stale(start + STALE_AFTER * 2), "no opponent yet");
+        track.observe(&progress("YOU", 100, false, 0), start);
+        assert!(!track.is_stale(start + STALE_AFTER));
+        assert!(track.is_stale(start + STALE_AFTER + Duration::from_millis(1)));
+        track.observe(&progress("YOU", 100, true, 0), start);
+        assert!(!track.is_stale(start + STALE_AFTER * 2));
+    }
+
+    #[test]
+    fn samples_are_dated_by_server_age_and_half_the_round_trip() {
+        let mut session = session(vec![progress("ME", 0, false, 0)]);
+        assert!(!session.opponent_joined());
+
+        let received_at = Instant::now() + Duration::from_secs(10);
+        let state = race(vec![progress("ME", 50, false, 0), progress("YOU", 80, false, 1000)]);
+        session.missed = 2;
+        session.apply(&state, received_at, Duration::from_millis(400));
+        assert_eq!(session.opponent.sampled_at, Some(received_at - Duration::from_millis(1200)));
+        assert_eq!((session.opponent.name.as_deref(), session.opponent.score), (Some("YOU"), 80));
+        assert_eq!(session.missed, 0);
+        assert!(session.opponent_joined());
+
+        // 其他比赛的返回不影响这一场
+        let mut other = race(vec![progress("YOU", 999, true, 0)]);
+        other.id = "ZZZ999".to_string();
+        session.apply(&other, received_at, Duration::ZERO);
+        assert_eq!(session.opponent.score, 80);
+    }
+
+    #[test]
+    fn syncs_wait_for_the_interval_and_skip_lost_requests() {
+        let mut session = session(vec![progress("ME", 0, false, 0)]);
+        let start = Instant::now();
+        assert!(!session.due(SYNC_INTERVAL / 2.0, start));
+        assert!(session.due(SYNC_INTERVAL / 2.0, start));
+
+        // 上一个请求还没返回：到了时间也不再发
+        let mut now = start;
+        for _ in 0..4 {
+            now += Duration::from_secs(1);
+            assert!(!session.due(1.0, now));
+        }
+        // 超时后算作丢失一次，直接发下一个
+        now = start + REQUEST_TIMEOUT;
+        assert!(session.due(1.0, now));
+        assert_eq!(session.missed, 1);
+
+        // 正常返回后按往返时间清除等待
+        let returned = now + Duration::from_millis(300);
+        assert_eq!(session.round_trip(returned), Duration::from_millis(300));
+        assert_eq!(session.round_trip(returned), Duration::ZERO);
+        assert!(!session.due(0.1, returned));
+        assert!(session.due(SYNC_INTERVAL, returned));
+    }
+
+    #[test]
+    fn only_our_own_finish_counts_as_reported() {
+        let mut session = session(vec![progress("ME", 10, false, 0), progress("YOU", 20, true, 0)]);
+        assert!(!session.reported_finished());
+        let state = race(vec![progress("ME", 10, true, 0), progress("YOU", 20, true, 0)]);
+        session.apply(&state, Instant::now(), Duration::ZERO);
+        assert!(session.reported_finished());
+    }
+}
@@ -0,0 +1,251 @@
+use bevy::prelude::*;
+use std::collections::HashMap;
+
+use crate::api::Score;
+use crate::fonts::{text_style, FontAssets, FontKind};
+use crate::i18n::Locale;
+
+// 排名变化提示显示的总时长，以及最后淡出的时长（秒）
+const INDICATOR_DURATION: f32 = 5.0;
+const INDICATOR_FADE: f32 = 1.5;
+
+const RANK_UP_COLOR: Color = Color::rgb(0.2, 0.85, 0.3);
+const RANK_DOWN_COLOR: Color = Color::rgb(0.9, 0.25, 0.25);
+const NEW_ENTRY_COLOR: Color = Color::rgb(1.0, 0.85, 0.0);
+
+// 与上次看到的同一榜单相比的排名变化
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RankMovement {
+    Up(u32),
+    Down(u32),
+    New,
+}
+
+// 某个榜单上次看到的排名：分数标识 -> 排名，depth 为已加载到的最大排名
+#[derive(Debug, Clone, Default)]
+pub struct RankSnapshot {
+    ranks: HashMap<String, u32>,
+    depth: u32,
+}
+
+impl RankSnapshot {
+    fn record(&mut self, scores: &[Score]) {
+        for score in scores {
+            if let Some(rank) = score.rank {
+                self.ranks.insert(score_key(score), rank);
+                self.depth = self.depth.max(rank);
+            }
+        }
+    }
+}
+
+// 用于比较的分数标识；精简格式的排行榜没有 id，用名字、分数和关卡代替
+pub fn score_key(score: &Score) -> String {
+    match &score.id {
+        Some(id) => id.clone(),
+        None => format!("{}|{}|{}", score.player_name, score.score, score.level),
+    }
+}
+
+// 比较新加载的行和上次的快照。两边都有的行比较排名；
+// 只有排在上次已加载范围内的新行才算新上榜，更深的行上次根本没加载过，不做标记。
+// 上次有、这次没有的行直接忽略
+pub fn diff_ranks(previous: &RankSnapshot, scores: &[Score]) -> HashMap<String, RankMovement> {
+    let mut movements = HashMap::new();
+    for score in scores {
+        let Some(rank) = score.rank else {
+            continue;
+        };
+        let key = score_key(score);
+        let movement = match previous.ranks.get(&key) {
+            Some(old) if *old > rank => RankMovement::Up(old - rank),
+            Some(old) if *old < rank => RankMovement::Down(rank - old),
+            Some(_) => continue,
+            None if rank <= previous.depth => RankMovement::New,
+            None => continue,
+        };
+        movements.insert(key, movement);
+    }
+    movements
+}
+
+// 本次运行中看过的榜单快照，按（难度, 赛季）区分，不写入磁盘
+#[derive(Resource, Default)]
+pub struct LeaderboardHistory {
+    boards: HashMap<(String, String), RankSnapshot>,
+    // 当前榜单刷新前的快照，翻页时继续用它比较
+    baseline: Option<RankSnapshot>,
+    // 当前显示的排名变化和已显示的秒数
+    movements: HashMap<String, (RankMovement, f32)>,
+}
+
+impl LeaderboardHistory {
+    // 榜单第一页加载完成：与上次的快照比较，并开始记录新的快照
+    pub fn refreshed(&mut self, key: (String, String), scores: &[Score]) {
+        let baseline = self.boards.remove(&key);
+        self.movements.clear();
+        if let Some(baseline) = &baseline {
+            self.show(diff_ranks(baseline, scores));
+        }
+        let mut snapshot = RankSnapshot::default();
+        snapshot.record(scores);
+        self.boards.insert(key, snapshot);
+        self.baseline = baseline;
+    }
+
+    // 加载了下一页：只和刷新前的快照比较这一页的行
+    pub fn page_loaded(&mut self, key: (String, String), scores: &[Score]) {
+        if let Some(baseline) = &self.baseline {
+            self.show(diff_ranks(baseline, scores));
+        }
+        self.boards.entry(key).or_default().record(scores);
+    }
+
+    fn show(&mut self, movements: HashMap<String, RankMovement>) {
+        self.movements
+            .extend(movements.into_iter().map(|(id, movement)| (id, (movement, 0.0))));
+    }
+
+    pub fn movement(&self, score: &Score) -> Option<RankMovement> {
+        self.movements.get(&score_key(score)).map(|(movement, _)| *movement)
+    }
+}
+
+// 排名文字后面的变化提示，保存对应的分数标识
+#[derive(Component)]
+pub struct RankIndicator(pub String);
+
+// 排名变化提示的文字段，附在排名文字后面
+pub fn indicator_section(fonts: &FontAssets, locale: &Locale, movement: RankMovement) -> TextSection {
+    let (text, color) = match movement {
+        RankMovement::Up(delta) => (format!(" ▲{}", delta), RANK_UP_COLOR),
+        RankMovement::Down(delta) => (format!(" ▼{}", delta), RANK_DOWN_COLOR),
+        RankMovement::New => (format!(" {}", locale.tr("leaderboard.new_entry", &[])), NEW_ENTRY_COLOR),
+    };
+    TextSection::new(text, text_style(fonts, FontKind::Bold, 16.0, color))
+}
+
+// 计时并淡出排名变化提示，到时间后去掉
+pub fn fade_rank_indicators(
+    time: Res<Time>,
+    mut history: ResMut<LeaderboardHistory>,
+    mut query: Query<(&RankIndicator, &mut Text)>,
+) {
+    if history.movements.is_empty() {
+        return;
+    }
+    let delta = time.delta_seconds();
+    for (_, elapsed) in history.movements.values_mut() {
+        *elapsed += delta;
+    }
+    history.movements.retain(|_, (_, elapsed)| *elapsed < INDICATOR_DURATION);
+
+    for (indicator, mut text) in query.iter_mut() {
+        if text.sections.len() < 2 {
+            continue;
+        }
+        match history.movements.get(&indicator.0) {
+            Some((_, elapsed)) => {
+                let alpha = ((INDICATOR_DURATION - elapsed) / INDICATOR_FADE).min(1.0);
+                text.sections[1].style.color.set_a(alpha);
+            }
+            None => {
+                text.sections.truncate(1);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn score(id: &str, points: u32, rank: u32) -> Score {
+        Score {
+            id: Some(id.to_string()),
+            player_name: id.to_uppercase(),
+            score: points,
+            level: 1,
+            difficulty: "Medium".to_string(),
+            created_at: None,
+            rank: Some(rank),
+            season: None,
+            mode: None,
+            share_url: None,
+        }
+    }
+
+    fn snapshot(scores: &[Score]) -> RankSnapshot {
+        let mut snapshot = RankSnapshot::default();
+        snapshot.record(scores);
+        snapshot
+    }
+
+    fn key() -> (String, String) {
+        ("Medium".to_string(), "2025-06".to_string())
+    }
+
+    #[test]
+    fn moved_rows_get_the_rank_delta() {
+        let before = snapshot(&[score("a", 900, 1), score("b", 800, 2), score("c", 700, 3)]);
+        let movements = diff_ranks(&before, &[score("c", 950, 1), score("a", 900, 2), score("b", 800, 3)]);
+        assert_eq!(movements["c"], RankMovement::Up(2));
+        assert_eq!(movements["a"], RankMovement::Down(1));
+        assert_eq!(movements["b"], RankMovement::Down(1));
+    }
+
+    #[test]
+    fn tied_rows_keeping_their_shared_rank_do_not_move() {
+        let before = snapshot(&[score("a", 900, 1), score("b", 800, 2), score("c", 800, 2), score("d", 700, 4)]);
+        // d 追平 b 和 c，三人并列第二；b、c 的排名没变
+        let movements = diff_ranks(
+            &before,
+            &[score("a", 900, 1), score("b", 800, 2), score("c", 800, 2), score("d", 800, 2)],
+        );
+        assert_eq!(movements.len(), 1);
+        assert_eq!(movements["d"], RankMovement::Up(2));
+    }
+
+    #[test]
+    fn removed_rows_are_ignored_and_the_rest_move_up() {
+        let before = snapshot(&[score("a", 900, 1), score("b", 800, 2), score("c", 700, 3)]);
+        let movements = diff_ranks(&before, &[score("b", 800, 1), score("c", 700, 2)]);
+        assert!(!movements.contains_key("a"));
+        assert_eq!(movements["b"], RankMovement::Up(1));
+        assert_eq!(movements["c"], RankMovement::Up(1));
+    }
+
+    #[test]
+    fn new_rows_are_marked_only_within_the_loaded_depth() {
+        let before = snapshot(&[score("a", 900, 1), score("b", 800, 2)]);
+        let movements = diff_ranks(&before, &[score("n", 850, 2), score("b", 800, 3), score("deep", 10, 40)]);
+        assert_eq!(movements["n"], RankMovement::New);
+        assert_eq!(movements["b"], RankMovement::Down(1));
+        assert!(!movements.contains_key("deep"));
+    }
+
+    #[test]
+    fn compact_rows_are_matched_by_name_score_and_level() {
+        let mut compact = score("a", 900, 1);
+        compact.id = None;
+        let before = snapshot(&[compact.clone()]);
+        assert!(diff_ranks(&before, &[compact.clone()]).is_empty());
+        assert_eq!(score_key(&compact), "A|900|1");
+    }
+
+    #[test]
+    fn next_page_is_compared_with_the_snapshot_before_the_refresh() {
+        let mut history = LeaderboardHistory::default();
+        history.refreshed(key(), &[score("a", 900, 1), score("b", 800, 2)]);
+        history.page_loaded(key(), &[score("c", 700, 3), score("d", 600, 4)]);
+        // 第一次看这个榜单，没有可比较的快照
+        assert_eq!(history.movement(&score("a", 900, 1)), None);
+
+        history.refreshed(key(), &[score("d", 950, 1), score("a", 900, 2)]);
+        assert_eq!(history.movement(&score("d", 950, 1)), Some(RankMovement::Up(3)));
+        assert_eq!(history.movement(&score("a", 900, 2)), Some(RankMovement::Down(1)));
+        history.page_loaded(key(), &[score("b", 800, 3), score("c", 700, 4)]);
+        assert_eq!(history.movement(&score("b", 800, 3)), Some(RankMovement::Down(1)));
+        assert_eq!(history.movement(&score("c", 700, 4)), Some(RankMovement::Down(1)));
+    }
+}
@@ -0,0 +1,347 @@
+use bevy::prelude::*;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use std::ops::Range;
+
+use crate::api::Score;
+use crate::fonts::{text_style, FontAssets, FontKind};
+use crate::i18n::Locale;
+use crate::level_select::LEVEL_MODE;
+use crate::network::ApiResponse;
+use crate::profile::Profile;
+use crate::profiles::ProfilePaths;
+use crate::{GameState, Settings};
+
+// 排行榜第一页的名次数
+const FIRST_PAGE: u32 = 10;
+// 名次从旧的最好名次数到新名次所用的时间（秒）
+const COUNT_DURATION: f32 = 1.2;
+// 进入第一页时的庆祝火花数量
+const SPARK_COUNT: usize = 24;
+// 主菜单提示的显示时长和最后淡出的时长（秒）
+const TOAST_DURATION: f32 = 6.0;
+const TOAST_FADE: f32 = 1.0;
+
+const TOP_PAGE_COLOR: Color = Color::rgb(1.0, 0.85, 0.2);
+const RANK_UP_COLOR: Color = Color::rgb(0.2, 0.85, 0.3);
+
+// 一次提交在榜单上的名次，以及提交前本机记录的最好名次
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RankResult {
+    pub rank: u32,
+    pub previous_best: Option<u32>,
+}
+
+impl RankResult {
+    // 这次第一次进入第一页
+    fn entered_first_page(&self) -> bool {
+        self.rank <= FIRST_PAGE && self.previous_best.is_none_or(|best| best > FIRST_PAGE)
+    }
+
+    // 名次比以前的最好名次更高
+    fn improved(&self) -> bool {
+        self.previous_best.is_some_and(|best| self.rank < best)
+    }
+
+    // shown 为动画中当前显示的名次
+    fn message(&self, locale: &Locale, shown: u32) -> String {
+        if self.entered_first_page() {
+            return locale.tr("rank.first_page", &[("rank", &shown), ("page", &FIRST_PAGE)]);
+        }
+        match self.previous_best {
+            Some(best) if self.rank < best => locale.tr("rank.up_from", &[("rank", &shown), ("previous", &best)]),
+            Some(best) => locale.tr("rank.best", &[("rank", &shown), ("best", &best)]),
+            None => locale.tr("rank.position", &[("rank", &shown)]),
+        }
+    }
+
+    fn color(&self) -> Color {
+        if self.entered_first_page() {
+            TOP_PAGE_COLOR
+        } else if self.improved() {
+            RANK_UP_COLOR
+        } else {
+            Color::WHITE
+        }
+    }
+}
+
+// 档案中最好名次的键：名次只在同一赛季和难度的榜单内可比，单关挑战还要是同一关
+fn board_key(score: &Score) -> String {
+    let season = score.season.as_deref().unwrap_or_default();
+    match score.mode.as_deref() {
+        Some(LEVEL_MODE) => format!("{}|{}|{}{}", season, score.difficulty, LEVEL_MODE, score.level),
+        _ => format!("{}|{}", season, score.difficulty),
+    }
+}
+
+// 记录提交返回的名次，更新档案中的最好名次；unseen 为 true 时同时保存下来，稍后在主菜单提示
+fn record_rank(paths: &ProfilePaths, score: &Score, unseen: bool) -> Option<RankResult> {
+    let rank = score.rank?;
+    let mut profile = Profile::load(paths);
+    let best = profile.best_ranks.entry(board_key(score)).or_insert(u32::MAX);
+    let result = RankResult {
+        rank,
+        previous_best: (*best != u32::MAX).then_some(*best),
+    };
+    *best = (*best).min(rank);
+    if unseen {
+        profile.unseen_rank = Some(result);
+    }
+    if let Err(e) = profile.save(paths) {
+        eprintln!("Failed to save profile: {}", e);
+    }
+    Some(result)
+}
+
+// 结算界面上显示名次的文字，提交返回后开始计数
+#[derive(Component)]
+pub struct RankText;
+
+// 正在计数的名次
+#[derive(Component)]
+pub struct RankCounter {
+    result: RankResult,
+    elapsed: f32,
+}
+
+// 庆祝火花，位置相对发射它的节点
+#[derive(Component)]
+pub struct CelebrationSpark {
+    offset: Vec2,
+    velocity: Vec2,
+    age: f32,
+    lifetime: f32,
+    gravity: f32,
+}
+
+// 一次喷发的火花：出射方向（界面坐标，y 向下）和速度的范围、寿命、下落加速度和大小
+pub struct SparkBurst {
+    pub angle: Range<f32>,
+    pub speed: Range<f32>,
+    pub lifetime: f32,
+    pub gravity: f32,
+    pub size: Vec2,
+}
+
+// 名次进入第一页：从名次文字向四周炸开
+const RANK_SPARKS: SparkBurst = SparkBurst {
+    angle: 0.0..std::f32::consts::TAU,
+    speed: 80.0..220.0,
+    lifetime: 1.2,
+    gravity: 300.0,
+    size: Vec2::new(6.0, 6.0),
+};
+
+// 主菜单上显示的名次提示
+#[derive(Component)]
+pub struct RankToast {
+    age: f32,
+}
+
+// 有等待在主菜单显示的名次
+#[derive(Resource, Default)]
+pub struct RankToastPending(pub bool);
+
+// 提交返回后记录名次：结算界面还在时在界面上显示，已经离开时保存下来，回到主菜单再提示
+pub fn receive_submission_rank(
+    mut commands: Commands,
+    mut responses: EventReader<ApiResponse>,
+    paths: Res<ProfilePaths>,
+    settings: Res<Settings>,
+    mut toast_pending: ResMut<RankToastPending>,
+    rank_text: Query<Entity, With<RankText>>,
+) {
+    for response in responses.read() {
+        let ApiResponse::ScoreSubmitted(Ok(score)) = response else {
+            continue;
+        };
+        let on_screen = rank_text.get_single().ok();
+        let Some(result) = record_rank(&paths, score, on_screen.is_none()) else {
+            continue;
+        };
+        let Some(entity) = on_screen else {
+            toast_pending.0 = true;
+            continue;
+        };
+
+        // 减少动态效果时直接显示最终名次，也不放火花
+        let elapsed = if settings.reduce_motion { COUNT_DURATION } else { 0.0 };
+        commands.entity(entity).insert(RankCounter { result, elapsed });
+        if result.entered_first_page() && !settings.reduce_motion {
+            commands.entity(entity).with_children(|parent| spawn_sparks(parent, &RANK_SPARKS, SPARK_COUNT));
+        }
+    }
+}
+
+// 在 parent 的左上角喷出 count 个火花，颜色随机
+pub fn spawn_sparks(parent: &mut ChildBuilder, burst: &SparkBurst, count: usize) {
+    let mut rng = rand::thread_rng();
+    for _ in 0..count {
+        let angle = rng.gen_range(burst.angle.clone());
+        let speed = rng.gen_range(burst.speed.clone());
+        let color = Color::hsl(rng.gen_range(0.0..360.0), 0.9, 0.6);
+        parent.spawn((
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    width: Val::Px(burst.size.x),
+                    height: Val::Px(burst.size.y),
+                    ..default()
+                },
+                background_color: BackgroundColor(color),
+                ..default()
+            },
+            CelebrationSpark {
+                offset: Vec2::ZERO,
+                velocity: Vec2::from_angle(angle) * speed,
+                age: 0.0,
+                lifetime: burst.lifetime,
+                gravity: burst.gravity,
+            },
+        ));
+    }
+}
+
+// 计数动画的当前值：t 从 0 到 1，数值从 from 先快后慢地变到 to
+pub fn count_up(from: u32, to: u32, t: f32) -> u32 {
+    let eased = 1.0 - (1.0 - t.clamp(0.0, 1.0)).powi(3);
+    (from as f32 + (to as f32 - from as f32) * eased).round() as u32
+}
+
+// 名次从以前的最好名次逐渐数到新名次，先快后慢
+pub fn animate_rank_counter(
+    time: Res<Time>,
+    locale: Res<Locale>,
+    mut query: Query<(&mut RankCounter, &mut Text)>,
+) {
+    for (mut counter, mut text) in query.iter_mut() {
+        counter.elapsed = (counter.elapsed + time.delta_seconds()).min(COUNT_DURATION);
+        let result = counter.result;
+        let shown = match result.previous_best {
+            Some(best) if result.rank < best => count_up(best, result.rank, counter.elapsed / COUNT_DURATION),
+            _ => result.rank,
+        };
+        text.sections[0].value = result.message(&locale, shown);
+        text.sections[0].style.color = result.color();
+    }
+}
+
+// 火花向外飞散、下落并淡出
+pub fn animate_celebration(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut query: Query<(Entity, &mut CelebrationSpark, &mut Style, &mut BackgroundColor)>,
+) {
+    let delta = time.delta_seconds();
+    for (entity, mut spark, mut style, mut background) in query.iter_mut() {
+        spark.age += delta;
+        if spark.age >= spark.lifetime {
+            commands.entity(entity).despawn_recursive();
+            continue;
+        }
+        spark.velocity.y += spark.gravity * delta;
+        let velocity = spark.velocity;
+        spark.offset += velocity * delta;
+        style.left = Val::Px(spark.offset.x);
+        style.top = Val::Px(spark.offset.y);
+        background.0.set_a(1.0 - spark.age / spark.lifetime);
+    }
+}
+
+// 进入主菜单时检查上次保存下来的名次
+pub fn queue_rank_toast(mut toast_pending: ResMut<RankToastPending>) {
+    toast_pending.0 = true;
+}
+
+// 在主菜单底部提示还没看到的名次，显示后从档案中清除
+pub fn show_rank_toast(
+    mut commands: Commands,
+    paths: Res<ProfilePaths>,
+    fonts: Res<FontAssets>,
+    locale: Res<Locale>,
+    next_state: Res<NextState<GameState>>,
+    mut toast_pending: ResMut<RankToastPending>,
+) {
+    // 进入主菜单的同一帧可能马上跳到别的界面（例如补交未完成的局），等真正停在主菜单再显示
+    if !toast_pending.0 || next_state.0.is_some() {
+        return;
+    }
+    toast_pending.0 = false;
+
+    let mut profile = Profile::load(&paths);
+    let Some(result) = profile.unseen_rank.take() else {
+        return;
+    };
+    if let Err(e) = profile.save(&paths) {
+        eprintln!("Failed to save profile: {}", e);
+    }
+
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    bottom: Val::Px(20.0),
+                    width: Val::Percent(100.0),
+                    justify_content: JustifyContent::Center,
+                    ..default()
+                },
+                z_index: ZIndex::Global(100),
+                ..default()
+            },
+            RankToast { age: 0.0 },
+        ))
+        .with_children(|parent| {
+            parent
+                .spawn(NodeBundle {
+                    style: Style {
+                        padding: UiRect::axes(Val::Px(16.0), Val::Px(8.0)),
+                        ..default()
+                    },
+                    background_color: BackgroundColor(Color::rgba(0.2, 0.2, 0.25, 0.9)),
+                    ..default()
+                })
+                .with_children(|parent| {
+                    parent.spawn(TextBundle::from_section(
+                        locale.tr("rank.toast", &[("message", &result.message(&locale, result.rank))]),
+                        text_style(&fonts, FontKind::Regular, 20.0, result.color()),
+                    ));
+                });
+        });
+}
+
+// 提示显示一段时间后淡出
+pub fn fade_rank_toast(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut toasts: Query<(Entity, &mut RankToast, &Children)>,
+    mut panels: Query<(&mut BackgroundColor, &Children), Without<RankToast>>,
+    mut texts: Query<&mut Text>,
+) {
+    for (entity, mut toast, children) in toasts.iter_mut() {
+        toast.age += time.delta_seconds();
+        if toast.age >= TOAST_DURATION {
+            commands.entity(entity).despawn_recursive();
+            continue;
+        }
+        let alpha = ((TOAST_DURATION - toast.age) / TOAST_FADE).min(1.0);
+        for child in children.iter() {
+            let Ok((mut background, panel_children)) = panels.get_mut(*child) else {
+                continue;
+            };
+            background.0.set_a(0.9 * alpha);
+            for text_entity in panel_children.iter() {
+                if let Ok(mut text) = texts.get_mut(*text_entity) {
+                    text.sections[0].style.color.set_a(alpha);
+                }
+            }
+        }
+    }
+}
+
+pub fn cleanup_rank_toast(mut commands: Commands, query: Query<Entity, With<RankToast>>) {
+    for entity in query.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+}
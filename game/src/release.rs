@@ -0,0 +1,260 @@
+use bevy::asset::LoadState;
+use bevy::audio::AudioLoader;
+use bevy::prelude::*;
+use bevy::text::FontLoader;
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+use crate::audio::sound_files;
+use crate::changelog::{Changelog, CURRENT_VERSION};
+use crate::fonts::FONT_FILES;
+use crate::i18n::Language;
+use crate::level_file;
+use crate::level_select::MedalThresholds;
+use crate::persistence::save_versioned;
+use crate::quota::QUOTA_LEVEL_FILE;
+use crate::settings::Settings;
+
+// 由 build.rs 在编译时写入
+pub const GIT_HASH: &str = env!("BREAKOUT_GIT_HASH");
+pub const BUILD_DATE: &str = env!("BREAKOUT_BUILD_DATE");
+
+// 发布用的命令行参数，都在创建窗口之前处理并直接退出：
+//   --version                    版本、提交和构建日期
+//   --list-assets                游戏运行时会加载的资源文件（相对于 assets），打包时检查它们是否存在
+//   --write-package-files <目录>  写入默认设置模板和操作说明，打包时放进压缩包
+//   --headless --self-check      不创建窗口，加载全部资源并检查内嵌的数据，失败时以非零状态退出
+const VERSION_FLAG: &str = "--version";
+const LIST_ASSETS_FLAG: &str = "--list-assets";
+const WRITE_PACKAGE_FILES_FLAG: &str = "--write-package-files";
+const SELF_CHECK_FLAG: &str = "--self-check";
+const HEADLESS_FLAG: &str = "--headless";
+
+const SETTINGS_TEMPLATE_FILE: &str = "settings.template.json";
+const README_FILE: &str = "README.txt";
+// 自检时等待资源加载的最长时间
+const SELF_CHECK_TIMEOUT: Duration = Duration::from_secs(10);
+
+// 主菜单角落和 --version 显示的版本信息
+pub fn version_line() -> String {
+    format!("{} ({} {})", CURRENT_VERSION, GIT_HASH, BUILD_DATE)
+}
+
+// 处理上面的参数；返回退出码，没有这些参数时返回 None，继续正常启动
+pub fn run_cli_command() -> Option<i32> {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let has = |flag: &str| args.iter().any(|arg| arg == flag);
+
+    if has(VERSION_FLAG) {
+        println!("{} {}", env!("CARGO_PKG_NAME"), version_line());
+        return Some(0);
+    }
+    if has(LIST_ASSETS_FLAG) {
+        for path in referenced_assets() {
+            println!("{}", path);
+        }
+        return Some(0);
+    }
+    if let Some(position) = args.iter().position(|arg| arg == WRITE_PACKAGE_FILES_FLAG) {
+        let Some(dir) = args.get(position + 1) else {
+            eprintln!("{} needs a target directory", WRITE_PACKAGE_FILES_FLAG);
+            return Some(2);
+        };
+        return Some(match write_package_files(Path::new(dir)) {
+            Ok(()) => 0,
+            Err(e) => {
+                eprintln!("Failed to write package files: {}", e);
+                1
+            }
+        });
+    }
+    if has(SELF_CHECK_FLAG) {
+        return Some(if self_check() { 0 } else { 1 });
+    }
+    if has(HEADLESS_FLAG) {
+        eprintln!("{} is only supported together with {}", HEADLESS_FLAG, SELF_CHECK_FLAG);
+        return Some(2);
+    }
+    None
+}
+
+// 字体和音效在开发时可以缺失（使用默认字体、不播放）；发布的包里必须齐全，自检会检查
+fn referenced_assets() -> Vec<String> {
+    FONT_FILES
+        .iter()
+        .map(|path| path.to_string())
+        .chain(sound_files().map(|(_, path)| path))
+        .collect()
+}
+
+fn write_package_files(dir: &Path) -> std::io::Result<()> {
+    std::fs::create_dir_all(dir)?;
+    save_versioned(&dir.join(SETTINGS_TEMPLATE_FILE), &Settings::default())?;
+
+    // 操作说明使用英文语言包中的文本，和主菜单显示的一致
+    let controls = Language::En
+        .parse_bundle()
+        .ok()
+        .and_then(|mut strings| strings.remove("menu.controls"))
+        .unwrap_or_default();
+    let readme = format!(
+        "Super Breakout {}\n\n{}\n\n\
+         Start the game with the launcher next to this file so it finds the assets folder.\n\
+         Settings and profiles are stored next to the game. {} shows the default settings;\n\
+         copy it into a profile folder as settings.json to start from a known state.\n",
+        version_line(),
+        controls,
+        SETTINGS_TEMPLATE_FILE,
+    );
+    std::fs::write(dir.join(README_FILE), readme)
+}
+
+// 自检：内嵌的数据能解析，字体和音效都存在并且能加载。后备方案只用于开发，发布的包缺资源算失败
+fn self_check() -> bool {
+    println!("Self-check {}", version_line());
+    let mut ok = check_embedded_data();
+
+    // 只有资源加载相关的插件，不创建窗口也不需要显卡和声卡
+    let mut app = App::new();
+    app.add_plugins((MinimalPlugins, AssetPlugin::default()))
+        .init_asset::<Font>()
+        .init_asset_loader::<FontLoader>()
+        .init_asset::<AudioSource>()
+        .init_asset_loader::<AudioLoader>();
+    app.finish();
+    app.cleanup();
+
+    // 缺失的文件直接算失败，只加载存在的文件
+    let asset_server = app.world.resource::<AssetServer>();
+    let mut exists = |path: &str| {
+        let found = Path::new("assets").join(path).exists();
+        if !found {
+            println!("  FAILED   {} is missing", path);
+            ok = false;
+        }
+        found
+    };
+    let mut pending: Vec<(String, UntypedHandle)> = Vec::new();
+    for path in FONT_FILES.into_iter().filter(|path| exists(path)) {
+        pending.push((path.to_string(), asset_server.load::<Font>(path).untyped()));
+    }
+    for (_, path) in sound_files().filter(|(_, path)| exists(path)) {
+        pending.push((path.clone(), asset_server.load::<AudioSource>(path).untyped()));
+    }
+
+    let deadline = Instant::now() + SELF_CHECK_TIMEOUT;
+    while !pending.is_empty() {
+        app.update();
+        let asset_server = app.world.resource::<AssetServer>();
+        pending.retain(|(path, handle)| match asset_server.load_state(handle.id()) {
+            LoadState::Loaded => {
+                println!("  ok       {}", path);
+                false
+            }
+            LoadState::Failed => {
+                println!("  FAILED   {} could not be loaded", path);
+                ok = false;
+                false
+            }
+            LoadState::NotLoaded | LoadState::Loading => true,
+        });
+        if Instant::now() >= deadline {
+            for (path, _) in pending.drain(..) {
+                println!("  FAILED   {} did not load within {:?}", path, SELF_CHECK_TIMEOUT);
+            }
+            ok = false;
+        }
+        std::thread::sleep(Duration::from_millis(10));
+    }
+
+    println!("Self-check {}", if ok { "passed" } else { "failed" });
+    ok
+}
+
+// 内嵌在程序里的数据：语言包、奖牌线、配额关卡和更新日志
+fn check_embedded_data() -> bool {
+    let mut ok = true;
+    for language in Language::ALL {
+        match language.parse_bundle() {
+            Ok(strings) => println!("  ok       locale {} ({} strings)", language.code(), strings.len()),
+            Err(e) => {
+                println!("  FAILED   locale {}: {}", language.code(), e);
+                ok = false;
+            }
+        }
+    }
+    match MedalThresholds::parse() {
+        Ok(_) => println!("  ok       medals.ron"),
+        Err(e) => {
+            println!("  FAILED   medals.ron: {}", e);
+            ok = false;
+        }
+    }
+    match level_file::load_layout(QUOTA_LEVEL_FILE) {
+        Ok(_) => println!("  ok       levels/quota_pockets.ron"),
+        Err(e) => {
+            println!("  FAILED   levels/quota_pockets.ron: {}", e);
+            ok = false;
+        }
+    }
+    if Changelog::default().releases.is_empty() {
+        println!("  FAILED   changelog.json has no releases");
+        ok = false;
+    } else {
+        println!("  ok       changelog.json");
+    }
+    ok
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::persistence::load_versioned;
+
+    #[test]
+    fn embedded_data_passes_the_self_check() {
+        assert!(check_embedded_data());
+    }
+
+    #[test]
+    fn every_font_and_sound_is_listed_for_packaging() {
+        let listed = referenced_assets();
+        for path in FONT_FILES {
+            assert!(listed.iter().any(|listed| listed == path), "{} is not listed", path);
+        }
+        for (kind, path) in sound_files() {
+            assert!(listed.contains(&path), "{:?} is not listed", kind);
+        }
+        assert_eq!(listed.len(), FONT_FILES.len() + sound_files().count());
+    }
+
+    #[test]
+    fn package_files_hold_the_default_settings_and_the_controls() {
+        let dir = std::env::temp_dir().join(format!("breakout-release-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        write_package_files(&dir).unwrap();
+
+        let template: Option<Settings> = load_versioned(&dir.join(SETTINGS_TEMPLATE_FILE));
+        let readme = std::fs::read_to_string(dir.join(README_FILE)).unwrap();
+        let _ = std::fs::remove_dir_all(&dir);
+
+        // 匿名编号每次生成都不同，其余字段和默认设置一致
+        let mut template = template.expect("the template loads as settings");
+        let defaults = Settings::default();
+        template.anonymous_id = defaults.anonymous_id;
+        assert_eq!(serde_json::to_value(template).unwrap(), serde_json::to_value(defaults).unwrap());
+        assert!(readme.contains(&version_line()));
+        assert!(readme.contains(SETTINGS_TEMPLATE_FILE));
+        let controls = Language::En.parse_bundle().unwrap().remove("menu.controls").unwrap();
+        assert!(!controls.is_empty() && readme.contains(&controls));
+    }
+
+    #[test]
+    fn version_line_carries_the_build_info() {
+        let line = version_line();
+        assert!(line.starts_with(CURRENT_VERSION));
+        assert!(line.contains(GIT_HASH) && line.contains(BUILD_DATE));
+        assert_ne!(GIT_HASH, "", "build.rs always sets a hash");
+        assert_eq!(BUILD_DATE.len(), "2024-05-01".len());
+    }
+}
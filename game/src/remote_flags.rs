@@ -0,0 +1,47 @@
+use bevy::prelude::*;
+use std::collections::BTreeMap;
+
+use crate::network::{ApiCommand, ApiResponse, Network};
+
+// 服务器端的功能开关。启动时读取一次；取不到（离线、服务器太旧）或服务器没有返回的开关都按打开处理，
+// 和没有开关时的行为一样
+#[derive(Resource, Debug, Default)]
+pub struct RemoteFlags {
+    flags: BTreeMap<String, bool>,
+}
+
+impl RemoteFlags {
+    pub fn enabled(&self, name: &str) -> bool {
+        self.flags.get(name).copied().unwrap_or(true)
+    }
+}
+
+// 受开关控制的界面元素，开关关闭时不显示
+#[derive(Component)]
+pub struct FlagGated(pub &'static str);
+
+pub fn request_remote_flags(network: Res<Network>) {
+    network.send(ApiCommand::GetFlags);
+}
+
+pub fn receive_remote_flags(mut responses: EventReader<ApiResponse>, mut remote_flags: ResMut<RemoteFlags>) {
+    for response in responses.read() {
+        let ApiResponse::Flags(result) = response else {
+            continue;
+        };
+        match result {
+            Ok(response) => remote_flags.flags = response.flags.clone(),
+            Err(e) => eprintln!("Failed to fetch feature flags, using defaults: {}", e),
+        }
+    }
+}
+
+// 开关在菜单打开之后才返回时，已经显示的入口也要跟着隐藏
+pub fn apply_flag_gates(remote_flags: Res<RemoteFlags>, mut gated: Query<(&FlagGated, &mut Style)>) {
+    for (gate, mut style) in gated.iter_mut() {
+        let display = if remote_flags.enabled(gate.0) { Display::Flex } else { Display::None };
+        if style.display != display {
+            style.display = display;
+        }
+    }
+}
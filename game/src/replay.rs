@@ -0,0 +1,186 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+
+use crate::fixed_step::PHYSICS_HZ;
+use crate::paddle::{self, Paddle};
+use crate::persistence::{self, load_versioned, save_versioned, Versioned};
+use crate::profiles::ProfilePaths;
+use crate::{DifficultySettings, GameEntity, Level, RunSeed, Score, Settings, PADDLE_COLOR, PADDLE_SIZE, PADDLE_Y};
+
+// 物理改为固定频率之前，固定帧使用 Bevy 默认的 64 Hz
+const LEGACY_TICK_RATE: f64 = 64.0;
+
+// 个人最佳成绩的挡板轨迹（每个固定帧记录一次挡板 x 坐标）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GhostTrace {
+    pub seed: u64,
+    pub difficulty: String,
+    pub score: u32,
+    // 记录时固定帧的频率，回放时按它换算，频率改变后旧的轨迹速度不变
+    pub tick_rate: f64,
+    // 按关卡分组的轨迹，levels[0] 对应第一关
+    pub levels: Vec<Vec<f32>>,
+}
+
+impl GhostTrace {
+    fn path(paths: &ProfilePaths, difficulty: &str) -> PathBuf {
+        paths.replays().join(format!("ghost_{}.json", difficulty.to_lowercase()))
+    }
+
+    // 读取某难度的个人最佳轨迹
+    pub fn load(paths: &ProfilePaths, difficulty: &str) -> Option<Self> {
+        load_versioned(&Self::path(paths, difficulty))
+    }
+
+    pub fn save(&self, paths: &ProfilePaths) -> std::io::Result<()> {
+        save_versioned(&Self::path(paths, &self.difficulty), self)
+    }
+}
+
+impl Versioned for GhostTrace {
+    const VERSION: u32 = 2;
+    const LABEL: &'static str = "ghost trace";
+    // 轨迹有成千上万个数字，不缩进
+    const PRETTY: bool = false;
+
+    fn migrate(version: u32, mut data: serde_json::Value) -> Result<serde_json::Value, String> {
+        match version {
+            // v0：没有外层格式的旧轨迹，字段与 v1 相同
+            0 => Ok(data),
+            // v1 -> v2：记录固定帧的频率，之前的轨迹都是 64 Hz
+            1 => {
+                if let Some(object) = data.as_object_mut() {
+                    object.insert("tick_rate".to_string(), LEGACY_TICK_RATE.into());
+                }
+                Ok(data)
+            }
+            _ => persistence::unknown_version(version),
+        }
+    }
+}
+
+// 当前局的轨迹记录
+#[derive(Resource, Default)]
+pub struct ReplayRecorder {
+    pub levels: Vec<Vec<f32>>,
+}
+
+impl ReplayRecorder {
+    pub fn reset(&mut self) {
+        self.levels.clear();
+    }
+}
+
+// 幽灵挡板：纯视觉，不参与碰撞
+#[derive(Component)]
+pub struct GhostPaddle {
+    trace: Vec<f32>,
+    tick_rate: f64,
+    // 回放到轨迹的第几帧，按记录时的频率计
+    position: f64,
+}
+
+// 每个固定帧记录挡板位置
+pub fn record_paddle_trace(
+    mut recorder: ResMut<ReplayRecorder>,
+    level: Res<Level>,
+    paddle_query: Query<&Transform, With<Paddle>>,
+) {
+    let transform = match paddle_query.get_single() {
+        Ok(transform) => transform,
+        Err(e) => {
+            paddle::warn_paddle_query("record_paddle_trace", e);
+            return;
+        }
+    };
+    let index = level.0.saturating_sub(1) as usize;
+    if recorder.levels.len() <= index {
+        recorder.levels.resize(index + 1, Vec::new());
+    }
+    recorder.levels[index].push(transform.translation.x);
+}
+
+// 生成幽灵挡板（种子不同或设置关闭时不生成）
+pub fn spawn_ghost_paddle(
+    commands: &mut Commands,
+    paths: &ProfilePaths,
+    settings: &Settings,
+    difficulty_settings: &DifficultySettings,
+    run_seed: &RunSeed,
+    level: u32,
+) {
+    if !settings.ghost_enabled {
+        return;
+    }
+
+    let Some(ghost) = GhostTrace::load(paths, difficulty_settings.difficulty.name()) else {
+        return;
+    };
+    if ghost.seed != run_seed.0 {
+        return;
+    }
+    let Some(trace) = ghost.levels.get(level.saturating_sub(1) as usize) else {
+        return;
+    };
+    if trace.is_empty() {
+        return;
+    }
+
+    commands.spawn((
+        SpriteBundle {
+            sprite: Sprite {
+                color: PADDLE_COLOR.with_a(0.4),
+                ..default()
+            },
+            transform: Transform {
+                translation: Vec3::new(trace[0], PADDLE_Y, -1.0),
+                scale: Vec3::new(PADDLE_SIZE.x, PADDLE_SIZE.y, 1.0),
+                ..default()
+            },
+            ..default()
+        },
+        GhostPaddle {
+            trace: trace.clone(),
+            tick_rate: ghost.tick_rate,
+            position: 0.0,
+        },
+        GameEntity,
+    ));
+}
+
+// 每个固定帧推进幽灵挡板；轨迹按记录时的频率换算，和当前固定帧的频率无关
+pub fn ghost_playback(time: Res<Time>, mut ghosts: Query<(&mut Transform, &mut GhostPaddle)>) {
+    for (mut transform, mut ghost) in ghosts.iter_mut() {
+        if let Some(&x) = ghost.trace.get(ghost.position as usize) {
+            transform.translation.x = x;
+            ghost.position += ghost.tick_rate * time.delta_seconds_f64();
+        }
+    }
+}
+
+// 一局结束时，若刷新个人最佳则替换幽灵轨迹
+pub fn save_ghost_if_best(
+    paths: &ProfilePaths,
+    recorder: &ReplayRecorder,
+    run_seed: &RunSeed,
+    score: &Score,
+    difficulty_settings: &DifficultySettings,
+) {
+    let difficulty = difficulty_settings.difficulty.name();
+    let previous_best = GhostTrace::load(paths, difficulty).map(|ghost| ghost.score);
+    if previous_best.is_some_and(|best| best >= score.0) || recorder.levels.is_empty() {
+        return;
+    }
+
+    let trace = GhostTrace {
+        seed: run_seed.0,
+        difficulty: difficulty.to_string(),
+        score: score.0,
+        tick_rate: PHYSICS_HZ,
+        levels: recorder.levels.clone(),
+    };
+    if let Err(e) = trace.save(paths) {
+        eprintln!("Failed to save ghost trace: {}", e);
+    }
+}
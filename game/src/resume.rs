@@ -0,0 +1,164 @@
+use bevy::prelude::*;
+
+use crate::bonus::Orientation;
+use crate::countdown::Countdown;
+use crate::fonts::{text_style, FontAssets, FontKind};
+use crate::i18n::Locale;
+use crate::launch::HeldBall;
+use crate::paddle::{self, Paddle};
+use crate::sticky::StuckBall;
+use crate::{Ball, PowerUpEffects, BALL_SIZE, BALL_SPEED, PADDLE_SIZE, WINDOW_HEIGHT, WINDOW_WIDTH};
+
+// 从暂停返回且有球在飞时，先倒数几秒再继续
+const RESUME_COUNTDOWN: f32 = 3.0;
+
+// 这一次进入 Playing 的方式。从暂停菜单继续时这一局的实体都还在，只做轻量的检查；
+// 其他情况（新的一局、下一关、重新开始）走完整的初始化。离开 Playing 时恢复为完整初始化
+#[derive(Resource, Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum ResumeKind {
+    #[default]
+    FullSetup,
+    FromPause,
+}
+
+pub fn full_setup(kind: Res<ResumeKind>) -> bool {
+    *kind == ResumeKind::FullSetup
+}
+
+pub fn from_pause(kind: Res<ResumeKind>) -> bool {
+    *kind == ResumeKind::FromPause
+}
+
+// 暂停菜单选择继续。游戏时间在切换状态之前就停下，回到 Playing 的那一帧球不会先动一下；
+// 进入 Playing 后由 start_resume_countdown 决定倒计时还是立即恢复
+pub fn continue_from_pause(kind: &mut ResumeKind, virtual_time: &mut Time<Virtual>) {
+    *kind = ResumeKind::FromPause;
+    virtual_time.pause();
+}
+
+// 继续前的倒计时，显示在屏幕中央；倒计时期间游戏时间停止
+#[derive(Component)]
+pub struct ResumeCountdown(Countdown);
+
+// 暂停期间的状态不应该改变，出现偏差时在这里纠正：挡板回到挡板线上并且不超出墙壁，
+// 飞行中的球留在场地里并且有有效的速度。纠正了什么都打印出来，便于找出造成偏差的原因
+pub fn validate_after_resume(
+    orientation: Res<Orientation>,
+    power_effects: Res<PowerUpEffects>,
+    mut paddles: Query<&mut Transform, (With<Paddle>, Without<Ball>)>,
+    mut balls: Query<(&mut Transform, &mut Ball), (Without<HeldBall>, Without<StuckBall>)>,
+) {
+    match paddles.get_single_mut() {
+        Ok(mut transform) => {
+            let paddle_y = orientation.paddle_y();
+            if transform.translation.y != paddle_y {
+                eprintln!("Paddle was off its line after resume ({}), moved back", transform.translation.y);
+                transform.translation.y = paddle_y;
+            }
+            let boundary = WINDOW_WIDTH / 2.0 - PADDLE_SIZE.x * power_effects.paddle_size_modifier / 2.0;
+            let x = transform.translation.x;
+            if !x.is_finite() || x.abs() > boundary {
+                eprintln!("Paddle was outside the walls after resume ({}), clamped", x);
+                transform.translation.x = if x.is_finite() { x.clamp(-boundary, boundary) } else { 0.0 };
+            }
+        }
+        Err(e) => paddle::warn_paddle_query("validate_after_resume", e),
+    }
+
+    let limit = Vec2::new(WINDOW_WIDTH, WINDOW_HEIGHT) / 2.0 - BALL_SIZE / 2.0;
+    for (mut transform, mut ball) in balls.iter_mut() {
+        let position = transform.translation.truncate();
+        if !position.is_finite() || position.x.abs() > limit.x || position.y.abs() > limit.y {
+            eprintln!("Ball was outside the field after resume ({}), moved back", position);
+            let clamped = if position.is_finite() { position.clamp(-limit, limit) } else { Vec2::ZERO };
+            transform.translation.x = clamped.x;
+            transform.translation.y = clamped.y;
+        }
+        if !ball.velocity.is_finite() || ball.velocity.length_squared() < f32::EPSILON {
+            eprintln!("Ball had an invalid velocity after resume ({}), relaunched", ball.velocity);
+            // 朝远离挡板的方向重新出发
+            ball.velocity = orientation.apply(Vec2::new(0.0, BALL_SPEED));
+        }
+    }
+}
+
+// 有球在飞时保持游戏时间停止并开始倒计时，否则立即恢复；已经有倒计时的话从头开始，不会出现两个
+pub fn start_resume_countdown(
+    mut commands: Commands,
+    mut virtual_time: ResMut<Time<Virtual>>,
+    fonts: Res<FontAssets>,
+    locale: Res<Locale>,
+    balls: Query<(), (With<Ball>, Without<HeldBall>, Without<StuckBall>)>,
+    mut countdowns: Query<&mut ResumeCountdown>,
+) {
+    if balls.is_empty() {
+        virtual_time.unpause();
+        return;
+    }
+    if let Ok(mut countdown) = countdowns.get_single_mut() {
+        countdown.0.restart(RESUME_COUNTDOWN);
+        return;
+    }
+
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    width: Val::Percent(100.0),
+                    height: Val::Percent(100.0),
+                    justify_content: JustifyContent::Center,
+                    align_items: AlignItems::Center,
+                    ..default()
+                },
+                z_index: ZIndex::Global(90),
+                ..default()
+            },
+            ResumeCountdown(Countdown::new(RESUME_COUNTDOWN)),
+        ))
+        .with_children(|parent| {
+            parent.spawn(TextBundle::from_section(
+                locale.tr("resume.countdown", &[("seconds", &(RESUME_COUNTDOWN as u32))]),
+                text_style(&fonts, FontKind::Bold, 72.0, Color::WHITE),
+            ));
+        });
+}
+
+// 倒计时按真实时间前进，结束后恢复游戏时间
+pub fn resume_countdown_system(
+    mut commands: Commands,
+    real_time: Res<Time<Real>>,
+    locale: Res<Locale>,
+    mut virtual_time: ResMut<Time<Virtual>>,
+    mut countdowns: Query<(Entity, &mut ResumeCountdown, &Children)>,
+    mut texts: Query<&mut Text>,
+) {
+    for (entity, mut countdown, children) in countdowns.iter_mut() {
+        if countdown.0.tick(real_time.delta_seconds()) {
+            commands.entity(entity).despawn_recursive();
+            virtual_time.unpause();
+            continue;
+        }
+        let seconds = countdown.0.remaining().ceil() as u32;
+        for &child in children.iter() {
+            if let Ok(mut text) = texts.get_mut(child) {
+                text.sections[0].value = locale.tr("resume.countdown", &[("seconds", &seconds)]);
+            }
+        }
+    }
+}
+
+// 离开 Playing 时（再次暂停、过关、丢掉最后一条命……）去掉倒计时并无条件恢复游戏时间；
+// 下一次进入默认走完整的初始化，只有暂停菜单的继续会改成从暂停返回
+pub fn leave_playing(
+    mut commands: Commands,
+    mut kind: ResMut<ResumeKind>,
+    mut virtual_time: ResMut<Time<Virtual>>,
+    countdowns: Query<Entity, With<ResumeCountdown>>,
+) {
+    *kind = ResumeKind::FullSetup;
+    virtual_time.unpause();
+    for entity in countdowns.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+}
@@ -0,0 +1,300 @@
+use bevy::prelude::*;
+
+use crate::api::{RivalsResponse, Score};
+use crate::fonts::{text_style, FontAssets, FontKind};
+use crate::i18n::{name_key, Locale};
+use crate::level_select::LEVEL_MODE;
+use crate::network::{ApiCommand, ApiResponse, Network};
+use crate::tournament::TOURNAMENT_MODE;
+use crate::{DifficultySettings, PlayerName};
+
+// 超过对手提示的显示时长和最后淡出的时长（秒）
+const TOAST_DURATION: f32 = 5.0;
+const TOAST_FADE: f32 = 1.0;
+
+const PASSED_COLOR: Color = Color::rgb(0.2, 0.85, 0.3);
+
+// 开局前取到的对手列表，提交成绩后和新分数比较
+#[derive(Resource, Default)]
+pub struct RunRivals {
+    // 已经请求过的 (玩家名, 难度)
+    requested: Option<(String, String)>,
+    response: Option<RivalsResponse>,
+}
+
+impl RunRivals {
+    fn matches(&self, player_name: &str, difficulty: &str) -> bool {
+        self.requested
+            .as_ref()
+            .is_some_and(|(name, requested_difficulty)| name == player_name && requested_difficulty == difficulty)
+    }
+}
+
+// 档案界面上的对手列表，响应返回后填入
+#[derive(Component)]
+pub struct RivalsPanel;
+
+// 超过对手的提示
+#[derive(Component)]
+pub struct RivalToast {
+    age: f32,
+}
+
+// 只有进入普通排行榜的成绩才和对手比较
+fn on_main_board(score: &Score) -> bool {
+    !matches!(score.mode.as_deref(), Some(LEVEL_MODE) | Some(TOURNAMENT_MODE))
+}
+
+// 新分数超过的对手中最高的一位，以及下一个目标；没有超过任何人时为 None
+fn passed_message(before: &RivalsResponse, score: u32, locale: &Locale) -> Option<String> {
+    let passed = before.rivals.iter().rev().find(|rival| rival.best_score < score)?;
+    let message = match before.rivals.iter().find(|rival| rival.best_score > score) {
+        Some(next) => locale.tr(
+            "rivals.passed_next",
+            &[
+                ("name", &passed.player_name),
+                ("next", &next.player_name),
+                ("gap", &(next.best_score - score)),
+            ],
+        ),
+        None => locale.tr("rivals.passed", &[("name", &passed.player_name)]),
+    };
+    Some(message)
+}
+
+// 开局时取一次对手列表；暂停后继续和进入下一关不会重复请求
+pub fn request_run_rivals(
+    network: Res<Network>,
+    player_name: Res<PlayerName>,
+    difficulty_settings: Res<DifficultySettings>,
+    mut run_rivals: ResMut<RunRivals>,
+) {
+    let difficulty = difficulty_settings.difficulty.name();
+    if player_name.0.is_empty() || run_rivals.matches(&player_name.0, difficulty) {
+        return;
+    }
+    network.send(ApiCommand::GetRivals {
+        player_name: player_name.0.clone(),
+        difficulty: difficulty.to_string(),
+    });
+    *run_rivals = RunRivals {
+        requested: Some((player_name.0.clone(), difficulty.to_string())),
+        response: None,
+    };
+}
+
+// 保存开局前的对手列表；成绩提交后如果超过了其中的对手就提示，并在下一局重新获取
+pub fn receive_run_rivals(
+    mut commands: Commands,
+    mut responses: EventReader<ApiResponse>,
+    fonts: Res<FontAssets>,
+    locale: Res<Locale>,
+    mut run_rivals: ResMut<RunRivals>,
+) {
+    for response in responses.read() {
+        match response {
+            ApiResponse::Rivals(Ok(rivals))
+                if run_rivals.response.is_none() && run_rivals.matches(&rivals.player_name, &rivals.difficulty) =>
+            {
+                run_rivals.response = Some(rivals.clone());
+            }
+            ApiResponse::ScoreSubmitted(Ok(score)) => {
+                if !on_main_board(score) || !run_rivals.matches(&score.player_name, &score.difficulty) {
+                    continue;
+                }
+                let before = std::mem::take(&mut *run_rivals).response;
+                if let Some(message) = before.and_then(|before| passed_message(&before, score.score, &locale)) {
+                    spawn_rival_toast(&mut commands, &fonts, message);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+fn spawn_rival_toast(commands: &mut Commands, fonts: &FontAssets, message: String) {
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    top: Val::Px(20.0),
+                    width: Val::Percent(100.0),
+                    justify_content: JustifyContent::Center,
+                    ..default()
+                },
+                z_index: ZIndex::Global(100),
+                ..default()
+            },
+            RivalToast { age: 0.0 },
+        ))
+        .with_children(|parent| {
+            parent
+                .spawn(NodeBundle {
+                    style: Style {
+                        padding: UiRect::axes(Val::Px(16.0), Val::Px(8.0)),
+                        ..default()
+                    },
+                    background_color: BackgroundColor(Color::rgba(0.2, 0.2, 0.25, 0.9)),
+                    ..default()
+                })
+                .with_children(|parent| {
+                    parent.spawn(TextBundle::from_section(
+                        message,
+                        text_style(fonts, FontKind::Bold, 22.0, PASSED_COLOR),
+                    ));
+                });
+        });
+}
+
+// 提示不属于某个界面，切换界面时保留，显示一段时间后淡出
+pub fn fade_rival_toast(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut toasts: Query<(Entity, &mut RivalToast, &Children)>,
+    mut panels: Query<(&mut BackgroundColor, &Children), Without<RivalToast>>,
+    mut texts: Query<&mut Text>,
+) {
+    for (entity, mut toast, children) in toasts.iter_mut() {
+        toast.age += time.delta_seconds();
+        if toast.age >= TOAST_DURATION {
+            commands.entity(entity).despawn_recursive();
+            continue;
+        }
+        let alpha = ((TOAST_DURATION - toast.age) / TOAST_FADE).min(1.0);
+        for child in children.iter() {
+            let Ok((mut background, panel_children)) = panels.get_mut(*child) else {
+                continue;
+            };
+            background.0.set_a(0.9 * alpha);
+            for text_entity in panel_children.iter() {
+                if let Ok(mut text) = texts.get_mut(*text_entity) {
+                    text.sections[0].style.color.set_a(alpha);
+                }
+            }
+        }
+    }
+}
+
+// 档案界面的对手分区：标题和等待响应的占位行
+pub fn spawn_rivals_panel(
+    parent: &mut ChildBuilder,
+    fonts: &FontAssets,
+    locale: &Locale,
+    player_name: &str,
+    difficulty_settings: &DifficultySettings,
+) {
+    let difficulty = locale.tr(&name_key("difficulty", difficulty_settings.difficulty.name()), &[]);
+    parent.spawn(
+        TextBundle::from_section(
+            locale.tr("profile.rivals", &[("difficulty", &difficulty)]),
+            text_style(fonts, FontKind::Bold, 26.0, Color::rgb(1.0, 0.85, 0.2)),
+        )
+        .with_style(Style {
+            margin: UiRect::vertical(Val::Px(8.0)),
+            ..default()
+        }),
+    );
+    let placeholder = if player_name.is_empty() { "rivals.no_name" } else { "rivals.loading" };
+    parent
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    flex_direction: FlexDirection::Column,
+                    ..default()
+                },
+                ..default()
+            },
+            RivalsPanel,
+        ))
+        .with_children(|parent| {
+            spawn_placeholder(parent, fonts, locale.tr(placeholder, &[]));
+        });
+}
+
+fn spawn_placeholder(parent: &mut ChildBuilder, fonts: &FontAssets, message: String) {
+    parent.spawn(TextBundle::from_section(
+        message,
+        text_style(fonts, FontKind::Regular, 18.0, Color::rgb(0.5, 0.5, 0.5)),
+    ));
+}
+
+// 进入档案界面时请求当前难度的对手
+pub fn request_profile_rivals(
+    network: Res<Network>,
+    player_name: Res<PlayerName>,
+    difficulty_settings: Res<DifficultySettings>,
+) {
+    if player_name.0.is_empty() {
+        return;
+    }
+    network.send(ApiCommand::GetRivals {
+        player_name: player_name.0.clone(),
+        difficulty: difficulty_settings.difficulty.name().to_string(),
+    });
+}
+
+// 对手响应返回后填入档案界面：名称、最好成绩和差距
+pub fn update_rivals_panel(
+    mut commands: Commands,
+    mut responses: EventReader<ApiResponse>,
+    fonts: Res<FontAssets>,
+    locale: Res<Locale>,
+    player_name: Res<PlayerName>,
+    difficulty_settings: Res<DifficultySettings>,
+    panels: Query<Entity, With<RivalsPanel>>,
+) {
+    for response in responses.read() {
+        let ApiResponse::Rivals(result) = response else {
+            continue;
+        };
+        let Ok(panel) = panels.get_single() else {
+            continue;
+        };
+        let rivals = match result {
+            Ok(rivals)
+                if rivals.player_name == player_name.0
+                    && rivals.difficulty == difficulty_settings.difficulty.name() =>
+            {
+                rivals
+            }
+            Ok(_) => continue,
+            Err(e) => {
+                eprintln!("Failed to load rivals: {}", e);
+                commands.entity(panel).despawn_descendants().with_children(|parent| {
+                    spawn_placeholder(parent, &fonts, locale.tr("rivals.unavailable", &[]));
+                });
+                continue;
+            }
+        };
+
+        commands.entity(panel).despawn_descendants().with_children(|parent| {
+            if rivals.rivals.is_empty() {
+                let key = if rivals.best_score.is_some() { "rivals.top" } else { "rivals.none" };
+                spawn_placeholder(parent, &fonts, locale.tr(key, &[]));
+                return;
+            }
+            for rival in &rivals.rivals {
+                parent
+                    .spawn(NodeBundle {
+                        style: Style {
+                            justify_content: JustifyContent::SpaceBetween,
+                            min_height: Val::Px(24.0),
+                            ..default()
+                        },
+                        ..default()
+                    })
+                    .with_children(|parent| {
+                        parent.spawn(TextBundle::from_section(
+                            rival.player_name.clone(),
+                            text_style(&fonts, FontKind::Regular, 20.0, Color::rgb(0.8, 0.8, 0.8)),
+                        ));
+                        parent.spawn(TextBundle::from_section(
+                            locale.tr("rivals.row", &[("score", &rival.best_score), ("gap", &rival.gap)]),
+                            text_style(&fonts, FontKind::Mono, 20.0, Color::WHITE),
+                        ));
+                    });
+            }
+        });
+    }
+}
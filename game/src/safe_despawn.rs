@@ -0,0 +1,28 @@
+use bevy::hierarchy::despawn_with_children_recursive;
+use bevy::prelude::*;
+
+// 同一帧里可能被多处删除的游戏实体（同时被激光和球打碎的砖块、
+// 状态切换时又被 cleanup_game 清理的球等）用这里的方法删除：
+// 命令执行时实体已经不存在就直接跳过，不会输出 Bevy 的删除警告
+pub trait SafeDespawnExt {
+    fn safe_despawn(&mut self, entity: Entity);
+    fn safe_despawn_recursive(&mut self, entity: Entity);
+}
+
+impl SafeDespawnExt for Commands<'_, '_> {
+    fn safe_despawn(&mut self, entity: Entity) {
+        self.add(move |world: &mut World| {
+            if let Some(entity) = world.get_entity_mut(entity) {
+                entity.despawn();
+            }
+        });
+    }
+
+    fn safe_despawn_recursive(&mut self, entity: Entity) {
+        self.add(move |world: &mut World| {
+            if world.get_entity(entity).is_some() {
+                despawn_with_children_recursive(world, entity);
+            }
+        });
+    }
+}
@@ -0,0 +1,317 @@
+use bevy::ecs::system::EntityCommands;
+use bevy::prelude::*;
+use chrono::{Datelike, Local, NaiveDate};
+use rand::Rng;
+use serde::Deserialize;
+
+use crate::governor::{EffectBudget, EffectKind};
+use crate::hazard_rules::PlayClock;
+use crate::{BrickType, GameEntity, Settings, WINDOW_HEIGHT, WINDOW_WIDTH};
+
+// 节日主题只改变画面：砖块配色、背景飘落的粒子、主菜单标题装饰和坚硬砖块的金色外观。
+// 不影响任何游戏数值，排行榜成绩仍然可以互相比较
+const SEASONS_CONFIG: &str = include_str!("../assets/seasons.ron");
+
+// 背景粒子在最高特效档位下的数量，按特效预算缩减，最低档关闭
+const AMBIENT_PARTICLES: usize = 40;
+const AMBIENT_FALL_SPEED: (f32, f32) = (30.0, 70.0);
+// 左右摆动的幅度（像素/秒）和频率
+const AMBIENT_SWAY: f32 = 20.0;
+const AMBIENT_SWAY_FREQUENCY: f32 = 1.5;
+// 在砖块和球的后面
+const AMBIENT_Z: f32 = -2.0;
+// 金色外观的高光条（相对砖块的高度比例和颜色）
+const GOLDEN_SHINE_HEIGHT: f32 = 0.25;
+const GOLDEN_SHINE_COLOR: Color = Color::rgba(1.0, 0.95, 0.6, 0.7);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+pub enum Holiday {
+    Winter,
+    LunarNewYear,
+}
+
+impl Holiday {
+    // 主菜单标题上方的装饰文字
+    pub fn title_key(&self) -> &'static str {
+        match self {
+            Holiday::Winter => "holiday.winter",
+            Holiday::LunarNewYear => "holiday.lunar_new_year",
+        }
+    }
+
+    pub fn title_color(&self) -> Color {
+        match self {
+            Holiday::Winter => Color::rgb(0.75, 0.9, 1.0),
+            Holiday::LunarNewYear => Color::rgb(1.0, 0.8, 0.25),
+        }
+    }
+
+    // 冬季是雪花，农历新年是金色的火花
+    fn particle_color(&self) -> Color {
+        match self {
+            Holiday::Winter => Color::rgba(1.0, 1.0, 1.0, 0.7),
+            Holiday::LunarNewYear => Color::rgba(1.0, 0.8, 0.3, 0.6),
+        }
+    }
+
+    // 各类砖块仍然保持可以区分
+    fn brick_color(&self, brick_type: BrickType) -> Color {
+        match (self, brick_type) {
+            (Holiday::Winter, BrickType::Normal) => Color::rgb(0.8, 0.15, 0.2),
+            (Holiday::Winter, BrickType::Rubber) => Color::rgb(0.1, 0.45, 0.25),
+            (Holiday::Winter, BrickType::Ice) => Color::rgb(0.9, 0.97, 1.0),
+            (Holiday::LunarNewYear, BrickType::Normal) => Color::rgb(0.85, 0.1, 0.12),
+            (Holiday::LunarNewYear, BrickType::Rubber) => Color::rgb(0.95, 0.5, 0.15),
+            (_, BrickType::Hard) => Color::rgb(0.85, 0.65, 0.15),
+            (_, brick_type) => brick_type.color(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+pub struct MonthDay {
+    pub month: u32,
+    pub day: u32,
+}
+
+#[derive(Debug, Clone, Copy, Deserialize)]
+pub struct SeasonRange {
+    pub holiday: Holiday,
+    pub start: MonthDay,
+    pub end: MonthDay,
+}
+
+impl SeasonRange {
+    // 包含首尾两天；start 晚于 end 时跨年
+    pub fn contains(&self, date: NaiveDate) -> bool {
+        let today = (date.month(), date.day());
+        let start = (self.start.month, self.start.day);
+        let end = (self.end.month, self.end.day);
+        if start <= end {
+            start <= today && today <= end
+        } else {
+            today >= start || today <= end
+        }
+    }
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct SeasonsConfig {
+    ranges: Vec<SeasonRange>,
+}
+
+impl SeasonsConfig {
+    fn load() -> Self {
+        match ron::from_str(SEASONS_CONFIG) {
+            Ok(config) => config,
+            Err(e) => {
+                eprintln!("Failed to parse seasonal themes: {}", e);
+                Self::default()
+            }
+        }
+    }
+}
+
+// 当前使用的节日主题；启动时按本机日期和设置确定，设置变化后重新确定
+#[derive(Resource, Debug)]
+pub struct SeasonalTheme {
+    pub holiday: Option<Holiday>,
+    today: NaiveDate,
+    ranges: Vec<SeasonRange>,
+}
+
+impl SeasonalTheme {
+    pub fn new(setting: Option<bool>) -> Self {
+        let mut theme = Self {
+            holiday: None,
+            today: Local::now().date_naive(),
+            ranges: SeasonsConfig::load().ranges,
+        };
+        theme.resolve(setting);
+        theme
+    }
+
+    // None 按日期；强制开启但今天不在任何范围内时使用第一个配置的主题
+    fn resolve(&mut self, setting: Option<bool>) {
+        let by_date = self.ranges.iter().find(|range| range.contains(self.today)).map(|range| range.holiday);
+        self.holiday = match setting {
+            Some(false) => None,
+            None => by_date,
+            Some(true) => by_date
+                .or_else(|| self.ranges.first().map(|range| range.holiday))
+                .or(Some(Holiday::Winter)),
+        };
+    }
+
+    pub fn brick_color(&self, brick_type: BrickType) -> Color {
+        match self.holiday {
+            Some(holiday) => holiday.brick_color(brick_type),
+            None => brick_type.color(),
+        }
+    }
+
+    // 节日期间坚硬砖块加一条金色高光（子精灵使用砖块的单位坐标）
+    pub fn decorate_brick(&self, brick: &mut EntityCommands, brick_type: BrickType) {
+        if self.holiday.is_none() || brick_type != BrickType::Hard {
+            return;
+        }
+        brick.with_children(|parent| {
+            parent.spawn(SpriteBundle {
+                sprite: Sprite {
+                    color: GOLDEN_SHINE_COLOR,
+                    ..default()
+                },
+                transform: Transform {
+                    translation: Vec3::new(0.0, 0.5 - GOLDEN_SHINE_HEIGHT, 0.05),
+                    scale: Vec3::new(0.9, GOLDEN_SHINE_HEIGHT, 1.0),
+                    ..default()
+                },
+                ..default()
+            });
+        });
+    }
+}
+
+pub fn sync_seasonal_theme(settings: Res<Settings>, mut theme: ResMut<SeasonalTheme>) {
+    if settings.is_changed() {
+        let previous = theme.holiday;
+        theme.bypass_change_detection().resolve(settings.holiday_theme);
+        if theme.holiday != previous {
+            theme.set_changed();
+        }
+    }
+}
+
+// 背景中飘落的粒子，只是装饰，不参与任何碰撞
+#[derive(Component)]
+pub struct AmbientParticle {
+    fall_speed: f32,
+    phase: f32,
+}
+
+// 游戏中维持一定数量的背景粒子：从顶部生成，落到底部后移除。
+// 数量随特效预算变化，减少动态效果或没有节日主题时逐渐清空
+pub fn ambient_particles(
+    mut commands: Commands,
+    time: Res<Time>,
+    clock: Res<PlayClock>,
+    theme: Res<SeasonalTheme>,
+    settings: Res<Settings>,
+    budget: Res<EffectBudget>,
+    mut particles: Query<(Entity, &mut Transform, &AmbientParticle)>,
+) {
+    let target = match theme.holiday {
+        Some(_) if !settings.reduce_motion => budget.particle_count(EffectKind::Minor, AMBIENT_PARTICLES),
+        _ => 0,
+    };
+    let bottom = -WINDOW_HEIGHT / 2.0;
+    let top = WINDOW_HEIGHT / 2.0;
+    let elapsed = clock.now();
+
+    let mut alive = 0;
+    for (entity, mut transform, particle) in particles.iter_mut() {
+        if transform.translation.y < bottom || alive >= target {
+            commands.entity(entity).despawn();
+            continue;
+        }
+        alive += 1;
+        transform.translation.y -= particle.fall_speed * time.delta_seconds();
+        transform.translation.x +=
+            (elapsed * AMBIENT_SWAY_FREQUENCY + particle.phase).sin() * AMBIENT_SWAY * time.delta_seconds();
+    }
+
+    let Some(holiday) = theme.holiday else {
+        return;
+    };
+    // 每帧最多补一个，粒子分散出现而不是成排落下
+    if alive < target {
+        let mut rng = rand::thread_rng();
+        let size = rng.gen_range(2.0..5.0);
+        commands.spawn((
+            SpriteBundle {
+                sprite: Sprite {
+                    color: holiday.particle_color(),
+                    ..default()
+                },
+                transform: Transform {
+                    translation: Vec3::new(
+                        rng.gen_range(-WINDOW_WIDTH / 2.0..WINDOW_WIDTH / 2.0),
+                        top + size,
+                        AMBIENT_Z,
+                    ),
+                    scale: Vec3::new(size, size, 1.0),
+                    ..default()
+                },
+                ..default()
+            },
+            AmbientParticle {
+                fall_speed: rng.gen_range(AMBIENT_FALL_SPEED.0..AMBIENT_FALL_SPEED.1),
+                phase: rng.gen_range(0.0..std::f32::consts::TAU),
+            },
+            GameEntity,
+        ));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn range(start: (u32, u32), end: (u32, u32)) -> SeasonRange {
+        SeasonRange {
+            holiday: Holiday::Winter,
+            start: MonthDay {
+                month: start.0,
+                day: start.1,
+            },
+            end: MonthDay { month: end.0, day: end.1 },
+        }
+    }
+
+    fn date(year: i32, month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(year, month, day).unwrap()
+    }
+
+    #[test]
+    fn range_within_a_year_includes_both_ends() {
+        let lunar = range((1, 21), (2, 20));
+        assert!(!lunar.contains(date(2025, 1, 20)));
+        assert!(lunar.contains(date(2025, 1, 21)));
+        assert!(lunar.contains(date(2025, 2, 1)));
+        assert!(lunar.contains(date(2025, 2, 20)));
+        assert!(!lunar.contains(date(2025, 2, 21)));
+        assert!(!lunar.contains(date(2025, 7, 1)));
+    }
+
+    #[test]
+    fn range_wrapping_the_new_year_covers_both_sides() {
+        let winter = range((12, 20), (1, 6));
+        assert!(!winter.contains(date(2024, 12, 19)));
+        assert!(winter.contains(date(2024, 12, 20)));
+        assert!(winter.contains(date(2024, 12, 31)));
+        assert!(winter.contains(date(2025, 1, 1)));
+        assert!(winter.contains(date(2025, 1, 6)));
+        assert!(!winter.contains(date(2025, 1, 7)));
+        assert!(!winter.contains(date(2025, 6, 15)));
+    }
+
+    #[test]
+    fn single_day_range_and_leap_day() {
+        let single = range((3, 14), (3, 14));
+        assert!(single.contains(date(2025, 3, 14)));
+        assert!(!single.contains(date(2025, 3, 13)));
+        assert!(!single.contains(date(2025, 3, 15)));
+
+        let end_of_february = range((2, 28), (3, 1));
+        assert!(end_of_february.contains(date(2024, 2, 29)));
+        assert!(!range((2, 1), (2, 28)).contains(date(2024, 2, 29)));
+    }
+
+    #[test]
+    fn shipped_config_parses() {
+        let ranges = SeasonsConfig::load().ranges;
+        assert_eq!(ranges.len(), 2);
+        assert!(ranges[0].contains(date(2025, 1, 1)));
+        assert_eq!(ranges[0].holiday, Holiday::Winter);
+    }
+}
@@ -0,0 +1,292 @@
+use bevy::prelude::Resource;
+use serde::{Deserialize, Serialize};
+
+use crate::governor::QualityTier;
+use crate::i18n::{Language, Locale};
+use crate::persistence::{self, load_versioned, save_versioned, Versioned};
+use crate::privacy::{self, ScorePrivacy};
+use crate::profiles::ProfilePaths;
+use crate::stick::StickTuning;
+use crate::Difficulty;
+
+// UI 缩放范围
+pub const MIN_UI_SCALE: f32 = 0.75;
+pub const MAX_UI_SCALE: f32 = 1.5;
+const UI_SCALE_STEPS: [f32; 7] = [0.75, 0.875, 1.0, 1.125, 1.25, 1.375, 1.5];
+
+// 本地持久化设置
+#[derive(Resource, Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Settings {
+    // None 表示根据窗口高度自动计算
+    pub ui_scale: Option<f32>,
+    // 是否显示个人最佳的幽灵挡板
+    pub ghost_enabled: bool,
+    // 上一局使用的难度
+    pub last_difficulty: Difficulty,
+    // 减少动态效果（例如用短暂停顿代替死亡慢镜头）
+    pub reduce_motion: bool,
+    // 单声道：关闭音效声像
+    pub mono_audio: bool,
+    // 是否显示左上角的事件栏
+    pub event_feed: bool,
+    // None 表示跟随系统语言
+    pub language: Option<Language>,
+    // 输入名字时使用街机式三字母输入（使用手柄时总是启用）
+    pub arcade_name_entry: bool,
+    // 手柄摇杆的死区、饱和点、曲线和平滑
+    pub stick: StickTuning,
+    // 特效质量，None 表示根据帧时间自动调整
+    pub quality: Option<QualityTier>,
+    // 节日主题，None 表示按日期自动开启
+    pub holiday_theme: Option<bool>,
+    // 提交分数时用名字、匿名代号还是只保存在本机
+    pub score_privacy: ScorePrivacy,
+    // 匿名代号由这个编号生成
+    pub anonymous_id: u32,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            ui_scale: None,
+            ghost_enabled: true,
+            last_difficulty: Difficulty::Medium,
+            reduce_motion: false,
+            mono_audio: false,
+            event_feed: true,
+            language: None,
+            arcade_name_entry: false,
+            stick: StickTuning::default(),
+            quality: None,
+            holiday_theme: None,
+            score_privacy: ScorePrivacy::Full,
+            anonymous_id: privacy::random_anonymous_id(),
+        }
+    }
+}
+
+impl Versioned for Settings {
+    const VERSION: u32 = 1;
+    const LABEL: &'static str = "settings";
+
+    fn migrate(version: u32, data: serde_json::Value) -> Result<serde_json::Value, String> {
+        match version {
+            // v0：没有外层格式的旧设置文件，之后新增的设置项由 serde(default) 补齐
+            0 => Ok(data),
+            _ => persistence::unknown_version(version),
+        }
+    }
+}
+
+impl Settings {
+    // 读取设置文件，失败时使用默认值
+    pub fn load(paths: &ProfilePaths) -> Self {
+        load_versioned(&paths.settings()).unwrap_or_default()
+    }
+
+    // 保存设置文件
+    pub fn save(&self, paths: &ProfilePaths) {
+        if let Err(e) = save_versioned(&paths.settings(), self) {
+            eprintln!("Failed to save settings: {}", e);
+        }
+    }
+
+    // 实际使用的界面语言
+    pub fn language(&self) -> Language {
+        self.language.unwrap_or_else(Language::detect)
+    }
+
+    // 在 自动 -> 各语言 之间循环切换
+    pub fn step_language(&mut self, direction: i32) {
+        let count = Language::ALL.len() as i32 + 1;
+        let current = match self.language {
+            None => 0,
+            Some(language) => Language::ALL.iter().position(|l| *l == language).map(|i| i as i32 + 1).unwrap_or(0),
+        };
+        let next = (current + direction).rem_euclid(count);
+        self.language = if next == 0 { None } else { Some(Language::ALL[(next - 1) as usize]) };
+    }
+
+    // 在 自动 -> 高 -> 中 -> 低 之间循环切换
+    pub fn step_quality(&mut self, direction: i32) {
+        let count = QualityTier::ALL.len() as i32 + 1;
+        let current = match self.quality {
+            None => 0,
+            Some(tier) => QualityTier::ALL.iter().position(|t| *t == tier).map(|i| i as i32 + 1).unwrap_or(0),
+        };
+        let next = (current + direction).rem_euclid(count);
+        self.quality = if next == 0 { None } else { Some(QualityTier::ALL[(next - 1) as usize]) };
+    }
+
+    // 在 按日期 -> 开 -> 关 之间循环切换
+    pub fn step_holiday_theme(&mut self, direction: i32) {
+        const OPTIONS: [Option<bool>; 3] = [None, Some(true), Some(false)];
+        let current = OPTIONS.iter().position(|option| *option == self.holiday_theme).unwrap_or(0) as i32;
+        self.holiday_theme = OPTIONS[(current + direction).rem_euclid(OPTIONS.len() as i32) as usize];
+    }
+
+    // 排行榜上代表这个档案的名字：匿名时是代号，否则是输入的名字
+    pub fn public_name(&self, entered: &str) -> String {
+        match self.score_privacy {
+            ScorePrivacy::Anonymous => privacy::anonymous_handle(self.anonymous_id),
+            ScorePrivacy::Full | ScorePrivacy::LocalOnly => entered.to_string(),
+        }
+    }
+
+    // 提交分数时使用的名字，只保存在本机时为 None
+    pub fn submission_name(&self, entered: &str) -> Option<String> {
+        match self.score_privacy {
+            ScorePrivacy::LocalOnly => None,
+            ScorePrivacy::Full | ScorePrivacy::Anonymous => Some(self.public_name(entered)),
+        }
+    }
+
+    // 实际生效的 UI 缩放
+    pub fn effective_ui_scale(&self, auto_scale: f32) -> f32 {
+        self.ui_scale
+            .unwrap_or(auto_scale)
+            .clamp(MIN_UI_SCALE, MAX_UI_SCALE)
+    }
+
+    // 在 自动 -> 0.75 -> ... -> 1.5 之间循环切换
+    pub fn step_ui_scale(&mut self, direction: i32) {
+        let count = UI_SCALE_STEPS.len() as i32 + 1;
+        let current = match self.ui_scale {
+            None => 0,
+            Some(scale) => UI_SCALE_STEPS
+                .iter()
+                .position(|step| (step - scale).abs() < 0.01)
+                .map(|i| i as i32 + 1)
+                .unwrap_or(0),
+        };
+        let next = (current + direction).rem_euclid(count);
+        self.ui_scale = if next == 0 {
+            None
+        } else {
+            Some(UI_SCALE_STEPS[(next - 1) as usize])
+        };
+    }
+}
+
+// 根据窗口高度计算自动缩放
+pub fn auto_ui_scale(window_height: f32, reference_height: f32) -> f32 {
+    (window_height / reference_height).clamp(MIN_UI_SCALE, MAX_UI_SCALE)
+}
+
+// 设置界面中的条目
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SettingsItem {
+    UiScale,
+    Ghost,
+    ReduceMotion,
+    MonoAudio,
+    EventFeed,
+    Language,
+    ArcadeNameEntry,
+    StickDeadzone,
+    StickSaturation,
+    StickCurve,
+    StickSmoothing,
+    Quality,
+    HolidayTheme,
+    ScorePrivacy,
+}
+
+impl SettingsItem {
+    pub const ALL: [SettingsItem; 14] = [
+        SettingsItem::UiScale,
+        SettingsItem::Ghost,
+        SettingsItem::ReduceMotion,
+        SettingsItem::MonoAudio,
+        SettingsItem::EventFeed,
+        SettingsItem::Language,
+        SettingsItem::ArcadeNameEntry,
+        SettingsItem::StickDeadzone,
+        SettingsItem::StickSaturation,
+        SettingsItem::StickCurve,
+        SettingsItem::StickSmoothing,
+        SettingsItem::Quality,
+        SettingsItem::HolidayTheme,
+        SettingsItem::ScorePrivacy,
+    ];
+
+    pub fn label_key(&self) -> &'static str {
+        match self {
+            SettingsItem::UiScale => "settings.ui_scale",
+            SettingsItem::Ghost => "settings.ghost",
+            SettingsItem::ReduceMotion => "settings.reduce_motion",
+            SettingsItem::MonoAudio => "settings.mono_audio",
+            SettingsItem::EventFeed => "settings.event_feed",
+            SettingsItem::Language => "settings.language",
+            SettingsItem::ArcadeNameEntry => "settings.arcade_name_entry",
+            SettingsItem::StickDeadzone => "settings.stick_deadzone",
+            SettingsItem::StickSaturation => "settings.stick_saturation",
+            SettingsItem::StickCurve => "settings.stick_curve",
+            SettingsItem::StickSmoothing => "settings.stick_smoothing",
+            SettingsItem::Quality => "settings.quality",
+            SettingsItem::HolidayTheme => "settings.holiday_theme",
+            SettingsItem::ScorePrivacy => "settings.score_privacy",
+        }
+    }
+
+    pub fn value_text(&self, settings: &Settings, auto_scale: f32, locale: &Locale) -> String {
+        match self {
+            SettingsItem::UiScale => match settings.ui_scale {
+                None => locale.tr("settings.auto_scale", &[("scale", &format!("{:.2}", auto_scale))]),
+                Some(scale) => format!("{}x", scale),
+            },
+            SettingsItem::Ghost => on_off(settings.ghost_enabled, locale),
+            SettingsItem::ReduceMotion => on_off(settings.reduce_motion, locale),
+            SettingsItem::MonoAudio => on_off(settings.mono_audio, locale),
+            SettingsItem::EventFeed => on_off(settings.event_feed, locale),
+            SettingsItem::Language => match settings.language {
+                None => locale.tr("settings.auto_language", &[("language", &Language::detect().native_name())]),
+                Some(language) => language.native_name().to_string(),
+            },
+            SettingsItem::ArcadeNameEntry => on_off(settings.arcade_name_entry, locale),
+            SettingsItem::StickDeadzone => format!("{:.0}%", settings.stick.deadzone * 100.0),
+            SettingsItem::StickSaturation => format!("{:.0}%", settings.stick.saturation * 100.0),
+            SettingsItem::StickCurve => locale.tr(settings.stick.curve.label_key(), &[]),
+            SettingsItem::StickSmoothing => {
+                if settings.stick.smoothing > 0.0 {
+                    format!("{:.0} ms", settings.stick.smoothing * 1000.0)
+                } else {
+                    on_off(false, locale)
+                }
+            }
+            SettingsItem::Quality => match settings.quality {
+                None => locale.tr("settings.auto_quality", &[]),
+                Some(tier) => locale.tr(tier.label_key(), &[]),
+            },
+            SettingsItem::HolidayTheme => match settings.holiday_theme {
+                None => locale.tr("settings.auto_holiday", &[]),
+                Some(enabled) => on_off(enabled, locale),
+            },
+            SettingsItem::ScorePrivacy => locale.tr(settings.score_privacy.label_key(), &[]),
+        }
+    }
+
+    pub fn adjust(&self, settings: &mut Settings, direction: i32) {
+        match self {
+            SettingsItem::UiScale => settings.step_ui_scale(direction),
+            SettingsItem::Ghost => settings.ghost_enabled = !settings.ghost_enabled,
+            SettingsItem::ReduceMotion => settings.reduce_motion = !settings.reduce_motion,
+            SettingsItem::MonoAudio => settings.mono_audio = !settings.mono_audio,
+            SettingsItem::EventFeed => settings.event_feed = !settings.event_feed,
+            SettingsItem::Language => settings.step_language(direction),
+            SettingsItem::ArcadeNameEntry => settings.arcade_name_entry = !settings.arcade_name_entry,
+            SettingsItem::StickDeadzone => settings.stick.step_deadzone(direction),
+            SettingsItem::StickSaturation => settings.stick.step_saturation(direction),
+            SettingsItem::StickCurve => settings.stick.toggle_curve(),
+            SettingsItem::StickSmoothing => settings.stick.step_smoothing(direction),
+            SettingsItem::Quality => settings.step_quality(direction),
+            SettingsItem::HolidayTheme => settings.step_holiday_theme(direction),
+            SettingsItem::ScorePrivacy => settings.score_privacy = settings.score_privacy.step(direction),
+        }
+    }
+}
+
+fn on_off(value: bool, locale: &Locale) -> String {
+    locale.tr(if value { "settings.on" } else { "settings.off" }, &[])
+}
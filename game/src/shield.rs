@@ -0,0 +1,369 @@
+use bevy::ecs::system::EntityCommands;
+use bevy::prelude::*;
+
+use crate::countdown::Countdown;
+use crate::fonts::{text_style, FontAssets, FontKind};
+use crate::hazard_rules::PlayClock;
+use crate::i18n::Locale;
+use crate::{BrickSpec, BrickType, GameEntity, GameplayEvent, Layout, PowerUpType, BRICK_COLUMNS, BRICK_ROWS, BRICK_SIZE};
+
+// 护盾发生器关出现的关卡
+pub const SHIELD_LEVEL: u32 = 5;
+// 发生器的耐久
+const GENERATOR_HEALTH: i32 = 3;
+// 全部发生器被摧毁后，剩余砖块的分数倍数和奖励时间（秒）
+const CLEARED_MULTIPLIER: u32 = 2;
+const BONUS_WINDOW: f32 = 30.0;
+// 发生器外框比砖块多出的宽度，以及闪烁的周期（秒）
+const OUTLINE_WIDTH: f32 = 4.0;
+const PULSE_PERIOD: f32 = 1.2;
+// 提示箭头与发生器中心的距离和上下浮动的幅度
+const ARROW_OFFSET: f32 = 38.0;
+const ARROW_BOB: f32 = 6.0;
+// "LOCKED" 提示的持续时间和上升速度
+const POPUP_LIFETIME: f32 = 0.8;
+const POPUP_RISE_SPEED: f32 = 50.0;
+
+const GENERATOR_COLOR: Color = Color::rgb(0.3, 0.9, 1.0);
+const SHIELD_OVERLAY_COLOR: Color = Color::rgba(0.3, 0.9, 1.0, 0.3);
+const LOCKED_COLOR: Color = Color::rgb(1.0, 0.4, 0.3);
+const WINDOW_COLOR: Color = Color::rgb(1.0, 0.85, 0.2);
+
+// 护盾发生器关的砖块：发生器让同一行的其他砖块无敌，发生器必须按顺序（从左到右）摧毁
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Shield {
+    Generator { order: usize },
+    Protected { row: usize },
+}
+
+// 发生器的闪烁外框
+#[derive(Component)]
+pub struct GeneratorOutline;
+
+// 受保护砖块上的护盾层，这一行的发生器被摧毁后隐藏
+#[derive(Component)]
+pub struct ShieldOverlay {
+    row: usize,
+}
+
+// 指向下一个可以摧毁的发生器的箭头
+#[derive(Component)]
+pub struct GeneratorArrow;
+
+#[derive(Component)]
+pub struct ShieldBannerText;
+
+#[derive(Component)]
+pub struct LockedPopup {
+    age: f32,
+}
+
+// 当前关卡的发生器顺序；普通关卡为空
+#[derive(Resource, Debug, Default)]
+pub struct GeneratorChain {
+    // rows[order] 是第 order 个发生器所在的行
+    rows: Vec<usize>,
+    // 下一个可以摧毁的发生器；之前的都已经被摧毁
+    next: usize,
+    // 全部摧毁后开始的奖励时间
+    window: Option<Countdown>,
+}
+
+impl GeneratorChain {
+    pub fn from_layout(layout: &Layout) -> Self {
+        let mut generators: Vec<(usize, usize)> = layout
+            .bricks
+            .iter()
+            .filter_map(|spec| Some((spec.generator?, spec.cell.0)))
+            .collect();
+        generators.sort_unstable();
+        Self {
+            rows: generators.into_iter().map(|(_, row)| row).collect(),
+            ..default()
+        }
+    }
+
+    pub fn is_active(&self) -> bool {
+        !self.rows.is_empty()
+    }
+
+    fn row_protected(&self, row: usize) -> bool {
+        self.rows[self.next.min(self.rows.len())..].contains(&row)
+    }
+
+    // 击中砖块时是否只反弹、不造成伤害；Some(true) 表示打在还没轮到的发生器上
+    pub fn deflects(&self, shield: Option<&Shield>) -> Option<bool> {
+        match shield? {
+            Shield::Generator { order } => (*order != self.next).then_some(true),
+            Shield::Protected { row } => self.row_protected(*row).then_some(false),
+        }
+    }
+
+    // 砖块分数的倍数
+    pub fn score_multiplier(&self) -> u32 {
+        if self.window.is_some() {
+            CLEARED_MULTIPLIER
+        } else {
+            1
+        }
+    }
+
+    // 奖励时间用完，这一关结束
+    pub fn window_expired(&self) -> bool {
+        self.window.is_some_and(|window| window.remaining() <= 0.0)
+    }
+}
+
+pub fn chain_active(chain: Res<GeneratorChain>) -> bool {
+    chain.is_active()
+}
+
+// 手工设计的护盾发生器关：三个发生器从左下到右上排成台阶，摧毁一个才能打到下一个所在的行
+pub fn shield_layout() -> Layout {
+    const GENERATORS: [(usize, usize); 3] = [(5, 1), (3, 5), (1, 8)];
+    let mut bricks = Vec::with_capacity(BRICK_ROWS * BRICK_COLUMNS);
+    for row in 0..BRICK_ROWS {
+        for col in 0..BRICK_COLUMNS {
+            let generator = GENERATORS.iter().position(|&cell| cell == (row, col));
+            let (brick_type, health) = match generator {
+                Some(_) => (BrickType::Hard, GENERATOR_HEALTH),
+                // 没有发生器的行两端是坚硬砖块
+                None if col == 0 || col == BRICK_COLUMNS - 1 => (BrickType::Hard, 2),
+                None => (BrickType::Normal, 1),
+            };
+            // 保底道具放在没有护盾的行里，一开始就能拿到
+            let guaranteed_drop = match (row, col) {
+                (4, 2) => Some(PowerUpType::MultiBall),
+                (2, 6) => Some(PowerUpType::LaserGun),
+                _ => None,
+            };
+            bricks.push(BrickSpec {
+                cell: (row, col),
+                brick_type,
+                health,
+                armor: None,
+                guaranteed_drop,
+                golden: false,
+                generator,
+            });
+        }
+    }
+    Layout { bricks, ..default() }
+}
+
+// 有发生器的行
+pub fn generator_rows(layout: &Layout) -> Vec<usize> {
+    layout
+        .bricks
+        .iter()
+        .filter(|spec| spec.generator.is_some())
+        .map(|spec| spec.cell.0)
+        .collect()
+}
+
+// 给发生器加上外框，给同一行的其他砖块加上护盾层（子实体使用砖块的单位坐标）
+pub fn attach(brick: &mut EntityCommands, spec: &BrickSpec, generator_rows: &[usize]) {
+    if let Some(order) = spec.generator {
+        let outline = Vec2::new(1.0 + OUTLINE_WIDTH * 2.0 / BRICK_SIZE.x, 1.0 + OUTLINE_WIDTH * 2.0 / BRICK_SIZE.y);
+        brick.insert(Shield::Generator { order }).with_children(|parent| {
+            parent.spawn((
+                SpriteBundle {
+                    sprite: Sprite {
+                        color: GENERATOR_COLOR,
+                        ..default()
+                    },
+                    transform: Transform {
+                        translation: Vec3::new(0.0, 0.0, -0.1),
+                        scale: outline.extend(1.0),
+                        ..default()
+                    },
+                    ..default()
+                },
+                GeneratorOutline,
+            ));
+        });
+    } else if generator_rows.contains(&spec.cell.0) {
+        let row = spec.cell.0;
+        brick.insert(Shield::Protected { row }).with_children(|parent| {
+            parent.spawn((
+                SpriteBundle {
+                    sprite: Sprite {
+                        color: SHIELD_OVERLAY_COLOR,
+                        ..default()
+                    },
+                    transform: Transform::from_xyz(0.0, 0.0, 0.1),
+                    ..default()
+                },
+                ShieldOverlay { row },
+            ));
+        });
+    }
+}
+
+// 箭头由两条斜线组成，尖端朝下指向发生器
+pub fn spawn_generator_arrow(commands: &mut Commands) {
+    commands
+        .spawn((SpatialBundle::from_transform(Transform::from_xyz(0.0, 0.0, 5.0)), GeneratorArrow, GameEntity))
+        .with_children(|parent| {
+            for side in [-1.0, 1.0] {
+                parent.spawn(SpriteBundle {
+                    sprite: Sprite {
+                        color: GENERATOR_COLOR,
+                        custom_size: Some(Vec2::new(18.0, 4.0)),
+                        ..default()
+                    },
+                    transform: Transform::from_xyz(side * 6.0, 6.0, 0.0)
+                        .with_rotation(Quat::from_rotation_z(side * std::f32::consts::FRAC_PI_4)),
+                    ..default()
+                });
+            }
+        });
+}
+
+// 提示文字放在状态栏下方
+pub fn spawn_shield_hud(commands: &mut Commands, fonts: &FontAssets) {
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    width: Val::Percent(100.0),
+                    top: Val::Px(50.0),
+                    justify_content: JustifyContent::Center,
+                    ..default()
+                },
+                ..default()
+            },
+            GameEntity,
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                TextBundle::from_section("", text_style(fonts, FontKind::Bold, 22.0, GENERATOR_COLOR)),
+                ShieldBannerText,
+            ));
+        });
+}
+
+// 本帧被打碎的发生器（命令已经应用）推进顺序；最后一个被摧毁时开始奖励时间
+pub fn advance_generator_chain(time: Res<Time>, mut chain: ResMut<GeneratorChain>, shields: Query<&Shield>) {
+    while chain.next < chain.rows.len() {
+        let next = chain.next;
+        if shields.iter().any(|shield| *shield == Shield::Generator { order: next }) {
+            break;
+        }
+        chain.next += 1;
+        if chain.next == chain.rows.len() {
+            chain.window = Some(Countdown::new(BONUS_WINDOW));
+        }
+    }
+    if let Some(window) = chain.window.as_mut() {
+        window.tick(time.delta_seconds());
+    }
+}
+
+// 发生器外框闪烁，箭头停在下一个发生器上方，失去保护的行隐藏护盾层
+pub fn animate_shields(
+    clock: Res<PlayClock>,
+    chain: Res<GeneratorChain>,
+    generators: Query<(&Shield, &Transform), Without<GeneratorArrow>>,
+    mut outlines: Query<&mut Sprite, With<GeneratorOutline>>,
+    mut overlays: Query<(&ShieldOverlay, &mut Visibility), Without<GeneratorArrow>>,
+    mut arrows: Query<(&mut Transform, &mut Visibility), With<GeneratorArrow>>,
+) {
+    let phase = (clock.now() / PULSE_PERIOD * std::f32::consts::TAU).sin() * 0.5 + 0.5;
+    for mut sprite in outlines.iter_mut() {
+        sprite.color.set_a(0.35 + 0.65 * phase);
+    }
+
+    for (overlay, mut visibility) in overlays.iter_mut() {
+        let target = if chain.row_protected(overlay.row) { Visibility::Inherited } else { Visibility::Hidden };
+        if *visibility != target {
+            *visibility = target;
+        }
+    }
+
+    let next = generators.iter().find_map(|(shield, transform)| {
+        (*shield == Shield::Generator { order: chain.next }).then_some(transform.translation)
+    });
+    for (mut transform, mut visibility) in arrows.iter_mut() {
+        match next {
+            Some(position) => {
+                transform.translation.x = position.x;
+                transform.translation.y = position.y + ARROW_OFFSET + ARROW_BOB * phase;
+                *visibility = Visibility::Inherited;
+            }
+            None => *visibility = Visibility::Hidden,
+        }
+    }
+}
+
+pub fn update_shield_hud(
+    chain: Res<GeneratorChain>,
+    locale: Res<Locale>,
+    mut banners: Query<&mut Text, With<ShieldBannerText>>,
+) {
+    let (message, color) = match &chain.window {
+        Some(window) => (
+            locale.tr(
+                "shield.window",
+                &[("multiplier", &CLEARED_MULTIPLIER), ("seconds", &(window.remaining().ceil() as u32))],
+            ),
+            WINDOW_COLOR,
+        ),
+        None => (
+            locale.tr("shield.banner", &[("destroyed", &chain.next), ("total", &chain.rows.len())]),
+            GENERATOR_COLOR,
+        ),
+    };
+    for mut text in banners.iter_mut() {
+        if text.sections[0].value != message {
+            text.sections[0].value = message.clone();
+        }
+        text.sections[0].style.color = color;
+    }
+}
+
+// 打在还没轮到的发生器上时在砖块位置显示 "LOCKED"
+pub fn show_locked_popups(
+    mut commands: Commands,
+    mut gameplay_events: EventReader<GameplayEvent>,
+    fonts: Res<FontAssets>,
+    locale: Res<Locale>,
+) {
+    for event in gameplay_events.read() {
+        let GameplayEvent::ShieldDeflected { position, locked: true } = *event else {
+            continue;
+        };
+        commands.spawn((
+            Text2dBundle {
+                text: Text::from_section(
+                    locale.tr("popup.locked", &[]),
+                    text_style(&fonts, FontKind::Bold, 20.0, LOCKED_COLOR),
+                ),
+                transform: Transform::from_translation(position + Vec3::new(0.0, 0.0, 5.0)),
+                ..default()
+            },
+            LockedPopup { age: 0.0 },
+            GameEntity,
+        ));
+    }
+}
+
+// 提示文字上升并淡出
+pub fn animate_locked_popups(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut popups: Query<(Entity, &mut LockedPopup, &mut Transform, &mut Text)>,
+) {
+    for (entity, mut popup, mut transform, mut text) in popups.iter_mut() {
+        popup.age += time.delta_seconds();
+        if popup.age >= POPUP_LIFETIME {
+            commands.entity(entity).despawn();
+            continue;
+        }
+        transform.translation.y += POPUP_RISE_SPEED * time.delta_seconds();
+        for section in text.sections.iter_mut() {
+            section.style.color.set_a(1.0 - popup.age / POPUP_LIFETIME);
+        }
+    }
+}
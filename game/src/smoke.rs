@@ -0,0 +1,625 @@
+use bevy::app::AppExit;
+use bevy::prelude::*;
+use bevy::window::PrimaryWindow;
+use std::collections::VecDeque;
+
+use crate::api::{ApiClient, CreateScoreRequest, LeaderboardResponse, Score as ApiScore};
+use crate::bonus::Orientation;
+use crate::hazard_rules::{GameTimers, PlayClock};
+use crate::launch::HeldBall;
+use crate::network::{ApiResponse, Network};
+use crate::resume::ResumeCountdown;
+use crate::{
+    Ball, DifficultySettings, GameEntity, GameOverAction, GameOverMenuState, GameState, KioskConfig, LeaderboardData,
+    Level, LevelTimer, Lives, NameInput, Paddle, PlayerName, PowerUpEffects, Score, PADDLE_SIZE, WINDOW_WIDTH,
+};
+
+// --smoke-test：不用键盘，按脚本走完主菜单 -> 输入名字 -> 选择难度 -> 暂停后重新开始 -> 球在飞行中反复暂停和继续
+// -> 游戏 -> 结算 -> 排行榜 -> 主菜单。
+// 每一帧都检查挡板数量：任何时候都不能多于一个，Playing 时恰好一个；继续前的倒计时也最多一个。
+// 任何一步超时或检查失败都以非零状态退出，供发布前在 CI 中运行。
+// 会写入当前档案的统计，应在干净的用户目录下运行
+pub const SMOKE_TEST_FLAG: &str = "--smoke-test";
+// 冒烟测试连接的服务器地址：本机的 discard 端口，提交只进入队列，不会写入真实的排行榜
+pub const SMOKE_API_URL: &str = "http://127.0.0.1:9/api";
+
+const SMOKE_NAME: &str = "SMOKE";
+// 普通步骤的超时（秒）
+const STEP_TIMEOUT: f32 = 15.0;
+// 自动挡板最多玩多久；没打完这一关也停下来，让球掉落进入结算
+const PLAY_BUDGET: f32 = 90.0;
+// 停止接球后到进入结算的超时
+const LOSE_TIMEOUT: f32 = 90.0;
+// 游戏中每隔多久按一次空格发射停在挡板上的球
+const LAUNCH_INTERVAL: f32 = 0.5;
+// 菜单中每个按键之间的间隔，让界面有时间切换
+const KEY_INTERVAL: f32 = 0.2;
+// 从暂停菜单继续的次数
+const PAUSE_CYCLES: u32 = 20;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Step {
+    MainMenu,
+    EnterName,
+    DifficultySelect,
+    PauseRestart,
+    PauseResume,
+    Play,
+    Lose,
+    GameOver,
+    Leaderboard,
+    BackToMenu,
+}
+
+enum Action {
+    Press(KeyCode),
+    Type(&'static str),
+}
+
+#[derive(Resource)]
+pub struct SmokeTest {
+    step: Step,
+    // 当前步骤已用的时间
+    elapsed: f32,
+    // 待执行的输入，按 KEY_INTERVAL 依次执行；离开 script_state 时丢弃
+    script: VecDeque<Action>,
+    script_state: Option<GameState>,
+    next_action: f32,
+    // 上一帧按下、这一帧松开的键
+    held: Option<KeyCode>,
+    canned_sent: bool,
+    // 已经从暂停菜单重新开始过
+    restarted: bool,
+    // 已经从暂停菜单继续的次数，以及第一次暂停时的分数、生命、这一局的实体数和局内计时
+    resumes: u32,
+    paused_snapshot: Option<(u32, u32, usize, GameTimers)>,
+}
+
+impl Default for SmokeTest {
+    fn default() -> Self {
+        Self {
+            step: Step::MainMenu,
+            elapsed: 0.0,
+            script: VecDeque::new(),
+            script_state: None,
+            next_action: KEY_INTERVAL,
+            held: None,
+            canned_sent: false,
+            restarted: false,
+            resumes: 0,
+            paused_snapshot: None,
+        }
+    }
+}
+
+impl SmokeTest {
+    fn advance(&mut self, step: Step) {
+        println!("smoke test: {:?} -> {:?}", self.step, step);
+        self.step = step;
+        self.elapsed = 0.0;
+    }
+
+    // 每次进入 state 只排一次输入，返回这次是否排入
+    fn queue(&mut self, state: GameState, actions: impl IntoIterator<Item = Action>) -> bool {
+        if self.script_state == Some(state) {
+            return false;
+        }
+        self.script_state = Some(state);
+        self.script = actions.into_iter().collect();
+        true
+    }
+
+    // 停在 state 期间反复按同一个键
+    fn repeat(&mut self, state: GameState, key: KeyCode) {
+        if self.script_state != Some(state) || self.script.is_empty() {
+            self.script_state = Some(state);
+            self.script = VecDeque::from([Action::Press(key)]);
+        }
+    }
+
+    fn timeout(&self) -> f32 {
+        match self.step {
+            Step::Play => PLAY_BUDGET + STEP_TIMEOUT,
+            Step::Lose => LOSE_TIMEOUT,
+            _ => STEP_TIMEOUT,
+        }
+    }
+}
+
+fn fail(message: &str) -> ! {
+    eprintln!("Smoke test failed: {}", message);
+    std::process::exit(1);
+}
+
+// 只有带 --smoke-test 启动时才启用
+pub struct SmokeTestPlugin {
+    pub enabled: bool,
+}
+
+impl Plugin for SmokeTestPlugin {
+    fn build(&self, app: &mut App) {
+        if !self.enabled {
+            return;
+        }
+        // 替换 NetworkPlugin 创建的连接，旧的后台线程随之退出
+        app.insert_resource(Network::spawn(ApiClient::with_base_url(SMOKE_API_URL)))
+            .init_resource::<SmokeTest>()
+            .add_systems(PreUpdate, inject_input.after(bevy::input::InputSystem))
+            .add_systems(Update, (drive_smoke_test, autopilot).chain());
+    }
+}
+
+// 在输入系统之后写入脚本按键，游戏系统读到的和真实按键一样。
+// 脚本按真实时间推进，游戏时间停止（继续前的倒计时）时也能继续按键
+fn inject_input(
+    mut smoke: ResMut<SmokeTest>,
+    mut keyboard: ResMut<ButtonInput<KeyCode>>,
+    mut characters: EventWriter<ReceivedCharacter>,
+    windows: Query<Entity, With<PrimaryWindow>>,
+    time: Res<Time<Real>>,
+) {
+    if let Some(key) = smoke.held.take() {
+        keyboard.release(key);
+    }
+
+    smoke.next_action -= time.delta_seconds();
+    if smoke.next_action > 0.0 {
+        return;
+    }
+    let Some(action) = smoke.script.pop_front() else {
+        return;
+    };
+    smoke.next_action = KEY_INTERVAL;
+    match action {
+        Action::Press(key) => {
+            keyboard.press(key);
+            smoke.held = Some(key);
+        }
+        Action::Type(text) => {
+            let Ok(window) = windows.get_single() else {
+                fail("no primary window to type into");
+            };
+            for ch in text.chars() {
+                characters.send(ReceivedCharacter {
+                    window,
+                    char: ch.to_string().into(),
+                });
+            }
+        }
+    }
+}
+
+fn canned_leaderboard() -> LeaderboardResponse {
+    let scores = (1..=3)
+        .map(|rank| ApiScore {
+            id: None,
+            player_name: format!("CANNED{}", rank),
+            score: 1000 / rank,
+            level: 4 - rank,
+            difficulty: "Medium".to_string(),
+            created_at: None,
+            rank: Some(rank),
+            season: None,
+            mode: None,
+            share_url: None,
+        })
+        .collect();
+    LeaderboardResponse {
+        scores,
+        total: 3,
+        limit: 10,
+        offset: 0,
+        next_cursor: None,
+    }
+}
+
+// 结算时最近一次提交必须是这一局：冒烟测试的名字、最终的分数和关卡、所选难度，并且不是补交的未完成局
+fn check_submission(
+    request: Option<CreateScoreRequest>,
+    score: u32,
+    level: u32,
+    difficulty: &str,
+) -> Result<(), String> {
+    let Some(request) = request else {
+        return Err("no score submission was enqueued".to_string());
+    };
+    let expected = (SMOKE_NAME, score, level, difficulty);
+    let actual = (request.player_name.as_str(), request.score, request.level, request.difficulty.as_str());
+    if actual != expected || request.abandoned {
+        return Err(format!("submitted {:?}, expected {:?}", request, expected));
+    }
+    Ok(())
+}
+
+// 按当前步骤推进脚本并检查结果
+fn drive_smoke_test(
+    time: Res<Time<Real>>,
+    state: Res<State<GameState>>,
+    mut smoke: ResMut<SmokeTest>,
+    network: Res<Network>,
+    (player_name, score, level, difficulty_settings): (Res<PlayerName>, Res<Score>, Res<Level>, Res<DifficultySettings>),
+    kiosk: Res<KioskConfig>,
+    leaderboard_data: Res<LeaderboardData>,
+    mut name_input: ResMut<NameInput>,
+    mut menu_state: ResMut<GameOverMenuState>,
+    mut responses: EventWriter<ApiResponse>,
+    mut exit: EventWriter<AppExit>,
+    game_entities: Query<(), With<GameEntity>>,
+    paddles: Query<(), With<Paddle>>,
+    (lives, free_balls, countdowns): (Res<Lives>, Query<(), (With<Ball>, Without<HeldBall>)>, Query<(), With<ResumeCountdown>>),
+    (play_clock, level_timer, power_effects): (Res<PlayClock>, Res<LevelTimer>, Res<PowerUpEffects>),
+) {
+    smoke.elapsed += time.delta_seconds();
+    if smoke.elapsed > smoke.timeout() {
+        fail(&format!("step {:?} timed out in state {:?}", smoke.step, state.get()));
+    }
+    let state = *state.get();
+    let paddle_count = paddles.iter().count();
+    if paddle_count > 1 || (state == GameState::Playing && paddle_count != 1) {
+        fail(&format!("{} paddles exist in state {:?} during step {:?}", paddle_count, state, smoke.step));
+    }
+    let countdown_count = countdowns.iter().count();
+    if countdown_count > 1 {
+        fail(&format!("{} resume countdowns exist in state {:?}", countdown_count, state));
+    }
+    // 状态已经切换，上一个界面没用完的输入作废
+    if smoke.script_state.is_some_and(|queued| queued != state) {
+        smoke.script.clear();
+        smoke.script_state = None;
+    }
+
+    match smoke.step {
+        Step::MainMenu => match state {
+            // 第一次启动还没有档案：新建一个
+            GameState::ProfileSelect => {
+                smoke.queue(
+                    state,
+                    [Action::Press(KeyCode::Enter), Action::Type(SMOKE_NAME), Action::Press(KeyCode::Enter)],
+                );
+            }
+            // 上次留下的未完成局直接丢弃
+            GameState::RecoverRun => {
+                smoke.queue(state, [Action::Press(KeyCode::Escape)]);
+            }
+            GameState::MainMenu => {
+                smoke.queue(state, [Action::Press(KeyCode::KeyN)]);
+            }
+            GameState::EnterName => smoke.advance(Step::EnterName),
+            _ => {}
+        },
+        Step::EnterName => match state {
+            // 输入框里预填了当前名字，先清空
+            GameState::EnterName
+                if smoke.queue(state, [Action::Type(SMOKE_NAME), Action::Press(KeyCode::Enter)]) =>
+            {
+                name_input.text.clear();
+            }
+            GameState::DifficultySelect => {
+                if player_name.0 != SMOKE_NAME {
+                    fail(&format!("player name is {:?} after typing {:?}", player_name.0, SMOKE_NAME));
+                }
+                smoke.advance(Step::DifficultySelect);
+            }
+            _ => {}
+        },
+        Step::DifficultySelect => match state {
+            GameState::DifficultySelect => {
+                smoke.queue(state, [Action::Press(KeyCode::Enter)]);
+            }
+            GameState::Playing | GameState::LevelInterlude => smoke.advance(Step::PauseRestart),
+            _ => {}
+        },
+        // 暂停菜单的 N 在同一帧清理旧的一局，下一帧生成新的一局
+        Step::PauseRestart => match state {
+            GameState::LevelInterlude => smoke.repeat(state, KeyCode::Space),
+            GameState::Playing if smoke.restarted => smoke.advance(Step::PauseResume),
+            GameState::Playing => {
+                smoke.queue(state, [Action::Press(KeyCode::Escape)]);
+            }
+            GameState::Paused if smoke.queue(state, [Action::Press(KeyCode::KeyN)]) => {
+                smoke.restarted = true;
+            }
+            _ => {}
+        },
+        // 发射后在球飞行中反复暂停和继续：从暂停返回只倒计时，不重新初始化这一局；
+        // 游戏时间从第一次暂停起一直停着，分数、生命、实体数和局内计时（游戏时钟、关卡和道具倒计时）都不能变
+        Step::PauseResume => match state {
+            GameState::Playing if smoke.resumes >= PAUSE_CYCLES && countdown_count == 0 => smoke.advance(Step::Play),
+            // 等最后一次继续的倒计时结束
+            GameState::Playing if smoke.resumes >= PAUSE_CYCLES => {}
+            GameState::Playing if smoke.paused_snapshot.is_none() && free_balls.is_empty() => {
+                smoke.repeat(state, KeyCode::Space);
+            }
+            GameState::Playing => smoke.repeat(state, KeyCode::Escape),
+            GameState::Paused => {
+                let timers = GameTimers::capture(&play_clock, &level_timer, &power_effects);
+                let snapshot = (score.0, lives.0, game_entities.iter().count(), timers);
+                match smoke.paused_snapshot {
+                    None => smoke.paused_snapshot = Some(snapshot),
+                    Some(before) if before != snapshot => {
+                        let moved = timers.compare(&before.3, true);
+                        fail(&format!(
+                            "state changed across pause and resume: {:?} -> {:?}, timers moved: {:?}",
+                            before, snapshot, moved
+                        ));
+                    }
+                    Some(_) => {}
+                }
+                if smoke.queue(state, [Action::Press(KeyCode::Escape)]) {
+                    smoke.resumes += 1;
+                }
+            }
+            _ => {}
+        },
+        Step::Play | Step::Lose => match state {
+            GameState::Playing | GameState::LevelInterlude | GameState::NextLevel | GameState::DeathSequence => {
+                if smoke.step == Step::Play && smoke.elapsed > PLAY_BUDGET {
+                    smoke.advance(Step::Lose);
+                }
+                // 发射停在挡板上的球、跳过关卡预览和死亡镜头
+                smoke.repeat(state, KeyCode::Space);
+                smoke.next_action = smoke.next_action.min(LAUNCH_INTERVAL);
+            }
+            // 打完一关已经走通了游戏流程，之后不再接球
+            GameState::Victory => {
+                if smoke.step == Step::Play {
+                    smoke.advance(Step::Lose);
+                }
+                smoke.repeat(state, KeyCode::Space);
+            }
+            GameState::GameOver => smoke.advance(Step::GameOver),
+            _ => {}
+        },
+        Step::GameOver => {
+            if state != GameState::GameOver {
+                if state == GameState::Leaderboard {
+                    smoke.advance(Step::Leaderboard);
+                }
+                return;
+            }
+            if smoke.queue(state, [Action::Press(KeyCode::Enter)]) {
+                let difficulty = difficulty_settings.difficulty.name();
+                if let Err(message) = check_submission(network.last_submission(), score.0, level.0, difficulty) {
+                    fail(&message);
+                }
+                let Some(index) = GameOverAction::available(&kiosk)
+                    .iter()
+                    .position(|action| *action == GameOverAction::Leaderboard)
+                else {
+                    fail("game over menu has no leaderboard option");
+                };
+                menu_state.selected = index;
+            }
+        }
+        Step::Leaderboard => {
+            if state == GameState::MainMenu {
+                smoke.advance(Step::BackToMenu);
+                return;
+            }
+            // 等连不上服务器的真实请求失败后再放入预设的榜单，之后检查界面收到了它
+            if !smoke.canned_sent {
+                if matches!(*leaderboard_data, LeaderboardData::Failed) {
+                    responses.send(ApiResponse::Leaderboard(Ok(canned_leaderboard())));
+                    smoke.canned_sent = true;
+                }
+                return;
+            }
+            match &*leaderboard_data {
+                LeaderboardData::Loaded(response) if response.total == canned_leaderboard().total => {
+                    smoke.queue(state, [Action::Press(KeyCode::Space)]);
+                }
+                LeaderboardData::Loaded(_) => fail("leaderboard screen did not show the canned data"),
+                _ => {}
+            }
+        }
+        Step::BackToMenu => {
+            if state != GameState::MainMenu || smoke.elapsed < 0.5 {
+                return;
+            }
+            let leaked = game_entities.iter().count() + paddle_count;
+            if leaked > 0 {
+                fail(&format!("{} gameplay entities leaked into the main menu", leaked));
+            }
+            println!("smoke test passed");
+            exit.send(AppExit);
+        }
+    }
+}
+
+// 自动挡板：游戏阶段跟着离挡板最近的球移动，失败阶段躲开球
+fn autopilot(
+    smoke: Res<SmokeTest>,
+    state: Res<State<GameState>>,
+    orientation: Res<Orientation>,
+    balls: Query<&Transform, (With<Ball>, Without<Paddle>)>,
+    mut paddles: Query<&mut Transform, With<Paddle>>,
+) {
+    if *state.get() != GameState::Playing {
+        return;
+    }
+    let sign = orientation.sign();
+    let Some(lowest) = balls.iter().min_by(|a, b| (a.translation.y * sign).total_cmp(&(b.translation.y * sign))) else {
+        return;
+    };
+    let max_x = WINDOW_WIDTH / 2.0 - PADDLE_SIZE.x / 2.0;
+    for mut paddle in paddles.iter_mut() {
+        let target = match smoke.step {
+            Step::Play => lowest.translation.x,
+            _ if lowest.translation.x > 0.0 => -max_x,
+            _ => max_x,
+        };
+        paddle.translation.x = target.clamp(-max_x, max_x);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Ball;
+    use bevy::time::TimeUpdateStrategy;
+    use std::time::Duration;
+
+    #[test]
+    fn each_state_queues_its_script_once() {
+        let mut smoke = SmokeTest::default();
+        assert!(smoke.queue(GameState::MainMenu, [Action::Press(KeyCode::KeyN)]));
+        assert!(!smoke.queue(GameState::MainMenu, [Action::Press(KeyCode::KeyN)]));
+        assert_eq!(smoke.script.len(), 1);
+        assert!(smoke.queue(GameState::EnterName, [Action::Type(SMOKE_NAME), Action::Press(KeyCode::Enter)]));
+        assert_eq!(smoke.script.len(), 2);
+
+        // repeat 只在脚本用完后补上一次按键
+        smoke.repeat(GameState::Playing, KeyCode::Space);
+        assert_eq!(smoke.script.len(), 1);
+        smoke.repeat(GameState::Playing, KeyCode::Space);
+        assert_eq!(smoke.script.len(), 1);
+        smoke.script.clear();
+        smoke.repeat(GameState::Playing, KeyCode::Space);
+        assert!(matches!(smoke.script.front(), Some(Action::Press(KeyCode::Space))));
+    }
+
+    #[test]
+    fn advancing_restarts_the_step_timeout() {
+        let mut smoke = SmokeTest { elapsed: 10.0, ..default() };
+        smoke.advance(Step::Play);
+        assert_eq!(smoke.elapsed, 0.0);
+        assert_eq!(smoke.timeout(), PLAY_BUDGET + STEP_TIMEOUT);
+        smoke.advance(Step::Lose);
+        assert_eq!(smoke.timeout(), LOSE_TIMEOUT);
+        smoke.advance(Step::Leaderboard);
+        assert_eq!(smoke.timeout(), STEP_TIMEOUT);
+    }
+
+    // 每帧按下的键和收到的字符
+    #[derive(Resource, Default)]
+    struct Seen(Vec<(Vec<KeyCode>, String)>);
+
+    fn record_input(
+        keyboard: Res<ButtonInput<KeyCode>>,
+        mut characters: EventReader<ReceivedCharacter>,
+        mut seen: ResMut<Seen>,
+    ) {
+        let text = characters.read().map(|event| event.char.as_str()).collect();
+        seen.0.push((keyboard.get_pressed().copied().collect(), text));
+    }
+
+    #[test]
+    fn scripted_keys_are_held_for_one_frame_and_spaced_apart() {
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins)
+            .insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_secs_f32(KEY_INTERVAL)))
+            .init_resource::<ButtonInput<KeyCode>>()
+            .add_event::<ReceivedCharacter>()
+            .init_resource::<Seen>()
+            .insert_resource(SmokeTest {
+                script: VecDeque::from([
+                    Action::Press(KeyCode::Enter),
+                    Action::Type("AB"),
+                    Action::Press(KeyCode::Escape),
+                ]),
+                ..default()
+            })
+            .add_systems(Update, (inject_input, record_input).chain());
+        app.world.spawn((Window::default(), PrimaryWindow));
+
+        for _ in 0..6 {
+            app.update();
+        }
+        // 第一帧没有经过时间；之后每隔 KEY_INTERVAL 执行一个动作，按键下一帧松开
+        let expected = [
+            (vec![], ""),
+            (vec![KeyCode::Enter], ""),
+            (vec![], "AB"),
+            (vec![KeyCode::Escape], ""),
+            (vec![], ""),
+            (vec![], ""),
+        ];
+        let seen = &app.world.resource::<Seen>().0;
+        let seen: Vec<(Vec<KeyCode>, &str)> = seen.iter().map(|(keys, text)| (keys.clone(), text.as_str())).collect();
+        assert_eq!(seen, expected);
+        assert!(app.world.resource::<SmokeTest>().script.is_empty());
+    }
+
+    fn autopilot_app(step: Step, orientation: Orientation) -> App {
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins)
+            .insert_state(GameState::Playing)
+            .insert_resource(orientation)
+            .insert_resource(SmokeTest { step, ..default() })
+            .add_systems(Update, autopilot);
+        app
+    }
+
+    fn paddle_x_with_balls(app: &mut App, balls: &[Vec2]) -> f32 {
+        for position in balls {
+            app.world.spawn((Ball { velocity: Vec2::ZERO }, Transform::from_translation(position.extend(0.0))));
+        }
+        let paddle = app.world.spawn((Paddle, Transform::default())).id();
+        app.update();
+        app.world.get::<Transform>(paddle).unwrap().translation.x
+    }
+
+    #[test]
+    fn autopilot_follows_the_ball_nearest_the_paddle() {
+        let balls = [Vec2::new(-120.0, 200.0), Vec2::new(80.0, -150.0)];
+        let mut app = autopilot_app(Step::Play, Orientation::Normal);
+        assert_eq!(paddle_x_with_balls(&mut app, &balls), 80.0);
+        // 翻转后挡板在上方
+        let mut app = autopilot_app(Step::Play, Orientation::Flipped);
+        assert_eq!(paddle_x_with_balls(&mut app, &balls), -120.0);
+        // 不会移出场地
+        let mut app = autopilot_app(Step::Play, Orientation::Normal);
+        let max_x = WINDOW_WIDTH / 2.0 - PADDLE_SIZE.x / 2.0;
+        assert_eq!(paddle_x_with_balls(&mut app, &[Vec2::new(WINDOW_WIDTH, 0.0)]), max_x);
+    }
+
+    #[test]
+    fn autopilot_dodges_the_ball_once_it_should_lose() {
+        let max_x = WINDOW_WIDTH / 2.0 - PADDLE_SIZE.x / 2.0;
+        let mut app = autopilot_app(Step::Lose, Orientation::Normal);
+        assert_eq!(paddle_x_with_balls(&mut app, &[Vec2::new(30.0, 0.0)]), -max_x);
+        let mut app = autopilot_app(Step::Lose, Orientation::Normal);
+        assert_eq!(paddle_x_with_balls(&mut app, &[Vec2::new(-30.0, 0.0)]), max_x);
+    }
+
+    #[test]
+    fn autopilot_leaves_the_paddle_alone_outside_play() {
+        let mut app = autopilot_app(Step::Play, Orientation::Normal);
+        app.world.insert_resource(NextState(Some(GameState::Paused)));
+        assert_eq!(paddle_x_with_balls(&mut app, &[Vec2::new(100.0, 0.0)]), 0.0);
+    }
+
+    fn submission(name: &str, score: u32, level: u32, difficulty: &str, abandoned: bool) -> CreateScoreRequest {
+        serde_json::from_value(serde_json::json!({
+            "player_name": name,
+            "score": score,
+            "level": level,
+            "difficulty": difficulty,
+            "abandoned": abandoned,
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn the_submission_must_match_the_finished_run() {
+        let run = submission(SMOKE_NAME, 1200, 2, "Medium", false);
+        assert_eq!(check_submission(Some(run.clone()), 1200, 2, "Medium"), Ok(()));
+        assert!(check_submission(None, 1200, 2, "Medium").is_err());
+        assert!(check_submission(Some(run.clone()), 1300, 2, "Medium").is_err());
+        assert!(check_submission(Some(run.clone()), 1200, 3, "Medium").is_err());
+        assert!(check_submission(Some(run), 1200, 2, "Hard").is_err());
+        assert!(check_submission(Some(submission("Player", 1200, 2, "Medium", false)), 1200, 2, "Medium").is_err());
+        assert!(check_submission(Some(submission(SMOKE_NAME, 1200, 2, "Medium", true)), 1200, 2, "Medium").is_err());
+    }
+
+    // 冒烟测试把请求发到本机的 discard 端口：提交只进入队列，并记录下来供结算时检查
+    #[test]
+    fn submissions_are_recorded_without_a_real_leaderboard() {
+        let network = Network::spawn(ApiClient::with_base_url(SMOKE_API_URL));
+        assert!(network.last_submission().is_none());
+        network.send(crate::network::ApiCommand::SubmitScore(submission(SMOKE_NAME, 500, 1, "Easy", false)));
+        let recorded = network.last_submission().expect("the submission is recorded");
+        assert_eq!((recorded.player_name.as_str(), recorded.score), (SMOKE_NAME, 500));
+        assert_eq!(canned_leaderboard().scores.len(), canned_leaderboard().total);
+    }
+}
@@ -0,0 +1,311 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::fonts::{text_style, FontAssets, FontKind};
+use crate::i18n::Locale;
+use crate::settings::Settings;
+
+// 可选的内侧死区、外侧饱和点和平滑时间
+const DEADZONE_STEPS: [f32; 7] = [0.0, 0.05, 0.1, 0.15, 0.2, 0.25, 0.3];
+const SATURATION_STEPS: [f32; 7] = [0.7, 0.75, 0.8, 0.85, 0.9, 0.95, 1.0];
+const SMOOTHING_STEPS: [f32; 5] = [0.0, 0.03, 0.06, 0.1, 0.15];
+
+const PREVIEW_WIDTH: f32 = 240.0;
+const PREVIEW_HEIGHT: f32 = 8.0;
+const RAW_PREVIEW_COLOR: Color = Color::rgb(0.6, 0.6, 0.6);
+const PROCESSED_PREVIEW_COLOR: Color = Color::rgb(1.0, 0.85, 0.2);
+
+// 摇杆推动量到挡板速度的映射曲线
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ResponseCurve {
+    Linear,
+    // 平方：小幅推动更精细，推满仍是全速
+    Squared,
+}
+
+impl ResponseCurve {
+    pub fn label_key(&self) -> &'static str {
+        match self {
+            ResponseCurve::Linear => "settings.curve_linear",
+            ResponseCurve::Squared => "settings.curve_squared",
+        }
+    }
+}
+
+// 手柄摇杆的调校参数，保存在设置中
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(default)]
+pub struct StickTuning {
+    // 推动量低于它时视为没有推动
+    pub deadzone: f32,
+    // 推动量达到它时视为推满
+    pub saturation: f32,
+    pub curve: ResponseCurve,
+    // 挡板目标速度的平滑时间常数（秒），0 表示不平滑
+    pub smoothing: f32,
+}
+
+impl Default for StickTuning {
+    fn default() -> Self {
+        Self {
+            deadzone: 0.1,
+            saturation: 0.95,
+            curve: ResponseCurve::Linear,
+            smoothing: 0.0,
+        }
+    }
+}
+
+impl StickTuning {
+    pub fn step_deadzone(&mut self, direction: i32) {
+        self.deadzone = step(&DEADZONE_STEPS, self.deadzone, direction);
+    }
+
+    pub fn step_saturation(&mut self, direction: i32) {
+        self.saturation = step(&SATURATION_STEPS, self.saturation, direction);
+    }
+
+    pub fn toggle_curve(&mut self) {
+        self.curve = match self.curve {
+            ResponseCurve::Linear => ResponseCurve::Squared,
+            ResponseCurve::Squared => ResponseCurve::Linear,
+        };
+    }
+
+    pub fn step_smoothing(&mut self, direction: i32) {
+        self.smoothing = step(&SMOOTHING_STEPS, self.smoothing, direction);
+    }
+}
+
+// 在固定档位中循环切换，当前值不在档位中时从第一档开始
+fn step(steps: &[f32], current: f32, direction: i32) -> f32 {
+    let count = steps.len() as i32;
+    let index = steps.iter().position(|value| (value - current).abs() < 0.001);
+    let next = match index {
+        Some(index) => (index as i32 + direction).rem_euclid(count),
+        None => 0,
+    };
+    steps[next as usize]
+}
+
+// 把摇杆原始值（-1..1）按死区、饱和点和曲线处理成挡板速度比例（-1..1）
+pub fn process_axis(raw: f32, tuning: &StickTuning) -> f32 {
+    let magnitude = raw.abs().min(1.0);
+    if magnitude <= tuning.deadzone {
+        return 0.0;
+    }
+    let range = (tuning.saturation - tuning.deadzone).max(f32::EPSILON);
+    let t = ((magnitude - tuning.deadzone) / range).min(1.0);
+    let shaped = match tuning.curve {
+        ResponseCurve::Linear => t,
+        ResponseCurve::Squared => t * t,
+    };
+    shaped * raw.signum()
+}
+
+// 指数平滑：current 以时间常数 time_constant 逼近 target
+pub fn smooth(current: f32, target: f32, time_constant: f32, delta: f32) -> f32 {
+    if time_constant <= 0.0 {
+        return target;
+    }
+    current + (target - current) * (1.0 - (-delta / time_constant).exp())
+}
+
+// 所有已连接手柄中推动量最大的左摇杆横向值
+pub fn raw_stick_x(gamepads: &Gamepads, axes: &Axis<GamepadAxis>) -> f32 {
+    gamepads
+        .iter()
+        .filter_map(|gamepad| axes.get(GamepadAxis::new(gamepad, GamepadAxisType::LeftStickX)))
+        .fold(0.0, |best: f32, value| if value.abs() > best.abs() { value } else { best })
+}
+
+// 手柄控制挡板时平滑后的速度比例
+#[derive(Resource, Default)]
+pub struct StickSmoothing(pub f32);
+
+// 设置界面中显示摇杆原始值和处理后数值的预览条
+#[derive(Component)]
+pub struct StickPreviewBar {
+    processed: bool,
+}
+
+// 两条预览条：上面是原始值，下面是处理后的值，从中间向两侧填充
+pub fn spawn_stick_preview(parent: &mut ChildBuilder, fonts: &FontAssets, locale: &Locale) {
+    parent
+        .spawn(NodeBundle {
+            style: Style {
+                margin: UiRect::top(Val::Px(16.0)),
+                display: Display::Grid,
+                grid_template_columns: vec![GridTrack::auto(), GridTrack::px(PREVIEW_WIDTH)],
+                column_gap: Val::Px(12.0),
+                row_gap: Val::Px(6.0),
+                align_items: AlignItems::Center,
+                ..default()
+            },
+            ..default()
+        })
+        .with_children(|parent| {
+            for (label_key, processed, color) in [
+                ("settings.stick_raw", false, RAW_PREVIEW_COLOR),
+                ("settings.stick_processed", true, PROCESSED_PREVIEW_COLOR),
+            ] {
+                parent.spawn(TextBundle::from_section(
+                    locale.tr(label_key, &[]),
+                    text_style(fonts, FontKind::Regular, 16.0, Color::rgb(0.6, 0.6, 0.6)),
+                ));
+                parent
+                    .spawn(NodeBundle {
+                        style: Style {
+                            width: Val::Px(PREVIEW_WIDTH),
+                            height: Val::Px(PREVIEW_HEIGHT),
+                            ..default()
+                        },
+                        background_color: BackgroundColor(Color::rgb(0.25, 0.25, 0.3)),
+                        ..default()
+                    })
+                    .with_children(|parent| {
+                        parent.spawn((
+                            NodeBundle {
+                                style: Style {
+                                    position_type: PositionType::Absolute,
+                                    left: Val::Percent(50.0),
+                                    width: Val::Percent(0.0),
+                                    height: Val::Percent(100.0),
+                                    ..default()
+                                },
+                                background_color: BackgroundColor(color),
+                                ..default()
+                            },
+                            StickPreviewBar { processed },
+                        ));
+                    });
+            }
+        });
+}
+
+// 根据当前摇杆位置更新预览条
+pub fn update_stick_preview(
+    gamepads: Res<Gamepads>,
+    axes: Res<Axis<GamepadAxis>>,
+    settings: Res<Settings>,
+    mut query: Query<(&StickPreviewBar, &mut Style)>,
+) {
+    let raw = raw_stick_x(&gamepads, &axes);
+    let processed = process_axis(raw, &settings.stick);
+    for (bar, mut style) in query.iter_mut() {
+        let value = if bar.processed { processed } else { raw };
+        style.left = Val::Percent(50.0 + value.min(0.0) * 50.0);
+        style.width = Val::Percent(value.abs() * 50.0);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tuning(deadzone: f32, saturation: f32, curve: ResponseCurve) -> StickTuning {
+        StickTuning {
+            deadzone,
+            saturation,
+            curve,
+            smoothing: 0.0,
+        }
+    }
+
+    fn close(a: f32, b: f32) -> bool {
+        (a - b).abs() < 1e-5
+    }
+
+    #[test]
+    fn inside_deadzone_is_zero() {
+        for curve in [ResponseCurve::Linear, ResponseCurve::Squared] {
+            let tuning = tuning(0.2, 0.9, curve);
+            assert_eq!(process_axis(0.0, &tuning), 0.0);
+            assert_eq!(process_axis(0.2, &tuning), 0.0);
+            assert_eq!(process_axis(-0.15, &tuning), 0.0);
+        }
+    }
+
+    #[test]
+    fn saturation_and_beyond_is_full_speed() {
+        for curve in [ResponseCurve::Linear, ResponseCurve::Squared] {
+            let tuning = tuning(0.1, 0.8, curve);
+            assert!(close(process_axis(0.8, &tuning), 1.0));
+            assert!(close(process_axis(1.0, &tuning), 1.0));
+            // 有些手柄会报告略大于 1 的值
+            assert!(close(process_axis(-1.2, &tuning), -1.0));
+        }
+    }
+
+    #[test]
+    fn linear_curve_rescales_between_deadzone_and_saturation() {
+        let tuning = tuning(0.1, 0.9, ResponseCurve::Linear);
+        assert!(close(process_axis(0.5, &tuning), 0.5));
+        assert!(close(process_axis(0.3, &tuning), 0.25));
+        assert!(close(process_axis(-0.7, &tuning), -0.75));
+    }
+
+    #[test]
+    fn squared_curve_is_finer_near_the_centre_and_keeps_the_sign() {
+        let tuning = tuning(0.1, 0.9, ResponseCurve::Squared);
+        assert!(close(process_axis(0.5, &tuning), 0.25));
+        assert!(close(process_axis(-0.3, &tuning), -0.0625));
+        let linear = StickTuning {
+            curve: ResponseCurve::Linear,
+            ..tuning
+        };
+        for raw in [0.2, 0.4, 0.6, 0.8] {
+            assert!(process_axis(raw, &tuning) <= process_axis(raw, &linear));
+        }
+    }
+
+    #[test]
+    fn output_is_monotonic_for_every_curve() {
+        for curve in [ResponseCurve::Linear, ResponseCurve::Squared] {
+            let tuning = tuning(0.15, 0.85, curve);
+            let mut previous = 0.0;
+            for step in 0..=100 {
+                let value = process_axis(step as f32 / 100.0, &tuning);
+                assert!(value >= previous);
+                previous = value;
+            }
+        }
+    }
+
+    #[test]
+    fn deadzone_equal_to_saturation_does_not_divide_by_zero() {
+        let tuning = tuning(0.3, 0.3, ResponseCurve::Linear);
+        assert_eq!(process_axis(0.3, &tuning), 0.0);
+        assert!(close(process_axis(0.31, &tuning), 1.0));
+    }
+
+    #[test]
+    fn smoothing_approaches_the_target_without_overshoot() {
+        // 不平滑时直接到达目标，键盘输入走这条路
+        assert_eq!(smooth(0.0, 1.0, 0.0, 1.0 / 120.0), 1.0);
+        // 经过一个时间常数约到 63%
+        assert!(close(smooth(0.0, 1.0, 0.1, 0.1), 1.0 - (-1.0f32).exp()));
+        let mut value = 0.0;
+        for _ in 0..120 {
+            let next = smooth(value, -1.0, 0.06, 1.0 / 120.0);
+            assert!(next <= value && next >= -1.0);
+            value = next;
+        }
+        assert!(value < -0.99);
+    }
+
+    #[test]
+    fn steps_cycle_and_reset_unknown_values() {
+        let mut tuning = StickTuning::default();
+        tuning.step_deadzone(1);
+        assert!(close(tuning.deadzone, 0.15));
+        tuning.deadzone = 0.3;
+        tuning.step_deadzone(1);
+        assert_eq!(tuning.deadzone, 0.0);
+        tuning.step_deadzone(-1);
+        assert!(close(tuning.deadzone, 0.3));
+        tuning.smoothing = 0.42;
+        tuning.step_smoothing(1);
+        assert_eq!(tuning.smoothing, 0.0);
+    }
+}
@@ -0,0 +1,113 @@
+use bevy::prelude::*;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+use crate::bonus::Orientation;
+use crate::curse::Curse;
+use crate::game_rng::GameRng;
+use crate::governor::EffectBudget;
+use crate::hazard_rules::{HazardRules, LastPaddleContact, PlayClock};
+use crate::safe_despawn::SafeDespawnExt;
+use crate::team_colors::{HotTeam, TeamTint};
+use crate::weekly_event::EventRun;
+use crate::{
+    drop_powerup, spawn_particles, Ball, Brick, GameplayEvent, RunSeed, Score, BALL_SIZE, BALL_SPEED, BRICK_SIZE,
+};
+
+// 粘性砖块抓住球的时间（秒）
+pub const STICKY_HOLD_TIME: f32 = 0.6;
+// 被抓住时的抖动幅度和频率
+const WOBBLE_AMPLITUDE: f32 = 2.0;
+const WOBBLE_FREQUENCY: f32 = 40.0;
+// 弹出方向与水平方向的最小夹角，避免球贴着砖块横向来回
+const MIN_EJECT_ANGLE: f32 = 20.0 * std::f32::consts::PI / 180.0;
+
+// 被粘性砖块抓住的球：by 为抓住它的砖块，release_at 按 PlayClock 计
+#[derive(Component)]
+pub struct StuckBall {
+    pub by: Entity,
+    pub release_at: f32,
+    // 被抓住时的位置，抖动围绕它进行
+    pub anchor: Vec3,
+}
+
+// 随机的弹出方向，上下两个扇区都可能
+fn eject_direction(rng: &mut impl Rng) -> Vec2 {
+    let angle = rng.gen_range(MIN_EJECT_ANGLE..std::f32::consts::PI - MIN_EJECT_ANGLE);
+    let direction = Vec2::from_angle(angle);
+    if rng.gen_bool(0.5) {
+        direction
+    } else {
+        -direction
+    }
+}
+
+// 从砖块中心沿 direction 移出多远球才不再与砖块重叠
+fn exit_distance(direction: Vec2) -> f32 {
+    let half_extent = (BRICK_SIZE + BALL_SIZE) / 2.0;
+    let along_x = if direction.x.abs() > f32::EPSILON { half_extent.x / direction.x.abs() } else { f32::MAX };
+    let along_y = if direction.y.abs() > f32::EPSILON { half_extent.y / direction.y.abs() } else { f32::MAX };
+    along_x.min(along_y) + 1.0
+}
+
+// 抖动被抓住的球，到时间后以满速向随机方向弹出，砖块受到 1 点伤害
+pub fn release_stuck_balls(
+    mut commands: Commands,
+    play_clock: Res<PlayClock>,
+    run_seed: Res<RunSeed>,
+    mut score: ResMut<Score>,
+    mut gameplay_events: EventWriter<GameplayEvent>,
+    mut balls: Query<(Entity, &mut Transform, &mut Ball, &StuckBall, Option<&LastPaddleContact>)>,
+    mut bricks: Query<(&Transform, &mut Brick, Option<&TeamTint>), Without<Ball>>,
+    budget: Res<EffectBudget>,
+    orientation: Res<Orientation>,
+    event_run: Res<EventRun>,
+    hot_team: Res<HotTeam>,
+    curse: Res<Curse>,
+    mut game_rng: ResMut<GameRng>,
+) {
+    let now = play_clock.now();
+    for (ball_entity, mut ball_transform, mut ball, stuck, contact) in balls.iter_mut() {
+        // 抓住它的砖块已经被激光打碎（包括本帧刚打碎、还没删除的），直接朝挡板一侧释放
+        let brick = bricks.get_mut(stuck.by).ok().filter(|(_, brick, _)| brick.health > 0);
+        let Some((brick_transform, mut brick, tint)) = brick else {
+            ball.velocity = orientation.apply(Vec2::new(0.0, -BALL_SPEED));
+            ball_transform.translation = stuck.anchor;
+            commands.entity(ball_entity).remove::<StuckBall>();
+            continue;
+        };
+
+        if now < stuck.release_at {
+            let wobble = (now * WOBBLE_FREQUENCY).sin() * WOBBLE_AMPLITUDE;
+            ball_transform.translation = stuck.anchor + Vec3::new(wobble, -wobble * 0.5, 0.0);
+            continue;
+        }
+
+        // 用本局种子和释放时间决定方向，同一局的表现可以复现
+        let mut rng = StdRng::seed_from_u64(run_seed.0 ^ u64::from(now.to_bits()) ^ ball_entity.to_bits());
+        let direction = eject_direction(&mut rng);
+        ball.velocity = direction * BALL_SPEED;
+        ball_transform.translation = brick_transform.translation + (direction * exit_distance(direction)).extend(0.0);
+        commands.entity(ball_entity).remove::<StuckBall>();
+
+        brick.health -= 1;
+        if brick.health <= 0 {
+            commands.safe_despawn_recursive(stuck.by);
+            let points = curse.apply(hot_team.apply(brick.points(), tint));
+            score.0 += points;
+            spawn_particles(&mut commands, &budget, brick_transform.translation, brick_transform.scale);
+            gameplay_events.send(GameplayEvent::BrickDestroyed {
+                brick_type: brick.brick_type,
+                points,
+                position: brick_transform.translation,
+                combo: HazardRules::counts_for_combo(contact, &play_clock),
+            });
+            drop_powerup(&mut commands, &mut game_rng, &brick, brick_transform.translation, &event_run, &curse);
+        } else {
+            gameplay_events.send(GameplayEvent::BrickDamaged {
+                position: brick_transform.translation,
+            });
+            brick.shade *= 0.8;
+        }
+    }
+}
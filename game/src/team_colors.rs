@@ -0,0 +1,266 @@
+use bevy::prelude::*;
+
+use crate::countdown::Countdown;
+use crate::fonts::{text_style, FontAssets, FontKind};
+use crate::i18n::Locale;
+use crate::weekly_event::EventRun;
+use crate::GameEntity;
+
+// 冷热两队每隔这么久交换一次，交换前几秒横幅闪烁提醒
+const SWAP_INTERVAL: f32 = 20.0;
+const WARNING_TIME: f32 = 3.0;
+const WARNING_FLASHES_PER_SECOND: f32 = 3.0;
+// 每队连续占几列，两队按列交替
+const COLUMNS_PER_BAND: usize = 2;
+// 砖块颜色向队伍颜色靠近的比例
+const TINT_STRENGTH: f32 = 0.45;
+
+const WARM_COLOR: Color = Color::rgb(1.0, 0.45, 0.2);
+const COOL_COLOR: Color = Color::rgb(0.2, 0.6, 1.0);
+
+// 冷暖砖块活动中砖块所属的队伍，生成时按列分配
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TeamTint {
+    Warm,
+    Cool,
+}
+
+impl TeamTint {
+    pub fn for_column(column: usize) -> Self {
+        if (column / COLUMNS_PER_BAND).is_multiple_of(2) {
+            TeamTint::Warm
+        } else {
+            TeamTint::Cool
+        }
+    }
+
+    fn other(self) -> Self {
+        match self {
+            TeamTint::Warm => TeamTint::Cool,
+            TeamTint::Cool => TeamTint::Warm,
+        }
+    }
+
+    fn color(self) -> Color {
+        match self {
+            TeamTint::Warm => WARM_COLOR,
+            TeamTint::Cool => COOL_COLOR,
+        }
+    }
+
+    fn label_key(self) -> &'static str {
+        match self {
+            TeamTint::Warm => "team.warm",
+            TeamTint::Cool => "team.cool",
+        }
+    }
+
+    // 在类型颜色上叠加队伍颜色，受损变暗和光照在这之后计算
+    pub fn tint(self, base: Color) -> Color {
+        let team = self.color();
+        let mix = |from: f32, to: f32| from + (to - from) * TINT_STRENGTH;
+        Color::rgb(mix(base.r(), team.r()), mix(base.g(), team.g()), mix(base.b(), team.b()))
+    }
+}
+
+// 当前得分加倍的队伍和距离下次交换的时间；另一队得分减半
+#[derive(Resource, Debug, Clone, Copy)]
+pub struct HotTeam {
+    hot: TeamTint,
+    timer: Countdown,
+}
+
+impl Default for HotTeam {
+    fn default() -> Self {
+        Self {
+            hot: TeamTint::Warm,
+            timer: Countdown::new(SWAP_INTERVAL),
+        }
+    }
+}
+
+impl HotTeam {
+    // 前进 delta 秒，到时间时交换冷热队伍
+    pub fn tick(&mut self, delta: f32) {
+        if self.timer.tick(delta) {
+            self.hot = self.hot.other();
+            self.timer.restart(SWAP_INTERVAL);
+        }
+    }
+
+    pub fn warning(&self) -> bool {
+        self.timer.remaining() <= WARNING_TIME
+    }
+
+    // 打碎砖块的得分：热队翻倍，冷队减半；没有队伍的砖块不变
+    pub fn apply(&self, points: u32, tint: Option<&TeamTint>) -> u32 {
+        match tint {
+            Some(&tint) if tint == self.hot => points * 2,
+            Some(_) => points / 2,
+            None => points,
+        }
+    }
+}
+
+// 顶部的冷热队伍横幅
+#[derive(Component)]
+pub struct TeamBanner;
+
+pub fn spawn_team_banner(commands: &mut Commands, fonts: &FontAssets) {
+    commands.spawn((
+        TextBundle::from_section("", text_style(fonts, FontKind::Bold, 20.0, Color::WHITE)).with_style(Style {
+            position_type: PositionType::Absolute,
+            top: Val::Px(40.0),
+            left: Val::Percent(30.0),
+            ..default()
+        }),
+        TeamBanner,
+        GameEntity,
+    ));
+}
+
+// 只在活动局计时，系统只在 Playing 状态运行，暂停时不会交换
+pub fn tick_hot_team(time: Res<Time>, event_run: Res<EventRun>, mut hot_team: ResMut<HotTeam>) {
+    if event_run.team_colors() {
+        hot_team.tick(time.delta_seconds());
+    }
+}
+
+// 横幅显示热队、冷队和交换倒计时，交换前闪烁
+pub fn update_team_banner(
+    hot_team: Res<HotTeam>,
+    locale: Res<Locale>,
+    mut banners: Query<&mut Text, With<TeamBanner>>,
+) {
+    let Ok(mut text) = banners.get_single_mut() else {
+        return;
+    };
+    let remaining = hot_team.timer.remaining();
+    let value = locale.tr(
+        "team.banner",
+        &[
+            ("hot", &locale.tr(hot_team.hot.label_key(), &[])),
+            ("cold", &locale.tr(hot_team.hot.other().label_key(), &[])),
+            ("seconds", &(remaining.ceil() as u32)),
+        ],
+    );
+    if text.sections[0].value != value {
+        text.sections[0].value = value;
+    }
+    let dimmed = hot_team.warning() && ((remaining * WARNING_FLASHES_PER_SECOND * 2.0) as u32).is_multiple_of(2);
+    let color = if dimmed { Color::rgba(1.0, 1.0, 1.0, 0.3) } else { hot_team.hot.color() };
+    if text.sections[0].style.color != color {
+        text.sections[0].style.color = color;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::weekly_event::{ActiveEvent, EventModifier};
+    use crate::GameState;
+    use std::time::Duration;
+
+    #[test]
+    fn teams_alternate_in_two_column_bands() {
+        use TeamTint::{Cool, Warm};
+        let teams: Vec<TeamTint> = (0..6).map(TeamTint::for_column).collect();
+        assert_eq!(teams, [Warm, Warm, Cool, Cool, Warm, Warm]);
+    }
+
+    #[test]
+    fn hot_bricks_score_double_and_cold_bricks_half() {
+        let mut hot_team = HotTeam::default();
+        assert_eq!(hot_team.apply(50, Some(&TeamTint::Warm)), 100);
+        assert_eq!(hot_team.apply(50, Some(&TeamTint::Cool)), 25);
+        // 减半向下取整；没有队伍的砖块（不可破坏的砖块、非活动局）不变
+        assert_eq!(hot_team.apply(15, Some(&TeamTint::Cool)), 7);
+        assert_eq!(hot_team.apply(50, None), 50);
+
+        hot_team.tick(SWAP_INTERVAL);
+        assert_eq!(hot_team.apply(50, Some(&TeamTint::Warm)), 25);
+        assert_eq!(hot_team.apply(50, Some(&TeamTint::Cool)), 100);
+    }
+
+    #[test]
+    fn teams_swap_every_twenty_seconds_after_a_three_second_warning() {
+        let mut hot_team = HotTeam::default();
+        hot_team.tick(SWAP_INTERVAL - WARNING_TIME - 0.1);
+        assert!(!hot_team.warning());
+        hot_team.tick(0.2);
+        assert!(hot_team.warning());
+        assert_eq!(hot_team.hot, TeamTint::Warm);
+
+        hot_team.tick(WARNING_TIME);
+        assert_eq!(hot_team.hot, TeamTint::Cool);
+        assert!(!hot_team.warning());
+        assert_eq!(hot_team.timer.remaining(), SWAP_INTERVAL);
+
+        hot_team.tick(SWAP_INTERVAL);
+        assert_eq!(hot_team.hot, TeamTint::Warm);
+    }
+
+    #[test]
+    fn tint_pulls_the_brick_color_toward_the_team() {
+        let base = Color::rgb(0.5, 0.5, 0.5);
+        let warm = TeamTint::Warm.tint(base);
+        let cool = TeamTint::Cool.tint(base);
+        assert!(warm.r() > base.r() && warm.b() < base.b());
+        assert!(cool.b() > base.b() && cool.r() < base.r());
+    }
+
+    fn hot_team_app(modifier: EventModifier) -> App {
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins)
+            .insert_resource(bevy::time::TimeUpdateStrategy::ManualDuration(Duration::from_secs(1)))
+            .init_state::<GameState>()
+            .insert_resource(EventRun(Some(ActiveEvent {
+                id: "2025-W07".to_string(),
+                modifier,
+            })))
+            .init_resource::<HotTeam>()
+            .add_systems(Update, tick_hot_team.run_if(in_state(GameState::Playing)));
+        app.world.resource_mut::<Time<Virtual>>().set_max_delta(Duration::from_secs(2));
+        app.world.resource_mut::<NextState<GameState>>().set(GameState::Playing);
+        app
+    }
+
+    fn remaining(app: &App) -> f32 {
+        app.world.resource::<HotTeam>().timer.remaining()
+    }
+
+    // 暂停期间横幅倒计时不动，恢复后从原处继续
+    #[test]
+    fn pausing_freezes_the_swap_timer() {
+        let mut app = hot_team_app(EventModifier::TeamColors);
+        // 第一帧进入 Playing，第一帧的时间差为 0
+        for _ in 0..11 {
+            app.update();
+        }
+        let before_pause = remaining(&app);
+        assert_eq!(before_pause, SWAP_INTERVAL - 10.0);
+
+        app.world.resource_mut::<NextState<GameState>>().set(GameState::Paused);
+        for _ in 0..30 {
+            app.update();
+        }
+        assert_eq!(remaining(&app), before_pause);
+        assert_eq!(app.world.resource::<HotTeam>().hot, TeamTint::Warm);
+
+        app.world.resource_mut::<NextState<GameState>>().set(GameState::Playing);
+        for _ in 0..12 {
+            app.update();
+        }
+        assert_eq!(app.world.resource::<HotTeam>().hot, TeamTint::Cool);
+    }
+
+    #[test]
+    fn other_event_runs_never_swap() {
+        let mut app = hot_team_app(EventModifier::DoubleDrops);
+        for _ in 0..60 {
+            app.update();
+        }
+        assert_eq!(remaining(&app), SWAP_INTERVAL);
+        assert_eq!(app.world.resource::<HotTeam>().hot, TeamTint::Warm);
+    }
+}
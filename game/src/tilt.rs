@@ -0,0 +1,192 @@
+use bevy::prelude::*;
+
+use crate::{collide, Collision, BALL_SIZE, BALL_SPEED};
+
+// 挡板最大倾斜角度和从水平转到最大角度所需的时间
+const MAX_TILT: f32 = 15.0 * std::f32::consts::PI / 180.0;
+const TILT_TIME: f32 = 0.15;
+// 挡板速度传给球的比例，以及加上之后球离开挡板时与水平方向的最小夹角
+const SPIN_FACTOR: f32 = 0.3;
+const MIN_BOUNCE_ANGLE: f32 = 15.0 * std::f32::consts::PI / 180.0;
+
+// 挡板倾斜状态；角度为正时右端抬高，球向左偏
+#[derive(Component, Default)]
+pub struct PaddleTilt {
+    pub angle: f32,
+    // 最近按下的方向键（-1 左，1 右），同时按住两个方向键时朝它倾斜
+    last_pressed: f32,
+}
+
+impl PaddleTilt {
+    // 记录最近按下的方向键
+    pub fn press(&mut self, direction: f32) {
+        self.last_pressed = direction;
+    }
+
+    // 以固定角速度转向目标方向（-1 向右偏、0 水平、1 向左偏）
+    pub fn update(&mut self, target: f32, delta: f32) {
+        let target_angle = target * MAX_TILT;
+        let step = MAX_TILT / TILT_TIME * delta;
+        self.angle += (target_angle - self.angle).clamp(-step, step);
+    }
+
+    // 同时按住两个方向键时的倾斜方向：朝最近按下的一侧偏转
+    pub fn both_keys_target(&self) -> f32 {
+        -self.last_pressed
+    }
+}
+
+// 倾斜后挡板在水平方向上的半宽，用于限制在墙内
+pub fn tilted_half_width(size: Vec2, angle: f32) -> f32 {
+    size.x / 2.0 * angle.cos() + size.y / 2.0 * angle.sin().abs()
+}
+
+// 把世界坐标转到挡板坐标系（以挡板中心为原点、沿挡板表面为 x 轴）
+fn to_paddle_local(point: Vec2, paddle_center: Vec2, angle: f32) -> Vec2 {
+    Vec2::from_angle(-angle).rotate(point - paddle_center)
+}
+
+// 一次挡板反弹的结果
+pub struct PaddleBounce {
+    // 反弹后的速度（世界坐标）
+    pub velocity: Vec2,
+    // 击中位置相对挡板中心的偏移，-1 为左端、1 为右端
+    pub offset: f32,
+    // 是否打在挡板表面上（而不是两侧）
+    pub on_surface: bool,
+}
+
+// 球与（可能倾斜的）挡板碰撞：在挡板坐标系里做 AABB 检测和反弹，再把速度转回世界坐标
+// 没有碰撞时返回 None
+pub fn paddle_bounce(ball_position: Vec2, velocity: Vec2, paddle_center: Vec2, paddle_size: Vec2, angle: f32) -> Option<PaddleBounce> {
+    let local_position = to_paddle_local(ball_position, paddle_center, angle);
+    let collision = collide(local_position.extend(0.0), BALL_SIZE, Vec3::ZERO, paddle_size)?;
+
+    let mut local_velocity = Vec2::from_angle(-angle).rotate(velocity);
+    // 球心可能略微超出挡板两端，偏移超过 1 时沿表面的速度也相应更大
+    let hit_position = local_position.x / (paddle_size.x / 2.0);
+    let on_surface = matches!(collision, Collision::Top | Collision::Bottom);
+    if on_surface {
+        // 沿挡板法线反弹，并根据击中位置调整沿挡板表面的速度
+        local_velocity.y = local_velocity.y.abs();
+        local_velocity.x = hit_position * BALL_SPEED * 0.75;
+    } else {
+        local_velocity.x = -local_velocity.x;
+    }
+    Some(PaddleBounce {
+        velocity: Vec2::from_angle(angle).rotate(local_velocity),
+        offset: hit_position.clamp(-1.0, 1.0),
+        on_surface,
+    })
+}
+
+// 移动中的挡板把一部分速度传给弹开的球，可以把球“搓”出更斜的角度。速度大小不变，
+// 方向限制在离水平方向至少 MIN_BOUNCE_ANGLE，球总是向远离挡板的方向飞出
+pub fn add_spin(velocity: Vec2, paddle_velocity: f32) -> Vec2 {
+    let spun = Vec2::new(velocity.x + paddle_velocity * SPIN_FACTOR, velocity.y);
+    let angle = spun.y.atan2(spun.x).clamp(MIN_BOUNCE_ANGLE, std::f32::consts::PI - MIN_BOUNCE_ANGLE);
+    Vec2::from_angle(angle) * velocity.length()
+}
+
+// 沿挡板法线方向（挡板水平时竖直向上）以给定速度弹回
+pub fn straight_return(speed: f32, angle: f32) -> Vec2 {
+    Vec2::from_angle(angle).rotate(Vec2::Y) * speed
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SIZE: Vec2 = Vec2::new(100.0, 20.0);
+
+    #[test]
+    fn flat_paddle_half_width_is_half_its_length() {
+        assert_eq!(tilted_half_width(SIZE, 0.0), 50.0);
+    }
+
+    #[test]
+    fn tilting_either_way_gives_the_same_half_width() {
+        assert_eq!(tilted_half_width(SIZE, MAX_TILT), tilted_half_width(SIZE, -MAX_TILT));
+    }
+
+    #[test]
+    fn tilted_half_width_includes_the_raised_corner() {
+        let expected = 50.0 * MAX_TILT.cos() + 10.0 * MAX_TILT.sin();
+        assert!((tilted_half_width(SIZE, MAX_TILT) - expected).abs() < 1e-4);
+        // 15 度时抬起的一角比缩短的长度多，倾斜后的挡板反而更宽
+        assert!(tilted_half_width(SIZE, MAX_TILT) > tilted_half_width(SIZE, 0.0));
+    }
+
+    #[test]
+    fn upright_paddle_half_width_is_half_its_height() {
+        let half_width = tilted_half_width(SIZE, std::f32::consts::FRAC_PI_2);
+        assert!((half_width - 10.0).abs() < 1e-4);
+    }
+
+    fn bounce_angle(velocity: Vec2) -> f32 {
+        velocity.y.atan2(velocity.x)
+    }
+
+    #[test]
+    fn spin_keeps_the_speed() {
+        let velocity = Vec2::new(120.0, 380.0);
+        for paddle_velocity in [-2000.0, -300.0, 0.0, 300.0, 2000.0] {
+            let spun = add_spin(velocity, paddle_velocity);
+            assert!((spun.length() - velocity.length()).abs() < 1e-3, "paddle velocity {}", paddle_velocity);
+        }
+    }
+
+    #[test]
+    fn spin_follows_the_paddle_direction() {
+        let velocity = Vec2::new(0.0, BALL_SPEED);
+        assert!(add_spin(velocity, 300.0).x > 0.0);
+        assert!(add_spin(velocity, -300.0).x < 0.0);
+        assert_eq!(add_spin(velocity, 0.0), Vec2::from_angle(std::f32::consts::FRAC_PI_2) * BALL_SPEED);
+    }
+
+    // 无论挡板多快、球原来多平，离开挡板时与水平方向至少 15 度，并且总是向上
+    #[test]
+    fn spin_angle_is_clamped_between_15_and_165_degrees() {
+        let low = MIN_BOUNCE_ANGLE;
+        let high = std::f32::consts::PI - MIN_BOUNCE_ANGLE;
+        for velocity in [Vec2::new(BALL_SPEED, 1.0), Vec2::new(-BALL_SPEED, 1.0), Vec2::new(0.0, BALL_SPEED)] {
+            for paddle_velocity in [-5000.0, -1000.0, 1000.0, 5000.0] {
+                let angle = bounce_angle(add_spin(velocity, paddle_velocity));
+                let degrees = angle.to_degrees();
+                assert!((low - 1e-4..=high + 1e-4).contains(&angle), "{} with {}: {}", velocity, paddle_velocity, degrees);
+            }
+        }
+        assert!((bounce_angle(add_spin(Vec2::new(0.0, BALL_SPEED), 1e6)) - low).abs() < 1e-4);
+        assert!((bounce_angle(add_spin(Vec2::new(0.0, BALL_SPEED), -1e6)) - high).abs() < 1e-4);
+        // 向下的速度也会被转到向上
+        assert!(add_spin(Vec2::new(100.0, -100.0), 0.0).y > 0.0);
+    }
+
+    #[test]
+    fn surface_hit_speed_along_the_paddle_depends_on_the_hit_position() {
+        let paddle_center = Vec2::new(0.0, -250.0);
+        let velocity = Vec2::new(0.0, -BALL_SPEED);
+        for (x, offset) in [(-50.0, -1.0), (0.0, 0.0), (25.0, 0.5)] {
+            let ball = Vec2::new(x, paddle_center.y + SIZE.y / 2.0 + BALL_SIZE.y / 2.0 - 1.0);
+            let bounce = paddle_bounce(ball, velocity, paddle_center, SIZE, 0.0).unwrap();
+            assert!(bounce.on_surface);
+            assert_eq!(bounce.offset, offset);
+            assert_eq!(bounce.velocity, Vec2::new(offset * BALL_SPEED * 0.75, BALL_SPEED));
+        }
+        let far = Vec2::new(0.0, paddle_center.y + 100.0);
+        assert!(paddle_bounce(far, velocity, paddle_center, SIZE, 0.0).is_none());
+    }
+
+    #[test]
+    fn tilted_paddle_returns_the_ball_along_its_normal() {
+        let paddle_center = Vec2::new(0.0, -250.0);
+        let normal = Vec2::from_angle(MAX_TILT).rotate(Vec2::Y);
+        let ball = paddle_center + normal * (SIZE.y / 2.0 + BALL_SIZE.y / 2.0 - 1.0);
+        let bounce = paddle_bounce(ball, Vec2::new(0.0, -BALL_SPEED), paddle_center, SIZE, MAX_TILT).unwrap();
+        assert!(bounce.on_surface);
+        // 打在中心，沿表面的速度为 0，只剩法线方向；右端抬高时球向左偏
+        assert!(bounce.velocity.x < 0.0);
+        assert!((bounce.velocity.normalize() - normal).length() < 1e-4);
+        assert!((straight_return(BALL_SPEED, MAX_TILT) - normal * BALL_SPEED).length() < 1e-3);
+    }
+}
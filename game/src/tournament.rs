@@ -0,0 +1,689 @@
+use bevy::prelude::*;
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+
+use crate::api::CreateScoreRequest;
+use crate::fonts::{text_style, FontAssets, FontKind};
+use crate::i18n::Locale;
+use crate::level_select::GameMode;
+use crate::network::{ApiCommand, Network};
+use crate::profile::Profile;
+use crate::privacy::ScorePrivacy;
+use crate::profiles::ProfilePaths;
+use crate::{
+    is_name_char, normalize_player_name, start_new_run, Difficulty, DifficultySettings, GameState, NameInput,
+    RunResources, Score, Settings, MAX_NAME_LENGTH,
+};
+
+// 淘汰赛的人数
+const MIN_PLAYERS: usize = 3;
+const MAX_PLAYERS: usize = 8;
+// 淘汰赛固定使用的难度，同一场对决的两个人条件完全相同
+const TOURNAMENT_DIFFICULTY: Difficulty = Difficulty::Medium;
+// 提交时的模式名
+pub use breakout_shared::TOURNAMENT_MODE;
+
+const BACKGROUND_COLOR: Color = Color::rgb(0.1, 0.1, 0.15);
+const MATCH_COLOR: Color = Color::rgba(0.2, 0.2, 0.25, 0.8);
+const WINNER_COLOR: Color = Color::rgb(1.0, 0.85, 0.2);
+const LOSER_COLOR: Color = Color::rgb(0.45, 0.45, 0.45);
+const NEXT_MATCH_COLOR: Color = Color::rgb(0.2, 0.8, 0.8);
+const HINT_COLOR: Color = Color::rgb(0.6, 0.6, 0.6);
+const ERROR_COLOR: Color = Color::rgb(0.9, 0.3, 0.3);
+
+// 对阵表中的一场对决：两个人轮流打同一个种子的同一关，分数高的晋级
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BracketMatch {
+    // 选手在 Bracket::players 中的下标；第一轮的空位是轮空，之后的空位等待上一轮的胜者
+    pub players: [Option<usize>; 2],
+    pub scores: [Option<u32>; 2],
+    pub winner: Option<usize>,
+    pub seed: u64,
+}
+
+impl BracketMatch {
+    fn new(players: [Option<usize>; 2]) -> Self {
+        Self {
+            players,
+            scores: [None, None],
+            winner: None,
+            seed: rand::random(),
+        }
+    }
+}
+
+// 整个对阵表，保存在档案中，中断后可以从档案继续
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Bracket {
+    // 提交到服务器时附带的比赛 id，也用来查询这场比赛的全部成绩
+    pub id: String,
+    pub players: Vec<String>,
+    // rounds[0] 是第一轮，最后一轮只有决赛一场
+    pub rounds: Vec<Vec<BracketMatch>>,
+    pub submitted: bool,
+}
+
+// 轮到的一局：第几轮、第几场和哪一边的选手
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Turn {
+    round: usize,
+    index: usize,
+    slot: usize,
+}
+
+impl Bracket {
+    // 按输入顺序排位：人数不足 2 的幂时，排在前面的选手第一轮轮空
+    fn new(players: Vec<String>) -> Self {
+        let size = players.len().next_power_of_two();
+        let byes = size - players.len();
+        let mut next_player = 0..players.len();
+        let first_round: Vec<BracketMatch> = (0..size / 2)
+            .map(|index| {
+                let first = next_player.next();
+                let second = if index < byes { None } else { next_player.next() };
+                BracketMatch::new([first, second])
+            })
+            .collect();
+
+        let mut rounds = vec![first_round];
+        while rounds.last().is_some_and(|round| round.len() > 1) {
+            let matches = rounds.last().map_or(0, |round| round.len() / 2);
+            rounds.push((0..matches).map(|_| BracketMatch::new([None, None])).collect());
+        }
+
+        let mut bracket = Self {
+            id: format!("{}-{:08x}", Utc::now().format("%Y%m%d"), rand::random::<u32>()),
+            players,
+            rounds,
+            submitted: false,
+        };
+        // 轮空的选手直接晋级
+        for index in 0..byes {
+            let winner = bracket.rounds[0][index].players[0];
+            bracket.decide(0, index, winner);
+        }
+        bracket
+    }
+
+    // 这一轮所有对决打的关卡：越往后越难
+    fn level(round: usize) -> u32 {
+        round as u32 + 1
+    }
+
+    // 按轮次顺序找到下一场还没打完的对决，先打第一个位置的选手
+    fn next_turn(&self) -> Option<Turn> {
+        self.rounds.iter().enumerate().find_map(|(round, matches)| {
+            matches.iter().enumerate().find_map(|(index, game)| {
+                if game.winner.is_some() || game.players.iter().any(Option::is_none) {
+                    return None;
+                }
+                let slot = game.scores.iter().position(Option::is_none)?;
+                Some(Turn { round, index, slot })
+            })
+        })
+    }
+
+    fn player_name(&self, player: Option<usize>) -> Option<&str> {
+        player.and_then(|player| self.players.get(player)).map(String::as_str)
+    }
+
+    // 记录一局的分数，两个人都打完后决出胜者；平分时换一个种子重打，返回 true
+    fn record(&mut self, turn: Turn, score: u32) -> bool {
+        let game = &mut self.rounds[turn.round][turn.index];
+        game.scores[turn.slot] = Some(score);
+        let [Some(first), Some(second)] = game.scores else {
+            return false;
+        };
+        if first == second {
+            game.scores = [None, None];
+            game.seed = rand::random();
+            return true;
+        }
+        let winner = if first > second { game.players[0] } else { game.players[1] };
+        self.decide(turn.round, turn.index, winner);
+        false
+    }
+
+    // 胜者进入下一轮对应的位置
+    fn decide(&mut self, round: usize, index: usize, winner: Option<usize>) {
+        self.rounds[round][index].winner = winner;
+        if let Some(next) = self.rounds.get_mut(round + 1) {
+            next[index / 2].players[index % 2] = winner;
+        }
+    }
+
+    fn champion(&self) -> Option<usize> {
+        self.rounds.last()?.first()?.winner
+    }
+}
+
+// 进行中的淘汰赛；turn 是正在进行的那一局，结束时把分数记到对阵表上
+#[derive(Resource, Default)]
+pub struct Tournament {
+    pub bracket: Option<Bracket>,
+    turn: Option<Turn>,
+    // 上一场平分，需要重打
+    tied: bool,
+}
+
+// 对阵表写回档案；None 表示比赛已经结束
+fn save_bracket(paths: &ProfilePaths, bracket: Option<&Bracket>) {
+    let mut profile = Profile::load(paths);
+    profile.tournament = bracket.cloned();
+    if let Err(e) = profile.save(paths) {
+        eprintln!("Failed to save profile: {}", e);
+    }
+}
+
+// 从主菜单进入淘汰赛：档案中有没打完的比赛就继续，否则先输入选手名字
+pub fn open_tournament(paths: &ProfilePaths, tournament: &mut Tournament) -> GameState {
+    *tournament = Tournament {
+        bracket: Profile::load(paths).tournament,
+        ..default()
+    };
+    match &tournament.bracket {
+        Some(bracket) if bracket.champion().is_some() => GameState::TournamentChampion,
+        Some(_) => GameState::TournamentBracket,
+        None => GameState::TournamentSetup,
+    }
+}
+
+// 开始淘汰赛的一局；暂停菜单重新开始时也用同一个种子
+pub fn start_turn(run: &mut RunResources, level: u32, seed: u64) {
+    *run.difficulty_settings = DifficultySettings::new(TOURNAMENT_DIFFICULTY);
+    start_new_run(run, Some(seed));
+    run.level.0 = level;
+    *run.game_mode = GameMode::Tournament;
+}
+
+// 输入选手名字时已经确定的名字和提示
+#[derive(Resource, Default)]
+pub struct TournamentEntry {
+    names: Vec<String>,
+    error: Option<&'static str>,
+}
+
+#[derive(Component)]
+pub struct TournamentUI;
+
+// 名单中的一行，最后一行是正在输入的名字
+#[derive(Component)]
+pub struct EntryLine(usize);
+
+#[derive(Component)]
+pub struct EntryStatusText;
+
+pub fn setup_tournament_entry(
+    mut commands: Commands,
+    fonts: Res<FontAssets>,
+    locale: Res<Locale>,
+    mut entry: ResMut<TournamentEntry>,
+    mut name_input: ResMut<NameInput>,
+) {
+    *entry = TournamentEntry::default();
+    name_input.text.clear();
+    name_input.cursor_visible = true;
+    name_input.cursor_timer = 0.0;
+
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    width: Val::Percent(100.0),
+                    height: Val::Percent(100.0),
+                    flex_direction: FlexDirection::Column,
+                    justify_content: JustifyContent::Center,
+                    align_items: AlignItems::Center,
+                    ..default()
+                },
+                background_color: BackgroundColor(BACKGROUND_COLOR),
+                ..default()
+            },
+            TournamentUI,
+        ))
+        .with_children(|parent| {
+            parent.spawn(TextBundle::from_section(
+                locale.tr("tournament.title", &[]),
+                text_style(&fonts, FontKind::Bold, 60.0, Color::WHITE),
+            ));
+            parent.spawn(
+                TextBundle::from_section(
+                    locale.tr("tournament.enter_names", &[("min", &MIN_PLAYERS), ("max", &MAX_PLAYERS)]),
+                    text_style(&fonts, FontKind::Regular, 22.0, HINT_COLOR),
+                )
+                .with_style(Style {
+                    margin: UiRect::vertical(Val::Px(15.0)),
+                    ..default()
+                }),
+            );
+            for index in 0..MAX_PLAYERS {
+                parent.spawn((
+                    TextBundle::from_section("", text_style(&fonts, FontKind::Mono, 24.0, Color::WHITE)).with_style(
+                        Style {
+                            width: Val::Px(360.0),
+                            ..default()
+                        },
+                    ),
+                    EntryLine(index),
+                ));
+            }
+            parent.spawn((
+                TextBundle::from_section("", text_style(&fonts, FontKind::Regular, 20.0, ERROR_COLOR)).with_style(
+                    Style {
+                        margin: UiRect::top(Val::Px(15.0)),
+                        ..default()
+                    },
+                ),
+                EntryStatusText,
+            ));
+            parent.spawn(
+                TextBundle::from_section(
+                    locale.tr("tournament.entry_hint", &[]),
+                    text_style(&fonts, FontKind::Regular, 18.0, HINT_COLOR),
+                )
+                .with_style(Style {
+                    margin: UiRect::top(Val::Px(10.0)),
+                    ..default()
+                }),
+            );
+        });
+}
+
+// 和输入名字界面一样逐字输入；回车加入名单，名字为空时回车开始比赛，退格删掉上一个名字
+pub fn tournament_entry_system(
+    mut char_events: EventReader<ReceivedCharacter>,
+    keyboard: Res<ButtonInput<KeyCode>>,
+    locale: Res<Locale>,
+    paths: Res<ProfilePaths>,
+    mut name_input: ResMut<NameInput>,
+    mut entry: ResMut<TournamentEntry>,
+    mut tournament: ResMut<Tournament>,
+    mut next_state: ResMut<NextState<GameState>>,
+    mut lines: Query<(&mut Text, &EntryLine)>,
+    mut status: Query<&mut Text, (With<EntryStatusText>, Without<EntryLine>)>,
+) {
+    let full = entry.names.len() >= MAX_PLAYERS;
+    for event in char_events.read() {
+        if let Some(ch) = event.char.as_str().chars().next() {
+            if !full && is_name_char(ch) && name_input.text.chars().count() < MAX_NAME_LENGTH {
+                name_input.text.push(ch);
+            }
+        }
+    }
+
+    if keyboard.just_pressed(KeyCode::Backspace) {
+        if name_input.text.pop().is_none() {
+            entry.names.pop();
+        }
+        entry.error = None;
+    }
+
+    if keyboard.just_pressed(KeyCode::Enter) {
+        if name_input.text.trim().is_empty() {
+            if entry.names.len() >= MIN_PLAYERS {
+                let bracket = Bracket::new(std::mem::take(&mut entry.names));
+                save_bracket(&paths, Some(&bracket));
+                *tournament = Tournament {
+                    bracket: Some(bracket),
+                    ..default()
+                };
+                next_state.set(GameState::TournamentBracket);
+            } else {
+                entry.error = Some("tournament.need_players");
+            }
+        } else {
+            match normalize_player_name(&name_input.text) {
+                Some(name) if entry.names.iter().any(|other| other.eq_ignore_ascii_case(&name)) => {
+                    entry.error = Some("tournament.duplicate_name");
+                }
+                Some(name) => {
+                    entry.names.push(name);
+                    name_input.text.clear();
+                    entry.error = None;
+                }
+                None => entry.error = Some("tournament.invalid_name"),
+            }
+        }
+    }
+
+    if keyboard.just_pressed(KeyCode::Escape) {
+        next_state.set(GameState::MainMenu);
+    }
+
+    for (mut text, line) in lines.iter_mut() {
+        let value = match entry.names.get(line.0) {
+            Some(name) => format!("{}. {}", line.0 + 1, name),
+            None if line.0 == entry.names.len() => {
+                let cursor = if name_input.cursor_visible { "_" } else { "" };
+                format!("{}. {}{}", line.0 + 1, name_input.text, cursor)
+            }
+            None => String::new(),
+        };
+        if text.sections[0].value != value {
+            text.sections[0].value = value;
+        }
+    }
+    if let Ok(mut text) = status.get_single_mut() {
+        text.sections[0].value = entry.error.map(|key| locale.tr(key, &[])).unwrap_or_default();
+    }
+}
+
+// 一局结束回到对阵表时记下这一局的分数
+pub fn finish_turn(mut tournament: ResMut<Tournament>, score: Res<Score>, paths: Res<ProfilePaths>) {
+    let Some(turn) = tournament.turn.take() else {
+        return;
+    };
+    let Some(bracket) = tournament.bracket.as_mut() else {
+        return;
+    };
+    let tied = bracket.record(turn, score.0);
+    save_bracket(&paths, Some(bracket));
+    tournament.tied = tied;
+}
+
+pub fn setup_bracket(
+    mut commands: Commands,
+    fonts: Res<FontAssets>,
+    locale: Res<Locale>,
+    tournament: Res<Tournament>,
+) {
+    let Some(bracket) = &tournament.bracket else {
+        return;
+    };
+    let next_turn = bracket.next_turn();
+
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    width: Val::Percent(100.0),
+                    height: Val::Percent(100.0),
+                    flex_direction: FlexDirection::Column,
+                    justify_content: JustifyContent::Center,
+                    align_items: AlignItems::Center,
+                    ..default()
+                },
+                background_color: BackgroundColor(BACKGROUND_COLOR),
+                ..default()
+            },
+            TournamentUI,
+        ))
+        .with_children(|parent| {
+            parent.spawn(TextBundle::from_section(
+                locale.tr("tournament.bracket_title", &[]),
+                text_style(&fonts, FontKind::Bold, 48.0, Color::WHITE),
+            ));
+
+            // 每一轮一列，对决在列中均匀分布，后面的轮次正好落在前一轮两场对决之间
+            parent
+                .spawn(NodeBundle {
+                    style: Style {
+                        height: Val::Px(340.0),
+                        margin: UiRect::vertical(Val::Px(20.0)),
+                        column_gap: Val::Px(24.0),
+                        ..default()
+                    },
+                    ..default()
+                })
+                .with_children(|parent| {
+                    for (round, matches) in bracket.rounds.iter().enumerate() {
+                        parent
+                            .spawn(NodeBundle {
+                                style: Style {
+                                    flex_direction: FlexDirection::Column,
+                                    justify_content: JustifyContent::SpaceAround,
+                                    ..default()
+                                },
+                                ..default()
+                            })
+                            .with_children(|parent| {
+                                for (index, game) in matches.iter().enumerate() {
+                                    let is_next = next_turn.is_some_and(|turn| (turn.round, turn.index) == (round, index));
+                                    spawn_match_box(parent, &fonts, &locale, bracket, round, game, is_next);
+                                }
+                            });
+                    }
+                });
+
+            if tournament.tied {
+                parent.spawn(TextBundle::from_section(
+                    locale.tr("tournament.tied", &[]),
+                    text_style(&fonts, FontKind::Bold, 22.0, WINNER_COLOR),
+                ));
+            }
+
+            let message = match next_turn {
+                Some(turn) => {
+                    let game = &bracket.rounds[turn.round][turn.index];
+                    let name = bracket.player_name(game.players[turn.slot]).unwrap_or_default();
+                    locale.tr("tournament.up_next", &[("name", &name), ("level", &Bracket::level(turn.round))])
+                }
+                None => locale.tr("tournament.finished", &[]),
+            };
+            parent.spawn(TextBundle::from_section(
+                message,
+                text_style(&fonts, FontKind::Bold, 26.0, NEXT_MATCH_COLOR),
+            ));
+            parent.spawn(
+                TextBundle::from_section(
+                    locale.tr("tournament.bracket_hint", &[]),
+                    text_style(&fonts, FontKind::Regular, 18.0, HINT_COLOR),
+                )
+                .with_style(Style {
+                    margin: UiRect::top(Val::Px(10.0)),
+                    ..default()
+                }),
+            );
+        });
+}
+
+// 一场对决的方框：两行选手名字和分数，胜者高亮，下一场加边框
+fn spawn_match_box(
+    parent: &mut ChildBuilder,
+    fonts: &FontAssets,
+    locale: &Locale,
+    bracket: &Bracket,
+    round: usize,
+    game: &BracketMatch,
+    is_next: bool,
+) {
+    parent
+        .spawn(NodeBundle {
+            style: Style {
+                width: Val::Px(200.0),
+                padding: UiRect::axes(Val::Px(8.0), Val::Px(4.0)),
+                border: UiRect::all(Val::Px(2.0)),
+                flex_direction: FlexDirection::Column,
+                ..default()
+            },
+            background_color: BackgroundColor(MATCH_COLOR),
+            border_color: BorderColor(if is_next { NEXT_MATCH_COLOR } else { Color::NONE }),
+            ..default()
+        })
+        .with_children(|parent| {
+            for slot in 0..2 {
+                let player = game.players[slot];
+                let name = match bracket.player_name(player) {
+                    Some(name) => name.to_string(),
+                    // 第一轮的空位是轮空，之后的空位等待上一轮的胜者
+                    None if round == 0 => locale.tr("tournament.bye", &[]),
+                    None => "...".to_string(),
+                };
+                let color = match game.winner {
+                    Some(winner) if player == Some(winner) => WINNER_COLOR,
+                    Some(_) => LOSER_COLOR,
+                    None => Color::WHITE,
+                };
+                let score = game.scores[slot].map(|score| score.to_string()).unwrap_or_default();
+                parent
+                    .spawn(NodeBundle {
+                        style: Style {
+                            justify_content: JustifyContent::SpaceBetween,
+                            ..default()
+                        },
+                        ..default()
+                    })
+                    .with_children(|parent| {
+                        parent.spawn(TextBundle::from_section(
+                            name,
+                            text_style(fonts, FontKind::Regular, 18.0, color),
+                        ));
+                        parent.spawn(TextBundle::from_section(score, text_style(fonts, FontKind::Mono, 18.0, color)));
+                    });
+            }
+        });
+}
+
+// 回车开始下一局（比赛结束时查看冠军），ESC 回到主菜单，比赛保存在档案里
+pub fn bracket_system(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    mut tournament: ResMut<Tournament>,
+    mut next_state: ResMut<NextState<GameState>>,
+    mut run: RunResources,
+) {
+    if keyboard_input.just_pressed(KeyCode::Escape) {
+        next_state.set(GameState::MainMenu);
+        return;
+    }
+    if !keyboard_input.just_pressed(KeyCode::Enter) {
+        return;
+    }
+    let Some(bracket) = &tournament.bracket else {
+        next_state.set(GameState::MainMenu);
+        return;
+    };
+    match bracket.next_turn() {
+        Some(turn) => {
+            let seed = bracket.rounds[turn.round][turn.index].seed;
+            start_turn(&mut run, Bracket::level(turn.round), seed);
+            tournament.turn = Some(turn);
+            tournament.tied = false;
+            next_state.set(GameState::Playing);
+        }
+        None => next_state.set(GameState::TournamentChampion),
+    }
+}
+
+#[derive(Component)]
+pub struct ChampionStatusText;
+
+pub fn setup_champion(
+    mut commands: Commands,
+    fonts: Res<FontAssets>,
+    locale: Res<Locale>,
+    tournament: Res<Tournament>,
+) {
+    let Some(bracket) = &tournament.bracket else {
+        return;
+    };
+    let champion = bracket.player_name(bracket.champion()).unwrap_or_default();
+
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    width: Val::Percent(100.0),
+                    height: Val::Percent(100.0),
+                    flex_direction: FlexDirection::Column,
+                    justify_content: JustifyContent::Center,
+                    align_items: AlignItems::Center,
+                    row_gap: Val::Px(15.0),
+                    ..default()
+                },
+                background_color: BackgroundColor(BACKGROUND_COLOR),
+                ..default()
+            },
+            TournamentUI,
+        ))
+        .with_children(|parent| {
+            parent.spawn(TextBundle::from_section(
+                locale.tr("tournament.champion", &[]),
+                text_style(&fonts, FontKind::Bold, 40.0, Color::WHITE),
+            ));
+            parent.spawn(TextBundle::from_section(
+                champion,
+                text_style(&fonts, FontKind::Bold, 72.0, WINNER_COLOR),
+            ));
+            parent.spawn(TextBundle::from_section(
+                locale.tr("tournament.id", &[("id", &bracket.id)]),
+                text_style(&fonts, FontKind::Mono, 18.0, HINT_COLOR),
+            ));
+            let status = if bracket.submitted { locale.tr("tournament.shared", &[]) } else { String::new() };
+            parent.spawn((
+                TextBundle::from_section(status, text_style(&fonts, FontKind::Regular, 20.0, NEXT_MATCH_COLOR)),
+                ChampionStatusText,
+            ));
+            parent.spawn(TextBundle::from_section(
+                locale.tr("tournament.champion_hint", &[]),
+                text_style(&fonts, FontKind::Regular, 18.0, HINT_COLOR),
+            ));
+        });
+}
+
+// U 把全部对决的分数提交到服务器（带比赛 id，可以按 id 查询整场比赛），只保存在本机时不提交；
+// 回车或 ESC 结束比赛
+pub fn champion_system(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    settings: Res<Settings>,
+    locale: Res<Locale>,
+    paths: Res<ProfilePaths>,
+    network: Res<Network>,
+    mut tournament: ResMut<Tournament>,
+    mut next_state: ResMut<NextState<GameState>>,
+    mut status: Query<&mut Text, With<ChampionStatusText>>,
+) {
+    if keyboard_input.any_just_pressed([KeyCode::Enter, KeyCode::Escape]) {
+        *tournament = Tournament::default();
+        save_bracket(&paths, None);
+        next_state.set(GameState::MainMenu);
+        return;
+    }
+    if !keyboard_input.just_pressed(KeyCode::KeyU) {
+        return;
+    }
+    let Some(bracket) = tournament.bracket.as_mut().filter(|bracket| !bracket.submitted) else {
+        return;
+    };
+    if settings.score_privacy == ScorePrivacy::LocalOnly {
+        if let Ok(mut text) = status.get_single_mut() {
+            text.sections[0].value = locale.tr("tournament.local_only", &[]);
+        }
+        return;
+    }
+    for (round, matches) in bracket.rounds.iter().enumerate() {
+        for game in matches {
+            for (player, score) in game.players.iter().zip(game.scores) {
+                let (Some(player), Some(score)) = (player, score) else {
+                    continue;
+                };
+                network.send(ApiCommand::SubmitScore(CreateScoreRequest {
+                    player_name: bracket.players[*player].clone(),
+                    score,
+                    level: Bracket::level(round),
+                    difficulty: TOURNAMENT_DIFFICULTY.name().to_string(),
+                    idempotency_key: None,
+                    venue: None,
+                    mode: Some(TOURNAMENT_MODE.to_string()),
+                    seed: None,
+                    daily_date: None,
+                    event_id: None,
+                    tournament_id: Some(bracket.id.clone()),
+                    abandoned: false,
+                    assisted: false,
+                    curse: None,
+                }));
+            }
+        }
+    }
+    bracket.submitted = true;
+    save_bracket(&paths, Some(bracket));
+    if let Ok(mut text) = status.get_single_mut() {
+        text.sections[0].value = locale.tr("tournament.shared", &[]);
+    }
+}
+
+pub fn cleanup_tournament_ui(mut commands: Commands, query: Query<Entity, With<TournamentUI>>) {
+    for entity in query.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+}
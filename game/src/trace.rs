@@ -0,0 +1,236 @@
+use bevy::prelude::*;
+
+use crate::{brick_position, collide, BrickType, Layout, Side, BALL_SIZE, BRICK_SIZE, WINDOW_HEIGHT, WINDOW_WIDTH};
+
+// 每一步前进的距离，小于球的一半，不会穿过砖块之间的缝隙或砖块本身
+const TRACE_STEP: f32 = BALL_SIZE.y / 4.0;
+// 轨迹最长距离，防止在不可破坏砖块之间无限反弹
+const MAX_TRACE_LENGTH: f32 = 6000.0;
+
+// 轨迹中的一段直线
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Segment {
+    pub from: Vec2,
+    pub to: Vec2,
+}
+
+// 轨迹结果：折线和会被击中的砖块。缓冲区可以反复使用，每帧重新计算时不用分配内存
+#[derive(Debug, Default)]
+pub struct BallTrace {
+    pub segments: Vec<Segment>,
+    // 会受到伤害的砖块在 layout.bricks 中的下标，按击中顺序排列，不重复
+    pub damaged: Vec<usize>,
+    // 其中会被打碎的砖块
+    pub destroyed: Vec<usize>,
+    // 模拟过程中砖块的剩余生命值，以及上一步是否与球重叠
+    health: Vec<i32>,
+    touching: Vec<bool>,
+}
+
+impl BallTrace {
+    fn reset(&mut self, layout: &Layout) {
+        self.segments.clear();
+        self.damaged.clear();
+        self.destroyed.clear();
+        self.health.clear();
+        self.health.extend(layout.bricks.iter().map(|brick| brick.health));
+        self.touching.clear();
+        self.touching.resize(layout.bricks.len(), false);
+    }
+
+    fn damage(&mut self, index: usize) {
+        if !self.damaged.contains(&index) {
+            self.damaged.push(index);
+        }
+    }
+}
+
+// 从 start 沿 dir 发射一个模拟球，返回最多 max_bounces 次反弹的轨迹。
+// 与 ball_collision 使用相同的碰撞判定和各类砖块的反弹规则，不考虑挡板和道具效果
+#[allow(dead_code)]
+pub fn trace_ball(layout: &Layout, start: Vec2, dir: Vec2, max_bounces: usize) -> Vec<Segment> {
+    let mut trace = BallTrace::default();
+    trace_ball_into(layout, start, dir, max_bounces, &mut trace);
+    trace.segments
+}
+
+// 同 trace_ball，结果写入可复用的缓冲区
+pub fn trace_ball_into(layout: &Layout, start: Vec2, dir: Vec2, max_bounces: usize, trace: &mut BallTrace) {
+    trace.reset(layout);
+    let mut direction = dir.normalize_or_zero();
+    if direction == Vec2::ZERO {
+        return;
+    }
+
+    let max_x = WINDOW_WIDTH / 2.0 - BALL_SIZE.x / 2.0;
+    let max_y = WINDOW_HEIGHT / 2.0 - BALL_SIZE.y / 2.0;
+    let mut position = start;
+    let mut segment_start = start;
+    let mut bounces = 0;
+    let mut travelled = 0.0;
+
+    while travelled < MAX_TRACE_LENGTH {
+        position += direction * TRACE_STEP;
+        travelled += TRACE_STEP;
+        let incoming = direction;
+
+        // 墙壁：与游戏相同，把球推回场内再反弹
+        if position.x < -max_x {
+            position.x = -max_x;
+            direction.x = direction.x.abs();
+        } else if position.x > max_x {
+            position.x = max_x;
+            direction.x = -direction.x.abs();
+        }
+        if position.y > max_y {
+            position.y = max_y;
+            direction.y = -direction.y.abs();
+        }
+
+        // 从底部掉出
+        if position.y < -WINDOW_HEIGHT / 2.0 {
+            break;
+        }
+
+        // 砖块：只在刚开始重叠的那一步处理，避免同一块砖在相邻几步里重复反弹
+        let mut captured = false;
+        for (index, brick) in layout.bricks.iter().enumerate() {
+            if trace.health[index] == 0 {
+                continue;
+            }
+            let (row, col) = brick.cell;
+            let collision = collide(
+                position.extend(0.0),
+                BALL_SIZE,
+                brick_position(row, col).extend(0.0),
+                BRICK_SIZE,
+            );
+            let was_touching = std::mem::replace(&mut trace.touching[index], collision.is_some());
+            let Some(collision) = collision else {
+                continue;
+            };
+            if was_touching {
+                continue;
+            }
+
+            direction = brick.brick_type.bounce_response(direction, collision).normalize_or_zero();
+            if matches!(brick.brick_type, BrickType::Unbreakable) || brick.armor == Some(Side::struck_by(collision)) {
+                continue;
+            }
+
+            trace.damage(index);
+            // 粘性砖会抓住球，轨迹到此结束
+            if matches!(brick.brick_type, BrickType::Sticky) {
+                captured = true;
+                break;
+            }
+            trace.health[index] -= 1;
+            if trace.health[index] == 0 {
+                trace.destroyed.push(index);
+            }
+        }
+
+        if captured {
+            break;
+        }
+        if direction != incoming {
+            trace.segments.push(Segment {
+                from: segment_start,
+                to: position,
+            });
+            segment_start = position;
+            bounces += 1;
+            if bounces >= max_bounces {
+                return;
+            }
+        }
+    }
+
+    trace.segments.push(Segment {
+        from: segment_start,
+        to: position,
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn brick(cell: (usize, usize), brick_type: BrickType, health: i32) -> crate::BrickSpec {
+        crate::BrickSpec {
+            cell,
+            brick_type,
+            health,
+            armor: None,
+            guaranteed_drop: None,
+            golden: false,
+            generator: None,
+        }
+    }
+
+    fn layout(bricks: Vec<crate::BrickSpec>) -> Layout {
+        Layout { bricks, quota: None }
+    }
+
+    fn assert_near(actual: Vec2, expected: Vec2) {
+        assert!(actual.distance(expected) <= TRACE_STEP, "{actual} 离 {expected} 太远");
+    }
+
+    #[test]
+    fn empty_board_bounces_off_ceiling_then_side_wall() {
+        let max_x = WINDOW_WIDTH / 2.0 - BALL_SIZE.x / 2.0;
+        let max_y = WINDOW_HEIGHT / 2.0 - BALL_SIZE.y / 2.0;
+        let segments = trace_ball(&layout(Vec::new()), Vec2::ZERO, Vec2::ONE, 10);
+
+        // 45° 发射：先碰天花板，再碰右墙，然后从底部掉出
+        assert_eq!(segments.len(), 3);
+        assert_eq!(segments[0].from, Vec2::ZERO);
+        assert_near(segments[0].to, Vec2::new(max_y, max_y));
+        assert_eq!(segments[1].from, segments[0].to);
+        assert_near(segments[1].to, Vec2::new(max_x, 2.0 * max_y - max_x));
+        assert_eq!(segments[2].from, segments[1].to);
+        assert!(segments[2].to.y < -WINDOW_HEIGHT / 2.0);
+        assert!(segments[2].to.x < max_x);
+    }
+
+    #[test]
+    fn unbreakable_brick_sends_the_ball_straight_back() {
+        let target = brick_position(0, 4);
+        let start = Vec2::new(target.x, 0.0);
+        let layout = layout(vec![brick((0, 4), BrickType::Unbreakable, 1)]);
+        let mut trace = BallTrace::default();
+        trace_ball_into(&layout, start, Vec2::Y, 10, &mut trace);
+
+        let contact = target.y - BRICK_SIZE.y / 2.0 - BALL_SIZE.y / 2.0;
+        assert_eq!(trace.segments.len(), 2);
+        assert_near(trace.segments[0].to, Vec2::new(target.x, contact));
+        assert_eq!(trace.segments[1].to.x, target.x);
+        assert!(trace.segments[1].to.y < -WINDOW_HEIGHT / 2.0);
+        assert!(trace.damaged.is_empty());
+    }
+
+    #[test]
+    fn first_brick_hit_takes_the_damage() {
+        let first = brick_position(1, 4);
+        let start = Vec2::new(first.x, 0.0);
+        let layout = layout(vec![
+            brick((0, 4), BrickType::Normal, 1),
+            brick((1, 4), BrickType::Hard, 2),
+        ]);
+        let mut trace = BallTrace::default();
+        trace_ball_into(&layout, start, Vec2::Y, 10, &mut trace);
+
+        // 先撞到下面一行的硬砖（掉一格血没碎），球被弹回，上面的砖没有碰到
+        assert_eq!(trace.damaged, vec![1]);
+        assert!(trace.destroyed.is_empty());
+        assert_eq!(trace.segments.len(), 2);
+    }
+
+    #[test]
+    fn trace_stops_after_max_bounces() {
+        let segments = trace_ball(&layout(Vec::new()), Vec2::ZERO, Vec2::ONE, 1);
+        assert_eq!(segments.len(), 1);
+
+        assert!(trace_ball(&layout(Vec::new()), Vec2::ZERO, Vec2::ZERO, 10).is_empty());
+    }
+}
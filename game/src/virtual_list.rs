@@ -0,0 +1,52 @@
+use std::ops::Range;
+
+// 固定行高的虚拟列表：只为可见范围（加上前后各 overscan 行）生成界面行，滚动时复用这些行。
+// offset 为内容向上滚动的距离，0 表示在顶部
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct VirtualList {
+    pub row_height: f32,
+    pub viewport_height: f32,
+    pub overscan: usize,
+}
+
+impl VirtualList {
+    pub fn new(row_height: f32, viewport_height: f32, overscan: usize) -> Self {
+        Self {
+            row_height: row_height.max(1.0),
+            viewport_height: viewport_height.max(0.0),
+            overscan,
+        }
+    }
+
+    // 全部行的总高度，列表节点按这个高度布局，滚动范围不变
+    pub fn content_height(&self, len: usize) -> f32 {
+        len as f32 * self.row_height
+    }
+
+    pub fn max_offset(&self, len: usize) -> f32 {
+        (self.content_height(len) - self.viewport_height).max(0.0)
+    }
+
+    // 需要显示的行的下标范围
+    pub fn visible_range(&self, offset: f32, len: usize) -> Range<usize> {
+        let offset = offset.clamp(0.0, self.max_offset(len));
+        let first = (offset / self.row_height).floor() as usize;
+        let last = ((offset + self.viewport_height) / self.row_height).ceil() as usize;
+        first.saturating_sub(self.overscan).min(len)..(last + self.overscan).min(len)
+    }
+
+    // 任意滚动位置下同时可见的最多行数，也就是需要准备的界面行数
+    pub fn pool_size(&self) -> usize {
+        (self.viewport_height / self.row_height).ceil() as usize + 1 + 2 * self.overscan
+    }
+
+    // 循环复用：第 index 行总是放在第 index % pool 个界面行上，滚动一行只需要改写一个界面行。
+    // 返回 slot 在当前范围内应该显示的行，没有时为 None
+    pub fn row_for_slot(range: &Range<usize>, slot: usize, pool: usize) -> Option<usize> {
+        if pool == 0 {
+            return None;
+        }
+        let index = range.start + (slot + pool - range.start % pool) % pool;
+        (index < range.end).then_some(index)
+    }
+}
@@ -0,0 +1,204 @@
+use bevy::prelude::*;
+
+use crate::api::EventResponse;
+use crate::fonts::{text_style, FontAssets, FontKind};
+use crate::i18n::Locale;
+use crate::network::{ApiCommand, ApiResponse, Network};
+use crate::remote_flags::FlagGated;
+use crate::{start_new_run, BrickSpec, Difficulty, DifficultySettings, GameplayEvent, PowerUpType, RunResources};
+
+// 活动局固定使用的难度，同一周的成绩才可以比较
+const EVENT_DIFFICULTY: Difficulty = Difficulty::Medium;
+// 提交时的模式名
+pub use breakout_shared::EVENT_MODE;
+// 迷雾：最上面这么多行的砖块在第一次受到伤害前不可见
+const FOG_ROWS: usize = 3;
+// 没有激光时，保底的激光道具换成这个
+const LASER_REPLACEMENT: PowerUpType = PowerUpType::PenetratingBall;
+
+const EVENT_COLOR: Color = Color::rgb(0.4, 0.9, 0.8);
+
+// 服务器轮换的活动修改器，名字与服务器配置中的一致
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EventModifier {
+    // 随机掉落道具的概率翻倍
+    DoubleDrops,
+    // 不会掉落激光道具
+    NoLasers,
+    // 上面几行砖块在第一次受到伤害前隐藏
+    FogOfWar,
+    // 砖块分成冷暖两队，得分倍数每隔一段时间交换
+    TeamColors,
+}
+
+impl EventModifier {
+    fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "double_drops" => Some(EventModifier::DoubleDrops),
+            "no_lasers" => Some(EventModifier::NoLasers),
+            "fog_of_war" => Some(EventModifier::FogOfWar),
+            "team_colors" => Some(EventModifier::TeamColors),
+            _ => None,
+        }
+    }
+
+    pub fn label_key(&self) -> &'static str {
+        match self {
+            EventModifier::DoubleDrops => "event.double_drops",
+            EventModifier::NoLasers => "event.no_lasers",
+            EventModifier::FogOfWar => "event.fog_of_war",
+            EventModifier::TeamColors => "event.team_colors",
+        }
+    }
+}
+
+// 一周的活动：id 为 ISO 周（例如 "2025-W07"），提交时原样带上
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ActiveEvent {
+    pub id: String,
+    pub modifier: EventModifier,
+}
+
+// 启动时从服务器获取的本周活动；离线、没有活动或修改器不认识时为空
+#[derive(Resource, Default)]
+pub struct CurrentEvent(pub Option<ActiveEvent>);
+
+// 正在进行的活动局，保存开始时的活动，跨周才结束的局仍然算在开始的那一周
+#[derive(Resource, Default)]
+pub struct EventRun(pub Option<ActiveEvent>);
+
+impl EventRun {
+    fn modifier(&self) -> Option<EventModifier> {
+        self.0.as_ref().map(|event| event.modifier)
+    }
+
+    // 砖块随机掉落道具的概率
+    pub fn drop_chance(&self, base: f64) -> f64 {
+        match self.modifier() {
+            Some(EventModifier::DoubleDrops) => (base * 2.0).min(1.0),
+            _ => base,
+        }
+    }
+
+    pub fn lasers_enabled(&self) -> bool {
+        self.modifier() != Some(EventModifier::NoLasers)
+    }
+
+    // 某一行的砖块是否被迷雾隐藏
+    pub fn fogged(&self, row: usize) -> bool {
+        self.modifier() == Some(EventModifier::FogOfWar) && row < FOG_ROWS
+    }
+
+    // 砖块是否分成冷暖两队
+    pub fn team_colors(&self) -> bool {
+        self.modifier() == Some(EventModifier::TeamColors)
+    }
+
+    // 按修改器调整布局中的保底道具
+    pub fn adjust_drops(&self, bricks: &mut [BrickSpec]) {
+        if self.lasers_enabled() {
+            return;
+        }
+        for spec in bricks.iter_mut() {
+            if spec.guaranteed_drop == Some(PowerUpType::LaserGun) {
+                spec.guaranteed_drop = Some(LASER_REPLACEMENT);
+            }
+        }
+    }
+}
+
+// 被迷雾隐藏的砖块
+#[derive(Component)]
+pub struct Fogged;
+
+// 主菜单上的活动提示
+#[derive(Component)]
+pub struct EventBannerText;
+
+pub fn request_weekly_event(network: Res<Network>) {
+    network.send(ApiCommand::GetEvent);
+}
+
+pub fn receive_weekly_event(mut responses: EventReader<ApiResponse>, mut current: ResMut<CurrentEvent>) {
+    for response in responses.read() {
+        let ApiResponse::Event(result) = response else {
+            continue;
+        };
+        current.0 = match result {
+            Ok(Some(EventResponse { id, modifier, .. })) => match EventModifier::from_name(modifier) {
+                Some(modifier) => Some(ActiveEvent { id: id.clone(), modifier }),
+                None => {
+                    eprintln!("Unknown weekly event modifier '{}'", modifier);
+                    None
+                }
+            },
+            Ok(None) => None,
+            Err(e) => {
+                eprintln!("Failed to fetch weekly event: {}", e);
+                None
+            }
+        };
+    }
+}
+
+// 提示挂在主菜单的节点下，活动在菜单打开后才返回时也会显示出来；活动开关关闭时隐藏
+pub fn spawn_event_banner(parent: &mut ChildBuilder, fonts: &FontAssets) {
+    parent.spawn((
+        TextBundle::from_section("", text_style(fonts, FontKind::Bold, 22.0, EVENT_COLOR)).with_style(Style {
+            margin: UiRect::top(Val::Px(15.0)),
+            ..default()
+        }),
+        EventBannerText,
+        FlagGated(breakout_shared::FLAG_WEEKLY_EVENT),
+    ));
+}
+
+pub fn update_event_banner(
+    current: Res<CurrentEvent>,
+    locale: Res<Locale>,
+    mut banners: Query<&mut Text, With<EventBannerText>>,
+) {
+    let message = match &current.0 {
+        Some(event) => locale.tr(
+            "menu.event",
+            &[("modifier", &locale.tr(event.modifier.label_key(), &[])), ("week", &event.id)],
+        ),
+        None => String::new(),
+    };
+    for mut text in banners.iter_mut() {
+        if text.sections[0].value != message {
+            text.sections[0].value = message.clone();
+        }
+    }
+}
+
+// 开始本周的活动局（固定难度、随机种子），没有活动时返回 false
+pub fn start_event_run(run: &mut RunResources, current: &CurrentEvent) -> bool {
+    let Some(event) = current.0.clone() else {
+        return false;
+    };
+    *run.difficulty_settings = DifficultySettings::new(EVENT_DIFFICULTY);
+    start_new_run(run, None);
+    run.event_run.0 = Some(event);
+    true
+}
+
+// 砖块第一次受到伤害（包括打在装甲上）时揭开迷雾；被一击打碎的砖块直接消失
+pub fn reveal_fogged_bricks(
+    mut commands: Commands,
+    mut gameplay_events: EventReader<GameplayEvent>,
+    mut bricks: Query<(Entity, &Transform, &mut Visibility), With<Fogged>>,
+) {
+    for event in gameplay_events.read() {
+        let position = match *event {
+            GameplayEvent::BrickDamaged { position } | GameplayEvent::ArmorDeflected { position } => position,
+            _ => continue,
+        };
+        for (entity, transform, mut visibility) in bricks.iter_mut() {
+            if transform.translation.truncate().distance(position.truncate()) < 1.0 {
+                *visibility = Visibility::Inherited;
+                commands.entity(entity).remove::<Fogged>();
+            }
+        }
+    }
+}
@@ -0,0 +1,543 @@
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::net::IpAddr;
+use std::path::PathBuf;
+use std::time::Duration;
+
+// 未指定 --config 时尝试读取的配置文件
+const DEFAULT_CONFIG_PATH: &str = "config.toml";
+// 环境变量前缀，例如 BREAKOUT_PORT
+const ENV_PREFIX: &str = "BREAKOUT_";
+// 管理密钥的最短长度
+const MIN_ADMIN_KEY_LENGTH: usize = 16;
+// 单个事务最多归档的行数，也受 SQLite 绑定参数数量的限制
+const MAX_RETENTION_BATCH_SIZE: u32 = 1000;
+
+// 服务器配置，按 默认值 <- 配置文件 <- 环境变量 <- 命令行参数 的顺序覆盖
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ServerConfig {
+    pub bind_address: String,
+    pub port: u16,
+    pub database_url: String,
+    // 允许跨域访问的来源，需写完整的来源；为空时不允许跨域，"*" 表示任意来源，必须显式设置
+    pub cors_origins: Vec<String>,
+    // 在反向代理之后运行时设为 true，按 X-Forwarded-For / X-Forwarded-Proto 识别客户端
+    pub trust_proxy: bool,
+    // 每名玩家每天（UTC）最多提交的次数
+    pub daily_submission_cap: u32,
+    // 关卡分布缓存时长（秒）
+    pub level_histogram_cache_ttl_secs: u64,
+    // 管理接口（删除分数、修改玩家名和 /api/admin 下的接口）需要在 X-Admin-Key 请求头中提供；未设置时这些接口全部关闭
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub admin_api_key: Option<String>,
+    // 定期把旧记录移到 scores_archive；个人最好成绩和各赛季前几名不会被移走
+    pub retention_enabled: bool,
+    // 早于这么多天的记录才会被归档
+    pub retention_max_age_days: u32,
+    // 每个赛季、每个难度保留的名次
+    pub retention_protected_top: u32,
+    // 每个事务最多归档的行数
+    pub retention_batch_size: u32,
+    // 后台任务的运行间隔（小时）
+    pub retention_interval_hours: u64,
+    // 只统计会被归档的行数，不做修改
+    pub retention_dry_run: bool,
+    // 后台定期刷新全局统计汇总；关闭后 /api/stats/global 每次都实时计算
+    pub stats_summary_enabled: bool,
+    // 每周活动按 ISO 周轮换的修改器，为空时不举办活动
+    pub weekly_event_rotation: Vec<String>,
+    // 对外访问的地址（例如 https://breakout.example.com），设置后提交分数时返回分享链接
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub public_base_url: Option<String>,
+    // 设置后使用 HTTPS，否则使用 HTTP
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tls: Option<TlsConfig>,
+}
+
+// HTTPS 证书和私钥（PEM 格式）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct TlsConfig {
+    pub cert_path: PathBuf,
+    pub key_path: PathBuf,
+}
+
+impl Default for ServerConfig {
+    fn default() -> Self {
+        Self {
+            bind_address: "127.0.0.1".to_string(),
+            port: 8080,
+            database_url: "sqlite://breakout_scores.db".to_string(),
+            cors_origins: Vec::new(),
+            trust_proxy: false,
+            daily_submission_cap: 200,
+            level_histogram_cache_ttl_secs: 30,
+            admin_api_key: None,
+            retention_enabled: false,
+            retention_max_age_days: 365,
+            retention_protected_top: 100,
+            retention_batch_size: 500,
+            retention_interval_hours: 24,
+            retention_dry_run: false,
+            stats_summary_enabled: true,
+            weekly_event_rotation: crate::event::KNOWN_MODIFIERS.iter().map(|name| name.to_string()).collect(),
+            public_base_url: None,
+            tls: None,
+        }
+    }
+}
+
+// 启动方式：正常运行、打印配置、只检查配置或执行一次数据保留任务
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RunMode {
+    Serve,
+    PrintConfig,
+    Check,
+    Retention,
+}
+
+#[derive(Debug)]
+pub struct ConfigError(String);
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl std::error::Error for ConfigError {}
+
+// 命令行参数
+#[derive(Debug, Default)]
+struct CliArgs {
+    config_path: Option<PathBuf>,
+    bind_address: Option<String>,
+    port: Option<String>,
+    database_url: Option<String>,
+    mode: Option<RunMode>,
+    dry_run: bool,
+}
+
+impl CliArgs {
+    fn parse(args: &[String]) -> Result<Self, ConfigError> {
+        let mut cli = Self::default();
+        let mut args = args.iter();
+        while let Some(arg) = args.next() {
+            let mut value = |name: &str| {
+                args.next()
+                    .cloned()
+                    .ok_or_else(|| ConfigError(format!("{} requires a value", name)))
+            };
+            match arg.as_str() {
+                "--config" => cli.config_path = Some(PathBuf::from(value("--config")?)),
+                "--bind" => cli.bind_address = Some(value("--bind")?),
+                "--port" => cli.port = Some(value("--port")?),
+                "--database-url" => cli.database_url = Some(value("--database-url")?),
+                "--print-config" => cli.mode = Some(RunMode::PrintConfig),
+                "--check" => cli.mode = Some(RunMode::Check),
+                "--run-retention" => cli.mode = Some(RunMode::Retention),
+                "--dry-run" => cli.dry_run = true,
+                other => return Err(ConfigError(format!("Unknown argument '{}'", other))),
+            }
+        }
+        Ok(cli)
+    }
+}
+
+impl ServerConfig {
+    // 从命令行参数（不含程序名）、配置文件和环境变量加载并校验配置
+    pub fn load(args: &[String], env: impl Fn(&str) -> Option<String>) -> Result<(Self, RunMode), ConfigError> {
+        let cli = CliArgs::parse(args)?;
+
+        // 显式指定的配置文件必须存在，默认路径不存在时跳过
+        let config_path = cli
+            .config_path
+            .clone()
+            .or_else(|| env("BREAKOUT_CONFIG").map(PathBuf::from));
+        let mut config = match &config_path {
+            Some(path) => Self::from_file(path)?,
+            None if std::path::Path::new(DEFAULT_CONFIG_PATH).exists() => {
+                Self::from_file(std::path::Path::new(DEFAULT_CONFIG_PATH))?
+            }
+            None => Self::default(),
+        };
+
+        config.apply_env(&env)?;
+        config.apply_cli(cli.bind_address, cli.port, cli.database_url)?;
+        config.retention_dry_run |= cli.dry_run;
+        config.validate()?;
+        Ok((config, cli.mode.unwrap_or(RunMode::Serve)))
+    }
+
+    fn from_file(path: &std::path::Path) -> Result<Self, ConfigError> {
+        let content = std::fs::read_to_string(path)
+            .map_err(|e| ConfigError(format!("Failed to read {}: {}", path.display(), e)))?;
+        toml::from_str(&content).map_err(|e| ConfigError(format!("Failed to parse {}: {}", path.display(), e)))
+    }
+
+    fn apply_env(&mut self, env: &impl Fn(&str) -> Option<String>) -> Result<(), ConfigError> {
+        let var = |name: &str| env(&format!("{}{}", ENV_PREFIX, name));
+
+        if let Some(value) = var("BIND_ADDRESS") {
+            self.bind_address = value;
+        }
+        if let Some(value) = var("PORT") {
+            self.port = parse_value("BREAKOUT_PORT", &value)?;
+        }
+        if let Some(value) = var("DATABASE_URL") {
+            self.database_url = value;
+        }
+        if let Some(value) = var("CORS_ORIGINS") {
+            self.cors_origins = value
+                .split(',')
+                .map(str::trim)
+                .filter(|origin| !origin.is_empty())
+                .map(String::from)
+                .collect();
+        }
+        if let Some(value) = var("TRUST_PROXY") {
+            self.trust_proxy = parse_value("BREAKOUT_TRUST_PROXY", &value)?;
+        }
+        // 证书和私钥需要一起设置，只设置一个时沿用配置文件中的另一个
+        let cert_path = var("TLS_CERT_PATH").map(PathBuf::from);
+        let key_path = var("TLS_KEY_PATH").map(PathBuf::from);
+        if cert_path.is_some() || key_path.is_some() {
+            let current = self.tls.take();
+            let cert_path = cert_path.or_else(|| current.as_ref().map(|tls| tls.cert_path.clone()));
+            let key_path = key_path.or_else(|| current.as_ref().map(|tls| tls.key_path.clone()));
+            match (cert_path, key_path) {
+                (Some(cert_path), Some(key_path)) => self.tls = Some(TlsConfig { cert_path, key_path }),
+                _ => {
+                    return Err(ConfigError(
+                        "BREAKOUT_TLS_CERT_PATH and BREAKOUT_TLS_KEY_PATH must be set together".to_string(),
+                    ))
+                }
+            }
+        }
+        // 旧的 DAILY_SUBMISSION_CAP 仍然有效，带前缀的变量优先
+        if let Some(value) = var("DAILY_SUBMISSION_CAP") {
+            self.daily_submission_cap = parse_value("BREAKOUT_DAILY_SUBMISSION_CAP", &value)?;
+        } else if let Some(value) = env("DAILY_SUBMISSION_CAP") {
+            self.daily_submission_cap = parse_value("DAILY_SUBMISSION_CAP", &value)?;
+        }
+        if let Some(value) = var("LEVEL_HISTOGRAM_CACHE_TTL_SECS") {
+            self.level_histogram_cache_ttl_secs = parse_value("BREAKOUT_LEVEL_HISTOGRAM_CACHE_TTL_SECS", &value)?;
+        }
+        if let Some(value) = var("ADMIN_API_KEY") {
+            self.admin_api_key = Some(value);
+        }
+        if let Some(value) = var("RETENTION_ENABLED") {
+            self.retention_enabled = parse_value("BREAKOUT_RETENTION_ENABLED", &value)?;
+        }
+        if let Some(value) = var("RETENTION_MAX_AGE_DAYS") {
+            self.retention_max_age_days = parse_value("BREAKOUT_RETENTION_MAX_AGE_DAYS", &value)?;
+        }
+        if let Some(value) = var("RETENTION_DRY_RUN") {
+            self.retention_dry_run = parse_value("BREAKOUT_RETENTION_DRY_RUN", &value)?;
+        }
+        if let Some(value) = var("STATS_SUMMARY_ENABLED") {
+            self.stats_summary_enabled = parse_value("BREAKOUT_STATS_SUMMARY_ENABLED", &value)?;
+        }
+        if let Some(value) = var("PUBLIC_BASE_URL") {
+            self.public_base_url = Some(value);
+        }
+        if let Some(value) = var("WEEKLY_EVENT_ROTATION") {
+            self.weekly_event_rotation = value
+                .split(',')
+                .map(str::trim)
+                .filter(|modifier| !modifier.is_empty())
+                .map(String::from)
+                .collect();
+        }
+        Ok(())
+    }
+
+    fn apply_cli(
+        &mut self,
+        bind_address: Option<String>,
+        port: Option<String>,
+        database_url: Option<String>,
+    ) -> Result<(), ConfigError> {
+        if let Some(bind_address) = bind_address {
+            self.bind_address = bind_address;
+        }
+        if let Some(port) = port {
+            self.port = parse_value("--port", &port)?;
+        }
+        if let Some(database_url) = database_url {
+            self.database_url = database_url;
+        }
+        Ok(())
+    }
+
+    // 检查配置是否合理，把所有问题一起报告
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        let mut problems = Vec::new();
+
+        if self.bind_address != "localhost" && self.bind_address.parse::<IpAddr>().is_err() {
+            problems.push(format!("bind_address '{}' is not an IP address or 'localhost'", self.bind_address));
+        }
+        if self.port == 0 {
+            problems.push("port must be between 1 and 65535".to_string());
+        }
+        if !self.database_url.starts_with("sqlite:") {
+            problems.push(format!("database_url '{}' must start with 'sqlite:'", self.database_url));
+        }
+        if self.cors_origins.len() > 1 && self.cors_origins.iter().any(|origin| origin == "*") {
+            problems.push("cors_origins cannot mix \"*\" with specific origins".to_string());
+        }
+        for origin in self.cors_origins.iter().filter(|origin| *origin != "*") {
+            let valid_scheme = origin.starts_with("http://") || origin.starts_with("https://");
+            if !valid_scheme || origin.ends_with('/') {
+                problems.push(format!("cors origin '{}' must look like https://example.com", origin));
+            }
+        }
+        if self.daily_submission_cap == 0 {
+            problems.push("daily_submission_cap must be at least 1".to_string());
+        }
+        if self.level_histogram_cache_ttl_secs > 3600 {
+            problems.push("level_histogram_cache_ttl_secs must be at most 3600".to_string());
+        }
+        if self.retention_max_age_days == 0 {
+            problems.push("retention_max_age_days must be at least 1".to_string());
+        }
+        if !(1..=MAX_RETENTION_BATCH_SIZE).contains(&self.retention_batch_size) {
+            problems.push(format!("retention_batch_size must be between 1 and {}", MAX_RETENTION_BATCH_SIZE));
+        }
+        if self.retention_interval_hours == 0 {
+            problems.push("retention_interval_hours must be at least 1".to_string());
+        }
+        for modifier in &self.weekly_event_rotation {
+            if !crate::event::KNOWN_MODIFIERS.contains(&modifier.as_str()) {
+                problems.push(format!(
+                    "weekly_event_rotation contains unknown modifier '{}' (expected one of {})",
+                    modifier,
+                    crate::event::KNOWN_MODIFIERS.join(", ")
+                ));
+            }
+        }
+        if let Some(url) = &self.public_base_url {
+            let valid_scheme = url.starts_with("http://") || url.starts_with("https://");
+            if !valid_scheme || url.ends_with('/') {
+                problems.push(format!("public_base_url '{}' must look like https://example.com", url));
+            }
+        }
+        if self.admin_api_key.as_ref().is_some_and(|key| key.len() < MIN_ADMIN_KEY_LENGTH) {
+            problems.push(format!("admin_api_key must be at least {} characters", MIN_ADMIN_KEY_LENGTH));
+        }
+
+        if problems.is_empty() {
+            Ok(())
+        } else {
+            Err(ConfigError(format!("Invalid configuration:\n  - {}", problems.join("\n  - "))))
+        }
+    }
+
+    pub fn bind_target(&self) -> (String, u16) {
+        (self.bind_address.clone(), self.port)
+    }
+
+    pub fn level_histogram_cache_ttl(&self) -> Duration {
+        Duration::from_secs(self.level_histogram_cache_ttl_secs)
+    }
+
+    pub fn retention_interval(&self) -> Duration {
+        Duration::from_secs(self.retention_interval_hours * 3600)
+    }
+
+    pub fn scheme(&self) -> &'static str {
+        if self.tls.is_some() {
+            "https"
+        } else {
+            "http"
+        }
+    }
+
+    // 一条分数的分享链接，没有配置公开地址时为 None
+    pub fn share_url(&self, score_id: &str) -> Option<String> {
+        self.public_base_url.as_ref().map(|base| format!("{}/s/{}", base, score_id))
+    }
+
+    pub fn allows_any_origin(&self) -> bool {
+        self.cors_origins.iter().any(|origin| origin == "*")
+    }
+
+    // 用于 --print-config 的 TOML，密钥已隐藏
+    pub fn to_redacted_toml(&self) -> String {
+        let mut redacted = self.clone();
+        if redacted.admin_api_key.is_some() {
+            redacted.admin_api_key = Some("<redacted>".to_string());
+        }
+        toml::to_string_pretty(&redacted).unwrap_or_else(|e| format!("# failed to serialize config: {}\n", e))
+    }
+}
+
+fn parse_value<T: std::str::FromStr>(name: &str, value: &str) -> Result<T, ConfigError> {
+    value
+        .trim()
+        .parse()
+        .map_err(|_| ConfigError(format!("{} has an invalid value '{}'", name, value)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    // 测试用的配置文件，离开作用域时删除
+    struct ConfigFile(PathBuf);
+
+    impl ConfigFile {
+        fn new(name: &str, content: &str) -> Self {
+            let path = std::env::temp_dir().join(format!("breakout-config-{}-{}.toml", std::process::id(), name));
+            std::fs::write(&path, content).unwrap();
+            Self(path)
+        }
+
+        fn arg(&self) -> String {
+            self.0.display().to_string()
+        }
+    }
+
+    impl Drop for ConfigFile {
+        fn drop(&mut self) {
+            let _ = std::fs::remove_file(&self.0);
+        }
+    }
+
+    fn args(args: &[&str]) -> Vec<String> {
+        args.iter().map(|arg| arg.to_string()).collect()
+    }
+
+    fn load(cli: &[&str], env: &[(&str, &str)]) -> Result<(ServerConfig, RunMode), ConfigError> {
+        let env: HashMap<String, String> =
+            env.iter().map(|(name, value)| (name.to_string(), value.to_string())).collect();
+        ServerConfig::load(&args(cli), |name| env.get(name).cloned())
+    }
+
+    #[test]
+    fn later_layers_override_earlier_ones() {
+        let file = ConfigFile::new(
+            "layers",
+            "port = 9000\ndatabase_url = \"sqlite://file.db\"\ndaily_submission_cap = 5\n",
+        );
+        let config_arg = file.arg();
+
+        let (config, mode) = load(&["--config", &config_arg], &[]).unwrap();
+        assert_eq!((config.port, config.database_url.as_str()), (9000, "sqlite://file.db"));
+        // 文件里没有的设置保持默认值
+        assert_eq!(config.bind_address, ServerConfig::default().bind_address);
+        assert_eq!(mode, RunMode::Serve);
+
+        let env = [("BREAKOUT_PORT", "9100"), ("BREAKOUT_DAILY_SUBMISSION_CAP", "7")];
+        let (config, _) = load(&["--config", &config_arg], &env).unwrap();
+        assert_eq!((config.port, config.daily_submission_cap), (9100, 7));
+        assert_eq!(config.database_url, "sqlite://file.db");
+
+        let cli = ["--config", &config_arg, "--port", "9200", "--database-url", "sqlite://cli.db"];
+        let (config, _) = load(&cli, &env).unwrap();
+        assert_eq!((config.port, config.database_url.as_str()), (9200, "sqlite://cli.db"));
+        assert_eq!(config.daily_submission_cap, 7);
+    }
+
+    #[test]
+    fn config_path_can_come_from_the_environment() {
+        let file = ConfigFile::new("env-path", "port = 9300\n");
+        let (config, _) = load(&[], &[("BREAKOUT_CONFIG", &file.arg())]).unwrap();
+        assert_eq!(config.port, 9300);
+        // 显式指定的文件必须存在
+        assert!(load(&["--config", "/nonexistent/breakout.toml"], &[]).is_err());
+    }
+
+    #[test]
+    fn legacy_daily_cap_variable_yields_to_the_prefixed_one() {
+        let file = ConfigFile::new("legacy-cap", "");
+        let config_arg = file.arg();
+        let (config, _) = load(&["--config", &config_arg], &[("DAILY_SUBMISSION_CAP", "3")]).unwrap();
+        assert_eq!(config.daily_submission_cap, 3);
+        let env = [("DAILY_SUBMISSION_CAP", "3"), ("BREAKOUT_DAILY_SUBMISSION_CAP", "4")];
+        assert_eq!(load(&["--config", &config_arg], &env).unwrap().0.daily_submission_cap, 4);
+    }
+
+    #[test]
+    fn list_variables_are_split_on_commas() {
+        let file = ConfigFile::new("lists", "");
+        let env = [
+            ("BREAKOUT_CORS_ORIGINS", "https://a.example.com, https://b.example.com,"),
+            ("BREAKOUT_WEEKLY_EVENT_ROTATION", "no_lasers,fog_of_war"),
+        ];
+        let (config, _) = load(&["--config", &file.arg()], &env).unwrap();
+        assert_eq!(config.cors_origins, ["https://a.example.com", "https://b.example.com"]);
+        assert_eq!(config.weekly_event_rotation, ["no_lasers", "fog_of_war"]);
+    }
+
+    #[test]
+    fn tls_paths_must_be_set_together() {
+        let file = ConfigFile::new("tls", "");
+        let config_arg = file.arg();
+        assert!(load(&["--config", &config_arg], &[("BREAKOUT_TLS_CERT_PATH", "cert.pem")]).is_err());
+        let env = [("BREAKOUT_TLS_CERT_PATH", "cert.pem"), ("BREAKOUT_TLS_KEY_PATH", "key.pem")];
+        let (config, _) = load(&["--config", &config_arg], &env).unwrap();
+        assert_eq!(config.scheme(), "https");
+    }
+
+    #[test]
+    fn bad_values_and_unknown_keys_are_rejected() {
+        let file = ConfigFile::new("unknown-key", "prot = 9000\n");
+        assert!(load(&["--config", &file.arg()], &[]).is_err());
+        let file = ConfigFile::new("bad-port", "");
+        assert!(load(&["--config", &file.arg()], &[("BREAKOUT_PORT", "http")]).is_err());
+        assert!(load(&["--config", &file.arg(), "--port", "70000"], &[]).is_err());
+        assert!(load(&["--config", &file.arg(), "--port"], &[]).is_err());
+        assert!(load(&["--config", &file.arg(), "--verbose"], &[]).is_err());
+    }
+
+    #[test]
+    fn validation_reports_every_problem() {
+        let config = ServerConfig {
+            bind_address: "example.com".to_string(),
+            port: 0,
+            database_url: "postgres://db".to_string(),
+            cors_origins: vec!["*".to_string(), "https://a.example.com/".to_string()],
+            admin_api_key: Some("short".to_string()),
+            ..ServerConfig::default()
+        };
+        let message = config.validate().unwrap_err().to_string();
+        for field in ["bind_address", "port", "database_url", "cors_origins", "cors origin", "admin_api_key"] {
+            assert!(message.contains(field), "{} missing from:\n{}", field, message);
+        }
+        assert!(ServerConfig::default().validate().is_ok());
+    }
+
+    #[test]
+    fn run_modes_come_from_flags() {
+        let file = ConfigFile::new("modes", "");
+        let config_arg = file.arg();
+        assert_eq!(load(&["--config", &config_arg, "--check"], &[]).unwrap().1, RunMode::Check);
+        assert_eq!(load(&["--config", &config_arg, "--print-config"], &[]).unwrap().1, RunMode::PrintConfig);
+        let (config, mode) = load(&["--config", &config_arg, "--run-retention", "--dry-run"], &[]).unwrap();
+        assert_eq!(mode, RunMode::Retention);
+        assert!(config.retention_dry_run);
+    }
+
+    #[test]
+    fn printed_config_hides_the_admin_key() {
+        let config = ServerConfig {
+            admin_api_key: Some("a-very-secret-admin-key".to_string()),
+            ..ServerConfig::default()
+        };
+        let printed = config.to_redacted_toml();
+        assert!(!printed.contains("a-very-secret-admin-key"));
+        assert!(printed.contains("admin_api_key = \"<redacted>\""));
+        // 打印出来的配置可以原样读回
+        let parsed: ServerConfig = toml::from_str(&ServerConfig::default().to_redacted_toml()).unwrap();
+        assert_eq!(parsed.port, ServerConfig::default().port);
+    }
+
+    #[test]
+    fn example_config_is_valid() {
+        let config: ServerConfig = toml::from_str(include_str!("../config.example.toml")).unwrap();
+        config.validate().unwrap();
+    }
+}
@@ -0,0 +1,277 @@
+use actix_web::body::MessageBody;
+use actix_web::dev::{ServiceRequest, ServiceResponse};
+use actix_web::http::StatusCode;
+use actix_web::middleware::Next;
+use actix_web::web;
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::sqlite::SqliteConnectOptions;
+use sqlx::SqlitePool;
+use std::collections::{BTreeMap, VecDeque};
+use std::str::FromStr;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+
+use crate::{AppState, DbScore, Score};
+
+// 最近错误的环形缓冲区最多保留的条数，超出时丢弃最早的一条
+pub const MAX_RECENT_ERRORS: usize = 50;
+// 仪表盘列出最近 24 小时的前几名
+const TOP_SCORES: i64 = 5;
+
+// 一次以错误状态码结束的请求
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ErrorEntry {
+    pub at: String,
+    pub status: u16,
+    pub method: String,
+    pub path: String,
+}
+
+// 启动以来的错误记录：按类型的累计次数，加上最近几条的明细
+pub struct ErrorLog {
+    started_at: DateTime<Utc>,
+    inner: Mutex<ErrorLogInner>,
+}
+
+#[derive(Default)]
+struct ErrorLogInner {
+    recent: VecDeque<ErrorEntry>,
+    counts: BTreeMap<String, u64>,
+}
+
+impl ErrorLog {
+    pub fn new(started_at: DateTime<Utc>) -> Self {
+        Self {
+            started_at,
+            inner: Mutex::new(ErrorLogInner::default()),
+        }
+    }
+
+    pub fn record(&self, entry: ErrorEntry) {
+        let mut inner = self.inner.lock().unwrap();
+        *inner.counts.entry(error_kind(entry.status)).or_insert(0) += 1;
+        if inner.recent.len() == MAX_RECENT_ERRORS {
+            inner.recent.pop_front();
+        }
+        inner.recent.push_back(entry);
+    }
+
+    // 最近的错误按时间倒序
+    pub fn summary(&self) -> ErrorSummary {
+        let inner = self.inner.lock().unwrap();
+        ErrorSummary {
+            since: self.started_at.to_rfc3339(),
+            total: inner.counts.values().sum(),
+            by_type: inner.counts.clone(),
+            recent: inner.recent.iter().rev().cloned().collect(),
+        }
+    }
+}
+
+// 错误类型：状态码和它的标准名称，例如 "500 Internal Server Error"
+fn error_kind(status: u16) -> String {
+    match StatusCode::from_u16(status).ok().and_then(|status| status.canonical_reason()) {
+        Some(reason) => format!("{} {}", status, reason),
+        None => status.to_string(),
+    }
+}
+
+// 中间件：以 4xx/5xx 结束的请求记入错误记录
+pub async fn record_errors(
+    req: ServiceRequest,
+    next: Next<impl MessageBody>,
+) -> Result<ServiceResponse<impl MessageBody>, actix_web::Error> {
+    let state = req.app_data::<web::Data<Arc<AppState>>>().cloned();
+    let method = req.method().to_string();
+    let path = req.path().to_string();
+    let response = next.call(req).await?;
+    let status = response.status();
+    if let Some(state) = state.filter(|_| status.is_client_error() || status.is_server_error()) {
+        state.error_log.record(ErrorEntry {
+            at: Utc::now().to_rfc3339(),
+            status: status.as_u16(),
+            method,
+            path,
+        });
+    }
+    Ok(response)
+}
+
+// 一个缓存的命中和未命中次数
+#[derive(Default)]
+pub struct CacheCounter {
+    hits: AtomicU64,
+    misses: AtomicU64,
+}
+
+impl CacheCounter {
+    pub fn hit(&self) {
+        self.hits.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn miss(&self) {
+        self.misses.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn stats(&self) -> CacheStats {
+        CacheStats {
+            hits: self.hits.load(Ordering::Relaxed),
+            misses: self.misses.load(Ordering::Relaxed),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
+pub struct CacheStats {
+    pub hits: u64,
+    pub misses: u64,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SubmissionCounts {
+    pub last_hour: u64,
+    pub last_day: u64,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ErrorSummary {
+    // 计数从服务器启动时开始
+    pub since: String,
+    pub total: u64,
+    pub by_type: BTreeMap<String, u64>,
+    pub recent: Vec<ErrorEntry>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CacheSummary {
+    pub level_histogram: CacheStats,
+    pub flags: CacheStats,
+    // 所有缓存合计的命中率，还没有查询过时为空
+    pub hit_rate: Option<f64>,
+}
+
+impl CacheSummary {
+    fn new(level_histogram: CacheStats, flags: CacheStats) -> Self {
+        let hits = level_histogram.hits + flags.hits;
+        let lookups = hits + level_histogram.misses + flags.misses;
+        Self {
+            level_histogram,
+            flags,
+            hit_rate: (lookups > 0).then(|| hits as f64 / lookups as f64),
+        }
+    }
+}
+
+// GET /api/admin/dashboard 的响应，字段只增不改，方便脚本直接读取
+#[derive(Debug, Serialize, Deserialize)]
+pub struct DashboardResponse {
+    pub submissions: SubmissionCounts,
+    pub errors: ErrorSummary,
+    pub cache: CacheSummary,
+    // 数据库文件（加上 WAL 文件）的大小，内存数据库或读取失败时为空
+    pub db_size_bytes: Option<u64>,
+    // 今天（UTC）提交过分数的不同玩家数
+    pub players_today: u64,
+    // 最近 24 小时的最高分，不含未完成局
+    pub top_scores: Vec<Score>,
+    pub generated_at: String,
+}
+
+// 数据库文件在磁盘上的大小
+fn database_size(database_url: &str) -> Option<u64> {
+    let path = SqliteConnectOptions::from_str(database_url).ok()?.get_filename();
+    let size = std::fs::metadata(&path).ok()?.len();
+    let mut wal = path.into_owned().into_os_string();
+    wal.push("-wal");
+    Some(size + std::fs::metadata(wal).map(|metadata| metadata.len()).unwrap_or(0))
+}
+
+async fn submissions_since(pool: &SqlitePool, since: DateTime<Utc>) -> Result<u64, sqlx::Error> {
+    let (count,): (i64,) = sqlx::query_as("SELECT COUNT(*) FROM scores WHERE created_at >= ?1")
+        .bind(since.to_rfc3339())
+        .fetch_one(pool)
+        .await?;
+    Ok(count as u64)
+}
+
+// 汇总仪表盘的各项数据
+pub async fn build_dashboard(state: &AppState, now: DateTime<Utc>) -> Result<DashboardResponse, sqlx::Error> {
+    let last_hour = submissions_since(&state.pool, now - Duration::hours(1)).await?;
+    let last_day = submissions_since(&state.pool, now - Duration::days(1)).await?;
+
+    let (players_today,): (i64,) =
+        sqlx::query_as("SELECT COUNT(DISTINCT player_name) FROM scores WHERE substr(created_at, 1, 10) = ?1")
+            .bind(now.format("%Y-%m-%d").to_string())
+            .fetch_one(&state.pool)
+            .await?;
+
+    let top_scores: Vec<DbScore> = sqlx::query_as(
+        "SELECT * FROM scores WHERE abandoned = 0 AND created_at >= ?1 \
+         ORDER BY score DESC, created_at ASC, id ASC LIMIT ?2",
+    )
+    .bind((now - Duration::days(1)).to_rfc3339())
+    .bind(TOP_SCORES)
+    .fetch_all(&state.pool)
+    .await?;
+
+    Ok(DashboardResponse {
+        submissions: SubmissionCounts { last_hour, last_day },
+        errors: state.error_log.summary(),
+        cache: CacheSummary::new(state.level_histogram_counter.stats(), state.flag_cache.stats()),
+        db_size_bytes: database_size(&state.config.database_url),
+        players_today: players_today as u64,
+        top_scores: top_scores.into_iter().map(|db_score| db_score.into_score(None)).collect(),
+        generated_at: now.to_rfc3339(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(status: u16, path: &str) -> ErrorEntry {
+        ErrorEntry {
+            at: "2025-06-01T00:00:00+00:00".to_string(),
+            status,
+            method: "GET".to_string(),
+            path: path.to_string(),
+        }
+    }
+
+    #[test]
+    fn full_buffer_drops_the_oldest_error_first() {
+        let log = ErrorLog::new(Utc::now());
+        for index in 0..MAX_RECENT_ERRORS + 3 {
+            log.record(entry(404, &format!("/missing/{}", index)));
+        }
+        let summary = log.summary();
+        // 计数不受缓冲区大小限制，明细只留最近的，最新的在前
+        assert_eq!(summary.total, MAX_RECENT_ERRORS as u64 + 3);
+        assert_eq!(summary.recent.len(), MAX_RECENT_ERRORS);
+        assert_eq!(summary.recent[0].path, format!("/missing/{}", MAX_RECENT_ERRORS + 2));
+        assert_eq!(summary.recent[MAX_RECENT_ERRORS - 1].path, "/missing/3");
+    }
+
+    #[test]
+    fn errors_are_counted_by_status_name() {
+        let log = ErrorLog::new(Utc::now());
+        log.record(entry(404, "/a"));
+        log.record(entry(500, "/b"));
+        log.record(entry(404, "/c"));
+        log.record(entry(599, "/d"));
+        let by_type = log.summary().by_type;
+        assert_eq!(by_type.get("404 Not Found"), Some(&2));
+        assert_eq!(by_type.get("500 Internal Server Error"), Some(&1));
+        // 没有标准名称的状态码只写数字
+        assert_eq!(by_type.get("599"), Some(&1));
+        assert_eq!(by_type.len(), 3);
+    }
+
+    #[test]
+    fn cache_hit_rate_is_empty_until_the_first_lookup() {
+        assert_eq!(CacheSummary::new(CacheStats::default(), CacheStats::default()).hit_rate, None);
+        let summary = CacheSummary::new(CacheStats { hits: 3, misses: 1 }, CacheStats { hits: 0, misses: 4 });
+        assert_eq!(summary.hit_rate, Some(3.0 / 8.0));
+    }
+}
@@ -0,0 +1,89 @@
+use chrono::{Datelike, Duration, NaiveDate};
+
+// 每周活动的模式名
+pub use breakout_shared::EVENT_MODE;
+use breakout_shared::api::EventResponse;
+
+// 客户端认识的活动修改器，配置中的轮换列表只能使用这些名字
+pub const KNOWN_MODIFIERS: [&str; 4] = ["double_drops", "no_lasers", "fog_of_war", "team_colors"];
+
+// 某天所在 ISO 周的周一
+fn week_start(date: NaiveDate) -> NaiveDate {
+    date - Duration::days(i64::from(date.weekday().num_days_from_monday()))
+}
+
+// 某天所在的 ISO 周，年份按 ISO 周计算，年初几天可能属于上一年的最后一周
+pub fn week_id(date: NaiveDate) -> String {
+    let week = date.iso_week();
+    format!("{:04}-W{:02}", week.year(), week.week())
+}
+
+// 某天的活动：按 1970-01-05（周一）以来的周数在轮换列表中循环；列表为空时没有活动
+pub fn event_for(date: NaiveDate, rotation: &[String]) -> Option<EventResponse> {
+    if rotation.is_empty() {
+        return None;
+    }
+    let monday = week_start(date);
+    let epoch = NaiveDate::from_ymd_opt(1970, 1, 5).expect("valid epoch");
+    let weeks = (monday - epoch).num_days().div_euclid(7);
+    let index = weeks.rem_euclid(rotation.len() as i64) as usize;
+    Some(EventResponse {
+        id: week_id(date),
+        modifier: rotation[index].clone(),
+        starts_on: monday.format("%Y-%m-%d").to_string(),
+        ends_on: (monday + Duration::days(6)).format("%Y-%m-%d").to_string(),
+    })
+}
+
+// 活动局可以提交到本周或上一周的活动，周日深夜开始、跨过周一才结束的局不会被拒绝
+pub fn accepts_event_id(event_id: &str, today: NaiveDate) -> bool {
+    event_id == week_id(today) || event_id == week_id(today - Duration::days(7))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(year: i32, month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(year, month, day).unwrap()
+    }
+
+    #[test]
+    fn week_start_is_the_monday_of_the_same_week() {
+        // 2025-02-10 是周一
+        assert_eq!(week_start(date(2025, 2, 10)), date(2025, 2, 10));
+        assert_eq!(week_start(date(2025, 2, 13)), date(2025, 2, 10));
+        assert_eq!(week_start(date(2025, 2, 16)), date(2025, 2, 10));
+        // 周一跨月、跨年
+        assert_eq!(week_start(date(2025, 3, 1)), date(2025, 2, 24));
+        assert_eq!(week_start(date(2025, 1, 1)), date(2024, 12, 30));
+    }
+
+    #[test]
+    fn week_id_is_zero_padded() {
+        assert_eq!(week_id(date(2025, 2, 13)), "2025-W07");
+        assert_eq!(week_id(date(2025, 12, 24)), "2025-W52");
+    }
+
+    #[test]
+    fn week_id_uses_the_iso_year_around_new_year() {
+        // 2024-12-30 到 2025-01-05 是 2025 年第 1 周
+        assert_eq!(week_id(date(2024, 12, 30)), "2025-W01");
+        assert_eq!(week_id(date(2025, 1, 5)), "2025-W01");
+        // 2021-01-01（周五）还属于 2020 年的第 53 周
+        assert_eq!(week_id(date(2021, 1, 1)), "2020-W53");
+        assert_eq!(week_id(date(2021, 1, 4)), "2021-W01");
+    }
+
+    #[test]
+    fn event_and_previous_week_are_accepted() {
+        let rotation = vec!["double_drops".to_string(), "no_lasers".to_string()];
+        let event = event_for(date(2025, 1, 1), &rotation).unwrap();
+        assert_eq!(event.id, "2025-W01");
+        assert_eq!(event.starts_on, "2024-12-30");
+        assert_eq!(event.ends_on, "2025-01-05");
+        assert!(accepts_event_id("2025-W01", date(2025, 1, 6)));
+        assert!(!accepts_event_id("2024-W52", date(2025, 1, 6)));
+        assert_eq!(event_for(date(2025, 1, 1), &[]), None);
+    }
+}
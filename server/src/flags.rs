@@ -0,0 +1,227 @@
+use breakout_shared::api::FlagsResponse;
+use breakout_shared::FEATURE_FLAGS;
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+use sqlx::SqlitePool;
+use std::collections::BTreeMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use crate::dashboard::{CacheCounter, CacheStats};
+
+// 缓存的开关最多使用这么久，直接改数据库的修改也会在这之后生效；通过管理接口修改时立即失效
+const CACHE_TTL: Duration = Duration::from_secs(30);
+
+// 管理接口设置开关的请求体
+#[derive(Debug, Deserialize)]
+pub struct SetFlagRequest {
+    pub enabled: bool,
+}
+
+// 缓存的响应和对应的 ETag
+#[derive(Debug)]
+pub struct CachedFlags {
+    pub response: FlagsResponse,
+    pub etag: String,
+}
+
+#[derive(Default)]
+pub struct FlagCache {
+    cached: Mutex<Option<(Instant, Arc<CachedFlags>)>>,
+    counter: CacheCounter,
+}
+
+impl FlagCache {
+    // 返回缓存的开关，缓存缺失或过期时重新读取数据库
+    pub async fn get(&self, pool: &SqlitePool) -> Result<Arc<CachedFlags>, sqlx::Error> {
+        self.get_at(pool, Instant::now()).await
+    }
+
+    async fn get_at(&self, pool: &SqlitePool, now: Instant) -> Result<Arc<CachedFlags>, sqlx::Error> {
+        if let Some((cached_at, cached)) = self.cached.lock().unwrap().as_ref() {
+            if now.saturating_duration_since(*cached_at) < CACHE_TTL {
+                self.counter.hit();
+                return Ok(cached.clone());
+            }
+        }
+        self.counter.miss();
+        let response = load_flags(pool).await?;
+        let cached = Arc::new(CachedFlags {
+            etag: etag(&response),
+            response,
+        });
+        *self.cached.lock().unwrap() = Some((now, cached.clone()));
+        Ok(cached)
+    }
+
+    pub fn invalidate(&self) {
+        *self.cached.lock().unwrap() = None;
+    }
+
+    pub fn stats(&self) -> CacheStats {
+        self.counter.stats()
+    }
+}
+
+// 迁移：创建功能开关表，只保存设置过的开关
+pub async fn migrate_add_feature_flags(pool: &SqlitePool) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS feature_flags (
+            name TEXT PRIMARY KEY,
+            enabled INTEGER NOT NULL,
+            updated_at TEXT NOT NULL
+        )
+        "#,
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+pub fn is_known(name: &str) -> bool {
+    FEATURE_FLAGS.contains(&name)
+}
+
+// 所有已知开关的当前值，没有设置过的为打开；表里不认识的名字忽略
+pub async fn load_flags(pool: &SqlitePool) -> Result<FlagsResponse, sqlx::Error> {
+    let mut flags: BTreeMap<String, bool> = FEATURE_FLAGS.iter().map(|name| (name.to_string(), true)).collect();
+    let rows: Vec<(String, bool)> = sqlx::query_as("SELECT name, enabled FROM feature_flags")
+        .fetch_all(pool)
+        .await?;
+    for (name, enabled) in rows {
+        if let Some(value) = flags.get_mut(&name) {
+            *value = enabled;
+        }
+    }
+    Ok(FlagsResponse { flags })
+}
+
+pub async fn set_flag(pool: &SqlitePool, name: &str, enabled: bool, now: DateTime<Utc>) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"
+        INSERT INTO feature_flags (name, enabled, updated_at) VALUES (?1, ?2, ?3)
+        ON CONFLICT(name) DO UPDATE SET enabled = excluded.enabled, updated_at = excluded.updated_at
+        "#,
+    )
+    .bind(name)
+    .bind(enabled)
+    .bind(now.to_rfc3339())
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+// 删除设置，开关恢复默认（打开）
+pub async fn reset_flag(pool: &SqlitePool, name: &str) -> Result<(), sqlx::Error> {
+    sqlx::query("DELETE FROM feature_flags WHERE name = ?1")
+        .bind(name)
+        .execute(pool)
+        .await?;
+    Ok(())
+}
+
+// 按响应内容计算的强 ETag（FNV-1a），内容不变时重启服务器也不会变
+fn etag(response: &FlagsResponse) -> String {
+    let body = serde_json::to_vec(response).unwrap_or_default();
+    let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+    for byte in body {
+        hash ^= u64::from(byte);
+        hash = hash.wrapping_mul(0x0100_0000_01b3);
+    }
+    format!("\"{:016x}\"", hash)
+}
+
+// If-None-Match 中是否包含当前的 ETag（可以是逗号分隔的多个值或 *）
+pub fn etag_matches(if_none_match: &str, etag: &str) -> bool {
+    if_none_match
+        .split(',')
+        .map(|candidate| candidate.trim().trim_start_matches("W/"))
+        .any(|candidate| candidate == "*" || candidate == etag)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use breakout_shared::FLAG_RACES;
+    use sqlx::sqlite::SqlitePoolOptions;
+
+    // 内存数据库只能有一个连接，否则每个连接看到的是不同的数据库
+    async fn test_pool() -> SqlitePool {
+        let pool = SqlitePoolOptions::new().max_connections(1).connect("sqlite::memory:").await.unwrap();
+        migrate_add_feature_flags(&pool).await.unwrap();
+        pool
+    }
+
+    fn lookups(cache: &FlagCache) -> (u64, u64) {
+        let stats = cache.stats();
+        (stats.hits, stats.misses)
+    }
+
+    #[actix_web::test]
+    async fn cached_flags_expire_after_the_ttl() {
+        let pool = test_pool().await;
+        let cache = FlagCache::default();
+        let start = Instant::now();
+        let first = cache.get_at(&pool, start).await.unwrap();
+        assert!(first.response.flags[FLAG_RACES]);
+
+        // 直接改数据库的修改在缓存过期之前看不到
+        set_flag(&pool, FLAG_RACES, false, Utc::now()).await.unwrap();
+        let cached = cache.get_at(&pool, start + CACHE_TTL - Duration::from_millis(1)).await.unwrap();
+        assert!(Arc::ptr_eq(&first, &cached));
+        assert_eq!(lookups(&cache), (1, 1));
+
+        let reloaded = cache.get_at(&pool, start + CACHE_TTL).await.unwrap();
+        assert!(!reloaded.response.flags[FLAG_RACES]);
+        assert_ne!(reloaded.etag, first.etag);
+        assert_eq!(lookups(&cache), (1, 2));
+    }
+
+    #[actix_web::test]
+    async fn invalidating_reloads_on_the_next_lookup() {
+        let pool = test_pool().await;
+        let cache = FlagCache::default();
+        let start = Instant::now();
+        let original = cache.get_at(&pool, start).await.unwrap();
+
+        set_flag(&pool, FLAG_RACES, false, Utc::now()).await.unwrap();
+        cache.invalidate();
+        let disabled = cache.get_at(&pool, start).await.unwrap();
+        assert!(!disabled.response.flags[FLAG_RACES]);
+
+        reset_flag(&pool, FLAG_RACES).await.unwrap();
+        cache.invalidate();
+        let reset = cache.get_at(&pool, start).await.unwrap();
+        assert!(reset.response.flags[FLAG_RACES]);
+        // 内容回到原样，ETag 也回到原样
+        assert_eq!(reset.etag, original.etag);
+        assert_eq!(lookups(&cache), (0, 3));
+    }
+
+    #[actix_web::test]
+    async fn etag_only_depends_on_the_flag_values() {
+        let pool = test_pool().await;
+        let defaults = load_flags(&pool).await.unwrap();
+        assert_eq!(etag(&defaults), etag(&load_flags(&pool).await.unwrap()));
+        // 写入和默认值相同的设置，响应内容不变，ETag 也不变
+        set_flag(&pool, FLAG_RACES, true, Utc::now()).await.unwrap();
+        assert_eq!(etag(&load_flags(&pool).await.unwrap()), etag(&defaults));
+        set_flag(&pool, FLAG_RACES, false, Utc::now()).await.unwrap();
+        let changed = etag(&load_flags(&pool).await.unwrap());
+        assert_ne!(changed, etag(&defaults));
+        assert!(changed.starts_with('"') && changed.ends_with('"') && changed.len() == 18);
+    }
+
+    #[test]
+    fn if_none_match_accepts_lists_weak_tags_and_wildcards() {
+        let etag = "\"0123456789abcdef\"";
+        assert!(etag_matches(etag, etag));
+        assert!(etag_matches("\"other\", \"0123456789abcdef\"", etag));
+        assert!(etag_matches("W/\"0123456789abcdef\"", etag));
+        assert!(etag_matches("*", etag));
+        assert!(!etag_matches("\"other\"", etag));
+        assert!(!etag_matches("0123456789abcdef", etag));
+    }
+}
@@ -0,0 +1,80 @@
+use actix_web::http::header::HeaderMap;
+use std::net::{IpAddr, SocketAddr};
+
+// 请求方的真实地址。只有配置了 trust_proxy 才读取 X-Forwarded-For，否则客户端可以随意伪造；
+// 代理把对端地址追加在末尾，所以取最后一项（多个同名请求头时取最后一个）
+pub fn client_ip(headers: &HeaderMap, peer: Option<SocketAddr>, trust_proxy: bool) -> Option<IpAddr> {
+    if trust_proxy {
+        let forwarded = headers
+            .get_all("x-forwarded-for")
+            .last()
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.rsplit(',').next())
+            .and_then(|ip| ip.trim().parse().ok());
+        if forwarded.is_some() {
+            return forwarded;
+        }
+    }
+    peer.map(|addr| addr.ip())
+}
+
+// 客户端使用的协议：代理终止 HTTPS 后按 X-Forwarded-Proto 判断，只接受 http 和 https
+pub fn request_scheme(headers: &HeaderMap, trust_proxy: bool, own_scheme: &'static str) -> &'static str {
+    if !trust_proxy {
+        return own_scheme;
+    }
+    match headers.get("x-forwarded-proto").and_then(|value| value.to_str().ok()) {
+        Some(proto) if proto.trim().eq_ignore_ascii_case("https") => "https",
+        Some(proto) if proto.trim().eq_ignore_ascii_case("http") => "http",
+        _ => own_scheme,
+    }
+}
+
+// 日志中显示的地址
+pub fn display_ip(ip: Option<IpAddr>) -> String {
+    ip.map_or_else(|| "-".to_string(), |ip| ip.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use actix_web::http::header::{HeaderName, HeaderValue};
+
+    fn headers(values: &[&str]) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        for value in values {
+            headers.append(HeaderName::from_static("x-forwarded-for"), HeaderValue::from_str(value).unwrap());
+        }
+        headers
+    }
+
+    fn peer() -> Option<SocketAddr> {
+        Some("10.0.0.1:5000".parse().unwrap())
+    }
+
+    fn ip(text: &str) -> Option<IpAddr> {
+        Some(text.parse().unwrap())
+    }
+
+    #[test]
+    fn header_is_ignored_without_trust_proxy() {
+        assert_eq!(client_ip(&headers(&["203.0.113.7"]), peer(), false), ip("10.0.0.1"));
+        assert_eq!(client_ip(&headers(&["203.0.113.7"]), None, false), None);
+    }
+
+    #[test]
+    fn last_entry_of_the_last_header_wins() {
+        let spoofed = headers(&["1.2.3.4, 203.0.113.7"]);
+        assert_eq!(client_ip(&spoofed, peer(), true), ip("203.0.113.7"));
+        let repeated = headers(&["1.2.3.4", "198.51.100.2 , 203.0.113.9"]);
+        assert_eq!(client_ip(&repeated, peer(), true), ip("203.0.113.9"));
+        assert_eq!(client_ip(&headers(&["2001:db8::1"]), peer(), true), ip("2001:db8::1"));
+    }
+
+    #[test]
+    fn missing_or_malformed_header_falls_back_to_peer() {
+        assert_eq!(client_ip(&HeaderMap::new(), peer(), true), ip("10.0.0.1"));
+        assert_eq!(client_ip(&headers(&["203.0.113.7, not-an-ip"]), peer(), true), ip("10.0.0.1"));
+        assert_eq!(client_ip(&headers(&[""]), None, true), None);
+    }
+}
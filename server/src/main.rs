@@ -1,42 +1,42 @@
 use actix_cors::Cors;
-use actix_web::{middleware, web, App, HttpResponse, HttpServer, Result};
-use chrono::Utc;
+use actix_web::{middleware, web, App, HttpRequest, HttpResponse, HttpServer, Result};
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+// 接口格式、模式名、每日挑战的种子算法和关卡分布与客户端共用
+use breakout_shared::api::{
+    BatchItemResult, BatchSubmitResponse, CreateScoreRequest, DailyResponse, ErrorResponse, LeaderboardResponse,
+    RaceJoinRequest, RaceProgressRequest, RecentScoresResponse, RenameScoreRequest, Rival, RivalsResponse, Score,
+    SeasonSummary, SeasonsResponse,
+};
+use breakout_shared::histogram::{LevelBucket, LevelHistogram};
+use breakout_shared::{daily_seed, ADMIN_KEY_HEADER, DAILY_MODE, LEVEL_MODE, TOURNAMENT_MODE};
+use chrono::{DateTime, NaiveDate, Utc};
 use serde::{Deserialize, Serialize};
-use sqlx::{FromRow, SqlitePool};
-use std::sync::Arc;
+use sqlx::sqlite::SqliteConnectOptions;
+use sqlx::{FromRow, SqliteExecutor, SqlitePool};
+use std::collections::HashMap;
+use std::str::FromStr;
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
 use uuid::Uuid;
 
-// 数据模型
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct Score {
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub id: Option<String>,
-    pub player_name: String,
-    pub score: u32,
-    pub level: u32,
-    pub difficulty: String,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub created_at: Option<String>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub rank: Option<u32>,
-}
-
-#[derive(Debug, Serialize, Deserialize)]
-pub struct CreateScoreRequest {
-    pub player_name: String,
-    pub score: u32,
-    pub level: u32,
-    pub difficulty: String,
-}
-
-#[derive(Debug, Serialize, Deserialize)]
-pub struct LeaderboardResponse {
-    pub scores: Vec<Score>,
-    pub total: usize,
-    pub limit: usize,
-    pub offset: usize,
-}
+mod config;
+mod dashboard;
+mod event;
+mod flags;
+mod forwarded;
+mod race;
+mod retention;
+mod share_page;
+mod stats_summary;
+mod tls;
+use config::{RunMode, ServerConfig};
+use dashboard::{CacheCounter, ErrorLog};
+use flags::FlagCache;
+use stats_summary::SummaryRefresher;
+use event::EVENT_MODE;
 
+// 服务器自己的统计格式，客户端不使用
 #[derive(Debug, Serialize, Deserialize)]
 pub struct PlayerStats {
     pub player_name: String,
@@ -46,6 +46,8 @@ pub struct PlayerStats {
     pub highest_level: u32,
     pub favorite_difficulty: String,
     pub scores_by_difficulty: DifficultyScores,
+    // 其中补交的未完成局数量
+    pub abandoned_runs: u32,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -66,13 +68,8 @@ pub struct GlobalStats {
     pub average_score: f64,
     pub scores_by_difficulty: DifficultyScores,
     pub popular_difficulty: String,
-}
-
-#[derive(Debug, Serialize, Deserialize)]
-pub struct ErrorResponse {
-    pub error: String,
-    pub message: String,
-    pub timestamp: String,
+    // 统计的计算时间：来自汇总表时是上次刷新的时间
+    pub computed_at: String,
 }
 
 // 数据库模型
@@ -84,6 +81,25 @@ struct DbScore {
     level: i32,
     difficulty: String,
     created_at: String,
+    season: Option<String>,
+    mode: Option<String>,
+}
+
+impl DbScore {
+    fn into_score(self, rank: Option<u32>) -> Score {
+        Score {
+            id: Some(self.id),
+            player_name: self.player_name,
+            score: self.score as u32,
+            level: self.level as u32,
+            difficulty: self.difficulty,
+            created_at: Some(self.created_at),
+            rank,
+            season: self.season,
+            mode: self.mode,
+            share_url: None,
+        }
+    }
 }
 
 // 查询参数
@@ -91,12 +107,143 @@ struct DbScore {
 pub struct LeaderboardQuery {
     limit: Option<usize>,
     offset: Option<usize>,
+    cursor: Option<String>,
     difficulty: Option<String>,
+    season: Option<String>,
+    // 只看某一周活动的成绩
+    event: Option<String>,
+    // 只看某个模式的成绩；缺省时包括除单关挑战和淘汰赛以外的所有成绩
+    mode: Option<String>,
+    // 只看某一场淘汰赛的成绩
+    tournament: Option<String>,
+    min_level: Option<u32>,
+    max_level: Option<u32>,
+    // "full"（默认）或 "compact"
+    fields: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct RecentScoresQuery {
+    limit: Option<usize>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct LevelStatsQuery {
+    difficulty: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct RivalsQuery {
+    difficulty: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct RetentionQuery {
+    dry_run: Option<bool>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SeasonQuery {
+    season: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct DailyQuery {
+    // YYYY-MM-DD，缺省为今天（UTC）
+    date: Option<String>,
+    player_name: Option<String>,
+    limit: Option<usize>,
+}
+
+// 淘汰赛 id 的最大长度
+const MAX_TOURNAMENT_ID_LENGTH: usize = 64;
+// 对手推荐最多返回几名
+const MAX_RIVALS: i64 = 3;
+// 客户端可以选择的诅咒
+const CURSES: [&str; 4] = ["SmallPaddle", "FastBall", "ScarceDrops", "Timed"];
+
+// 赛季：按 UTC 月份划分，例如 "2025-06"
+fn season_of(time: DateTime<Utc>) -> String {
+    time.format("%Y-%m").to_string()
+}
+
+fn current_season() -> String {
+    season_of(Utc::now())
+}
+
+// 解析赛季参数：缺省为当前赛季，"all" 表示全部赛季（返回 None）
+fn resolve_season(param: Option<&str>) -> std::result::Result<Option<String>, String> {
+    match param {
+        None | Some("current") => Ok(Some(current_season())),
+        Some("all") => Ok(None),
+        Some(season) => {
+            let valid = season.len() == 7
+                && season.as_bytes()[4] == b'-'
+                && season[..4].chars().all(|c| c.is_ascii_digit())
+                && season[5..].parse::<u32>().map(|m| (1..=12).contains(&m)).unwrap_or(false);
+            if valid {
+                Ok(Some(season.to_string()))
+            } else {
+                Err(format!("Invalid season '{}', expected YYYY-MM or 'all'", season))
+            }
+        }
+    }
+}
+
+fn invalid_input_response(message: String) -> HttpResponse {
+    HttpResponse::BadRequest().json(ErrorResponse {
+        error: "Invalid Input".to_string(),
+        message,
+        timestamp: Utc::now().to_rfc3339(),
+    })
+}
+
+// 排行榜分页游标：上一页最后一行的排序键，编码为 base64
+#[derive(Debug, Clone, PartialEq)]
+struct LeaderboardCursor {
+    score: i32,
+    created_at: String,
+    id: String,
+}
+
+impl LeaderboardCursor {
+    fn from_row(row: &DbScore) -> Self {
+        Self {
+            score: row.score,
+            created_at: row.created_at.clone(),
+            id: row.id.clone(),
+        }
+    }
+
+    fn encode(&self) -> String {
+        URL_SAFE_NO_PAD.encode(format!("{}|{}|{}", self.score, self.created_at, self.id))
+    }
+
+    fn decode(cursor: &str) -> Option<Self> {
+        let bytes = URL_SAFE_NO_PAD.decode(cursor).ok()?;
+        let text = String::from_utf8(bytes).ok()?;
+        let mut parts = text.splitn(3, '|');
+        let score = parts.next()?.parse().ok()?;
+        let created_at = parts.next()?.to_string();
+        let id = parts.next()?.to_string();
+        Some(Self { score, created_at, id })
+    }
 }
 
+// 一次批量提交最多包含的记录数
+const MAX_BATCH_SIZE: usize = 50;
+
 // 应用状态
 struct AppState {
     pool: SqlitePool,
+    // 按难度缓存关卡分布（None 表示全部难度）
+    level_histogram_cache: Mutex<HashMap<Option<String>, (Instant, LevelHistogram)>>,
+    level_histogram_counter: CacheCounter,
+    stats_refresher: Arc<SummaryRefresher>,
+    flag_cache: FlagCache,
+    // 以错误状态码结束的请求，由 dashboard::record_errors 中间件记录
+    error_log: ErrorLog,
+    config: ServerConfig,
 }
 
 // 数据库初始化
@@ -115,66 +262,522 @@ async fn init_db(pool: &SqlitePool) -> Result<(), sqlx::Error> {
         CREATE INDEX IF NOT EXISTS idx_score ON scores(score DESC);
         CREATE INDEX IF NOT EXISTS idx_player ON scores(player_name);
         CREATE INDEX IF NOT EXISTS idx_difficulty ON scores(difficulty);
+        CREATE INDEX IF NOT EXISTS idx_level ON scores(level);
+        CREATE INDEX IF NOT EXISTS idx_rank_order ON scores(score DESC, created_at, id);
+        CREATE INDEX IF NOT EXISTS idx_created_at ON scores(created_at DESC);
         "#,
     )
     .execute(pool)
     .await?;
+
+    migrate_add_season(pool).await?;
+    migrate_add_idempotency_key(pool).await?;
+    migrate_add_venue(pool).await?;
+    migrate_add_daily(pool).await?;
+    migrate_add_abandoned(pool).await?;
+    retention::migrate_add_archive(pool).await?;
+    migrate_add_event(pool).await?;
+    migrate_add_tournament(pool).await?;
+    migrate_add_assisted(pool).await?;
+    migrate_add_curse(pool).await?;
+    stats_summary::migrate_add_stats_summary(pool).await?;
+    race::migrate_add_races(pool).await?;
+    flags::migrate_add_feature_flags(pool).await?;
     
     Ok(())
 }
+
+// 迁移：为旧数据库添加 season 列并按 created_at 回填
+async fn migrate_add_season(pool: &SqlitePool) -> Result<(), sqlx::Error> {
+    let columns: Vec<(String,)> = sqlx::query_as("SELECT name FROM pragma_table_info('scores')")
+        .fetch_all(pool)
+        .await?;
+
+    if !columns.iter().any(|(name,)| name == "season") {
+        sqlx::query("ALTER TABLE scores ADD COLUMN season TEXT")
+            .execute(pool)
+            .await?;
+        log::info!("Added season column to scores table");
+    }
+
+    let backfilled = sqlx::query("UPDATE scores SET season = substr(created_at, 1, 7) WHERE season IS NULL")
+        .execute(pool)
+        .await?;
+    if backfilled.rows_affected() > 0 {
+        log::info!("Backfilled season for {} scores", backfilled.rows_affected());
+    }
+
+    sqlx::query("CREATE INDEX IF NOT EXISTS idx_season ON scores(season)")
+        .execute(pool)
+        .await?;
+
+    Ok(())
+}
+
+// 迁移：添加幂等键列（旧记录为空）
+async fn migrate_add_idempotency_key(pool: &SqlitePool) -> Result<(), sqlx::Error> {
+    let columns: Vec<(String,)> = sqlx::query_as("SELECT name FROM pragma_table_info('scores')")
+        .fetch_all(pool)
+        .await?;
+
+    if !columns.iter().any(|(name,)| name == "idempotency_key") {
+        sqlx::query("ALTER TABLE scores ADD COLUMN idempotency_key TEXT")
+            .execute(pool)
+            .await?;
+        log::info!("Added idempotency_key column to scores table");
+    }
+
+    sqlx::query(
+        "CREATE UNIQUE INDEX IF NOT EXISTS idx_idempotency_key ON scores(idempotency_key) \
+         WHERE idempotency_key IS NOT NULL",
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+// 迁移：添加场地标签列（旧记录为空）
+async fn migrate_add_venue(pool: &SqlitePool) -> Result<(), sqlx::Error> {
+    let columns: Vec<(String,)> = sqlx::query_as("SELECT name FROM pragma_table_info('scores')")
+        .fetch_all(pool)
+        .await?;
+
+    if !columns.iter().any(|(name,)| name == "venue") {
+        sqlx::query("ALTER TABLE scores ADD COLUMN venue TEXT")
+            .execute(pool)
+            .await?;
+        log::info!("Added venue column to scores table");
+    }
+
+    Ok(())
+}
+
+// 迁移：添加每日挑战的模式、日期和种子列（旧记录为空）
+async fn migrate_add_daily(pool: &SqlitePool) -> Result<(), sqlx::Error> {
+    let columns: Vec<(String,)> = sqlx::query_as("SELECT name FROM pragma_table_info('scores')")
+        .fetch_all(pool)
+        .await?;
+
+    for column in ["mode", "daily_date", "seed"] {
+        if !columns.iter().any(|(name,)| name == column) {
+            sqlx::query(&format!("ALTER TABLE scores ADD COLUMN {} TEXT", column))
+                .execute(pool)
+                .await?;
+            log::info!("Added {} column to scores table", column);
+        }
+    }
+
+    sqlx::query("CREATE INDEX IF NOT EXISTS idx_daily ON scores(mode, daily_date, score DESC)")
+        .execute(pool)
+        .await?;
+
+    Ok(())
+}
+
+// 迁移：添加未完成局标记列（旧记录都是正常结束的局）
+async fn migrate_add_abandoned(pool: &SqlitePool) -> Result<(), sqlx::Error> {
+    let columns: Vec<(String,)> = sqlx::query_as("SELECT name FROM pragma_table_info('scores')")
+        .fetch_all(pool)
+        .await?;
+
+    if !columns.iter().any(|(name,)| name == "abandoned") {
+        sqlx::query("ALTER TABLE scores ADD COLUMN abandoned INTEGER NOT NULL DEFAULT 0")
+            .execute(pool)
+            .await?;
+        log::info!("Added abandoned column to scores table");
+    }
+
+    Ok(())
+}
+
+// 迁移：给 scores 和 scores_archive 添加活动列（旧记录为空）
+async fn migrate_add_event(pool: &SqlitePool) -> Result<(), sqlx::Error> {
+    for table in ["scores", "scores_archive"] {
+        let columns: Vec<(String,)> = sqlx::query_as(&format!("SELECT name FROM pragma_table_info('{}')", table))
+            .fetch_all(pool)
+            .await?;
+
+        if !columns.iter().any(|(name,)| name == "event_id") {
+            sqlx::query(&format!("ALTER TABLE {} ADD COLUMN event_id TEXT", table))
+                .execute(pool)
+                .await?;
+            log::info!("Added event_id column to {} table", table);
+        }
+    }
+
+    sqlx::query("CREATE INDEX IF NOT EXISTS idx_event ON scores(event_id, score DESC)")
+        .execute(pool)
+        .await?;
+
+    Ok(())
+}
+
+// 迁移：给 scores 和 scores_archive 添加淘汰赛列（旧记录为空）
+async fn migrate_add_tournament(pool: &SqlitePool) -> Result<(), sqlx::Error> {
+    for table in ["scores", "scores_archive"] {
+        let columns: Vec<(String,)> = sqlx::query_as(&format!("SELECT name FROM pragma_table_info('{}')", table))
+            .fetch_all(pool)
+            .await?;
+
+        if !columns.iter().any(|(name,)| name == "tournament_id") {
+            sqlx::query(&format!("ALTER TABLE {} ADD COLUMN tournament_id TEXT", table))
+                .execute(pool)
+                .await?;
+            log::info!("Added tournament_id column to {} table", table);
+        }
+    }
+
+    sqlx::query("CREATE INDEX IF NOT EXISTS idx_tournament ON scores(tournament_id)")
+        .execute(pool)
+        .await?;
+
+    Ok(())
+}
+
+// 迁移：添加辅助标记列，scores 和归档表都要有（旧记录都没有使用辅助）
+async fn migrate_add_assisted(pool: &SqlitePool) -> Result<(), sqlx::Error> {
+    for table in ["scores", "scores_archive"] {
+        let columns: Vec<(String,)> = sqlx::query_as(&format!("SELECT name FROM pragma_table_info('{}')", table))
+            .fetch_all(pool)
+            .await?;
+
+        if !columns.iter().any(|(name,)| name == "assisted") {
+            sqlx::query(&format!("ALTER TABLE {} ADD COLUMN assisted INTEGER NOT NULL DEFAULT 0", table))
+                .execute(pool)
+                .await?;
+            log::info!("Added assisted column to {} table", table);
+        }
+    }
+
+    Ok(())
+}
+
+// 迁移：添加诅咒列，scores 和归档表都要有（旧记录都没有诅咒）
+async fn migrate_add_curse(pool: &SqlitePool) -> Result<(), sqlx::Error> {
+    for table in ["scores", "scores_archive"] {
+        let columns: Vec<(String,)> = sqlx::query_as(&format!("SELECT name FROM pragma_table_info('{}')", table))
+            .fetch_all(pool)
+            .await?;
+
+        if !columns.iter().any(|(name,)| name == "curse") {
+            sqlx::query(&format!("ALTER TABLE {} ADD COLUMN curse TEXT", table))
+                .execute(pool)
+                .await?;
+            log::info!("Added curse column to {} table", table);
+        }
+    }
+
+    Ok(())
+}
+
+// 每日挑战的提交：日期是客户端开始这一局时的 UTC 日期，跨过午夜才结束的局仍可提交到前一天；
+// 种子必须与该日期推导出的种子一致
+fn validate_daily(request: &CreateScoreRequest, now: DateTime<Utc>) -> std::result::Result<(), String> {
+    let date = request
+        .daily_date
+        .as_deref()
+        .and_then(|date| NaiveDate::parse_from_str(date, "%Y-%m-%d").ok())
+        .ok_or_else(|| "Daily runs must include daily_date as YYYY-MM-DD".to_string())?;
+    let today = now.date_naive();
+    if date != today && Some(date) != today.pred_opt() {
+        return Err(format!("The daily challenge for {} is closed", date));
+    }
+    if request.seed != Some(daily_seed(date)) {
+        return Err("Seed does not match the daily challenge".to_string());
+    }
+    Ok(())
+}
+
+// 每周活动的提交：活动必须是本周或上一周的
+fn validate_event(request: &CreateScoreRequest, now: DateTime<Utc>) -> std::result::Result<(), String> {
+    let event_id = request
+        .event_id
+        .as_deref()
+        .ok_or_else(|| "Event runs must include event_id".to_string())?;
+    if !event::accepts_event_id(event_id, now.date_naive()) {
+        return Err(format!("The event '{}' is closed", event_id));
+    }
+    Ok(())
+}
+
+// 单关挑战的提交：level 是挑战的关卡
+fn validate_level(request: &CreateScoreRequest) -> std::result::Result<(), String> {
+    if request.level == 0 {
+        return Err("Level runs must include the level played".to_string());
+    }
+    if request.seed.is_some() || request.daily_date.is_some() {
+        return Err("Level runs cannot include a daily seed or date".to_string());
+    }
+    Ok(())
+}
+
+// 淘汰赛的提交：level 是这场对决打的关卡，必须带上比赛 id
+fn validate_tournament(request: &CreateScoreRequest) -> std::result::Result<(), String> {
+    let tournament_id = request
+        .tournament_id
+        .as_deref()
+        .ok_or_else(|| "Tournament runs must include tournament_id".to_string())?;
+    if tournament_id.is_empty()
+        || tournament_id.len() > MAX_TOURNAMENT_ID_LENGTH
+        || !tournament_id.chars().all(|ch| ch.is_ascii_alphanumeric() || ch == '-')
+    {
+        return Err(format!(
+            "Tournament id must be 1 to {} letters, digits or dashes",
+            MAX_TOURNAMENT_ID_LENGTH
+        ));
+    }
+    if request.level == 0 {
+        return Err("Tournament runs must include the level played".to_string());
+    }
+    if request.seed.is_some() || request.daily_date.is_some() {
+        return Err("Tournament runs cannot include a daily seed or date".to_string());
+    }
+    Ok(())
+}
+
+// 单条提交和批量提交共用的输入检查
+fn validate_score_request(request: &CreateScoreRequest, now: DateTime<Utc>) -> std::result::Result<(), String> {
+    if request.player_name.is_empty() || request.player_name.len() > 50 {
+        return Err("Player name must be between 1 and 50 characters".to_string());
+    }
+    if !["Easy", "Medium", "Hard"].contains(&request.difficulty.as_str()) {
+        return Err("Difficulty must be Easy, Medium, or Hard".to_string());
+    }
+    if request.idempotency_key.as_ref().is_some_and(|key| key.is_empty() || key.len() > 64) {
+        return Err("Idempotency key must be between 1 and 64 characters".to_string());
+    }
+    if request.venue.as_ref().is_some_and(|venue| venue.is_empty() || venue.chars().count() > 64) {
+        return Err("Venue must be between 1 and 64 characters".to_string());
+    }
+    if request.abandoned && request.mode.is_some() {
+        return Err("Abandoned runs cannot be submitted to a game mode".to_string());
+    }
+    if request.event_id.is_some() && request.mode.as_deref() != Some(EVENT_MODE) {
+        return Err("Only event runs can include event_id".to_string());
+    }
+    if request.tournament_id.is_some() && request.mode.as_deref() != Some(TOURNAMENT_MODE) {
+        return Err("Only tournament runs can include tournament_id".to_string());
+    }
+    if request.curse.as_deref().is_some_and(|curse| !CURSES.contains(&curse)) {
+        return Err(format!("Curse must be one of {}", CURSES.join(", ")));
+    }
+    match request.mode.as_deref() {
+        None => {}
+        Some(DAILY_MODE) => validate_daily(request, now)?,
+        Some(EVENT_MODE) => validate_event(request, now)?,
+        Some(LEVEL_MODE) => validate_level(request)?,
+        Some(TOURNAMENT_MODE) => validate_tournament(request)?,
+        Some(mode) => return Err(format!("Unknown mode '{}'", mode)),
+    }
+    Ok(())
+}
+
+// 按 UTC 日期统计玩家某天的提交次数
+async fn submissions_on_day<'e>(
+    executor: impl SqliteExecutor<'e>,
+    player_name: &str,
+    day: &str,
+) -> Result<i64, sqlx::Error> {
+    let submitted: Option<(String, i64)> = sqlx::query_as(
+        "SELECT substr(created_at, 1, 10) AS day, COUNT(*) FROM scores \
+         WHERE player_name = ?1 AND substr(created_at, 1, 10) = ?2 GROUP BY day",
+    )
+    .bind(player_name)
+    .bind(day)
+    .fetch_optional(executor)
+    .await?;
+    Ok(submitted.map(|(_, count)| count).unwrap_or(0))
+}
+
+// 查找使用某个幂等键保存的记录
+async fn find_by_idempotency_key<'e>(
+    executor: impl SqliteExecutor<'e>,
+    key: &str,
+) -> Result<Option<DbScore>, sqlx::Error> {
+    sqlx::query_as("SELECT * FROM scores WHERE idempotency_key = ?1")
+        .bind(key)
+        .fetch_optional(executor)
+        .await
+}
+
+// 按 id 查找一条分数，并按当前榜单计算排名
+async fn find_score(pool: &SqlitePool, id: &str) -> Result<Option<Score>, sqlx::Error> {
+    let row: Option<DbScore> = sqlx::query_as("SELECT * FROM scores WHERE id = ?1")
+        .bind(id)
+        .fetch_optional(pool)
+        .await?;
+    let Some(row) = row else {
+        return Ok(None);
+    };
+    let (abandoned,): (bool,) = sqlx::query_as("SELECT abandoned FROM scores WHERE id = ?1")
+        .bind(id)
+        .fetch_one(pool)
+        .await?;
+    with_rank(pool, row.into_score(None), abandoned).await.map(Some)
+}
+
+// 插入一条分数记录
+async fn insert_score<'e>(
+    executor: impl SqliteExecutor<'e>,
+    request: &CreateScoreRequest,
+    now: DateTime<Utc>,
+) -> Result<Score, sqlx::Error> {
+    let id = Uuid::new_v4().to_string();
+    // 同一时刻生成创建时间和赛季，避免跨月边界不一致
+    let created_at = now.to_rfc3339();
+    let season = season_of(now);
+
+    sqlx::query(
+        r#"
+        INSERT INTO scores (id, player_name, score, level, difficulty, created_at, season, idempotency_key, venue,
+                            mode, daily_date, seed, abandoned, event_id, tournament_id, assisted, curse)
+        VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16, ?17)
+        "#,
+    )
+    .bind(&id)
+    .bind(&request.player_name)
+    .bind(request.score as i32)
+    .bind(request.level as i32)
+    .bind(&request.difficulty)
+    .bind(&created_at)
+    .bind(&season)
+    .bind(request.idempotency_key.as_deref())
+    .bind(request.venue.as_deref())
+    .bind(request.mode.as_deref())
+    .bind(request.daily_date.as_deref())
+    .bind(request.seed.map(|seed| seed.to_string()))
+    .bind(request.abandoned)
+    .bind(request.event_id.as_deref())
+    .bind(request.tournament_id.as_deref())
+    .bind(request.assisted)
+    .bind(request.curse.as_deref())
+    .execute(executor)
+    .await?;
+
+    Ok(Score {
+        id: Some(id),
+        player_name: request.player_name.clone(),
+        score: request.score,
+        level: request.level,
+        difficulty: request.difficulty.clone(),
+        created_at: Some(created_at),
+        rank: None,
+        season: Some(season),
+        mode: request.mode.clone(),
+        share_url: None,
+    })
+}
+
+// 分数在所属赛季和难度榜单上的排名：排在它前面的行数 + 1，与榜单的排序规则一致。
+// 单关挑战只和同一关的单关挑战比较；淘汰赛成绩没有排名，也不参与比较
+async fn leaderboard_rank<'e>(executor: impl SqliteExecutor<'e>, score: &Score) -> Result<u32, sqlx::Error> {
+    let ahead: (i64,) = sqlx::query_as(
+        "SELECT COUNT(*) FROM scores WHERE abandoned = 0 AND difficulty = ?1 AND season IS ?2 \
+         AND mode IS NOT ?9 AND (CASE WHEN ?6 IS ?7 THEN mode IS ?7 AND level = ?8 ELSE mode IS NOT ?7 END) \
+         AND (score > ?3 OR (score = ?3 AND (created_at < ?4 OR (created_at = ?4 AND id < ?5))))",
+    )
+    .bind(&score.difficulty)
+    .bind(score.season.as_deref())
+    .bind(score.score as i32)
+    .bind(score.created_at.as_deref())
+    .bind(score.id.as_deref())
+    .bind(score.mode.as_deref())
+    .bind(LEVEL_MODE)
+    .bind(score.level as i32)
+    .bind(TOURNAMENT_MODE)
+    .fetch_one(executor)
+    .await?;
+    Ok(ahead.0 as u32 + 1)
+}
+
+// 填入新提交分数的排名；未完成局不上榜，淘汰赛成绩只按比赛查询，都没有排名
+async fn with_rank(pool: &SqlitePool, mut score: Score, abandoned: bool) -> Result<Score, sqlx::Error> {
+    if !abandoned && score.mode.as_deref() != Some(TOURNAMENT_MODE) {
+        score.rank = Some(leaderboard_rank(pool, &score).await?);
+    }
+    Ok(score)
+}
+
+// 跨域设置：只允许配置中的来源，"*" 表示任意来源
+fn cors(config: &ServerConfig) -> Cors {
+    let mut cors = Cors::default()
+        .allow_any_method()
+        .allow_any_header()
+        .max_age(3600);
+    if config.allows_any_origin() {
+        cors = cors.allow_any_origin();
+    } else {
+        for origin in &config.cors_origins {
+            cors = cors.allowed_origin(origin);
+        }
+    }
+    cors
+}
+
 // API 处理函数
 
-// 提交分数
+// 提交分数，返回保存的记录及其当前排名
 async fn submit_score(
+    req: HttpRequest,
     data: web::Data<Arc<AppState>>,
     score_req: web::Json<CreateScoreRequest>,
 ) -> Result<HttpResponse> {
+    let now = Utc::now();
+
     // 验证输入
-    if score_req.player_name.is_empty() || score_req.player_name.len() > 50 {
-        return Ok(HttpResponse::BadRequest().json(ErrorResponse {
-            error: "Invalid Input".to_string(),
-            message: "Player name must be between 1 and 50 characters".to_string(),
-            timestamp: Utc::now().to_rfc3339(),
-        }));
+    if let Err(message) = validate_score_request(&score_req, now) {
+        return Ok(invalid_input_response(message));
     }
-    
-    if !["Easy", "Medium", "Hard"].contains(&score_req.difficulty.as_str()) {
-        return Ok(HttpResponse::BadRequest().json(ErrorResponse {
-            error: "Invalid Input".to_string(),
-            message: "Difficulty must be Easy, Medium, or Hard".to_string(),
+
+    // 重复提交同一个幂等键时返回已保存的记录
+    if let Some(key) = score_req.idempotency_key.as_deref() {
+        let existing = find_by_idempotency_key(&data.pool, key).await.map_err(|e| {
+            log::error!("Database error: {:?}", e);
+            actix_web::error::ErrorInternalServerError("Database error")
+        })?;
+        if let Some(existing) = existing {
+            let mut existing = with_rank(&data.pool, existing.into_score(None), score_req.abandoned)
+                .await
+                .map_err(|e| {
+                    log::error!("Database error: {:?}", e);
+                    actix_web::error::ErrorInternalServerError("Database error")
+                })?;
+            existing.share_url = existing.id.as_deref().and_then(|id| data.config.share_url(id));
+            return Ok(HttpResponse::Ok().json(existing));
+        }
+    }
+
+    // 按 UTC 日期统计该玩家今天的提交次数，超过上限时拒绝
+    let today = now.format("%Y-%m-%d").to_string();
+    let submitted_today = submissions_on_day(&data.pool, &score_req.player_name, &today)
+        .await
+        .map_err(|e| {
+            log::error!("Database error: {:?}", e);
+            actix_web::error::ErrorInternalServerError("Database error")
+        })?;
+    if submitted_today >= data.config.daily_submission_cap as i64 {
+        let client_ip = forwarded::client_ip(req.headers(), req.peer_addr(), data.config.trust_proxy);
+        log::warn!(
+            "Player '{}' reached the daily submission cap (client {})",
+            score_req.player_name,
+            forwarded::display_ip(client_ip)
+        );
+        return Ok(HttpResponse::TooManyRequests().json(ErrorResponse {
+            error: "Too Many Requests".to_string(),
+            message: format!("At most {} scores can be submitted per player per day", data.config.daily_submission_cap),
             timestamp: Utc::now().to_rfc3339(),
         }));
     }
-    
-    let id = Uuid::new_v4().to_string();
-    let created_at = Utc::now().to_rfc3339();
-    
-    let result = sqlx::query(
-        r#"
-        INSERT INTO scores (id, player_name, score, level, difficulty, created_at)
-        VALUES (?1, ?2, ?3, ?4, ?5, ?6)
-        "#,
-    )
-    .bind(&id)
-    .bind(&score_req.player_name)
-    .bind(score_req.score as i32)
-    .bind(score_req.level as i32)
-    .bind(&score_req.difficulty)
-    .bind(&created_at)
-    .execute(&data.pool)
-    .await;
-    
-    match result {
-        Ok(_) => {
-            let score = Score {
-                id: Some(id),
-                player_name: score_req.player_name.clone(),
-                score: score_req.score,
-                level: score_req.level,
-                difficulty: score_req.difficulty.clone(),
-                created_at: Some(created_at),
-                rank: None,
-            };
+
+    let inserted = match insert_score(&data.pool, &score_req, now).await {
+        Ok(score) => with_rank(&data.pool, score, score_req.abandoned).await,
+        Err(e) => Err(e),
+    };
+    match inserted {
+        Ok(mut score) => {
+            data.stats_refresher.record_writes(1);
+            score.share_url = score.id.as_deref().and_then(|id| data.config.share_url(id));
             Ok(HttpResponse::Created().json(score))
         }
         Err(e) => {
@@ -188,32 +791,130 @@ async fn submit_score(
     }
 }
 
+// 批量提交分数：在一个事务中逐条校验并插入，返回每条记录的结果
+async fn submit_scores_batch(
+    data: web::Data<Arc<AppState>>,
+    batch: web::Json<Vec<CreateScoreRequest>>,
+) -> Result<HttpResponse> {
+    if batch.is_empty() || batch.len() > MAX_BATCH_SIZE {
+        return Ok(invalid_input_response(format!(
+            "A batch must contain between 1 and {} scores",
+            MAX_BATCH_SIZE
+        )));
+    }
+
+    let database_error = |e: sqlx::Error| {
+        log::error!("Database error: {:?}", e);
+        actix_web::error::ErrorInternalServerError("Database error")
+    };
+
+    let now = Utc::now();
+    let today = now.format("%Y-%m-%d").to_string();
+    let mut tx = data.pool.begin().await.map_err(database_error)?;
+    let mut results = Vec::with_capacity(batch.len());
+
+    for request in batch.iter() {
+        if let Err(reason) = validate_score_request(request, now) {
+            results.push(BatchItemResult::Rejected { reason });
+            continue;
+        }
+
+        // 同一批次内的重复键也会在这里查到前面刚插入的记录
+        if let Some(key) = request.idempotency_key.as_deref() {
+            if let Some(existing) = find_by_idempotency_key(&mut *tx, key).await.map_err(database_error)? {
+                results.push(BatchItemResult::Duplicate { id: existing.id });
+                continue;
+            }
+        }
+
+        let submitted_today = submissions_on_day(&mut *tx, &request.player_name, &today)
+            .await
+            .map_err(database_error)?;
+        if submitted_today >= data.config.daily_submission_cap as i64 {
+            results.push(BatchItemResult::Rejected {
+                reason: format!("At most {} scores can be submitted per player per day", data.config.daily_submission_cap),
+            });
+            continue;
+        }
+
+        let score = insert_score(&mut *tx, request, now).await.map_err(database_error)?;
+        results.push(BatchItemResult::Created { score });
+    }
+
+    tx.commit().await.map_err(database_error)?;
+
+    let created = results
+        .iter()
+        .filter(|result| matches!(result, BatchItemResult::Created { .. }))
+        .count();
+    log::info!("Batch submission: {} of {} scores created", created, results.len());
+    data.stats_refresher.record_writes(created as u32);
+
+    Ok(HttpResponse::Ok().json(BatchSubmitResponse { results }))
+}
+
 // 获取排行榜
 async fn get_leaderboard(
     data: web::Data<Arc<AppState>>,
     query: web::Query<LeaderboardQuery>,
 ) -> Result<HttpResponse> {
     let limit = query.limit.unwrap_or(10).min(100);
-    let offset = query.offset.unwrap_or(0);
-    
-    // 构建查询
-    let mut sql = "SELECT * FROM scores".to_string();
-    let mut conditions = Vec::new();
-    
-    if let Some(ref difficulty) = query.difficulty {
-        if ["Easy", "Medium", "Hard"].contains(&difficulty.as_str()) {
-            conditions.push(format!("difficulty = '{}'", difficulty));
+    let compact = match query.fields.as_deref() {
+        None | Some("full") => false,
+        Some("compact") => true,
+        Some(fields) => {
+            return Ok(invalid_input_response(format!(
+                "Invalid fields '{}', expected 'full' or 'compact'",
+                fields
+            )))
         }
-    }
-    
-    if !conditions.is_empty() {
-        sql.push_str(&format!(" WHERE {}", conditions.join(" AND ")));
-    }
+    };
+    let season = match resolve_season(query.season.as_deref()) {
+        Ok(season) => season,
+        Err(message) => return Ok(invalid_input_response(message)),
+    };
+    let difficulty = query
+        .difficulty
+        .as_deref()
+        .filter(|difficulty| ["Easy", "Medium", "Hard"].contains(difficulty));
+    // 有游标时按排序键继续往后取，忽略 offset
+    let cursor = match query.cursor.as_deref() {
+        Some(cursor) => match LeaderboardCursor::decode(cursor) {
+            Some(cursor) => Some(cursor),
+            None => return Ok(invalid_input_response("Invalid cursor".to_string())),
+        },
+        None => None,
+    };
+    let offset = if cursor.is_some() { 0 } else { query.offset.unwrap_or(0) };
     
-    sql.push_str(" ORDER BY score DESC");
-    sql.push_str(&format!(" LIMIT {} OFFSET {}", limit, offset));
+    // 构建查询条件（参数为 NULL 时不过滤），未完成局不上榜；单关挑战和淘汰赛只在指定模式时出现
+    let conditions = "WHERE abandoned = 0 AND (?1 IS NULL OR difficulty = ?1) AND (?2 IS NULL OR season = ?2) \
+        AND (?3 IS NULL OR level >= ?3) AND (?4 IS NULL OR level <= ?4) AND (?5 IS NULL OR event_id = ?5) \
+        AND (CASE WHEN ?6 IS NULL THEN mode IS NOT ?7 AND mode IS NOT ?8 ELSE mode = ?6 END) \
+        AND (?9 IS NULL OR tournament_id = ?9)";
+    // 排在游标之后的行：分数更低，或同分但提交更晚
+    let page_conditions = format!(
+        "{} AND (?10 IS NULL OR score < ?10 OR (score = ?10 AND (created_at > ?11 OR (created_at = ?11 AND id > ?12))))",
+        conditions
+    );
+    let sql = format!(
+        "SELECT * FROM scores {} ORDER BY score DESC, created_at ASC, id ASC LIMIT {} OFFSET {}",
+        page_conditions, limit, offset
+    );
     
     let scores: Vec<DbScore> = sqlx::query_as(&sql)
+        .bind(difficulty)
+        .bind(season.as_deref())
+        .bind(query.min_level)
+        .bind(query.max_level)
+        .bind(query.event.as_deref())
+        .bind(query.mode.as_deref())
+        .bind(LEVEL_MODE)
+        .bind(TOURNAMENT_MODE)
+        .bind(query.tournament.as_deref())
+        .bind(cursor.as_ref().map(|cursor| cursor.score))
+        .bind(cursor.as_ref().map(|cursor| cursor.created_at.as_str()))
+        .bind(cursor.as_ref().map(|cursor| cursor.id.as_str()))
         .fetch_all(&data.pool)
         .await
         .map_err(|e| {
@@ -222,36 +923,220 @@ async fn get_leaderboard(
         })?;
     
     // 计算总数
-    let count_sql = if let Some(ref difficulty) = query.difficulty {
-        format!("SELECT COUNT(*) FROM scores WHERE difficulty = '{}'", difficulty)
-    } else {
-        "SELECT COUNT(*) FROM scores".to_string()
-    };
-    
+    let count_sql = format!("SELECT COUNT(*) FROM scores {}", conditions);
     let total: (i32,) = sqlx::query_as(&count_sql)
+        .bind(difficulty)
+        .bind(season.as_deref())
+        .bind(query.min_level)
+        .bind(query.max_level)
+        .bind(query.event.as_deref())
+        .bind(query.mode.as_deref())
+        .bind(LEVEL_MODE)
+        .bind(TOURNAMENT_MODE)
+        .bind(query.tournament.as_deref())
         .fetch_one(&data.pool)
         .await
         .unwrap_or((0,));
+    let total = total.0 as usize;
+
+    // 游标分页时由游标之后剩余的行数推算起始排名
+    let start = match &cursor {
+        Some(cursor) => {
+            let remaining_sql = format!("SELECT COUNT(*) FROM scores {}", page_conditions);
+            let remaining: (i32,) = sqlx::query_as(&remaining_sql)
+                .bind(difficulty)
+                .bind(season.as_deref())
+                .bind(query.min_level)
+                .bind(query.max_level)
+                .bind(query.event.as_deref())
+                .bind(query.mode.as_deref())
+                .bind(LEVEL_MODE)
+                .bind(TOURNAMENT_MODE)
+                .bind(query.tournament.as_deref())
+                .bind(cursor.score)
+                .bind(cursor.created_at.as_str())
+                .bind(cursor.id.as_str())
+                .fetch_one(&data.pool)
+                .await
+                .unwrap_or((0,));
+            total.saturating_sub(remaining.0 as usize)
+        }
+        None => offset,
+    };
+
+    let next_cursor = match scores.last() {
+        Some(last) if scores.len() == limit && start + scores.len() < total => {
+            Some(LeaderboardCursor::from_row(last).encode())
+        }
+        _ => None,
+    };
     
     // 转换为响应格式
-    let mut response_scores = Vec::new();
-    for (index, db_score) in scores.iter().enumerate() {
-        response_scores.push(Score {
-            id: Some(db_score.id.clone()),
-            player_name: db_score.player_name.clone(),
-            score: db_score.score as u32,
-            level: db_score.level as u32,
-            difficulty: db_score.difficulty.clone(),
-            created_at: Some(db_score.created_at.clone()),
-            rank: Some((offset + index + 1) as u32),
-        });
-    }
+    let response_scores = scores
+        .into_iter()
+        .enumerate()
+        .map(|(index, db_score)| db_score.into_score(Some((start + index + 1) as u32)))
+        .map(|score| if compact { score.into_compact() } else { score })
+        .collect();
     
     Ok(HttpResponse::Ok().json(LeaderboardResponse {
         scores: response_scores,
-        total: total.0 as usize,
+        total,
         limit,
-        offset,
+        offset: start,
+        next_cursor,
+    }))
+}
+
+// 获取最近提交的分数，按提交时间倒序
+async fn get_recent_scores(
+    data: web::Data<Arc<AppState>>,
+    query: web::Query<RecentScoresQuery>,
+) -> Result<HttpResponse> {
+    let limit = query.limit.unwrap_or(10).clamp(1, 50);
+
+    let scores: Vec<DbScore> = sqlx::query_as("SELECT * FROM scores ORDER BY created_at DESC, id DESC LIMIT ?1")
+        .bind(limit as i64)
+        .fetch_all(&data.pool)
+        .await
+        .map_err(|e| {
+            log::error!("Database error: {:?}", e);
+            actix_web::error::ErrorInternalServerError("Database error")
+        })?;
+
+    Ok(HttpResponse::Ok().json(RecentScoresResponse {
+        scores: scores.into_iter().map(|db_score| db_score.into_score(None)).collect(),
+    }))
+}
+
+// 本周活动；没有配置轮换列表时返回 404
+async fn get_event(data: web::Data<Arc<AppState>>) -> Result<HttpResponse> {
+    match event::event_for(Utc::now().date_naive(), &data.config.weekly_event_rotation) {
+        Some(event) => Ok(HttpResponse::Ok().json(event)),
+        None => Ok(HttpResponse::NotFound().json(ErrorResponse {
+            error: "Not Found".to_string(),
+            message: "No weekly event is configured".to_string(),
+            timestamp: Utc::now().to_rfc3339(),
+        })),
+    }
+}
+
+// 某一天的每日挑战榜单；提供玩家名字时附带该玩家当天的最好成绩和排名
+async fn get_daily(
+    data: web::Data<Arc<AppState>>,
+    query: web::Query<DailyQuery>,
+) -> Result<HttpResponse> {
+    let date = match query.date.as_deref() {
+        None => Utc::now().date_naive(),
+        Some(date) => match NaiveDate::parse_from_str(date, "%Y-%m-%d") {
+            Ok(date) => date,
+            Err(_) => {
+                return Ok(invalid_input_response(format!("Invalid date '{}', expected YYYY-MM-DD", date)))
+            }
+        },
+    };
+    let date_text = date.format("%Y-%m-%d").to_string();
+    let limit = query.limit.unwrap_or(10).clamp(1, 100);
+
+    let database_error = |e: sqlx::Error| {
+        log::error!("Database error: {:?}", e);
+        actix_web::error::ErrorInternalServerError("Database error")
+    };
+
+    let scores: Vec<DbScore> = sqlx::query_as(
+        "SELECT * FROM scores WHERE mode = ?1 AND daily_date = ?2 \
+         ORDER BY score DESC, created_at ASC, id ASC LIMIT ?3",
+    )
+    .bind(DAILY_MODE)
+    .bind(&date_text)
+    .bind(limit as i64)
+    .fetch_all(&data.pool)
+    .await
+    .map_err(database_error)?;
+
+    let total: (i64,) = sqlx::query_as("SELECT COUNT(*) FROM scores WHERE mode = ?1 AND daily_date = ?2")
+        .bind(DAILY_MODE)
+        .bind(&date_text)
+        .fetch_one(&data.pool)
+        .await
+        .map_err(database_error)?;
+
+    let mut player_best = None;
+    if let Some(player_name) = query.player_name.as_deref() {
+        let best: Option<DbScore> = sqlx::query_as(
+            "SELECT * FROM scores WHERE mode = ?1 AND daily_date = ?2 AND player_name = ?3 \
+             ORDER BY score DESC, created_at ASC, id ASC LIMIT 1",
+        )
+        .bind(DAILY_MODE)
+        .bind(&date_text)
+        .bind(player_name)
+        .fetch_optional(&data.pool)
+        .await
+        .map_err(database_error)?;
+
+        if let Some(best) = best {
+            // 排在它前面的行数 + 1 即为排名，与榜单的排序规则一致
+            let ahead: (i64,) = sqlx::query_as(
+                "SELECT COUNT(*) FROM scores WHERE mode = ?1 AND daily_date = ?2 \
+                 AND (score > ?3 OR (score = ?3 AND (created_at < ?4 OR (created_at = ?4 AND id < ?5))))",
+            )
+            .bind(DAILY_MODE)
+            .bind(&date_text)
+            .bind(best.score)
+            .bind(&best.created_at)
+            .bind(&best.id)
+            .fetch_one(&data.pool)
+            .await
+            .map_err(database_error)?;
+            player_best = Some(best.into_score(Some(ahead.0 as u32 + 1)));
+        }
+    }
+
+    Ok(HttpResponse::Ok().json(DailyResponse {
+        date: date_text,
+        seed: daily_seed(date),
+        scores: scores
+            .into_iter()
+            .enumerate()
+            .map(|(index, db_score)| db_score.into_score(Some(index as u32 + 1)))
+            .collect(),
+        total: total.0 as usize,
+        player_best,
+    }))
+}
+
+// 列出所有赛季及其最高分
+async fn get_seasons(data: web::Data<Arc<AppState>>) -> Result<HttpResponse> {
+    let seasons: Vec<(String, i32)> = sqlx::query_as(
+        "SELECT season, COUNT(*) FROM scores WHERE season IS NOT NULL GROUP BY season ORDER BY season DESC"
+    )
+    .fetch_all(&data.pool)
+    .await
+    .map_err(|e| {
+        log::error!("Database error: {:?}", e);
+        actix_web::error::ErrorInternalServerError("Database error")
+    })?;
+    
+    let mut summaries = Vec::new();
+    for (season, total_games) in seasons {
+        let top_score: Option<DbScore> = sqlx::query_as(
+            "SELECT * FROM scores WHERE season = ?1 AND abandoned = 0 ORDER BY score DESC LIMIT 1"
+        )
+        .bind(&season)
+        .fetch_optional(&data.pool)
+        .await
+        .unwrap_or(None);
+        
+        summaries.push(SeasonSummary {
+            season,
+            total_games: total_games as u32,
+            top_score: top_score.map(|db_score| db_score.into_score(Some(1))),
+        });
+    }
+    
+    Ok(HttpResponse::Ok().json(SeasonsResponse {
+        current: current_season(),
+        seasons: summaries,
     }))
 }
 
@@ -259,14 +1144,20 @@ async fn get_leaderboard(
 async fn get_player_stats(
     data: web::Data<Arc<AppState>>,
     player_name: web::Path<String>,
+    query: web::Query<SeasonQuery>,
 ) -> Result<HttpResponse> {
     let player_name = player_name.into_inner();
+    let season = match resolve_season(query.season.as_deref()) {
+        Ok(season) => season,
+        Err(message) => return Ok(invalid_input_response(message)),
+    };
     
     // 检查玩家是否存在
     let exists: (i32,) = sqlx::query_as(
-        "SELECT COUNT(*) FROM scores WHERE player_name = ?1"
+        "SELECT COUNT(*) FROM scores WHERE player_name = ?1 AND (?2 IS NULL OR season = ?2)"
     )
     .bind(&player_name)
+    .bind(season.as_deref())
     .fetch_one(&data.pool)
     .await
     .unwrap_or((0,));
@@ -288,35 +1179,48 @@ async fn get_player_stats(
             AVG(score) as average_score,
             MAX(level) as highest_level
         FROM scores 
-        WHERE player_name = ?1
+        WHERE player_name = ?1 AND (?2 IS NULL OR season = ?2)
         "#
     )
     .bind(&player_name)
+    .bind(season.as_deref())
     .fetch_one(&data.pool)
     .await
     .unwrap_or((0, 0, 0.0, 0));
     
     // 按难度统计
     let easy_count: (i32,) = sqlx::query_as(
-        "SELECT COUNT(*) FROM scores WHERE player_name = ?1 AND difficulty = 'Easy'"
+        "SELECT COUNT(*) FROM scores WHERE player_name = ?1 AND difficulty = 'Easy' AND (?2 IS NULL OR season = ?2)"
     )
     .bind(&player_name)
+    .bind(season.as_deref())
     .fetch_one(&data.pool)
     .await
     .unwrap_or((0,));
     
     let medium_count: (i32,) = sqlx::query_as(
-        "SELECT COUNT(*) FROM scores WHERE player_name = ?1 AND difficulty = 'Medium'"
+        "SELECT COUNT(*) FROM scores WHERE player_name = ?1 AND difficulty = 'Medium' AND (?2 IS NULL OR season = ?2)"
     )
     .bind(&player_name)
+    .bind(season.as_deref())
     .fetch_one(&data.pool)
     .await
     .unwrap_or((0,));
     
     let hard_count: (i32,) = sqlx::query_as(
-        "SELECT COUNT(*) FROM scores WHERE player_name = ?1 AND difficulty = 'Hard'"
+        "SELECT COUNT(*) FROM scores WHERE player_name = ?1 AND difficulty = 'Hard' AND (?2 IS NULL OR season = ?2)"
+    )
+    .bind(&player_name)
+    .bind(season.as_deref())
+    .fetch_one(&data.pool)
+    .await
+    .unwrap_or((0,));
+    
+    let abandoned_count: (i32,) = sqlx::query_as(
+        "SELECT COUNT(*) FROM scores WHERE player_name = ?1 AND abandoned = 1 AND (?2 IS NULL OR season = ?2)"
     )
     .bind(&player_name)
+    .bind(season.as_deref())
     .fetch_one(&data.pool)
     .await
     .unwrap_or((0,));
@@ -342,94 +1246,324 @@ async fn get_player_stats(
             medium: medium_count.0 as u32,
             hard: hard_count.0 as u32,
         },
+        abandoned_runs: abandoned_count.0 as u32,
+    }))
+}
+
+// 对手推荐：同难度下最好成绩最接近且高于玩家的几名玩家（同分不算），只看会上榜的成绩
+async fn get_player_rivals(
+    data: web::Data<Arc<AppState>>,
+    player_name: web::Path<String>,
+    query: web::Query<RivalsQuery>,
+) -> Result<HttpResponse> {
+    let player_name = player_name.into_inner();
+    let difficulty = match query.difficulty.as_deref() {
+        Some(difficulty) if ["Easy", "Medium", "Hard"].contains(&difficulty) => difficulty.to_string(),
+        Some(difficulty) => {
+            return Ok(invalid_input_response(format!(
+                "Invalid difficulty '{}', expected Easy, Medium or Hard",
+                difficulty
+            )))
+        }
+        None => return Ok(invalid_input_response("Missing difficulty".to_string())),
+    };
+
+    let best: (Option<i32>,) = sqlx::query_as(
+        "SELECT MAX(score) FROM scores WHERE player_name = ?1 AND difficulty = ?2 AND abandoned = 0 \
+         AND mode IS NOT ?3 AND mode IS NOT ?4"
+    )
+    .bind(&player_name)
+    .bind(&difficulty)
+    .bind(LEVEL_MODE)
+    .bind(TOURNAMENT_MODE)
+    .fetch_one(&data.pool)
+    .await
+    .map_err(|e| {
+        log::error!("Database error: {:?}", e);
+        actix_web::error::ErrorInternalServerError("Database error")
+    })?;
+    let best_score = best.0.map(|score| score as u32);
+    let floor = best.0.unwrap_or(0);
+
+    let rows: Vec<(String, i32)> = sqlx::query_as(
+        "SELECT player_name, MAX(score) AS best FROM scores WHERE difficulty = ?1 AND abandoned = 0 \
+         AND mode IS NOT ?2 AND mode IS NOT ?3 AND player_name != ?4 \
+         GROUP BY player_name HAVING best > ?5 ORDER BY best ASC, player_name ASC LIMIT ?6"
+    )
+    .bind(&difficulty)
+    .bind(LEVEL_MODE)
+    .bind(TOURNAMENT_MODE)
+    .bind(&player_name)
+    .bind(floor)
+    .bind(MAX_RIVALS)
+    .fetch_all(&data.pool)
+    .await
+    .map_err(|e| {
+        log::error!("Database error: {:?}", e);
+        actix_web::error::ErrorInternalServerError("Database error")
+    })?;
+
+    let rivals = rows
+        .into_iter()
+        .map(|(name, score)| Rival {
+            player_name: name,
+            best_score: score as u32,
+            gap: (score - floor) as u32,
+        })
+        .collect();
+
+    Ok(HttpResponse::Ok().json(RivalsResponse {
+        player_name,
+        difficulty,
+        best_score,
+        rivals,
     }))
 }
-// 获取全局统计
+// 获取全局统计：优先读取后台定期刷新的汇总表，汇总缺失或太旧时实时计算
 async fn get_global_stats(
     data: web::Data<Arc<AppState>>,
+    query: web::Query<SeasonQuery>,
 ) -> Result<HttpResponse> {
+    let season = match resolve_season(query.season.as_deref()) {
+        Ok(season) => season,
+        Err(message) => return Ok(invalid_input_response(message)),
+    };
+    let now = Utc::now();
+
+    match summary_global_stats(&data.pool, season.as_deref(), now).await {
+        Ok(Some(stats)) => {
+            data.stats_refresher.record_served(true);
+            return Ok(HttpResponse::Ok().json(stats));
+        }
+        Ok(None) => {}
+        Err(e) => log::error!("Failed to read stats summary: {:?}", e),
+    }
+    data.stats_refresher.record_served(false);
+    Ok(HttpResponse::Ok().json(live_global_stats(&data.pool, season.as_deref(), now).await))
+}
+
+// 从汇总表组装全局统计；最高分记录在刷新之后被删除时也返回 None，改用实时查询
+async fn summary_global_stats(
+    pool: &SqlitePool,
+    season: Option<&str>,
+    now: DateTime<Utc>,
+) -> Result<Option<GlobalStats>, sqlx::Error> {
+    let Some(summary) = stats_summary::load(pool, season, now).await? else {
+        return Ok(None);
+    };
+    let highest_score_ever = match summary.highest_score_id.as_deref() {
+        Some(id) => {
+            let row: Option<DbScore> = sqlx::query_as("SELECT * FROM scores WHERE id = ?1")
+                .bind(id)
+                .fetch_optional(pool)
+                .await?;
+            match row {
+                Some(row) => Some(row.into_score(Some(1))),
+                None => return Ok(None),
+            }
+        }
+        None => None,
+    };
+    let scores_by_difficulty = DifficultyScores {
+        easy: summary.easy_games as u32,
+        medium: summary.medium_games as u32,
+        hard: summary.hard_games as u32,
+    };
+
+    Ok(Some(GlobalStats {
+        total_games_played: summary.total_games as u32,
+        total_players: summary.total_players as u32,
+        highest_score_ever,
+        average_score: summary.average_score,
+        popular_difficulty: popular_difficulty(&scores_by_difficulty),
+        scores_by_difficulty,
+        computed_at: summary.computed_at,
+    }))
+}
+
+// 游戏次数最多的难度，都没有记录时为 Medium
+fn popular_difficulty(counts: &DifficultyScores) -> String {
+    let max_count = counts.easy.max(counts.medium).max(counts.hard);
+    if max_count == counts.easy && counts.easy > 0 {
+        "Easy".to_string()
+    } else if max_count == counts.hard && counts.hard > 0 {
+        "Hard".to_string()
+    } else {
+        "Medium".to_string()
+    }
+}
+
+// 实时计算全局统计，每次都要扫描整张表
+async fn live_global_stats(pool: &SqlitePool, season: Option<&str>, now: DateTime<Utc>) -> GlobalStats {
     // 总游戏数和平均分
     let game_stats: (i32, f64) = sqlx::query_as(
-        "SELECT COUNT(*), AVG(score) FROM scores"
+        "SELECT COUNT(*), AVG(score) FROM scores WHERE (?1 IS NULL OR season = ?1)"
     )
-    .fetch_one(&data.pool)
+    .bind(season)
+    .fetch_one(pool)
     .await
     .unwrap_or((0, 0.0));
     
     // 总玩家数
     let player_count: (i32,) = sqlx::query_as(
-        "SELECT COUNT(DISTINCT player_name) FROM scores"
+        "SELECT COUNT(DISTINCT player_name) FROM scores WHERE (?1 IS NULL OR season = ?1)"
     )
-    .fetch_one(&data.pool)
+    .bind(season)
+    .fetch_one(pool)
     .await
     .unwrap_or((0,));
     
     // 最高分记录
     let highest_score: Option<DbScore> = sqlx::query_as(
-        "SELECT * FROM scores ORDER BY score DESC LIMIT 1"
+        "SELECT * FROM scores WHERE (?1 IS NULL OR season = ?1) ORDER BY score DESC LIMIT 1"
     )
-    .fetch_optional(&data.pool)
+    .bind(season)
+    .fetch_optional(pool)
     .await
     .unwrap_or(None);
     
     // 按难度统计
     let easy_count: (i32,) = sqlx::query_as(
-        "SELECT COUNT(*) FROM scores WHERE difficulty = 'Easy'"
+        "SELECT COUNT(*) FROM scores WHERE difficulty = 'Easy' AND (?1 IS NULL OR season = ?1)"
     )
-    .fetch_one(&data.pool)
+    .bind(season)
+    .fetch_one(pool)
     .await
     .unwrap_or((0,));
     
     let medium_count: (i32,) = sqlx::query_as(
-        "SELECT COUNT(*) FROM scores WHERE difficulty = 'Medium'"
+        "SELECT COUNT(*) FROM scores WHERE difficulty = 'Medium' AND (?1 IS NULL OR season = ?1)"
     )
-    .fetch_one(&data.pool)
+    .bind(season)
+    .fetch_one(pool)
     .await
     .unwrap_or((0,));
     
     let hard_count: (i32,) = sqlx::query_as(
-        "SELECT COUNT(*) FROM scores WHERE difficulty = 'Hard'"
+        "SELECT COUNT(*) FROM scores WHERE difficulty = 'Hard' AND (?1 IS NULL OR season = ?1)"
     )
-    .fetch_one(&data.pool)
+    .bind(season)
+    .fetch_one(pool)
     .await
     .unwrap_or((0,));
     
-    // 确定最受欢迎的难度
-    let mut popular_difficulty = "Medium".to_string();
-    let max_count = easy_count.0.max(medium_count.0).max(hard_count.0);
-    if max_count == easy_count.0 && easy_count.0 > 0 {
-        popular_difficulty = "Easy".to_string();
-    } else if max_count == hard_count.0 && hard_count.0 > 0 {
-        popular_difficulty = "Hard".to_string();
-    }
-    
-    Ok(HttpResponse::Ok().json(GlobalStats {
+    let scores_by_difficulty = DifficultyScores {
+        easy: easy_count.0 as u32,
+        medium: medium_count.0 as u32,
+        hard: hard_count.0 as u32,
+    };
+
+    GlobalStats {
         total_games_played: game_stats.0 as u32,
         total_players: player_count.0 as u32,
-        highest_score_ever: highest_score.map(|db_score| Score {
-            id: Some(db_score.id),
-            player_name: db_score.player_name,
-            score: db_score.score as u32,
-            level: db_score.level as u32,
-            difficulty: db_score.difficulty,
-            created_at: Some(db_score.created_at),
-            rank: Some(1),
-        }),
+        highest_score_ever: highest_score.map(|db_score| db_score.into_score(Some(1))),
         average_score: game_stats.1,
-        scores_by_difficulty: DifficultyScores {
-            easy: easy_count.0 as u32,
-            medium: medium_count.0 as u32,
-            hard: hard_count.0 as u32,
-        },
-        popular_difficulty,
-    }))
+        popular_difficulty: popular_difficulty(&scores_by_difficulty),
+        scores_by_difficulty,
+        computed_at: now.to_rfc3339(),
+    }
+}
+
+// 最终到达关卡的分布，短时间缓存
+async fn get_level_stats(
+    data: web::Data<Arc<AppState>>,
+    query: web::Query<LevelStatsQuery>,
+) -> Result<HttpResponse> {
+    let difficulty = query
+        .difficulty
+        .clone()
+        .filter(|difficulty| ["Easy", "Medium", "Hard"].contains(&difficulty.as_str()));
+
+    if let Some((cached_at, histogram)) = data.level_histogram_cache.lock().unwrap().get(&difficulty) {
+        if cached_at.elapsed() < data.config.level_histogram_cache_ttl() {
+            data.level_histogram_counter.hit();
+            return Ok(HttpResponse::Ok().json(histogram));
+        }
+    }
+    data.level_histogram_counter.miss();
+
+    let rows: Vec<(i32, i32)> = sqlx::query_as(
+        "SELECT level, COUNT(*) FROM scores WHERE abandoned = 0 AND mode IS NOT ?2 AND mode IS NOT ?3 \
+         AND (?1 IS NULL OR difficulty = ?1) GROUP BY level ORDER BY level"
+    )
+    .bind(difficulty.as_deref())
+    .bind(LEVEL_MODE)
+    .bind(TOURNAMENT_MODE)
+    .fetch_all(&data.pool)
+    .await
+    .map_err(|e| {
+        log::error!("Database error: {:?}", e);
+        actix_web::error::ErrorInternalServerError("Database error")
+    })?;
+
+    let buckets = rows
+        .into_iter()
+        .map(|(level, count)| LevelBucket {
+            level: level as u32,
+            count: count as u32,
+        })
+        .collect();
+    let histogram = LevelHistogram::from_buckets(difficulty.clone(), buckets);
+
+    data.level_histogram_cache
+        .lock()
+        .unwrap()
+        .insert(difficulty, (Instant::now(), histogram.clone()));
+
+    Ok(HttpResponse::Ok().json(histogram))
+}
+
+// 查询一条分数的完整记录和当前排名，分享链接和外部工具使用
+async fn get_score(data: web::Data<Arc<AppState>>, score_id: web::Path<String>) -> Result<HttpResponse> {
+    match find_score(&data.pool, &score_id).await {
+        Ok(Some(mut score)) => {
+            score.share_url = data.config.share_url(&score_id);
+            Ok(HttpResponse::Ok().json(score))
+        }
+        Ok(None) => Ok(HttpResponse::NotFound().json(ErrorResponse {
+            error: "Not Found".to_string(),
+            message: "Score not found".to_string(),
+            timestamp: Utc::now().to_rfc3339(),
+        })),
+        Err(e) => {
+            log::error!("Database error: {:?}", e);
+            Ok(HttpResponse::InternalServerError().json(ErrorResponse {
+                error: "Database Error".to_string(),
+                message: "Failed to fetch score".to_string(),
+                timestamp: Utc::now().to_rfc3339(),
+            }))
+        }
+    }
+}
+
+// 分享链接打开的成绩卡片页面，名次按打开时的榜单计算
+async fn score_page(data: web::Data<Arc<AppState>>, score_id: web::Path<String>) -> Result<HttpResponse> {
+    let html = |mut response: actix_web::HttpResponseBuilder, body: String| {
+        response.content_type("text/html; charset=utf-8").body(body)
+    };
+    match find_score(&data.pool, &score_id).await {
+        Ok(Some(score)) => {
+            let page_url = data.config.share_url(&score_id);
+            Ok(html(HttpResponse::Ok(), share_page::render_score_page(&score, page_url.as_deref())))
+        }
+        Ok(None) => Ok(html(HttpResponse::NotFound(), share_page::render_not_found())),
+        Err(e) => {
+            log::error!("Database error: {:?}", e);
+            Ok(HttpResponse::InternalServerError().finish())
+        }
+    }
 }
 
 // 删除分数（管理员功能）
 async fn delete_score(
+    req: HttpRequest,
     data: web::Data<Arc<AppState>>,
     score_id: web::Path<String>,
 ) -> Result<HttpResponse> {
+    if let Some(response) = require_admin(&req, &data.config) {
+        return Ok(response);
+    }
+
     let result = sqlx::query("DELETE FROM scores WHERE id = ?1")
         .bind(score_id.as_str())
         .execute(&data.pool)
@@ -438,6 +1572,7 @@ async fn delete_score(
     match result {
         Ok(result) => {
             if result.rows_affected() > 0 {
+                data.stats_refresher.request_refresh();
                 Ok(HttpResponse::NoContent().finish())
             } else {
                 Ok(HttpResponse::NotFound().json(ErrorResponse {
@@ -458,44 +1593,424 @@ async fn delete_score(
     }
 }
 
-// 健康检查
-async fn health_check() -> Result<HttpResponse> {
+// 修改分数的玩家名（管理员功能），分数和排名不变
+async fn rename_score(
+    req: HttpRequest,
+    data: web::Data<Arc<AppState>>,
+    score_id: web::Path<String>,
+    request: web::Json<RenameScoreRequest>,
+) -> Result<HttpResponse> {
+    if let Some(response) = require_admin(&req, &data.config) {
+        return Ok(response);
+    }
+    if request.player_name.is_empty() || request.player_name.len() > 50 {
+        return Ok(invalid_input_response(
+            "Player name must be between 1 and 50 characters".to_string(),
+        ));
+    }
+
+    let result = sqlx::query("UPDATE scores SET player_name = ?1 WHERE id = ?2")
+        .bind(&request.player_name)
+        .bind(score_id.as_str())
+        .execute(&data.pool)
+        .await;
+
+    match result {
+        Ok(result) if result.rows_affected() > 0 => {
+            log::info!("Renamed score {} to {}", score_id.as_str(), request.player_name);
+            data.stats_refresher.request_refresh();
+            Ok(HttpResponse::NoContent().finish())
+        }
+        Ok(_) => Ok(HttpResponse::NotFound().json(ErrorResponse {
+            error: "Not Found".to_string(),
+            message: "Score not found".to_string(),
+            timestamp: Utc::now().to_rfc3339(),
+        })),
+        Err(e) => {
+            log::error!("Database error: {:?}", e);
+            Ok(HttpResponse::InternalServerError().json(ErrorResponse {
+                error: "Database Error".to_string(),
+                message: "Failed to rename score".to_string(),
+                timestamp: Utc::now().to_rfc3339(),
+            }))
+        }
+    }
+}
+
+// 管理接口必须在请求头中提供配置的管理密钥，否则返回 401 响应；没有配置密钥时管理接口全部关闭，返回 503
+fn require_admin(req: &HttpRequest, config: &ServerConfig) -> Option<HttpResponse> {
+    let Some(admin_key) = config.admin_api_key.as_ref() else {
+        return Some(HttpResponse::ServiceUnavailable().json(ErrorResponse {
+            error: "Admin API disabled".to_string(),
+            message: "No admin_api_key is configured on the server".to_string(),
+            timestamp: Utc::now().to_rfc3339(),
+        }));
+    };
+    let provided = req.headers().get(ADMIN_KEY_HEADER).map(|value| value.as_bytes());
+    if provided.is_some_and(|provided| constant_time_eq(provided, admin_key.as_bytes())) {
+        return None;
+    }
+    Some(HttpResponse::Unauthorized().json(ErrorResponse {
+        error: "Unauthorized".to_string(),
+        message: "A valid X-Admin-Key header is required".to_string(),
+        timestamp: Utc::now().to_rfc3339(),
+    }))
+}
+
+// 比较密钥时逐字节比较完所有字节，耗时不随第一个不同字节的位置变化；只会暴露长度是否相同
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0u8, |diff, (x, y)| diff | (x ^ y)) == 0
+}
+
+// 立即执行一次数据保留任务；dry_run 未指定时使用配置
+async fn run_retention_now(
+    req: HttpRequest,
+    data: web::Data<Arc<AppState>>,
+    query: web::Query<RetentionQuery>,
+) -> Result<HttpResponse> {
+    if let Some(response) = require_admin(&req, &data.config) {
+        return Ok(response);
+    }
+
+    let dry_run = query.dry_run.unwrap_or(data.config.retention_dry_run);
+    match retention::run_retention(&data.pool, &data.config, dry_run, Utc::now()).await {
+        Ok(report) => {
+            // 归档改变了关卡分布和全局统计
+            if report.archived > 0 {
+                data.level_histogram_cache.lock().unwrap().clear();
+                data.stats_refresher.request_refresh();
+            }
+            Ok(HttpResponse::Ok().json(report))
+        }
+        Err(e) => {
+            log::error!("Retention job failed: {:?}", e);
+            Ok(HttpResponse::InternalServerError().json(ErrorResponse {
+                error: "Database Error".to_string(),
+                message: "Failed to run retention job".to_string(),
+                timestamp: Utc::now().to_rfc3339(),
+            }))
+        }
+    }
+}
+
+// 运行指标（管理员功能），目前包括统计汇总的刷新情况
+async fn get_metrics(req: HttpRequest, data: web::Data<Arc<AppState>>) -> Result<HttpResponse> {
+    if let Some(response) = require_admin(&req, &data.config) {
+        return Ok(response);
+    }
+
     Ok(HttpResponse::Ok().json(serde_json::json!({
-        "status": "healthy",
+        "stats_summary": data.stats_refresher.metrics(),
         "timestamp": Utc::now().to_rfc3339()
     })))
 }
 
-// 配置路由
-fn config_routes(cfg: &mut web::ServiceConfig) {
-    cfg.service(
-        web::scope("/api")
-            .route("/health", web::get().to(health_check))
-            .route("/scores", web::post().to(submit_score))
-            .route("/scores", web::get().to(get_leaderboard))
-            .route("/scores/{id}", web::delete().to(delete_score))
-            .route("/players/{player_name}/stats", web::get().to(get_player_stats))
-            .route("/stats/global", web::get().to(get_global_stats))
-    );
+// 运维仪表盘（管理员功能）：最近的提交量、启动以来的错误、缓存命中率、数据库大小、今天的玩家数和最近 24 小时的前五名
+async fn get_dashboard(req: HttpRequest, data: web::Data<Arc<AppState>>) -> Result<HttpResponse> {
+    if let Some(response) = require_admin(&req, &data.config) {
+        return Ok(response);
+    }
+
+    match dashboard::build_dashboard(&data, Utc::now()).await {
+        Ok(dashboard) => Ok(HttpResponse::Ok().json(dashboard)),
+        Err(e) => {
+            log::error!("Database error: {:?}", e);
+            Ok(HttpResponse::InternalServerError().json(ErrorResponse {
+                error: "Database Error".to_string(),
+                message: "Failed to build dashboard".to_string(),
+                timestamp: Utc::now().to_rfc3339(),
+            }))
+        }
+    }
 }
 
-#[actix_web::main]
-async fn main() -> std::io::Result<()> {
-    env_logger::init_from_env(env_logger::Env::new().default_filter_or("info"));
-    
-    log::info!("Starting Breakout Game API Server...");
-    
-    // 创建数据库文件（如果不存在）
-    let db_path = "breakout_scores.db";
-    if !std::path::Path::new(db_path).exists() {
-        std::fs::File::create(db_path).expect("Failed to create database file");
+// 立即刷新统计汇总（管理员功能），返回刷新后的指标
+async fn refresh_stats_summary(req: HttpRequest, data: web::Data<Arc<AppState>>) -> Result<HttpResponse> {
+    if let Some(response) = require_admin(&req, &data.config) {
+        return Ok(response);
+    }
+
+    match data.stats_refresher.refresh_now(&data.pool).await {
+        Ok(()) => Ok(HttpResponse::Ok().json(data.stats_refresher.metrics())),
+        Err(e) => {
+            log::error!("Stats summary refresh failed: {:?}", e);
+            Ok(HttpResponse::InternalServerError().json(ErrorResponse {
+                error: "Database Error".to_string(),
+                message: "Failed to refresh stats summary".to_string(),
+                timestamp: Utc::now().to_rfc3339(),
+            }))
+        }
+    }
+}
+
+// 功能开关，客户端启动时读取。带 ETag，内容没变时返回 304
+async fn get_flags(req: HttpRequest, data: web::Data<Arc<AppState>>) -> Result<HttpResponse> {
+    let cached = match data.flag_cache.get(&data.pool).await {
+        Ok(cached) => cached,
+        Err(e) => {
+            log::error!("Database error: {:?}", e);
+            return Ok(HttpResponse::InternalServerError().json(ErrorResponse {
+                error: "Database Error".to_string(),
+                message: "Failed to load feature flags".to_string(),
+                timestamp: Utc::now().to_rfc3339(),
+            }));
+        }
+    };
+
+    let not_modified = req
+        .headers()
+        .get(actix_web::http::header::IF_NONE_MATCH)
+        .and_then(|value| value.to_str().ok())
+        .is_some_and(|value| flags::etag_matches(value, &cached.etag));
+    let mut response = if not_modified { HttpResponse::NotModified() } else { HttpResponse::Ok() };
+    response
+        .insert_header((actix_web::http::header::ETAG, cached.etag.clone()))
+        .insert_header((actix_web::http::header::CACHE_CONTROL, "no-cache"));
+    if not_modified {
+        Ok(response.finish())
+    } else {
+        Ok(response.json(&cached.response))
+    }
+}
+
+fn unknown_flag_response(name: &str) -> HttpResponse {
+    HttpResponse::NotFound().json(ErrorResponse {
+        error: "Not Found".to_string(),
+        message: format!("Unknown feature flag '{}'", name),
+        timestamp: Utc::now().to_rfc3339(),
+    })
+}
+
+// 设置功能开关（管理员功能），返回设置后的全部开关
+async fn set_flag(
+    req: HttpRequest,
+    data: web::Data<Arc<AppState>>,
+    name: web::Path<String>,
+    request: web::Json<flags::SetFlagRequest>,
+) -> Result<HttpResponse> {
+    if let Some(response) = require_admin(&req, &data.config) {
+        return Ok(response);
+    }
+    if !flags::is_known(&name) {
+        return Ok(unknown_flag_response(&name));
+    }
+
+    let result = flags::set_flag(&data.pool, &name, request.enabled, Utc::now()).await;
+    flag_update_response(&data, &name, result).await
+}
+
+// 清除功能开关的设置（管理员功能），恢复默认的打开状态
+async fn reset_flag(req: HttpRequest, data: web::Data<Arc<AppState>>, name: web::Path<String>) -> Result<HttpResponse> {
+    if let Some(response) = require_admin(&req, &data.config) {
+        return Ok(response);
+    }
+    if !flags::is_known(&name) {
+        return Ok(unknown_flag_response(&name));
+    }
+
+    let result = flags::reset_flag(&data.pool, &name).await;
+    flag_update_response(&data, &name, result).await
+}
+
+async fn flag_update_response(
+    data: &AppState,
+    name: &str,
+    result: std::result::Result<(), sqlx::Error>,
+) -> Result<HttpResponse> {
+    data.flag_cache.invalidate();
+    let result = match result {
+        Ok(()) => flags::load_flags(&data.pool).await,
+        Err(e) => Err(e),
+    };
+    match result {
+        Ok(response) => {
+            log::info!("Feature flag '{}' is now {}", name, if response.flags[name] { "on" } else { "off" });
+            Ok(HttpResponse::Ok().json(response))
+        }
+        Err(e) => {
+            log::error!("Database error: {:?}", e);
+            Ok(HttpResponse::InternalServerError().json(ErrorResponse {
+                error: "Database Error".to_string(),
+                message: "Failed to update feature flag".to_string(),
+                timestamp: Utc::now().to_rfc3339(),
+            }))
+        }
+    }
+}
+
+// 比赛接口出错时的响应
+fn race_error_response(e: race::RaceError) -> HttpResponse {
+    let (mut response, error, message) = match e {
+        race::RaceError::NotFound => (HttpResponse::NotFound(), "Not Found", "Race not found"),
+        race::RaceError::Full => (HttpResponse::Conflict(), "Race Full", "This race already has two players"),
+        race::RaceError::NotJoined => (HttpResponse::Forbidden(), "Not Joined", "Player has not joined this race"),
+        race::RaceError::NoFreeCode => (
+            HttpResponse::ServiceUnavailable(),
+            "Unavailable",
+            "Could not allocate a race code, try again",
+        ),
+        race::RaceError::Database(e) => {
+            log::error!("Database error: {:?}", e);
+            (HttpResponse::InternalServerError(), "Database Error", "Failed to update race")
+        }
+    };
+    response.json(ErrorResponse {
+        error: error.to_string(),
+        message: message.to_string(),
+        timestamp: Utc::now().to_rfc3339(),
+    })
+}
+
+fn validate_race_player(player_name: &str) -> std::result::Result<(), String> {
+    if player_name.is_empty() || player_name.len() > 50 {
+        return Err("Player name must be between 1 and 50 characters".to_string());
+    }
+    Ok(())
+}
+
+// 创建比赛：返回加入码和当天的每日挑战种子
+async fn create_race(
+    data: web::Data<Arc<AppState>>,
+    request: web::Json<RaceJoinRequest>,
+) -> Result<HttpResponse> {
+    if let Err(message) = validate_race_player(&request.player_name) {
+        return Ok(invalid_input_response(message));
+    }
+    match race::create_race(&data.pool, &request.player_name, Utc::now()).await {
+        Ok(race) => Ok(HttpResponse::Created().json(race)),
+        Err(e) => Ok(race_error_response(e)),
+    }
+}
+
+// 用加入码加入比赛
+async fn join_race(
+    data: web::Data<Arc<AppState>>,
+    race_id: web::Path<String>,
+    request: web::Json<RaceJoinRequest>,
+) -> Result<HttpResponse> {
+    if let Err(message) = validate_race_player(&request.player_name) {
+        return Ok(invalid_input_response(message));
+    }
+    let code = race::normalize_code(&race_id);
+    match race::join_race(&data.pool, &code, &request.player_name, Utc::now()).await {
+        Ok(race) => Ok(HttpResponse::Ok().json(race)),
+        Err(e) => Ok(race_error_response(e)),
+    }
+}
+
+// 上报进度并返回双方的最新进度
+async fn post_race_progress(
+    data: web::Data<Arc<AppState>>,
+    race_id: web::Path<String>,
+    request: web::Json<RaceProgressRequest>,
+) -> Result<HttpResponse> {
+    if let Err(message) = validate_race_player(&request.player_name) {
+        return Ok(invalid_input_response(message));
+    }
+    let code = race::normalize_code(&race_id);
+    match race::update_progress(&data.pool, &code, &request, Utc::now()).await {
+        Ok(race) => Ok(HttpResponse::Ok().json(race)),
+        Err(e) => Ok(race_error_response(e)),
+    }
+}
+
+// 比赛的当前状态；双方都结束后包含胜者
+async fn get_race(data: web::Data<Arc<AppState>>, race_id: web::Path<String>) -> Result<HttpResponse> {
+    let code = race::normalize_code(&race_id);
+    match race::load_race(&data.pool, &code, Utc::now()).await {
+        Ok(Some(race)) => Ok(HttpResponse::Ok().json(race)),
+        Ok(None) => Ok(race_error_response(race::RaceError::NotFound)),
+        Err(e) => Ok(race_error_response(race::RaceError::Database(e))),
+    }
+}
+
+// 健康检查
+async fn health_check() -> Result<HttpResponse> {
+    Ok(HttpResponse::Ok().json(serde_json::json!({
+        "status": "healthy",
+        "timestamp": Utc::now().to_rfc3339()
+    })))
+}
+
+// 配置路由
+fn config_routes(cfg: &mut web::ServiceConfig) {
+    cfg.service(
+        web::scope("/api")
+            .route("/health", web::get().to(health_check))
+            .route("/scores", web::post().to(submit_score))
+            .route("/scores/batch", web::post().to(submit_scores_batch))
+            .route("/scores", web::get().to(get_leaderboard))
+            .route("/scores/recent", web::get().to(get_recent_scores))
+            .route("/seasons", web::get().to(get_seasons))
+            .route("/daily", web::get().to(get_daily))
+            .route("/event", web::get().to(get_event))
+            .route("/scores/{id}", web::get().to(get_score))
+            .route("/scores/{id}", web::delete().to(delete_score))
+            .route("/scores/{id}", web::patch().to(rename_score))
+            .route("/admin/retention", web::post().to(run_retention_now))
+            .route("/admin/metrics", web::get().to(get_metrics))
+            .route("/admin/dashboard", web::get().to(get_dashboard))
+            .route("/admin/stats-summary/refresh", web::post().to(refresh_stats_summary))
+            .route("/admin/flags/{name}", web::put().to(set_flag))
+            .route("/admin/flags/{name}", web::delete().to(reset_flag))
+            .route("/flags", web::get().to(get_flags))
+            .route("/players/{player_name}/stats", web::get().to(get_player_stats))
+            .route("/players/{player_name}/rivals", web::get().to(get_player_rivals))
+            .route("/stats/global", web::get().to(get_global_stats))
+            .route("/stats/levels", web::get().to(get_level_stats))
+            .route("/races", web::post().to(create_race))
+            .route("/races/{id}", web::get().to(get_race))
+            .route("/races/{id}/join", web::post().to(join_race))
+            .route("/races/{id}/progress", web::post().to(post_race_progress))
+    );
+    // 分享链接，比 /api 下的路径短
+    cfg.route("/s/{id}", web::get().to(score_page));
+}
+
+#[actix_web::main]
+async fn main() -> std::io::Result<()> {
+    env_logger::init_from_env(env_logger::Env::new().default_filter_or("info"));
+
+    // 加载配置，有问题时直接退出
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let (config, mode) = match ServerConfig::load(&args, |name| std::env::var(name).ok()) {
+        Ok(loaded) => loaded,
+        Err(e) => {
+            eprintln!("{}", e);
+            std::process::exit(2);
+        }
+    };
+    match mode {
+        RunMode::PrintConfig => {
+            print!("{}", config.to_redacted_toml());
+            return Ok(());
+        }
+        RunMode::Check | RunMode::Serve | RunMode::Retention => {}
+    }
+
+    // 证书和私钥在启动时就读取，无法使用时直接退出
+    let tls_config = match (&config.tls, mode) {
+        (Some(tls), RunMode::Check | RunMode::Serve) => match tls::load_server_config(tls) {
+            Ok(tls_config) => Some(tls_config),
+            Err(e) => {
+                eprintln!("{}", e);
+                std::process::exit(2);
+            }
+        },
+        _ => None,
+    };
+    if mode == RunMode::Check {
+        println!("Configuration OK");
+        return Ok(());
     }
     
-    // 使用完整的数据库 URL
-    let database_url = format!("sqlite://{}", db_path);
-    
-    // 创建数据库连接池
-    let pool = SqlitePool::connect(&database_url)
+    log::info!("Starting Breakout Game API Server...");
+    
+    // 创建数据库连接池（数据库文件不存在时自动创建）
+    let connect_options = SqliteConnectOptions::from_str(&config.database_url)
+        .expect("Invalid database URL")
+        .create_if_missing(true);
+    let pool = SqlitePool::connect_with(connect_options)
         .await
         .expect("Failed to create pool");
     
@@ -505,25 +2020,948 @@ async fn main() -> std::io::Result<()> {
         .expect("Failed to initialize database");
     
     log::info!("Database initialized");
+
+    // --run-retention：执行一次数据保留任务后退出
+    if mode == RunMode::Retention {
+        if let Err(e) = retention::run_retention(&pool, &config, config.retention_dry_run, Utc::now()).await {
+            eprintln!("Retention job failed: {}", e);
+            std::process::exit(1);
+        }
+        return Ok(());
+    }
+    if config.retention_enabled {
+        log::info!(
+            "Retention enabled: archiving scores older than {} days every {} hours",
+            config.retention_max_age_days,
+            config.retention_interval_hours
+        );
+        tokio::spawn(retention::retention_loop(pool.clone(), config.clone()));
+    }
+    tokio::spawn(race::cleanup_loop(pool.clone()));
+    let stats_refresher = Arc::new(SummaryRefresher::default());
+    if config.stats_summary_enabled {
+        tokio::spawn(stats_summary::refresh_loop(pool.clone(), stats_refresher.clone()));
+    } else {
+        log::info!("Stats summary refresher disabled, /api/stats/global uses live queries");
+    }
     
-    let app_state = Arc::new(AppState { pool });
+    if config.admin_api_key.is_none() {
+        log::warn!("No admin_api_key configured, all /api/admin routes and score deletion/renaming are disabled");
+    }
+    if config.allows_any_origin() {
+        log::warn!("cors_origins is \"*\", any website can call the API from a browser");
+    }
+
+    let bind_target = config.bind_target();
+    let scheme = config.scheme();
+    let app_state = Arc::new(AppState {
+        pool,
+        level_histogram_cache: Mutex::new(HashMap::new()),
+        level_histogram_counter: CacheCounter::default(),
+        stats_refresher,
+        flag_cache: FlagCache::default(),
+        error_log: ErrorLog::new(Utc::now()),
+        config,
+    });
     
-    log::info!("Starting HTTP server at http://localhost:8080");
+    log::info!("Starting HTTP server at {}://{}:{}", scheme, bind_target.0, bind_target.1);
     
-    HttpServer::new(move || {
-        let cors = Cors::default()
-            .allow_any_origin()
-            .allow_any_method()
-            .allow_any_header()
-            .max_age(3600);
+    let server = HttpServer::new(move || {
+        // 访问日志记录客户端的真实地址和协议，在反向代理之后时按转发的请求头判断
+        let trust_proxy = app_state.config.trust_proxy;
+        let logger = middleware::Logger::new(
+            "%{client_ip}xi %{scheme}xi \"%r\" %s %b \"%{Referer}i\" \"%{User-Agent}i\" %T",
+        )
+        .custom_request_replace("client_ip", move |req| {
+            forwarded::display_ip(forwarded::client_ip(req.headers(), req.peer_addr(), trust_proxy))
+        })
+        .custom_request_replace("scheme", move |req| {
+            forwarded::request_scheme(req.headers(), trust_proxy, scheme).to_string()
+        });
         
         App::new()
             .app_data(web::Data::new(app_state.clone()))
-            .wrap(cors)
-            .wrap(middleware::Logger::default())
+            .wrap(cors(&app_state.config))
+            // 按 Accept-Encoding 使用 gzip/brotli 压缩响应
+            .wrap(middleware::Compress::default())
+            .wrap(logger)
+            .wrap(middleware::from_fn(dashboard::record_errors))
             .configure(config_routes)
-    })
-    .bind("127.0.0.1:8080")?
-    .run()
-    .await
-}
\ No newline at end of file
+    });
+    let server = match tls_config {
+        Some(tls_config) => server.bind_rustls_021(bind_target, tls_config)?,
+        None => server.bind(bind_target)?,
+    };
+    server.run().await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use actix_web::dev::ServiceResponse;
+    use actix_web::http::{header, StatusCode};
+    use actix_web::test as actix_test;
+    use chrono::TimeZone;
+
+    // 每个测试在临时目录里使用自己的数据库文件，结束时删除
+    struct TestServer {
+        state: Arc<AppState>,
+        path: std::path::PathBuf,
+    }
+
+    impl Drop for TestServer {
+        fn drop(&mut self) {
+            for suffix in ["", "-wal", "-shm"] {
+                let _ = std::fs::remove_file(format!("{}{}", self.path.display(), suffix));
+            }
+        }
+    }
+
+    async fn test_server(name: &str, config: ServerConfig) -> TestServer {
+        let path = std::env::temp_dir().join(format!("breakout-server-test-{}-{}.db", name, std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let connect_options = SqliteConnectOptions::from_str(&format!("sqlite://{}", path.display()))
+            .expect("valid database url")
+            .create_if_missing(true);
+        let pool = SqlitePool::connect_with(connect_options).await.expect("test database");
+        init_db(&pool).await.expect("schema");
+        let state = Arc::new(AppState {
+            pool,
+            level_histogram_cache: Mutex::new(HashMap::new()),
+            level_histogram_counter: CacheCounter::default(),
+            stats_refresher: Arc::new(SummaryRefresher::default()),
+            flag_cache: FlagCache::default(),
+            error_log: ErrorLog::new(Utc::now()),
+            config,
+        });
+        TestServer { state, path }
+    }
+
+    // 请求类型在 actix-web 中没有公开导出，用宏代替返回 impl Service 的函数
+    macro_rules! test_app {
+        ($server:expr) => {
+            actix_test::init_service(
+                App::new()
+                    .app_data(web::Data::new($server.state.clone()))
+                    .configure(config_routes),
+            )
+            .await
+        };
+    }
+
+    fn score_request(player_name: &str, score: u32, difficulty: &str) -> CreateScoreRequest {
+        CreateScoreRequest {
+            player_name: player_name.to_string(),
+            score,
+            level: 1,
+            difficulty: difficulty.to_string(),
+            idempotency_key: None,
+            venue: None,
+            mode: None,
+            seed: None,
+            daily_date: None,
+            event_id: None,
+            tournament_id: None,
+            abandoned: false,
+            assisted: false,
+            curse: None,
+        }
+    }
+
+    fn utc(year: i32, month: u32, day: u32, hour: u32, minute: u32, second: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(year, month, day, hour, minute, second).unwrap()
+    }
+
+    // 直接写入数据库，提交时间由测试指定
+    async fn insert_at(server: &TestServer, request: &CreateScoreRequest, now: DateTime<Utc>) -> Score {
+        insert_score(&server.state.pool, request, now).await.expect("insert score")
+    }
+
+    #[test]
+    fn season_is_the_utc_month() {
+        assert_eq!(season_of(utc(2025, 5, 31, 23, 59, 59)), "2025-05");
+        assert_eq!(season_of(utc(2025, 6, 1, 0, 0, 0)), "2025-06");
+        assert_eq!(season_of(utc(2025, 12, 31, 23, 59, 59)), "2025-12");
+        assert_eq!(season_of(utc(2026, 1, 1, 0, 0, 0)), "2026-01");
+    }
+
+    #[test]
+    fn season_parameter_is_validated() {
+        assert_eq!(resolve_season(Some("all")), Ok(None));
+        assert_eq!(resolve_season(None), Ok(Some(current_season())));
+        assert_eq!(resolve_season(Some("2025-06")), Ok(Some("2025-06".to_string())));
+        assert!(resolve_season(Some("2025-13")).is_err());
+        assert!(resolve_season(Some("2025-6")).is_err());
+        assert!(resolve_season(Some("25-06-01")).is_err());
+    }
+
+    #[actix_web::test]
+    async fn scores_are_stamped_with_the_month_they_were_inserted_in() {
+        let server = test_server("season-stamp", ServerConfig::default()).await;
+        let may = insert_at(&server, &score_request("LATE", 100, "Medium"), utc(2025, 5, 31, 23, 59, 59)).await;
+        let june = insert_at(&server, &score_request("EARLY", 200, "Medium"), utc(2025, 6, 1, 0, 0, 0)).await;
+        assert_eq!(may.season.as_deref(), Some("2025-05"));
+        assert_eq!(june.season.as_deref(), Some("2025-06"));
+
+        let stored: Vec<(String, String)> = sqlx::query_as("SELECT player_name, season FROM scores ORDER BY player_name")
+            .fetch_all(&server.state.pool)
+            .await
+            .unwrap();
+        assert_eq!(
+            stored,
+            vec![("EARLY".to_string(), "2025-06".to_string()), ("LATE".to_string(), "2025-05".to_string())]
+        );
+    }
+
+    #[actix_web::test]
+    async fn migration_backfills_the_season_from_created_at() {
+        let server = test_server("season-backfill", ServerConfig::default()).await;
+        insert_at(&server, &score_request("OLD", 100, "Easy"), utc(2025, 5, 31, 23, 59, 59)).await;
+        sqlx::query("UPDATE scores SET season = NULL").execute(&server.state.pool).await.unwrap();
+        migrate_add_season(&server.state.pool).await.unwrap();
+        let (season,): (String,) = sqlx::query_as("SELECT season FROM scores").fetch_one(&server.state.pool).await.unwrap();
+        assert_eq!(season, "2025-05");
+    }
+
+    #[actix_web::test]
+    async fn all_seasons_leaderboard_aggregates_every_month() {
+        let server = test_server("season-all", ServerConfig::default()).await;
+        insert_at(&server, &score_request("MAY", 500, "Medium"), utc(2025, 5, 31, 23, 59, 59)).await;
+        insert_at(&server, &score_request("JUNE", 300, "Medium"), utc(2025, 6, 1, 0, 0, 0)).await;
+        insert_at(&server, &score_request("JUNE", 900, "Medium"), utc(2025, 6, 15, 12, 0, 0)).await;
+        let app = test_app!(server);
+
+        let request = actix_test::TestRequest::get().uri("/api/scores?season=all&difficulty=Medium").to_request();
+        let all: LeaderboardResponse = actix_test::call_and_read_body_json(&app, request).await;
+        assert_eq!(all.total, 3);
+        let ranked: Vec<(u32, Option<u32>)> = all.scores.iter().map(|score| (score.score, score.rank)).collect();
+        assert_eq!(ranked, vec![(900, Some(1)), (500, Some(2)), (300, Some(3))]);
+
+        let request = actix_test::TestRequest::get().uri("/api/scores?season=2025-05").to_request();
+        let may: LeaderboardResponse = actix_test::call_and_read_body_json(&app, request).await;
+        assert_eq!(may.total, 1);
+        assert_eq!(may.scores[0].player_name, "MAY");
+
+        let request = actix_test::TestRequest::get().uri("/api/seasons").to_request();
+        let seasons: SeasonsResponse = actix_test::call_and_read_body_json(&app, request).await;
+        let summary: Vec<(&str, u32, Option<u32>)> = seasons
+            .seasons
+            .iter()
+            .map(|season| (season.season.as_str(), season.total_games, season.top_score.as_ref().map(|top| top.score)))
+            .collect();
+        assert_eq!(summary, vec![("2025-06", 2, Some(900)), ("2025-05", 1, Some(500))]);
+
+        let request = actix_test::TestRequest::get().uri("/api/scores?season=2025-13").to_request();
+        let response = actix_test::call_service(&app, request).await;
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[test]
+    fn cursor_round_trips_and_rejects_garbage() {
+        let cursor = LeaderboardCursor {
+            score: 1200,
+            created_at: "2025-06-01T00:00:00+00:00".to_string(),
+            id: "a|b".to_string(),
+        };
+        assert_eq!(LeaderboardCursor::decode(&cursor.encode()), Some(cursor));
+        assert_eq!(LeaderboardCursor::decode("not base64!"), None);
+        assert_eq!(LeaderboardCursor::decode(&URL_SAFE_NO_PAD.encode("abc|x|y")), None);
+        assert_eq!(LeaderboardCursor::decode(&URL_SAFE_NO_PAD.encode("12")), None);
+    }
+
+    #[actix_web::test]
+    async fn cursor_pages_stay_stable_while_scores_are_inserted() {
+        let server = test_server("cursor-pages", ServerConfig::default()).await;
+        let base = utc(2025, 6, 10, 12, 0, 0);
+        // 同分的两行按提交时间排序
+        for (index, (name, score)) in [("P1", 600), ("P2", 500), ("P3", 500), ("P4", 400), ("P5", 300), ("P6", 200)]
+            .into_iter()
+            .enumerate()
+        {
+            insert_at(&server, &score_request(name, score, "Medium"), base + chrono::Duration::seconds(index as i64)).await;
+        }
+        let app = test_app!(server);
+
+        let mut seen = Vec::new();
+        let mut ranks = Vec::new();
+        let mut uri = "/api/scores?season=all&limit=2".to_string();
+        let mut page_number = 0;
+        loop {
+            let request = actix_test::TestRequest::get().uri(&uri).to_request();
+            let page: LeaderboardResponse = actix_test::call_and_read_body_json(&app, request).await;
+            seen.extend(page.scores.iter().map(|score| score.player_name.clone()));
+            ranks.extend(page.scores.iter().map(|score| score.rank));
+            page_number += 1;
+            if page_number == 1 {
+                // 翻页过程中插入一条排在已读页之前的成绩、一条与游标同分的成绩和一条排在后面的成绩
+                insert_at(&server, &score_request("ABOVE", 700, "Medium"), base + chrono::Duration::seconds(10)).await;
+                insert_at(&server, &score_request("TIED", 500, "Medium"), base + chrono::Duration::seconds(11)).await;
+                insert_at(&server, &score_request("BELOW", 250, "Medium"), base + chrono::Duration::seconds(12)).await;
+            }
+            match page.next_cursor {
+                Some(cursor) => uri = format!("/api/scores?season=all&limit=2&cursor={}", cursor),
+                None => break,
+            }
+        }
+
+        // 已经翻过的位置不会重复或跳过，游标之后插入的成绩按顺序出现，之前插入的不会出现
+        assert_eq!(seen, vec!["P1", "P2", "P3", "TIED", "P4", "P5", "BELOW", "P6"]);
+        // 排名按当前数据计算：ABOVE 插入后，后面的每一行都往后挪一位
+        let ranks: Vec<u32> = ranks.into_iter().map(Option::unwrap).collect();
+        assert_eq!(ranks, vec![1, 2, 4, 5, 6, 7, 8, 9]);
+    }
+
+    #[actix_web::test]
+    async fn invalid_cursor_is_rejected() {
+        let server = test_server("cursor-invalid", ServerConfig::default()).await;
+        let app = test_app!(server);
+        let request = actix_test::TestRequest::get().uri("/api/scores?cursor=%25%25%25").to_request();
+        let response = actix_test::call_service(&app, request).await;
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+
+    fn with_key(mut request: CreateScoreRequest, key: &str) -> CreateScoreRequest {
+        request.idempotency_key = Some(key.to_string());
+        request
+    }
+
+    fn capped_config(daily_submission_cap: u32) -> ServerConfig {
+        ServerConfig {
+            daily_submission_cap,
+            ..ServerConfig::default()
+        }
+    }
+
+    #[actix_web::test]
+    async fn submissions_over_the_daily_cap_are_rejected() {
+        let server = test_server("daily-cap", capped_config(3)).await;
+        // 昨天的提交不计入今天的次数
+        insert_at(&server, &score_request("CAPPED", 10, "Easy"), Utc::now() - chrono::Duration::days(1)).await;
+        let app = test_app!(server);
+
+        for index in 0..3 {
+            let body = with_key(score_request("CAPPED", 100 + index, "Easy"), &format!("cap-{}", index));
+            let request = actix_test::TestRequest::post().uri("/api/scores").set_json(&body).to_request();
+            let response = actix_test::call_service(&app, request).await;
+            assert_eq!(response.status(), StatusCode::CREATED, "submission {}", index);
+        }
+
+        let body = with_key(score_request("CAPPED", 999, "Easy"), "cap-3");
+        let request = actix_test::TestRequest::post().uri("/api/scores").set_json(&body).to_request();
+        let response = actix_test::call_service(&app, request).await;
+        assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+        let error: ErrorResponse = actix_test::read_body_json(response).await;
+        assert_eq!(error.error, "Too Many Requests");
+
+        // 重发已保存的记录不算新的提交，其他玩家不受影响
+        let body = with_key(score_request("CAPPED", 100, "Easy"), "cap-0");
+        let request = actix_test::TestRequest::post().uri("/api/scores").set_json(&body).to_request();
+        assert_eq!(actix_test::call_service(&app, request).await.status(), StatusCode::OK);
+        let body = score_request("OTHER", 100, "Easy");
+        let request = actix_test::TestRequest::post().uri("/api/scores").set_json(&body).to_request();
+        assert_eq!(actix_test::call_service(&app, request).await.status(), StatusCode::CREATED);
+
+        let (stored,): (i64,) = sqlx::query_as("SELECT COUNT(*) FROM scores WHERE player_name = 'CAPPED'")
+            .fetch_one(&server.state.pool)
+            .await
+            .unwrap();
+        assert_eq!(stored, 4);
+    }
+
+    #[actix_web::test]
+    async fn mixed_batch_reports_each_item() {
+        let server = test_server("batch-mixed", capped_config(2)).await;
+        let existing = insert_at(&server, &with_key(score_request("EARLIER", 50, "Hard"), "batch-old"), Utc::now()).await;
+        let app = test_app!(server);
+
+        let batch = vec![
+            with_key(score_request("ALICE", 300, "Medium"), "batch-1"),
+            score_request("ALICE", 200, "Impossible"),
+            // 与同一批次前面的记录重复
+            with_key(score_request("ALICE", 300, "Medium"), "batch-1"),
+            // 与已经保存的记录重复
+            with_key(score_request("EARLIER", 50, "Hard"), "batch-old"),
+            score_request("ALICE", 100, "Medium"),
+            // 本批次已经为 ALICE 插入两条，超过每日上限
+            score_request("ALICE", 400, "Medium"),
+            score_request("", 10, "Easy"),
+        ];
+        let request = actix_test::TestRequest::post().uri("/api/scores/batch").set_json(&batch).to_request();
+        let response = actix_test::call_service(&app, request).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        let body: BatchSubmitResponse = actix_test::read_body_json(response).await;
+        assert_eq!(body.results.len(), batch.len());
+
+        let first_id = match &body.results[0] {
+            BatchItemResult::Created { score } => {
+                assert_eq!((score.player_name.as_str(), score.score), ("ALICE", 300));
+                score.id.clone().unwrap()
+            }
+            other => panic!("expected created, got {:?}", other),
+        };
+        assert!(matches!(&body.results[1], BatchItemResult::Rejected { reason } if reason.starts_with("Difficulty")));
+        assert!(matches!(&body.results[2], BatchItemResult::Duplicate { id } if *id == first_id));
+        assert!(matches!(&body.results[3], BatchItemResult::Duplicate { id } if Some(id) == existing.id.as_ref()));
+        assert!(matches!(&body.results[4], BatchItemResult::Created { score } if score.score == 100));
+        assert!(matches!(&body.results[5], BatchItemResult::Rejected { reason } if reason.contains("per day")));
+        assert!(matches!(&body.results[6], BatchItemResult::Rejected { .. }));
+
+        let (stored,): (i64,) = sqlx::query_as("SELECT COUNT(*) FROM scores").fetch_one(&server.state.pool).await.unwrap();
+        assert_eq!(stored, 3);
+    }
+
+    #[actix_web::test]
+    async fn empty_and_oversized_batches_are_rejected() {
+        let server = test_server("batch-size", ServerConfig::default()).await;
+        let app = test_app!(server);
+        let empty: Vec<CreateScoreRequest> = Vec::new();
+        let oversized = vec![score_request("ALICE", 1, "Easy"); MAX_BATCH_SIZE + 1];
+        for batch in [empty, oversized] {
+            let request = actix_test::TestRequest::post().uri("/api/scores/batch").set_json(&batch).to_request();
+            let response = actix_test::call_service(&app, request).await;
+            assert_eq!(response.status(), StatusCode::BAD_REQUEST, "batch of {}", batch.len());
+        }
+        let (stored,): (i64,) = sqlx::query_as("SELECT COUNT(*) FROM scores").fetch_one(&server.state.pool).await.unwrap();
+        assert_eq!(stored, 0);
+    }
+
+    async fn rivals(server: &TestServer, player_name: &str) -> RivalsResponse {
+        let app = test_app!(server);
+        let uri = format!("/api/players/{}/rivals?difficulty=Medium", player_name);
+        let request = actix_test::TestRequest::get().uri(&uri).to_request();
+        actix_test::call_and_read_body_json(&app, request).await
+    }
+
+    fn rival_names(response: &RivalsResponse) -> Vec<(&str, u32, u32)> {
+        response
+            .rivals
+            .iter()
+            .map(|rival| (rival.player_name.as_str(), rival.best_score, rival.gap))
+            .collect()
+    }
+
+    #[actix_web::test]
+    async fn rivals_skip_ties_and_order_equal_bests_by_name() {
+        let server = test_server("rivals-ties", ServerConfig::default()).await;
+        let now = Utc::now();
+        for (name, score) in [
+            ("ME", 500),
+            ("ME", 100),
+            // 与玩家最好成绩相同的不算对手
+            ("TWIN", 500),
+            ("ZED", 600),
+            ("AMY", 600),
+            ("BEN", 550),
+            ("TOP", 900),
+        ] {
+            insert_at(&server, &score_request(name, score, "Medium"), now).await;
+        }
+        // 其他难度和未完成局不影响结果
+        insert_at(&server, &score_request("EASY", 510, "Easy"), now).await;
+        let mut abandoned = score_request("QUIT", 520, "Medium");
+        abandoned.abandoned = true;
+        insert_at(&server, &abandoned, now).await;
+
+        let response = rivals(&server, "ME").await;
+        assert_eq!(response.best_score, Some(500));
+        assert_eq!(rival_names(&response), vec![("BEN", 550, 50), ("AMY", 600, 100), ("ZED", 600, 100)]);
+
+        // 排名第一的玩家没有对手
+        let response = rivals(&server, "TOP").await;
+        assert_eq!(response.best_score, Some(900));
+        assert!(response.rivals.is_empty());
+
+        // 还没有成绩的玩家按 0 分计算
+        let response = rivals(&server, "NEWBIE").await;
+        assert_eq!(response.best_score, None);
+        assert_eq!(rival_names(&response), vec![("ME", 500, 500), ("TWIN", 500, 500), ("BEN", 550, 550)]);
+    }
+
+    #[actix_web::test]
+    async fn rivals_require_a_valid_difficulty() {
+        let server = test_server("rivals-difficulty", ServerConfig::default()).await;
+        let app = test_app!(server);
+        for uri in ["/api/players/ME/rivals", "/api/players/ME/rivals?difficulty=Nightmare"] {
+            let request = actix_test::TestRequest::get().uri(uri).to_request();
+            assert_eq!(actix_test::call_service(&app, request).await.status(), StatusCode::BAD_REQUEST, "{}", uri);
+        }
+    }
+
+    fn cors_config(origins: &[&str]) -> ServerConfig {
+        ServerConfig {
+            cors_origins: origins.iter().map(|origin| origin.to_string()).collect(),
+            ..ServerConfig::default()
+        }
+    }
+
+    // 带 Origin 请求头访问健康检查，返回状态码和 Access-Control-Allow-Origin
+    async fn cors_response(config: ServerConfig, origin: &str) -> (StatusCode, Option<String>) {
+        let app = actix_test::init_service(
+            App::new()
+                .wrap(cors(&config))
+                .route("/api/health", web::get().to(health_check)),
+        )
+        .await;
+        let request = actix_test::TestRequest::get()
+            .uri("/api/health")
+            .insert_header((header::ORIGIN, origin))
+            .to_request();
+        let response = actix_test::call_service(&app, request).await;
+        let allowed = response
+            .headers()
+            .get(header::ACCESS_CONTROL_ALLOW_ORIGIN)
+            .map(|value| value.to_str().unwrap().to_string());
+        (response.status(), allowed)
+    }
+
+    #[actix_web::test]
+    async fn cors_allows_only_configured_origins() {
+        let config = cors_config(&["https://play.example.com"]);
+        assert_eq!(
+            cors_response(config.clone(), "https://play.example.com").await,
+            (StatusCode::OK, Some("https://play.example.com".to_string()))
+        );
+        let (status, allowed) = cors_response(config, "https://evil.example.com").await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(allowed, None);
+    }
+
+    #[actix_web::test]
+    async fn cors_denies_every_origin_by_default() {
+        let (status, allowed) = cors_response(ServerConfig::default(), "https://play.example.com").await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(allowed, None);
+    }
+
+    #[actix_web::test]
+    async fn cors_wildcard_allows_any_origin() {
+        let (status, allowed) = cors_response(cors_config(&["*"]), "https://anywhere.example.org").await;
+        assert_eq!(status, StatusCode::OK);
+        assert!(allowed.is_some());
+    }
+
+    async fn read_text(response: ServiceResponse) -> String {
+        String::from_utf8(actix_test::read_body(response).await.to_vec()).unwrap()
+    }
+
+    #[actix_web::test]
+    async fn score_can_be_fetched_by_id() {
+        let config = ServerConfig {
+            public_base_url: Some("https://breakout.example.com".to_string()),
+            ..ServerConfig::default()
+        };
+        let server = test_server("score-by-id", config).await;
+        let saved = insert_at(&server, &score_request("ALICE", 4200, "Hard"), utc(2025, 6, 2, 8, 30, 0)).await;
+        let id = saved.id.clone().unwrap();
+        let app = test_app!(server);
+
+        let request = actix_test::TestRequest::get().uri(&format!("/api/scores/{}", id)).to_request();
+        let score: Score = actix_test::call_and_read_body_json(&app, request).await;
+        assert_eq!((score.player_name.as_str(), score.score, score.difficulty.as_str()), ("ALICE", 4200, "Hard"));
+        assert_eq!(score.share_url, Some(format!("https://breakout.example.com/s/{}", id)));
+
+        let request = actix_test::TestRequest::get().uri("/api/scores/no-such-score").to_request();
+        let response = actix_test::call_service(&app, request).await;
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    }
+
+    #[actix_web::test]
+    async fn share_page_has_open_graph_tags() {
+        let config = ServerConfig {
+            public_base_url: Some("https://breakout.example.com".to_string()),
+            ..ServerConfig::default()
+        };
+        let server = test_server("share-page", config).await;
+        insert_at(&server, &score_request("BOB", 9000, "Hard"), utc(2025, 6, 1, 0, 0, 0)).await;
+        let saved = insert_at(&server, &score_request("<ALICE>", 4200, "Hard"), utc(2025, 6, 2, 8, 30, 0)).await;
+        let id = saved.id.clone().unwrap();
+        let app = test_app!(server);
+
+        let request = actix_test::TestRequest::get().uri(&format!("/s/{}", id)).to_request();
+        let response = actix_test::call_service(&app, request).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.headers().get(header::CONTENT_TYPE).unwrap(), "text/html; charset=utf-8");
+        let html = read_text(response).await;
+        // 玩家名经过转义，名次按当前榜单计算
+        assert!(html.contains("<meta property=\"og:title\" content=\"&lt;ALICE&gt; scored 4200 in Super Breakout\">"));
+        assert!(html.contains("<meta property=\"og:description\" content=\"Level 1 · Hard · Rank #2 · 2025-06-02\">"));
+        assert!(html.contains(&format!("<meta property=\"og:url\" content=\"https://breakout.example.com/s/{}\">", id)));
+        assert!(!html.contains("<ALICE>"));
+    }
+
+    #[actix_web::test]
+    async fn share_page_for_a_missing_score_is_not_found() {
+        let server = test_server("share-page-missing", ServerConfig::default()).await;
+        let app = test_app!(server);
+        let request = actix_test::TestRequest::get().uri("/s/no-such-score").to_request();
+        let response = actix_test::call_service(&app, request).await;
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+        let html = read_text(response).await;
+        assert!(html.contains("<h1>Score not found</h1>"));
+        assert!(html.contains("<meta name=\"robots\" content=\"noindex\">"));
+        assert!(!html.contains("og:title"));
+    }
+
+    const ADMIN_KEY: &str = "test-admin-key-0123456789";
+
+    fn admin_config() -> ServerConfig {
+        ServerConfig {
+            admin_api_key: Some(ADMIN_KEY.to_string()),
+            ..ServerConfig::default()
+        }
+    }
+
+    // 没有配置管理密钥时，带不带请求头，所有管理接口都关闭
+    #[actix_web::test]
+    async fn admin_routes_are_disabled_without_a_key() {
+        let server = test_server("admin-no-key", ServerConfig::default()).await;
+        let app = test_app!(server);
+        let requests = [
+            actix_test::TestRequest::delete().uri("/api/scores/some-id"),
+            actix_test::TestRequest::patch()
+                .uri("/api/scores/some-id")
+                .set_json(serde_json::json!({ "player_name": "MALLORY" })),
+            actix_test::TestRequest::post().uri("/api/admin/retention"),
+            actix_test::TestRequest::get().uri("/api/admin/metrics"),
+            actix_test::TestRequest::get().uri("/api/admin/dashboard"),
+            actix_test::TestRequest::post().uri("/api/admin/stats-summary/refresh"),
+            actix_test::TestRequest::put()
+                .uri("/api/admin/flags/races")
+                .set_json(serde_json::json!({ "enabled": false })),
+            actix_test::TestRequest::delete().uri("/api/admin/flags/races"),
+        ];
+        for request in requests {
+            let request = request.insert_header((ADMIN_KEY_HEADER, "")).to_request();
+            let path = request.path().to_string();
+            let response = actix_test::call_service(&app, request).await;
+            assert_eq!(response.status(), StatusCode::SERVICE_UNAVAILABLE, "{}", path);
+        }
+    }
+
+    #[actix_web::test]
+    async fn admin_routes_need_the_exact_key() {
+        let server = test_server("admin-key", admin_config()).await;
+        let app = test_app!(server);
+        for provided in [None, Some(""), Some("test-admin-key-012345678"), Some("test-admin-key-0123456789x")] {
+            let mut request = actix_test::TestRequest::get().uri("/api/admin/metrics");
+            if let Some(provided) = provided {
+                request = request.insert_header((ADMIN_KEY_HEADER, provided));
+            }
+            let response = actix_test::call_service(&app, request.to_request()).await;
+            assert_eq!(response.status(), StatusCode::UNAUTHORIZED, "{:?}", provided);
+        }
+        let request = actix_test::TestRequest::get()
+            .uri("/api/admin/metrics")
+            .insert_header((ADMIN_KEY_HEADER, ADMIN_KEY))
+            .to_request();
+        assert_eq!(actix_test::call_service(&app, request).await.status(), StatusCode::OK);
+    }
+
+    #[test]
+    fn constant_time_eq_compares_whole_keys() {
+        assert!(constant_time_eq(b"abc", b"abc"));
+        assert!(constant_time_eq(b"", b""));
+        assert!(!constant_time_eq(b"abc", b"abd"));
+        assert!(!constant_time_eq(b"abc", b"xbc"));
+        assert!(!constant_time_eq(b"abc", b"abcd"));
+    }
+
+    // 和 main 一样在路由外面包上错误记录中间件
+    macro_rules! recording_app {
+        ($server:expr) => {
+            actix_test::init_service(
+                App::new()
+                    .app_data(web::Data::new($server.state.clone()))
+                    .wrap(middleware::from_fn(dashboard::record_errors))
+                    .configure(config_routes),
+            )
+            .await
+        };
+    }
+
+    #[actix_web::test]
+    async fn only_error_statuses_are_recorded() {
+        let server = test_server("error-log", ServerConfig::default()).await;
+        let app = recording_app!(server);
+        let requests = [
+            actix_test::TestRequest::get().uri("/api/health"),
+            actix_test::TestRequest::get().uri("/api/scores/no-such-score"),
+            actix_test::TestRequest::post()
+                .uri("/api/scores")
+                .insert_header((header::CONTENT_TYPE, "application/json"))
+                .set_payload("not json"),
+            actix_test::TestRequest::get().uri("/api/admin/metrics"),
+            actix_test::TestRequest::get().uri("/api/scores?difficulty=Medium"),
+        ];
+        for request in requests {
+            actix_test::call_service(&app, request.to_request()).await;
+        }
+        let errors = server.state.error_log.summary();
+        let recorded: Vec<(u16, &str, &str)> = errors
+            .recent
+            .iter()
+            .map(|entry| (entry.status, entry.method.as_str(), entry.path.as_str()))
+            .collect();
+        assert_eq!(
+            recorded,
+            [
+                (503, "GET", "/api/admin/metrics"),
+                (400, "POST", "/api/scores"),
+                (404, "GET", "/api/scores/no-such-score"),
+            ]
+        );
+        assert_eq!(errors.total, 3);
+    }
+
+    #[actix_web::test]
+    async fn dashboard_needs_the_admin_key() {
+        let server = test_server("dashboard-no-key", ServerConfig::default()).await;
+        let app = recording_app!(server);
+        let request = actix_test::TestRequest::get()
+            .uri("/api/admin/dashboard")
+            .insert_header((ADMIN_KEY_HEADER, ADMIN_KEY))
+            .to_request();
+        assert_eq!(actix_test::call_service(&app, request).await.status(), StatusCode::SERVICE_UNAVAILABLE);
+
+        let server = test_server("dashboard", admin_config()).await;
+        insert_at(&server, &score_request("ALICE", 4200, "Hard"), Utc::now()).await;
+        let app = recording_app!(server);
+        let request = actix_test::TestRequest::get().uri("/api/admin/dashboard").to_request();
+        assert_eq!(actix_test::call_service(&app, request).await.status(), StatusCode::UNAUTHORIZED);
+
+        let request = actix_test::TestRequest::get()
+            .uri("/api/admin/dashboard")
+            .insert_header((ADMIN_KEY_HEADER, ADMIN_KEY))
+            .to_request();
+        let dashboard: dashboard::DashboardResponse = actix_test::call_and_read_body_json(&app, request).await;
+        assert_eq!((dashboard.submissions.last_hour, dashboard.submissions.last_day), (1, 1));
+        assert_eq!(dashboard.players_today, 1);
+        assert_eq!(dashboard.top_scores.len(), 1);
+        // 刚才没带密钥的请求记在错误记录里
+        assert_eq!(dashboard.errors.total, 1);
+        assert_eq!(dashboard.errors.recent[0].status, 401);
+        assert_eq!(dashboard.errors.recent[0].path, "/api/admin/dashboard");
+    }
+
+    fn flags_request(if_none_match: Option<&str>) -> actix_test::TestRequest {
+        let request = actix_test::TestRequest::get().uri("/api/flags");
+        match if_none_match {
+            Some(etag) => request.insert_header((header::IF_NONE_MATCH, etag)),
+            None => request,
+        }
+    }
+
+    #[actix_web::test]
+    async fn flags_answer_304_until_an_admin_changes_them() {
+        let server = test_server("flags-etag", admin_config()).await;
+        let app = test_app!(server);
+
+        let response = actix_test::call_service(&app, flags_request(None).to_request()).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        let etag = response.headers().get(header::ETAG).unwrap().to_str().unwrap().to_string();
+
+        let response = actix_test::call_service(&app, flags_request(Some(&etag)).to_request()).await;
+        assert_eq!(response.status(), StatusCode::NOT_MODIFIED);
+        assert_eq!(response.headers().get(header::ETAG).unwrap().to_str().unwrap(), etag);
+        assert!(read_text(response).await.is_empty());
+
+        // 关掉一个开关后缓存立即失效，旧的 ETag 不再匹配
+        let request = actix_test::TestRequest::put()
+            .uri("/api/admin/flags/races")
+            .insert_header((ADMIN_KEY_HEADER, ADMIN_KEY))
+            .set_json(serde_json::json!({ "enabled": false }))
+            .to_request();
+        assert_eq!(actix_test::call_service(&app, request).await.status(), StatusCode::OK);
+        let response = actix_test::call_service(&app, flags_request(Some(&etag)).to_request()).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        let disabled_etag = response.headers().get(header::ETAG).unwrap().to_str().unwrap().to_string();
+        assert_ne!(disabled_etag, etag);
+        let flags: breakout_shared::api::FlagsResponse = actix_test::read_body_json(response).await;
+        assert!(!flags.flags["races"]);
+
+        // 恢复默认后内容和 ETag 都回到原样
+        let request = actix_test::TestRequest::delete()
+            .uri("/api/admin/flags/races")
+            .insert_header((ADMIN_KEY_HEADER, ADMIN_KEY))
+            .to_request();
+        assert_eq!(actix_test::call_service(&app, request).await.status(), StatusCode::OK);
+        let response = actix_test::call_service(&app, flags_request(Some(&disabled_etag)).to_request()).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.headers().get(header::ETAG).unwrap().to_str().unwrap(), etag);
+    }
+
+    // 汇总新鲜时由汇总表回答（看不到刷新之后的成绩），过期或还没有汇总时改用实时查询
+    #[actix_web::test]
+    async fn global_stats_use_the_summary_only_while_it_is_fresh() {
+        let server = test_server("stats-summary", ServerConfig::default()).await;
+        let app = test_app!(server);
+        let pool = &server.state.pool;
+        let now = Utc::now();
+        let mut served = Vec::new();
+        insert_at(&server, &score_request("ALICE", 500, "Easy"), now).await;
+
+        for refreshed_at in [None, Some(now), Some(now - chrono::Duration::minutes(10))] {
+            if let Some(refreshed_at) = refreshed_at {
+                stats_summary::refresh(pool, refreshed_at).await.unwrap();
+                // 刷新之后的新成绩只有实时查询能看到
+                insert_at(&server, &score_request("BOB", 100, "Hard"), now).await;
+            }
+            let request = actix_test::TestRequest::get().uri("/api/stats/global?season=all").to_request();
+            let stats: GlobalStats = actix_test::call_and_read_body_json(&app, request).await;
+            let metrics = server.state.stats_refresher.metrics();
+            served.push((stats.total_games_played, metrics.served_from_summary, metrics.live_fallbacks));
+        }
+        assert_eq!(served, [(1, 0, 1), (1, 1, 1), (3, 1, 2)]);
+    }
+
+    // 补交的未完成局不上榜也不排名，但计入玩家统计
+    #[actix_web::test]
+    async fn abandoned_runs_stay_off_the_boards_but_count_in_player_stats() {
+        let server = test_server("abandoned", ServerConfig::default()).await;
+        insert_at(&server, &score_request("ALICE", 300, "Medium"), Utc::now()).await;
+        let app = test_app!(server);
+
+        let mut abandoned = score_request("ALICE", 900, "Medium");
+        abandoned.abandoned = true;
+        let request = actix_test::TestRequest::post().uri("/api/scores").set_json(&abandoned).to_request();
+        let response = actix_test::call_service(&app, request).await;
+        assert_eq!(response.status(), StatusCode::CREATED);
+        let saved: Score = actix_test::read_body_json(response).await;
+        assert_eq!(saved.rank, None);
+
+        let request = actix_test::TestRequest::get().uri("/api/scores?difficulty=Medium").to_request();
+        let board: LeaderboardResponse = actix_test::call_and_read_body_json(&app, request).await;
+        let scores: Vec<u32> = board.scores.iter().map(|score| score.score).collect();
+        assert_eq!(scores, [300]);
+
+        let request = actix_test::TestRequest::get().uri("/api/players/ALICE/stats?season=all").to_request();
+        let stats: PlayerStats = actix_test::call_and_read_body_json(&app, request).await;
+        assert_eq!((stats.total_games, stats.abandoned_runs), (2, 1));
+
+        abandoned.mode = Some(DAILY_MODE.to_string());
+        let request = actix_test::TestRequest::post().uri("/api/scores").set_json(&abandoned).to_request();
+        assert_eq!(actix_test::call_service(&app, request).await.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[actix_web::test]
+    async fn compact_leaderboard_omits_ids_and_timestamps() {
+        let server = test_server("compact-scores", ServerConfig::default()).await;
+        insert_at(&server, &score_request("ALICE", 500, "Hard"), Utc::now()).await;
+        let app = test_app!(server);
+
+        let request = actix_test::TestRequest::get().uri("/api/scores?difficulty=Hard&fields=compact").to_request();
+        let body: serde_json::Value = actix_test::call_and_read_body_json(&app, request).await;
+        let row = body["scores"][0].as_object().expect("one row");
+        assert!(!row.contains_key("id"));
+        assert!(!row.contains_key("created_at"));
+        assert_eq!(row["difficulty"], "H");
+        assert_eq!(row["player_name"], "ALICE");
+        assert_eq!(row["rank"], 1);
+
+        let request = actix_test::TestRequest::get().uri("/api/scores?difficulty=Hard&fields=full").to_request();
+        let body: serde_json::Value = actix_test::call_and_read_body_json(&app, request).await;
+        let row = body["scores"][0].as_object().expect("one row");
+        assert!(row.contains_key("id"));
+        assert!(row.contains_key("created_at"));
+        assert_eq!(row["difficulty"], "Hard");
+
+        let request = actix_test::TestRequest::get().uri("/api/scores?fields=short").to_request();
+        assert_eq!(actix_test::call_service(&app, request).await.status(), StatusCode::BAD_REQUEST);
+    }
+
+    // 和 main 一样包上压缩中间件：gzip 响应解压后和未压缩的响应完全相同
+    #[actix_web::test]
+    async fn gzip_responses_round_trip() {
+        use std::io::Read;
+
+        let server = test_server("compressed-scores", ServerConfig::default()).await;
+        for index in 0..20 {
+            insert_at(&server, &score_request(&format!("P{}", index), 100 + index, "Medium"), Utc::now()).await;
+        }
+        let app = actix_test::init_service(
+            App::new()
+                .app_data(web::Data::new(server.state.clone()))
+                .wrap(middleware::Compress::default())
+                .configure(config_routes),
+        )
+        .await;
+
+        let uri = "/api/scores?difficulty=Medium&limit=20";
+        let plain = actix_test::call_and_read_body(&app, actix_test::TestRequest::get().uri(uri).to_request()).await;
+        let request = actix_test::TestRequest::get()
+            .uri(uri)
+            .insert_header((header::ACCEPT_ENCODING, "gzip"))
+            .to_request();
+        let response = actix_test::call_service(&app, request).await;
+        assert_eq!(response.headers().get(header::CONTENT_ENCODING).unwrap(), "gzip");
+        let compressed = actix_test::read_body(response).await;
+        assert!(compressed.len() < plain.len());
+
+        let mut decoded = Vec::new();
+        flate2::read::GzDecoder::new(&compressed[..]).read_to_end(&mut decoded).expect("valid gzip");
+        assert_eq!(decoded, plain);
+        let leaderboard: LeaderboardResponse = serde_json::from_slice(&decoded).unwrap();
+        assert_eq!(leaderboard.scores.len(), 20);
+    }
+
+    #[actix_web::test]
+    async fn race_endpoints_play_a_race_through_to_the_result() {
+        let server = test_server("races", ServerConfig::default()).await;
+        let app = test_app!(server);
+        let join = |name: &str| RaceJoinRequest { player_name: name.to_string() };
+        let progress = |name: &str, score: u32, finished: bool| RaceProgressRequest {
+            player_name: name.to_string(),
+            score,
+            level: 2,
+            finished,
+        };
+
+        let request = actix_test::TestRequest::post().uri("/api/races").set_json(join("Alice")).to_request();
+        let response = actix_test::call_service(&app, request).await;
+        assert_eq!(response.status(), StatusCode::CREATED);
+        let race: breakout_shared::api::RaceResponse = actix_test::read_body_json(response).await;
+        assert_eq!(race.seed, daily_seed(Utc::now().date_naive()));
+
+        // 加入码不区分大小写
+        let code = race.id.to_ascii_lowercase();
+        let request = actix_test::TestRequest::post()
+            .uri(&format!("/api/races/{}/join", code))
+            .set_json(join("Bob"))
+            .to_request();
+        assert_eq!(actix_test::call_service(&app, request).await.status(), StatusCode::OK);
+        let request = actix_test::TestRequest::post()
+            .uri(&format!("/api/races/{}/join", code))
+            .set_json(join("Carol"))
+            .to_request();
+        assert_eq!(actix_test::call_service(&app, request).await.status(), StatusCode::CONFLICT);
+
+        for (name, score) in [("Alice", 300), ("Bob", 500)] {
+            let request = actix_test::TestRequest::post()
+                .uri(&format!("/api/races/{}/progress", race.id))
+                .set_json(progress(name, score, true))
+                .to_request();
+            assert_eq!(actix_test::call_service(&app, request).await.status(), StatusCode::OK);
+        }
+        let request = actix_test::TestRequest::post()
+            .uri(&format!("/api/races/{}/progress", race.id))
+            .set_json(progress("Mallory", 900, false))
+            .to_request();
+        assert_eq!(actix_test::call_service(&app, request).await.status(), StatusCode::FORBIDDEN);
+        let request = actix_test::TestRequest::post()
+            .uri(&format!("/api/races/{}/progress", race.id))
+            .set_json(progress("", 900, false))
+            .to_request();
+        assert_eq!(actix_test::call_service(&app, request).await.status(), StatusCode::BAD_REQUEST);
+
+        let request = actix_test::TestRequest::get().uri(&format!("/api/races/{}", race.id)).to_request();
+        let response = actix_test::call_service(&app, request).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        let result: breakout_shared::api::RaceResponse = actix_test::read_body_json(response).await;
+        assert!(result.finished);
+        assert_eq!(result.winner.as_deref(), Some("Bob"));
+        assert_eq!(result.players.iter().map(|player| player.score).collect::<Vec<_>>(), [300, 500]);
+
+        let request = actix_test::TestRequest::get().uri("/api/races/NOPE22").to_request();
+        assert_eq!(actix_test::call_service(&app, request).await.status(), StatusCode::NOT_FOUND);
+    }
+}
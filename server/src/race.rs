@@ -0,0 +1,503 @@
+use breakout_shared::api::{RaceProgress, RaceProgressRequest, RaceResponse};
+use chrono::{DateTime, Duration, Utc};
+use sqlx::{FromRow, SqlitePool};
+use uuid::Uuid;
+
+use crate::daily_seed;
+
+// 加入码使用的字符，去掉了容易看错的 0/O、1/I/L
+const CODE_ALPHABET: &[u8] = b"ABCDEFGHJKMNPQRSTUVWXYZ23456789";
+const CODE_LENGTH: usize = 6;
+// 生成加入码时遇到重复最多重试的次数
+const CODE_ATTEMPTS: usize = 8;
+// 一场比赛的人数
+pub const RACE_PLAYERS: usize = 2;
+// 没有结束的比赛这么久没有任何更新就清理
+const STALE_AFTER_HOURS: i64 = 2;
+// 结束的比赛保留这么多天，之后清理
+const FINISHED_KEEP_DAYS: i64 = 7;
+// 清理任务的间隔
+const CLEANUP_INTERVAL: std::time::Duration = std::time::Duration::from_secs(600);
+
+#[derive(Debug)]
+pub enum RaceError {
+    NotFound,
+    // 比赛已经满员
+    Full,
+    // 玩家没有加入这场比赛
+    NotJoined,
+    // 多次生成的加入码都已被占用
+    NoFreeCode,
+    Database(sqlx::Error),
+}
+
+impl From<sqlx::Error> for RaceError {
+    fn from(e: sqlx::Error) -> Self {
+        RaceError::Database(e)
+    }
+}
+
+#[derive(Debug, FromRow)]
+struct DbRace {
+    id: String,
+    daily_date: String,
+    created_at: String,
+    finished_at: Option<String>,
+    winner: Option<String>,
+}
+
+#[derive(Debug, FromRow)]
+struct DbProgress {
+    player_name: String,
+    score: i64,
+    level: i64,
+    finished: bool,
+    updated_at: String,
+}
+
+// 迁移：创建比赛表和进度表
+pub async fn migrate_add_races(pool: &SqlitePool) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS races (
+            id TEXT PRIMARY KEY,
+            daily_date TEXT NOT NULL,
+            created_at TEXT NOT NULL,
+            updated_at TEXT NOT NULL,
+            finished_at TEXT,
+            winner TEXT
+        );
+
+        CREATE TABLE IF NOT EXISTS race_progress (
+            race_id TEXT NOT NULL REFERENCES races(id) ON DELETE CASCADE,
+            player_name TEXT NOT NULL,
+            score INTEGER NOT NULL DEFAULT 0,
+            level INTEGER NOT NULL DEFAULT 1,
+            finished INTEGER NOT NULL DEFAULT 0,
+            joined_at TEXT NOT NULL,
+            updated_at TEXT NOT NULL,
+            PRIMARY KEY (race_id, player_name)
+        );
+
+        CREATE INDEX IF NOT EXISTS idx_races_updated ON races(updated_at);
+        "#,
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+// 随机加入码
+fn new_code() -> String {
+    Uuid::new_v4()
+        .as_bytes()
+        .iter()
+        .take(CODE_LENGTH)
+        .map(|byte| CODE_ALPHABET[*byte as usize % CODE_ALPHABET.len()] as char)
+        .collect()
+}
+
+// 加入码不区分大小写
+pub fn normalize_code(code: &str) -> String {
+    code.trim().to_ascii_uppercase()
+}
+
+// 创建比赛，创建者自动加入
+pub async fn create_race(pool: &SqlitePool, player_name: &str, now: DateTime<Utc>) -> Result<RaceResponse, RaceError> {
+    let now_text = now.to_rfc3339();
+    let daily_date = now.date_naive().format("%Y-%m-%d").to_string();
+
+    for _ in 0..CODE_ATTEMPTS {
+        let code = new_code();
+        let mut tx = pool.begin().await?;
+        let inserted = sqlx::query(
+            "INSERT OR IGNORE INTO races (id, daily_date, created_at, updated_at) VALUES (?1, ?2, ?3, ?3)",
+        )
+        .bind(&code)
+        .bind(&daily_date)
+        .bind(&now_text)
+        .execute(&mut *tx)
+        .await?
+        .rows_affected();
+        if inserted == 0 {
+            continue;
+        }
+        sqlx::query("INSERT INTO race_progress (race_id, player_name, joined_at, updated_at) VALUES (?1, ?2, ?3, ?3)")
+            .bind(&code)
+            .bind(player_name)
+            .bind(&now_text)
+            .execute(&mut *tx)
+            .await?;
+        tx.commit().await?;
+        return load_race(pool, &code, now).await?.ok_or(RaceError::NotFound);
+    }
+
+    Err(RaceError::NoFreeCode)
+}
+
+// 加入比赛；已经在比赛中的玩家重新加入（例如客户端重启）直接返回当前状态
+pub async fn join_race(
+    pool: &SqlitePool,
+    code: &str,
+    player_name: &str,
+    now: DateTime<Utc>,
+) -> Result<RaceResponse, RaceError> {
+    let now_text = now.to_rfc3339();
+    let mut tx = pool.begin().await?;
+
+    let race: Option<(Option<String>,)> = sqlx::query_as("SELECT finished_at FROM races WHERE id = ?1")
+        .bind(code)
+        .fetch_optional(&mut *tx)
+        .await?;
+    let Some((finished_at,)) = race else {
+        return Err(RaceError::NotFound);
+    };
+
+    let players: Vec<(String,)> = sqlx::query_as("SELECT player_name FROM race_progress WHERE race_id = ?1")
+        .bind(code)
+        .fetch_all(&mut *tx)
+        .await?;
+    if !players.iter().any(|(name,)| name == player_name) {
+        if players.len() >= RACE_PLAYERS || finished_at.is_some() {
+            return Err(RaceError::Full);
+        }
+        sqlx::query("INSERT INTO race_progress (race_id, player_name, joined_at, updated_at) VALUES (?1, ?2, ?3, ?3)")
+            .bind(code)
+            .bind(player_name)
+            .bind(&now_text)
+            .execute(&mut *tx)
+            .await?;
+        sqlx::query("UPDATE races SET updated_at = ?2 WHERE id = ?1")
+            .bind(code)
+            .bind(&now_text)
+            .execute(&mut *tx)
+            .await?;
+    }
+    tx.commit().await?;
+
+    load_race(pool, code, now).await?.ok_or(RaceError::NotFound)
+}
+
+// 记录进度；乱序到达的旧进度不会覆盖新进度。双方都结束时记录比赛结果
+pub async fn update_progress(
+    pool: &SqlitePool,
+    code: &str,
+    request: &RaceProgressRequest,
+    now: DateTime<Utc>,
+) -> Result<RaceResponse, RaceError> {
+    let now_text = now.to_rfc3339();
+    let mut tx = pool.begin().await?;
+
+    let joined: Option<(bool,)> =
+        sqlx::query_as("SELECT finished FROM race_progress WHERE race_id = ?1 AND player_name = ?2")
+            .bind(code)
+            .bind(&request.player_name)
+            .fetch_optional(&mut *tx)
+            .await?;
+    match joined {
+        None => {
+            let exists: Option<(String,)> = sqlx::query_as("SELECT id FROM races WHERE id = ?1")
+                .bind(code)
+                .fetch_optional(&mut *tx)
+                .await?;
+            return Err(if exists.is_some() { RaceError::NotJoined } else { RaceError::NotFound });
+        }
+        Some((true,)) => {}
+        Some((false,)) => {
+            sqlx::query(
+                "UPDATE race_progress SET score = MAX(score, ?3), level = MAX(level, ?4), finished = ?5, updated_at = ?6 \
+                 WHERE race_id = ?1 AND player_name = ?2",
+            )
+            .bind(code)
+            .bind(&request.player_name)
+            .bind(request.score as i64)
+            .bind(request.level as i64)
+            .bind(request.finished)
+            .bind(&now_text)
+            .execute(&mut *tx)
+            .await?;
+            sqlx::query("UPDATE races SET updated_at = ?2 WHERE id = ?1")
+                .bind(code)
+                .bind(&now_text)
+                .execute(&mut *tx)
+                .await?;
+            if request.finished {
+                record_result(&mut tx, code, &now_text).await?;
+            }
+        }
+    }
+    tx.commit().await?;
+
+    load_race(pool, code, now).await?.ok_or(RaceError::NotFound)
+}
+
+// 满员且双方都结束时写入结束时间和胜者
+async fn record_result(
+    tx: &mut sqlx::Transaction<'_, sqlx::Sqlite>,
+    code: &str,
+    now_text: &str,
+) -> Result<(), sqlx::Error> {
+    let players: Vec<(String, i64, bool)> =
+        sqlx::query_as("SELECT player_name, score, finished FROM race_progress WHERE race_id = ?1")
+            .bind(code)
+            .fetch_all(&mut **tx)
+            .await?;
+    if players.len() < RACE_PLAYERS || players.iter().any(|(_, _, finished)| !finished) {
+        return Ok(());
+    }
+
+    let best = players.iter().map(|(_, score, _)| *score).max().unwrap_or(0);
+    let mut leaders = players.iter().filter(|(_, score, _)| *score == best);
+    let winner = match (leaders.next(), leaders.next()) {
+        (Some((name, _, _)), None) => Some(name.clone()),
+        _ => None,
+    };
+    sqlx::query("UPDATE races SET finished_at = ?2, winner = ?3 WHERE id = ?1 AND finished_at IS NULL")
+        .bind(code)
+        .bind(now_text)
+        .bind(winner)
+        .execute(&mut **tx)
+        .await?;
+    Ok(())
+}
+
+// 比赛的当前状态；不存在时返回 None
+pub async fn load_race(pool: &SqlitePool, code: &str, now: DateTime<Utc>) -> Result<Option<RaceResponse>, sqlx::Error> {
+    let race: Option<DbRace> =
+        sqlx::query_as("SELECT id, daily_date, created_at, finished_at, winner FROM races WHERE id = ?1")
+            .bind(code)
+            .fetch_optional(pool)
+            .await?;
+    let Some(race) = race else {
+        return Ok(None);
+    };
+
+    let progress: Vec<DbProgress> = sqlx::query_as(
+        "SELECT player_name, score, level, finished, updated_at FROM race_progress \
+         WHERE race_id = ?1 ORDER BY joined_at ASC, player_name ASC",
+    )
+    .bind(code)
+    .fetch_all(pool)
+    .await?;
+
+    let seed = chrono::NaiveDate::parse_from_str(&race.daily_date, "%Y-%m-%d")
+        .map(daily_seed)
+        .unwrap_or(0);
+    let players = progress
+        .into_iter()
+        .map(|row| {
+            let age_ms = DateTime::parse_from_rfc3339(&row.updated_at)
+                .map(|updated| (now - updated.with_timezone(&Utc)).num_milliseconds().max(0))
+                .unwrap_or(0);
+            RaceProgress {
+                player_name: row.player_name,
+                score: row.score as u32,
+                level: row.level as u32,
+                finished: row.finished,
+                updated_at: row.updated_at,
+                age_ms,
+            }
+        })
+        .collect();
+
+    Ok(Some(RaceResponse {
+        id: race.id,
+        seed,
+        daily_date: race.daily_date,
+        created_at: race.created_at,
+        finished: race.finished_at.is_some(),
+        winner: race.winner,
+        players,
+    }))
+}
+
+// 删除长时间没有更新的未结束比赛和过了保留期的已结束比赛，返回删除的比赛数
+pub async fn cleanup_races(pool: &SqlitePool, now: DateTime<Utc>) -> Result<u64, sqlx::Error> {
+    let stale_cutoff = (now - Duration::hours(STALE_AFTER_HOURS)).to_rfc3339();
+    let finished_cutoff = (now - Duration::days(FINISHED_KEEP_DAYS)).to_rfc3339();
+
+    let mut tx = pool.begin().await?;
+    let expired = "SELECT id FROM races WHERE (finished_at IS NULL AND updated_at < ?1) \
+                   OR (finished_at IS NOT NULL AND finished_at < ?2)";
+    sqlx::query(&format!("DELETE FROM race_progress WHERE race_id IN ({})", expired))
+        .bind(&stale_cutoff)
+        .bind(&finished_cutoff)
+        .execute(&mut *tx)
+        .await?;
+    let deleted = sqlx::query(&format!("DELETE FROM races WHERE id IN ({})", expired))
+        .bind(&stale_cutoff)
+        .bind(&finished_cutoff)
+        .execute(&mut *tx)
+        .await?
+        .rows_affected();
+    tx.commit().await?;
+
+    if deleted > 0 {
+        log::info!("Removed {} stale races", deleted);
+    }
+    Ok(deleted)
+}
+
+// 后台定期清理比赛，出错时只记录日志，下个周期再试
+pub async fn cleanup_loop(pool: SqlitePool) {
+    let mut interval = tokio::time::interval(CLEANUP_INTERVAL);
+    loop {
+        interval.tick().await;
+        if let Err(e) = cleanup_races(&pool, Utc::now()).await {
+            log::error!("Race cleanup failed: {:?}", e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+    use sqlx::sqlite::SqlitePoolOptions;
+
+    // 内存数据库只能有一个连接，否则每个连接看到的是不同的数据库
+    async fn test_pool() -> SqlitePool {
+        let pool = SqlitePoolOptions::new().max_connections(1).connect("sqlite::memory:").await.unwrap();
+        crate::init_db(&pool).await.unwrap();
+        pool
+    }
+
+    fn now() -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2025, 6, 1, 12, 0, 0).unwrap()
+    }
+
+    fn progress(player_name: &str, score: u32, level: u32, finished: bool) -> RaceProgressRequest {
+        RaceProgressRequest {
+            player_name: player_name.to_string(),
+            score,
+            level,
+            finished,
+        }
+    }
+
+    async fn race_count(pool: &SqlitePool) -> i64 {
+        sqlx::query_scalar("SELECT COUNT(*) FROM races").fetch_one(pool).await.unwrap()
+    }
+
+    #[test]
+    fn codes_avoid_ambiguous_characters() {
+        for _ in 0..200 {
+            let code = new_code();
+            assert_eq!(code.len(), CODE_LENGTH);
+            assert!(code.bytes().all(|byte| CODE_ALPHABET.contains(&byte)), "{}", code);
+        }
+        assert_eq!(normalize_code("  ab3xyz \n"), "AB3XYZ");
+    }
+
+    #[tokio::test]
+    async fn a_race_takes_the_creator_and_one_friend() {
+        let pool = test_pool().await;
+        let race = create_race(&pool, "Alice", now()).await.unwrap();
+        assert_eq!(race.daily_date, "2025-06-01");
+        assert_eq!(race.seed, daily_seed(now().date_naive()));
+        assert!(!race.finished);
+        assert_eq!(race.players.len(), 1);
+        assert_eq!((race.players[0].player_name.as_str(), race.players[0].score), ("Alice", 0));
+
+        let joined = join_race(&pool, &race.id, "Bob", now()).await.unwrap();
+        let names: Vec<&str> = joined.players.iter().map(|player| player.player_name.as_str()).collect();
+        assert_eq!(names, ["Alice", "Bob"]);
+        // 已经在比赛中的玩家可以重新加入
+        assert_eq!(join_race(&pool, &race.id, "Bob", now()).await.unwrap().players.len(), RACE_PLAYERS);
+        assert!(matches!(join_race(&pool, &race.id, "Carol", now()).await, Err(RaceError::Full)));
+        assert!(matches!(join_race(&pool, "NOPE22", "Carol", now()).await, Err(RaceError::NotFound)));
+    }
+
+    #[tokio::test]
+    async fn progress_only_moves_forward_and_needs_a_joined_player() {
+        let pool = test_pool().await;
+        let race = create_race(&pool, "Alice", now()).await.unwrap();
+
+        update_progress(&pool, &race.id, &progress("Alice", 900, 3, false), now()).await.unwrap();
+        // 乱序到达的旧进度不会覆盖新进度
+        let later = now() + Duration::seconds(2);
+        let response = update_progress(&pool, &race.id, &progress("Alice", 400, 2, false), later).await.unwrap();
+        assert_eq!((response.players[0].score, response.players[0].level), (900, 3));
+
+        // 距离上次上报的时间按读取时计算
+        let read_at = later + Duration::milliseconds(1500);
+        let response = load_race(&pool, &race.id, read_at).await.unwrap().unwrap();
+        assert_eq!(response.players[0].age_ms, 1500);
+
+        let stranger = update_progress(&pool, &race.id, &progress("Mallory", 1, 1, false), now()).await;
+        assert!(matches!(stranger, Err(RaceError::NotJoined)));
+        let missing = update_progress(&pool, "NOPE22", &progress("Alice", 1, 1, false), now()).await;
+        assert!(matches!(missing, Err(RaceError::NotFound)));
+        assert!(load_race(&pool, "NOPE22", now()).await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn the_result_is_recorded_once_both_players_finish() {
+        let pool = test_pool().await;
+        let race = create_race(&pool, "Alice", now()).await.unwrap();
+        join_race(&pool, &race.id, "Bob", now()).await.unwrap();
+
+        let response = update_progress(&pool, &race.id, &progress("Alice", 1200, 4, true), now()).await.unwrap();
+        assert!(!response.finished, "the opponent is still playing");
+        // 结束后不再接受更新
+        let response = update_progress(&pool, &race.id, &progress("Alice", 5000, 9, false), now()).await.unwrap();
+        assert_eq!((response.players[0].score, response.players[0].finished), (1200, true));
+
+        let response = update_progress(&pool, &race.id, &progress("Bob", 800, 3, true), now()).await.unwrap();
+        assert!(response.finished);
+        assert_eq!(response.winner.as_deref(), Some("Alice"));
+        // 结束的比赛不能再加入
+        assert!(matches!(join_race(&pool, &race.id, "Carol", now()).await, Err(RaceError::Full)));
+    }
+
+    #[tokio::test]
+    async fn a_tie_has_no_winner() {
+        let pool = test_pool().await;
+        let race = create_race(&pool, "Alice", now()).await.unwrap();
+        join_race(&pool, &race.id, "Bob", now()).await.unwrap();
+        update_progress(&pool, &race.id, &progress("Alice", 700, 2, true), now()).await.unwrap();
+        let response = update_progress(&pool, &race.id, &progress("Bob", 700, 3, true), now()).await.unwrap();
+        assert!(response.finished);
+        assert_eq!(response.winner, None);
+    }
+
+    #[tokio::test]
+    async fn a_lone_finisher_does_not_end_the_race() {
+        let pool = test_pool().await;
+        let race = create_race(&pool, "Alice", now()).await.unwrap();
+        let response = update_progress(&pool, &race.id, &progress("Alice", 700, 2, true), now()).await.unwrap();
+        assert!(!response.finished);
+        // 对手还能加入并完成比赛
+        join_race(&pool, &race.id, "Bob", now()).await.unwrap();
+        let response = update_progress(&pool, &race.id, &progress("Bob", 900, 2, true), now()).await.unwrap();
+        assert_eq!(response.winner.as_deref(), Some("Bob"));
+    }
+
+    #[tokio::test]
+    async fn cleanup_removes_idle_and_expired_races() {
+        let pool = test_pool().await;
+        let idle = create_race(&pool, "Idle", now()).await.unwrap();
+        let active = create_race(&pool, "Active", now()).await.unwrap();
+        let finished = create_race(&pool, "Alice", now()).await.unwrap();
+        join_race(&pool, &finished.id, "Bob", now()).await.unwrap();
+        update_progress(&pool, &finished.id, &progress("Alice", 10, 1, true), now()).await.unwrap();
+        update_progress(&pool, &finished.id, &progress("Bob", 20, 1, true), now()).await.unwrap();
+
+        let later = now() + Duration::hours(STALE_AFTER_HOURS) + Duration::minutes(1);
+        update_progress(&pool, &active.id, &progress("Active", 50, 1, false), later - Duration::minutes(5))
+            .await
+            .unwrap();
+        assert_eq!(cleanup_races(&pool, later).await.unwrap(), 1);
+        assert!(load_race(&pool, &idle.id, later).await.unwrap().is_none());
+        assert!(load_race(&pool, &active.id, later).await.unwrap().is_some());
+        // 结束的比赛保留到期限为止
+        assert!(load_race(&pool, &finished.id, later).await.unwrap().is_some());
+
+        let expired = now() + Duration::days(FINISHED_KEEP_DAYS) + Duration::minutes(1);
+        assert_eq!(cleanup_races(&pool, expired).await.unwrap(), 2);
+        assert_eq!(race_count(&pool).await, 0);
+        let orphans: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM race_progress").fetch_one(&pool).await.unwrap();
+        assert_eq!(orphans, 0);
+    }
+}
@@ -0,0 +1,293 @@
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::SqlitePool;
+
+use crate::config::ServerConfig;
+
+// 归档时复制的列，scores 新增列时这里和 scores_archive 都要同步
+const ARCHIVED_COLUMNS: &str = "id, player_name, score, level, difficulty, created_at, season, \
+    idempotency_key, venue, mode, daily_date, seed, abandoned, event_id, tournament_id, assisted, curse";
+
+// 可以归档的记录：早于截止时间，不是玩家的最好成绩，也不在所属赛季和难度的前几名
+const CANDIDATES_SQL: &str = r#"
+    WITH ranked AS (
+        SELECT id, created_at,
+            ROW_NUMBER() OVER (PARTITION BY player_name ORDER BY score DESC, created_at ASC, id ASC) AS player_rank,
+            ROW_NUMBER() OVER (PARTITION BY season, difficulty ORDER BY score DESC, created_at ASC, id ASC) AS board_rank
+        FROM scores
+    )
+    SELECT id FROM ranked
+    WHERE created_at < ?1 AND player_rank > 1 AND board_rank > ?2
+    ORDER BY created_at ASC, id ASC
+"#;
+
+// 一次数据保留任务的结果
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RetentionReport {
+    pub cutoff: String,
+    pub dry_run: bool,
+    // 符合归档条件的行数（试运行时是会被归档的行数）
+    pub eligible: u64,
+    pub archived: u64,
+    pub batches: u32,
+}
+
+// 迁移：创建归档表，列与 scores 一致，另外记录归档时间
+pub async fn migrate_add_archive(pool: &SqlitePool) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS scores_archive (
+            id TEXT PRIMARY KEY,
+            player_name TEXT NOT NULL,
+            score INTEGER NOT NULL,
+            level INTEGER NOT NULL,
+            difficulty TEXT NOT NULL,
+            created_at TEXT NOT NULL,
+            season TEXT,
+            idempotency_key TEXT,
+            venue TEXT,
+            mode TEXT,
+            daily_date TEXT,
+            seed TEXT,
+            abandoned INTEGER NOT NULL DEFAULT 0,
+            archived_at TEXT NOT NULL,
+            event_id TEXT,
+            tournament_id TEXT
+        );
+
+        CREATE INDEX IF NOT EXISTS idx_archive_player ON scores_archive(player_name);
+        "#,
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+async fn count_eligible(pool: &SqlitePool, cutoff: &str, protected_top: u32) -> Result<u64, sqlx::Error> {
+    let (count,): (i64,) = sqlx::query_as(&format!("SELECT COUNT(*) FROM ({})", CANDIDATES_SQL))
+        .bind(cutoff)
+        .bind(protected_top as i64)
+        .fetch_one(pool)
+        .await?;
+    Ok(count as u64)
+}
+
+// 归档一批记录：在同一个事务中复制到 scores_archive 再从 scores 删除，返回处理的行数
+async fn archive_batch(
+    pool: &SqlitePool,
+    cutoff: &str,
+    protected_top: u32,
+    batch_size: u32,
+    now: &str,
+) -> Result<u64, sqlx::Error> {
+    let mut tx = pool.begin().await?;
+
+    let ids: Vec<(String,)> = sqlx::query_as(&format!("{} LIMIT ?3", CANDIDATES_SQL))
+        .bind(cutoff)
+        .bind(protected_top as i64)
+        .bind(batch_size as i64)
+        .fetch_all(&mut *tx)
+        .await?;
+    if ids.is_empty() {
+        return Ok(0);
+    }
+
+    let placeholders = vec!["?"; ids.len()].join(", ");
+    let insert_sql = format!(
+        "INSERT INTO scores_archive ({columns}, archived_at) SELECT {columns}, ? FROM scores WHERE id IN ({placeholders})",
+        columns = ARCHIVED_COLUMNS,
+        placeholders = placeholders,
+    );
+    let mut insert = sqlx::query(&insert_sql).bind(now);
+    for (id,) in &ids {
+        insert = insert.bind(id);
+    }
+    insert.execute(&mut *tx).await?;
+
+    let delete_sql = format!("DELETE FROM scores WHERE id IN ({})", placeholders);
+    let mut delete = sqlx::query(&delete_sql);
+    for (id,) in &ids {
+        delete = delete.bind(id);
+    }
+    let deleted = delete.execute(&mut *tx).await?.rows_affected();
+
+    tx.commit().await?;
+    Ok(deleted)
+}
+
+// 按配置执行一次数据保留任务；试运行时只统计符合条件的行数
+pub async fn run_retention(
+    pool: &SqlitePool,
+    config: &ServerConfig,
+    dry_run: bool,
+    now: DateTime<Utc>,
+) -> Result<RetentionReport, sqlx::Error> {
+    let cutoff = (now - Duration::days(config.retention_max_age_days as i64)).to_rfc3339();
+    let eligible = count_eligible(pool, &cutoff, config.retention_protected_top).await?;
+    let mut report = RetentionReport {
+        cutoff,
+        dry_run,
+        eligible,
+        ..RetentionReport::default()
+    };
+
+    if dry_run {
+        log::info!(
+            "Retention dry run: {} rows older than {} would be archived",
+            report.eligible,
+            report.cutoff
+        );
+        return Ok(report);
+    }
+
+    // 归档的行不会改变剩余行是否受保护，所以分批处理结果与一次处理相同
+    let archived_at = now.to_rfc3339();
+    loop {
+        let archived = archive_batch(
+            pool,
+            &report.cutoff,
+            config.retention_protected_top,
+            config.retention_batch_size,
+            &archived_at,
+        )
+        .await?;
+        if archived == 0 {
+            break;
+        }
+        report.archived += archived;
+        report.batches += 1;
+        if archived < config.retention_batch_size as u64 {
+            break;
+        }
+    }
+
+    log::info!(
+        "Retention archived {} rows older than {} in {} batches",
+        report.archived,
+        report.cutoff,
+        report.batches
+    );
+    Ok(report)
+}
+
+// 后台定期执行数据保留任务，出错时只记录日志，下个周期再试
+pub async fn retention_loop(pool: SqlitePool, config: ServerConfig) {
+    let mut interval = tokio::time::interval(config.retention_interval());
+    loop {
+        interval.tick().await;
+        if let Err(e) = run_retention(&pool, &config, config.retention_dry_run, Utc::now()).await {
+            log::error!("Retention job failed: {:?}", e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+    use sqlx::sqlite::SqlitePoolOptions;
+
+    // 内存数据库只能有一个连接，否则每个连接看到的是不同的数据库
+    async fn test_pool() -> SqlitePool {
+        let pool = SqlitePoolOptions::new().max_connections(1).connect("sqlite::memory:").await.unwrap();
+        crate::init_db(&pool).await.unwrap();
+        pool
+    }
+
+    fn now() -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2025, 6, 1, 0, 0, 0).unwrap()
+    }
+
+    async fn seed(pool: &SqlitePool, id: &str, player_name: &str, score: u32, season: &str, days_ago: i64) {
+        let created_at = now() - Duration::days(days_ago);
+        sqlx::query(
+            "INSERT INTO scores (id, player_name, score, level, difficulty, created_at, season) \
+             VALUES (?, ?, ?, 1, 'Medium', ?, ?)",
+        )
+        .bind(id)
+        .bind(player_name)
+        .bind(score as i64)
+        .bind(created_at.to_rfc3339())
+        .bind(season)
+        .execute(pool)
+        .await
+        .unwrap();
+    }
+
+    // 两个赛季的数据：每名玩家的最好成绩和每个赛季前两名必须保留，其余超过 30 天的记录归档
+    async fn seeded_pool() -> SqlitePool {
+        let pool = test_pool().await;
+        seed(&pool, "a-best", "ALICE", 1000, "2025-01", 120).await;
+        seed(&pool, "a-chaff-1", "ALICE", 50, "2025-01", 110).await;
+        seed(&pool, "a-chaff-2", "ALICE", 40, "2025-01", 100).await;
+        seed(&pool, "b-best", "BOB", 900, "2025-01", 120).await;
+        seed(&pool, "b-chaff", "BOB", 800, "2025-01", 115).await;
+        // 不是个人最好成绩，但在 2025-02 赛季排第二
+        seed(&pool, "a-season-top", "ALICE", 20, "2025-02", 90).await;
+        seed(&pool, "c-best", "CAROL", 30, "2025-02", 90).await;
+        seed(&pool, "c-chaff", "CAROL", 10, "2025-02", 85).await;
+        // 未超过保留期限
+        seed(&pool, "a-recent", "ALICE", 5, "2025-05", 3).await;
+        pool
+    }
+
+    fn config() -> ServerConfig {
+        ServerConfig {
+            retention_max_age_days: 30,
+            retention_protected_top: 2,
+            retention_batch_size: 2,
+            ..ServerConfig::default()
+        }
+    }
+
+    async fn ids(pool: &SqlitePool, table: &str) -> Vec<String> {
+        sqlx::query_as::<_, (String,)>(&format!("SELECT id FROM {} ORDER BY id", table))
+            .fetch_all(pool)
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|(id,)| id)
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn personal_bests_and_season_tops_survive() {
+        let pool = seeded_pool().await;
+        let report = run_retention(&pool, &config(), false, now()).await.unwrap();
+        assert_eq!(report.eligible, 4);
+        assert_eq!(report.archived, 4);
+        // 每批 2 行，最后一批正好装满时再查一次才知道已经处理完
+        assert_eq!(report.batches, 2);
+
+        assert_eq!(ids(&pool, "scores_archive").await, vec!["a-chaff-1", "a-chaff-2", "b-chaff", "c-chaff"]);
+        assert_eq!(ids(&pool, "scores").await, vec!["a-best", "a-recent", "a-season-top", "b-best", "c-best"]);
+
+        // 再跑一次没有可归档的记录
+        let again = run_retention(&pool, &config(), false, now()).await.unwrap();
+        assert_eq!((again.eligible, again.archived, again.batches), (0, 0, 0));
+    }
+
+    #[tokio::test]
+    async fn archived_rows_keep_their_columns() {
+        let pool = seeded_pool().await;
+        run_retention(&pool, &config(), false, now()).await.unwrap();
+        let (player_name, score, season, archived_at): (String, i64, String, String) =
+            sqlx::query_as("SELECT player_name, score, season, archived_at FROM scores_archive WHERE id = 'b-chaff'")
+                .fetch_one(&pool)
+                .await
+                .unwrap();
+        assert_eq!((player_name.as_str(), score, season.as_str()), ("BOB", 800, "2025-01"));
+        assert_eq!(archived_at, now().to_rfc3339());
+    }
+
+    #[tokio::test]
+    async fn dry_run_only_counts() {
+        let pool = seeded_pool().await;
+        let report = run_retention(&pool, &config(), true, now()).await.unwrap();
+        assert!(report.dry_run);
+        assert_eq!((report.eligible, report.archived, report.batches), (4, 0, 0));
+        assert_eq!(ids(&pool, "scores").await.len(), 9);
+        assert!(ids(&pool, "scores_archive").await.is_empty());
+    }
+}
@@ -0,0 +1,129 @@
+use crate::Score;
+
+const SITE_NAME: &str = "Super Breakout";
+
+// 分享链接打开的成绩卡片页面。OpenGraph 标签让 Discord 等聊天软件展开链接时显示成绩摘要；
+// page_url 为配置的公开地址下的链接，没有配置时省略 og:url
+pub fn render_score_page(score: &Score, page_url: Option<&str>) -> String {
+    let title = format!("{} scored {} in {}", score.player_name, score.score, SITE_NAME);
+    let date = score
+        .created_at
+        .as_deref()
+        .map(|created_at| created_at.get(..10).unwrap_or(created_at))
+        .unwrap_or("");
+    let mut details = vec![format!("Level {}", score.level), score.difficulty.clone()];
+    if let Some(rank) = score.rank {
+        details.push(format!("Rank #{}", rank));
+    }
+    if !date.is_empty() {
+        details.push(date.to_string());
+    }
+    let description = details.join(" · ");
+
+    let mut meta = vec![
+        meta_property("og:type", "website"),
+        meta_property("og:site_name", SITE_NAME),
+        meta_property("og:title", &title),
+        meta_property("og:description", &description),
+        meta_name("twitter:card", "summary"),
+        meta_name("description", &description),
+    ];
+    if let Some(url) = page_url {
+        meta.push(meta_property("og:url", url));
+    }
+
+    let rank = match score.rank {
+        Some(rank) => format!("#{}", rank),
+        None => "-".to_string(),
+    };
+    let rows = [
+        ("Level", score.level.to_string()),
+        ("Difficulty", score.difficulty.clone()),
+        ("Rank", rank),
+        ("Date", date.to_string()),
+    ]
+    .iter()
+    .map(|(label, value)| format!("<tr><th>{}</th><td>{}</td></tr>", label, escape_html(value)))
+    .collect::<Vec<_>>()
+    .join("\n        ");
+
+    page(
+        &escape_html(&title),
+        &meta.join("\n  "),
+        &format!(
+            "<main class=\"card\">\n      <p class=\"site\">{site}</p>\n      <h1>{name}</h1>\n      \
+             <p class=\"score\">{score}</p>\n      <table>\n        {rows}\n      </table>\n    </main>",
+            site = SITE_NAME,
+            name = escape_html(&score.player_name),
+            score = score.score,
+            rows = rows,
+        ),
+    )
+}
+
+// 分数不存在（或已被删除、归档）时的页面
+pub fn render_not_found() -> String {
+    page(
+        "Score not found",
+        &meta_name("robots", "noindex"),
+        &format!(
+            "<main class=\"card\">\n      <p class=\"site\">{}</p>\n      <h1>Score not found</h1>\n      \
+             <p>This score does not exist or has been removed.</p>\n    </main>",
+            SITE_NAME
+        ),
+    )
+}
+
+fn page(title: &str, meta: &str, body: &str) -> String {
+    format!(
+        r#"<!DOCTYPE html>
+<html lang="en">
+<head>
+  <meta charset="utf-8">
+  <meta name="viewport" content="width=device-width, initial-scale=1">
+  <title>{title}</title>
+  {meta}
+  <style>
+    body {{ margin: 0; min-height: 100vh; display: flex; align-items: center; justify-content: center;
+           background: #1a1a26; color: #fff; font-family: sans-serif; }}
+    .card {{ background: #33333f; padding: 32px 48px; border-radius: 8px; text-align: center; }}
+    .site {{ color: #999; margin: 0; }}
+    .score {{ font-size: 48px; font-weight: bold; color: #ffd94d; margin: 8px 0 16px; }}
+    th {{ color: #aaa; font-weight: normal; text-align: left; padding-right: 24px; }}
+    td {{ text-align: right; }}
+  </style>
+</head>
+<body>
+    {body}
+</body>
+</html>
+"#,
+        title = title,
+        meta = meta,
+        body = body,
+    )
+}
+
+fn meta_property(property: &str, content: &str) -> String {
+    format!("<meta property=\"{}\" content=\"{}\">", property, escape_html(content))
+}
+
+fn meta_name(name: &str, content: &str) -> String {
+    format!("<meta name=\"{}\" content=\"{}\">", name, escape_html(content))
+}
+
+// 玩家名来自用户输入，写进页面前必须转义
+fn escape_html(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for ch in text.chars() {
+        match ch {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            _ => escaped.push(ch),
+        }
+    }
+    escaped
+}
@@ -0,0 +1,332 @@
+use chrono::{DateTime, Duration, Utc};
+use serde::Serialize;
+use sqlx::{FromRow, SqlitePool};
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::Mutex;
+use std::time::Instant;
+use tokio::sync::Notify;
+
+// 后台刷新间隔
+const REFRESH_INTERVAL: std::time::Duration = std::time::Duration::from_secs(60);
+// 上次刷新后累计这么多次写入就提前刷新
+const BURST_WRITES: u32 = 50;
+// 汇总超过这个时间没有刷新就改用实时查询
+const MAX_AGE_MINUTES: i64 = 5;
+// 全部赛季那一行的键
+const ALL_SEASONS: &str = "";
+
+// 一条语句算出全部赛季和每个赛季的汇总。只有一个 MAX 聚合时，SQLite 的裸列 id 取自最高分那一行
+const REFRESH_SQL: &str = r#"
+    INSERT INTO stats_summary (season_key, total_games, total_players, average_score, highest_score, highest_score_id,
+                               easy_games, medium_games, hard_games, computed_at)
+    SELECT ?1, COUNT(*), COUNT(DISTINCT player_name), COALESCE(AVG(score), 0), MAX(score), id,
+           COALESCE(SUM(difficulty = 'Easy'), 0), COALESCE(SUM(difficulty = 'Medium'), 0),
+           COALESCE(SUM(difficulty = 'Hard'), 0), ?2
+    FROM scores
+    UNION ALL
+    SELECT season, COUNT(*), COUNT(DISTINCT player_name), AVG(score), MAX(score), id,
+           SUM(difficulty = 'Easy'), SUM(difficulty = 'Medium'), SUM(difficulty = 'Hard'), ?2
+    FROM scores WHERE season IS NOT NULL
+    GROUP BY season
+"#;
+
+// 汇总表中的一行，对应 /api/stats/global 需要的聚合值
+#[derive(Debug, Clone, FromRow)]
+pub struct Summary {
+    pub total_games: i64,
+    pub total_players: i64,
+    pub average_score: f64,
+    pub highest_score_id: Option<String>,
+    pub easy_games: i64,
+    pub medium_games: i64,
+    pub hard_games: i64,
+    pub computed_at: String,
+}
+
+// 后台刷新任务的运行情况，管理接口 /api/admin/metrics 返回
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct SummaryMetrics {
+    pub refreshes: u64,
+    pub failures: u64,
+    pub last_refreshed_at: Option<String>,
+    pub last_duration_ms: Option<u64>,
+    pub pending_writes: u32,
+    // 请求由汇总表回答的次数和改用实时查询的次数
+    pub served_from_summary: u64,
+    pub live_fallbacks: u64,
+}
+
+// 汇总刷新的共享状态：写入计数、提前刷新的通知和运行指标
+#[derive(Default)]
+pub struct SummaryRefresher {
+    pending_writes: AtomicU32,
+    wake: Notify,
+    metrics: Mutex<SummaryMetrics>,
+}
+
+impl SummaryRefresher {
+    // 记录新写入的行数，短时间写入很多时提前唤醒刷新任务
+    pub fn record_writes(&self, count: u32) {
+        let pending = self.pending_writes.fetch_add(count, Ordering::Relaxed) + count;
+        if pending >= BURST_WRITES && pending - count < BURST_WRITES {
+            self.wake.notify_one();
+        }
+    }
+
+    // 删除或改名会让汇总里的最高分失效，立即刷新
+    pub fn request_refresh(&self) {
+        self.wake.notify_one();
+    }
+
+    pub fn record_served(&self, from_summary: bool) {
+        let mut metrics = self.metrics.lock().unwrap();
+        if from_summary {
+            metrics.served_from_summary += 1;
+        } else {
+            metrics.live_fallbacks += 1;
+        }
+    }
+
+    pub fn metrics(&self) -> SummaryMetrics {
+        let mut metrics = self.metrics.lock().unwrap().clone();
+        metrics.pending_writes = self.pending_writes.load(Ordering::Relaxed);
+        metrics
+    }
+
+    // 刷新一次并记录结果
+    pub async fn refresh_now(&self, pool: &SqlitePool) -> Result<(), sqlx::Error> {
+        let started = Instant::now();
+        let now = Utc::now();
+        // 先清零再刷新：刷新期间的写入留到下一次
+        let pending = self.pending_writes.swap(0, Ordering::Relaxed);
+        let result = refresh(pool, now).await;
+        let mut metrics = self.metrics.lock().unwrap();
+        match &result {
+            Ok(()) => {
+                metrics.refreshes += 1;
+                metrics.last_refreshed_at = Some(now.to_rfc3339());
+                metrics.last_duration_ms = Some(started.elapsed().as_millis() as u64);
+            }
+            Err(_) => {
+                metrics.failures += 1;
+                self.pending_writes.fetch_add(pending, Ordering::Relaxed);
+            }
+        }
+        result
+    }
+}
+
+// 迁移：创建统计汇总表
+pub async fn migrate_add_stats_summary(pool: &SqlitePool) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS stats_summary (
+            season_key TEXT PRIMARY KEY,
+            total_games INTEGER NOT NULL,
+            total_players INTEGER NOT NULL,
+            average_score REAL NOT NULL,
+            highest_score INTEGER,
+            highest_score_id TEXT,
+            easy_games INTEGER NOT NULL,
+            medium_games INTEGER NOT NULL,
+            hard_games INTEGER NOT NULL,
+            computed_at TEXT NOT NULL
+        )
+        "#,
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+// 在一个事务里整体替换汇总，读取方只会看到旧的或新的一整份
+pub async fn refresh(pool: &SqlitePool, now: DateTime<Utc>) -> Result<(), sqlx::Error> {
+    let mut tx = pool.begin().await?;
+    sqlx::query("DELETE FROM stats_summary").execute(&mut *tx).await?;
+    sqlx::query(REFRESH_SQL)
+        .bind(ALL_SEASONS)
+        .bind(now.to_rfc3339())
+        .execute(&mut *tx)
+        .await?;
+    tx.commit().await
+}
+
+// 读取汇总；汇总缺失或太旧时返回 None，由调用方改用实时查询。
+// 汇总是新的但没有这个赛季的行，说明该赛季还没有成绩
+pub async fn load(pool: &SqlitePool, season: Option<&str>, now: DateTime<Utc>) -> Result<Option<Summary>, sqlx::Error> {
+    let all: Option<Summary> = sqlx::query_as("SELECT * FROM stats_summary WHERE season_key = ?1")
+        .bind(ALL_SEASONS)
+        .fetch_optional(pool)
+        .await?;
+    let Some(all) = all.filter(|summary| is_fresh(&summary.computed_at, now)) else {
+        return Ok(None);
+    };
+    let Some(season) = season else {
+        return Ok(Some(all));
+    };
+    let row: Option<Summary> = sqlx::query_as("SELECT * FROM stats_summary WHERE season_key = ?1")
+        .bind(season)
+        .fetch_optional(pool)
+        .await?;
+    Ok(Some(row.unwrap_or(Summary {
+        total_games: 0,
+        total_players: 0,
+        average_score: 0.0,
+        highest_score_id: None,
+        easy_games: 0,
+        medium_games: 0,
+        hard_games: 0,
+        computed_at: all.computed_at,
+    })))
+}
+
+fn is_fresh(computed_at: &str, now: DateTime<Utc>) -> bool {
+    DateTime::parse_from_rfc3339(computed_at)
+        .is_ok_and(|computed_at| now - computed_at.with_timezone(&Utc) <= Duration::minutes(MAX_AGE_MINUTES))
+}
+
+// 后台定期刷新汇总，写入较多时提前刷新；出错时只记录日志，下个周期再试
+pub async fn refresh_loop(pool: SqlitePool, refresher: std::sync::Arc<SummaryRefresher>) {
+    let mut interval = tokio::time::interval(REFRESH_INTERVAL);
+    loop {
+        tokio::select! {
+            _ = interval.tick() => {}
+            _ = refresher.wake.notified() => {}
+        }
+        if let Err(e) = refresher.refresh_now(&pool).await {
+            log::error!("Stats summary refresh failed: {:?}", e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+    use sqlx::sqlite::SqlitePoolOptions;
+
+    // 内存数据库只能有一个连接，否则每个连接看到的是不同的数据库
+    async fn test_pool() -> SqlitePool {
+        let pool = SqlitePoolOptions::new().max_connections(1).connect("sqlite::memory:").await.unwrap();
+        crate::init_db(&pool).await.unwrap();
+        pool
+    }
+
+    fn now() -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2025, 6, 1, 12, 0, 0).unwrap()
+    }
+
+    async fn seed(pool: &SqlitePool, id: &str, player_name: &str, score: u32, difficulty: &str, season: &str) {
+        sqlx::query(
+            "INSERT INTO scores (id, player_name, score, level, difficulty, created_at, season) \
+             VALUES (?, ?, ?, 1, ?, ?, ?)",
+        )
+        .bind(id)
+        .bind(player_name)
+        .bind(score as i64)
+        .bind(difficulty)
+        .bind(now().to_rfc3339())
+        .bind(season)
+        .execute(pool)
+        .await
+        .unwrap();
+    }
+
+    // 刷新任务有没有被唤醒；唤醒只保留一次，读取后清掉
+    async fn woken(refresher: &SummaryRefresher) -> bool {
+        tokio::time::timeout(std::time::Duration::from_millis(10), refresher.wake.notified()).await.is_ok()
+    }
+
+    #[actix_web::test]
+    async fn reaching_the_burst_threshold_wakes_the_refresher_once() {
+        let refresher = SummaryRefresher::default();
+        refresher.record_writes(BURST_WRITES - 1);
+        assert!(!woken(&refresher).await);
+        refresher.record_writes(1);
+        assert!(woken(&refresher).await);
+        // 超过阈值之后的写入不再重复唤醒，等刷新清零后重新累计
+        refresher.record_writes(10);
+        assert!(!woken(&refresher).await);
+        assert_eq!(refresher.metrics().pending_writes, BURST_WRITES + 10);
+    }
+
+    #[actix_web::test]
+    async fn one_large_batch_across_the_threshold_wakes_the_refresher() {
+        let refresher = SummaryRefresher::default();
+        refresher.record_writes(3);
+        refresher.record_writes(BURST_WRITES * 2);
+        assert!(woken(&refresher).await);
+    }
+
+    #[test]
+    fn summaries_older_than_five_minutes_are_stale() {
+        let computed_at = now().to_rfc3339();
+        assert!(is_fresh(&computed_at, now()));
+        assert!(is_fresh(&computed_at, now() + Duration::minutes(MAX_AGE_MINUTES)));
+        assert!(!is_fresh(&computed_at, now() + Duration::minutes(MAX_AGE_MINUTES) + Duration::seconds(1)));
+        assert!(!is_fresh("not a time", now()));
+    }
+
+    #[actix_web::test]
+    async fn fresh_summary_matches_the_scores() {
+        let pool = test_pool().await;
+        seed(&pool, "a", "ALICE", 500, "Easy", "2025-05").await;
+        seed(&pool, "b", "ALICE", 900, "Hard", "2025-06").await;
+        seed(&pool, "c", "BOB", 100, "Hard", "2025-06").await;
+        refresh(&pool, now()).await.unwrap();
+
+        let all = load(&pool, None, now()).await.unwrap().unwrap();
+        assert_eq!((all.total_games, all.total_players), (3, 2));
+        assert_eq!((all.easy_games, all.medium_games, all.hard_games), (1, 0, 2));
+        assert_eq!(all.average_score, 500.0);
+        assert_eq!(all.highest_score_id.as_deref(), Some("b"));
+        assert_eq!(all.computed_at, now().to_rfc3339());
+
+        let june = load(&pool, Some("2025-06"), now()).await.unwrap().unwrap();
+        assert_eq!((june.total_games, june.total_players), (2, 2));
+        assert_eq!(june.highest_score_id.as_deref(), Some("b"));
+    }
+
+    #[actix_web::test]
+    async fn stale_or_missing_summary_falls_back_to_live_queries() {
+        let pool = test_pool().await;
+        assert!(load(&pool, None, now()).await.unwrap().is_none());
+
+        seed(&pool, "a", "ALICE", 500, "Easy", "2025-06").await;
+        refresh(&pool, now() - Duration::minutes(MAX_AGE_MINUTES + 1)).await.unwrap();
+        assert!(load(&pool, None, now()).await.unwrap().is_none());
+        assert!(load(&pool, Some("2025-06"), now()).await.unwrap().is_none());
+    }
+
+    #[actix_web::test]
+    async fn season_without_scores_is_empty_not_stale() {
+        let pool = test_pool().await;
+        seed(&pool, "a", "ALICE", 500, "Easy", "2025-05").await;
+        refresh(&pool, now()).await.unwrap();
+
+        let june = load(&pool, Some("2025-06"), now()).await.unwrap().unwrap();
+        assert_eq!((june.total_games, june.total_players), (0, 0));
+        assert_eq!(june.highest_score_id, None);
+        assert_eq!(june.computed_at, now().to_rfc3339());
+    }
+
+    #[actix_web::test]
+    async fn failed_refresh_keeps_the_pending_writes() {
+        // 没有汇总表，刷新必然失败
+        let pool = SqlitePoolOptions::new().max_connections(1).connect("sqlite::memory:").await.unwrap();
+        let refresher = SummaryRefresher::default();
+        refresher.record_writes(7);
+        assert!(refresher.refresh_now(&pool).await.is_err());
+        let metrics = refresher.metrics();
+        assert_eq!((metrics.failures, metrics.refreshes, metrics.pending_writes), (1, 0, 7));
+        assert_eq!(metrics.last_refreshed_at, None);
+
+        crate::init_db(&pool).await.unwrap();
+        refresher.record_writes(2);
+        refresher.refresh_now(&pool).await.unwrap();
+        let metrics = refresher.metrics();
+        assert_eq!((metrics.failures, metrics.refreshes, metrics.pending_writes), (1, 1, 0));
+        assert!(metrics.last_refreshed_at.is_some());
+    }
+}
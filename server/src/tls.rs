@@ -0,0 +1,46 @@
+use rustls::{Certificate, PrivateKey, ServerConfig};
+use rustls_pemfile::Item;
+use std::fs::File;
+use std::io::BufReader;
+use std::path::Path;
+
+use crate::config::TlsConfig;
+
+// 读取 PEM 证书链和私钥并检查能否使用；启动和 --check 时调用，有问题时不会开始监听
+pub fn load_server_config(tls: &TlsConfig) -> Result<ServerConfig, String> {
+    let certs = read_certs(&tls.cert_path)?;
+    let key = read_key(&tls.key_path)?;
+    ServerConfig::builder()
+        .with_safe_defaults()
+        .with_no_client_auth()
+        .with_single_cert(certs, key)
+        .map_err(|e| format!("TLS certificate and key cannot be used together: {}", e))
+}
+
+fn open(path: &Path) -> Result<BufReader<File>, String> {
+    File::open(path)
+        .map(BufReader::new)
+        .map_err(|e| format!("Failed to open {}: {}", path.display(), e))
+}
+
+fn read_certs(path: &Path) -> Result<Vec<Certificate>, String> {
+    let certs = rustls_pemfile::certs(&mut open(path)?)
+        .map_err(|e| format!("Failed to parse certificate {}: {}", path.display(), e))?;
+    if certs.is_empty() {
+        return Err(format!("No PEM certificate found in {}", path.display()));
+    }
+    Ok(certs.into_iter().map(Certificate).collect())
+}
+
+// 使用文件中第一个私钥，支持 PKCS#8、PKCS#1（RSA）和 SEC1（EC）格式
+fn read_key(path: &Path) -> Result<PrivateKey, String> {
+    let items = rustls_pemfile::read_all(&mut open(path)?)
+        .map_err(|e| format!("Failed to parse private key {}: {}", path.display(), e))?;
+    items
+        .into_iter()
+        .find_map(|item| match item {
+            Item::PKCS8Key(key) | Item::RSAKey(key) | Item::ECKey(key) => Some(PrivateKey(key)),
+            _ => None,
+        })
+        .ok_or_else(|| format!("No PEM private key found in {}", path.display()))
+}
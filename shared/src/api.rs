@@ -0,0 +1,282 @@
+// 服务器接口的请求和响应格式，服务器按这些类型返回，客户端按这些类型解析
+use serde::{Deserialize, Deserializer, Serialize};
+use std::collections::BTreeMap;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Score {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+    pub player_name: String,
+    pub score: u32,
+    pub level: u32,
+    #[serde(deserialize_with = "expand_difficulty")]
+    pub difficulty: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub created_at: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rank: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub season: Option<String>,
+    // 普通局为空，单关挑战的成绩为 "Level"，只和同一关的成绩排名
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mode: Option<String>,
+    // 提交成功且服务器配置了公开地址时返回的分享链接
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub share_url: Option<String>,
+}
+
+impl Score {
+    // 精简格式：去掉 id 和提交时间，难度缩写为首字母
+    pub fn into_compact(self) -> Self {
+        Self {
+            id: None,
+            created_at: None,
+            difficulty: self.difficulty.chars().take(1).collect(),
+            ..self
+        }
+    }
+}
+
+// 精简格式中难度只有首字母，解析时还原为完整名称
+fn expand_difficulty<'de, D: Deserializer<'de>>(deserializer: D) -> Result<String, D::Error> {
+    let difficulty = String::deserialize(deserializer)?;
+    Ok(match difficulty.as_str() {
+        "E" => "Easy".to_string(),
+        "M" => "Medium".to_string(),
+        "H" => "Hard".to_string(),
+        _ => difficulty,
+    })
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CreateScoreRequest {
+    pub player_name: String,
+    pub score: u32,
+    pub level: u32,
+    pub difficulty: String,
+    // 客户端生成的唯一键，重复提交同一个键只会保存一次
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub idempotency_key: Option<String>,
+    // 展台模式提交时附带的场地标签
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub venue: Option<String>,
+    // 游戏模式：为空是普通局，"Daily" 是每日挑战，"Event" 是每周活动，"Level" 是单关挑战，"Tournament" 是本地淘汰赛
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mode: Option<String>,
+    // 每日挑战使用的种子和开始这一局时的 UTC 日期
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub seed: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub daily_date: Option<String>,
+    // 每周活动局所属的活动（ISO 周，例如 "2025-W07"）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub event_id: Option<String>,
+    // 淘汰赛成绩所属的比赛，由客户端生成，用来查询同一场比赛的全部成绩
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tournament_id: Option<String>,
+    // 崩溃或中途关闭窗口后补交的未完成局，不进入排行榜
+    #[serde(default)]
+    pub abandoned: bool,
+    // 使用了辅助（例如保险代币）的局，照常上榜，记录下来供查看
+    #[serde(default)]
+    pub assisted: bool,
+    // 第三关前选择的诅咒（得分倍率更高的局），没有选择时为空
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub curse: Option<String>,
+}
+
+// 批量提交中单条记录的结果
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum BatchItemResult {
+    Created { score: Score },
+    // 相同幂等键的记录已经存在
+    Duplicate { id: String },
+    Rejected { reason: String },
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BatchSubmitResponse {
+    // 与请求中的记录一一对应
+    pub results: Vec<BatchItemResult>,
+}
+
+// 管理接口修改成绩的玩家名
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RenameScoreRequest {
+    pub player_name: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LeaderboardResponse {
+    pub scores: Vec<Score>,
+    pub total: usize,
+    pub limit: usize,
+    pub offset: usize,
+    // 下一页的游标，没有更多数据时为空
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub next_cursor: Option<String>,
+}
+
+// 最近提交的分数，不分难度和赛季
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecentScoresResponse {
+    pub scores: Vec<Score>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SeasonSummary {
+    pub season: String,
+    pub total_games: u32,
+    pub top_score: Option<Score>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SeasonsResponse {
+    pub current: String,
+    pub seasons: Vec<SeasonSummary>,
+}
+
+// 某一天的每日挑战榜单
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DailyResponse {
+    pub date: String,
+    pub seed: u64,
+    pub scores: Vec<Score>,
+    pub total: usize,
+    // 查询的玩家当天的最好成绩及其排名
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub player_best: Option<Score>,
+}
+
+// 同难度下最好成绩刚好高于玩家的对手
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Rival {
+    pub player_name: String,
+    pub best_score: u32,
+    // 对手最好成绩比玩家高出多少
+    pub gap: u32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RivalsResponse {
+    pub player_name: String,
+    pub difficulty: String,
+    // 玩家在该难度还没有成绩时为空，按 0 分计算对手
+    #[serde(default)]
+    pub best_score: Option<u32>,
+    // 按差距从小到大
+    pub rivals: Vec<Rival>,
+}
+
+// 本周活动
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EventResponse {
+    // ISO 周，例如 "2025-W07"，也是提交时的 event_id
+    pub id: String,
+    pub modifier: String,
+    // 活动开始和结束的 UTC 日期（周一到周日）
+    pub starts_on: String,
+    pub ends_on: String,
+}
+
+// /api/flags 的响应：所有已知开关的当前值，开关名见 FEATURE_FLAGS
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FlagsResponse {
+    pub flags: BTreeMap<String, bool>,
+}
+
+// 创建或加入比赛
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RaceJoinRequest {
+    pub player_name: String,
+}
+
+// 客户端定期上报的进度；分数和关卡只会增加，结束后不再接受更新
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RaceProgressRequest {
+    pub player_name: String,
+    pub score: u32,
+    pub level: u32,
+    #[serde(default)]
+    pub finished: bool,
+}
+
+// 比赛中一名玩家的最新进度
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RaceProgress {
+    pub player_name: String,
+    pub score: u32,
+    pub level: u32,
+    pub finished: bool,
+    pub updated_at: String,
+    // 距离这名玩家上次上报过了多久，客户端据此显示数据的新旧
+    pub age_ms: i64,
+}
+
+// 比赛的当前状态，结束后也是比赛结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RaceResponse {
+    // 加入码
+    pub id: String,
+    // 比赛使用创建当天的每日挑战种子
+    pub seed: u64,
+    pub daily_date: String,
+    pub created_at: String,
+    pub finished: bool,
+    // 双方都结束后分数高的一方；平局为空
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub winner: Option<String>,
+    // 按加入顺序排列
+    pub players: Vec<RaceProgress>,
+}
+
+// 错误响应；客户端把其中的说明显示给玩家
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ErrorResponse {
+    pub error: String,
+    pub message: String,
+    pub timestamp: String,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn full_score() -> Score {
+        Score {
+            id: Some("abc".to_string()),
+            player_name: "ALICE".to_string(),
+            score: 500,
+            level: 3,
+            difficulty: "Hard".to_string(),
+            created_at: Some("2025-06-01T00:00:00Z".to_string()),
+            rank: Some(1),
+            season: Some("2025-06".to_string()),
+            mode: None,
+            share_url: None,
+        }
+    }
+
+    #[test]
+    fn compact_scores_round_trip_to_the_full_difficulty() {
+        let json = serde_json::to_value(full_score().into_compact()).unwrap();
+        assert!(json.get("id").is_none());
+        assert!(json.get("created_at").is_none());
+        assert_eq!(json["difficulty"], "H");
+
+        let parsed: Score = serde_json::from_value(json).unwrap();
+        assert_eq!(parsed.difficulty, "Hard");
+        assert_eq!((parsed.id, parsed.created_at), (None, None));
+        assert_eq!((parsed.player_name.as_str(), parsed.score, parsed.rank), ("ALICE", 500, Some(1)));
+    }
+
+    #[test]
+    fn full_difficulty_names_parse_unchanged() {
+        for difficulty in ["Easy", "Medium", "Hard", "Custom"] {
+            let json = serde_json::json!({ "player_name": "BOB", "score": 1, "level": 1, "difficulty": difficulty });
+            let parsed: Score = serde_json::from_value(json).unwrap();
+            assert_eq!(parsed.difficulty, difficulty);
+        }
+    }
+}
@@ -0,0 +1,117 @@
+// 最终到达关卡的分布：服务器按关卡分组统计后算出中位数和 p90，游戏结束画面用它算出“超过了多少局”
+use serde::{Deserialize, Serialize};
+
+// 样本少于这个数时不给百分比，几局的数据没有意义
+pub const MIN_HISTOGRAM_RUNS: u32 = 20;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LevelBucket {
+    pub level: u32,
+    pub count: u32,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LevelHistogram {
+    pub difficulty: Option<String>,
+    pub total_runs: u32,
+    pub buckets: Vec<LevelBucket>,
+    pub median: u32,
+    pub p90: u32,
+}
+
+impl LevelHistogram {
+    // buckets 需按关卡升序排列
+    pub fn from_buckets(difficulty: Option<String>, buckets: Vec<LevelBucket>) -> Self {
+        let total_runs = buckets.iter().map(|bucket| bucket.count).sum();
+        let mut histogram = LevelHistogram {
+            difficulty,
+            total_runs,
+            buckets,
+            median: 0,
+            p90: 0,
+        };
+        histogram.median = histogram.percentile(0.5);
+        histogram.p90 = histogram.percentile(0.9);
+        histogram
+    }
+
+    // 至少有 fraction 比例的记录不超过返回的关卡；没有记录时为 0
+    pub fn percentile(&self, fraction: f64) -> u32 {
+        let target = (self.total_runs as f64 * fraction).ceil().max(1.0) as u32;
+        let mut seen = 0;
+        for bucket in &self.buckets {
+            seen += bucket.count;
+            if seen >= target {
+                return bucket.level;
+            }
+        }
+        0
+    }
+
+    // 到达关卡低于 level 的记录所占百分比；样本太少时返回 None
+    pub fn beaten_percentage(&self, level: u32) -> Option<f64> {
+        if self.total_runs < MIN_HISTOGRAM_RUNS {
+            return None;
+        }
+        let beaten: u32 = self
+            .buckets
+            .iter()
+            .filter(|bucket| bucket.level < level)
+            .map(|bucket| bucket.count)
+            .sum();
+        Some(beaten as f64 / self.total_runs as f64 * 100.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn histogram(counts: &[(u32, u32)]) -> LevelHistogram {
+        let buckets = counts.iter().map(|&(level, count)| LevelBucket { level, count }).collect();
+        LevelHistogram::from_buckets(Some("Hard".to_string()), buckets)
+    }
+
+    #[test]
+    fn empty_dataset_has_no_percentiles_or_percentage() {
+        let empty = histogram(&[]);
+        assert_eq!(empty.total_runs, 0);
+        assert_eq!(empty.median, 0);
+        assert_eq!(empty.p90, 0);
+        assert_eq!(empty.beaten_percentage(5), None);
+    }
+
+    #[test]
+    fn percentage_needs_at_least_twenty_runs() {
+        assert_eq!(histogram(&[(1, 10), (2, 9)]).beaten_percentage(2), None);
+        assert_eq!(histogram(&[(1, 10), (2, 10)]).beaten_percentage(2), Some(50.0));
+    }
+
+    #[test]
+    fn percentage_counts_only_lower_levels() {
+        let runs = histogram(&[(1, 5), (2, 5), (3, 10)]);
+        assert_eq!(runs.beaten_percentage(1), Some(0.0));
+        assert_eq!(runs.beaten_percentage(3), Some(50.0));
+        assert_eq!(runs.beaten_percentage(4), Some(100.0));
+    }
+
+    #[test]
+    fn median_and_p90_land_on_bucket_boundaries() {
+        // 20 局：前 10 局正好到第 2 关为止，所以中位数是 2；第 18 局在第 3 关，p90 是 3
+        let exact = histogram(&[(1, 4), (2, 6), (3, 8), (4, 2)]);
+        assert_eq!(exact.median, 2);
+        assert_eq!(exact.p90, 3);
+
+        // 多一局落在第 4 关，第 11 局（21 局的一半向上取整）和第 19 局都往后移一个桶
+        let shifted = histogram(&[(1, 4), (2, 6), (3, 8), (4, 3)]);
+        assert_eq!(shifted.median, 3);
+        assert_eq!(shifted.p90, 4);
+    }
+
+    #[test]
+    fn single_run_is_its_own_median() {
+        let single = histogram(&[(7, 1)]);
+        assert_eq!(single.median, 7);
+        assert_eq!(single.p90, 7);
+    }
+}
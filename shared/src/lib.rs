@@ -0,0 +1,45 @@
+// 游戏客户端和服务器共用的约定：接口的请求和响应格式、提交成绩时的模式名、每日挑战的种子算法、服务器地址、
+// 功能开关名和关卡分布。
+// 两边必须完全一致，放在这里避免各写一份
+use chrono::NaiveDate;
+
+pub mod api;
+pub mod histogram;
+
+// 提交时的模式名；为空是普通局
+pub const DAILY_MODE: &str = "Daily";
+pub const EVENT_MODE: &str = "Event";
+// 单关挑战：每一关单独排名，不进入普通排行榜
+pub const LEVEL_MODE: &str = "Level";
+// 本地淘汰赛：只按比赛查询，没有排名，不进入普通排行榜
+pub const TOURNAMENT_MODE: &str = "Tournament";
+
+// 客户端连接的服务器地址，设置这个环境变量时覆盖默认地址
+pub const API_URL_ENV: &str = "BREAKOUT_API_URL";
+pub const DEFAULT_API_URL: &str = "http://localhost:8080/api";
+// 健康检查接口，相对于 API 地址
+pub const HEALTH_PATH: &str = "/health";
+// 管理接口（删除、改名）的密钥放在这个请求头中；客户端从环境变量读取密钥，没有时不显示管理功能
+pub const ADMIN_KEY_HEADER: &str = "X-Admin-Key";
+pub const ADMIN_KEY_ENV: &str = "BREAKOUT_ADMIN_API_KEY";
+
+// 服务器端的功能开关，管理员关闭后客户端隐藏对应的入口，不需要发新版本。
+// 没有设置过的开关和客户端取不到开关时都按打开处理
+pub const FLAG_DAILY_CHALLENGE: &str = "daily_challenge";
+pub const FLAG_WEEKLY_EVENT: &str = "weekly_event";
+pub const FLAG_RACES: &str = "races";
+pub const FEATURE_FLAGS: [&str; 3] = [FLAG_DAILY_CHALLENGE, FLAG_WEEKLY_EVENT, FLAG_RACES];
+
+// 每日挑战的种子，由 UTC 日期推导（FNV-1a）
+pub fn daily_seed(date: NaiveDate) -> u64 {
+    let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+    for byte in format!("breakout-daily-{}", date_key(date)).bytes() {
+        hash ^= u64::from(byte);
+        hash = hash.wrapping_mul(0x0100_0000_01b3);
+    }
+    hash
+}
+
+pub fn date_key(date: NaiveDate) -> String {
+    date.format("%Y-%m-%d").to_string()
+}
@@ -0,0 +1,629 @@
+// 开发用的命令，通过 cargo xtask <命令> 运行（别名见 .cargo/config.toml）：
+//   dev       在空闲端口启动服务器，等健康检查通过后启动游戏并连接到它；Ctrl-C 同时关闭两者
+//   test-all  依次运行单元测试、游戏的冒烟测试和服务器的接口检查
+//   package   编译发布版游戏，连同资源、设置模板、说明和启动脚本打成当前平台的压缩包，解压后运行自检
+use std::env;
+use std::ffi::OsStr;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{Ipv4Addr, TcpListener, TcpStream};
+use std::path::{Path, PathBuf};
+use std::process::{Child, Command, ExitCode, Stdio};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use breakout_shared::{API_URL_ENV, HEALTH_PATH};
+
+const SERVER_PACKAGE: &str = "breakout-server";
+const GAME_PACKAGE: &str = "breakout-game";
+// 服务器启动后等待健康检查通过的最长时间
+const HEALTH_TIMEOUT: Duration = Duration::from_secs(30);
+const POLL_INTERVAL: Duration = Duration::from_millis(100);
+// 收到 Ctrl-C 后等待子进程自行退出的时间，超时后强制结束
+const SHUTDOWN_GRACE: Duration = Duration::from_secs(5);
+// 接口检查使用的只读接口
+const CHECKED_ENDPOINTS: [&str; 4] = [HEALTH_PATH, "/scores?difficulty=Easy", "/scores/recent?limit=5", "/daily"];
+
+fn main() -> ExitCode {
+    let args: Vec<String> = env::args().skip(1).collect();
+    let result = match args.first().map(String::as_str) {
+        Some("dev") => dev(&args[1..]),
+        Some("test-all") => test_all(),
+        Some("package") => package(&args[1..]),
+        _ => {
+            print_usage();
+            return ExitCode::from(2);
+        }
+    };
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("xtask: {}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn print_usage() {
+    eprintln!("Usage: cargo xtask <command>");
+    eprintln!();
+    eprintln!("Commands:");
+    eprintln!("  dev [-- <game args>]  run the server on a free port and the game connected to it");
+    eprintln!("  test-all              run unit tests, the game smoke test and the server API checks");
+    eprintln!("  package               build a release archive of the game for this platform and self-check it");
+}
+
+// 工作区根目录：xtask 位于根目录下一层
+fn workspace_root() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR"))
+        .parent()
+        .expect("xtask is inside the workspace")
+        .to_path_buf()
+}
+
+fn target_dir(root: &Path) -> PathBuf {
+    env::var_os("CARGO_TARGET_DIR")
+        .map(PathBuf::from)
+        .unwrap_or_else(|| root.join("target"))
+}
+
+fn cargo() -> Command {
+    Command::new(env::var_os("CARGO").unwrap_or_else(|| "cargo".into()))
+}
+
+// 运行一条命令并等待结束，失败时返回错误
+fn run(step: &str, command: &mut Command) -> Result<(), String> {
+    println!("== {}", step);
+    let status = command
+        .status()
+        .map_err(|e| format!("failed to start {}: {}", step, e))?;
+    if status.success() {
+        Ok(())
+    } else {
+        Err(format!("{} failed ({})", step, status))
+    }
+}
+
+// 先编译再直接运行二进制：结束 cargo run 不会结束它启动的程序
+fn build(root: &Path, packages: &[&str]) -> Result<(), String> {
+    let mut command = cargo();
+    command.current_dir(root).arg("build");
+    for package in packages {
+        command.args(["-p", package]);
+    }
+    run("build", &mut command)
+}
+
+fn binary(root: &Path, package: &str) -> PathBuf {
+    binary_in(&target_dir(root).join("debug"), package)
+}
+
+fn binary_in(dir: &Path, package: &str) -> PathBuf {
+    dir.join(format!("{}{}", package, env::consts::EXE_SUFFIX))
+}
+
+// 运行一条命令并返回标准输出
+fn output(step: &str, command: &mut Command) -> Result<String, String> {
+    let output = command
+        .stderr(Stdio::inherit())
+        .output()
+        .map_err(|e| format!("failed to start {}: {}", step, e))?;
+    if !output.status.success() {
+        return Err(format!("{} failed ({})", step, output.status));
+    }
+    String::from_utf8(output.stdout).map_err(|e| format!("{} printed invalid UTF-8: {}", step, e))
+}
+
+// 向系统要一个空闲端口；释放后马上交给服务器使用
+fn free_port() -> Result<u16, String> {
+    TcpListener::bind((Ipv4Addr::LOCALHOST, 0))
+        .and_then(|listener| listener.local_addr())
+        .map(|addr| addr.port())
+        .map_err(|e| format!("failed to find a free port: {}", e))
+}
+
+// 带前缀的子进程：标准输出和标准错误逐行转发，两边的日志可以区分
+struct Managed {
+    name: &'static str,
+    child: Child,
+    forwarders: Vec<thread::JoinHandle<()>>,
+}
+
+impl Managed {
+    fn spawn(name: &'static str, command: &mut Command) -> Result<Self, String> {
+        let mut child = command
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .map_err(|e| format!("failed to start {}: {}", name, e))?;
+        let mut forwarders = Vec::new();
+        if let Some(stdout) = child.stdout.take() {
+            forwarders.push(forward(name, stdout, false));
+        }
+        if let Some(stderr) = child.stderr.take() {
+            forwarders.push(forward(name, stderr, true));
+        }
+        Ok(Self { name, child, forwarders })
+    }
+
+    fn has_exited(&mut self) -> bool {
+        !matches!(self.child.try_wait(), Ok(None))
+    }
+
+    // 等待自行退出，超时后强制结束；日志转发到管道关闭为止
+    fn shutdown(mut self, grace: Duration) {
+        let deadline = Instant::now() + grace;
+        while !self.has_exited() && Instant::now() < deadline {
+            thread::sleep(POLL_INTERVAL);
+        }
+        if !self.has_exited() {
+            eprintln!("xtask: stopping {}", self.name);
+            let _ = self.child.kill();
+        }
+        let _ = self.child.wait();
+        for forwarder in self.forwarders {
+            let _ = forwarder.join();
+        }
+    }
+}
+
+fn forward(name: &'static str, stream: impl Read + Send + 'static, to_stderr: bool) -> thread::JoinHandle<()> {
+    thread::spawn(move || {
+        for line in BufReader::new(stream).lines() {
+            let Ok(line) = line else {
+                break;
+            };
+            if to_stderr {
+                eprintln!("[{:<6}] {}", name, line);
+            } else {
+                println!("[{:<6}] {}", name, line);
+            }
+        }
+    })
+}
+
+// 终端的 Ctrl-C 会同时发给子进程；这里只记下来，由主循环等它们退出
+fn interrupt_flag() -> Result<Arc<AtomicBool>, String> {
+    let interrupted = Arc::new(AtomicBool::new(false));
+    let flag = interrupted.clone();
+    ctrlc::set_handler(move || flag.store(true, Ordering::SeqCst))
+        .map_err(|e| format!("failed to install the Ctrl-C handler: {}", e))?;
+    Ok(interrupted)
+}
+
+// 发一个 GET 请求，返回状态码；只用于本机的健康检查和接口检查
+fn http_get_status(port: u16, path: &str) -> Option<u16> {
+    let mut stream = TcpStream::connect((Ipv4Addr::LOCALHOST, port)).ok()?;
+    stream.set_read_timeout(Some(Duration::from_secs(5))).ok()?;
+    write!(
+        stream,
+        "GET {} HTTP/1.1\r\nHost: 127.0.0.1:{}\r\nConnection: close\r\n\r\n",
+        path, port
+    )
+    .ok()?;
+    let mut status_line = String::new();
+    BufReader::new(stream).read_line(&mut status_line).ok()?;
+    status_line.split_whitespace().nth(1)?.parse().ok()
+}
+
+// 等待健康检查返回 200；服务器提前退出或被 Ctrl-C 打断时放弃
+fn wait_for_health(server: &mut Managed, port: u16, interrupted: &AtomicBool) -> Result<(), String> {
+    let path = format!("/api{}", HEALTH_PATH);
+    let deadline = Instant::now() + HEALTH_TIMEOUT;
+    loop {
+        if http_get_status(port, &path) == Some(200) {
+            return Ok(());
+        }
+        if server.has_exited() {
+            return Err("server exited before becoming healthy".to_string());
+        }
+        if interrupted.load(Ordering::SeqCst) {
+            return Err("interrupted".to_string());
+        }
+        if Instant::now() >= deadline {
+            return Err(format!("server did not become healthy within {:?}", HEALTH_TIMEOUT));
+        }
+        thread::sleep(POLL_INTERVAL);
+    }
+}
+
+fn server_command(root: &Path, port: u16) -> Command {
+    let mut command = Command::new(binary(root, SERVER_PACKAGE));
+    // 在 server 目录下运行，使用那里的 config.toml 和数据库，和手动 cargo run 一致
+    command
+        .current_dir(root.join("server"))
+        .args(["--bind", "127.0.0.1", "--port", &port.to_string()]);
+    command
+}
+
+fn game_command(root: &Path, args: &[impl AsRef<OsStr>]) -> Command {
+    let mut command = Command::new(binary(root, GAME_PACKAGE));
+    // 直接运行二进制时 Bevy 按 BEVY_ASSET_ROOT 查找 assets
+    command
+        .current_dir(root.join("game"))
+        .env("BEVY_ASSET_ROOT", root.join("game"))
+        .args(args);
+    command
+}
+
+// cargo xtask dev：服务器和游戏一起运行，任意一个退出或按下 Ctrl-C 时关闭两者
+fn dev(game_args: &[String]) -> Result<(), String> {
+    let game_args: Vec<&String> = game_args.iter().skip_while(|arg| *arg == "--").collect();
+    let root = workspace_root();
+    let interrupted = interrupt_flag()?;
+    build(&root, &[SERVER_PACKAGE, GAME_PACKAGE])?;
+
+    let port = free_port()?;
+    let mut server = Managed::spawn("server", &mut server_command(&root, port))?;
+    if let Err(e) = wait_for_health(&mut server, port, &interrupted) {
+        server.shutdown(SHUTDOWN_GRACE);
+        return Err(e);
+    }
+    let api_url = format!("http://127.0.0.1:{}/api", port);
+    println!("== server is healthy at {}", api_url);
+
+    let mut game = match Managed::spawn("game", game_command(&root, &game_args).env(API_URL_ENV, &api_url)) {
+        Ok(game) => game,
+        Err(e) => {
+            server.shutdown(SHUTDOWN_GRACE);
+            return Err(e);
+        }
+    };
+
+    while !interrupted.load(Ordering::SeqCst) && !game.has_exited() && !server.has_exited() {
+        thread::sleep(POLL_INTERVAL);
+    }
+    let server_died = server.has_exited() && !interrupted.load(Ordering::SeqCst);
+    // 先关游戏，让它最后的请求还能送到服务器
+    game.shutdown(SHUTDOWN_GRACE);
+    server.shutdown(SHUTDOWN_GRACE);
+    if server_died {
+        return Err("server exited unexpectedly".to_string());
+    }
+    Ok(())
+}
+
+// cargo xtask test-all：单元测试 -> 游戏冒烟测试 -> 服务器接口检查，任何一步失败就停止
+fn test_all() -> Result<(), String> {
+    let root = workspace_root();
+    let interrupted = interrupt_flag()?;
+    unit_tests(&root)?;
+
+    build(&root, &[SERVER_PACKAGE, GAME_PACKAGE])?;
+    // 冒烟测试需要窗口，没有显示器的 CI 上用 xvfb-run cargo xtask test-all 运行
+    run("game smoke test", &mut game_command(&root, &["--smoke-test"]))?;
+
+    server_checks(&root, &interrupted)
+}
+
+// cargo test --workspace；一个测试都没有运行也算失败，避免测试被过滤掉或没有编译进去时悄悄通过
+fn unit_tests(root: &Path) -> Result<(), String> {
+    let step = "unit tests";
+    let stdout = output(step, cargo().current_dir(root).args(["test", "--workspace"]))?;
+    print!("{}", stdout);
+    match passed_tests(&stdout) {
+        0 => Err(format!("{} ran no tests", step)),
+        passed => {
+            println!("   ok  {} tests passed", passed);
+            Ok(())
+        }
+    }
+}
+
+// 累加 cargo test 每个测试程序的 "test result: ok. N passed" 行
+fn passed_tests(stdout: &str) -> usize {
+    stdout
+        .lines()
+        .filter_map(|line| line.trim().strip_prefix("test result: ok. "))
+        .filter_map(|rest| rest.split_once(" passed"))
+        .filter_map(|(count, _)| count.parse::<usize>().ok())
+        .sum()
+}
+
+// 用临时数据库启动服务器，检查配置、健康检查和几个只读接口
+fn server_checks(root: &Path, interrupted: &AtomicBool) -> Result<(), String> {
+    let scratch = target_dir(root).join("xtask");
+    std::fs::create_dir_all(&scratch).map_err(|e| format!("failed to create {}: {}", scratch.display(), e))?;
+    let database = scratch.join("server-checks.db");
+    let _ = std::fs::remove_file(&database);
+    let database_url = format!("sqlite://{}", database.display());
+
+    let port = free_port()?;
+    run(
+        "server config check",
+        server_command(root, port).args(["--database-url", &database_url, "--check"]),
+    )?;
+
+    println!("== server API checks");
+    let mut server = Managed::spawn(
+        "server",
+        server_command(root, port).args(["--database-url", &database_url]),
+    )?;
+    let result = wait_for_health(&mut server, port, interrupted).and_then(|()| {
+        for endpoint in CHECKED_ENDPOINTS {
+            match http_get_status(port, &format!("/api{}", endpoint)) {
+                Some(200) => println!("   ok  GET /api{}", endpoint),
+                status => return Err(format!("GET /api{} returned {:?}", endpoint, status)),
+            }
+        }
+        Ok(())
+    });
+    let _ = server.child.kill();
+    server.shutdown(SHUTDOWN_GRACE);
+    let _ = std::fs::remove_file(&database);
+    result
+}
+
+// cargo xtask package：发布版游戏、assets、默认设置模板、操作说明和启动脚本打成 target/package 下当前平台的压缩包，
+// 解压到临时目录后运行自检
+fn package(args: &[String]) -> Result<(), String> {
+    if let Some(unknown) = args.first() {
+        return Err(format!("unknown package option {}", unknown));
+    }
+    let root = workspace_root();
+    run(
+        "release build",
+        cargo().current_dir(&root).args(["build", "--release", "-p", GAME_PACKAGE]),
+    )?;
+    let game = binary_in(&target_dir(&root).join("release"), GAME_PACKAGE);
+    let assets = root.join("game").join("assets");
+
+    // 资源列表由游戏自己给出，和运行时加载的文件一致；缺少任何一个都不打包，包里的自检同样会失败
+    let listed = output("list assets", Command::new(&game).arg("--list-assets"))?;
+    check_assets(&listed, &assets)?;
+
+    let version = output("read version", Command::new(&game).arg("--version"))?;
+    let name = package_name(&version)?;
+    let out_dir = target_dir(&root).join("package");
+    let stage = out_dir.join(&name);
+
+    println!("== staging {}", stage.display());
+    recreate_dir(&stage)?;
+    std::fs::copy(&game, binary_in(&stage, GAME_PACKAGE)).map_err(|e| format!("failed to copy the game: {}", e))?;
+    copy_dir(&assets, &stage.join("assets"))?;
+    run(
+        "write package files",
+        Command::new(&game).arg("--write-package-files").arg(&stage),
+    )?;
+    write_launcher(&stage)?;
+
+    let archive = create_archive(&out_dir, &name)?;
+    println!("== packaged {}", archive.display());
+    check_archive(&out_dir, &archive, &name)
+}
+
+// --list-assets 每行一个相对于 assets 的路径；缺失的文件全部列出来，而不是只报第一个
+fn check_assets(listed: &str, assets: &Path) -> Result<(), String> {
+    let missing: Vec<&str> = listed
+        .lines()
+        .map(str::trim)
+        .filter(|path| !path.is_empty() && !assets.join(path).is_file())
+        .collect();
+    if missing.is_empty() {
+        return Ok(());
+    }
+    let list: String = missing.iter().map(|path| format!("\n  assets/{}", path)).collect();
+    Err(format!("missing assets:{}", list))
+}
+
+// --version 输出形如 "breakout-game 0.2.0 (提交 日期)"，压缩包按版本和当前平台命名
+fn package_name(version_output: &str) -> Result<String, String> {
+    let version = version_output
+        .split_whitespace()
+        .nth(1)
+        .ok_or_else(|| "the game did not print its version".to_string())?;
+    Ok(format!("super-breakout-{}-{}-{}", version, env::consts::OS, env::consts::ARCH))
+}
+
+fn recreate_dir(dir: &Path) -> Result<(), String> {
+    if dir.exists() {
+        std::fs::remove_dir_all(dir).map_err(|e| format!("failed to remove {}: {}", dir.display(), e))?;
+    }
+    std::fs::create_dir_all(dir).map_err(|e| format!("failed to create {}: {}", dir.display(), e))
+}
+
+fn copy_dir(from: &Path, to: &Path) -> Result<(), String> {
+    let error = |e: std::io::Error| format!("failed to copy {}: {}", from.display(), e);
+    std::fs::create_dir_all(to).map_err(error)?;
+    for entry in std::fs::read_dir(from).map_err(error)? {
+        let entry = entry.map_err(error)?;
+        let target = to.join(entry.file_name());
+        if entry.file_type().map_err(error)?.is_dir() {
+            copy_dir(&entry.path(), &target)?;
+        } else {
+            std::fs::copy(entry.path(), &target).map_err(error)?;
+        }
+    }
+    Ok(())
+}
+
+// 启动脚本先切换到自己所在的目录：游戏在当前目录下查找 assets，设置和档案也保存在那里
+fn write_launcher(stage: &Path) -> Result<(), String> {
+    let executable = format!("{}{}", GAME_PACKAGE, env::consts::EXE_SUFFIX);
+    let (file_name, content) = match env::consts::OS {
+        "windows" => (
+            "Super Breakout.bat",
+            format!("@echo off\r\ncd /d \"%~dp0\"\r\nstart \"\" \"{}\" %*\r\n", executable),
+        ),
+        // macOS 的 .command 文件可以在访达中双击运行
+        "macos" => (
+            "Super Breakout.command",
+            format!("#!/bin/sh\ncd \"$(dirname \"$0\")\" || exit 1\nexec \"./{}\" \"$@\"\n", executable),
+        ),
+        _ => (
+            "super-breakout.sh",
+            format!("#!/bin/sh\ncd \"$(dirname \"$0\")\" || exit 1\nexec \"./{}\" \"$@\"\n", executable),
+        ),
+    };
+    let path = stage.join(file_name);
+    std::fs::write(&path, content).map_err(|e| format!("failed to write {}: {}", path.display(), e))?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o755))
+            .map_err(|e| format!("failed to make {} executable: {}", path.display(), e))?;
+    }
+    Ok(())
+}
+
+// Windows 打成 zip（系统自带的 tar 支持），其他平台打成保留执行权限的 tar.gz
+fn create_archive(out_dir: &Path, name: &str) -> Result<PathBuf, String> {
+    let archive = if cfg!(windows) {
+        out_dir.join(format!("{}.zip", name))
+    } else {
+        out_dir.join(format!("{}.tar.gz", name))
+    };
+    let _ = std::fs::remove_file(&archive);
+    let mut command = Command::new("tar");
+    if cfg!(windows) {
+        command.arg("-a").arg("-cf");
+    } else {
+        command.arg("-czf");
+    }
+    command.arg(&archive).arg("-C").arg(out_dir).arg(name);
+    run("archive", &mut command)?;
+    Ok(archive)
+}
+
+// 解压到单独的目录，用包里的启动方式（当前目录为游戏目录）运行自检，确认压缩包本身是完整的
+fn check_archive(out_dir: &Path, archive: &Path, name: &str) -> Result<(), String> {
+    let unpacked = out_dir.join("self-check");
+    recreate_dir(&unpacked)?;
+    run(
+        "unpack",
+        Command::new("tar").arg("-xf").arg(archive).arg("-C").arg(&unpacked),
+    )?;
+    let game_dir = unpacked.join(name);
+    // cargo xtask 会设置 CARGO_MANIFEST_DIR，Bevy 会据此查找 assets；去掉它才能检查包里的资源
+    run(
+        "packaged self-check",
+        Command::new(binary_in(&game_dir, GAME_PACKAGE))
+            .current_dir(&game_dir)
+            .env_remove("CARGO_MANIFEST_DIR")
+            .env_remove("BEVY_ASSET_ROOT")
+            .args(["--headless", "--self-check"]),
+    )?;
+    let _ = std::fs::remove_dir_all(&unpacked);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn passed_tests_sums_every_test_binary() {
+        let stdout = "\
+running 2 tests
+test a ... ok
+test b ... ok
+
+test result: ok. 2 passed; 0 failed; 0 ignored; 0 measured; 0 filtered out; finished in 0.00s
+
+running 0 tests
+
+test result: ok. 0 passed; 0 failed; 0 ignored; 0 measured; 0 filtered out; finished in 0.00s
+
+running 5 tests
+test result: ok. 5 passed; 0 failed; 0 ignored; 0 measured; 0 filtered out; finished in 0.01s
+";
+        assert_eq!(passed_tests(stdout), 7);
+    }
+
+    #[test]
+    fn passed_tests_is_zero_without_results() {
+        assert_eq!(passed_tests(""), 0);
+        assert_eq!(passed_tests("running 0 tests\n"), 0);
+    }
+
+    // 测试用的临时目录，离开作用域时删除
+    struct Scratch(PathBuf);
+
+    impl Scratch {
+        fn new(name: &str) -> Self {
+            let dir = env::temp_dir().join(format!("breakout-xtask-{}-{}", name, std::process::id()));
+            recreate_dir(&dir).unwrap();
+            Scratch(dir)
+        }
+    }
+
+    impl Drop for Scratch {
+        fn drop(&mut self) {
+            let _ = std::fs::remove_dir_all(&self.0);
+        }
+    }
+
+    #[test]
+    fn missing_assets_are_all_listed() {
+        let scratch = Scratch::new("assets");
+        std::fs::create_dir_all(scratch.0.join("fonts")).unwrap();
+        std::fs::write(scratch.0.join("fonts/main.ttf"), b"font").unwrap();
+
+        assert_eq!(check_assets("fonts/main.ttf\n\n", &scratch.0), Ok(()));
+        let error = check_assets("fonts/main.ttf\nsounds/hit.ogg\nfonts/cjk.otf\n", &scratch.0).unwrap_err();
+        assert_eq!(error, "missing assets:\n  assets/sounds/hit.ogg\n  assets/fonts/cjk.otf");
+        // 目录不算资源文件
+        assert!(check_assets("fonts", &scratch.0).is_err());
+    }
+
+    #[test]
+    fn package_name_comes_from_the_version_line() {
+        let name = package_name("breakout-game 0.2.0 (abc1234 2024-05-01)\n").unwrap();
+        assert_eq!(name, format!("super-breakout-0.2.0-{}-{}", env::consts::OS, env::consts::ARCH));
+        assert!(package_name("").is_err());
+        assert!(package_name("breakout-game").is_err());
+    }
+
+    // 冒烟测试：打出压缩包，解压到单独的目录并以 --headless --self-check 运行里面的程序。
+    // 这里用一个脚本代替发布版游戏，它只在参数正确、并且当前目录下有解压出来的 assets 时成功
+    #[cfg(unix)]
+    #[test]
+    fn packaged_archive_unpacks_and_runs_the_self_check() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let scratch = Scratch::new("package");
+        let name = "super-breakout-test";
+        let stage = scratch.0.join(name);
+        std::fs::create_dir_all(stage.join("assets/locales")).unwrap();
+        std::fs::write(stage.join("assets/locales/en.ron"), "{}").unwrap();
+        let game = binary_in(&stage, GAME_PACKAGE);
+        std::fs::write(
+            &game,
+            "#!/bin/sh\n[ \"$1\" = --headless ] && [ \"$2\" = --self-check ] && [ -f assets/locales/en.ron ]\n",
+        )
+        .unwrap();
+        std::fs::set_permissions(&game, std::fs::Permissions::from_mode(0o755)).unwrap();
+        write_launcher(&stage).unwrap();
+
+        let archive = create_archive(&scratch.0, name).unwrap();
+        assert!(archive.is_file());
+        // 解压出来的副本才是被检查的对象：删掉暂存目录，自检仍然要通过
+        std::fs::remove_dir_all(&stage).unwrap();
+        check_archive(&scratch.0, &archive, name).unwrap();
+        assert!(!scratch.0.join("self-check").exists(), "the unpacked copy is cleaned up");
+
+        // 包里的程序自检失败时打包也失败
+        std::fs::create_dir_all(stage.join("assets")).unwrap();
+        std::fs::write(&game, "#!/bin/sh\nexit 1\n").unwrap();
+        std::fs::set_permissions(&game, std::fs::Permissions::from_mode(0o755)).unwrap();
+        let archive = create_archive(&scratch.0, name).unwrap();
+        assert!(check_archive(&scratch.0, &archive, name).is_err());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn launcher_changes_into_the_game_directory() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let scratch = Scratch::new("launcher");
+        write_launcher(&scratch.0).unwrap();
+        let file_name = if env::consts::OS == "macos" { "Super Breakout.command" } else { "super-breakout.sh" };
+        let launcher = scratch.0.join(file_name);
+        let content = std::fs::read_to_string(&launcher).unwrap();
+        assert!(content.contains("cd \"$(dirname \"$0\")\""));
+        assert!(content.contains(&format!("exec \"./{}\" \"$@\"", GAME_PACKAGE)));
+        assert_eq!(std::fs::metadata(&launcher).unwrap().permissions().mode() & 0o111, 0o111);
+    }
+}